@@ -75,6 +75,12 @@ impl ActivityIndicator {
 
             cx.observe(&project, |_, _, cx| cx.notify()).detach();
 
+            let this = cx.weak_entity();
+            cx.observe_progress(move |cx| {
+                this.update(cx, |_, cx| cx.notify()).ok();
+            })
+            .detach();
+
             if let Some(auto_updater) = auto_updater.as_ref() {
                 cx.observe(auto_updater, |_, _, cx| cx.notify()).detach();
             }
@@ -254,6 +260,49 @@ impl ActivityIndicator {
             });
         }
 
+        // Show any other task that's reporting its progress.
+        let progress = cx.progress();
+        if let Some(info) = progress.first() {
+            let mut message = info.title.to_string();
+
+            if let Some(fraction) = info.fraction {
+                write!(&mut message, " ({}%)", (fraction * 100.).round()).unwrap();
+            }
+
+            if let Some(progress_message) = info.message.as_ref() {
+                message.push_str(": ");
+                message.push_str(progress_message);
+            }
+
+            if progress.len() > 1 {
+                write!(&mut message, " + {} more", progress.len() - 1).unwrap();
+            }
+
+            if info.cancellable {
+                message.push_str(" (click to cancel)");
+            }
+
+            return Some(Content {
+                icon: Some(
+                    Icon::new(IconName::ArrowCircle)
+                        .size(IconSize::Small)
+                        .with_animation(
+                            "arrow-circle",
+                            Animation::new(Duration::from_secs(2)).repeat(),
+                            |icon, delta| icon.transform(Transformation::rotate(percentage(delta))),
+                        )
+                        .into_any_element(),
+                ),
+                message,
+                on_click: if info.cancellable {
+                    let id = info.id;
+                    Some(Arc::new(move |_, _, cx| cx.cancel_progress(id)))
+                } else {
+                    None
+                },
+            });
+        }
+
         // Show any language server installation info.
         let mut downloading = SmallVec::<[_; 3]>::new();
         let mut checking_for_update = SmallVec::<[_; 3]>::new();
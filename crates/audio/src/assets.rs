@@ -33,6 +33,20 @@ impl SoundRegistry {
 
     pub(crate) fn set_global(source: impl AssetSource, cx: &mut App) {
         cx.set_global(GlobalSoundRegistry(SoundRegistry::new(source)));
+        cx.on_assets_reloaded(|paths, cx| {
+            let registry = Self::global(cx);
+            let mut cache = registry.cache.lock();
+            for path in paths {
+                if let Some(name) = Self::sound_name(path) {
+                    cache.remove(name);
+                }
+            }
+        })
+        .detach();
+    }
+
+    fn sound_name(path: &str) -> Option<&str> {
+        path.strip_prefix("sounds/")?.strip_suffix(".wav")
     }
 
     pub fn get(&self, name: &str) -> Result<impl Source<Item = f32>> {
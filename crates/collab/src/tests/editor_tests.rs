@@ -1533,6 +1533,7 @@ async fn test_mutual_editor_inlay_hint_cache_update(
                     enabled: true,
                     edit_debounce_ms: 0,
                     scroll_debounce_ms: 0,
+                    toggle_on_modifiers_press: None,
                     show_type_hints: true,
                     show_parameter_hints: false,
                     show_other_hints: true,
@@ -1548,6 +1549,7 @@ async fn test_mutual_editor_inlay_hint_cache_update(
                     enabled: true,
                     edit_debounce_ms: 0,
                     scroll_debounce_ms: 0,
+                    toggle_on_modifiers_press: None,
                     show_type_hints: true,
                     show_parameter_hints: false,
                     show_other_hints: true,
@@ -1766,6 +1768,7 @@ async fn test_inlay_hint_refresh_is_forwarded(
                     enabled: false,
                     edit_debounce_ms: 0,
                     scroll_debounce_ms: 0,
+                    toggle_on_modifiers_press: None,
                     show_type_hints: false,
                     show_parameter_hints: false,
                     show_other_hints: false,
@@ -1781,6 +1784,7 @@ async fn test_inlay_hint_refresh_is_forwarded(
                     enabled: true,
                     edit_debounce_ms: 0,
                     scroll_debounce_ms: 0,
+                    toggle_on_modifiers_press: None,
                     show_type_hints: true,
                     show_parameter_hints: true,
                     show_other_hints: true,
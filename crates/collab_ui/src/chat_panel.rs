@@ -884,13 +884,9 @@ impl ChatPanel {
                 {
                     this.update(&mut cx, |this, cx| {
                         if let Some(highlight_message_id) = highlight_message_id {
-                            let task = cx.spawn(|this, mut cx| async move {
-                                cx.background_executor().timer(Duration::from_secs(2)).await;
-                                this.update(&mut cx, |this, cx| {
-                                    this.highlighted_message.take();
-                                    cx.notify();
-                                })
-                                .ok();
+                            let task = cx.set_timeout(Duration::from_secs(2), |this, cx| {
+                                this.highlighted_message.take();
+                                cx.notify();
                             });
 
                             this.highlighted_message = Some((highlight_message_id, task));
@@ -66,5 +66,9 @@ fn notification_window_options(
         app_id: Some(app_id.to_owned()),
         window_min_size: None,
         window_decorations: Some(WindowDecorations::Client),
+        parent: None,
+        modal: false,
+        level: None,
+        hdr: false,
     }
 }
@@ -2,8 +2,8 @@ use std::time::Duration;
 
 use editor::Editor;
 use gpui::{
-    Context, Entity, EventEmitter, IntoElement, ParentElement, Render, Styled, Subscription, Task,
-    WeakEntity, Window,
+    AnnouncementPriority, Context, Entity, EventEmitter, IntoElement, ParentElement, Render,
+    Styled, Subscription, Task, WeakEntity, Window,
 };
 use language::Diagnostic;
 use ui::{h_flex, prelude::*, Button, ButtonLike, Color, Icon, IconName, Label, Tooltip};
@@ -86,6 +86,8 @@ impl Render for DiagnosticIndicator {
 
         h_flex()
             .gap_2()
+            .accessibility_label(self.summary_label())
+            .accessibility_live_region(AnnouncementPriority::Polite)
             .child(
                 ButtonLike::new("diagnostic-indicator")
                     .child(diagnostic_indicator)
@@ -110,6 +112,25 @@ impl Render for DiagnosticIndicator {
 }
 
 impl DiagnosticIndicator {
+    fn summary_label(&self) -> String {
+        let errors = match self.summary.error_count {
+            0 => None,
+            1 => Some("1 error".to_string()),
+            count => Some(format!("{count} errors")),
+        };
+        let warnings = match self.summary.warning_count {
+            0 => None,
+            1 => Some("1 warning".to_string()),
+            count => Some(format!("{count} warnings")),
+        };
+        match (errors, warnings) {
+            (None, None) => "No problems".to_string(),
+            (Some(errors), None) => errors,
+            (None, Some(warnings)) => warnings,
+            (Some(errors), Some(warnings)) => format!("{errors}, {warnings}"),
+        }
+    }
+
     pub fn new(workspace: &Workspace, cx: &mut Context<Self>) -> Self {
         let project = workspace.project();
         cx.subscribe(project, |this, project, event, cx| match event {
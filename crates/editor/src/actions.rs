@@ -195,6 +195,22 @@ pub struct SpawnNearestTask {
     pub reveal: task::RevealStrategy,
 }
 
+#[derive(PartialEq, Clone, Deserialize, Default, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ExportToPdf {
+    #[serde(default)]
+    pub page_size: PdfPageSize,
+}
+
+/// The paper size that [`ExportToPdf`] lays pages out for.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PdfPageSize {
+    #[default]
+    Letter,
+    A4,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Default)]
 pub enum UuidVersion {
     #[default]
@@ -213,6 +229,7 @@ impl_actions!(
         ExpandExcerpts,
         ExpandExcerptsDown,
         ExpandExcerptsUp,
+        ExportToPdf,
         FoldAt,
         HandleInput,
         MoveDownByLines,
@@ -249,6 +266,7 @@ gpui::actions!(
         Backspace,
         Cancel,
         CancelLanguageServerWork,
+        ClearBookmarks,
         ConfirmRename,
         ContextMenuFirst,
         ContextMenuLast,
@@ -292,6 +310,7 @@ gpui::actions!(
         ToggleFoldRecursive,
         Format,
         FormatSelections,
+        GoToBookmark,
         GoToDeclaration,
         GoToDeclarationSplit,
         GoToDefinition,
@@ -300,6 +319,7 @@ gpui::actions!(
         GoToHunk,
         GoToImplementation,
         GoToImplementationSplit,
+        GoToPrevBookmark,
         GoToPrevDiagnostic,
         GoToPrevHunk,
         GoToTypeDefinition,
@@ -365,6 +385,10 @@ gpui::actions!(
         ScrollCursorTop,
         SelectAll,
         SelectAllMatches,
+        SelectColumnDown,
+        SelectColumnLeft,
+        SelectColumnRight,
+        SelectColumnUp,
         SelectDown,
         SelectEnclosingSymbol,
         SelectLargerSyntaxNode,
@@ -390,10 +414,12 @@ gpui::actions!(
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
         SplitSelectionIntoLines,
+        StageSelectedHunks,
         SwitchSourceHeader,
         Tab,
         TabPrev,
         ToggleAutoSignatureHelp,
+        ToggleBookmark,
         ToggleGitBlame,
         ToggleGitBlameInline,
         ToggleIndentGuides,
@@ -414,6 +440,7 @@ gpui::actions!(
         UnfoldRecursive,
         UniqueLinesCaseInsensitive,
         UniqueLinesCaseSensitive,
+        ViewBookmarks,
     ]
 );
 
@@ -0,0 +1,241 @@
+use crate::{
+    actions::{ClearBookmarks, GoToBookmark, GoToPrevBookmark, ToggleBookmark, ViewBookmarks},
+    persistence::DB,
+    Autoscroll, DisplayPoint, DisplayRow, Editor, MultibufferSelectionMode,
+};
+use collections::BTreeMap;
+use multi_buffer::{MultiBufferRow, MultiBufferSnapshot, ToPoint as _};
+use project::Location;
+use std::{ops::Range, path::PathBuf};
+use text::{Point, ToPoint as _};
+use ui::{prelude::*, IconButtonShape, Tooltip};
+use util::ResultExt as _;
+use workspace::Workspace;
+
+impl Editor {
+    /// Bookmarks the lines containing the selections, or removes their
+    /// bookmarks if they already have them.
+    pub fn toggle_bookmark(&mut self, _: &ToggleBookmark, _: &mut Window, cx: &mut Context<Self>) {
+        let heads = self
+            .selections
+            .all::<Point>(cx)
+            .into_iter()
+            .map(|selection| selection.head())
+            .collect::<Vec<_>>();
+        self.toggle_bookmarks_at(heads, cx);
+    }
+
+    fn toggle_bookmarks_at(&mut self, points: Vec<Point>, cx: &mut Context<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut lines = Vec::new();
+        for point in points {
+            let Some((buffer_snapshot, point, _)) = snapshot.point_to_buffer_point(point) else {
+                continue;
+            };
+            let Some(buffer) = self.buffer.read(cx).buffer(buffer_snapshot.remote_id()) else {
+                continue;
+            };
+            lines.push((buffer, point.row));
+        }
+        lines.dedup();
+
+        let bookmark_store = project.read(cx).bookmark_store().clone();
+        bookmark_store.update(cx, |bookmark_store, cx| {
+            for (buffer, row) in lines {
+                bookmark_store.toggle(&buffer, row, cx);
+            }
+        });
+    }
+
+    pub fn clear_bookmarks(&mut self, _: &ClearBookmarks, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(project) = self.project.clone() {
+            let bookmark_store = project.read(cx).bookmark_store().clone();
+            bookmark_store.update(cx, |bookmark_store, cx| bookmark_store.clear(cx));
+        }
+    }
+
+    pub fn go_to_bookmark(
+        &mut self,
+        _: &GoToBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest::<Point>(cx).head();
+        let rows = self.bookmarked_rows(&snapshot, Point::zero()..snapshot.max_point(), cx);
+        let row = rows
+            .iter()
+            .find(|row| row.0 > head.row)
+            .or_else(|| rows.first());
+        if let Some(row) = row {
+            self.go_to_bookmarked_row(*row, window, cx);
+        }
+    }
+
+    pub fn go_to_prev_bookmark(
+        &mut self,
+        _: &GoToPrevBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest::<Point>(cx).head();
+        let rows = self.bookmarked_rows(&snapshot, Point::zero()..snapshot.max_point(), cx);
+        let row = rows
+            .iter()
+            .rev()
+            .find(|row| row.0 < head.row)
+            .or_else(|| rows.last());
+        if let Some(row) = row {
+            self.go_to_bookmarked_row(*row, window, cx);
+        }
+    }
+
+    fn go_to_bookmarked_row(
+        &mut self,
+        row: MultiBufferRow,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let destination = Point::new(row.0, 0);
+        self.unfold_ranges(&[destination..destination], false, false, cx);
+        self.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+            s.select_ranges(vec![destination..destination]);
+        });
+    }
+
+    pub(crate) fn render_bookmark_indicator(
+        &self,
+        row: DisplayRow,
+        cx: &mut Context<Self>,
+    ) -> IconButton {
+        IconButton::new(("bookmark_indicator", row.0 as usize), IconName::Bookmark)
+            .shape(IconButtonShape::Square)
+            .icon_size(IconSize::XSmall)
+            .icon_color(Color::Accent)
+            .tooltip(Tooltip::text("Remove Bookmark"))
+            .on_click(cx.listener(move |editor, _, window, cx| {
+                let snapshot = editor.snapshot(window, cx);
+                let point = DisplayPoint::new(row, 0).to_point(&snapshot.display_snapshot);
+                editor.toggle_bookmarks_at(vec![point], cx);
+            }))
+    }
+
+    /// Opens a multibuffer with every bookmarked line in the project, along
+    /// with the lines around it.
+    pub fn view_bookmarks(
+        workspace: &mut Workspace,
+        _: &ViewBookmarks,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let bookmark_store = workspace.project().read(cx).bookmark_store().read(cx);
+        let mut locations = Vec::new();
+        for (buffer, anchors) in bookmark_store.bookmarks() {
+            let snapshot = buffer.read(cx).snapshot();
+            for anchor in anchors {
+                let row = anchor.to_point(&snapshot).row;
+                locations.push(Location {
+                    buffer: buffer.clone(),
+                    range: snapshot.anchor_before(Point::new(row, 0))
+                        ..snapshot.anchor_after(Point::new(row, snapshot.line_len(row))),
+                });
+            }
+        }
+        if locations.is_empty() {
+            return;
+        }
+
+        Self::open_locations_in_multibuffer(
+            workspace,
+            locations,
+            "Bookmarks".to_string(),
+            false,
+            MultibufferSelectionMode::First,
+            window,
+            cx,
+        );
+    }
+
+    /// The bookmarked rows of the multibuffer that lie within `range`, in
+    /// ascending order.
+    pub(crate) fn bookmarked_rows(
+        &self,
+        snapshot: &MultiBufferSnapshot,
+        range: Range<Point>,
+        cx: &App,
+    ) -> Vec<MultiBufferRow> {
+        let Some(project) = self.project.as_ref() else {
+            return Vec::new();
+        };
+        let bookmark_store = project.read(cx).bookmark_store().read(cx);
+        if bookmark_store.is_empty() {
+            return Vec::new();
+        }
+
+        let mut rows = Vec::new();
+        for (buffer, buffer_range, excerpt_id) in snapshot.range_to_buffer_ranges(range) {
+            let start_row = buffer.offset_to_point(buffer_range.start).row;
+            let end_row = buffer.offset_to_point(buffer_range.end).row;
+            for row in bookmark_store.bookmarked_rows(buffer) {
+                if row < start_row || row > end_row {
+                    continue;
+                }
+                let anchor = buffer.anchor_before(Point::new(row, 0));
+                if let Some(anchor) = snapshot.anchor_in_excerpt(excerpt_id, anchor) {
+                    rows.push(MultiBufferRow(anchor.to_point(snapshot).row));
+                }
+            }
+        }
+        rows.sort_unstable();
+        rows.dedup();
+        rows
+    }
+}
+
+/// Restores the bookmarks that were saved for the workspace, and keeps them
+/// saved as they change.
+pub(crate) fn restore_and_persist_bookmarks(workspace: &Workspace, cx: &mut Context<Workspace>) {
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    let project = workspace.project().clone();
+    if !project.read(cx).is_local() {
+        return;
+    }
+
+    let mut rows_by_path = BTreeMap::<PathBuf, Vec<u32>>::new();
+    for (path, row) in DB.get_bookmarks(workspace_id).log_err().unwrap_or_default() {
+        rows_by_path.entry(path).or_default().push(row);
+    }
+    let bookmark_store = project.read(cx).bookmark_store().clone();
+    cx.spawn(|workspace, mut cx| async move {
+        for (path, rows) in rows_by_path {
+            // Files that were deleted since take their bookmarks with them.
+            let Some(buffer) = project
+                .update(&mut cx, |project, cx| project.open_local_buffer(path, cx))?
+                .await
+                .log_err()
+            else {
+                continue;
+            };
+            bookmark_store.update(&mut cx, |bookmark_store, cx| {
+                bookmark_store.insert(&buffer, rows, cx)
+            })?;
+        }
+
+        workspace.update(&mut cx, |_, cx| {
+            cx.observe(&bookmark_store, move |_, bookmark_store, cx| {
+                let bookmarks = bookmark_store.read(cx).serialize(cx);
+                cx.background_executor()
+                    .spawn(DB.save_bookmarks(workspace_id, bookmarks))
+                    .detach_and_log_err(cx);
+            })
+            .detach();
+        })
+    })
+    .detach_and_log_err(cx);
+}
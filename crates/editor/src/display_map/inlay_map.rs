@@ -1066,7 +1066,7 @@ fn push_isomorphic(sum_tree: &mut SumTree<Transform>, summary: TextSummary) {
 mod tests {
     use super::*;
     use crate::{
-        display_map::{InlayHighlights, TextHighlights},
+        display_map::{HighlightKey, InlayHighlights, TextHighlights},
         hover_links::InlayHighlight,
         InlayId, MultiBuffer,
     };
@@ -1093,6 +1093,7 @@ mod tests {
                     tooltip: None,
                     kind: None,
                     resolve_state: ResolveState::Resolved,
+                    text_edits: Vec::new(),
                 },
             )
             .text
@@ -1113,6 +1114,7 @@ mod tests {
                     tooltip: None,
                     kind: None,
                     resolve_state: ResolveState::Resolved,
+                    text_edits: Vec::new(),
                 },
             )
             .text
@@ -1133,6 +1135,7 @@ mod tests {
                     tooltip: None,
                     kind: None,
                     resolve_state: ResolveState::Resolved,
+                    text_edits: Vec::new(),
                 },
             )
             .text
@@ -1153,6 +1156,7 @@ mod tests {
                     tooltip: None,
                     kind: None,
                     resolve_state: ResolveState::Resolved,
+                    text_edits: Vec::new(),
                 },
             )
             .text
@@ -1581,7 +1585,7 @@ mod tests {
             text_highlight_ranges.sort_by_key(|range| (range.start, Reverse(range.end)));
             log::info!("highlighting text ranges {text_highlight_ranges:?}");
             text_highlights.insert(
-                TypeId::of::<()>(),
+                HighlightKey::Type(TypeId::of::<()>()),
                 Arc::new((
                     HighlightStyle::default(),
                     text_highlight_ranges
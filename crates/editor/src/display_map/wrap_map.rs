@@ -3,7 +3,7 @@ use super::{
     tab_map::{self, TabEdit, TabPoint, TabSnapshot},
     Highlights,
 };
-use gpui::{App, AppContext as _, Context, Entity, Font, LineWrapper, Pixels, Task};
+use gpui::{App, AppContext as _, Context, Entity, Font, LineWrapper, Pixels, Priority, Task};
 use language::{Chunk, Point};
 use multi_buffer::{MultiBufferSnapshot, RowInfo};
 use smol::future::yield_now;
@@ -171,23 +171,26 @@ impl WrapMap {
 
             let text_system = cx.text_system().clone();
             let (font, font_size) = self.font_with_size.clone();
-            let task = cx.background_executor().spawn(async move {
-                let mut line_wrapper = text_system.line_wrapper(font, font_size);
-                let tab_snapshot = new_snapshot.tab_snapshot.clone();
-                let range = TabPoint::zero()..tab_snapshot.max_point();
-                let edits = new_snapshot
-                    .update(
-                        tab_snapshot,
-                        &[TabEdit {
-                            old: range.clone(),
-                            new: range.clone(),
-                        }],
-                        wrap_width,
-                        &mut line_wrapper,
-                    )
-                    .await;
-                (new_snapshot, edits)
-            });
+            // Rewrapping blocks the main thread for a moment, since it changes what's visible.
+            let task =
+                cx.background_executor()
+                    .spawn_with_priority(Priority::UserBlocking, async move {
+                        let mut line_wrapper = text_system.line_wrapper(font, font_size);
+                        let tab_snapshot = new_snapshot.tab_snapshot.clone();
+                        let range = TabPoint::zero()..tab_snapshot.max_point();
+                        let edits = new_snapshot
+                            .update(
+                                tab_snapshot,
+                                &[TabEdit {
+                                    old: range.clone(),
+                                    new: range.clone(),
+                                }],
+                                wrap_width,
+                                &mut line_wrapper,
+                            )
+                            .await;
+                        (new_snapshot, edits)
+                    });
 
             match cx
                 .background_executor()
@@ -255,17 +258,20 @@ impl WrapMap {
                 let mut snapshot = self.snapshot.clone();
                 let text_system = cx.text_system().clone();
                 let (font, font_size) = self.font_with_size.clone();
-                let update_task = cx.background_executor().spawn(async move {
-                    let mut edits = Patch::default();
-                    let mut line_wrapper = text_system.line_wrapper(font, font_size);
-                    for (tab_snapshot, tab_edits) in pending_edits {
-                        let wrap_edits = snapshot
-                            .update(tab_snapshot, &tab_edits, wrap_width, &mut line_wrapper)
-                            .await;
-                        edits = edits.compose(&wrap_edits);
-                    }
-                    (snapshot, edits)
-                });
+                let update_task = cx.background_executor().spawn_with_priority(
+                    Priority::UserBlocking,
+                    async move {
+                        let mut edits = Patch::default();
+                        let mut line_wrapper = text_system.line_wrapper(font, font_size);
+                        for (tab_snapshot, tab_edits) in pending_edits {
+                            let wrap_edits = snapshot
+                                .update(tab_snapshot, &tab_edits, wrap_width, &mut line_wrapper)
+                                .await;
+                            edits = edits.compose(&wrap_edits);
+                        }
+                        (snapshot, edits)
+                    },
+                );
 
                 match cx
                     .background_executor()
@@ -15,6 +15,7 @@
 pub mod actions;
 mod blame_entry_tooltip;
 mod blink_manager;
+mod bookmarks;
 mod clangd_ext;
 mod code_context_menus;
 pub mod display_map;
@@ -32,8 +33,10 @@ mod linked_editing_ranges;
 mod lsp_ext;
 mod mouse_context_menu;
 pub mod movement;
+mod pdf_export;
 mod persistence;
 mod proposed_changes_editor;
+mod rainbow_brackets;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
@@ -83,7 +86,7 @@ use gpui::{
     FontWeight, Global, HighlightStyle, Hsla, InteractiveText, KeyContext, Modifiers, MouseButton,
     MouseDownEvent, PaintQuad, ParentElement, Pixels, Render, SharedString, Size, Styled,
     StyledText, Subscription, Task, TextRun, TextStyle, TextStyleRefinement, UTF16Selection,
-    UnderlineStyle, UniformListScrollHandle, WeakEntity, WeakFocusHandle, Window,
+    UnderlineKind, UnderlineStyle, UniformListScrollHandle, WeakEntity, WeakFocusHandle, Window,
 };
 use highlight_matching_bracket::refresh_matching_bracket_highlights;
 use hover_popover::{hide_hover, HoverState};
@@ -97,8 +100,8 @@ use language::{
     language_settings::{self, all_language_settings, language_settings, InlayHintSettings},
     markdown, point_from_lsp, AutoindentMode, BracketPair, Buffer, Capability, CharKind, CodeLabel,
     CompletionDocumentation, CursorShape, Diagnostic, EditPreview, HighlightedText, IndentKind,
-    IndentSize, InlineCompletionPreviewMode, Language, OffsetRangeExt, Point, Selection,
-    SelectionGoal, TextObject, TransactionId, TreeSitterOptions,
+    IndentSize, InlineCompletionPreviewMode, Language, LineEnding, OffsetRangeExt, Point,
+    Selection, SelectionGoal, TextObject, TransactionId, TreeSitterOptions,
 };
 use language::{point_to_lsp, BufferRow, CharClassifier, Runnable, RunnableRange};
 use linked_editing_ranges::refresh_linked_ranges;
@@ -106,6 +109,7 @@ use mouse_context_menu::MouseContextMenu;
 pub use proposed_changes_editor::{
     ProposedChangeLocation, ProposedChangesEditor, ProposedChangesEditorToolbar,
 };
+use rainbow_brackets::{refresh_rainbow_brackets, RainbowBracketsState};
 use similar::{ChangeTag, TextDiff};
 use std::iter::Peekable;
 use task::{ResolvedTask, TaskTemplate, TaskVariables};
@@ -129,7 +133,7 @@ use multi_buffer::{
 };
 use project::{
     lsp_store::{FormatTrigger, LspFormatTarget, OpenLspBufferHandle},
-    project_settings::{GitGutterSetting, ProjectSettings},
+    project_settings::{is_large_buffer, GitGutterSetting, ProjectSettings},
     CodeAction, Completion, CompletionIntent, DocumentHighlight, InlayHint, Location, LocationLink,
     LspStore, PrepareRenameResponse, Project, ProjectItem, ProjectTransaction, TaskSourceKind,
 };
@@ -168,11 +172,12 @@ use util::{defer, maybe, post_inc, RangeExt, ResultExt, TakeUntilExt, TryFutureE
 use workspace::item::{ItemHandle, PreviewTabsSettings};
 use workspace::notifications::{DetachAndPromptErr, NotificationId, NotifyTaskExt};
 use workspace::{
-    searchable::SearchEvent, ItemNavHistory, SplitDirection, ViewId, Workspace, WorkspaceId,
+    searchable::SearchEvent, ItemId, ItemNavHistory, SplitDirection, ViewId, Workspace, WorkspaceId,
 };
 use workspace::{Item as WorkspaceItem, OpenInTerminal, OpenTerminal, TabBarSettings, Toast};
 
 use crate::hover_links::{find_url, find_url_from_range};
+use crate::persistence::DB;
 use crate::signature_help::{SignatureHelpHiddenBy, SignatureHelpState};
 
 pub const FILE_HEADER_HEIGHT: u32 = 2;
@@ -189,6 +194,7 @@ pub const CODE_ACTIONS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
 
 pub(crate) const FORMAT_TIMEOUT: Duration = Duration::from_secs(2);
 pub(crate) const SCROLL_CENTER_TOP_BOTTOM_DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(1);
+const SERIALIZE_FOLDS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(500);
 
 pub fn render_parsed_markdown(
     element_id: impl Into<ElementId>,
@@ -303,11 +309,13 @@ pub fn init(cx: &mut App) {
     workspace::register_serializable_item::<Editor>(cx);
 
     cx.observe_new(
-        |workspace: &mut Workspace, _: Option<&mut Window>, _cx: &mut Context<Workspace>| {
+        |workspace: &mut Workspace, _: Option<&mut Window>, cx: &mut Context<Workspace>| {
             workspace.register_action(Editor::new_file);
             workspace.register_action(Editor::new_file_vertical);
             workspace.register_action(Editor::new_file_horizontal);
             workspace.register_action(Editor::cancel_language_server_work);
+            workspace.register_action(Editor::view_bookmarks);
+            bookmarks::restore_and_persist_bookmarks(workspace, cx);
         },
     )
     .detach();
@@ -608,6 +616,7 @@ pub struct Editor {
     /// typing enters text into each of them, even the ones that aren't focused.
     pub(crate) show_cursor_when_unfocused: bool,
     columnar_selection_tail: Option<Anchor>,
+    block_selection: Option<BlockSelection>,
     add_selections_state: Option<AddSelectionsState>,
     select_next_state: Option<SelectNextState>,
     select_prev_state: Option<SelectNextState>,
@@ -646,6 +655,7 @@ pub struct Editor {
     gutter_highlights: TreeMap<TypeId, GutterHighlight>,
     scrollbar_marker_state: ScrollbarMarkerState,
     active_indent_guides_state: ActiveIndentGuidesState,
+    rainbow_brackets: Option<RainbowBracketsState>,
     nav_history: Option<ItemNavHistory>,
     context_menu: RefCell<Option<CodeContextMenu>>,
     mouse_context_menu: Option<MouseContextMenu>,
@@ -730,6 +740,7 @@ pub struct Editor {
     registered_buffers: HashMap<BufferId, OpenLspBufferHandle>,
     selection_mark_mode: bool,
     toggle_fold_multiple_buffers: Task<()>,
+    serialize_folds: Task<()>,
     _scroll_cursor_center_top_bottom_task: Task<()>,
 }
 
@@ -808,9 +819,13 @@ pub struct RemoteSelection {
 #[derive(Clone, Debug)]
 struct SelectionHistoryEntry {
     selections: Arc<[Selection<Anchor>]>,
+    /// Where the editor was scrolled before the selections changed, so that undoing a jump
+    /// also scrolls back to where it started.
+    scroll_anchor: ScrollAnchor,
     select_next_state: Option<SelectNextState>,
     select_prev_state: Option<SelectNextState>,
     add_selections_state: Option<AddSelectionsState>,
+    block_selection: Option<BlockSelection>,
 }
 
 enum SelectionHistoryMode {
@@ -914,6 +929,16 @@ struct RowHighlight {
     should_autoscroll: bool,
 }
 
+/// The corners of a rectangular selection, made by dragging with the mouse or with the
+/// `SelectColumn*` actions. The head can be past the end of its line, so its column is kept
+/// separately to preserve the rectangle's shape across short lines.
+#[derive(Clone, Debug)]
+struct BlockSelection {
+    tail: Anchor,
+    head: Anchor,
+    goal_column: u32,
+}
+
 #[derive(Clone, Debug)]
 struct AddSelectionsState {
     above: bool,
@@ -979,6 +1004,10 @@ pub struct ClipboardSelection {
     pub len: usize,
     pub is_entire_line: bool,
     pub first_line_indent: u32,
+    /// Whether the selection was one line of a rectangular selection, in which case pasting
+    /// the clipboard at a single cursor inserts each line at the same column of successive rows.
+    #[serde(default)]
+    pub is_block: bool,
 }
 
 #[derive(Debug)]
@@ -1206,7 +1235,13 @@ impl Editor {
 
         let blink_manager = cx.new(|cx| BlinkManager::new(CURSOR_BLINK_INTERVAL, cx));
 
-        let soft_wrap_mode_override = matches!(mode, EditorMode::SingleLine { .. })
+        let is_large_file = mode == EditorMode::Full
+            && buffer
+                .read(cx)
+                .as_singleton()
+                .map_or(false, |buffer| is_large_buffer(buffer.read(cx), cx));
+        let soft_wrap_mode_override = (matches!(mode, EditorMode::SingleLine { .. })
+            || is_large_file)
             .then(|| language_settings::SoftWrap::None);
 
         let mut project_subscriptions = Vec::new();
@@ -1246,6 +1281,8 @@ impl Editor {
                         }
                     },
                 ));
+                let bookmark_store = project.read(cx).bookmark_store().clone();
+                project_subscriptions.push(cx.observe(&bookmark_store, |_, _, cx| cx.notify()));
                 if let Some(task_inventory) = project
                     .read(cx)
                     .task_store()
@@ -1304,6 +1341,7 @@ impl Editor {
             selections,
             scroll_manager: ScrollManager::new(cx),
             columnar_selection_tail: None,
+            block_selection: None,
             add_selections_state: None,
             select_next_state: None,
             select_prev_state: None,
@@ -1338,6 +1376,7 @@ impl Editor {
             gutter_highlights: TreeMap::default(),
             scrollbar_marker_state: ScrollbarMarkerState::default(),
             active_indent_guides_state: ActiveIndentGuidesState::default(),
+            rainbow_brackets: None,
             nav_history: None,
             context_menu: RefCell::new(None),
             mouse_context_menu: None,
@@ -1410,16 +1449,8 @@ impl Editor {
                 cx.observe_in(&display_map, window, Self::on_display_map_changed),
                 cx.observe(&blink_manager, |_, _, cx| cx.notify()),
                 cx.observe_global_in::<SettingsStore>(window, Self::settings_changed),
-                cx.observe_window_activation(window, |editor, window, cx| {
-                    let active = window.is_window_active();
-                    editor.blink_manager.update(cx, |blink_manager, cx| {
-                        if active {
-                            blink_manager.enable(cx);
-                        } else {
-                            blink_manager.disable(cx);
-                        }
-                    });
-                }),
+                cx.observe_window_activation(window, Self::update_cursor_blinking),
+                cx.observe_window_visibility(window, Self::update_cursor_blinking),
             ],
             tasks_update_task: None,
             linked_edit_ranges: Default::default(),
@@ -1433,6 +1464,7 @@ impl Editor {
             _scroll_cursor_center_top_bottom_task: Task::ready(()),
             selection_mark_mode: false,
             toggle_fold_multiple_buffers: Task::ready(()),
+            serialize_folds: Task::ready(()),
             text_style_refinement: None,
         };
         this.tasks_update_task = Some(this.refresh_runnables(window, cx));
@@ -1995,6 +2027,7 @@ impl Editor {
             .update(cx, |display_map, cx| display_map.snapshot(cx));
         let buffer = &display_map.buffer_snapshot;
         self.add_selections_state = None;
+        self.block_selection = None;
         self.select_next_state = None;
         self.select_prev_state = None;
         self.select_larger_syntax_node_stack.clear();
@@ -2558,9 +2591,88 @@ impl Editor {
         self.change_selections(None, window, cx, |s| {
             s.select_ranges(selection_ranges);
         });
+        self.block_selection = Some(BlockSelection {
+            tail: display_map
+                .buffer_snapshot
+                .anchor_before(tail.to_point(display_map)),
+            head: display_map.buffer_snapshot.anchor_before(
+                display_map
+                    .clip_point(head, Bias::Left)
+                    .to_point(display_map),
+            ),
+            goal_column,
+        });
         cx.notify();
     }
 
+    pub fn select_column_up(
+        &mut self,
+        _: &SelectColumnUp,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.select_column(-1, 0, window, cx);
+    }
+
+    pub fn select_column_down(
+        &mut self,
+        _: &SelectColumnDown,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.select_column(1, 0, window, cx);
+    }
+
+    pub fn select_column_left(
+        &mut self,
+        _: &SelectColumnLeft,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.select_column(0, -1, window, cx);
+    }
+
+    pub fn select_column_right(
+        &mut self,
+        _: &SelectColumnRight,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.select_column(0, 1, window, cx);
+    }
+
+    /// Moves the head of the rectangular selection by the given number of rows and columns,
+    /// starting a new one at the newest selection if there isn't one.
+    fn select_column(
+        &mut self,
+        row_delta: i32,
+        column_delta: i32,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let (tail, head_row, goal_column) = match self.block_selection.as_ref() {
+            Some(block) => (
+                block.tail.to_display_point(&display_map),
+                block.head.to_display_point(&display_map).row(),
+                block.goal_column,
+            ),
+            None => {
+                let newest = self.selections.newest_display(cx);
+                (newest.tail(), newest.head().row(), newest.head().column())
+            }
+        };
+
+        let head_row = head_row
+            .0
+            .saturating_add_signed(row_delta)
+            .min(display_map.max_point().row().0);
+        let goal_column = goal_column.saturating_add_signed(column_delta);
+        let head = DisplayPoint::new(DisplayRow(head_row), goal_column);
+        self.select_columns(tail, head, goal_column, &display_map, window, cx);
+        self.request_autoscroll(Autoscroll::fit(), cx);
+    }
+
     pub fn has_pending_nonempty_selection(&self) -> bool {
         let pending_nonempty_selection = match self.selections.pending_anchor() {
             Some(Selection { start, end, .. }) => start != end,
@@ -3581,6 +3693,98 @@ impl Editor {
         self.inlay_hint_cache.enabled
     }
 
+    /// Toggles inlay hints while the modifiers of the `toggle_on_modifiers_press` setting are
+    /// held, and restores them once the modifiers change.
+    pub(crate) fn update_inlay_hints_for_modifiers(
+        &mut self,
+        modifiers: &Modifiers,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(toggle_modifiers) = self.inlay_hint_cache.toggle_on_modifiers_press else {
+            return;
+        };
+        let held = *modifiers == toggle_modifiers;
+        match (held, self.inlay_hint_cache.enabled_before_modifiers_press) {
+            (true, None) => {
+                let enabled = self.inlay_hint_cache.enabled;
+                self.inlay_hint_cache.enabled_before_modifiers_press = Some(enabled);
+                self.refresh_inlay_hints(InlayHintRefreshReason::Toggle(!enabled), cx);
+            }
+            (false, Some(enabled)) => {
+                self.inlay_hint_cache.enabled_before_modifiers_press = None;
+                self.refresh_inlay_hints(InlayHintRefreshReason::Toggle(enabled), cx);
+            }
+            _ => {}
+        }
+    }
+
+    /// Applies the text edits of the inlay hint at the given position, which usually insert the
+    /// hint's text into the buffer. Returns whether there was such a hint.
+    pub(crate) fn apply_inlay_hint_text_edits(
+        &mut self,
+        point_for_position: &PointForPosition,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if point_for_position.as_valid().is_some()
+            || point_for_position.column_overshoot_after_line_end != 0
+            || self.read_only(cx)
+        {
+            return false;
+        }
+
+        let display_snapshot = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let buffer_snapshot = &display_snapshot.buffer_snapshot;
+        let previous_valid_anchor = buffer_snapshot.anchor_at(
+            point_for_position
+                .previous_valid
+                .to_point(&display_snapshot),
+            Bias::Left,
+        );
+        let next_valid_anchor = buffer_snapshot.anchor_at(
+            point_for_position.next_valid.to_point(&display_snapshot),
+            Bias::Right,
+        );
+        let Some(hovered_hint) = self
+            .visible_inlay_hints(cx)
+            .into_iter()
+            .filter(|hint| {
+                hint.position
+                    .cmp(&previous_valid_anchor, buffer_snapshot)
+                    .is_ge()
+                    && hint
+                        .position
+                        .cmp(&next_valid_anchor, buffer_snapshot)
+                        .is_le()
+            })
+            .max_by_key(|hint| hint.id)
+        else {
+            return false;
+        };
+        let excerpt_id = previous_valid_anchor.excerpt_id;
+        let Some(cached_hint) = self
+            .inlay_hint_cache
+            .hint_by_id(excerpt_id, hovered_hint.id)
+        else {
+            return false;
+        };
+        let edits = cached_hint
+            .text_edits
+            .into_iter()
+            .filter_map(|(range, new_text)| {
+                let start = buffer_snapshot.anchor_in_excerpt(excerpt_id, range.start)?;
+                let end = buffer_snapshot.anchor_in_excerpt(excerpt_id, range.end)?;
+                Some((start..end, new_text))
+            })
+            .collect::<Vec<_>>();
+        if edits.is_empty() {
+            return false;
+        }
+
+        self.transact(window, cx, |editor, _, cx| editor.edit(edits, cx));
+        true
+    }
+
     fn refresh_inlay_hints(&mut self, reason: InlayHintRefreshReason, cx: &mut Context<Self>) {
         if self.semantics_provider.is_none() || self.mode != EditorMode::Full {
             return;
@@ -3991,7 +4195,29 @@ impl Editor {
         let text;
 
         if completion.is_snippet() {
-            snippet = Some(Snippet::parse(&completion.new_text).log_err()?);
+            let newest_selection = self.selections.newest_anchor();
+            let buffer = buffer_handle.read(cx);
+            // The selection can only be read from this buffer if both of its ends are in it.
+            let buffer_id = Some(buffer.remote_id());
+            let selected_text = if newest_selection.start.buffer_id == buffer_id
+                && newest_selection.end.buffer_id == buffer_id
+            {
+                buffer
+                    .text_for_range(
+                        newest_selection.start.text_anchor..newest_selection.end.text_anchor,
+                    )
+                    .collect()
+            } else {
+                String::new()
+            };
+            let variables =
+                snippet_variables(buffer, completion.old_range.start, selected_text, cx);
+            snippet = Some(
+                Snippet::parse_with_variables(&completion.new_text, |name| {
+                    variables.get(name).cloned()
+                })
+                .log_err()?,
+            );
             text = snippet.as_ref().unwrap().text.clone();
         } else {
             snippet = None;
@@ -6791,6 +7017,120 @@ impl Editor {
         }
     }
 
+    pub fn stage_selected_hunks(
+        &mut self,
+        _: &StageSelectedHunks,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let selections = self.selections.all(cx).into_iter().map(|s| s.range());
+        self.stage_hunks_in_ranges(selections, window, cx);
+    }
+
+    /// Stages the unstaged changes within the hunks intersecting the given ranges, by writing
+    /// the index text with only those changes applied.
+    fn stage_hunks_in_ranges(
+        &mut self,
+        ranges: impl Iterator<Item = Range<Point>>,
+        window: &mut Window,
+        cx: &mut Context<Editor>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let snapshot = self.snapshot(window, cx);
+        let mut hunk_ranges_by_buffer = HashMap::<BufferId, Vec<Range<text::Anchor>>>::default();
+        for hunk in snapshot.hunks_for_ranges(ranges) {
+            hunk_ranges_by_buffer
+                .entry(hunk.buffer_id)
+                .or_default()
+                .push(hunk.buffer_range);
+        }
+
+        for (buffer_id, hunk_ranges) in hunk_ranges_by_buffer {
+            let multibuffer = self.buffer.read(cx);
+            let Some(buffer) = multibuffer.buffer(buffer_id) else {
+                continue;
+            };
+            let Some(project_path) = buffer.read(cx).project_path(cx) else {
+                continue;
+            };
+            let Some((repository, repo_path)) = project
+                .read(cx)
+                .all_repositories(cx)
+                .into_iter()
+                .find_map(|repository| {
+                    let repo_path = repository
+                        .read(cx)
+                        .project_path_to_repo_path(&project_path)?;
+                    Some((repository, repo_path))
+                })
+            else {
+                continue;
+            };
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            let unstaged_diff = multibuffer
+                .diff_for(buffer_id)
+                .and_then(|diff| diff.read(cx).unstaged_diff.clone());
+
+            let result = match unstaged_diff.as_ref().and_then(|diff| {
+                let diff = diff.read(cx);
+                Some((diff, diff.snapshot.base_text.as_ref()?.as_rope()))
+            }) {
+                Some((unstaged_diff, index_text)) => {
+                    let mut new_index_text = String::new();
+                    let mut last_end = 0;
+                    let mut staged_any = false;
+                    for hunk in unstaged_diff
+                        .diff_hunks_intersecting_range(
+                            text::Anchor::MIN..text::Anchor::MAX,
+                            &buffer_snapshot,
+                        )
+                        .filter(|hunk| {
+                            hunk_ranges.iter().any(|range| {
+                                hunk.buffer_range
+                                    .start
+                                    .cmp(&range.end, &buffer_snapshot)
+                                    .is_le()
+                                    && hunk
+                                        .buffer_range
+                                        .end
+                                        .cmp(&range.start, &buffer_snapshot)
+                                        .is_ge()
+                            })
+                        })
+                    {
+                        new_index_text.extend(
+                            index_text.chunks_in_range(last_end..hunk.diff_base_byte_range.start),
+                        );
+                        new_index_text
+                            .extend(buffer_snapshot.text_for_range(hunk.buffer_range.clone()));
+                        last_end = hunk.diff_base_byte_range.end;
+                        staged_any = true;
+                    }
+                    if !staged_any {
+                        continue;
+                    }
+                    new_index_text.extend(index_text.chunks_in_range(last_end..index_text.len()));
+                    // The index text was normalized to LF when it was loaded, so write it back
+                    // with the file's line endings, as git would find them in the working tree.
+                    let line_ending = buffer_snapshot.line_ending();
+                    if line_ending != LineEnding::Unix {
+                        new_index_text = new_index_text.replace('\n', line_ending.as_str());
+                    }
+                    repository
+                        .read(cx)
+                        .set_index_text(repo_path, Some(new_index_text))
+                }
+                // Files that aren't in the index yet are staged as a whole.
+                None => repository.read(cx).stage_entries(vec![repo_path]),
+            };
+            cx.background_executor()
+                .spawn(async move { result.await? })
+                .detach_and_log_err(cx);
+        }
+    }
+
     pub fn open_active_item_in_terminal(
         &mut self,
         _: &OpenInTerminal,
@@ -7626,6 +7966,7 @@ impl Editor {
         let mut text = String::new();
         let buffer = self.buffer.read(cx).snapshot(cx);
         let mut selections = self.selections.all::<Point>(cx);
+        let is_block = self.block_selection.is_some();
         let mut clipboard_selections = Vec::with_capacity(selections.len());
         {
             let max_point = buffer.max_point();
@@ -7657,6 +7998,7 @@ impl Editor {
                     first_line_indent: buffer
                         .indent_size_for_line(MultiBufferRow(selection.start.row))
                         .len,
+                    is_block,
                 });
             }
         }
@@ -7708,6 +8050,7 @@ impl Editor {
     pub fn copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
         let selections = self.selections.all::<Point>(cx);
         let buffer = self.buffer.read(cx).read(cx);
+        let is_block = self.block_selection.is_some();
         let mut text = String::new();
 
         let mut clipboard_selections = Vec::with_capacity(selections.len());
@@ -7736,6 +8079,7 @@ impl Editor {
                     len,
                     is_entire_line,
                     first_line_indent: buffer.indent_size_for_line(MultiBufferRow(start.row)).len,
+                    is_block,
                 });
             }
         }
@@ -7767,6 +8111,28 @@ impl Editor {
                     clipboard_selections.iter().all(|s| s.is_entire_line);
                 let first_selection_indent_column =
                     clipboard_selections.first().map(|s| s.first_line_indent);
+
+                // Like other editors, paste a rectangular selection as a rectangle when there's
+                // a single cursor, rather than inserting its lines one after the other.
+                if clipboard_selections.len() > 1
+                    && clipboard_selections.iter().all(|s| s.is_block)
+                    && old_selections.len() == 1
+                    && old_selections[0].is_empty()
+                {
+                    let mut start_offset = 0;
+                    let lines = clipboard_selections
+                        .iter()
+                        .map(|clipboard_selection| {
+                            let end_offset = start_offset + clipboard_selection.len;
+                            let line = &clipboard_text[start_offset..end_offset];
+                            start_offset = end_offset + 1;
+                            line
+                        })
+                        .collect::<Vec<_>>();
+                    this.paste_block(&lines, window, cx);
+                    return;
+                }
+
                 if clipboard_selections.len() != old_selections.len() {
                     clipboard_selections.drain(..);
                 }
@@ -7837,6 +8203,57 @@ impl Editor {
         });
     }
 
+    /// Inserts each line at the newest cursor's column on successive rows, padding short rows
+    /// with spaces and adding rows past the end of the buffer, and leaves a cursor after each.
+    fn paste_block(&mut self, lines: &[&str], window: &mut Window, cx: &mut Context<Self>) {
+        let cursor = self.selections.newest::<Point>(cx).head();
+        let mut edits = Vec::new();
+        let mut cursors = Vec::new();
+        {
+            let snapshot = self.buffer.read(cx).snapshot(cx);
+            let max_point = snapshot.max_point();
+            let column = snapshot
+                .text_for_range(Point::new(cursor.row, 0)..cursor)
+                .flat_map(str::chars)
+                .count();
+
+            let mut appended_text = String::new();
+            for (ix, line) in lines.iter().enumerate() {
+                let row = cursor.row + ix as u32;
+                if row > max_point.row {
+                    appended_text.push('\n');
+                    appended_text.push_str(&" ".repeat(column));
+                    appended_text.push_str(line);
+                    cursors.push(Point::new(row, (column + line.len()) as u32));
+                    continue;
+                }
+
+                let line_len = snapshot.line_len(MultiBufferRow(row));
+                let mut position = Point::new(row, 0);
+                let mut chars = 0;
+                for ch in snapshot.chars_at(position) {
+                    if chars == column || position.column == line_len {
+                        break;
+                    }
+                    position.column += ch.len_utf8() as u32;
+                    chars += 1;
+                }
+                let text = " ".repeat(column - chars) + line;
+                cursors.push(Point::new(row, position.column + text.len() as u32));
+                edits.push((position..position, text));
+            }
+            if !appended_text.is_empty() {
+                edits.push((max_point..max_point, appended_text));
+            }
+        }
+
+        self.buffer
+            .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+        self.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+            s.select_ranges(cursors.into_iter().map(|cursor| cursor..cursor));
+        });
+    }
+
     pub fn paste(&mut self, _: &Paste, window: &mut Window, cx: &mut Context<Self>) {
         if let Some(item) = cx.read_from_clipboard() {
             let entries = item.entries();
@@ -10077,6 +10494,8 @@ impl Editor {
             self.select_next_state = entry.select_next_state;
             self.select_prev_state = entry.select_prev_state;
             self.add_selections_state = entry.add_selections_state;
+            self.block_selection = entry.block_selection;
+            self.set_scroll_anchor(entry.scroll_anchor, window, cx);
             self.request_autoscroll(Autoscroll::newest(), cx);
         }
         self.selection_history.mode = SelectionHistoryMode::Normal;
@@ -10097,6 +10516,8 @@ impl Editor {
             self.select_next_state = entry.select_next_state;
             self.select_prev_state = entry.select_prev_state;
             self.add_selections_state = entry.add_selections_state;
+            self.block_selection = entry.block_selection;
+            self.set_scroll_anchor(entry.scroll_anchor, window, cx);
             self.request_autoscroll(Autoscroll::newest(), cx);
         }
         self.selection_history.mode = SelectionHistoryMode::Normal;
@@ -11605,9 +12026,11 @@ impl Editor {
     fn push_to_selection_history(&mut self) {
         self.selection_history.push(SelectionHistoryEntry {
             selections: self.selections.disjoint_anchors(),
+            scroll_anchor: self.scroll_manager.anchor(),
             select_next_state: self.select_next_state.clone(),
             select_prev_state: self.select_prev_state.clone(),
             add_selections_state: self.add_selections_state.clone(),
+            block_selection: self.block_selection.clone(),
         });
     }
 
@@ -12130,6 +12553,7 @@ impl Editor {
         }
 
         cx.notify();
+        self.folds_did_change(cx);
 
         if let Some(active_diagnostics) = self.active_diagnostics.take() {
             // Clear diagnostics block when folding a range that contains it.
@@ -12235,10 +12659,72 @@ impl Editor {
         }
 
         cx.notify();
+        self.folds_did_change(cx);
         self.scrollbar_marker_state.dirty = true;
         self.active_indent_guides_state.dirty = true;
     }
 
+    fn folds_did_change(&mut self, cx: &mut Context<Self>) {
+        if let Some(workspace_id) = self.workspace.as_ref().and_then(|workspace| workspace.1) {
+            self.serialize_folds(workspace_id, cx);
+        }
+    }
+
+    /// Saves the folds of a singleton buffer, so that they're restored along with the editor
+    /// when the workspace is reopened. Folds with a type tag are created by features that
+    /// manage their own placeholders, so they aren't saved.
+    fn serialize_folds(&mut self, workspace_id: WorkspaceId, cx: &mut Context<Self>) {
+        if !self.buffer().read(cx).is_singleton() {
+            return;
+        }
+
+        let snapshot = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let folds = snapshot
+            .folds_in_range(0..snapshot.buffer_snapshot.len())
+            .filter(|fold| fold.placeholder.type_tag.is_none())
+            .map(|fold| {
+                let range = fold.range.to_offset(&snapshot.buffer_snapshot);
+                (range.start, range.end)
+            })
+            .collect::<Vec<_>>();
+        let item_id = cx.entity().entity_id().as_u64() as ItemId;
+        let executor = cx.background_executor().clone();
+        self.serialize_folds = cx.background_executor().spawn(async move {
+            executor.timer(SERIALIZE_FOLDS_DEBOUNCE_TIMEOUT).await;
+            DB.save_editor_folds(item_id, workspace_id, folds)
+                .await
+                .log_err();
+        });
+    }
+
+    /// Restores the scroll position and folds that were saved for the editor with the given
+    /// item id, when deserializing a workspace.
+    pub fn read_metadata_from_db(
+        &mut self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.buffer().read(cx).is_singleton() {
+            if let Some(folds) = DB.get_editor_folds(item_id, workspace_id).log_err() {
+                let len = self.buffer().read(cx).len(cx);
+                let ranges = folds
+                    .into_iter()
+                    .map(|(start, end)| start.min(len)..end.min(len))
+                    .filter(|range| !range.is_empty())
+                    .collect::<Vec<_>>();
+                if !ranges.is_empty() {
+                    self.fold_ranges(ranges, false, window, cx);
+                    // This editor has a new item id, so its folds are saved again to be restored
+                    // the next time the workspace is reopened.
+                    self.serialize_folds(workspace_id, cx);
+                }
+            }
+        }
+        self.read_scroll_position_from_db(item_id, workspace_id, window, cx);
+    }
+
     pub fn default_fold_placeholder(&self, cx: &App) -> FoldPlaceholder {
         self.display_map.read(cx).fold_placeholder.clone()
     }
@@ -13662,7 +14148,27 @@ impl Editor {
         cx: &mut Context<Self>,
     ) {
         self.display_map.update(cx, |map, _| {
-            map.highlight_text(TypeId::of::<T>(), ranges, style)
+            map.highlight_text(HighlightKey::Type(TypeId::of::<T>()), ranges, style)
+        });
+        cx.notify();
+    }
+
+    /// Like [`Self::highlight_text`], but for types that highlight several sets of ranges with
+    /// different styles, each identified by a key. These are all cleared along with the type's
+    /// other highlights.
+    pub fn highlight_text_key<T: 'static>(
+        &mut self,
+        key: usize,
+        ranges: Vec<Range<Anchor>>,
+        style: HighlightStyle,
+        cx: &mut Context<Self>,
+    ) {
+        self.display_map.update(cx, |map, _| {
+            map.highlight_text(
+                HighlightKey::TypePlus(TypeId::of::<T>(), key),
+                ranges,
+                style,
+            )
         });
         cx.notify();
     }
@@ -13747,6 +14253,7 @@ impl Editor {
                         }
                     }
                 }
+                refresh_rainbow_brackets(self, cx);
                 cx.emit(EditorEvent::BufferEdited);
                 cx.emit(SearchEvent::MatchesInvalidated);
                 if *singleton_buffer_edited {
@@ -13832,6 +14339,7 @@ impl Editor {
             }
             multi_buffer::Event::Reparsed(buffer_id) => {
                 self.tasks_update_task = Some(self.refresh_runnables(window, cx));
+                refresh_rainbow_brackets(self, cx);
 
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
             }
@@ -13883,6 +14391,8 @@ impl Editor {
             )),
             cx,
         );
+        self.rainbow_brackets = None;
+        refresh_rainbow_brackets(self, cx);
 
         let old_cursor_shape = self.cursor_shape;
 
@@ -14409,6 +14919,19 @@ impl Editor {
         }
     }
 
+    /// Stops blinking the cursor while the window is inactive or hidden, since nobody can
+    /// see it and each blink would otherwise redraw the window.
+    fn update_cursor_blinking(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let blinking = window.is_window_active() && window.is_visible();
+        self.blink_manager.update(cx, |blink_manager, cx| {
+            if blinking {
+                blink_manager.enable(cx);
+            } else {
+                blink_manager.disable(cx);
+            }
+        });
+    }
+
     fn handle_focus_in(&mut self, _: &mut Window, cx: &mut Context<Self>) {
         cx.emit(EditorEvent::FocusedIn)
     }
@@ -14585,6 +15108,79 @@ fn get_uncommitted_diff_for_buffer(
     .detach();
 }
 
+/// Returns the values of the variables that snippets can refer to, like `$TM_FILENAME`, for a
+/// snippet inserted at the given position.
+fn snippet_variables(
+    buffer: &Buffer,
+    position: text::Anchor,
+    selected_text: String,
+    cx: &App,
+) -> HashMap<&'static str, String> {
+    let snapshot = buffer.snapshot();
+    let point = text::ToPoint::to_point(&position, &snapshot);
+    let line_range = Point::new(point.row, 0)..Point::new(point.row, snapshot.line_len(point.row));
+    let (word_range, _) = snapshot.surrounding_word(point);
+
+    let mut variables = HashMap::default();
+    variables.insert("TM_SELECTED_TEXT", selected_text);
+    variables.insert(
+        "TM_CURRENT_LINE",
+        snapshot.text_for_range(line_range).collect(),
+    );
+    variables.insert(
+        "TM_CURRENT_WORD",
+        snapshot.text_for_range(word_range).collect(),
+    );
+    variables.insert("TM_LINE_INDEX", point.row.to_string());
+    variables.insert("TM_LINE_NUMBER", (point.row + 1).to_string());
+
+    if let Some(file) = buffer.file() {
+        let path = match file.as_local() {
+            Some(file) => file.abs_path(cx),
+            None => file.full_path(cx),
+        };
+        if let Some(file_name) = path.file_name() {
+            variables.insert("TM_FILENAME", file_name.to_string_lossy().into_owned());
+        }
+        if let Some(file_stem) = path.file_stem() {
+            variables.insert("TM_FILENAME_BASE", file_stem.to_string_lossy().into_owned());
+        }
+        if let Some(directory) = path.parent() {
+            variables.insert("TM_DIRECTORY", directory.to_string_lossy().into_owned());
+        }
+        variables.insert("TM_FILEPATH", path.to_string_lossy().into_owned());
+        variables.insert(
+            "RELATIVE_FILEPATH",
+            file.path().to_string_lossy().into_owned(),
+        );
+    }
+
+    if let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) {
+        variables.insert("CLIPBOARD", text);
+    }
+
+    let now = chrono::Local::now();
+    for (name, format) in [
+        ("CURRENT_YEAR", "%Y"),
+        ("CURRENT_YEAR_SHORT", "%y"),
+        ("CURRENT_MONTH", "%m"),
+        ("CURRENT_MONTH_NAME", "%B"),
+        ("CURRENT_MONTH_NAME_SHORT", "%b"),
+        ("CURRENT_DATE", "%d"),
+        ("CURRENT_DAY_NAME", "%A"),
+        ("CURRENT_DAY_NAME_SHORT", "%a"),
+        ("CURRENT_HOUR", "%H"),
+        ("CURRENT_MINUTE", "%M"),
+        ("CURRENT_SECOND", "%S"),
+    ] {
+        variables.insert(name, now.format(format).to_string());
+    }
+    variables.insert("CURRENT_SECONDS_UNIX", now.timestamp().to_string());
+    variables.insert("UUID", uuid::Uuid::new_v4().to_string());
+
+    variables
+}
+
 fn char_len_with_expanded_tabs(offset: usize, text: &str, tab_size: NonZeroU32) -> usize {
     let tab_size = tab_size.get() as usize;
     let mut width = offset;
@@ -16005,7 +16601,7 @@ impl EntityInputHandler for Editor {
                         underline: Some(UnderlineStyle {
                             thickness: px(1.),
                             color: None,
-                            wavy: false,
+                            kind: UnderlineKind::Solid,
                         }),
                         ..Default::default()
                     },
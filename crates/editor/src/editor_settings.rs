@@ -15,6 +15,9 @@ pub struct EditorSettings {
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
     pub gutter: Gutter,
+    pub sticky_scroll: StickyScroll,
+    pub minimap: Minimap,
+    pub rainbow_brackets: RainbowBrackets,
     pub scroll_beyond_last_line: ScrollBeyondLastLine,
     pub vertical_scroll_margin: f32,
     pub autoscroll_on_clicks: bool,
@@ -116,6 +119,25 @@ pub struct Gutter {
     pub code_actions: bool,
     pub runnables: bool,
     pub folds: bool,
+    pub wrap_indicators: bool,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScroll {
+    pub enabled: bool,
+    pub max_depth: usize,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct Minimap {
+    pub show: bool,
+    pub max_columns: u32,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct RainbowBrackets {
+    pub enabled: bool,
+    pub max_depth: usize,
 }
 
 /// When to show the scrollbar in the editor.
@@ -292,6 +314,12 @@ pub struct EditorSettingsContent {
     pub scrollbar: Option<ScrollbarContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
+    /// Sticky scroll related settings
+    pub sticky_scroll: Option<StickyScrollContent>,
+    /// Minimap related settings
+    pub minimap: Option<MinimapContent>,
+    /// Rainbow brackets related settings
+    pub rainbow_brackets: Option<RainbowBracketsContent>,
     /// Whether the editor will scroll beyond the last line.
     ///
     /// Default: one_page
@@ -460,6 +488,51 @@ pub struct GutterContent {
     ///
     /// Default: true
     pub folds: Option<bool>,
+    /// Whether to mark rows that continue a soft-wrapped line in the gutter.
+    ///
+    /// Default: false
+    pub wrap_indicators: Option<bool>,
+}
+
+/// Sticky scroll related settings
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScrollContent {
+    /// Whether to pin the first lines of the enclosing functions, classes and other
+    /// outline items to the top of the editor while scrolling through their bodies.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The maximum number of nested outline items whose first lines are pinned.
+    ///
+    /// Default: 5
+    pub max_depth: Option<usize>,
+}
+
+/// Minimap related settings
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct MinimapContent {
+    /// Whether to show a minimap of the buffer to the right of the text.
+    ///
+    /// Default: false
+    pub show: Option<bool>,
+    /// The number of columns of text shown in the minimap, each of which is a pixel wide.
+    ///
+    /// Default: 120
+    pub max_columns: Option<u32>,
+}
+
+/// Rainbow brackets related settings
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct RainbowBracketsContent {
+    /// Whether to color brackets by how deeply they're nested.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The number of nesting levels that are colored. Brackets nested more
+    /// deeply keep their syntax color.
+    ///
+    /// Default: 10
+    pub max_depth: Option<usize>,
 }
 
 impl EditorSettings {
@@ -21,7 +21,7 @@ use language::{
     BracketPairConfig,
     Capability::ReadWrite,
     FakeLspAdapter, LanguageConfig, LanguageConfigOverride, LanguageMatcher, LanguageName,
-    Override, ParsedMarkdown, Point,
+    LineEnding, Override, ParsedMarkdown, Point,
 };
 use language_settings::{Formatter, FormatterList, IndentGuideSettings};
 use multi_buffer::IndentGuide;
@@ -4609,6 +4609,46 @@ async fn test_rewrap(cx: &mut TestAppContext) {
     }
 }
 
+#[gpui::test]
+async fn test_column_selection_clipboard(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    // Rows that are too short to contain the rectangle are skipped or clipped.
+    cx.set_state(indoc! {"
+        oneˇ two
+        three four
+        five
+        six seven
+    "});
+    cx.update_editor(|e, window, cx| {
+        e.select_column_right(&SelectColumnRight, window, cx);
+        e.select_column_right(&SelectColumnRight, window, cx);
+        for _ in 0..3 {
+            e.select_column_down(&SelectColumnDown, window, cx);
+        }
+        e.copy(&Copy, window, cx);
+    });
+    cx.assert_editor_state(indoc! {"
+        one« tˇ»wo
+        thr«eeˇ» four
+        fiv«eˇ»
+        six« sˇ»even
+    "});
+
+    // Pasting at a single cursor inserts the lines as a rectangle, padding short rows and
+    // adding rows past the end of the buffer.
+    cx.set_state("abˇ\nc\n");
+    cx.update_editor(|e, window, cx| e.paste(&Paste, window, cx));
+    cx.assert_editor_state("ab tˇ\nc eeˇ\n  eˇ\n   sˇ");
+
+    // With a cursor for each line, each cursor pastes one line.
+    cx.set_state("ˇ\nˇ\nˇ\nˇ");
+    cx.update_editor(|e, window, cx| e.paste(&Paste, window, cx));
+    cx.assert_editor_state(" tˇ\neeˇ\neˇ\n sˇ");
+}
+
 #[gpui::test]
 async fn test_clipboard(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -5203,6 +5243,32 @@ async fn test_add_selection_above_below(cx: &mut TestAppContext) {
     ));
 }
 
+#[gpui::test]
+async fn test_undo_selection_restores_scroll_position(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(&format!("ˇ{}", "line\n".repeat(100)));
+    cx.update_editor(|editor, window, cx| {
+        editor.set_scroll_position(point(0., 10.), window, cx);
+        editor.move_to_end(&MoveToEnd, window, cx);
+        // Simulate the autoscroll that follows the cursor to the end of the buffer.
+        editor.set_scroll_position(point(0., 80.), window, cx);
+    });
+
+    cx.update_editor(|editor, window, cx| {
+        editor.undo_selection(&UndoSelection, window, cx);
+        assert_eq!(editor.scroll_position(cx), point(0., 10.));
+    });
+    cx.assert_editor_state(&format!("ˇ{}", "line\n".repeat(100)));
+
+    cx.update_editor(|editor, window, cx| {
+        editor.redo_selection(&RedoSelection, window, cx);
+        assert_eq!(editor.scroll_position(cx), point(0., 80.));
+    });
+    cx.assert_editor_state(&format!("{}ˇ", "line\n".repeat(100)));
+}
+
 #[gpui::test]
 async fn test_select_next(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -12977,6 +13043,101 @@ async fn test_mutlibuffer_in_navigation_history(cx: &mut gpui::TestAppContext) {
         .unwrap();
 }
 
+#[gpui::test]
+async fn test_stage_selected_hunks(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    let diff_base = "one\ntwo\nthree\nfour\nfive\n";
+    cx.set_state("one\nTWO\nthree\nfour\nˇFIVE\nsix\n");
+    cx.set_diff_base(diff_base);
+    cx.set_index_text(diff_base);
+
+    // Only the unstaged changes within the selected hunk are written to the index.
+    cx.update_editor(|editor, window, cx| {
+        editor.stage_selected_hunks(&StageSelectedHunks, window, cx);
+    });
+    cx.run_until_parked();
+    cx.assert_index_text(Some("one\ntwo\nthree\nfour\nFIVE\nsix\n"));
+
+    cx.update_editor(|editor, window, cx| {
+        editor.select_all(&SelectAll, window, cx);
+        editor.stage_selected_hunks(&StageSelectedHunks, window, cx);
+    });
+    cx.run_until_parked();
+    cx.assert_index_text(Some("one\nTWO\nthree\nfour\nFIVE\nsix\n"));
+}
+
+#[gpui::test]
+async fn test_bookmarks(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state("ˇone\ntwo\nthree\nfour\nfive\n");
+    let bookmarked_rows = |cx: &mut EditorTestContext| {
+        cx.update_editor(|editor, _, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            editor
+                .bookmarked_rows(&snapshot, Point::zero()..snapshot.max_point(), cx)
+                .into_iter()
+                .map(|row| row.0)
+                .collect::<Vec<_>>()
+        })
+    };
+
+    cx.update_editor(|editor, window, cx| {
+        editor.toggle_bookmark(&ToggleBookmark, window, cx);
+        editor.change_selections(None, window, cx, |s| {
+            s.select_ranges([Point::new(3, 2)..Point::new(3, 2)])
+        });
+        editor.toggle_bookmark(&ToggleBookmark, window, cx);
+    });
+    assert_eq!(bookmarked_rows(&mut cx), [0, 3]);
+
+    // Navigation wraps around the ends of the buffer.
+    cx.update_editor(|editor, window, cx| {
+        editor.go_to_bookmark(&GoToBookmark, window, cx);
+    });
+    cx.assert_editor_state("ˇone\ntwo\nthree\nfour\nfive\n");
+
+    // Bookmarks stay with their lines as text is inserted above them.
+    cx.update_editor(|editor, window, cx| {
+        editor.newline(&Newline, window, cx);
+    });
+    assert_eq!(bookmarked_rows(&mut cx), [1, 4]);
+
+    cx.update_editor(|editor, window, cx| {
+        editor.go_to_prev_bookmark(&GoToPrevBookmark, window, cx);
+    });
+    cx.assert_editor_state("\none\ntwo\nthree\nˇfour\nfive\n");
+
+    cx.update_editor(|editor, window, cx| {
+        editor.toggle_bookmark(&ToggleBookmark, window, cx);
+    });
+    assert_eq!(bookmarked_rows(&mut cx), [1]);
+}
+
+#[gpui::test]
+async fn test_stage_selected_hunks_with_crlf_line_endings(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    let diff_base = "one\ntwo\nthree\n";
+    cx.set_state("one\nTWO\nthree\nˇfour\n");
+    cx.update_buffer(|buffer, cx| {
+        buffer.did_reload(buffer.version(), LineEnding::Windows, None, cx);
+    });
+    cx.set_diff_base(diff_base);
+    cx.set_index_text(diff_base);
+
+    // The staged text keeps the file's line endings, including on unchanged lines.
+    cx.update_editor(|editor, window, cx| {
+        editor.stage_selected_hunks(&StageSelectedHunks, window, cx);
+    });
+    cx.run_until_parked();
+    cx.assert_index_text(Some("one\r\ntwo\r\nthree\r\nfour\r\n"));
+}
+
 #[gpui::test]
 async fn test_toggle_selected_diff_hunks(
     executor: BackgroundExecutor,
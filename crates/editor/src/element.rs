@@ -14,13 +14,13 @@ use crate::{
     },
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MenuPosition, MouseContextMenu},
-    scroll::{axis_pair, scroll_amount::ScrollAmount, AxisPair},
+    scroll::{axis_pair, scroll_amount::ScrollAmount, Autoscroll, AxisPair},
     BlockId, ChunkReplacement, CursorShape, CustomBlockId, DisplayPoint, DisplayRow,
     DocumentHighlightRead, DocumentHighlightWrite, EditDisplayMode, Editor, EditorMode,
     EditorSettings, EditorSnapshot, EditorStyle, ExpandExcerpts, FocusedBlock, GoToHunk,
     GoToPrevHunk, GutterDimensions, HalfPageDown, HalfPageUp, HandleInput, HoveredCursor,
     InlineCompletion, JumpData, LineDown, LineUp, OpenExcerpts, PageDown, PageUp, Point,
-    RevertSelectedHunks, RowExt, RowRangeExt, SelectPhase, Selection, SoftWrap,
+    RevertSelectedHunks, RowExt, RowRangeExt, SelectPhase, Selection, SoftWrap, StageSelectedHunks,
     StickyHeaderExcerpt, ToPoint, ToggleFold, CURSORS_VISIBLE_FOR, FILE_HEADER_HEIGHT,
     GIT_BLAME_MAX_AUTHOR_CHARS_DISPLAYED, MAX_LINE_LEN, MULTI_BUFFER_EXCERPT_HEADER_HEIGHT,
 };
@@ -62,7 +62,7 @@ use std::{
     cmp::{self, Ordering},
     fmt::{self, Write},
     iter, mem,
-    ops::{Deref, Range},
+    ops::{Deref, Range, RangeInclusive},
     rc::Rc,
     sync::Arc,
 };
@@ -78,6 +78,10 @@ use util::{RangeExt, ResultExt};
 use workspace::{item::Item, notifications::NotifyTaskExt, Workspace};
 
 const INLINE_BLAME_PADDING_EM_WIDTHS: f32 = 7.;
+const MINIMAP_ROW_HEIGHT: Pixels = px(2.);
+const MINIMAP_COLUMN_WIDTH: Pixels = px(1.);
+const MINIMAP_DIAGNOSTIC_WIDTH: Pixels = px(3.);
+const WRAP_INDICATOR: &str = "↪";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum DisplayDiffHunk {
@@ -313,6 +317,10 @@ impl EditorElement {
         register_action(editor, window, Editor::split_selection_into_lines);
         register_action(editor, window, Editor::add_selection_above);
         register_action(editor, window, Editor::add_selection_below);
+        register_action(editor, window, Editor::select_column_up);
+        register_action(editor, window, Editor::select_column_down);
+        register_action(editor, window, Editor::select_column_left);
+        register_action(editor, window, Editor::select_column_right);
         register_action(editor, window, |editor, action, window, cx| {
             editor.select_next(action, window, cx).log_err();
         });
@@ -335,6 +343,10 @@ impl EditorElement {
         register_action(editor, window, Editor::go_to_prev_diagnostic);
         register_action(editor, window, Editor::go_to_next_hunk);
         register_action(editor, window, Editor::go_to_prev_hunk);
+        register_action(editor, window, Editor::toggle_bookmark);
+        register_action(editor, window, Editor::go_to_bookmark);
+        register_action(editor, window, Editor::go_to_prev_bookmark);
+        register_action(editor, window, Editor::clear_bookmarks);
         register_action(editor, window, |editor, action, window, cx| {
             editor
                 .go_to_definition(action, window, cx)
@@ -409,6 +421,7 @@ impl EditorElement {
         register_action(editor, window, Editor::copy_path);
         register_action(editor, window, Editor::copy_relative_path);
         register_action(editor, window, Editor::copy_highlight_json);
+        register_action(editor, window, Editor::export_to_pdf);
         register_action(editor, window, Editor::copy_permalink_to_line);
         register_action(editor, window, Editor::open_permalink_to_line);
         register_action(editor, window, Editor::copy_file_location);
@@ -489,6 +502,7 @@ impl EditorElement {
         register_action(editor, window, Editor::accept_edit_prediction);
         register_action(editor, window, Editor::revert_file);
         register_action(editor, window, Editor::revert_selected_hunks);
+        register_action(editor, window, Editor::stage_selected_hunks);
         register_action(editor, window, Editor::apply_all_diff_hunks);
         register_action(editor, window, Editor::apply_selected_diff_hunks);
         register_action(editor, window, Editor::open_active_item_in_terminal);
@@ -525,6 +539,7 @@ impl EditorElement {
         cx: &mut Context<Editor>,
     ) {
         editor.update_inline_completion_preview(&event.modifiers, window, cx);
+        editor.update_inlay_hints_for_modifiers(&event.modifiers, cx);
 
         let mouse_position = window.mouse_position();
         if !position_map.text_hitbox.is_hovered(window) {
@@ -612,7 +627,16 @@ impl EditorElement {
         }
 
         let point_for_position = position_map.point_for_position(event.position);
+        if click_count == 2
+            && !modifiers.modified()
+            && editor.apply_inlay_hint_text_edits(&point_for_position, window, cx)
+        {
+            cx.stop_propagation();
+            return;
+        }
+
         let position = point_for_position.previous_valid;
+        let multi_cursor_setting = EditorSettings::get_global(cx).multi_cursor_modifier;
         if modifiers.shift && modifiers.alt {
             editor.select(
                 SelectPhase::BeginColumnar {
@@ -633,8 +657,21 @@ impl EditorElement {
                 window,
                 cx,
             );
+        } else if modifiers.alt
+            && !modifiers.secondary()
+            && multi_cursor_setting == MultiCursorModifier::CmdOrCtrl
+        {
+            // When alt isn't used to add cursors, alt-dragging starts a rectangular selection.
+            editor.select(
+                SelectPhase::BeginColumnar {
+                    position,
+                    reset: true,
+                    goal_column: point_for_position.exact_unclipped.column(),
+                },
+                window,
+                cx,
+            );
         } else {
-            let multi_cursor_setting = EditorSettings::get_global(cx).multi_cursor_modifier;
             let multi_cursor_modifier = match multi_cursor_setting {
                 MultiCursorModifier::Alt => modifiers.alt,
                 MultiCursorModifier::CmdOrCtrl => modifiers.secondary(),
@@ -1982,6 +2019,78 @@ impl EditorElement {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_bookmark_indicators(
+        &self,
+        line_height: Pixels,
+        range: Range<DisplayRow>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
+        code_actions_row: Option<DisplayRow>,
+        snapshot: &EditorSnapshot,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<AnyElement> {
+        if snapshot.mode != EditorMode::Full {
+            return Vec::new();
+        }
+        let show_runnables = EditorSettings::get_global(cx).gutter.runnables;
+        let start = snapshot
+            .display_point_to_anchor(DisplayPoint::new(range.start, 0), Bias::Left)
+            .to_point(&snapshot.buffer_snapshot);
+        let end = snapshot
+            .display_point_to_anchor(DisplayPoint::new(range.end, 0), Bias::Right)
+            .to_point(&snapshot.buffer_snapshot);
+
+        self.editor.update(cx, |editor, cx| {
+            editor
+                .bookmarked_rows(&snapshot.buffer_snapshot, start..end, cx)
+                .into_iter()
+                .filter_map(|row| {
+                    let (buffer, buffer_range) =
+                        snapshot.buffer_snapshot.buffer_line_for_row(row)?;
+                    if editor.is_buffer_folded(buffer.remote_id(), cx) {
+                        return None;
+                    }
+                    // Run indicators take the place of bookmarks on the rows they're on.
+                    if show_runnables
+                        && editor
+                            .tasks
+                            .contains_key(&(buffer.remote_id(), buffer_range.start.row))
+                    {
+                        return None;
+                    }
+                    if snapshot.is_line_folded(row)
+                        && row.0.checked_sub(1).map_or(false, |previous_row| {
+                            snapshot.is_line_folded(MultiBufferRow(previous_row))
+                        })
+                    {
+                        return None;
+                    }
+
+                    let display_row = Point::new(row.0, 0).to_display_point(snapshot).row();
+                    if Some(display_row) == code_actions_row || !range.contains(&display_row) {
+                        return None;
+                    }
+                    let button = editor.render_bookmark_indicator(display_row, cx);
+                    Some(prepaint_gutter_button(
+                        button,
+                        display_row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        rows_with_hunk_bounds,
+                        window,
+                        cx,
+                    ))
+                })
+                .collect()
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_code_actions_indicator(
         &self,
@@ -2081,6 +2190,58 @@ impl EditorElement {
         relative_rows
     }
 
+    /// Lays out a marker in the gutter for each row that continues a soft-wrapped line.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_wrap_indicators(
+        &self,
+        gutter_hitbox: &Hitbox,
+        gutter_dimensions: GutterDimensions,
+        line_height: Pixels,
+        scroll_position: gpui::Point<f32>,
+        rows: Range<DisplayRow>,
+        row_infos: &[RowInfo],
+        snapshot: &EditorSnapshot,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<(gpui::Point<Pixels>, ShapedLine)> {
+        if !EditorSettings::get_global(cx).gutter.wrap_indicators
+            || snapshot.mode != EditorMode::Full
+        {
+            return Vec::new();
+        }
+        let Some(indicator) = self
+            .shape_line_number(
+                SharedString::new_static(WRAP_INDICATOR),
+                cx.theme().colors().editor_line_number,
+                window,
+            )
+            .log_err()
+        else {
+            return Vec::new();
+        };
+
+        let scroll_top = scroll_position.y * line_height;
+        row_infos
+            .iter()
+            .enumerate()
+            .filter(|(ix, row_info)| {
+                // Rows without a buffer row are either blocks or soft-wrapped continuations.
+                row_info.buffer_row.is_none()
+                    && !snapshot.is_block_line(DisplayRow(rows.start.0 + *ix as u32))
+            })
+            .map(|(ix, _)| {
+                let origin = gutter_hitbox.origin
+                    + point(
+                        gutter_hitbox.size.width
+                            - indicator.width
+                            - gutter_dimensions.right_padding,
+                        ix as f32 * line_height - (scroll_top % line_height),
+                    );
+                (origin, indicator.clone())
+            })
+            .collect()
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_line_numbers(
         &self,
@@ -3086,6 +3247,243 @@ impl EditorElement {
         header
     }
 
+    /// Lays out the first lines of the outline items enclosing the top of the viewport, so that
+    /// they stay pinned while scrolling through the items' bodies. Once the end of an item
+    /// scrolls up into the pinned lines, its first line is pushed up along with it.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_sticky_scroll(
+        &self,
+        snapshot: &EditorSnapshot,
+        scroll_position: f32,
+        line_height: Pixels,
+        editor_width: Pixels,
+        hitbox: &Hitbox,
+        gutter_hitbox: &Hitbox,
+        gutter_dimensions: &GutterDimensions,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<StickyScrollLine> {
+        let settings = EditorSettings::get_global(cx);
+        let sticky_scroll = settings.sticky_scroll;
+        let show_line_numbers = snapshot
+            .show_line_numbers
+            .unwrap_or(settings.gutter.line_numbers);
+        if !sticky_scroll.enabled
+            || snapshot.mode != EditorMode::Full
+            || !snapshot.buffer_snapshot.is_singleton()
+            || scroll_position <= 0.
+        {
+            return Vec::new();
+        }
+
+        let top_point = DisplayPoint::new(DisplayRow(scroll_position as u32), 0).to_point(snapshot);
+        let Some((_, items)) = snapshot.buffer_snapshot.symbols_containing(top_point, None) else {
+            return Vec::new();
+        };
+
+        let mut lines = Vec::new();
+        for item in items {
+            if lines.len() >= sticky_scroll.max_depth {
+                break;
+            }
+            let start = item.range.start.to_display_point(snapshot);
+            let end_row = item.range.end.to_display_point(snapshot).row();
+            let slot = lines.len() as f32;
+            // Items whose first line is still visible below the pinned lines aren't pinned.
+            if start.row().as_f32() >= scroll_position + slot {
+                break;
+            }
+
+            let display_row = start.row();
+            let Some(line) = Self::layout_lines(
+                display_row..display_row.next_row(),
+                snapshot,
+                &self.style,
+                editor_width,
+                |_| false,
+                window,
+                cx,
+            )
+            .pop() else {
+                continue;
+            };
+
+            let offset = line_height * slot.min(end_row.as_f32() - scroll_position);
+            let bounds = Bounds::new(
+                point(hitbox.left(), hitbox.top() + offset),
+                size(hitbox.size.width, line_height),
+            );
+            let buffer_point = start.to_point(snapshot);
+            let line_number = show_line_numbers
+                .then(|| {
+                    let color = cx.theme().colors().editor_line_number;
+                    let number = SharedString::from((buffer_point.row + 1).to_string());
+                    let shaped_line = self.shape_line_number(number, color, window).log_err()?;
+                    let origin = point(
+                        gutter_hitbox.right() - shaped_line.width - gutter_dimensions.right_padding,
+                        bounds.top(),
+                    );
+                    Some((origin, shaped_line))
+                })
+                .flatten();
+
+            lines.push(StickyScrollLine {
+                buffer_point: Point::new(buffer_point.row, 0),
+                line,
+                line_number,
+                hitbox: window.insert_hitbox(bounds, true),
+            });
+        }
+        lines
+    }
+
+    /// Returns the width taken up by the minimap, which is only shown in full editors that are
+    /// wide enough for it to take up at most a quarter of their width.
+    fn minimap_width(snapshot: &EditorSnapshot, bounds: Bounds<Pixels>, cx: &App) -> Pixels {
+        let settings = EditorSettings::get_global(cx).minimap;
+        let width = MINIMAP_COLUMN_WIDTH * settings.max_columns as f32;
+        if settings.show && snapshot.mode == EditorMode::Full && width * 4. <= bounds.size.width {
+            width
+        } else {
+            Pixels::ZERO
+        }
+    }
+
+    /// Lays out the minimap, which shows the buffer at a fixed size per character. Only the rows
+    /// that are visible in the minimap are laid out, and when there are more rows than fit, the
+    /// minimap scrolls along with the editor so that the start and end of the buffer line up.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_minimap(
+        &self,
+        hitbox: Hitbox,
+        snapshot: &EditorSnapshot,
+        scroll_position: f32,
+        max_scroll_top: f32,
+        height_in_lines: f32,
+        cx: &mut App,
+    ) -> MinimapLayout {
+        let max_columns = EditorSettings::get_global(cx).minimap.max_columns;
+        let max_row = snapshot.max_point().row();
+        let row_count = max_row.next_row().as_f32();
+        let visible_rows = hitbox.size.height / MINIMAP_ROW_HEIGHT;
+        let scroll_ratio = if row_count > visible_rows && max_scroll_top > 0. {
+            ((row_count - visible_rows) / max_scroll_top).min(1.)
+        } else {
+            0.
+        };
+        let minimap_scroll_top = scroll_position * scroll_ratio;
+        let start_row = DisplayRow(minimap_scroll_top as u32);
+        let end_row = DisplayRow(
+            ((minimap_scroll_top + visible_rows).ceil() as u32).min(max_row.next_row().0),
+        );
+
+        let row_top = |row: f32| hitbox.top() + MINIMAP_ROW_HEIGHT * (row - minimap_scroll_top);
+        let row_bounds = |rows: RangeInclusive<DisplayRow>, left: Pixels, width: Pixels| {
+            Bounds::from_corners(
+                point(left, row_top(rows.start().as_f32())),
+                point(left + width, row_top(rows.end().next_row().as_f32())),
+            )
+        };
+        let mut quads = Vec::new();
+
+        let start_anchor = snapshot
+            .buffer_snapshot
+            .anchor_before(DisplayPoint::new(start_row, 0).to_offset(snapshot, Bias::Left));
+        let end_anchor = snapshot
+            .buffer_snapshot
+            .anchor_after(DisplayPoint::new(end_row, 0).to_offset(snapshot, Bias::Right));
+        let search_rows = self
+            .editor
+            .read(cx)
+            .background_highlight_row_ranges::<BufferSearchHighlights>(
+                start_anchor..end_anchor,
+                &snapshot.display_snapshot,
+                usize::MAX,
+            );
+        let search_color = cx.theme().colors().search_match_background;
+        for rows in search_rows {
+            let rows = rows.start().row()..=rows.end().row();
+            quads.push(fill(
+                row_bounds(rows, hitbox.left(), hitbox.size.width),
+                search_color,
+            ));
+        }
+
+        // Each run of non-whitespace characters with the same color is drawn as one block.
+        let mut row = start_row;
+        let mut column = 0;
+        let mut run: Option<(u32, Hsla)> = None;
+        let mut push_run = |row: DisplayRow, start: u32, end: u32, color: Hsla| {
+            let left = hitbox.left() + MINIMAP_COLUMN_WIDTH * start as f32;
+            let width = MINIMAP_COLUMN_WIDTH * (end - start) as f32;
+            quads.push(fill(row_bounds(row..=row, left, width), color.opacity(0.8)));
+        };
+        for chunk in snapshot.highlighted_chunks(start_row..end_row, true, &self.style) {
+            let color = chunk
+                .style
+                .and_then(|style| style.color)
+                .unwrap_or(self.style.text.color);
+            for ch in chunk.text.chars() {
+                let is_text = !ch.is_whitespace() && chunk.replacement.is_none();
+                if let Some((start, run_color)) = run {
+                    if !is_text || run_color != color || column >= max_columns {
+                        push_run(row, start, column.min(max_columns), run_color);
+                        run = None;
+                    }
+                }
+                if ch == '\n' {
+                    row.0 += 1;
+                    column = 0;
+                    continue;
+                }
+                if is_text && run.is_none() && column < max_columns {
+                    run = Some((column, color));
+                }
+                column += 1;
+            }
+        }
+        if let Some((start, color)) = run {
+            push_run(row, start, column.min(max_columns), color);
+        }
+
+        let status = cx.theme().status();
+        let start_point = DisplayPoint::new(start_row, 0).to_point(snapshot);
+        let end_point = DisplayPoint::new(end_row, 0).to_point(snapshot);
+        for entry in snapshot
+            .buffer_snapshot
+            .diagnostics_in_range::<Point>(start_point..end_point)
+        {
+            let color = match entry.diagnostic.severity {
+                DiagnosticSeverity::ERROR => status.error,
+                DiagnosticSeverity::WARNING => status.warning,
+                _ => continue,
+            };
+            let start = entry.range.start.to_display_point(snapshot).row();
+            let end = entry.range.end.to_display_point(snapshot).row();
+            quads.push(fill(
+                row_bounds(
+                    start..=end,
+                    hitbox.right() - MINIMAP_DIAGNOSTIC_WIDTH,
+                    MINIMAP_DIAGNOSTIC_WIDTH,
+                ),
+                color,
+            ));
+        }
+
+        let thumb_bounds = Bounds::new(
+            point(hitbox.left(), row_top(scroll_position)),
+            size(hitbox.size.width, MINIMAP_ROW_HEIGHT * height_in_lines),
+        );
+
+        MinimapLayout {
+            hitbox,
+            quads,
+            thumb_bounds,
+            scroll_ratio,
+            max_scroll_top,
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_cursor_popovers(
         &self,
@@ -4465,6 +4863,10 @@ impl EditorElement {
                 window.set_cursor_style(CursorStyle::PointingHand, &hitbox);
             }
         }
+
+        for (origin, indicator) in &layout.wrap_indicators {
+            indicator.paint(*origin, line_height, window, cx).log_err();
+        }
     }
 
     fn paint_diff_hunks(layout: &mut EditorLayout, window: &mut Window, cx: &mut App) {
@@ -4618,6 +5020,10 @@ impl EditorElement {
                 test_indicator.paint(window, cx);
             }
 
+            for bookmark_indicator in layout.bookmark_indicators.iter_mut() {
+                bookmark_indicator.paint(window, cx);
+            }
+
             if let Some(indicator) = layout.code_actions_indicator.as_mut() {
                 indicator.paint(window, cx);
             }
@@ -5418,6 +5824,183 @@ impl EditorElement {
         }
     }
 
+    fn paint_minimap(&mut self, layout: &mut EditorLayout, window: &mut Window, cx: &mut App) {
+        let Some(minimap) = layout.minimap.take() else {
+            return;
+        };
+
+        let is_dragging = self.editor.read(cx).scroll_manager.is_dragging_minimap();
+        let colors = cx.theme().colors();
+        let thumb_color = if is_dragging || minimap.hitbox.is_hovered(window) {
+            colors.scrollbar_thumb_hover_background
+        } else {
+            colors.scrollbar_thumb_background
+        };
+        let background_color = colors.editor_background;
+        window.paint_layer(minimap.hitbox.bounds, |window| {
+            window.paint_quad(fill(minimap.hitbox.bounds, background_color));
+            for quad in &minimap.quads {
+                window.paint_quad(quad.clone());
+            }
+            window.paint_quad(fill(minimap.thumb_bounds, thumb_color));
+        });
+        window.set_cursor_style(CursorStyle::Arrow, &minimap.hitbox);
+
+        let minimap = Rc::new(minimap);
+        window.on_mouse_event({
+            let editor = self.editor.clone();
+            let minimap = minimap.clone();
+            move |event: &MouseMoveEvent, phase, window, cx| {
+                if phase == DispatchPhase::Capture {
+                    return;
+                }
+
+                editor.update(cx, |editor, cx| {
+                    if !editor.scroll_manager.is_dragging_minimap() {
+                        return;
+                    }
+                    if event.pressed_button == Some(MouseButton::Left) {
+                        let mut position = editor.scroll_position(cx);
+                        position.y = minimap.scroll_position_for_y(event.position.y);
+                        editor.set_scroll_position(position, window, cx);
+                    } else {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                    }
+                })
+            }
+        });
+
+        if is_dragging {
+            window.on_mouse_event({
+                let editor = self.editor.clone();
+                move |_: &MouseUpEvent, phase, _, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        } else {
+            window.on_mouse_event({
+                let editor = self.editor.clone();
+                move |event: &MouseDownEvent, phase, window, cx| {
+                    if phase == DispatchPhase::Capture
+                        || event.button != MouseButton::Left
+                        || !minimap.hitbox.is_hovered(window)
+                    {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(true, cx);
+                        let mut position = editor.scroll_position(cx);
+                        position.y = minimap.scroll_position_for_y(event.position.y);
+                        editor.set_scroll_position(position, window, cx);
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        }
+    }
+
+    fn paint_sticky_scroll(
+        &mut self,
+        layout: &mut EditorLayout,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        if layout.sticky_scroll_lines.is_empty() {
+            return;
+        }
+
+        let line_height = layout.position_map.line_height;
+        let scroll_left = layout.position_map.scroll_pixel_position.x;
+        let text_bounds = layout.position_map.text_hitbox.bounds;
+        let background_color = cx.theme().colors().editor_background;
+        let border_color = cx.theme().colors().border_variant;
+        window.paint_layer(layout.hitbox.bounds, |window| {
+            // Inner items are painted first, so that when their first lines are pushed up they
+            // slide underneath the lines of the items enclosing them.
+            for line in layout.sticky_scroll_lines.iter().rev() {
+                window.paint_quad(fill(line.hitbox.bounds, background_color));
+                if let Some((origin, line_number)) = &line.line_number {
+                    line_number
+                        .paint(*origin, line_height, window, cx)
+                        .log_err();
+                }
+                window.with_content_mask(
+                    Some(ContentMask {
+                        bounds: text_bounds,
+                    }),
+                    |window| {
+                        let mut fragment_origin =
+                            point(layout.content_origin.x - scroll_left, line.hitbox.origin.y);
+                        for fragment in &line.line.fragments {
+                            match fragment {
+                                LineFragment::Text(shaped_line) => {
+                                    shaped_line
+                                        .paint(fragment_origin, line_height, window, cx)
+                                        .log_err();
+                                    fragment_origin.x += shaped_line.width;
+                                }
+                                LineFragment::Element { size, .. } => {
+                                    fragment_origin.x += size.width;
+                                }
+                            }
+                        }
+                    },
+                );
+                window.set_cursor_style(CursorStyle::PointingHand, &line.hitbox);
+            }
+
+            let bottom = layout
+                .sticky_scroll_lines
+                .iter()
+                .map(|line| line.hitbox.bottom())
+                .max()
+                .unwrap_or_default();
+            window.paint_quad(fill(
+                Bounds::new(
+                    point(layout.hitbox.left(), bottom - px(1.)),
+                    size(layout.hitbox.size.width, px(1.)),
+                ),
+                border_color,
+            ));
+        });
+
+        window.on_mouse_event({
+            let editor = self.editor.clone();
+            let lines = layout
+                .sticky_scroll_lines
+                .iter()
+                .map(|line| (line.hitbox.clone(), line.buffer_point))
+                .collect::<Vec<_>>();
+            move |event: &MouseDownEvent, phase, window, cx| {
+                if phase != DispatchPhase::Bubble || event.button != MouseButton::Left {
+                    return;
+                }
+                // Lines that are pushed up overlap, so the one painted on top is preferred.
+                let Some((_, point)) = lines.iter().find(|(hitbox, _)| hitbox.is_hovered(window))
+                else {
+                    return;
+                };
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(
+                        Some(Autoscroll::top_relative(0)),
+                        window,
+                        cx,
+                        |selections| selections.select_ranges([*point..*point]),
+                    );
+                });
+                cx.stop_propagation();
+            }
+        });
+    }
+
     fn paint_inline_completion_popover(
         &mut self,
         layout: &mut EditorLayout,
@@ -6728,7 +7311,8 @@ impl Element for EditorElement {
                             cx,
                         )
                         .unwrap_or_default();
-                    let text_width = bounds.size.width - gutter_dimensions.width;
+                    let minimap_width = Self::minimap_width(&snapshot, bounds, cx);
+                    let text_width = bounds.size.width - gutter_dimensions.width - minimap_width;
 
                     let editor_width = text_width - gutter_dimensions.margin - em_width;
 
@@ -6776,13 +7360,22 @@ impl Element for EditorElement {
                         },
                         false,
                     );
+                    let minimap_hitbox = (minimap_width > Pixels::ZERO).then(|| {
+                        window.insert_hitbox(
+                            Bounds::new(
+                                text_hitbox.top_right(),
+                                size(minimap_width, bounds.size.height),
+                            ),
+                            false,
+                        )
+                    });
                     // Offset the content_bounds from the text_bounds by the gutter margin (which
                     // is roughly half a character wide) to make hit testing work more like how we want.
                     let content_origin =
                         text_hitbox.origin + point(gutter_dimensions.margin, Pixels::ZERO);
 
                     let scrollbar_bounds =
-                        Bounds::from_corners(content_origin, bounds.bottom_right());
+                        Bounds::from_corners(content_origin, text_hitbox.bottom_right());
 
                     let height_in_lines = scrollbar_bounds.size.height / line_height;
 
@@ -6948,6 +7541,18 @@ impl Element for EditorElement {
                         cx,
                     );
 
+                    let wrap_indicators = self.layout_wrap_indicators(
+                        &gutter_hitbox,
+                        gutter_dimensions,
+                        line_height,
+                        scroll_position,
+                        start_row..end_row,
+                        &row_infos,
+                        &snapshot,
+                        window,
+                        cx,
+                    );
+
                     let mut crease_toggles =
                         window.with_element_namespace("crease_toggles", |window| {
                             self.layout_crease_toggles(
@@ -7083,6 +7688,29 @@ impl Element for EditorElement {
                         }
                     };
 
+                    let sticky_scroll_lines = self.layout_sticky_scroll(
+                        &snapshot,
+                        scroll_position.y,
+                        line_height,
+                        editor_width,
+                        &hitbox,
+                        &gutter_hitbox,
+                        &gutter_dimensions,
+                        window,
+                        cx,
+                    );
+
+                    let minimap = minimap_hitbox.map(|hitbox| {
+                        self.layout_minimap(
+                            hitbox,
+                            &snapshot,
+                            scroll_position.y,
+                            max_scroll_top,
+                            height_in_lines,
+                            cx,
+                        )
+                    });
+
                     let sticky_buffer_header = sticky_header_excerpt.map(|sticky_header_excerpt| {
                         window.with_element_namespace("blocks", |window| {
                             self.layout_sticky_buffer_header(
@@ -7387,6 +8015,21 @@ impl Element for EditorElement {
                         Vec::new()
                     };
 
+                    let bookmark_indicators = self.layout_bookmark_indicators(
+                        line_height,
+                        start_row..end_row,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &rows_with_hunk_bounds,
+                        newest_selection_head
+                            .filter(|_| code_actions_indicator.is_some())
+                            .map(|head| head.row()),
+                        &snapshot,
+                        window,
+                        cx,
+                    );
+
                     self.layout_signature_help(
                         &hitbox,
                         content_origin,
@@ -7536,6 +8179,7 @@ impl Element for EditorElement {
                         redacted_ranges,
                         line_elements,
                         line_numbers,
+                        wrap_indicators,
                         blamed_display_rows,
                         inline_blame,
                         blocks,
@@ -7546,12 +8190,15 @@ impl Element for EditorElement {
                         diff_hunk_controls: hunk_controls,
                         mouse_context_menu,
                         test_indicators,
+                        bookmark_indicators,
                         code_actions_indicator,
                         crease_toggles,
                         crease_trailers,
                         tab_invisible,
                         space_invisible,
                         sticky_buffer_header,
+                        sticky_scroll_lines,
+                        minimap,
                     }
                 })
             })
@@ -7618,6 +8265,9 @@ impl Element for EditorElement {
                         }
                     });
 
+                    self.paint_sticky_scroll(layout, window, cx);
+                    self.paint_minimap(layout, window, cx);
+
                     self.paint_scrollbars(layout, window, cx);
                     self.paint_inline_completion_popover(layout, window, cx);
                     self.paint_mouse_context_menu(layout, window, cx);
@@ -7707,6 +8357,7 @@ pub struct EditorLayout {
     highlighted_rows: BTreeMap<DisplayRow, Hsla>,
     line_elements: SmallVec<[AnyElement; 1]>,
     line_numbers: Arc<HashMap<MultiBufferRow, LineNumberLayout>>,
+    wrap_indicators: Vec<(gpui::Point<Pixels>, ShapedLine)>,
     display_hunks: Vec<(DisplayDiffHunk, Option<Hitbox>)>,
     blamed_display_rows: Option<Vec<AnyElement>>,
     inline_blame: Option<AnyElement>,
@@ -7719,6 +8370,7 @@ pub struct EditorLayout {
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
     code_actions_indicator: Option<AnyElement>,
     test_indicators: Vec<AnyElement>,
+    bookmark_indicators: Vec<AnyElement>,
     crease_toggles: Vec<Option<AnyElement>>,
     diff_hunk_controls: Vec<AnyElement>,
     crease_trailers: Vec<Option<CreaseTrailerLayout>>,
@@ -7727,6 +8379,8 @@ pub struct EditorLayout {
     tab_invisible: ShapedLine,
     space_invisible: ShapedLine,
     sticky_buffer_header: Option<AnyElement>,
+    sticky_scroll_lines: Vec<StickyScrollLine>,
+    minimap: Option<MinimapLayout>,
 }
 
 impl EditorLayout {
@@ -7741,6 +8395,32 @@ struct LineNumberLayout {
     display_row: DisplayRow,
 }
 
+struct MinimapLayout {
+    hitbox: Hitbox,
+    quads: Vec<PaintQuad>,
+    thumb_bounds: Bounds<Pixels>,
+    /// How many rows the minimap scrolls for each row the editor scrolls.
+    scroll_ratio: f32,
+    max_scroll_top: f32,
+}
+
+impl MinimapLayout {
+    /// Returns the scroll position at which the thumb is centered on the given y coordinate.
+    fn scroll_position_for_y(&self, y: Pixels) -> f32 {
+        let thumb_top = y - self.hitbox.top() - self.thumb_bounds.size.height / 2.;
+        // The thumb moves by the rows the editor scrolls, less the rows the minimap scrolls.
+        let scroll_position = thumb_top / MINIMAP_ROW_HEIGHT / (1. - self.scroll_ratio).max(0.1);
+        scroll_position.clamp(0., self.max_scroll_top)
+    }
+}
+
+struct StickyScrollLine {
+    buffer_point: Point,
+    line: LineWithInvisibles,
+    line_number: Option<(gpui::Point<Pixels>, ShapedLine)>,
+    hitbox: Hitbox,
+}
+
 struct ColoredRange<T> {
     start: T,
     end: T,
@@ -8338,12 +9018,14 @@ mod tests {
     use super::*;
     use crate::{
         display_map::{BlockPlacement, BlockProperties},
+        editor_settings::{MinimapContent, StickyScrollContent},
         editor_tests::{init_test, update_test_language_settings},
         Editor, MultiBuffer,
     };
     use gpui::{TestAppContext, VisualTestContext};
-    use language::language_settings;
+    use language::{language_settings, Buffer, Language, LanguageConfig};
     use log::info;
+    use settings::SettingsStore;
     use similar::DiffableStr;
     use std::num::NonZeroU32;
     use util::test::sample_text;
@@ -8649,6 +9331,58 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_wrap_indicators(cx: &mut TestAppContext) {
+        init_test(cx, |s| {
+            s.defaults.soft_wrap = Some(language_settings::SoftWrap::PreferredLineLength);
+            s.defaults.preferred_line_length = Some(10);
+        });
+
+        for wrap_indicators in [false, true] {
+            cx.update(|cx| {
+                cx.update_global::<SettingsStore, _>(|settings, cx| {
+                    settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                        settings
+                            .gutter
+                            .get_or_insert_with(Default::default)
+                            .wrap_indicators = Some(wrap_indicators);
+                    });
+                });
+            });
+
+            let window = cx.add_window(|window, cx| {
+                let buffer = MultiBuffer::build_simple("aaaa bbbb cccc dddd eeee\nffff", cx);
+                Editor::new(EditorMode::Full, buffer, None, true, window, cx)
+            });
+            let cx = &mut VisualTestContext::from_window(*window, cx);
+            let editor = window.root(cx).unwrap();
+            let style = cx.update(|_, cx| editor.read(cx).style().unwrap().clone());
+
+            let (_, state) = cx.draw(
+                point(px(500.), px(500.)),
+                size(px(500.), px(500.)),
+                |_, _| EditorElement::new(&editor, style),
+            );
+            let display_rows = window
+                .update(cx, |editor, window, cx| {
+                    editor.snapshot(window, cx).max_point().row().0 + 1
+                })
+                .unwrap();
+            assert!(display_rows > 2, "the first line should be soft-wrapped");
+
+            if wrap_indicators {
+                // Every display row but the first of each buffer line is marked.
+                assert_eq!(state.wrap_indicators.len(), display_rows as usize - 2);
+                assert!(state
+                    .wrap_indicators
+                    .iter()
+                    .all(|(_, indicator)| indicator.text.as_str() == Some(WRAP_INDICATOR)));
+            } else {
+                assert!(state.wrap_indicators.is_empty());
+            }
+        }
+    }
+
     #[gpui::test]
     fn test_all_invisibles_drawing(cx: &mut TestAppContext) {
         const TAB_SIZE: u32 = 4;
@@ -8821,6 +9555,153 @@ mod tests {
         }
     }
 
+    #[gpui::test]
+    fn test_sticky_scroll_lines(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_outline_query(
+                r#"
+                (impl_item "impl" @context type: (_) @name) @item
+                (function_item "fn" @context name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        );
+        let text = format!(
+            "impl A {{\n    fn b() {{\n{}    }}\n}}\n",
+            "        c();\n".repeat(40)
+        );
+        let window = cx.add_window(|window, cx| {
+            let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(language, cx));
+            let buffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx));
+            Editor::new(EditorMode::Full, buffer, None, true, window, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        cx.run_until_parked();
+        let editor = window.root(cx).unwrap();
+
+        // Nothing is pinned while the top of the buffer is visible.
+        assert_eq!(sticky_scroll_rows(&editor, 0., cx), Vec::<u32>::new());
+
+        // Scrolling into the function's body pins the impl and the function.
+        assert_eq!(sticky_scroll_rows(&editor, 10., cx), [0, 1]);
+
+        cx.update(|_, cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.sticky_scroll = Some(StickyScrollContent {
+                        enabled: Some(true),
+                        max_depth: Some(1),
+                    });
+                });
+            });
+        });
+        assert_eq!(sticky_scroll_rows(&editor, 10., cx), [0]);
+
+        cx.update(|_, cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.sticky_scroll = Some(StickyScrollContent {
+                        enabled: Some(false),
+                        max_depth: None,
+                    });
+                });
+            });
+        });
+        assert_eq!(sticky_scroll_rows(&editor, 10., cx), Vec::<u32>::new());
+    }
+
+    #[gpui::test]
+    fn test_minimap_layout(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let set_minimap = |cx: &mut TestAppContext, max_columns| {
+            cx.update(|cx| {
+                cx.update_global::<SettingsStore, _>(|settings, cx| {
+                    settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                        settings.minimap = Some(MinimapContent {
+                            show: Some(true),
+                            max_columns: Some(max_columns),
+                        });
+                    });
+                });
+            });
+        };
+        set_minimap(cx, 100);
+
+        let window = cx.add_window(|window, cx| {
+            let buffer = MultiBuffer::build_simple(&"aa bb\ncc\n".repeat(500), cx);
+            Editor::new(EditorMode::Full, buffer, None, true, window, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|_, cx| editor.read(cx).style().unwrap().clone());
+
+        editor.update_in(cx, |editor, window, cx| {
+            editor.set_scroll_position(point(0., 100.), window, cx)
+        });
+        let (_, state) = cx.draw(
+            point(px(500.), px(500.)),
+            size(px(500.), px(500.)),
+            |_, _| EditorElement::new(&editor, style.clone()),
+        );
+        let minimap = state.minimap.expect("the minimap fits in the editor");
+        assert_eq!(minimap.hitbox.size.width, MINIMAP_COLUMN_WIDTH * 100.);
+        assert_eq!(minimap.hitbox.right(), state.hitbox.right());
+
+        // The buffer has more rows than fit, so the minimap scrolls along with the editor, and
+        // only the rows it shows are laid out, one block per run of text.
+        assert!(minimap.scroll_ratio > 0. && minimap.scroll_ratio < 1.);
+        let visible_rows = (minimap.hitbox.size.height / MINIMAP_ROW_HEIGHT) as usize;
+        assert!(minimap.quads.len() <= visible_rows / 2 * 3 + 3);
+        assert!(minimap.quads.iter().all(|quad| {
+            quad.bounds.size == size(MINIMAP_COLUMN_WIDTH * 2., MINIMAP_ROW_HEIGHT)
+        }));
+
+        // Dragging to where the thumb is centered keeps the editor where it is.
+        let scroll_position = minimap.scroll_position_for_y(minimap.thumb_bounds.center().y);
+        assert!((scroll_position - 100.).abs() < 0.01, "{scroll_position}");
+
+        // The minimap is hidden when it would take up too much of the editor.
+        set_minimap(cx, 200);
+        let (_, state) = cx.draw(
+            point(px(500.), px(500.)),
+            size(px(500.), px(500.)),
+            |_, _| EditorElement::new(&editor, style),
+        );
+        assert!(state.minimap.is_none());
+    }
+
+    /// Draws the editor scrolled to the given row, and returns the buffer rows of the lines that
+    /// are pinned to its top.
+    fn sticky_scroll_rows(
+        editor: &Entity<Editor>,
+        scroll_top: f32,
+        cx: &mut VisualTestContext,
+    ) -> Vec<u32> {
+        let style = cx.update(|_, cx| editor.read(cx).style().unwrap().clone());
+        editor.update_in(cx, |editor, window, cx| {
+            editor.set_scroll_position(point(0., scroll_top), window, cx)
+        });
+        let (_, state) = cx.draw(
+            point(px(500.), px(500.)),
+            size(px(500.), px(500.)),
+            |_, _| EditorElement::new(editor, style),
+        );
+        state
+            .sticky_scroll_lines
+            .iter()
+            .map(|line| line.buffer_point.row)
+            .collect()
+    }
+
     fn collect_invisibles_from_new_editor(
         cx: &mut TestAppContext,
         editor_mode: EditorMode,
@@ -8932,6 +9813,33 @@ fn diff_hunk_controls(
                     }
                 }),
         )
+        .child(
+            IconButton::new("stage", IconName::Check)
+                .shape(IconButtonShape::Square)
+                .icon_size(IconSize::Small)
+                .tooltip({
+                    let focus_handle = editor.focus_handle(cx);
+                    move |window, cx| {
+                        Tooltip::for_action_in(
+                            "Stage Hunk",
+                            &StageSelectedHunks,
+                            &focus_handle,
+                            window,
+                            cx,
+                        )
+                    }
+                })
+                .on_click({
+                    let editor = editor.clone();
+                    move |_event, window, cx| {
+                        editor.update(cx, |editor, cx| {
+                            let snapshot = editor.snapshot(window, cx);
+                            let point = hunk_range.start.to_point(&snapshot.buffer_snapshot);
+                            editor.stage_hunks_in_ranges([point..point].into_iter(), window, cx);
+                        });
+                    }
+                }),
+        )
         .child(
             IconButton::new("discard", IconName::Undo)
                 .shape(IconButtonShape::Square)
@@ -1267,6 +1267,7 @@ mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: true,
                 show_parameter_hints: true,
                 show_other_hints: true,
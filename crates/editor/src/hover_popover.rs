@@ -350,7 +350,7 @@ fn show_hover(
                                 underline: Some(gpui::UnderlineStyle {
                                     thickness: px(1.),
                                     color: Some(cx.theme().colors().editor_foreground),
-                                    wavy: false,
+                                    kind: gpui::UnderlineKind::Solid,
                                 }),
                                 ..Default::default()
                             },
@@ -592,7 +592,7 @@ async fn parse_blocks(
                     underline: Some(gpui::UnderlineStyle {
                         thickness: px(1.),
                         color: Some(cx.theme().colors().editor_foreground),
-                        wavy: false,
+                        kind: gpui::UnderlineKind::Solid,
                     }),
                     ..Default::default()
                 },
@@ -1475,6 +1475,7 @@ mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: true,
                 show_parameter_hints: true,
                 show_other_hints: true,
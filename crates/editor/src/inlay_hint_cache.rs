@@ -19,7 +19,7 @@ use crate::{
 use anyhow::Context as _;
 use clock::Global;
 use futures::future;
-use gpui::{AsyncApp, Context, Entity, Task, Window};
+use gpui::{AsyncApp, Context, Entity, Modifiers, Task, Window};
 use language::{language_settings::InlayHintKind, Buffer, BufferSnapshot};
 use parking_lot::RwLock;
 use project::{InlayHint, ResolveState};
@@ -37,6 +37,10 @@ pub struct InlayHintCache {
     version: usize,
     pub(super) enabled: bool,
     enabled_in_settings: bool,
+    pub(super) toggle_on_modifiers_press: Option<Modifiers>,
+    /// Whether hints were enabled before the `toggle_on_modifiers_press` modifiers were pressed,
+    /// while they're held.
+    pub(super) enabled_before_modifiers_press: Option<bool>,
     update_tasks: HashMap<ExcerptId, TasksForRanges>,
     refresh_task: Task<()>,
     invalidate_debounce: Option<Duration>,
@@ -266,6 +270,8 @@ impl InlayHintCache {
             allowed_hint_kinds: inlay_hint_settings.enabled_inlay_hint_kinds(),
             enabled: inlay_hint_settings.enabled,
             enabled_in_settings: inlay_hint_settings.enabled,
+            toggle_on_modifiers_press: inlay_hint_settings.toggle_on_modifiers_press,
+            enabled_before_modifiers_press: None,
             hints: HashMap::default(),
             update_tasks: HashMap::default(),
             refresh_task: Task::ready(()),
@@ -297,6 +303,7 @@ impl InlayHintCache {
             self.enabled = new_hint_settings.enabled;
         };
         self.enabled_in_settings = new_hint_settings.enabled;
+        self.toggle_on_modifiers_press = new_hint_settings.toggle_on_modifiers_press;
         self.invalidate_debounce = debounce_value(new_hint_settings.edit_debounce_ms);
         self.append_debounce = debounce_value(new_hint_settings.scroll_debounce_ms);
         let new_allowed_hint_kinds = new_hint_settings.enabled_inlay_hint_kinds();
@@ -1261,7 +1268,10 @@ fn apply_hint_update(
 pub mod tests {
     use crate::editor_tests::update_test_language_settings;
     use crate::scroll::ScrollAmount;
-    use crate::{scroll::Autoscroll, test::editor_lsp_test_context::rust_lang, ExcerptRange};
+    use crate::{
+        scroll::Autoscroll, test::editor_lsp_test_context::rust_lang, DisplayPoint, DisplayRow,
+        ExcerptRange, PointForPosition,
+    };
     use futures::StreamExt;
     use gpui::{AppContext as _, Context, SemanticVersion, TestAppContext, WindowHandle};
     use itertools::Itertools as _;
@@ -1286,6 +1296,7 @@ pub mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: allowed_hint_kinds.contains(&Some(InlayHintKind::Type)),
                 show_parameter_hints: allowed_hint_kinds.contains(&Some(InlayHintKind::Parameter)),
                 show_other_hints: allowed_hint_kinds.contains(&None),
@@ -1389,6 +1400,7 @@ pub mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: true,
                 show_parameter_hints: true,
                 show_other_hints: true,
@@ -1491,6 +1503,7 @@ pub mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: true,
                 show_parameter_hints: true,
                 show_other_hints: true,
@@ -1710,6 +1723,7 @@ pub mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: allowed_hint_kinds.contains(&Some(InlayHintKind::Type)),
                 show_parameter_hints: allowed_hint_kinds.contains(&Some(InlayHintKind::Parameter)),
                 show_other_hints: allowed_hint_kinds.contains(&None),
@@ -1868,6 +1882,7 @@ pub mod tests {
                     enabled: true,
                     edit_debounce_ms: 0,
                     scroll_debounce_ms: 0,
+                    toggle_on_modifiers_press: None,
                     show_type_hints: new_allowed_hint_kinds.contains(&Some(InlayHintKind::Type)),
                     show_parameter_hints: new_allowed_hint_kinds
                         .contains(&Some(InlayHintKind::Parameter)),
@@ -1910,6 +1925,7 @@ pub mod tests {
                 enabled: false,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: another_allowed_hint_kinds.contains(&Some(InlayHintKind::Type)),
                 show_parameter_hints: another_allowed_hint_kinds
                     .contains(&Some(InlayHintKind::Parameter)),
@@ -1964,6 +1980,7 @@ pub mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: final_allowed_hint_kinds.contains(&Some(InlayHintKind::Type)),
                 show_parameter_hints: final_allowed_hint_kinds
                     .contains(&Some(InlayHintKind::Parameter)),
@@ -2036,6 +2053,7 @@ pub mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: true,
                 show_parameter_hints: true,
                 show_other_hints: true,
@@ -2167,6 +2185,7 @@ pub mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: true,
                 show_parameter_hints: true,
                 show_other_hints: true,
@@ -2465,6 +2484,7 @@ pub mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: true,
                 show_parameter_hints: true,
                 show_other_hints: true,
@@ -2809,6 +2829,7 @@ pub mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: false,
                 show_parameter_hints: false,
                 show_other_hints: false,
@@ -2990,6 +3011,7 @@ pub mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: true,
                 show_parameter_hints: true,
                 show_other_hints: true,
@@ -3021,6 +3043,7 @@ pub mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: true,
                 show_parameter_hints: true,
                 show_other_hints: true,
@@ -3112,6 +3135,7 @@ pub mod tests {
                 enabled: false,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: true,
                 show_parameter_hints: true,
                 show_other_hints: true,
@@ -3185,6 +3209,7 @@ pub mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: true,
                 show_parameter_hints: true,
                 show_other_hints: true,
@@ -3244,6 +3269,7 @@ pub mod tests {
                 enabled: true,
                 edit_debounce_ms: 0,
                 scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: None,
                 show_type_hints: true,
                 show_parameter_hints: true,
                 show_other_hints: true,
@@ -3389,6 +3415,76 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_modifiers_toggle_and_hint_text_edits(cx: &mut gpui::TestAppContext) {
+        let toggle_modifiers = Modifiers {
+            control: true,
+            alt: true,
+            ..Modifiers::default()
+        };
+        init_test(cx, |settings| {
+            settings.defaults.inlay_hints = Some(InlayHintSettings {
+                enabled: true,
+                edit_debounce_ms: 0,
+                scroll_debounce_ms: 0,
+                toggle_on_modifiers_press: Some(toggle_modifiers),
+                show_type_hints: true,
+                show_parameter_hints: true,
+                show_other_hints: true,
+                show_background: false,
+            })
+        });
+        let (_, editor, _fake_server) = prepare_test_objects(cx, |fake_server, _| {
+            fake_server.handle_request::<lsp::request::InlayHintRequest, _, _>(
+                move |_, _| async move {
+                    Ok(Some(vec![lsp::InlayHint {
+                        position: lsp::Position::new(0, 13),
+                        label: lsp::InlayHintLabel::String(": i32".to_string()),
+                        kind: Some(lsp::InlayHintKind::TYPE),
+                        text_edits: Some(vec![lsp::TextEdit {
+                            range: lsp::Range::new(
+                                lsp::Position::new(0, 13),
+                                lsp::Position::new(0, 13),
+                            ),
+                            new_text: ": i32".to_string(),
+                        }]),
+                        tooltip: None,
+                        padding_left: None,
+                        padding_right: None,
+                        data: None,
+                    }]))
+                },
+            );
+        })
+        .await;
+        cx.executor().run_until_parked();
+
+        editor
+            .update(cx, |editor, _, cx| {
+                editor.update_inlay_hints_for_modifiers(&toggle_modifiers, cx);
+                assert!(!editor.inlay_hints_enabled());
+                assert!(visible_hint_labels(editor, cx).is_empty());
+                editor.update_inlay_hints_for_modifiers(&Modifiers::default(), cx);
+                assert!(editor.inlay_hints_enabled());
+            })
+            .unwrap();
+        cx.executor().run_until_parked();
+
+        editor
+            .update(cx, |editor, window, cx| {
+                assert_eq!(visible_hint_labels(editor, cx), [": i32"]);
+                let point_for_position = PointForPosition {
+                    previous_valid: DisplayPoint::new(DisplayRow(0), 13),
+                    next_valid: DisplayPoint::new(DisplayRow(0), 13),
+                    exact_unclipped: DisplayPoint::new(DisplayRow(0), 15),
+                    column_overshoot_after_line_end: 0,
+                };
+                assert!(editor.apply_inlay_hint_text_edits(&point_for_position, window, cx));
+                assert!(editor.text(cx).starts_with("fn main() { a: i32 }"));
+            })
+            .unwrap();
+    }
+
     pub(crate) fn init_test(cx: &mut TestAppContext, f: impl Fn(&mut AllLanguageSettingsContent)) {
         cx.update(|cx| {
             let settings_store = SettingsStore::test(cx);
@@ -2,8 +2,9 @@ use crate::{
     editor_settings::SeedQuerySetting,
     persistence::{SerializedEditor, DB},
     scroll::ScrollAnchor,
-    Anchor, Autoscroll, Editor, EditorEvent, EditorSettings, ExcerptId, ExcerptRange, FormatTarget,
-    MultiBuffer, MultiBufferSnapshot, NavigationData, SearchWithinRange, ToPoint as _,
+    Anchor, Autoscroll, Editor, EditorEvent, EditorMode, EditorSettings, ExcerptId, ExcerptRange,
+    FormatTarget, MultiBuffer, MultiBufferSnapshot, NavigationData, SearchWithinRange,
+    ToPoint as _,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::HashSet;
@@ -20,8 +21,10 @@ use language::{
 };
 use lsp::DiagnosticSeverity;
 use project::{
-    lsp_store::FormatTrigger, project_settings::ProjectSettings, search::SearchQuery, Project,
-    ProjectItem as _, ProjectPath,
+    lsp_store::FormatTrigger,
+    project_settings::{is_large_buffer, ProjectSettings},
+    search::SearchQuery,
+    Project, ProjectItem as _, ProjectPath,
 };
 use rpc::proto::{self, update_view, PeerId};
 use settings::Settings;
@@ -45,6 +48,7 @@ use workspace::{
 };
 use workspace::{
     item::{FollowableItem, Item, ItemEvent, ProjectItem},
+    notifications::{simple_message_notification::MessageNotification, NotificationId},
     searchable::{Direction, SearchEvent, SearchableItem, SearchableItemHandle},
     ItemId, ItemNavHistory, ToolbarItemLocation, ViewId, Workspace, WorkspaceId,
 };
@@ -941,9 +945,10 @@ impl Item for Editor {
         &mut self,
         workspace: &mut Workspace,
         _window: &mut Window,
-        _: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) {
         self.workspace = Some((workspace.weak_handle(), workspace.database_id()));
+        self.notify_if_large_file(workspace, cx);
     }
 
     fn to_item_events(event: &EditorEvent, mut f: impl FnMut(ItemEvent)) {
@@ -985,6 +990,58 @@ impl Item for Editor {
     }
 }
 
+struct LargeFileNotification;
+
+impl Editor {
+    /// Lets the user know why a large file opened without language features,
+    /// and offers to turn them back on.
+    fn notify_if_large_file(&self, workspace: &Workspace, cx: &mut Context<Self>) {
+        if self.mode != EditorMode::Full {
+            return;
+        }
+        let Some((buffer, project)) = self
+            .buffer
+            .read(cx)
+            .as_singleton()
+            .zip(self.project.clone())
+        else {
+            return;
+        };
+        if !is_large_buffer(buffer.read(cx), cx) || buffer.read(cx).language().is_some() {
+            return;
+        }
+
+        let workspace = workspace.weak_handle();
+        cx.defer(move |cx| {
+            workspace
+                .update(cx, |workspace, cx| {
+                    workspace.show_notification(
+                        NotificationId::composite::<LargeFileNotification>((
+                            "large-file",
+                            buffer.entity_id(),
+                        )),
+                        cx,
+                        |cx| {
+                            cx.new(|_| {
+                                MessageNotification::new(
+                                    "This file is large, so syntax highlighting, language servers \
+                                     and soft wrap are turned off.",
+                                )
+                                .primary_message("Enable Language Features")
+                                .primary_on_click(move |_, cx| {
+                                    project.update(cx, |project, cx| {
+                                        project.enable_language_for_large_buffer(&buffer, cx)
+                                    });
+                                })
+                            })
+                        },
+                    );
+                })
+                .ok();
+        });
+    }
+}
+
 impl SerializableItem for Editor {
     fn serialized_item_kind() -> &'static str {
         "Editor"
@@ -1075,7 +1132,7 @@ impl SerializableItem for Editor {
                         cx.new(|cx| {
                             let mut editor = Editor::for_buffer(buffer, Some(project), window, cx);
 
-                            editor.read_scroll_position_from_db(item_id, workspace_id, window, cx);
+                            editor.read_metadata_from_db(item_id, workspace_id, window, cx);
                             editor
                         })
                     })
@@ -1130,12 +1187,7 @@ impl SerializableItem for Editor {
                                     let mut editor =
                                         Editor::for_buffer(buffer, Some(project), window, cx);
 
-                                    editor.read_scroll_position_from_db(
-                                        item_id,
-                                        workspace_id,
-                                        window,
-                                        cx,
-                                    );
+                                    editor.read_metadata_from_db(item_id, workspace_id, window, cx);
                                     editor
                                 })
                             })
@@ -1148,7 +1200,7 @@ impl SerializableItem for Editor {
                         window.spawn(cx, |mut cx| async move {
                             let editor = open_by_abs_path?.await?.downcast::<Editor>().with_context(|| format!("Failed to downcast to Editor after opening abs path {abs_path:?}"))?;
                             editor.update_in(&mut cx, |editor, window, cx| {
-                                editor.read_scroll_position_from_db(item_id, workspace_id, window, cx);
+                                editor.read_metadata_from_db(item_id, workspace_id, window, cx);
                             })?;
                             Ok(editor)
                         })
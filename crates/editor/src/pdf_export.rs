@@ -0,0 +1,485 @@
+use crate::{
+    actions::{ExportToPdf, PdfPageSize},
+    Editor,
+};
+use gpui::{FontStyle, FontWeight, Hsla, NewPathPromptOptions, PathPromptFilter, Rgba};
+use std::{fmt::Write as _, path::PathBuf};
+use ui::prelude::*;
+use workspace::notifications::NotifyTaskExt as _;
+
+const FONT_SIZE: f32 = 9.;
+const LINE_HEIGHT: f32 = 11.;
+const MARGIN: f32 = 36.;
+/// Every Courier glyph is 600 units wide, out of 1000 per em.
+const GLYPH_WIDTH: f32 = FONT_SIZE * 0.6;
+
+impl PdfPageSize {
+    /// The page's width and height in points.
+    fn dimensions(self) -> (f32, f32) {
+        match self {
+            PdfPageSize::Letter => (612., 792.),
+            PdfPageSize::A4 => (595., 842.),
+        }
+    }
+}
+
+/// The standard Courier fonts, which every PDF reader provides.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PdfFont {
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic,
+}
+
+impl PdfFont {
+    const ALL: [PdfFont; 4] = [
+        PdfFont::Regular,
+        PdfFont::Bold,
+        PdfFont::Italic,
+        PdfFont::BoldItalic,
+    ];
+
+    fn base_font(self) -> &'static str {
+        match self {
+            PdfFont::Regular => "Courier",
+            PdfFont::Bold => "Courier-Bold",
+            PdfFont::Italic => "Courier-Oblique",
+            PdfFont::BoldItalic => "Courier-BoldOblique",
+        }
+    }
+
+    fn resource_name(self) -> &'static str {
+        match self {
+            PdfFont::Regular => "F1",
+            PdfFont::Bold => "F2",
+            PdfFont::Italic => "F3",
+            PdfFont::BoldItalic => "F4",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PdfRun {
+    text: String,
+    color: Rgba,
+    font: PdfFont,
+}
+
+/// A buffer's highlighted lines, laid out on pages with the buffer's title in
+/// the header and page numbers in the footer.
+struct PdfDocument {
+    title: String,
+    lines: Vec<Vec<PdfRun>>,
+    page_size: PdfPageSize,
+    background: Rgba,
+    text_color: Rgba,
+    line_number_color: Rgba,
+}
+
+impl PdfDocument {
+    /// Encodes the document as a PDF file. Lines longer than the page is wide
+    /// are wrapped, and characters outside of Latin-1 are replaced with `?`,
+    /// since the standard fonts can't show them.
+    fn render(&self) -> String {
+        let (width, height) = self.page_size.dimensions();
+        let line_number_columns = self.lines.len().max(1).to_string().len() + 1;
+        let columns = (((width - 2. * MARGIN) / GLYPH_WIDTH) as usize)
+            .saturating_sub(line_number_columns)
+            .max(1);
+        let body_top = height - MARGIN - 2. * LINE_HEIGHT;
+        let body_bottom = MARGIN + 2. * LINE_HEIGHT;
+        let rows_per_page = ((body_top - body_bottom) / LINE_HEIGHT) as usize + 1;
+
+        let rows = wrap_lines(&self.lines, columns);
+        let pages = rows.chunks(rows_per_page).collect::<Vec<_>>();
+        let page_count = pages.len().max(1);
+
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            String::new(),
+        ];
+        let mut font_resources = String::new();
+        for font in PdfFont::ALL {
+            objects.push(format!(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                font.base_font()
+            ));
+            write!(
+                font_resources,
+                "/{} {} 0 R ",
+                font.resource_name(),
+                objects.len()
+            )
+            .unwrap();
+        }
+
+        let mut page_ids = Vec::new();
+        for page_ix in 0..page_count {
+            let mut content = String::new();
+            writeln!(
+                content,
+                "{} 0 0 {width} {height} re f",
+                fill_color(self.background)
+            )
+            .unwrap();
+            content.push_str("BT\n");
+            write_text(
+                &mut content,
+                MARGIN,
+                height - MARGIN,
+                PdfFont::Bold,
+                self.text_color,
+                &self.title,
+            );
+
+            let rows = pages.get(page_ix).copied().unwrap_or_default();
+            for (row_ix, (line_number, runs)) in rows.iter().enumerate() {
+                let y = body_top - row_ix as f32 * LINE_HEIGHT;
+                if let Some(line_number) = line_number {
+                    write_text(
+                        &mut content,
+                        MARGIN,
+                        y,
+                        PdfFont::Regular,
+                        self.line_number_color,
+                        &format!(
+                            "{:>width$}",
+                            line_number + 1,
+                            width = line_number_columns - 1
+                        ),
+                    );
+                }
+                writeln!(
+                    content,
+                    "1 0 0 1 {} {y} Tm",
+                    MARGIN + line_number_columns as f32 * GLYPH_WIDTH
+                )
+                .unwrap();
+                for run in runs {
+                    writeln!(
+                        content,
+                        "/{} {FONT_SIZE} Tf {} ({}) Tj",
+                        run.font.resource_name(),
+                        fill_color(run.color),
+                        escape_text(&run.text)
+                    )
+                    .unwrap();
+                }
+            }
+
+            let footer = format!("Page {} of {}", page_ix + 1, page_count);
+            write_text(
+                &mut content,
+                width - MARGIN - footer.len() as f32 * GLYPH_WIDTH,
+                MARGIN,
+                PdfFont::Regular,
+                self.line_number_color,
+                &footer,
+            );
+            content.push_str("ET\n");
+
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{content}\nendstream",
+                content.len()
+            ));
+            let content_id = objects.len();
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {width} {height}] \
+                 /Resources << /Font << {font_resources}>> >> /Contents {content_id} 0 R >>"
+            ));
+            page_ids.push(objects.len());
+        }
+
+        objects[1] = format!(
+            "<< /Type /Pages /Kids [{}] /Count {page_count} >>",
+            page_ids
+                .iter()
+                .map(|id| format!("{id} 0 R"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::new();
+        for (ix, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            writeln!(pdf, "{} 0 obj\n{object}\nendobj", ix + 1).unwrap();
+        }
+        let xref_offset = pdf.len();
+        writeln!(pdf, "xref\n0 {}", objects.len() + 1).unwrap();
+        pdf.push_str("0000000000 65535 f \n");
+        for offset in offsets {
+            writeln!(pdf, "{offset:010} 00000 n ").unwrap();
+        }
+        writeln!(
+            pdf,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref_offset}\n%%EOF",
+            objects.len() + 1
+        )
+        .unwrap();
+        pdf
+    }
+}
+
+/// Splits lines into rows of at most `columns` characters, pairing each row
+/// with the number of the line it starts, if any.
+fn wrap_lines(lines: &[Vec<PdfRun>], columns: usize) -> Vec<(Option<usize>, Vec<PdfRun>)> {
+    let mut rows = Vec::new();
+    for (line_number, runs) in lines.iter().enumerate() {
+        let mut row = (Some(line_number), Vec::<PdfRun>::new());
+        let mut column = 0;
+        for run in runs {
+            for character in run.text.chars() {
+                if column == columns {
+                    rows.push(std::mem::replace(&mut row, (None, Vec::new())));
+                    column = 0;
+                }
+                match row.1.last_mut() {
+                    Some(last) if last.color == run.color && last.font == run.font => {
+                        last.text.push(character)
+                    }
+                    _ => row.1.push(PdfRun {
+                        text: character.to_string(),
+                        ..run.clone()
+                    }),
+                }
+                column += 1;
+            }
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+fn write_text(content: &mut String, x: f32, y: f32, font: PdfFont, color: Rgba, text: &str) {
+    writeln!(
+        content,
+        "1 0 0 1 {x} {y} Tm /{} {FONT_SIZE} Tf {} ({}) Tj",
+        font.resource_name(),
+        fill_color(color),
+        escape_text(text)
+    )
+    .unwrap();
+}
+
+fn fill_color(color: Rgba) -> String {
+    format!("{:.3} {:.3} {:.3} rg", color.r, color.g, color.b)
+}
+
+/// Escapes text for a PDF string literal. Non-ASCII Latin-1 characters are
+/// written as octal escapes, so the file stays ASCII.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            ' '..='~' => escaped.push(character),
+            '\u{a0}'..='\u{ff}' => write!(escaped, "\\{:03o}", character as u32).unwrap(),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+impl Editor {
+    /// Saves the buffer, with its syntax highlighting, to a PDF file chosen by
+    /// the user.
+    pub fn export_to_pdf(
+        &mut self,
+        action: &ExportToPdf,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some(style) = self.style.as_ref() else {
+            return;
+        };
+
+        let background = Rgba::from(style.background);
+        let opaque = |color: Hsla| background.blend(color.into());
+        let text_color = opaque(style.text.color);
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let tab_size = snapshot.settings_at(0, cx).tab_size.get() as usize;
+
+        let mut lines = vec![Vec::new()];
+        let mut column = 0;
+        for chunk in snapshot.chunks(0..snapshot.len(), true) {
+            let highlight = chunk
+                .syntax_highlight_id
+                .and_then(|id| id.style(&style.syntax))
+                .unwrap_or_default();
+            let bold = highlight
+                .font_weight
+                .is_some_and(|weight| weight >= FontWeight::BOLD);
+            let italic = highlight.font_style == Some(FontStyle::Italic);
+            let font = match (bold, italic) {
+                (false, false) => PdfFont::Regular,
+                (true, false) => PdfFont::Bold,
+                (false, true) => PdfFont::Italic,
+                (true, true) => PdfFont::BoldItalic,
+            };
+            let color = highlight.color.map_or(text_color, opaque);
+
+            let mut chunk_lines = chunk.text.split('\n').peekable();
+            while let Some(text) = chunk_lines.next() {
+                let mut expanded = String::with_capacity(text.len());
+                for character in text.chars() {
+                    if character == '\t' {
+                        let spaces = tab_size - column % tab_size;
+                        expanded.extend(std::iter::repeat(' ').take(spaces));
+                        column += spaces;
+                    } else {
+                        expanded.push(character);
+                        column += 1;
+                    }
+                }
+                if !expanded.is_empty() {
+                    lines.last_mut().unwrap().push(PdfRun {
+                        text: expanded,
+                        color,
+                        font,
+                    });
+                }
+                if chunk_lines.peek().is_some() {
+                    lines.push(Vec::new());
+                    column = 0;
+                }
+            }
+        }
+
+        let title = self.buffer.read(cx).title(cx).to_string();
+        let document = PdfDocument {
+            title: title.clone(),
+            lines,
+            page_size: action.page_size,
+            background,
+            text_color,
+            line_number_color: opaque(cx.theme().colors().editor_line_number),
+        };
+
+        let directory = self
+            .target_file_abs_path(cx)
+            .and_then(|path| Some(path.parent()?.to_path_buf()))
+            .unwrap_or_else(PathBuf::new);
+        let abs_path = cx.prompt_for_new_path_with_options(NewPathPromptOptions {
+            suggested_name: Some(format!("{title}.pdf").into()),
+            filters: vec![PathPromptFilter::new("PDF", ["pdf"])],
+            ..NewPathPromptOptions::new(directory)
+        });
+        let fs = project.read(cx).fs().clone();
+        cx.spawn_in(window, |_, cx| async move {
+            let Some(abs_path) = abs_path.await?? else {
+                return Ok(());
+            };
+            let pdf = cx
+                .background_executor()
+                .spawn(async move { document.render() })
+                .await;
+            fs.atomic_write(abs_path, pdf).await
+        })
+        .detach_and_notify_err(window, cx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(lines: &[&str], page_size: PdfPageSize) -> PdfDocument {
+        let black = Rgba {
+            r: 0.,
+            g: 0.,
+            b: 0.,
+            a: 1.,
+        };
+        PdfDocument {
+            title: "main.rs".into(),
+            lines: lines
+                .iter()
+                .map(|line| {
+                    vec![PdfRun {
+                        text: line.to_string(),
+                        color: black,
+                        font: PdfFont::Regular,
+                    }]
+                })
+                .collect(),
+            page_size,
+            background: Rgba {
+                r: 1.,
+                g: 1.,
+                b: 1.,
+                a: 1.,
+            },
+            text_color: black,
+            line_number_color: black,
+        }
+    }
+
+    #[test]
+    fn test_pdf_pagination() {
+        let pdf = document(&["fn main() {}"], PdfPageSize::Letter).render();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/Count 1"));
+        assert!(pdf.contains("/MediaBox [0 0 612 792]"));
+        assert!(pdf.contains("(main.rs) Tj"));
+        assert!(pdf.contains("(fn main\\(\\) {}) Tj"));
+        assert!(pdf.contains("(Page 1 of 1) Tj"));
+
+        // A4 pages are taller, so they fit more rows than Letter pages.
+        let lines = vec!["x"; 130];
+        let pdf = document(&lines, PdfPageSize::Letter).render();
+        assert!(pdf.contains("/Count 3"));
+        assert!(pdf.contains("(Page 3 of 3) Tj"));
+        let pdf = document(&lines, PdfPageSize::A4).render();
+        assert!(pdf.contains("/MediaBox [0 0 595 842]"));
+        assert!(pdf.contains("/Count 2"));
+
+        // The cross-reference table points at each object.
+        let xref_offset = pdf
+            .lines()
+            .rev()
+            .nth(1)
+            .and_then(|line| line.parse::<usize>().ok())
+            .unwrap();
+        assert!(pdf[xref_offset..].starts_with("xref\n"));
+        let first_object_offset = pdf[xref_offset..].lines().nth(3).unwrap()[..10]
+            .parse::<usize>()
+            .unwrap();
+        assert!(pdf[first_object_offset..].starts_with("1 0 obj\n"));
+    }
+
+    #[test]
+    fn test_pdf_line_wrapping() {
+        let long_line = "a".repeat(250);
+        let rows = wrap_lines(
+            &document(&[long_line.as_str(), "b"], PdfPageSize::Letter).lines,
+            100,
+        );
+        let rows = rows
+            .iter()
+            .map(|(line_number, runs)| {
+                let text = runs.iter().map(|run| run.text.as_str()).collect::<String>();
+                (*line_number, text.len())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [(Some(0), 100), (None, 100), (None, 50), (Some(1), 1)]
+        );
+    }
+
+    #[test]
+    fn test_pdf_text_escaping() {
+        assert_eq!(escape_text("a(b)\\c"), "a\\(b\\)\\\\c");
+        assert_eq!(escape_text("café"), "caf\\351");
+        assert_eq!(escape_text("→"), "?");
+    }
+}
@@ -87,6 +87,19 @@ define_connection!(
     //   mtime_seconds: Option<i64>,
     //   mtime_nanos: Option<i32>,
     // )
+    //
+    // editor_folds(
+    //   item_id: usize,
+    //   workspace_id: usize,
+    //   start_offset: usize,
+    //   end_offset: usize,
+    // )
+    //
+    // editor_bookmarks(
+    //   workspace_id: usize,
+    //   path: PathBuf,
+    //   buffer_row: u32,
+    // )
     pub static ref DB: EditorDb<WorkspaceDb> = &[
         sql! (
             CREATE TABLE editors(
@@ -134,6 +147,28 @@ define_connection!(
             ALTER TABLE editors ADD COLUMN mtime_seconds INTEGER DEFAULT NULL;
             ALTER TABLE editors ADD COLUMN mtime_nanos INTEGER DEFAULT NULL;
         ),
+        sql! (
+            CREATE TABLE editor_folds (
+                item_id INTEGER NOT NULL,
+                workspace_id INTEGER NOT NULL,
+                start_offset INTEGER NOT NULL,
+                end_offset INTEGER NOT NULL,
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        ),
+        sql! (
+            CREATE TABLE editor_bookmarks (
+                workspace_id INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                buffer_row INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, path, buffer_row),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        ),
     ];
 );
 
@@ -188,6 +223,72 @@ impl EditorDb {
         }
     }
 
+    // Returns the buffer offset ranges of the folds, ordered by their start
+    query! {
+        pub fn get_editor_folds(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Vec<(usize, usize)>> {
+            SELECT start_offset, end_offset
+            FROM editor_folds
+            WHERE item_id = ? AND workspace_id = ?
+            ORDER BY start_offset
+        }
+    }
+
+    pub async fn save_editor_folds(
+        &self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        folds: Vec<(usize, usize)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_editor_folds", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM editor_folds WHERE item_id = ? AND workspace_id = ?
+                ))?((item_id, workspace_id))?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT INTO editor_folds (item_id, workspace_id, start_offset, end_offset)
+                    VALUES (?, ?, ?, ?)
+                ))?;
+                for (start, end) in folds {
+                    insert((item_id, workspace_id, start, end))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub fn get_bookmarks(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, u32)>> {
+            SELECT path, buffer_row
+            FROM editor_bookmarks
+            WHERE workspace_id = ?
+            ORDER BY path, buffer_row
+        }
+    }
+
+    pub async fn save_bookmarks(
+        &self,
+        workspace_id: WorkspaceId,
+        bookmarks: Vec<(PathBuf, u32)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_bookmarks", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM editor_bookmarks WHERE workspace_id = ?
+                ))?(workspace_id)?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT INTO editor_bookmarks (workspace_id, path, buffer_row)
+                    VALUES (?, ?, ?)
+                ))?;
+                for (path, row) in bookmarks {
+                    insert((workspace_id, path, row))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
     pub async fn delete_unloaded_items(
         &self,
         workspace: WorkspaceId,
@@ -199,17 +300,19 @@ impl EditorDb {
             .collect::<Vec<&str>>()
             .join(", ");
 
-        let query = format!(
-            "DELETE FROM editors WHERE workspace_id = ? AND item_id NOT IN ({placeholders})"
-        );
-
         self.write(move |conn| {
-            let mut statement = Statement::prepare(conn, query)?;
-            let mut next_index = statement.bind(&workspace, 1)?;
-            for id in alive_items {
-                next_index = statement.bind(&id, next_index)?;
+            for table in ["editors", "editor_folds"] {
+                let query = format!(
+                    "DELETE FROM {table} WHERE workspace_id = ? AND item_id NOT IN ({placeholders})"
+                );
+                let mut statement = Statement::prepare(conn, query)?;
+                let mut next_index = statement.bind(&workspace, 1)?;
+                for id in &alive_items {
+                    next_index = statement.bind(id, next_index)?;
+                }
+                statement.exec()?;
             }
-            statement.exec()
+            Ok(())
         })
         .await
     }
@@ -294,4 +397,53 @@ mod tests {
             .unwrap();
         assert_eq!(have, serialized_editor);
     }
+
+    #[gpui::test]
+    async fn test_save_and_get_editor_folds() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+
+        DB.save_editor_folds(1234, workspace_id, vec![(20, 30), (5, 10)])
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_editor_folds(1234, workspace_id).unwrap(),
+            [(5, 10), (20, 30)]
+        );
+
+        // Saving replaces the previous folds
+        DB.save_editor_folds(1234, workspace_id, vec![(0, 3)])
+            .await
+            .unwrap();
+        assert_eq!(DB.get_editor_folds(1234, workspace_id).unwrap(), [(0, 3)]);
+
+        DB.delete_unloaded_items(workspace_id, vec![5678])
+            .await
+            .unwrap();
+        assert!(DB.get_editor_folds(1234, workspace_id).unwrap().is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_save_and_get_bookmarks() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+
+        DB.save_bookmarks(
+            workspace_id,
+            vec![(PathBuf::from("/b.rs"), 3), (PathBuf::from("/a.rs"), 7)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            DB.get_bookmarks(workspace_id).unwrap(),
+            [(PathBuf::from("/a.rs"), 7), (PathBuf::from("/b.rs"), 3)]
+        );
+
+        // Saving replaces the previous bookmarks
+        DB.save_bookmarks(workspace_id, vec![(PathBuf::from("/a.rs"), 1)])
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_bookmarks(workspace_id).unwrap(),
+            [(PathBuf::from("/a.rs"), 1)]
+        );
+    }
 }
@@ -0,0 +1,228 @@
+use crate::{Editor, EditorMode, EditorSettings};
+use gpui::{Context, HighlightStyle};
+use multi_buffer::{Anchor, MultiBufferSnapshot, ToPoint as _};
+use settings::Settings as _;
+use std::ops::Range;
+use text::{Bias, Point};
+use theme::ActiveTheme as _;
+
+enum RainbowBracketHighlight {}
+
+/// Bracket pairs from the brackets query that get colored. Others, like
+/// quotes, don't read as nesting and keep their syntax color.
+const RAINBOW_BRACKETS: [char; 4] = ['(', '[', '{', '<'];
+
+/// The part of the buffer that bracket colors were last computed for.
+#[derive(Debug)]
+pub(crate) struct RainbowBracketsState {
+    edit_count: usize,
+    non_text_state_update_count: usize,
+    range: Range<usize>,
+}
+
+/// Colors brackets by how deeply they're nested. Colors are computed a page
+/// above and below the visible rows, and only recomputed when the buffer
+/// changes or the editor scrolls past them.
+pub(crate) fn refresh_rainbow_brackets(editor: &mut Editor, cx: &mut Context<Editor>) {
+    let settings = EditorSettings::get_global(cx).rainbow_brackets;
+    if !settings.enabled || editor.mode != EditorMode::Full {
+        if editor.rainbow_brackets.take().is_some() {
+            editor.clear_highlights::<RainbowBracketHighlight>(cx);
+        }
+        return;
+    }
+
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let visible_start = editor.scroll_manager.anchor().anchor.to_point(&snapshot);
+    let visible_rows = editor.visible_line_count().unwrap_or(0.).ceil() as u32;
+    let visible_end =
+        snapshot.clip_point(Point::new(visible_start.row + visible_rows, 0), Bias::Left);
+    let visible_range =
+        snapshot.point_to_offset(visible_start)..snapshot.point_to_offset(visible_end);
+    if let Some(state) = &editor.rainbow_brackets {
+        if state.edit_count == snapshot.edit_count()
+            && state.non_text_state_update_count == snapshot.non_text_state_update_count()
+            && state.range.start <= visible_range.start
+            && visible_range.end <= state.range.end
+        {
+            return;
+        }
+    }
+
+    let start = Point::new(visible_start.row.saturating_sub(visible_rows), 0);
+    let end = snapshot.clip_point(
+        Point::new(visible_start.row + 2 * visible_rows, 0),
+        Bias::Left,
+    );
+    let range = snapshot.point_to_offset(start)..snapshot.point_to_offset(end);
+    let brackets_by_depth = brackets_by_depth(&snapshot, range.clone(), settings.max_depth);
+
+    let accents = cx.theme().accents().clone();
+    editor.clear_highlights::<RainbowBracketHighlight>(cx);
+    for (depth, ranges) in brackets_by_depth.into_iter().enumerate() {
+        editor.highlight_text_key::<RainbowBracketHighlight>(
+            depth,
+            ranges,
+            HighlightStyle {
+                color: Some(accents.color_for_index(depth as u32)),
+                ..Default::default()
+            },
+            cx,
+        );
+    }
+    editor.rainbow_brackets = Some(RainbowBracketsState {
+        edit_count: snapshot.edit_count(),
+        non_text_state_update_count: snapshot.non_text_state_update_count(),
+        range,
+    });
+}
+
+/// Returns the brackets within `range`, grouped by their nesting depth and
+/// sorted within each depth. Pairs enclosing the range still count towards
+/// the depth of the brackets inside it.
+fn brackets_by_depth(
+    snapshot: &MultiBufferSnapshot,
+    range: Range<usize>,
+    max_depth: usize,
+) -> Vec<Vec<Range<Anchor>>> {
+    let mut brackets_by_depth: Vec<Vec<Range<Anchor>>> = Vec::new();
+    for (buffer, buffer_range, excerpt_id) in snapshot.range_to_buffer_ranges(range) {
+        let mut pairs = buffer
+            .bracket_ranges(buffer_range.clone())
+            .filter(|(open, _)| {
+                buffer
+                    .chars_at(open.start)
+                    .next()
+                    .map_or(false, |c| RAINBOW_BRACKETS.contains(&c))
+            })
+            .collect::<Vec<_>>();
+        pairs.sort_by_key(|(open, close)| (open.start, std::cmp::Reverse(close.end)));
+        pairs.dedup();
+
+        let mut enclosing_ends = Vec::new();
+        for (open, close) in pairs {
+            while enclosing_ends
+                .last()
+                .map_or(false, |end| *end <= open.start)
+            {
+                enclosing_ends.pop();
+            }
+            let depth = enclosing_ends.len();
+            enclosing_ends.push(close.end);
+            if depth >= max_depth {
+                continue;
+            }
+
+            for bracket in [open, close] {
+                if bracket.start < buffer_range.start || bracket.end > buffer_range.end {
+                    continue;
+                }
+                let (Some(start), Some(end)) = (
+                    snapshot.anchor_in_excerpt(excerpt_id, buffer.anchor_after(bracket.start)),
+                    snapshot.anchor_in_excerpt(excerpt_id, buffer.anchor_before(bracket.end)),
+                ) else {
+                    continue;
+                };
+                if brackets_by_depth.len() <= depth {
+                    brackets_by_depth.resize_with(depth + 1, Vec::new);
+                }
+                brackets_by_depth[depth].push(start..end);
+            }
+        }
+    }
+
+    for ranges in &mut brackets_by_depth {
+        ranges.sort_by(|a, b| a.start.cmp(&b.start, snapshot));
+    }
+    brackets_by_depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_lsp_test_context::EditorLspTestContext};
+    use indoc::indoc;
+    use language::{Language, LanguageConfig, LanguageMatcher};
+    use multi_buffer::ToOffset as _;
+    use util::test::marked_text_ranges;
+
+    #[gpui::test]
+    async fn test_brackets_by_depth(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_brackets_query(indoc! {r#"
+                ("{" @open "}" @close)
+                ("(" @open ")" @close)
+                ("\"" @open "\"" @close)
+                "#})
+            .unwrap(),
+            Default::default(),
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {r#"
+            fn main() {
+                let a = (1, (2, "three"));ˇ
+            }
+        "#});
+        let expected = [
+            indoc! {r#"
+                fn main«(»«)» «{»
+                    let a = (1, (2, "three"));
+                «}»
+            "#},
+            indoc! {r#"
+                fn main() {
+                    let a = «(»1, (2, "three")«)»;
+                }
+            "#},
+            indoc! {r#"
+                fn main() {
+                    let a = (1, «(»2, "three"«)»);
+                }
+            "#},
+        ];
+
+        cx.update_editor(|editor, _, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let text = snapshot.text();
+            let bracket_offsets = |max_depth| {
+                brackets_by_depth(&snapshot, 0..snapshot.len(), max_depth)
+                    .into_iter()
+                    .map(|ranges| {
+                        ranges
+                            .into_iter()
+                            .map(|range| {
+                                range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            let expected_offsets = expected
+                .iter()
+                .map(|marked_text| {
+                    let (unmarked_text, ranges) = marked_text_ranges(marked_text, false);
+                    assert_eq!(unmarked_text, text);
+                    ranges
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(bracket_offsets(10), expected_offsets);
+            assert_eq!(bracket_offsets(2), expected_offsets[..2].to_vec());
+        });
+    }
+}
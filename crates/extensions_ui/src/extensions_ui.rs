@@ -69,6 +69,7 @@ pub fn init(cx: &mut App) {
                         files: false,
                         directories: true,
                         multiple: false,
+                        filters: Vec::new(),
                     },
                     DirectoryLister::Local(workspace.app_state().fs.clone()),
                     window,
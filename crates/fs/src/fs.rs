@@ -106,6 +106,12 @@ pub trait Fs: Send + Sync {
         Ok(String::from_utf8(self.load_bytes(path).await?)?)
     }
     async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+    /// Loads a text file into a rope without ever holding all of it as one string, so that very
+    /// large files can be opened. Returns the file's line ending, which is normalized to `\n`.
+    async fn load_rope(&self, path: &Path) -> Result<(Rope, LineEnding)> {
+        let reader = self.open_sync(path).await?;
+        smol::unblock(move || read_rope(reader)).await
+    }
     async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
@@ -1780,6 +1786,11 @@ impl Fs for FakeFs {
         self.load_internal(path).await
     }
 
+    async fn load_rope(&self, path: &Path) -> Result<(Rope, LineEnding)> {
+        let content = self.load_internal(path).await?;
+        read_rope(content.as_slice())
+    }
+
     async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path.as_path());
@@ -2089,6 +2100,53 @@ async fn file_id(path: impl AsRef<Path>) -> Result<u64> {
     .await
 }
 
+/// Reads UTF-8 text into a rope in fixed-size chunks, normalizing its line endings to `\n`.
+pub fn read_rope(mut reader: impl io::Read) -> Result<(Rope, LineEnding)> {
+    const CHUNK_LEN: usize = 64 * 1024;
+
+    let mut rope = Rope::new();
+    let mut line_ending = None;
+    let mut buffer = vec![0; CHUNK_LEN];
+    // Bytes at the end of the previous chunk that couldn't be pushed yet: an incomplete UTF-8
+    // character, or a `\r` that may be the start of a `\r\n`.
+    let mut pending_len = 0;
+    loop {
+        let mut len = pending_len;
+        while len < buffer.len() {
+            match reader.read(&mut buffer[len..]) {
+                Ok(0) => break,
+                Ok(read_len) => len += read_len,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        let is_last = len < buffer.len();
+
+        let mut text = match std::str::from_utf8(&buffer[..len]) {
+            Ok(text) => text,
+            Err(error) if error.error_len().is_none() && !is_last => {
+                std::str::from_utf8(&buffer[..error.valid_up_to()])?
+            }
+            Err(error) => return Err(error.into()),
+        };
+        if !is_last {
+            text = text.strip_suffix('\r').unwrap_or(text);
+        }
+        // Like `LineEnding::detect`, this only looks at the start of the text, which always
+        // fits in the first chunk.
+        line_ending.get_or_insert_with(|| LineEnding::detect(text));
+        rope.push(&LineEnding::normalize_cow(text.into()));
+        if is_last {
+            break;
+        }
+
+        let pushed_len = text.len();
+        buffer.copy_within(pushed_len..len, 0);
+        pending_len = len - pushed_len;
+    }
+    Ok((rope, line_ending.unwrap_or_default()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2146,4 +2204,24 @@ mod tests {
             "D",
         );
     }
+
+    #[test]
+    fn test_read_rope() {
+        let text = "one\r\ntwo\r\n";
+        let (rope, line_ending) = read_rope(text.as_bytes()).unwrap();
+        assert_eq!(rope.to_string(), "one\ntwo\n");
+        assert_eq!(line_ending, LineEnding::Windows);
+
+        // A line ending split between chunks is normalized as a whole.
+        let text = format!("{}\r\n{}", "a".repeat(64 * 1024 - 1), "b");
+        let (rope, _) = read_rope(text.as_bytes()).unwrap();
+        assert_eq!(rope.to_string(), text.replace("\r\n", "\n"));
+
+        // So is a character split between chunks.
+        let text = format!("{}éb", "a".repeat(64 * 1024 - 1));
+        let (rope, _) = read_rope(text.as_bytes()).unwrap();
+        assert_eq!(rope.to_string(), text);
+
+        assert!(read_rope([b'a', 0xff].as_slice()).is_err());
+    }
 }
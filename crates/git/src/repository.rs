@@ -8,9 +8,12 @@ use gpui::SharedString;
 use parking_lot::Mutex;
 use rope::Rope;
 use std::borrow::Borrow;
+use std::io::Write as _;
+use std::process::Stdio;
 use std::sync::LazyLock;
 use std::{
     cmp::Ordering,
+    ffi::OsString,
     path::{Component, Path, PathBuf},
     sync::Arc,
 };
@@ -79,6 +82,10 @@ pub trait GitRepository: Send + Sync {
     /// If any of the paths were previously staged but do not exist in HEAD, they will be removed from the index.
     fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()>;
 
+    /// Writes the given text to the index as the contents of the given path, keeping its file
+    /// mode, or removes the path from the index if there is no text.
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()>;
+
     fn commit(&self, message: &str, name_and_email: Option<(&str, &str)>) -> Result<()>;
 }
 
@@ -332,6 +339,69 @@ impl GitRepository for RealGitRepository {
         Ok(())
     }
 
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()> {
+        let (working_directory, mode) = {
+            let repo = self.repository.lock();
+            let working_directory = repo
+                .workdir()
+                .context("failed to read git work directory")?
+                .to_path_buf();
+            check_path_to_repo_path_errors(path)?;
+            let mode = repo
+                .index()?
+                .get_path(path, 0)
+                .map_or(0o100644, |entry| entry.mode);
+            (working_directory, mode)
+        };
+
+        let output = if let Some(content) = content {
+            // Hashing the text as if it were at its path applies the path's filters, such
+            // as the line ending conversions of `core.autocrlf`, like `git add` would.
+            let mut path_arg = OsString::from("--path=");
+            path_arg.push(path.as_os_str());
+            let mut child = new_std_command(&self.git_binary_path)
+                .current_dir(&working_directory)
+                .args(["hash-object", "-w", "--stdin"])
+                .arg(path_arg)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()?;
+            child
+                .stdin
+                .take()
+                .context("failed to open stdin of git hash-object")?
+                .write_all(content.as_bytes())?;
+            let output = child.wait_with_output()?;
+            if !output.status.success() {
+                return Err(anyhow!(
+                    "Failed to write index text:\n{}",
+                    String::from_utf8_lossy(&output.stderr)
+                ));
+            }
+            let sha = String::from_utf8(output.stdout)?;
+            new_std_command(&self.git_binary_path)
+                .current_dir(&working_directory)
+                .args(["update-index", "--add", "--cacheinfo"])
+                .args([format!("{mode:o}"), sha.trim().to_string()])
+                .arg(path.as_ref())
+                .output()?
+        } else {
+            new_std_command(&self.git_binary_path)
+                .current_dir(&working_directory)
+                .args(["update-index", "--force-remove", "--"])
+                .arg(path.as_ref())
+                .output()?
+        };
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to update index:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(())
+    }
+
     fn commit(&self, message: &str, name_and_email: Option<(&str, &str)>) -> Result<()> {
         let working_directory = self
             .repository
@@ -518,6 +588,20 @@ impl GitRepository for FakeGitRepository {
         unimplemented!()
     }
 
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()> {
+        let mut state = self.state.lock();
+        if let Some(content) = content {
+            state.index_contents.insert(path.clone(), content);
+        } else {
+            state.index_contents.remove(path);
+        }
+        state
+            .event_emitter
+            .try_send(state.path.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
     fn commit(&self, _message: &str, _name_and_email: Option<(&str, &str)>) -> Result<()> {
         unimplemented!()
     }
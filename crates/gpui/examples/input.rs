@@ -6,7 +6,7 @@ use gpui::{
     ElementInputHandler, Entity, EntityInputHandler, FocusHandle, Focusable, GlobalElementId,
     KeyBinding, Keystroke, LayoutId, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
     PaintQuad, Pixels, Point, ShapedLine, SharedString, Style, TextRun, UTF16Selection,
-    UnderlineStyle, Window, WindowBounds, WindowOptions,
+    UnderlineKind, UnderlineStyle, Window, WindowBounds, WindowOptions,
 };
 use unicode_segmentation::*;
 
@@ -458,7 +458,7 @@ impl Element for TextElement {
                     underline: Some(UnderlineStyle {
                         color: Some(run.color),
                         thickness: px(1.0),
-                        wavy: false,
+                        kind: UnderlineKind::Solid,
                     }),
                     ..run.clone()
                 },
@@ -1,7 +1,7 @@
 use gpui::{
     canvas, div, linear_color_stop, linear_gradient, point, prelude::*, px, rgb, size, Application,
-    Background, Bounds, ColorSpace, Context, MouseDownEvent, Path, PathBuilder, PathStyle, Pixels,
-    Point, Render, StrokeOptions, Window, WindowOptions,
+    Background, Bounds, ColorSpace, Context, LineCap, LineJoin, MouseDownEvent, Path, PathBuilder,
+    Pixels, Point, Render, Window, WindowOptions,
 };
 
 struct PaintingViewer {
@@ -88,10 +88,7 @@ impl PaintingViewer {
         ));
 
         // draw a wave
-        let options = StrokeOptions::default()
-            .with_line_width(1.)
-            .with_line_join(lyon::path::LineJoin::Bevel);
-        let mut builder = PathBuilder::stroke(px(1.)).with_style(PathStyle::Stroke(options));
+        let mut builder = PathBuilder::stroke(px(1.)).line_join(LineJoin::Bevel);
         builder.move_to(point(px(40.), px(320.)));
         for i in 0..50 {
             builder.line_to(point(
@@ -102,6 +99,19 @@ impl PaintingViewer {
         let path = builder.build().unwrap();
         lines.push((path, gpui::green().into()));
 
+        // draw an arc with rounded ends
+        let mut builder = PathBuilder::stroke(px(8.)).line_cap(LineCap::Round);
+        builder.move_to(point(px(600.), px(200.)));
+        builder.arc_to(
+            point(px(700.), px(200.)),
+            point(px(50.), px(50.)),
+            0.,
+            false,
+            true,
+        );
+        let path = builder.build().unwrap();
+        lines.push((path, rgb(0xf59e0b).into()));
+
         Self {
             default_lines: lines.clone(),
             lines: vec![],
@@ -0,0 +1,246 @@
+use crate::{Bounds, Pixels, SharedString};
+
+/// The kind of user interface element, which determines how assistive technology,
+/// such as a screen reader, presents it and which interactions it offers.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AccessibilityRole {
+    /// A container that groups related elements
+    #[default]
+    Group,
+    /// A control that performs an action when clicked
+    Button,
+    /// A control that can be checked or unchecked
+    CheckBox,
+    /// A modal or floating container, such as a picker or prompt
+    Dialog,
+    /// A title for a section of content
+    Heading,
+    /// An image or icon
+    Image,
+    /// A control that navigates somewhere when clicked
+    Link,
+    /// A container of list items
+    List,
+    /// An entry in a list
+    ListItem,
+    /// A container of menu items
+    Menu,
+    /// An entry in a menu
+    MenuItem,
+    /// Text that cannot be edited
+    StaticText,
+    /// A tab that selects one of several panes
+    Tab,
+    /// A container of tabs
+    TabList,
+    /// Editable text
+    TextField,
+    /// A container of controls, such as buttons
+    Toolbar,
+}
+
+/// How urgently assistive technology should speak an announcement, see [`crate::App::announce`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AnnouncementPriority {
+    /// Spoken once the user is idle, without interrupting current speech
+    #[default]
+    Polite,
+    /// Spoken immediately, interrupting current speech
+    Assertive,
+}
+
+/// Accessibility settings chosen by the user in the system settings, see
+/// [`crate::App::accessibility_preferences`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessibilityPreferences {
+    /// Whether animations should be minimized. Animations skip to their final state when set.
+    pub reduce_motion: bool,
+    /// Whether colors should be more distinct from one another, such as by strengthening borders
+    pub increase_contrast: bool,
+    /// Whether translucent surfaces should be made opaque. Blurred window backgrounds are
+    /// rendered opaque when set.
+    pub reduce_transparency: bool,
+}
+
+/// Describes an element to assistive technology, see [`crate::InteractiveElement::accessibility_role`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessibilityProperties {
+    /// The kind of element
+    pub role: AccessibilityRole,
+    /// A short, human-readable name for the element
+    pub label: Option<SharedString>,
+    /// The element's current value, such as the contents of a text field
+    pub value: Option<SharedString>,
+    /// If set, changes to the element's label or value are announced with this priority
+    pub live: Option<AnnouncementPriority>,
+}
+
+/// An element in a window's accessibility tree, see [`crate::Window::accessibility_tree`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessibilityNode {
+    /// The kind of element
+    pub role: AccessibilityRole,
+    /// A short, human-readable name for the element
+    pub label: Option<SharedString>,
+    /// The element's current value, such as the contents of a text field
+    pub value: Option<SharedString>,
+    /// If set, changes to the element's label or value are announced with this priority
+    pub live: Option<AnnouncementPriority>,
+    /// The bounds of the element, in window coordinates
+    pub bounds: Bounds<Pixels>,
+    /// The index of the node's parent in the tree, or `None` for top-level nodes
+    pub parent: Option<usize>,
+}
+
+/// Returns the announcements for live regions whose label or value changed between two trees.
+/// Live regions are matched up by the order in which they appear, and regions that were just
+/// added are not announced.
+pub(crate) fn live_region_announcements(
+    previous: &[AccessibilityNode],
+    current: &[AccessibilityNode],
+) -> Vec<(String, AnnouncementPriority)> {
+    let previous = previous.iter().filter(|node| node.live.is_some());
+    let current = current.iter().filter(|node| node.live.is_some());
+    previous
+        .zip(current)
+        .filter(|(previous, current)| {
+            previous.label != current.label || previous.value != current.value
+        })
+        .filter_map(|(_, node)| {
+            let text = node
+                .label
+                .iter()
+                .chain(node.value.iter())
+                .map(|text| text.as_ref())
+                .collect::<Vec<&str>>()
+                .join(" ");
+            (!text.is_empty()).then(|| (text, node.live.unwrap_or_default()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        self as gpui, div, AppContext as _, Context, InteractiveElement, IntoElement,
+        ParentElement, Render, TestAppContext, Window,
+    };
+
+    struct TestView;
+
+    impl Render for TestView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .accessibility_role(AccessibilityRole::List)
+                .accessibility_label("Files")
+                .child(div().child(div().accessibility_label("one")))
+                .child(
+                    div()
+                        .accessibility_role(AccessibilityRole::ListItem)
+                        .accessibility_label("two")
+                        .accessibility_value("selected"),
+                )
+        }
+    }
+
+    struct StatusView {
+        status: &'static str,
+    }
+
+    impl Render for StatusView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .accessibility_live_region(AnnouncementPriority::Polite)
+                .accessibility_label(self.status)
+        }
+    }
+
+    #[test]
+    fn test_live_region_announcements() {
+        let node = |label: &str, live| AccessibilityNode {
+            role: AccessibilityRole::StaticText,
+            label: Some(label.to_string().into()),
+            value: None,
+            live,
+            bounds: Bounds::default(),
+            parent: None,
+        };
+        let previous = [
+            node("Saving", None),
+            node("No problems", Some(AnnouncementPriority::Polite)),
+        ];
+        let current = [
+            node("Saved", None),
+            node("2 errors", Some(AnnouncementPriority::Polite)),
+            node("Connected", Some(AnnouncementPriority::Assertive)),
+        ];
+        assert_eq!(
+            live_region_announcements(&previous, &current),
+            vec![("2 errors".to_string(), AnnouncementPriority::Polite)]
+        );
+        assert_eq!(live_region_announcements(&current, &current), vec![]);
+    }
+
+    #[gpui::test]
+    fn test_accessibility_tree(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| cx.new(|_| TestView))
+                .unwrap()
+        });
+
+        let nodes = window
+            .update(cx, |_, window, _| window.accessibility_tree().to_vec())
+            .unwrap();
+        let summary = nodes
+            .iter()
+            .map(|node| {
+                (
+                    node.role,
+                    node.label.as_ref().map(|label| label.to_string()),
+                    node.value.as_ref().map(|value| value.to_string()),
+                    node.parent,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (AccessibilityRole::List, Some("Files".into()), None, None),
+                (AccessibilityRole::Group, Some("one".into()), None, Some(0)),
+                (
+                    AccessibilityRole::ListItem,
+                    Some("two".into()),
+                    Some("selected".into()),
+                    Some(0)
+                ),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_live_region_changes_are_announced(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|_| StatusView {
+                    status: "No problems",
+                })
+            })
+            .unwrap()
+        });
+        cx.run_until_parked();
+        assert_eq!(cx.announcements(), vec![]);
+
+        window
+            .update(cx, |view, _, cx| {
+                view.status = "2 errors";
+                cx.notify();
+            })
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            cx.announcements(),
+            vec![("2 errors".to_string(), AnnouncementPriority::Polite)]
+        );
+    }
+}
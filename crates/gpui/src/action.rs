@@ -1,4 +1,4 @@
-use crate::SharedString;
+use crate::{KeyBinding, SharedString};
 use anyhow::{anyhow, Result};
 use collections::HashMap;
 pub use no_action::{is_no_action, NoAction};
@@ -165,6 +165,23 @@ impl Default for ActionRegistry {
     }
 }
 
+/// A registered action, see [`App::all_actions`].
+///
+/// [`App::all_actions`]: crate::App::all_actions
+#[derive(Clone)]
+pub struct ActionInfo {
+    /// The action's full name, such as `editor::MoveUp`
+    pub name: SharedString,
+    /// The namespace the action is declared in, such as `editor`
+    pub namespace: SharedString,
+    /// The JSON schema for the action's arguments, if it takes any
+    pub json_schema: Option<schemars::schema::Schema>,
+    /// Deprecated names that still refer to this action
+    pub deprecated_aliases: Vec<SharedString>,
+    /// The key bindings for this action in the current keymap, in the order they were added
+    pub bindings: Vec<KeyBinding>,
+}
+
 struct ActionData {
     pub build: ActionBuilder,
     pub json_schema: fn(&mut schemars::gen::SchemaGenerator) -> Option<schemars::schema::Schema>,
@@ -289,6 +306,38 @@ impl ActionRegistry {
     pub fn action_deprecations(&self) -> &HashMap<SharedString, SharedString> {
         &self.deprecations
     }
+
+    /// Describes every registered action, leaving its bindings for the caller to fill in.
+    /// Deprecated aliases are listed on the action they refer to.
+    pub fn all_actions(&self, generator: &mut schemars::gen::SchemaGenerator) -> Vec<ActionInfo> {
+        let mut aliases_by_name = HashMap::<&SharedString, Vec<SharedString>>::default();
+        for (alias, name) in &self.deprecations {
+            aliases_by_name.entry(name).or_default().push(alias.clone());
+        }
+
+        self.all_names
+            .iter()
+            .filter(|name| !self.deprecations.contains_key(*name))
+            .map(|name| {
+                let action_data = self
+                    .by_name
+                    .get(name)
+                    .expect("All actions in all_names should be registered");
+                let namespace = name
+                    .rsplit_once("::")
+                    .map_or("", |(namespace, _)| namespace);
+                let mut deprecated_aliases = aliases_by_name.remove(name).unwrap_or_default();
+                deprecated_aliases.sort();
+                ActionInfo {
+                    name: name.clone(),
+                    namespace: SharedString::from(namespace.to_string()),
+                    json_schema: (action_data.json_schema)(generator),
+                    deprecated_aliases,
+                    bindings: Vec::new(),
+                }
+            })
+            .collect()
+    }
 }
 
 /// Defines and registers unit structs that can be used as actions.
@@ -541,3 +590,28 @@ mod no_action {
         action.as_any().type_id() == (NoAction {}).type_id()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{self as gpui, actions, KeyBinding, TestAppContext};
+
+    actions!(test_actions, [Launch]);
+
+    #[gpui::test]
+    fn test_all_actions(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            cx.bind_keys([KeyBinding::new("ctrl-l", Launch, None)]);
+
+            let actions = cx.all_actions();
+            let launch = actions
+                .iter()
+                .find(|action| action.name.as_ref() == "test_actions::Launch")
+                .unwrap();
+            assert_eq!(launch.namespace.as_ref(), "test_actions");
+            assert!(launch.json_schema.is_none());
+            assert!(launch.deprecated_aliases.is_empty());
+            assert_eq!(launch.bindings.len(), 1);
+            assert_eq!(launch.bindings[0].keystrokes()[0].key, "l");
+        });
+    }
+}
@@ -1,6 +1,8 @@
 use std::{
     any::{type_name, TypeId},
+    borrow::Cow,
     cell::{Ref, RefCell, RefMut},
+    collections::BTreeMap,
     marker::PhantomData,
     mem,
     ops::{Deref, DerefMut},
@@ -13,12 +15,14 @@ use std::{
 use anyhow::{anyhow, Result};
 use derive_more::{Deref, DerefMut};
 use futures::{
-    channel::oneshot,
+    channel::{mpsc, oneshot},
     future::{LocalBoxFuture, Shared},
-    Future, FutureExt,
+    Future, FutureExt, StreamExt,
 };
 use parking_lot::RwLock;
+use raw_window_handle::{HasWindowHandle as _, RawWindowHandle};
 use slotmap::SlotMap;
+use uuid::Uuid;
 
 pub use async_context::*;
 use collections::{FxHashMap, FxHashSet, HashMap, VecDeque};
@@ -30,13 +34,19 @@ pub use test_context::*;
 use util::ResultExt;
 
 use crate::{
-    current_platform, hash, init_app_menus, Action, ActionBuildError, ActionRegistry, Any, AnyView,
-    AnyWindowHandle, AppContext, Asset, AssetSource, BackgroundExecutor, Bounds, ClipboardItem,
-    DispatchPhase, DisplayId, EventEmitter, FocusHandle, FocusMap, ForegroundExecutor, Global,
-    KeyBinding, Keymap, Keystroke, LayoutId, Menu, MenuItem, OwnedMenu, PathPromptOptions, Pixels,
-    Platform, PlatformDisplay, Point, PromptBuilder, PromptHandle, PromptLevel, Render,
-    RenderablePromptHandle, Reservation, ScreenCaptureSource, SharedString, SubscriberSet,
-    Subscription, SvgRenderer, Task, TextSystem, Window, WindowAppearance, WindowHandle, WindowId,
+    current_platform, hash, init_app_menus, init_frame_profiler, init_inspector,
+    restore_persisted_global, restore_persisted_view, AccessibilityPreferences, Action,
+    ActionBuildError, ActionInfo, ActionRegistry, AnnouncementPriority, Antialiasing, Any, AnyView,
+    AnyWindowHandle, AppContext, Asset, AssetKind, AssetSource, AtlasConfig, BackgroundExecutor,
+    Bounds, ClipboardItem, CrashContext, DispatchPhase, DisplayId, EventEmitter, FocusHandle,
+    FocusMap, ForegroundExecutor, Global, ImgResourceLoader, KeyBinding, Keymap, KeymapDiff,
+    Keystroke, LayoutId, Localization, Menu, MenuItem, MessageCatalog, NewPathPromptOptions,
+    NotificationResponse, OwnedMenu, PathPromptOptions, PersistedGlobal, PersistedStateSource,
+    PersistedView, PersistenceStore, Pixels, Platform, PlatformDisplay, Point, PowerState,
+    Progress, ProgressId, ProgressInfo, ProgressRegistry, PromptBuilder, PromptHandle, PromptLevel,
+    Render, RenderablePromptHandle, Reservation, Resource, Rgba, ScreenCaptureSource, ShareItem,
+    SharedString, StatusItem, SubscriberSet, Subscription, SvgRenderer, SystemEvent, Task,
+    TextSystem, UndoStack, Window, WindowAppearance, WindowBounds, WindowHandle, WindowId,
     WindowInvalidator,
 };
 
@@ -164,6 +174,7 @@ impl Application {
         platform.run(Box::new(move || {
             let cx = &mut *this.borrow_mut();
             on_finish_launching(cx);
+            cx.notify_lifecycle_observers(LifecycleEvent::DidFinishLaunching);
         }));
     }
 
@@ -183,12 +194,14 @@ impl Application {
     where
         F: 'static + FnMut(&mut App),
     {
-        let this = Rc::downgrade(&self.0);
-        self.0.borrow_mut().platform.on_reopen(Box::new(move || {
-            if let Some(app) = this.upgrade() {
-                callback(&mut app.borrow_mut());
-            }
-        }));
+        self.0
+            .borrow()
+            .on_lifecycle_event(move |event, cx| {
+                if event == LifecycleEvent::Reopen {
+                    callback(cx);
+                }
+            })
+            .detach();
         self
     }
 
@@ -215,12 +228,35 @@ impl Application {
 
 type Handler = Box<dyn FnMut(&mut App) -> bool + 'static>;
 type Listener = Box<dyn FnMut(&dyn Any, &mut App) -> bool + 'static>;
+type SystemEventHandler = Box<dyn FnMut(SystemEvent, &mut App) -> bool + 'static>;
 pub(crate) type KeystrokeObserver =
     Box<dyn FnMut(&KeystrokeEvent, &mut Window, &mut App) -> bool + 'static>;
 type QuitHandler = Box<dyn FnOnce(&mut App) -> LocalBoxFuture<'static, ()> + 'static>;
+type ShouldQuitHandler = Box<dyn FnMut(&mut App) -> LocalBoxFuture<'static, bool> + 'static>;
+type LifecycleHandler = Box<dyn FnMut(LifecycleEvent, &mut App) -> bool + 'static>;
+type AssetsReloadedHandler = Box<dyn FnMut(&[SharedString], &mut App) -> bool + 'static>;
 type ReleaseListener = Box<dyn FnOnce(&mut dyn Any, &mut App) + 'static>;
 type NewEntityListener = Box<dyn FnMut(AnyEntity, &mut Option<&mut Window>, &mut App) + 'static>;
 
+/// A change in the application's lifecycle, see [`App::on_lifecycle_event`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    /// The application finished launching, after the callback given to [`Application::run`]
+    /// returned
+    DidFinishLaunching,
+
+    /// One of the application's windows became active while none of them were
+    DidBecomeActive,
+
+    /// The application's last active window was deactivated, such as when the user switched to
+    /// another application
+    DidResignActive,
+
+    /// The application was launched again while it was running, such as by clicking its icon in
+    /// the dock
+    Reopen,
+}
+
 /// Contains the state of the full application, and passed as a reference to a variety of callbacks.
 /// Other [Context] derefs to this type.
 /// You need a reference to an `App` to access the state of a [Entity].
@@ -229,7 +265,7 @@ pub struct App {
     pub(crate) platform: Rc<dyn Platform>,
     text_system: Arc<TextSystem>,
     flushing_effects: bool,
-    pending_updates: usize,
+    pub(crate) pending_updates: usize,
     pub(crate) actions: Rc<ActionRegistry>,
     pub(crate) active_drag: Option<AnyDrag>,
     pub(crate) background_executor: BackgroundExecutor,
@@ -237,6 +273,7 @@ pub struct App {
     pub(crate) loading_assets: FxHashMap<(TypeId, u64), Box<dyn Any>>,
     asset_source: Arc<dyn AssetSource>,
     pub(crate) svg_renderer: SvgRenderer,
+    assets_reloaded_observers: SubscriberSet<(), AssetsReloadedHandler>,
     http_client: Arc<dyn HttpClient>,
     pub(crate) globals_by_type: FxHashMap<TypeId, Box<dyn Any>>,
     pub(crate) entities: EntityMap,
@@ -244,9 +281,13 @@ pub struct App {
     pub(crate) new_entity_observers: SubscriberSet<TypeId, NewEntityListener>,
     pub(crate) windows: SlotMap<WindowId, Option<Window>>,
     pub(crate) window_handles: FxHashMap<WindowId, AnyWindowHandle>,
+    /// The platform handles of open windows, which are needed to parent new windows to them
+    /// while they are being updated and so aren't in `windows`.
+    pub(crate) raw_window_handles: FxHashMap<WindowId, RawWindowHandle>,
     pub(crate) focus_handles: Arc<FocusMap>,
     pub(crate) keymap: Rc<RefCell<Keymap>>,
     pub(crate) keyboard_layout: SharedString,
+    pub(crate) accessibility_preferences: AccessibilityPreferences,
     pub(crate) global_action_listeners:
         FxHashMap<TypeId, Vec<Rc<dyn Fn(&dyn Any, DispatchPhase, &mut Self)>>>,
     pending_effects: VecDeque<Effect>,
@@ -255,17 +296,38 @@ pub struct App {
     pub(crate) observers: SubscriberSet<EntityId, Handler>,
     // TypeId is the type of the event that the listener callback expects
     pub(crate) event_listeners: SubscriberSet<EntityId, (TypeId, Listener)>,
+    pub(crate) published_event_listeners: SubscriberSet<TypeId, Listener>,
     pub(crate) keystroke_observers: SubscriberSet<(), KeystrokeObserver>,
     pub(crate) keyboard_layout_observers: SubscriberSet<(), Handler>,
+    pub(crate) displays_observers: SubscriberSet<(), Handler>,
+    pub(crate) status_item_click_observers: SubscriberSet<(), Handler>,
+    pub(crate) system_appearance_observers: SubscriberSet<(), Handler>,
+    pub(crate) accessibility_preferences_observers: SubscriberSet<(), Handler>,
+    pub(crate) system_event_observers: SubscriberSet<(), SystemEventHandler>,
     pub(crate) release_listeners: SubscriberSet<EntityId, ReleaseListener>,
     pub(crate) global_observers: SubscriberSet<TypeId, Handler>,
     pub(crate) quit_observers: SubscriberSet<(), QuitHandler>,
+    should_quit_handlers: SubscriberSet<(), ShouldQuitHandler>,
+    lifecycle_observers: SubscriberSet<(), LifecycleHandler>,
+    /// Whether one of the application's windows was active when activation last changed.
+    active: bool,
     pub(crate) layout_id_buffer: Vec<LayoutId>, // We recycle this memory across layout requests.
     pub(crate) propagate_event: bool,
     pub(crate) prompt_builder: Option<PromptBuilder>,
     pub(crate) window_invalidators_by_entity:
         FxHashMap<EntityId, FxHashMap<WindowId, WindowInvalidator>>,
     pub(crate) tracked_entities: FxHashMap<WindowId, FxHashSet<EntityId>>,
+    pub(crate) debounced_tasks: FxHashMap<EntityId, FxHashMap<&'static str, Task<()>>>,
+    pub(crate) throttles: FxHashMap<(EntityId, &'static str), Throttle>,
+    pub(crate) persistence: PersistenceStore,
+    pub(crate) undo_stack: UndoStack,
+    pub(crate) progress: ProgressRegistry,
+    pub(crate) progress_observers: SubscriberSet<(), Handler>,
+    pub(crate) crash_context: CrashContext,
+    pub(crate) atlas_config: AtlasConfig,
+    pub(crate) antialiasing: Antialiasing,
+    localization: Localization,
+    locale_observers: SubscriberSet<(), Handler>,
     #[cfg(any(test, feature = "test-support", debug_assertions))]
     pub(crate) name: Option<&'static str>,
 }
@@ -287,6 +349,7 @@ impl App {
         let text_system = Arc::new(TextSystem::new(platform.text_system()));
         let entities = EntityMap::new();
         let keyboard_layout = SharedString::from(platform.keyboard_layout());
+        let accessibility_preferences = platform.accessibility_preferences();
 
         let app = Rc::new_cyclic(|this| AppCell {
             app: RefCell::new(App {
@@ -300,6 +363,7 @@ impl App {
                 background_executor: executor,
                 foreground_executor,
                 svg_renderer: SvgRenderer::new(asset_source.clone()),
+                assets_reloaded_observers: SubscriberSet::new(),
                 loading_assets: Default::default(),
                 asset_source,
                 http_client,
@@ -309,22 +373,44 @@ impl App {
                 windows: SlotMap::with_key(),
                 window_update_stack: Vec::new(),
                 window_handles: FxHashMap::default(),
+                raw_window_handles: FxHashMap::default(),
                 focus_handles: Arc::new(RwLock::new(SlotMap::with_key())),
                 keymap: Rc::new(RefCell::new(Keymap::default())),
                 keyboard_layout,
+                accessibility_preferences,
                 global_action_listeners: FxHashMap::default(),
                 pending_effects: VecDeque::new(),
                 pending_notifications: FxHashSet::default(),
                 pending_global_notifications: FxHashSet::default(),
                 observers: SubscriberSet::new(),
                 tracked_entities: FxHashMap::default(),
+                debounced_tasks: FxHashMap::default(),
+                throttles: FxHashMap::default(),
+                persistence: PersistenceStore::default(),
+                undo_stack: UndoStack::default(),
+                progress: ProgressRegistry::default(),
+                progress_observers: SubscriberSet::new(),
+                crash_context: CrashContext::default(),
+                atlas_config: AtlasConfig::default(),
+                antialiasing: Antialiasing::default(),
+                localization: Localization::default(),
+                locale_observers: SubscriberSet::new(),
                 window_invalidators_by_entity: FxHashMap::default(),
                 event_listeners: SubscriberSet::new(),
+                published_event_listeners: SubscriberSet::new(),
                 release_listeners: SubscriberSet::new(),
                 keystroke_observers: SubscriberSet::new(),
                 keyboard_layout_observers: SubscriberSet::new(),
+                displays_observers: SubscriberSet::new(),
+                status_item_click_observers: SubscriberSet::new(),
+                system_appearance_observers: SubscriberSet::new(),
+                accessibility_preferences_observers: SubscriberSet::new(),
+                system_event_observers: SubscriberSet::new(),
                 global_observers: SubscriberSet::new(),
                 quit_observers: SubscriberSet::new(),
+                should_quit_handlers: SubscriberSet::new(),
+                lifecycle_observers: SubscriberSet::new(),
+                active: false,
                 layout_id_buffer: Default::default(),
                 propagate_event: true,
                 prompt_builder: Some(PromptBuilder::Default),
@@ -335,6 +421,8 @@ impl App {
         });
 
         init_app_menus(platform.as_ref(), &mut app.borrow_mut());
+        init_inspector(&mut app.borrow_mut());
+        init_frame_profiler(&mut app.borrow_mut());
 
         platform.on_keyboard_layout_change(Box::new({
             let app = Rc::downgrade(&app);
@@ -342,6 +430,8 @@ impl App {
                 if let Some(app) = app.upgrade() {
                     let cx = &mut app.borrow_mut();
                     cx.keyboard_layout = SharedString::from(cx.platform.keyboard_layout());
+                    // Keystrokes shown in the UI are rendered with the current layout.
+                    cx.refresh_windows();
                     cx.keyboard_layout_observers
                         .clone()
                         .retain(&(), move |callback| (callback)(cx));
@@ -349,6 +439,83 @@ impl App {
             }
         }));
 
+        platform.on_displays_changed(Box::new({
+            let app = Rc::downgrade(&app);
+            move || {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.displays_observers
+                        .clone()
+                        .retain(&(), move |callback| (callback)(cx));
+                }
+            }
+        }));
+
+        platform.on_system_appearance_changed(Box::new({
+            let app = Rc::downgrade(&app);
+            move || {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.system_appearance_observers
+                        .clone()
+                        .retain(&(), move |callback| (callback)(cx));
+                }
+            }
+        }));
+
+        platform.on_accessibility_preferences_changed(Box::new({
+            let app = Rc::downgrade(&app);
+            move || {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.accessibility_preferences = cx.platform.accessibility_preferences();
+                    for window in cx.windows() {
+                        cx.update_window(window, |_, window, cx| {
+                            window.accessibility_preferences_changed(cx)
+                        })
+                        .ok();
+                    }
+                    cx.accessibility_preferences_observers
+                        .clone()
+                        .retain(&(), move |callback| (callback)(cx));
+                }
+            }
+        }));
+
+        platform.on_system_event(Box::new({
+            let app = Rc::downgrade(&app);
+            move |event| {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.system_event_observers
+                        .clone()
+                        .retain(&(), move |callback| (callback)(event, cx));
+                }
+            }
+        }));
+
+        platform.on_reopen(Box::new({
+            let app = Rc::downgrade(&app);
+            move || {
+                if let Some(app) = app.upgrade() {
+                    app.borrow_mut()
+                        .notify_lifecycle_observers(LifecycleEvent::Reopen);
+                }
+            }
+        }));
+
+        platform.on_status_item_click(Box::new({
+            let app = Rc::downgrade(&app);
+            move || {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.status_item_click_observers
+                        .clone()
+                        .retain(&(), move |callback| (callback)(cx));
+                }
+            }
+        }));
+
         platform.on_quit(Box::new({
             let cx = app.clone();
             move || {
@@ -356,6 +523,18 @@ impl App {
             }
         }));
 
+        platform.on_quit_requested(Box::new({
+            let cx = app.clone();
+            move || {
+                let mut cx = cx.borrow_mut();
+                if cx.should_quit_handlers.is_empty(&()) {
+                    return true;
+                }
+                cx.request_quit().detach();
+                false
+            }
+        }));
+
         app
     }
 
@@ -367,9 +546,17 @@ impl App {
         for observer in self.quit_observers.remove(&()) {
             futures.push(observer(self));
         }
+        let save_persisted_state = self.save_persisted_state();
+        futures.push(
+            async move {
+                save_persisted_state.await.log_err();
+            }
+            .boxed_local(),
+        );
 
         self.windows.clear();
         self.window_handles.clear();
+        self.raw_window_handles.clear();
         self.flush_effects();
 
         let futures = futures::future::join_all(futures);
@@ -387,6 +574,25 @@ impl App {
         &self.keyboard_layout
     }
 
+    /// Translates a keystroke naming keys by their position on a US QWERTY keyboard into the
+    /// keystroke typed by pressing those keys with the current keyboard layout. For example,
+    /// `cmd-q` becomes `cmd-a` with AZERTY. Returns the keystroke unchanged if the platform
+    /// can't translate it.
+    pub fn layout_keystroke(&self, physical: &Keystroke) -> Keystroke {
+        self.platform
+            .layout_keystroke(physical)
+            .unwrap_or_else(|| physical.clone())
+    }
+
+    /// Translates a keystroke typed with the current keyboard layout into one naming the keys
+    /// pressed by their position on a US QWERTY keyboard, the inverse of [`App::layout_keystroke`].
+    /// Returns the keystroke unchanged if the platform can't translate it.
+    pub fn physical_keystroke(&self, keystroke: &Keystroke) -> Keystroke {
+        self.platform
+            .physical_keystroke(keystroke)
+            .unwrap_or_else(|| keystroke.clone())
+    }
+
     /// Invokes a handler when the current keyboard layout changes
     pub fn on_keyboard_layout_change<F>(&self, mut callback: F) -> Subscription
     where
@@ -403,11 +609,327 @@ impl App {
         subscription
     }
 
+    /// Invokes a handler when displays are connected, disconnected, or rearranged, or when
+    /// their resolution, scale factor, or work area changes. See [`App::displays`].
+    pub fn on_displays_changed<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&mut App),
+    {
+        let (subscription, activate) = self.displays_observers.insert(
+            (),
+            Box::new(move |cx| {
+                callback(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Starts reporting the progress of a long-running task, such as indexing or a download,
+    /// which is listed in [`App::progress`] until every clone of the returned handle is dropped.
+    /// If `cancellable` is true, the task should stop once the handle is cancelled.
+    pub fn start_progress(
+        &mut self,
+        title: impl Into<SharedString>,
+        cancellable: bool,
+    ) -> Progress {
+        if let Some(mut changes) = self.progress.take_changes() {
+            self.spawn(|cx| async move {
+                while changes.next().await.is_some() {
+                    // Handles can be updated from any thread, so notify once for a batch of changes.
+                    while let Ok(Some(())) = changes.try_next() {}
+                    let notified = cx.update(|cx| {
+                        cx.progress_observers
+                            .clone()
+                            .retain(&(), |observer| observer(cx));
+                    });
+                    if notified.is_err() {
+                        break;
+                    }
+                }
+            })
+            .detach();
+        }
+        self.progress.start(title.into(), cancellable)
+    }
+
+    /// Returns the progress of every task that's currently reporting it, in the order they
+    /// were started.
+    pub fn progress(&self) -> Vec<ProgressInfo> {
+        self.progress.all()
+    }
+
+    /// Cancels the task with the given id, if it's cancellable.
+    pub fn cancel_progress(&self, id: ProgressId) {
+        self.progress.cancel(id);
+    }
+
+    /// Invokes a handler whenever a task starts, updates or finishes reporting its progress.
+    /// See [`App::progress`].
+    pub fn observe_progress<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&mut App),
+    {
+        let (subscription, activate) = self.progress_observers.insert(
+            (),
+            Box::new(move |cx| {
+                callback(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Returns the locale that UI strings are translated into, such as "en" or "pt-BR".
+    pub fn locale(&self) -> &SharedString {
+        self.localization.locale()
+    }
+
+    /// Switches the locale that UI strings are translated into. Every window is redrawn, so that
+    /// strings translated with [`App::translate`] while rendering are shown in the new language,
+    /// and locale observers are notified so that they can translate any strings they cached.
+    /// Strings that were never passed to [`App::translate`] stay as they are.
+    pub fn set_locale(&mut self, locale: impl Into<SharedString>) {
+        let locale = locale.into();
+        if *self.localization.locale() == locale {
+            return;
+        }
+        self.localization.set_locale(locale);
+        self.refresh_windows();
+        self.locale_observers
+            .clone()
+            .retain(&(), |callback| callback(self));
+    }
+
+    /// Adds translations for the given locale, such as "fr" or "pt-BR". Messages in a catalog for
+    /// a locale take precedence over those in the catalog for its language.
+    pub fn add_message_catalog(
+        &mut self,
+        locale: impl Into<SharedString>,
+        catalog: MessageCatalog,
+    ) {
+        self.localization.add_catalog(locale.into(), catalog);
+        self.refresh_windows();
+    }
+
+    /// Translates a UI string into the current locale, returning it unchanged if it has no
+    /// translation. See [`MessageCatalog`].
+    pub fn translate(&self, message: impl Into<SharedString>) -> SharedString {
+        self.localization.translate(message.into())
+    }
+
+    /// Translates a UI string that depends on a count, such as "{count} file", using the plural
+    /// rules of the current locale. Occurrences of `{count}` are replaced with the count, and the
+    /// English `one` and `other` forms are used when there's no translation.
+    pub fn translate_plural(
+        &self,
+        one: impl Into<SharedString>,
+        other: impl Into<SharedString>,
+        count: usize,
+    ) -> SharedString {
+        self.localization
+            .translate_plural(one.into(), other.into(), count)
+    }
+
+    /// Invokes a handler whenever the locale changes, so that UI strings that are cached, such
+    /// as the application menus, can be translated again.
+    pub fn observe_locale<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&mut App),
+    {
+        let (subscription, activate) = self.locale_observers.insert(
+            (),
+            Box::new(move |cx| {
+                callback(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Returns a handle to the recent actions, input events and focused views that are recorded
+    /// for crash reports, which can be read from a panic hook.
+    pub fn crash_context(&self) -> CrashContext {
+        self.crash_context.clone()
+    }
+
+    /// Starts or stops recording what the user does for crash reports. This should only be
+    /// enabled once the user has consented to sending diagnostics, and disabling it discards
+    /// everything that was recorded.
+    pub fn set_crash_context_enabled(&self, enabled: bool) {
+        self.crash_context.set_enabled(enabled);
+    }
+
+    /// Returns the limits on the sprite atlas of each window.
+    pub fn atlas_config(&self) -> AtlasConfig {
+        self.atlas_config
+    }
+
+    /// Sets the limits on the sprite atlas of each window. A new page size only applies to the
+    /// textures that are allocated afterwards, and a new budget is enforced when each window is
+    /// next drawn.
+    pub fn set_atlas_config(&mut self, config: AtlasConfig) {
+        self.atlas_config = config;
+        for window in self.windows.values().flatten() {
+            window.set_atlas_page_size(config.page_size);
+        }
+        // Windows that are being updated are taken out of `self.windows` until their update
+        // finishes, so the page size is applied to them once they're back.
+        self.defer(move |cx| {
+            if cx.atlas_config == config {
+                for window in cx.windows.values().flatten() {
+                    window.set_atlas_page_size(config.page_size);
+                }
+            }
+        });
+    }
+
+    /// Returns how the renderers of new windows anti-alias what they draw.
+    pub fn antialiasing(&self) -> Antialiasing {
+        self.antialiasing
+    }
+
+    /// Sets how the renderers of windows anti-alias what they draw, so that users can trade the
+    /// smoothness of paths for battery life. This applies to the windows that are already open
+    /// from their next frame on, as well as to the windows opened afterwards.
+    pub fn set_antialiasing(&mut self, antialiasing: Antialiasing) {
+        self.antialiasing = antialiasing;
+        for window in self.windows.values_mut().flatten() {
+            window.platform_window.set_antialiasing(antialiasing);
+            window.refresh();
+        }
+        // Windows that are being updated are taken out of `self.windows` until their update
+        // finishes, so their renderers are updated once they're back.
+        self.defer(move |cx| {
+            if cx.antialiasing == antialiasing {
+                for window in cx.windows.values_mut().flatten() {
+                    window.platform_window.set_antialiasing(antialiasing);
+                    window.refresh();
+                }
+            }
+        });
+    }
+
+    /// Returns whether the system is running on battery power or in low power mode.
+    pub fn power_state(&self) -> PowerState {
+        self.platform.power_state()
+    }
+
+    /// Invokes a handler when the system goes to sleep or wakes up, when the user's
+    /// session is locked or unlocked, or when the [`App::power_state`] changes.
+    ///
+    /// Use this to pause background work while asleep and to reconnect afterwards.
+    /// These events are reported on macOS and Windows. Linux reports none of them, and
+    /// only reports whether the system is on battery power in [`App::power_state`].
+    pub fn on_system_event<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(SystemEvent, &mut App),
+    {
+        let (subscription, activate) = self.system_event_observers.insert(
+            (),
+            Box::new(move |event, cx| {
+                callback(event, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Invokes a handler when the application finishes launching, becomes active or inactive,
+    /// or is launched again while running. See [`LifecycleEvent`].
+    pub fn on_lifecycle_event<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(LifecycleEvent, &mut App),
+    {
+        let (subscription, activate) = self.lifecycle_observers.insert(
+            (),
+            Box::new(move |event, cx| {
+                callback(event, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    fn notify_lifecycle_observers(&mut self, event: LifecycleEvent) {
+        self.lifecycle_observers
+            .clone()
+            .retain(&(), |callback| callback(event, self));
+    }
+
+    /// Called when a window's activation changes. Activation moving from one window to another
+    /// deactivates the first window before activating the second, so the application's state is
+    /// only updated once the platform has delivered both.
+    pub(crate) fn window_activation_changed(&mut self) {
+        self.spawn(|cx| async move {
+            cx.update(|cx| {
+                let active = cx
+                    .windows
+                    .values()
+                    .flatten()
+                    .any(|window| window.is_window_active());
+                if active != cx.active {
+                    cx.active = active;
+                    cx.notify_lifecycle_observers(if active {
+                        LifecycleEvent::DidBecomeActive
+                    } else {
+                        LifecycleEvent::DidResignActive
+                    });
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
     /// Gracefully quit the application via the platform's standard routine.
     pub fn quit(&self) {
         self.platform.quit();
     }
 
+    /// Quits the application unless a handler registered with [`App::on_should_quit`] vetoes it.
+    /// The returned task resolves to whether the application is quitting.
+    pub fn request_quit(&mut self) -> Task<bool> {
+        let mut decisions = Vec::new();
+        self.should_quit_handlers.clone().retain(&(), |handler| {
+            decisions.push(handler(self));
+            true
+        });
+        self.spawn(|cx| async move {
+            let should_quit = futures::future::join_all(decisions)
+                .await
+                .into_iter()
+                .all(|should_quit| should_quit);
+            if should_quit {
+                cx.update(|cx| cx.quit()).ok();
+            }
+            should_quit
+        })
+    }
+
+    /// Registers a handler that's consulted when [`App::request_quit`] is called or the user or
+    /// OS asks the platform to quit the application, which can
+    /// cancel quitting by resolving to `false`, such as after asking the user whether to discard
+    /// unsaved changes.
+    pub fn on_should_quit<Fut>(
+        &self,
+        mut should_quit: impl FnMut(&mut App) -> Fut + 'static,
+    ) -> Subscription
+    where
+        Fut: 'static + Future<Output = bool>,
+    {
+        let (subscription, activate) = self
+            .should_quit_handlers
+            .insert((), Box::new(move |cx| should_quit(cx).boxed_local()));
+        activate();
+        subscription
+    }
+
     /// Schedules all windows in the application to be redrawn. This can be called
     /// multiple times in an update cycle and still result in a single redraw.
     pub fn refresh_windows(&mut self) {
@@ -606,6 +1128,9 @@ impl App {
                     window.root.replace(root_view.into());
                     window.defer(cx, |window: &mut Window, cx| window.appearance_changed(cx));
                     cx.window_handles.insert(id, window.handle);
+                    if let Ok(raw_window_handle) = window.platform_window.window_handle() {
+                        cx.raw_window_handles.insert(id, raw_window_handle.as_raw());
+                    }
                     cx.windows.get_mut(id).unwrap().replace(window);
                     Ok(handle)
                 }
@@ -647,6 +1172,38 @@ impl App {
         self.platform.primary_display()
     }
 
+    /// Returns the display and bounds at which to reopen a window whose bounds were saved
+    /// while it was on the display with the given UUID. If that display is no longer
+    /// connected, the window is centered on the primary display instead. Either way, the
+    /// bounds are moved and shrunk as needed for the window to be fully visible.
+    ///
+    /// Returns `None` if no displays are connected.
+    pub fn restore_window_bounds(
+        &self,
+        display_uuid: Option<Uuid>,
+        window_bounds: WindowBounds,
+    ) -> Option<(DisplayId, WindowBounds)> {
+        let saved_display = display_uuid.and_then(|uuid| {
+            self.displays()
+                .into_iter()
+                .find(|display| display.uuid().ok() == Some(uuid))
+        });
+        let (display, window_bounds) = match saved_display {
+            Some(display) => (display, window_bounds),
+            None => {
+                let display = self.primary_display()?;
+                let center = display.visible_bounds().center();
+                let window_bounds =
+                    window_bounds.map(|bounds| Bounds::centered_at(center, bounds.size));
+                (display, window_bounds)
+            }
+        };
+        Some((
+            display.id(),
+            window_bounds.fit_within(display.visible_bounds()),
+        ))
+    }
+
     /// Returns a list of available screen capture sources.
     pub fn screen_capture_sources(
         &self,
@@ -654,6 +1211,18 @@ impl App {
         self.platform.screen_capture_sources()
     }
 
+    /// Lets the user choose a screen or window to capture using the system picker.
+    /// Resolves to `None` if the picker was dismissed without a selection.
+    ///
+    /// The picker is only available on macOS 14 and later. Elsewhere this resolves to an
+    /// error, and callers should offer the [`App::screen_capture_sources`] instead. Screen
+    /// capture itself is not implemented on Linux or Windows yet.
+    pub fn pick_screen_capture_source(
+        &self,
+    ) -> oneshot::Receiver<Result<Option<Box<dyn ScreenCaptureSource>>>> {
+        self.platform.pick_screen_capture_source()
+    }
+
     /// Returns the display with the given ID, if one exists.
     pub fn find_display(&self, id: DisplayId) -> Option<Rc<dyn PlatformDisplay>> {
         self.displays()
@@ -667,6 +1236,50 @@ impl App {
         self.platform.window_appearance()
     }
 
+    /// Returns the accent color chosen in the system settings, if the platform has one.
+    pub fn accent_color(&self) -> Option<Rgba> {
+        self.platform.accent_color()
+    }
+
+    /// Invokes a handler when the system switches between light and dark appearance, or
+    /// when its accent color changes. See [`App::window_appearance`] and [`App::accent_color`].
+    pub fn on_system_appearance_change<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&mut App),
+    {
+        let (subscription, activate) = self.system_appearance_observers.insert(
+            (),
+            Box::new(move |cx| {
+                callback(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Returns the accessibility settings chosen in the system settings.
+    pub fn accessibility_preferences(&self) -> AccessibilityPreferences {
+        self.accessibility_preferences
+    }
+
+    /// Invokes a handler when the accessibility settings change. Windows are redrawn
+    /// before the handler is called. See [`App::accessibility_preferences`].
+    pub fn on_accessibility_preferences_change<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&mut App),
+    {
+        let (subscription, activate) = self.accessibility_preferences_observers.insert(
+            (),
+            Box::new(move |cx| {
+                callback(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
     /// Writes data to the primary selection buffer.
     /// Only available on Linux.
     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
@@ -767,7 +1380,68 @@ impl App {
         &self,
         directory: &Path,
     ) -> oneshot::Receiver<Result<Option<PathBuf>>> {
-        self.platform.prompt_for_new_path(directory)
+        self.prompt_for_new_path_with_options(NewPathPromptOptions::new(directory))
+    }
+
+    /// Like [`App::prompt_for_new_path`], but allows suggesting a file name,
+    /// restricting the kinds of files that can be saved, and more.
+    pub fn prompt_for_new_path_with_options(
+        &self,
+        options: NewPathPromptOptions,
+    ) -> oneshot::Receiver<Result<Option<PathBuf>>> {
+        self.platform.prompt_for_new_path(options)
+    }
+
+    /// Shows a notification through the operating system's notification center,
+    /// offering the given actions as buttons where the platform supports them.
+    ///
+    /// The receiver resolves when the user clicks the notification or one of its
+    /// actions, and is dropped without resolving if the notification is dismissed.
+    /// An error is relayed on platforms without a notification service, in which
+    /// case callers should fall back to in-app notifications. Windows doesn't
+    /// implement notifications yet, so this always relays an error there.
+    pub fn send_notification(
+        &self,
+        title: &str,
+        body: &str,
+        actions: &[&str],
+    ) -> oneshot::Receiver<Result<NotificationResponse>> {
+        self.platform.send_notification(title, body, actions)
+    }
+
+    /// Returns whether the platform provides a share sheet, see [`App::share`].
+    pub fn can_share(&self) -> bool {
+        self.platform.can_share()
+    }
+
+    /// Offers the given items to other applications through the system's share sheet,
+    /// shown next to the mouse in the active window. Does nothing on platforms without
+    /// a share sheet.
+    pub fn share(&self, items: Vec<ShareItem>) {
+        if !items.is_empty() {
+            self.platform.share(items);
+        }
+    }
+
+    /// Asks assistive technology, such as a screen reader, to speak the given text without
+    /// moving focus. To announce changes to an element as they happen, make it a live region
+    /// with [`InteractiveElement::accessibility_live_region`](crate::InteractiveElement::accessibility_live_region).
+    ///
+    /// Announcements are only spoken on macOS. Linux and Windows don't expose gpui's
+    /// accessibility tree to assistive technology yet, so they drop them.
+    pub fn announce(&self, text: &str, priority: AnnouncementPriority) {
+        if !text.is_empty() {
+            self.platform.announce(text, priority);
+        }
+    }
+
+    /// Opens the system's color picker, initially showing the given color.
+    ///
+    /// Colors are streamed through the returned receiver as the user picks them.
+    /// Opening another picker ends the previous stream. Returns `None` on platforms
+    /// without a color picker.
+    pub fn pick_color(&self, initial: impl Into<Rgba>) -> Option<mpsc::UnboundedReceiver<Rgba>> {
+        self.platform.pick_color(initial.into())
     }
 
     /// Reveals the specified path at the platform level, such as in Finder on macOS.
@@ -843,6 +1517,10 @@ impl App {
                         event,
                     } => self.apply_emit_effect(emitter, event_type, event),
 
+                    Effect::Publish { event_type, event } => {
+                        self.apply_publish_effect(event_type, event)
+                    }
+
                     Effect::RefreshWindows => {
                         self.apply_refresh_effect();
                     }
@@ -897,6 +1575,7 @@ impl App {
             for (entity_id, mut entity) in dropped {
                 self.observers.remove(&entity_id);
                 self.event_listeners.remove(&entity_id);
+                self.debounced_tasks.remove(&entity_id);
                 for release_callback in self.release_listeners.remove(&entity_id) {
                     release_callback(entity.as_mut(), self);
                 }
@@ -947,6 +1626,12 @@ impl App {
             });
     }
 
+    fn apply_publish_effect(&mut self, event_type: TypeId, event: Box<dyn Any>) {
+        self.published_event_listeners
+            .clone()
+            .retain(&event_type, |handler| handler(event.as_ref(), self));
+    }
+
     fn apply_refresh_effect(&mut self) {
         for window in self.windows.values_mut() {
             if let Some(window) = window.as_mut() {
@@ -1007,7 +1692,10 @@ impl App {
 
             if window.removed {
                 cx.window_handles.remove(&id);
+                cx.raw_window_handles.remove(&id);
                 cx.windows.remove(id);
+                cx.crash_context.remove_window(id);
+                cx.window_activation_changed();
             } else {
                 cx.windows
                     .get_mut(id)
@@ -1049,6 +1737,65 @@ impl App {
         self.foreground_executor.spawn(f(self.to_async()))
     }
 
+    /// Invokes the given callback once, after the given duration has elapsed. Dropping the
+    /// returned task cancels the timeout, and detaching it lets the timeout fire regardless.
+    pub fn set_timeout(
+        &self,
+        duration: Duration,
+        callback: impl FnOnce(&mut App) + 'static,
+    ) -> Task<()> {
+        let timer = self.background_executor.timer(duration);
+        self.spawn(|cx| async move {
+            timer.await;
+            cx.update(callback).ok();
+        })
+    }
+
+    /// Invokes the given callback repeatedly, each time the given duration has elapsed, until the
+    /// returned task is dropped or the application quits.
+    pub fn set_interval(
+        &self,
+        duration: Duration,
+        mut callback: impl FnMut(&mut App) + 'static,
+    ) -> Task<()> {
+        let executor = self.background_executor.clone();
+        self.spawn(|cx| async move {
+            loop {
+                executor.timer(duration).await;
+                if cx.update(&mut callback).is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Starts a throttle window for the given key. When the window closes, the most recent call
+    /// made during it is invoked, which opens another window.
+    pub(crate) fn start_throttle(&mut self, key: (EntityId, &'static str), duration: Duration) {
+        let timer = self.background_executor.timer(duration);
+        let task = self.spawn(|cx| async move {
+            timer.await;
+            cx.update(|cx| {
+                let trailing = cx
+                    .throttles
+                    .remove(&key)
+                    .and_then(|throttle| throttle.trailing);
+                if let Some(trailing) = trailing {
+                    cx.start_throttle(key, duration);
+                    trailing(cx);
+                }
+            })
+            .ok();
+        });
+        self.throttles.insert(
+            key,
+            Throttle {
+                trailing: None,
+                _task: task,
+            },
+        );
+    }
+
     /// Schedules the given function to be run at the end of the current effect cycle, allowing entities
     /// that are currently on the stack to be returned to the app.
     pub fn defer(&mut self, f: impl FnOnce(&mut App) + 'static) {
@@ -1062,6 +1809,70 @@ impl App {
         &self.asset_source
     }
 
+    /// Lists the assets of the given kind under a directory of the asset source, such as every
+    /// font in "fonts", so that they can be referenced by their logical paths.
+    pub fn asset_paths(&self, directory: &str, kind: AssetKind) -> Result<Vec<SharedString>> {
+        Ok(self
+            .asset_source
+            .list(directory)?
+            .into_iter()
+            .filter(|path| AssetKind::of(path) == kind)
+            .collect())
+    }
+
+    /// Loads the asset at the given logical path, returning an error if the asset source doesn't
+    /// contain it.
+    pub fn load_asset(&self, path: &str) -> Result<Cow<'static, [u8]>> {
+        self.asset_source
+            .load(path)?
+            .ok_or_else(|| anyhow!("no asset found at path {path:?}"))
+    }
+
+    /// Discards everything that was cached from the assets at the given paths, so that they're
+    /// loaded from the asset source again the next time they're rendered. This is intended for
+    /// hot-reloading assets that have changed on disk during development. SVGs and images are
+    /// reloaded automatically, other caches can be cleared with [`App::on_assets_reloaded`], and
+    /// fonts are not reloaded.
+    pub fn reload_assets(&mut self, paths: Vec<SharedString>) {
+        if paths.is_empty() {
+            return;
+        }
+        for path in &paths {
+            match AssetKind::of(path) {
+                AssetKind::Svg => {
+                    for window in self.windows.values().flatten() {
+                        window.evict_svg(path);
+                    }
+                }
+                AssetKind::Image => {
+                    self.remove_asset::<ImgResourceLoader>(&Resource::Embedded(path.clone()));
+                }
+                AssetKind::Font | AssetKind::Sound | AssetKind::Other => {}
+            }
+        }
+        self.assets_reloaded_observers
+            .clone()
+            .retain(&(), |callback| callback(&paths, self));
+        self.refresh_windows();
+    }
+
+    /// Invokes a handler with the paths of the assets that were reloaded by
+    /// [`App::reload_assets`], so that anything cached from them can be discarded.
+    pub fn on_assets_reloaded<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&[SharedString], &mut App),
+    {
+        let (subscription, activate) = self.assets_reloaded_observers.insert(
+            (),
+            Box::new(move |paths, cx| {
+                callback(paths, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
     /// Accessor for the text system.
     pub fn text_system(&self) -> &Arc<TextSystem> {
         &self.text_system
@@ -1154,6 +1965,33 @@ impl App {
         subscription
     }
 
+    /// Publish an event to every listener registered for its type with [`App::on_published`],
+    /// without the publisher and listeners needing handles to each other. Listeners are invoked
+    /// once the current update completes.
+    pub fn publish<E: 'static>(&mut self, event: E) {
+        self.push_effect(Effect::Publish {
+            event_type: TypeId::of::<E>(),
+            event: Box::new(event),
+        });
+    }
+
+    /// Register a callback to be invoked whenever an event of the given type is published with
+    /// [`App::publish`].
+    pub fn on_published<E: 'static>(
+        &mut self,
+        mut f: impl FnMut(&E, &mut Self) + 'static,
+    ) -> Subscription {
+        let (subscription, activate) = self.published_event_listeners.insert(
+            TypeId::of::<E>(),
+            Box::new(move |event, cx| {
+                f(event.downcast_ref().expect("invalid event type"), cx);
+                true
+            }),
+        );
+        self.defer(move |_| activate());
+        subscription
+    }
+
     /// Move the global of the given type to the stack.
     #[track_caller]
     pub(crate) fn lease_global<G: Global>(&mut self) -> GlobalLease<G> {
@@ -1208,6 +2046,12 @@ impl App {
         )
     }
 
+    /// Returns weak handles to every live entity of the given type, such as all the views of a
+    /// particular kind, in no particular order.
+    pub fn entities_of_type<T: 'static>(&self) -> Vec<WeakEntity<T>> {
+        self.entities.entities_of_type()
+    }
+
     /// Observe the release of a entity. The callback is invoked after the entity
     /// has no more strong references but before it has been dropped.
     pub fn observe_release<T>(
@@ -1277,6 +2121,22 @@ impl App {
         self.pending_effects.push_back(Effect::RefreshWindows);
     }
 
+    /// Replace all key bindings in the app at once, such as when a keymap file is reloaded,
+    /// returning which bindings were added and removed. Windows are only refreshed if the keymap
+    /// changed.
+    pub fn replace_key_bindings(
+        &mut self,
+        bindings: impl IntoIterator<Item = KeyBinding>,
+    ) -> KeymapDiff {
+        let mut keymap = self.keymap.borrow_mut();
+        let version = keymap.version();
+        let diff = keymap.replace_bindings(bindings.into_iter().collect());
+        if keymap.version() != version {
+            self.pending_effects.push_back(Effect::RefreshWindows);
+        }
+        diff
+    }
+
     /// Clear all key bindings in the app.
     pub fn clear_key_bindings(&mut self) {
         self.keymap.borrow_mut().clear();
@@ -1342,6 +2202,30 @@ impl App {
         self.actions.action_schemas(generator)
     }
 
+    /// Describe every registered action, with the JSON schema for its arguments and the key
+    /// bindings that trigger it, for building command palettes, keymap editors and docs.
+    pub fn all_actions(&self) -> Vec<ActionInfo> {
+        let mut generator = schemars::gen::SchemaSettings::draft07()
+            .with(|settings| settings.option_add_null_type = false)
+            .into_generator();
+        let mut actions = self.actions.all_actions(&mut generator);
+
+        let keymap = RefCell::borrow(&self.keymap);
+        let mut bindings_by_name = HashMap::<&str, Vec<KeyBinding>>::default();
+        for binding in keymap.bindings() {
+            bindings_by_name
+                .entry(binding.action().name())
+                .or_default()
+                .push(binding.clone());
+        }
+        for action in &mut actions {
+            action.bindings = bindings_by_name
+                .remove(action.name.as_ref())
+                .unwrap_or_default();
+        }
+        actions
+    }
+
     /// Get a list of all deprecated action aliases and their canonical names.
     pub fn action_deprecations(&self) -> &HashMap<SharedString, SharedString> {
         self.actions.action_deprecations()
@@ -1367,6 +2251,205 @@ impl App {
         subscription
     }
 
+    /// Sets the file in which the state of persisted views and globals is stored, and loads any
+    /// state saved there previously in the background. Views and globals registered before the
+    /// returned task completes are restored once it does. The state is written back when the
+    /// application quits.
+    pub fn set_persistence_path(&mut self, path: impl Into<PathBuf>) -> Task<()> {
+        let path = path.into();
+        self.persistence.path = Some(path.clone());
+        let load = self.background_executor.spawn(async move {
+            match smol::fs::read(&path).await {
+                Ok(contents) => serde_json::from_slice(&contents)
+                    .log_err()
+                    .unwrap_or_default(),
+                Err(error) => {
+                    if error.kind() != std::io::ErrorKind::NotFound {
+                        log::error!("failed to read persisted state from {:?}: {}", path, error);
+                    }
+                    BTreeMap::default()
+                }
+            }
+        });
+        self.spawn(|cx| async move {
+            let values = load.await;
+            cx.update(|cx| cx.restore_persisted_values(values)).ok();
+        })
+    }
+
+    fn restore_persisted_values(&mut self, values: BTreeMap<String, serde_json::Value>) {
+        let sources = mem::take(&mut self.persistence.sources);
+        for (key, value) in values {
+            // State saved by views released since the path was set is newer than the loaded state.
+            if self.persistence.values.contains_key(&key) {
+                continue;
+            }
+            if let Some(source) = sources.get(&key) {
+                (source.restore)(value.clone(), self);
+            }
+            self.persistence.values.insert(key, value);
+        }
+        let registered_while_restoring = mem::replace(&mut self.persistence.sources, sources);
+        self.persistence.sources.extend(registered_while_restoring);
+    }
+
+    /// Restores the given view's state from the persisted state, if any was saved, and saves its
+    /// state when the application quits or the view is released.
+    pub fn register_persisted_view<V: PersistedView>(&mut self, view: &Entity<V>) {
+        let key = view.read(self).persistence_key().to_string();
+        if let Some(value) = self.persistence.values.get(&key).cloned() {
+            restore_persisted_view(view, value, self);
+        }
+
+        let weak_view = view.downgrade();
+        let weak_view_for_restore = weak_view.clone();
+        self.persistence.sources.insert(
+            key.clone(),
+            PersistedStateSource {
+                save: Box::new(move |cx| {
+                    let view = weak_view.upgrade()?;
+                    serde_json::to_value(view.read(cx).persisted_state(cx)).log_err()
+                }),
+                restore: Box::new(move |value, cx| {
+                    if let Some(view) = weak_view_for_restore.upgrade() {
+                        restore_persisted_view(&view, value, cx);
+                    }
+                }),
+            },
+        );
+        self.observe_release(view, move |view, cx| {
+            if let Some(value) = serde_json::to_value(view.persisted_state(cx)).log_err() {
+                cx.persistence.values.insert(key, value);
+            }
+        })
+        .detach();
+    }
+
+    /// Restores the global of the given type from the persisted state, if any was saved, and
+    /// saves its state when the application quits.
+    ///
+    /// Panics if no global has been set for the type.
+    pub fn register_persisted_global<G: PersistedGlobal>(&mut self) {
+        let key = G::PERSISTENCE_KEY;
+        if let Some(value) = self.persistence.values.get(key).cloned() {
+            restore_persisted_global::<G>(value, self);
+        }
+
+        self.persistence.sources.insert(
+            key.to_string(),
+            PersistedStateSource {
+                save: Box::new(|cx| {
+                    let global = cx.try_global::<G>()?;
+                    serde_json::to_value(global.persisted_state(cx)).log_err()
+                }),
+                restore: Box::new(|value, cx| {
+                    if cx.has_global::<G>() {
+                        restore_persisted_global::<G>(value, cx);
+                    }
+                }),
+            },
+        );
+    }
+
+    /// Saves the state of registered views and globals to the file given to
+    /// [`App::set_persistence_path`]. This happens automatically when the application quits.
+    pub fn save_persisted_state(&mut self) -> Task<Result<()>> {
+        let sources = mem::take(&mut self.persistence.sources);
+        for (key, source) in &sources {
+            if let Some(value) = (source.save)(self) {
+                self.persistence.values.insert(key.clone(), value);
+            }
+        }
+        self.persistence.sources = sources;
+
+        let Some(path) = self.persistence.path.clone() else {
+            return Task::ready(Ok(()));
+        };
+        let contents = serde_json::to_vec_pretty(&self.persistence.values);
+        self.background_executor.spawn(async move {
+            let contents = contents?;
+            if let Some(parent) = path.parent() {
+                smol::fs::create_dir_all(parent).await?;
+            }
+            smol::fs::write(&path, contents).await?;
+            Ok(())
+        })
+    }
+
+    /// Groups the reversible operations registered by the given callback into a single
+    /// transaction, which is undone and redone as a whole. Transactions can be nested, in which
+    /// case the inner transaction becomes part of the outer one.
+    pub fn transact<R>(
+        &mut self,
+        label: impl Into<SharedString>,
+        f: impl FnOnce(&mut App) -> R,
+    ) -> R {
+        self.undo_stack.start_transaction(label.into());
+        let result = f(self);
+        self.undo_stack.end_transaction();
+        result
+    }
+
+    /// Records an operation that was just performed, along with callbacks that reverse it and
+    /// perform it again. Outside of [`App::transact`], the operation forms its own transaction
+    /// with the given label. Operations registered while undoing or redoing are ignored.
+    pub fn register_undo(
+        &mut self,
+        label: impl Into<SharedString>,
+        undo: impl FnMut(&mut App) + 'static,
+        redo: impl FnMut(&mut App) + 'static,
+    ) {
+        self.undo_stack.push(label.into(), undo, redo);
+    }
+
+    /// Reverses the most recent transaction, returning whether there was one to undo. The
+    /// transaction is removed from the history immediately, but reversed at the end of the
+    /// current effect cycle, so that its operations can update entities that are currently on
+    /// the stack, such as the view handling an undo action.
+    pub fn undo(&mut self) -> bool {
+        let Some(mut transaction) = self.undo_stack.start_undo() else {
+            return false;
+        };
+        self.defer(move |cx| {
+            cx.undo_stack.set_replaying(true);
+            transaction.undo(cx);
+            cx.undo_stack.set_replaying(false);
+            cx.undo_stack.finish_undo(transaction);
+        });
+        true
+    }
+
+    /// Performs the most recently undone transaction again, returning whether there was one to
+    /// redo. Like [`App::undo`], the transaction is performed at the end of the current effect
+    /// cycle.
+    pub fn redo(&mut self) -> bool {
+        let Some(mut transaction) = self.undo_stack.start_redo() else {
+            return false;
+        };
+        self.defer(move |cx| {
+            cx.undo_stack.set_replaying(true);
+            transaction.redo(cx);
+            cx.undo_stack.set_replaying(false);
+            cx.undo_stack.finish_redo(transaction);
+        });
+        true
+    }
+
+    /// The label of the transaction that [`App::undo`] would reverse, if any.
+    pub fn undo_label(&self) -> Option<&SharedString> {
+        self.undo_stack.undo_label()
+    }
+
+    /// The label of the transaction that [`App::redo`] would perform again, if any.
+    pub fn redo_label(&self) -> Option<&SharedString> {
+        self.undo_stack.redo_label()
+    }
+
+    /// Forgets all transactions, such as when the state they modify has been discarded.
+    pub fn clear_undo_history(&mut self) {
+        self.undo_stack.clear();
+    }
+
     pub(crate) fn clear_pending_keystrokes(&mut self) {
         for window in self.windows() {
             window
@@ -1397,9 +2480,40 @@ impl App {
 
     /// Sets the menu bar for this application. This will replace any existing menu bar.
     pub fn set_menus(&self, menus: Vec<Menu>) {
+        let menus = menus
+            .into_iter()
+            .map(|menu| self.translate_menu(menu))
+            .collect();
         self.platform.set_menus(menus, &self.keymap.borrow());
     }
 
+    fn translate_menu(&self, menu: Menu) -> Menu {
+        Menu {
+            name: self.translate(menu.name),
+            items: menu
+                .items
+                .into_iter()
+                .map(|item| match item {
+                    MenuItem::Separator => MenuItem::Separator,
+                    MenuItem::Submenu(menu) => MenuItem::Submenu(self.translate_menu(menu)),
+                    MenuItem::Action {
+                        name,
+                        action,
+                        os_action,
+                        checked,
+                        disabled,
+                    } => MenuItem::Action {
+                        name: self.translate(name),
+                        action,
+                        os_action,
+                        checked,
+                        disabled,
+                    },
+                })
+                .collect(),
+        }
+    }
+
     /// Gets the menu bar for this application.
     pub fn get_menus(&self) -> Option<Vec<OwnedMenu>> {
         self.platform.get_menus()
@@ -1410,6 +2524,43 @@ impl App {
         self.platform.set_dock_menu(menus, &self.keymap.borrow());
     }
 
+    /// Shows an item in the menu bar's status area, replacing any previously shown one,
+    /// or removes it when `None` is given. Menu items dispatch their actions like
+    /// those of the application menu. This does nothing on platforms other than macOS.
+    pub fn set_status_item(&self, item: Option<StatusItem>) {
+        self.platform.set_status_item(item, &self.keymap.borrow());
+    }
+
+    /// Invokes a handler when the status item is clicked. Only status items without
+    /// a menu report clicks. See [`App::set_status_item`].
+    pub fn on_status_item_click<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&mut App),
+    {
+        let (subscription, activate) = self.status_item_click_observers.insert(
+            (),
+            Box::new(move |cx| {
+                callback(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Shows a short label, such as a count of unread mentions, on the application's icon in the
+    /// dock, or removes it when `None` is given. Only available on macOS.
+    pub fn set_dock_badge(&self, badge: Option<&str>) {
+        self.platform.set_dock_badge(badge);
+    }
+
+    /// Shows a progress bar on the application's icon in the dock or taskbar, or removes it when
+    /// `None` is given. The progress is clamped between 0.0 and 1.0.
+    pub fn set_dock_progress(&self, progress: Option<f32>) {
+        self.platform
+            .set_dock_progress(progress.map(|progress| progress.clamp(0., 1.)));
+    }
+
     /// Adds given path to the bottom of the list of recent paths for the application.
     /// The list is usually shown on the application icon's context menu in the dock,
     /// and allows to open the recent files via that context menu.
@@ -1433,6 +2584,7 @@ impl App {
     }
 
     fn dispatch_global_action(&mut self, action: &dyn Action) {
+        self.crash_context.record_action(None, action.name());
         self.propagate_event = true;
 
         if let Some(mut global_listeners) = self
@@ -1702,6 +2854,10 @@ pub(crate) enum Effect {
         event_type: TypeId,
         event: Box<dyn Any>,
     },
+    Publish {
+        event_type: TypeId,
+        event: Box<dyn Any>,
+    },
     RefreshWindows,
     NotifyGlobalObservers {
         global_type: TypeId,
@@ -1721,6 +2877,7 @@ impl std::fmt::Debug for Effect {
         match self {
             Effect::Notify { emitter } => write!(f, "Notify({})", emitter),
             Effect::Emit { emitter, .. } => write!(f, "Emit({:?})", emitter),
+            Effect::Publish { event_type, .. } => write!(f, "Publish({:?})", event_type),
             Effect::RefreshWindows => write!(f, "RefreshWindows"),
             Effect::NotifyGlobalObservers { global_type } => {
                 write!(f, "NotifyGlobalObservers({:?})", global_type)
@@ -1731,6 +2888,12 @@ impl std::fmt::Debug for Effect {
     }
 }
 
+/// A throttle window started by [`Context::throttle`], holding the call to make once it closes.
+pub(crate) struct Throttle {
+    trailing: Option<Box<dyn FnOnce(&mut App)>>,
+    _task: Task<()>,
+}
+
 /// Wraps a global variable value during `update_global` while the value has been moved to the stack.
 pub(crate) struct GlobalLease<G: Global> {
     global: Box<dyn Any>,
@@ -1821,3 +2984,54 @@ impl HttpClient for NullHttpClient {
         type_name::<Self>()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{self as gpui, point, px, size, TestAppContext};
+
+    #[gpui::test]
+    fn test_restore_window_bounds(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let display = cx.primary_display().unwrap();
+            assert_eq!(
+                display.visible_bounds(),
+                Bounds::new(point(px(0.), px(0.)), size(px(1920.), px(1080.)))
+            );
+
+            // A window saved on a display that has since become smaller is moved and shrunk
+            // to fit it.
+            let saved_bounds = WindowBounds::Windowed(Bounds::new(
+                point(px(1600.), px(100.)),
+                size(px(800.), px(1200.)),
+            ));
+            assert_eq!(
+                cx.restore_window_bounds(display.uuid().ok(), saved_bounds),
+                Some((
+                    display.id(),
+                    WindowBounds::Windowed(Bounds::new(
+                        point(px(1120.), px(0.)),
+                        size(px(800.), px(1080.)),
+                    )),
+                ))
+            );
+
+            // A window saved on a display that is no longer connected is centered on the
+            // primary display, and still fits within it.
+            let saved_bounds = WindowBounds::Maximized(Bounds::new(
+                point(px(3000.), px(200.)),
+                size(px(2560.), px(600.)),
+            ));
+            assert_eq!(
+                cx.restore_window_bounds(Some(Uuid::new_v4()), saved_bounds),
+                Some((
+                    display.id(),
+                    WindowBounds::Maximized(Bounds::new(
+                        point(px(0.), px(240.)),
+                        size(px(1920.), px(600.)),
+                    )),
+                ))
+            );
+        });
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     AnyView, AnyWindowHandle, AppContext, AsyncApp, DispatchPhase, Effect, EntityId, EventEmitter,
-    FocusHandle, FocusOutEvent, Focusable, Global, KeystrokeObserver, Reservation, SubscriberSet,
-    Subscription, Task, WeakEntity, WeakFocusHandle, Window, WindowHandle,
+    FocusHandle, FocusOutEvent, Focusable, Global, KeystrokeObserver, Reservation, SharedString,
+    SubscriberSet, Subscription, Task, WeakEntity, WeakFocusHandle, Window, WindowHandle,
 };
 use anyhow::Result;
 use derive_more::{Deref, DerefMut};
@@ -11,6 +11,7 @@ use std::{
     borrow::{Borrow, BorrowMut},
     future::Future,
     sync::Arc,
+    time::Duration,
 };
 
 use super::{App, AsyncWindowContext, Entity, KeystrokeEvent};
@@ -90,6 +91,45 @@ impl<'a, T: 'static> Context<'a, T> {
         })
     }
 
+    /// Observe another entity, only invoking the callback when the given predicate holds
+    /// for the entity's state after it notifies.
+    pub fn observe_filtered<W>(
+        &mut self,
+        entity: &Entity<W>,
+        predicate: impl Fn(&W, &App) -> bool + 'static,
+        mut on_notify: impl FnMut(&mut T, Entity<W>, &mut Context<'_, T>) + 'static,
+    ) -> Subscription
+    where
+        T: 'static,
+        W: 'static,
+    {
+        self.observe(entity, move |this, entity, cx| {
+            if predicate(entity.read(cx), cx) {
+                on_notify(this, entity, cx);
+            }
+        })
+    }
+
+    /// Subscribe to an event type from another entity, only invoking the callback for events
+    /// that match the given predicate.
+    pub fn subscribe_filtered<T2, Evt>(
+        &mut self,
+        entity: &Entity<T2>,
+        predicate: impl Fn(&Evt) -> bool + 'static,
+        mut on_event: impl FnMut(&mut T, Entity<T2>, &Evt, &mut Context<'_, T>) + 'static,
+    ) -> Subscription
+    where
+        T: 'static,
+        T2: 'static + EventEmitter<Evt>,
+        Evt: 'static,
+    {
+        self.subscribe(entity, move |this, emitter, event, cx| {
+            if predicate(event) {
+                on_event(this, emitter, event, cx);
+            }
+        })
+    }
+
     /// Register a callback to be invoked when GPUI releases this entity.
     pub fn on_release(&self, on_release: impl FnOnce(&mut T, &mut App) + 'static) -> Subscription
     where
@@ -148,6 +188,27 @@ impl<'a, T: 'static> Context<'a, T> {
         subscription
     }
 
+    /// Register a callback to be invoked whenever an event of the given type is published with
+    /// [`App::publish`], for as long as this entity is alive.
+    pub fn on_published<E: 'static>(
+        &mut self,
+        mut f: impl FnMut(&mut T, &E, &mut Context<'_, T>) + 'static,
+    ) -> Subscription
+    where
+        T: 'static,
+    {
+        let handle = self.weak_entity();
+        let (subscription, activate) = self.app.published_event_listeners.insert(
+            TypeId::of::<E>(),
+            Box::new(move |event, cx| {
+                let event = event.downcast_ref().expect("invalid event type");
+                handle.update(cx, |this, cx| f(this, event, cx)).is_ok()
+            }),
+        );
+        self.defer(move |_| activate());
+        subscription
+    }
+
     /// Arrange for the given function to be invoked whenever the application is quit.
     /// The future returned from this callback will be polled for up to [crate::SHUTDOWN_TIMEOUT] until the app fully quits.
     pub fn on_app_quit<Fut>(
@@ -194,6 +255,126 @@ impl<'a, T: 'static> Context<'a, T> {
         self.app.spawn(|cx| f(this, cx))
     }
 
+    /// Invokes the given callback with this entity once, after the given duration has elapsed,
+    /// unless the entity has been released. Dropping the returned task cancels the timeout.
+    pub fn set_timeout(
+        &self,
+        duration: Duration,
+        callback: impl FnOnce(&mut T, &mut Context<'_, T>) + 'static,
+    ) -> Task<()>
+    where
+        T: 'static,
+    {
+        let timer = self.background_executor().timer(duration);
+        self.spawn(|this, mut cx| async move {
+            timer.await;
+            this.update(&mut cx, callback).ok();
+        })
+    }
+
+    /// Invokes the given callback with this entity repeatedly, each time the given duration has
+    /// elapsed, until the returned task is dropped or the entity is released.
+    pub fn set_interval(
+        &self,
+        duration: Duration,
+        mut callback: impl FnMut(&mut T, &mut Context<'_, T>) + 'static,
+    ) -> Task<()>
+    where
+        T: 'static,
+    {
+        let executor = self.background_executor().clone();
+        self.spawn(|this, mut cx| async move {
+            loop {
+                executor.timer(duration).await;
+                if this.update(&mut cx, &mut callback).is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Invokes the given callback with this entity once the given duration has elapsed without
+    /// another call to `debounce` for the same key, such as to search only after the user stops
+    /// typing. Each call replaces the pending callback for its key and restarts the delay, and
+    /// pending callbacks are dropped when the entity is released.
+    pub fn debounce(
+        &mut self,
+        key: &'static str,
+        duration: Duration,
+        callback: impl FnOnce(&mut T, &mut Context<'_, T>) + 'static,
+    ) where
+        T: 'static,
+    {
+        let entity_id = self.entity_id();
+        let timer = self.background_executor().timer(duration);
+        let task = self.spawn(|this, mut cx| async move {
+            timer.await;
+            this.update(&mut cx, |this, cx| {
+                if let Some(tasks) = cx.debounced_tasks.get_mut(&entity_id) {
+                    tasks.remove(key);
+                    if tasks.is_empty() {
+                        cx.debounced_tasks.remove(&entity_id);
+                    }
+                }
+                callback(this, cx);
+            })
+            .ok();
+        });
+        self.debounced_tasks
+            .entry(entity_id)
+            .or_default()
+            .insert(key, task);
+    }
+
+    /// Invokes the given callback with this entity at most once per the given duration for the
+    /// same key, such as to react to a storm of resize events. The first call is invoked at the
+    /// end of the current effect cycle, and the most recent call made while throttled is invoked
+    /// once the duration has elapsed.
+    pub fn throttle(
+        &mut self,
+        key: &'static str,
+        duration: Duration,
+        callback: impl FnOnce(&mut T, &mut Context<'_, T>) + 'static,
+    ) where
+        T: 'static,
+    {
+        let key = (self.entity_id(), key);
+        let this = self.weak_entity();
+        let callback: Box<dyn FnOnce(&mut App)> = Box::new(move |cx| {
+            this.update(cx, callback).ok();
+        });
+        if let Some(throttle) = self.throttles.get_mut(&key) {
+            throttle.trailing = Some(callback);
+        } else {
+            self.app.start_throttle(key, duration);
+            self.app.defer(callback);
+        }
+    }
+
+    /// Records an operation that was just performed on this entity, along with callbacks that
+    /// reverse it and perform it again, see [`App::register_undo`]. The callbacks do nothing once
+    /// the entity has been released.
+    pub fn register_undo(
+        &mut self,
+        label: impl Into<SharedString>,
+        mut undo: impl FnMut(&mut T, &mut Context<'_, T>) + 'static,
+        mut redo: impl FnMut(&mut T, &mut Context<'_, T>) + 'static,
+    ) where
+        T: 'static,
+    {
+        let this = self.weak_entity();
+        let this_for_redo = this.clone();
+        self.app.register_undo(
+            label,
+            move |cx| {
+                this.update(cx, &mut undo).ok();
+            },
+            move |cx| {
+                this_for_redo.update(cx, &mut redo).ok();
+            },
+        );
+    }
+
     /// Convenience method for accessing view state in an event callback.
     ///
     /// Many GPUI callbacks take the form of `Fn(&E, &mut Window, &mut AppContext)`,
@@ -352,7 +533,7 @@ impl<'a, T: 'static> Context<'a, T> {
             })
     }
 
-    /// Register a callback to be invoked when the window is resized.
+    /// Register a callback to be invoked when the window is resized or moved, including onto another display.
     pub fn observe_window_bounds(
         &self,
         window: &mut Window,
@@ -370,6 +551,25 @@ impl<'a, T: 'static> Context<'a, T> {
         subscription
     }
 
+    /// Register a callback to be invoked when the window's scale factor changes, such as when
+    /// it moves between displays with different pixel densities.
+    pub fn observe_window_scale_factor(
+        &self,
+        window: &mut Window,
+        mut callback: impl FnMut(&mut T, &mut Window, &mut Context<T>) + 'static,
+    ) -> Subscription {
+        let view = self.weak_entity();
+        let (subscription, activate) = window.scale_factor_observers.insert(
+            (),
+            Box::new(move |window, cx| {
+                view.update(cx, |view, cx| callback(view, window, cx))
+                    .is_ok()
+            }),
+        );
+        activate();
+        subscription
+    }
+
     /// Register a callback to be invoked when the window is activated or deactivated.
     pub fn observe_window_activation(
         &self,
@@ -388,6 +588,44 @@ impl<'a, T: 'static> Context<'a, T> {
         subscription
     }
 
+    /// Register a callback to be invoked when the window is maximized, minimized, made
+    /// fullscreen, or restored. See [`Window::window_state`].
+    pub fn observe_window_state(
+        &self,
+        window: &mut Window,
+        mut callback: impl FnMut(&mut T, &mut Window, &mut Context<T>) + 'static,
+    ) -> Subscription {
+        let view = self.weak_entity();
+        let (subscription, activate) = window.window_state_observers.insert(
+            (),
+            Box::new(move |window, cx| {
+                view.update(cx, |view, cx| callback(view, window, cx))
+                    .is_ok()
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Register a callback to be invoked when the window becomes visible or hidden, such
+    /// as when it is minimized or covered by other windows. See [`Window::is_visible`].
+    pub fn observe_window_visibility(
+        &self,
+        window: &mut Window,
+        mut callback: impl FnMut(&mut T, &mut Window, &mut Context<T>) + 'static,
+    ) -> Subscription {
+        let view = self.weak_entity();
+        let (subscription, activate) = window.visibility_observers.insert(
+            (),
+            Box::new(move |window, cx| {
+                view.update(cx, |view, cx| callback(view, window, cx))
+                    .is_ok()
+            }),
+        );
+        activate();
+        subscription
+    }
+
     /// Registers a callback to be invoked when the window appearance changes.
     pub fn observe_window_appearance(
         &self,
@@ -749,3 +987,134 @@ impl<T> BorrowMut<App> for Context<'_, T> {
         self.app
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        self as gpui, AppContext as _, Empty, LifecycleEvent, TestAppContext, VisualTestContext,
+    };
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        time::Duration,
+    };
+
+    #[gpui::test]
+    fn test_debounce_and_throttle(cx: &mut TestAppContext) {
+        let entity = cx.new(|_| Vec::<usize>::new());
+        let delay = Duration::from_millis(100);
+
+        for i in 0..3 {
+            entity.update(cx, |_, cx| {
+                cx.debounce("search", delay, move |calls, _| calls.push(i))
+            });
+            cx.executor().advance_clock(delay / 2);
+        }
+        entity.read_with(cx, |calls, _| assert!(calls.is_empty()));
+        cx.executor().advance_clock(delay);
+        entity.read_with(cx, |calls, _| assert_eq!(calls, &[2]));
+
+        entity.update(cx, |calls, _| calls.clear());
+        for i in 0..3 {
+            entity.update(cx, |_, cx| {
+                cx.throttle("resize", delay, move |calls, _| calls.push(i))
+            });
+        }
+        entity.read_with(cx, |calls, _| assert_eq!(calls, &[0]));
+        cx.executor().advance_clock(delay);
+        entity.read_with(cx, |calls, _| assert_eq!(calls, &[0, 2]));
+        cx.executor().advance_clock(delay);
+        entity.update(cx, |_, cx| {
+            cx.throttle("resize", delay, move |calls, _| calls.push(3))
+        });
+        entity.read_with(cx, |calls, _| assert_eq!(calls, &[0, 2, 3]));
+
+        entity.update(cx, |_, cx| {
+            cx.debounce("search", delay, move |calls, _| calls.push(4))
+        });
+        cx.update(|cx| assert_eq!(cx.debounced_tasks.len(), 1));
+        drop(entity);
+        cx.run_until_parked();
+        cx.update(|cx| assert!(cx.debounced_tasks.is_empty()));
+    }
+
+    #[gpui::test]
+    fn test_publish(cx: &mut TestAppContext) {
+        struct Saved(&'static str);
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let listener = cx.new(|cx| {
+            let received = received.clone();
+            cx.on_published(move |_: &mut (), event: &Saved, _| {
+                received.borrow_mut().push(event.0)
+            })
+            .detach();
+        });
+        let all_received = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            let all_received = all_received.clone();
+            cx.on_published(move |event: &Saved, _| all_received.borrow_mut().push(event.0))
+        });
+        let publisher = cx.new(|_| ());
+
+        publisher.update(cx, |_, cx| cx.publish(Saved("a.rs")));
+        assert_eq!(*received.borrow(), ["a.rs"]);
+
+        // Entities stop receiving events once they're released.
+        drop(listener);
+        publisher.update(cx, |_, cx| cx.publish(Saved("b.rs")));
+        assert_eq!(*received.borrow(), ["a.rs"]);
+        assert_eq!(*all_received.borrow(), ["a.rs", "b.rs"]);
+    }
+
+    #[gpui::test]
+    async fn test_lifecycle_events(cx: &mut TestAppContext) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            let events = events.clone();
+            cx.on_lifecycle_event(move |event, _| events.borrow_mut().push(event))
+        });
+        let first = cx.update(|cx| cx.open_window(Default::default(), |_, cx| cx.new(|_| Empty)));
+        let second = cx.update(|cx| cx.open_window(Default::default(), |_, cx| cx.new(|_| Empty)));
+
+        first
+            .unwrap()
+            .update(cx, |_, window, _| window.activate_window())
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(events.take(), [LifecycleEvent::DidBecomeActive]);
+
+        // Moving activation between the application's windows doesn't deactivate it.
+        let second = second.unwrap();
+        second
+            .update(cx, |_, window, _| window.activate_window())
+            .unwrap();
+        cx.run_until_parked();
+        assert!(events.take().is_empty());
+
+        VisualTestContext::from_window(*second, cx).deactivate_window();
+        assert_eq!(events.take(), [LifecycleEvent::DidResignActive]);
+
+        // Quitting can be cancelled by any handler.
+        assert!(cx.simulate_quit_request());
+        let allow_quit = Rc::new(Cell::new(false));
+        let consulted = Rc::new(Cell::new(0));
+        let _should_quit = cx.update(|cx| {
+            let allow_quit = allow_quit.clone();
+            let consulted = consulted.clone();
+            cx.on_should_quit(move |_| {
+                consulted.set(consulted.get() + 1);
+                let allow_quit = allow_quit.get();
+                async move { allow_quit }
+            })
+        });
+        assert!(!cx.update(|cx| cx.request_quit()).await);
+        allow_quit.set(true);
+        assert!(cx.update(|cx| cx.request_quit()).await);
+
+        // Requests to quit from the platform are routed through the same handlers.
+        assert!(!cx.simulate_quit_request());
+        cx.run_until_parked();
+        assert_eq!(consulted.get(), 3);
+    }
+}
@@ -55,6 +55,8 @@ impl Display for EntityId {
 
 pub(crate) struct EntityMap {
     entities: SecondaryMap<EntityId, Box<dyn Any>>,
+    /// The type and type name of every inserted entity, including those that are currently leased.
+    entity_types: SecondaryMap<EntityId, (TypeId, &'static str)>,
     pub accessed_entities: RefCell<FxHashSet<EntityId>>,
     ref_counts: Arc<RwLock<EntityRefCounts>>,
 }
@@ -70,6 +72,7 @@ impl EntityMap {
     pub fn new() -> Self {
         Self {
             entities: SecondaryMap::new(),
+            entity_types: SecondaryMap::new(),
             accessed_entities: RefCell::new(FxHashSet::default()),
             ref_counts: Arc::new(RwLock::new(EntityRefCounts {
                 counts: SlotMap::with_key(),
@@ -78,6 +81,7 @@ impl EntityMap {
                 leak_detector: LeakDetector {
                     next_handle_id: 0,
                     entity_handles: HashMap::default(),
+                    tracked_entities: None,
                 },
             })),
         }
@@ -86,9 +90,35 @@ impl EntityMap {
     /// Reserve a slot for an entity, which you can subsequently use with `insert`.
     pub fn reserve<T: 'static>(&self) -> Slot<T> {
         let id = self.ref_counts.write().counts.insert(1.into());
+        #[cfg(any(test, feature = "test-support"))]
+        self.ref_counts
+            .write()
+            .leak_detector
+            .entity_created(id, type_name::<T>());
         Slot(Entity::new(id, Arc::downgrade(&self.ref_counts)))
     }
 
+    /// Start recording every entity that's created, so that [`EntityMap::assert_no_leaked_entities`]
+    /// can report the ones that are never released.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn track_leaked_entities(&self) {
+        self.ref_counts
+            .write()
+            .leak_detector
+            .tracked_entities
+            .get_or_insert_with(HashMap::default);
+    }
+
+    /// Panics, listing the entities that are still alive, if any of the entities created since
+    /// [`EntityMap::track_leaked_entities`] was called haven't been released.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn assert_no_leaked_entities(&self) {
+        self.ref_counts
+            .write()
+            .leak_detector
+            .assert_no_leaked_entities();
+    }
+
     /// Insert an entity into a slot obtained by calling `reserve`.
     pub fn insert<T>(&mut self, slot: Slot<T>, entity: T) -> Entity<T>
     where
@@ -99,9 +129,44 @@ impl EntityMap {
 
         let handle = slot.0;
         self.entities.insert(handle.entity_id, Box::new(entity));
+        self.entity_types.insert(
+            handle.entity_id,
+            (TypeId::of::<T>(), std::any::type_name::<T>()),
+        );
         handle
     }
 
+    /// Returns weak handles to every entity of the given type that hasn't been released, in no
+    /// particular order.
+    pub fn entities_of_type<T: 'static>(&self) -> Vec<WeakEntity<T>> {
+        let ref_counts = self.ref_counts.read();
+        self.entity_types
+            .iter()
+            .filter(|(entity_id, (entity_type, _))| {
+                *entity_type == TypeId::of::<T>()
+                    && ref_counts
+                        .counts
+                        .get(*entity_id)
+                        .is_some_and(|count| count.load(SeqCst) > 0)
+            })
+            .map(|(entity_id, _)| WeakEntity {
+                any_entity: AnyWeakEntity {
+                    entity_id,
+                    entity_type: TypeId::of::<T>(),
+                    entity_ref_counts: Arc::downgrade(&self.ref_counts),
+                },
+                entity_type: PhantomData,
+            })
+            .collect()
+    }
+
+    /// Returns the type name of the given entity, if it hasn't been released.
+    pub fn type_name(&self, entity_id: EntityId) -> Option<&'static str> {
+        self.entity_types
+            .get(entity_id)
+            .map(|(_, type_name)| *type_name)
+    }
+
     /// Move an entity to the stack.
     #[track_caller]
     pub fn lease<'a, T>(&mut self, pointer: &'a Entity<T>) -> Lease<'a, T> {
@@ -127,6 +192,29 @@ impl EntityMap {
             .insert(lease.pointer.entity_id, lease.entity.take().unwrap());
     }
 
+    /// The number of leases dropped by unwinding panics that haven't been restored yet.
+    pub fn unwound_lease_count() -> usize {
+        UNWOUND_LEASES.with_borrow(|leases| leases.len())
+    }
+
+    /// Returns the entities of this map whose leases were dropped by a panic since
+    /// [`EntityMap::unwound_lease_count`] returned `since`, so that they survive panics that are
+    /// caught, such as by an error boundary.
+    pub fn restore_unwound_leases(&mut self, since: usize) {
+        let entity_map = Arc::downgrade(&self.ref_counts);
+        let leases = UNWOUND_LEASES.with_borrow_mut(|leases| {
+            let since = since.min(leases.len());
+            leases.drain(since..).collect::<Vec<_>>()
+        });
+        for lease in leases {
+            if Weak::ptr_eq(&lease.entity_map, &entity_map) {
+                self.entities.insert(lease.entity_id, lease.entity);
+            } else {
+                UNWOUND_LEASES.with_borrow_mut(|leases| leases.push(lease));
+            }
+        }
+    }
+
     pub fn read<T: 'static>(&self, entity: &Entity<T>) -> &T {
         self.assert_valid_context(entity);
         let mut accessed_entities = self.accessed_entities.borrow_mut();
@@ -170,6 +258,7 @@ impl EntityMap {
                     "dropped an entity that was referenced"
                 );
                 accessed_entities.remove(&entity_id);
+                self.entity_types.remove(entity_id);
                 // If the EntityId was allocated with `Context::reserve`,
                 // the entity may not have been inserted.
                 Some((entity_id, self.entities.remove(entity_id)?))
@@ -185,6 +274,18 @@ fn double_lease_panic<T>(operation: &str) -> ! {
     )
 }
 
+thread_local! {
+    /// Entities whose lease was dropped by a panic unwinding through their update, which are
+    /// returned to their map by [`EntityMap::restore_unwound_leases`].
+    static UNWOUND_LEASES: RefCell<Vec<UnwoundLease>> = const { RefCell::new(Vec::new()) };
+}
+
+struct UnwoundLease {
+    entity_id: EntityId,
+    entity_map: Weak<RwLock<EntityRefCounts>>,
+    entity: Box<dyn Any>,
+}
+
 pub(crate) struct Lease<'a, T> {
     entity: Option<Box<dyn Any>>,
     pub pointer: &'a Entity<T>,
@@ -207,8 +308,17 @@ impl<'a, T: 'static> core::ops::DerefMut for Lease<'a, T> {
 
 impl<'a, T> Drop for Lease<'a, T> {
     fn drop(&mut self) {
-        if self.entity.is_some() && !panicking() {
-            panic!("Leases must be ended with EntityMap::end_lease")
+        if let Some(entity) = self.entity.take() {
+            if !panicking() {
+                panic!("Leases must be ended with EntityMap::end_lease")
+            }
+            UNWOUND_LEASES.with_borrow_mut(|leases| {
+                leases.push(UnwoundLease {
+                    entity_id: self.pointer.entity_id,
+                    entity_map: self.pointer.any_entity.entity_map.clone(),
+                    entity,
+                })
+            });
         }
     }
 }
@@ -724,6 +834,8 @@ pub(crate) struct HandleId {
 pub(crate) struct LeakDetector {
     next_handle_id: u64,
     entity_handles: HashMap<EntityId, HashMap<HandleId, Option<backtrace::Backtrace>>>,
+    /// The entities created while tracking leaks, with their type and allocation site
+    tracked_entities: Option<HashMap<EntityId, (&'static str, backtrace::Backtrace)>>,
 }
 
 #[cfg(any(test, feature = "test-support"))]
@@ -740,6 +852,15 @@ impl LeakDetector {
         handle_id
     }
 
+    pub fn entity_created(&mut self, entity_id: EntityId, type_name: &'static str) {
+        if let Some(tracked_entities) = &mut self.tracked_entities {
+            tracked_entities.insert(
+                entity_id,
+                (type_name, backtrace::Backtrace::new_unresolved()),
+            );
+        }
+    }
+
     pub fn handle_released(&mut self, entity_id: EntityId, handle_id: HandleId) {
         let handles = self.entity_handles.entry(entity_id).or_default();
         handles.remove(&handle_id);
@@ -759,6 +880,34 @@ impl LeakDetector {
             panic!();
         }
     }
+
+    pub fn assert_no_leaked_entities(&mut self) {
+        let Some(tracked_entities) = self.tracked_entities.as_mut() else {
+            return;
+        };
+        let mut leaked_entities = tracked_entities
+            .iter_mut()
+            .filter(|(entity_id, _)| {
+                self.entity_handles
+                    .get(*entity_id)
+                    .map_or(false, |handles| !handles.is_empty())
+            })
+            .collect::<Vec<_>>();
+        if leaked_entities.is_empty() {
+            return;
+        }
+
+        leaked_entities.sort_by_key(|(entity_id, _)| **entity_id);
+        let mut message = format!("{} entities were not released:\n", leaked_entities.len());
+        for (entity_id, (type_name, backtrace)) in leaked_entities {
+            backtrace.resolve();
+            message.push_str(&format!(
+                "\n{} {:?} was allocated at:\n{:?}\n",
+                type_name, entity_id, backtrace
+            ));
+        }
+        panic!("{}", message);
+    }
 }
 
 #[cfg(test)]
@@ -816,4 +965,39 @@ mod test {
             vec![1],
         );
     }
+
+    #[test]
+    fn test_entities_of_type() {
+        let mut entity_map = EntityMap::new();
+
+        let slot = entity_map.reserve::<TestEntity>();
+        let first = entity_map.insert(slot, TestEntity { i: 1 });
+        let slot = entity_map.reserve::<TestEntity>();
+        let second = entity_map.insert(slot, TestEntity { i: 2 });
+        let slot = entity_map.reserve::<()>();
+        let _other = entity_map.insert(slot, ());
+        let _unused_slot = entity_map.reserve::<TestEntity>();
+
+        // Leased entities are included.
+        let lease = entity_map.lease(&first);
+        let mut entity_ids = entity_map
+            .entities_of_type::<TestEntity>()
+            .into_iter()
+            .map(|entity| entity.entity_id())
+            .collect::<Vec<_>>();
+        entity_ids.sort();
+        assert_eq!(entity_ids, vec![first.entity_id(), second.entity_id()]);
+        entity_map.end_lease(lease);
+
+        // Released entities aren't, even before they've been dropped.
+        drop(first);
+        let entities = entity_map.entities_of_type::<TestEntity>();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(
+            entities[0].upgrade().unwrap().entity_id(),
+            second.entity_id()
+        );
+        entity_map.take_dropped();
+        assert_eq!(entity_map.entities_of_type::<TestEntity>().len(), 1);
+    }
 }
@@ -1,15 +1,26 @@
 use crate::{
-    Action, AnyView, AnyWindowHandle, App, AppCell, AppContext, AsyncApp, AvailableSpace,
-    BackgroundExecutor, BorrowAppContext, Bounds, ClipboardItem, DrawPhase, Drawable, Element,
-    Empty, EventEmitter, ForegroundExecutor, Global, InputEvent, Keystroke, Modifiers,
-    ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels,
-    Platform, Point, Render, Result, Size, Task, TestDispatcher, TestPlatform,
-    TestScreenCaptureSource, TestWindow, TextSystem, VisualContext, Window, WindowBounds,
-    WindowHandle, WindowOptions,
+    assert_golden_image, assert_scene_snapshot, Action, AnnouncementPriority, AnyView,
+    AnyWindowHandle, App, AppCell, AppContext, AsyncApp, AvailableSpace, BackgroundExecutor,
+    BorrowAppContext, Bounds, ClipboardItem, DrawPhase, Drawable, Element, Empty, EventEmitter,
+    ForegroundExecutor, Global, InputEvent, Keystroke, Modifiers, ModifiersChangedEvent,
+    MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels, Platform, Point, Render,
+    Result, ScrollDelta, ScrollWheelEvent, Size, Task, TestDispatcher, TestPlatform,
+    TestScreenCaptureSource, TestWindow, TextSystem, TouchPhase, VisualContext, Window,
+    WindowBounds, WindowHandle, WindowOptions,
 };
 use anyhow::{anyhow, bail};
 use futures::{channel::oneshot, Stream, StreamExt};
-use std::{cell::RefCell, future::Future, ops::Deref, rc::Rc, sync::Arc, time::Duration};
+use image::RgbaImage;
+use rand::rngs::StdRng;
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    ops::Deref,
+    path::Path,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 /// A TestAppContext is provided to tests created with `#[gpui::test]`, it provides
 /// an implementation of `Context` with additional methods that are useful in tests.
@@ -27,6 +38,7 @@ pub struct TestAppContext {
     text_system: Arc<TextSystem>,
     fn_name: Option<&'static str>,
     on_quit: Rc<RefCell<Vec<Box<dyn FnOnce() + 'static>>>>,
+    detect_leaked_entities: Rc<Cell<bool>>,
 }
 
 impl AppContext for TestAppContext {
@@ -131,6 +143,7 @@ impl TestAppContext {
             text_system,
             fn_name,
             on_quit: Rc::new(RefCell::new(Vec::default())),
+            detect_leaked_entities: Rc::new(Cell::new(false)),
         }
     }
 
@@ -154,6 +167,20 @@ impl TestAppContext {
     pub fn quit(&self) {
         self.on_quit.borrow_mut().drain(..).for_each(|f| f());
         self.app.borrow_mut().shutdown();
+        if self.detect_leaked_entities.get() {
+            // Let pending tasks release the entities they hold before looking for leaks.
+            self.background_executor.run_until_parked();
+            self.app.borrow().entities.assert_no_leaked_entities();
+        }
+    }
+
+    /// Fails the test at teardown if any entity created after this call is still alive,
+    /// listing each one with the backtrace of where it was created. This catches reference
+    /// cycles, such as a subscription whose callback holds a strong handle to its subscriber.
+    /// Entities kept alive by globals are reported too.
+    pub fn detect_leaked_entities(&self) {
+        self.detect_leaked_entities.set(true);
+        self.app.borrow().entities.track_leaked_entities();
     }
 
     /// Register cleanup to run when the test ends.
@@ -285,6 +312,12 @@ impl TestAppContext {
         self.test_platform.simulate_new_path_selection(select_path);
     }
 
+    /// Simulates the user or the OS asking the application to quit, returning whether the
+    /// platform would quit right away.
+    pub fn simulate_quit_request(&self) -> bool {
+        self.test_platform.simulate_quit_request()
+    }
+
     /// Simulates clicking a button in an platform-level alert dialog.
     pub fn simulate_prompt_answer(&self, button_ix: usize) {
         self.test_platform.simulate_prompt_answer(button_ix);
@@ -300,6 +333,11 @@ impl TestAppContext {
         self.test_platform.opened_url.borrow().clone()
     }
 
+    /// All the text that has been announced to assistive technology during this test.
+    pub fn announcements(&self) -> Vec<(String, AnnouncementPriority)> {
+        self.test_platform.announcements.borrow().clone()
+    }
+
     /// Simulates the user resizing the window to the new size.
     pub fn simulate_window_resize(&self, window_handle: AnyWindowHandle, size: Size<Pixels>) {
         self.test_window(window_handle).simulate_resize(size);
@@ -372,6 +410,27 @@ impl TestAppContext {
         self.background_executor.run_until_parked()
     }
 
+    /// Moves the test's clock forward, running timers as they become due, and then draws a
+    /// frame in each window so that animations advance to the new time.
+    pub fn advance_clock(&mut self, duration: Duration) {
+        self.background_executor.advance_clock(duration);
+        for window in self.windows() {
+            self.test_window(window).simulate_frame();
+        }
+        self.run_until_parked();
+    }
+
+    /// The current time according to the test's clock, which only moves when it's advanced.
+    pub fn now(&self) -> Instant {
+        self.background_executor.now()
+    }
+
+    /// Returns a random number generator seeded by the `SEED` environment variable, so that
+    /// randomized tests can be reproduced.
+    pub fn rng(&self) -> StdRng {
+        self.background_executor.rng()
+    }
+
     /// Simulate dispatching an action to the currently focused node in the window.
     pub fn dispatch_action<A>(&mut self, window: AnyWindowHandle, action: A)
     where
@@ -760,6 +819,58 @@ impl VisualTestContext {
         });
     }
 
+    /// Simulate clicking the primary mouse button `click_count` times in quick succession at the
+    /// given point, as in a double or triple click. Each press reports the number of clicks so far.
+    pub fn simulate_click_sequence(
+        &mut self,
+        position: Point<Pixels>,
+        click_count: usize,
+        modifiers: Modifiers,
+    ) {
+        self.simulate_mouse_move(position, None, modifiers);
+        for click_count in 1..=click_count {
+            self.simulate_event(MouseDownEvent {
+                position,
+                modifiers,
+                button: MouseButton::Left,
+                click_count,
+                first_mouse: false,
+            });
+            self.simulate_event(MouseUpEvent {
+                position,
+                modifiers,
+                button: MouseButton::Left,
+                click_count,
+            });
+        }
+    }
+
+    /// Simulate dragging with the primary mouse button from one point to another, moving the
+    /// mouse through intermediate points so that drag thresholds are crossed along the way.
+    pub fn simulate_drag(&mut self, from: Point<Pixels>, to: Point<Pixels>, modifiers: Modifiers) {
+        const STEPS: usize = 4;
+
+        self.simulate_mouse_move(from, None, modifiers);
+        self.simulate_mouse_down(from, MouseButton::Left, modifiers);
+        for step in 1..=STEPS {
+            let progress = step as f32 / STEPS as f32;
+            let position = from + (to - from) * progress;
+            self.simulate_mouse_move(position, MouseButton::Left, modifiers);
+        }
+        self.simulate_mouse_up(to, MouseButton::Left, modifiers);
+    }
+
+    /// Simulate scrolling by the given number of pixels with the mouse at the given point.
+    pub fn simulate_scroll(&mut self, position: Point<Pixels>, delta: Point<Pixels>) {
+        self.simulate_mouse_move(position, None, Modifiers::default());
+        self.simulate_event(ScrollWheelEvent {
+            position,
+            delta: ScrollDelta::Pixels(delta),
+            modifiers: Modifiers::default(),
+            touch_phase: TouchPhase::Moved,
+        });
+    }
+
     /// Simulate a modifiers changed event
     pub fn simulate_modifiers_change(&mut self, modifiers: Modifiers) {
         self.simulate_event(ModifiersChangedEvent { modifiers })
@@ -770,6 +881,44 @@ impl VisualTestContext {
         self.simulate_window_resize(self.window, size)
     }
 
+    /// Rasterizes the most recently rendered frame of this window on the CPU, so that
+    /// tests can assert on real pixel output without a GPU. Text, icons and images are
+    /// included, but surfaces are not.
+    pub fn rasterize_window(&mut self) -> RgbaImage {
+        self.update(|window, _| window.capture_screenshot().unwrap())
+    }
+
+    /// Rasterizes the most recently rendered frame of this window and compares it against the
+    /// reference image at `path`, panicking if any pixel differs perceptually by more than
+    /// `threshold`, from 0 (identical) to 1.
+    ///
+    /// On failure the rendered image and a diff are written next to the reference as
+    /// `name.actual.png` and `name.diff.png`. Run with `UPDATE_GOLDEN_IMAGES=1` to create or
+    /// replace the reference with the rendered image.
+    #[track_caller]
+    pub fn assert_golden_image(&mut self, path: impl AsRef<Path>, threshold: f32) {
+        let image = self.rasterize_window();
+        assert_golden_image(&image, path.as_ref(), threshold);
+    }
+
+    /// Serializes the most recently rendered frame of this window into a stable text format that
+    /// lists the quads, glyph runs, paths and sprites it draws, with their coordinates and colors,
+    /// so that tests can assert on draw output without a GPU or the platform's renderer.
+    pub fn scene_snapshot(&mut self) -> String {
+        self.update(|window, _| window.scene_snapshot())
+    }
+
+    /// Serializes the most recently rendered frame of this window and compares it against the
+    /// snapshot at `path`, panicking with the first line that differs.
+    ///
+    /// On failure the serialized frame is written next to the snapshot as `name.actual.ext`. Run
+    /// with `UPDATE_SCENE_SNAPSHOTS=1` to create or replace the snapshot.
+    #[track_caller]
+    pub fn assert_scene_snapshot(&mut self, path: impl AsRef<Path>) {
+        let snapshot = self.scene_snapshot();
+        assert_scene_snapshot(&snapshot, path.as_ref());
+    }
+
     /// debug_bounds returns the bounds of the element with the given selector.
     pub fn debug_bounds(&mut self, selector: &'static str) -> Option<Bounds<Pixels>> {
         self.update(|window, _| window.rendered_frame.debug_bounds.get(selector).copied())
@@ -1001,3 +1150,85 @@ impl AnyWindowHandle {
             .unwrap()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        self as gpui, div, point, px, AppContext as _, Context, Entity, InteractiveElement,
+        IntoElement, Modifiers, MouseButton, Render, ScrollDelta, Styled, TestAppContext, Window,
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    struct EventLog {
+        events: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Render for EventLog {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let events = self.events.clone();
+            let log = move |event: String| events.borrow_mut().push(event);
+            div()
+                .size_full()
+                .on_mouse_down(MouseButton::Left, {
+                    let log = log.clone();
+                    move |event, _, _| log(format!("down {}", event.click_count))
+                })
+                .on_mouse_up(MouseButton::Left, {
+                    let log = log.clone();
+                    move |event, _, _| log(format!("up {}", event.click_count))
+                })
+                .on_mouse_move({
+                    let log = log.clone();
+                    move |event, _, _| {
+                        if event.dragging() {
+                            log(format!("drag {}", event.position.x.0))
+                        }
+                    }
+                })
+                .on_scroll_wheel(move |event, _, _| {
+                    if let ScrollDelta::Pixels(delta) = event.delta {
+                        log(format!("scroll {}", delta.y.0))
+                    }
+                })
+        }
+    }
+
+    #[gpui::test]
+    fn test_simulated_interactions(cx: &mut TestAppContext) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let (_, cx) = cx.add_window_view(|_, _| EventLog {
+            events: events.clone(),
+        });
+
+        cx.simulate_click_sequence(point(px(10.), px(10.)), 2, Modifiers::default());
+        assert_eq!(*events.borrow(), ["down 1", "up 1", "down 2", "up 2"]);
+        events.borrow_mut().clear();
+
+        cx.simulate_drag(
+            point(px(10.), px(10.)),
+            point(px(50.), px(10.)),
+            Modifiers::default(),
+        );
+        assert_eq!(
+            *events.borrow(),
+            ["down 1", "drag 20", "drag 30", "drag 40", "drag 50", "up 1"]
+        );
+        events.borrow_mut().clear();
+
+        cx.simulate_scroll(point(px(10.), px(10.)), point(px(0.), px(-30.)));
+        assert_eq!(*events.borrow(), ["scroll -30"]);
+    }
+
+    struct Cycle {
+        _this: Option<Entity<Cycle>>,
+    }
+
+    #[gpui::test]
+    #[should_panic(expected = "1 entities were not released")]
+    fn test_detect_leaked_entities(cx: &mut TestAppContext) {
+        cx.detect_leaked_entities();
+        drop(cx.new(|_| Cycle { _this: None }));
+        let cycle = cx.new(|_| Cycle { _this: None });
+        cycle.update(cx, |cycle, cx| cycle._this = Some(cx.entity()));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{size, DevicePixels, Result, SharedString, Size};
+use crate::{size, DevicePixels, Result, ScaledPixels, SharedString, Size};
 use smallvec::SmallVec;
 
 use image::{Delay, Frame};
@@ -28,6 +28,37 @@ impl AssetSource for () {
     }
 }
 
+/// The kinds of assets an application can bundle, determined by the extension of their path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AssetKind {
+    /// A TrueType or OpenType font
+    Font,
+    /// An SVG, which is rendered as a monochrome icon by [`crate::svg()`]
+    Svg,
+    /// A raster image, which can be rendered with [`crate::img()`]
+    Image,
+    /// A sound
+    Sound,
+    /// Any other asset, such as a theme or a prompt template
+    Other,
+}
+
+impl AssetKind {
+    /// Returns the kind of the asset at the given path.
+    pub fn of(path: &str) -> Self {
+        let Some((_, extension)) = path.rsplit_once('.') else {
+            return Self::Other;
+        };
+        match extension.to_ascii_lowercase().as_str() {
+            "ttf" | "otf" | "ttc" => Self::Font,
+            "svg" => Self::Svg,
+            "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "ico" | "tif" | "tiff" => Self::Image,
+            "wav" | "mp3" | "ogg" | "flac" => Self::Sound,
+            _ => Self::Other,
+        }
+    }
+}
+
 /// A unique identifier for the image cache
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct ImageId(pub usize);
@@ -36,6 +67,58 @@ pub struct ImageId(pub usize);
 pub(crate) struct RenderImageParams {
     pub(crate) image_id: ImageId,
     pub(crate) frame_index: usize,
+    /// The size the image is downscaled to before it's uploaded, or `None` for its native size
+    pub(crate) downscaled_size: Option<Size<DevicePixels>>,
+}
+
+/// How an image is filtered when it's drawn at less than half its native size, see
+/// [`crate::StyledImage::filter`]. Images are downscaled in the background, and drawn at their
+/// native size until they're ready.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ImageFilter {
+    /// Sample the image at its native size. This is the cheapest option, but images drawn far
+    /// below their native size shimmer and look aliased.
+    Linear,
+    /// Sample the smallest mip level that's at least as large as the drawn size, where each
+    /// level is area-averaged to half the size of the previous one. Nearby sizes share a mip
+    /// level, so an image that's animated or drawn at several sizes is only downscaled a few
+    /// times.
+    #[default]
+    Mipmap,
+    /// Area-average the image down to exactly the size it's drawn at. This is the sharpest
+    /// option, but the image is downscaled again for every size it's drawn at.
+    Area,
+}
+
+impl ImageFilter {
+    /// Returns the size an image should be downscaled to before it's drawn at the given size,
+    /// or `None` if it should be drawn at its native size. The image's aspect ratio is kept.
+    pub(crate) fn downscaled_size(
+        self,
+        native_size: Size<DevicePixels>,
+        drawn_size: Size<ScaledPixels>,
+    ) -> Option<Size<DevicePixels>> {
+        let scale = (drawn_size.width.0 / native_size.width.0 as f32)
+            .max(drawn_size.height.0 / native_size.height.0 as f32);
+        if !(scale > 0. && scale <= 0.5) {
+            return None;
+        }
+
+        match self {
+            Self::Linear => None,
+            Self::Mipmap => {
+                let level = ((1. / scale).log2().floor() as u32).min(16);
+                Some(size(
+                    DevicePixels((native_size.width.0 >> level).max(1)),
+                    DevicePixels((native_size.height.0 >> level).max(1)),
+                ))
+            }
+            Self::Area => Some(size(
+                DevicePixels(((native_size.width.0 as f32 * scale).ceil() as i32).max(1)),
+                DevicePixels(((native_size.height.0 as f32 * scale).ceil() as i32).max(1)),
+            )),
+        }
+    }
 }
 
 /// A cached and processed image, in BGRA format
@@ -77,6 +160,50 @@ impl RenderImage {
         size(width.into(), height.into())
     }
 
+    /// Area-averages a frame of this image down to the given size, which must be no larger than
+    /// the image. Colors are weighted by alpha, so that transparent pixels don't darken the
+    /// edges of what's around them.
+    pub(crate) fn downscale(&self, frame_index: usize, target_size: Size<DevicePixels>) -> Vec<u8> {
+        let buffer = self.data[frame_index].buffer();
+        let (width, height) = buffer.dimensions();
+        let (target_width, target_height) =
+            (target_size.width.0 as u32, target_size.height.0 as u32);
+        let pixels = buffer.as_raw();
+
+        let mut bytes = Vec::with_capacity(target_width as usize * target_height as usize * 4);
+        for y in 0..target_height {
+            let rows = source_range(y, target_height, height);
+            for x in 0..target_width {
+                let columns = source_range(x, target_width, width);
+                let mut sums = [0u64; 4];
+                for source_y in rows.clone() {
+                    for source_x in columns.clone() {
+                        let pixel = (source_y * width + source_x) as usize * 4;
+                        let alpha = pixels[pixel + 3] as u64;
+                        for channel in 0..3 {
+                            sums[channel] += pixels[pixel + channel] as u64 * alpha;
+                        }
+                        sums[3] += alpha;
+                    }
+                }
+
+                let count = (rows.len() * columns.len()) as u64;
+                let alpha = sums[3];
+                if alpha == 0 {
+                    bytes.extend_from_slice(&[0; 4]);
+                } else {
+                    bytes.extend_from_slice(&[
+                        ((sums[0] + alpha / 2) / alpha) as u8,
+                        ((sums[1] + alpha / 2) / alpha) as u8,
+                        ((sums[2] + alpha / 2) / alpha) as u8,
+                        ((alpha + count / 2) / count) as u8,
+                    ]);
+                }
+            }
+        }
+        bytes
+    }
+
     /// Get the delay of this frame from the previous
     pub fn delay(&self, frame_index: usize) -> Delay {
         self.data[frame_index].delay()
@@ -88,6 +215,13 @@ impl RenderImage {
     }
 }
 
+/// Returns the source pixels covered by a pixel of a downscaled row or column.
+fn source_range(index: u32, target_len: u32, source_len: u32) -> std::ops::Range<u32> {
+    let start = (index as u64 * source_len as u64 / target_len as u64) as u32;
+    let end = ((index as u64 + 1) * source_len as u64).div_ceil(target_len as u64) as u32;
+    start..end.max(start + 1)
+}
+
 impl fmt::Debug for RenderImage {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ImageData")
@@ -96,3 +230,81 @@ impl fmt::Debug for RenderImage {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{self as gpui, TestAppContext};
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn test_image_downscaling() {
+        let native_size = size(DevicePixels(400), DevicePixels(200));
+        let drawn_size = size(ScaledPixels(90.), ScaledPixels(45.));
+        assert_eq!(
+            ImageFilter::Linear.downscaled_size(native_size, drawn_size),
+            None
+        );
+        assert_eq!(
+            ImageFilter::Mipmap.downscaled_size(native_size, drawn_size),
+            Some(size(DevicePixels(100), DevicePixels(50)))
+        );
+        assert_eq!(
+            ImageFilter::Area.downscaled_size(native_size, drawn_size),
+            Some(size(DevicePixels(90), DevicePixels(45)))
+        );
+        assert_eq!(
+            ImageFilter::Mipmap
+                .downscaled_size(native_size, size(ScaledPixels(300.), ScaledPixels(150.))),
+            None
+        );
+
+        // A 4x2 image of opaque white and transparent black pixels, in BGRA.
+        let pixels = [
+            [255, 255, 255, 255],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [255, 255, 255, 255],
+            [255, 255, 255, 255],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+        ]
+        .concat();
+        let buffer = image::RgbaImage::from_raw(4, 2, pixels).unwrap();
+        let image = RenderImage::new([Frame::new(buffer)]);
+        let downscaled = image.downscale(0, size(DevicePixels(2), DevicePixels(1)));
+        // Transparent pixels don't darken the average color.
+        assert_eq!(downscaled, [255, 255, 255, 191, 0, 0, 0, 0]);
+    }
+
+    #[gpui::test]
+    fn test_reload_assets(cx: &mut TestAppContext) {
+        assert_eq!(AssetKind::of("fonts/plex-mono/Mono.ttf"), AssetKind::Font);
+        assert_eq!(AssetKind::of("icons/file.svg"), AssetKind::Svg);
+        assert_eq!(AssetKind::of("images/logo.PNG"), AssetKind::Image);
+        assert_eq!(AssetKind::of("sounds/joined_call.wav"), AssetKind::Sound);
+        assert_eq!(
+            AssetKind::of("prompts/content_prompt.hbs"),
+            AssetKind::Other
+        );
+
+        let reloaded = Rc::new(RefCell::new(Vec::new()));
+        let subscription = cx.update(|cx| {
+            let reloaded = reloaded.clone();
+            cx.on_assets_reloaded(move |paths, _| reloaded.borrow_mut().extend_from_slice(paths))
+        });
+        cx.update(|cx| cx.reload_assets(vec!["icons/file.svg".into(), "images/logo.png".into()]));
+        assert_eq!(
+            reloaded.take(),
+            [
+                SharedString::from("icons/file.svg"),
+                SharedString::from("images/logo.png")
+            ]
+        );
+
+        drop(subscription);
+        cx.update(|cx| cx.reload_assets(vec!["icons/file.svg".into()]));
+        assert!(reloaded.take().is_empty());
+    }
+}
@@ -33,7 +33,11 @@ pub(crate) fn swap_rgba_pa_to_bgra(color: &mut [u8]) {
     }
 }
 
-/// An RGBA color
+/// An RGBA color in the sRGB color space
+///
+/// Colors are blended in sRGB's gamma-encoded space rather than in linear light. Colors outside
+/// of the sRGB gamut can't be represented, so wide-gamut displays show the same colors as sRGB
+/// displays.
 #[derive(PartialEq, Clone, Copy, Default)]
 pub struct Rgba {
     /// The red component of the color, in the range 0.0 to 1.0
@@ -237,7 +241,7 @@ impl TryFrom<&'_ str> for Rgba {
     }
 }
 
-/// An HSLA color
+/// An HSLA color in the sRGB color space, see [`Rgba`]
 #[derive(Default, Copy, Clone, Debug)]
 #[repr(C)]
 pub struct Hsla {
@@ -0,0 +1,229 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use collections::FxHashMap;
+use parking_lot::Mutex;
+
+use crate::{PlatformInput, WindowId};
+
+/// How many actions and input events are kept for each window.
+const HISTORY_LEN: usize = 32;
+
+/// What the user was doing in the window they last interacted with, for inclusion in crash
+/// reports. See [`CrashContext::report`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CrashReport {
+    /// The names of the actions most recently dispatched in the window, oldest first
+    pub actions: Vec<String>,
+    /// Descriptions of the input events the window most recently received, oldest first.
+    /// Keystrokes that type text are recorded without their key.
+    pub input_events: Vec<String>,
+    /// The type names of the views containing the focused element when the window was last
+    /// drawn, from the root view inwards
+    pub focused_views: Vec<&'static str>,
+}
+
+/// A handle to the UI state that's recorded for crash reports, see [`crate::App::crash_context`].
+/// Nothing is recorded until it's enabled with [`crate::App::set_crash_context_enabled`], which
+/// applications should only do once the user has consented to sending diagnostics.
+///
+/// It can be sent to other threads, so that a panic hook can include the report in the crash
+/// report it writes.
+#[derive(Clone, Default)]
+pub struct CrashContext(Arc<Mutex<CrashContextState>>);
+
+#[derive(Default)]
+struct CrashContextState {
+    enabled: bool,
+    current_window: Option<WindowId>,
+    windows: FxHashMap<WindowId, WindowHistory>,
+}
+
+#[derive(Default)]
+struct WindowHistory {
+    actions: VecDeque<String>,
+    input_events: VecDeque<String>,
+    focused_views: Vec<&'static str>,
+}
+
+impl CrashContext {
+    /// Returns what was recorded for the window the user last interacted with, or `None` if
+    /// recording isn't enabled.
+    ///
+    /// This is safe to call from a panic hook: if the panic happened while the context was being
+    /// updated, `None` is returned instead of deadlocking.
+    pub fn report(&self) -> Option<CrashReport> {
+        let state = self.0.try_lock()?;
+        if !state.enabled {
+            return None;
+        }
+        let window = state
+            .current_window
+            .and_then(|window_id| state.windows.get(&window_id));
+        Some(match window {
+            Some(window) => CrashReport {
+                actions: window.actions.iter().cloned().collect(),
+                input_events: window.input_events.iter().cloned().collect(),
+                focused_views: window.focused_views.clone(),
+            },
+            None => CrashReport::default(),
+        })
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.0.lock().enabled
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        let mut state = self.0.lock();
+        state.enabled = enabled;
+        if !enabled {
+            state.current_window = None;
+            state.windows.clear();
+        }
+    }
+
+    /// Records an action dispatched in the given window, or in the current window for actions
+    /// dispatched outside of any window.
+    pub(crate) fn record_action(&self, window_id: Option<WindowId>, name: &str) {
+        self.update(window_id, |window| {
+            push_bounded(&mut window.actions, name.to_string())
+        });
+    }
+
+    pub(crate) fn record_input(&self, window_id: WindowId, event: &PlatformInput) {
+        let Some(description) = describe_input(event) else {
+            return;
+        };
+        let mut state = self.0.lock();
+        if !state.enabled {
+            return;
+        }
+        state.current_window = Some(window_id);
+        let input_events = &mut state.windows.entry(window_id).or_default().input_events;
+        // Scrolling and dragging files produce bursts of identical events.
+        if input_events.back() != Some(&description) {
+            push_bounded(input_events, description);
+        }
+    }
+
+    pub(crate) fn set_focused_views(&self, window_id: WindowId, focused_views: Vec<&'static str>) {
+        self.update(Some(window_id), |window| {
+            window.focused_views = focused_views
+        });
+    }
+
+    pub(crate) fn remove_window(&self, window_id: WindowId) {
+        let mut state = self.0.lock();
+        state.windows.remove(&window_id);
+        if state.current_window == Some(window_id) {
+            state.current_window = None;
+        }
+    }
+
+    fn update(&self, window_id: Option<WindowId>, f: impl FnOnce(&mut WindowHistory)) {
+        let mut state = self.0.lock();
+        if !state.enabled {
+            return;
+        }
+        if let Some(window_id) = window_id.or(state.current_window) {
+            f(state.windows.entry(window_id).or_default());
+        }
+    }
+}
+
+fn push_bounded(history: &mut VecDeque<String>, entry: String) {
+    if history.len() == HISTORY_LEN {
+        history.pop_front();
+    }
+    history.push_back(entry);
+}
+
+fn describe_input(event: &PlatformInput) -> Option<String> {
+    match event {
+        PlatformInput::KeyDown(event) => {
+            // Only keystrokes that don't type anything, like shortcuts, are recorded in full, so
+            // that reports don't contain what the user was writing.
+            if event.keystroke.key_char.is_none() {
+                Some(format!("key down {}", event.keystroke.unparse()))
+            } else {
+                Some("key down".to_string())
+            }
+        }
+        PlatformInput::MouseDown(event) => Some(format!(
+            "{:?} mouse down ({} clicks)",
+            event.button, event.click_count
+        )),
+        PlatformInput::MouseUp(event) => Some(format!("{:?} mouse up", event.button)),
+        PlatformInput::ScrollWheel(_) => Some("scroll wheel".to_string()),
+        PlatformInput::FileDrop(_) => Some("file drop".to_string()),
+        PlatformInput::KeyUp(_)
+        | PlatformInput::ModifiersChanged(_)
+        | PlatformInput::MouseMove(_)
+        | PlatformInput::MouseExited(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        self as gpui, actions, div, AppContext as _, Context, FocusHandle, InteractiveElement as _,
+        IntoElement, Modifiers, Render, TestAppContext, Window,
+    };
+
+    actions!(test, [Save]);
+
+    struct Editor {
+        focus_handle: FocusHandle,
+    }
+
+    impl Render for Editor {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .track_focus(&self.focus_handle)
+                .on_action(cx.listener(|_, _: &Save, _, _| {}))
+        }
+    }
+
+    #[gpui::test]
+    fn test_crash_context(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |window, cx| {
+                cx.new(|cx| {
+                    let focus_handle = cx.focus_handle();
+                    window.focus(&focus_handle);
+                    Editor { focus_handle }
+                })
+            })
+            .unwrap()
+        });
+        let cx = &mut gpui::VisualTestContext::from_window(*window, cx);
+        let crash_context = cx.update(|_, cx| cx.crash_context());
+
+        // Nothing is recorded until the user has consented.
+        cx.dispatch_action(Save);
+        assert_eq!(crash_context.report(), None);
+
+        cx.update(|_, cx| cx.set_crash_context_enabled(true));
+        cx.simulate_click(Default::default(), Modifiers::default());
+        cx.simulate_keystrokes("a ctrl-s");
+        cx.dispatch_action(Save);
+        cx.update(|window, _| window.refresh());
+        cx.run_until_parked();
+
+        let report = crash_context.report().unwrap();
+        assert_eq!(report.actions, ["test::Save"]);
+        assert_eq!(
+            report.input_events,
+            [
+                "Left mouse down (1 clicks)",
+                "Left mouse up",
+                "key down",
+                "key down ctrl-s"
+            ]
+        );
+        assert!(report.focused_views[0].ends_with("Editor"));
+
+        cx.update(|_, cx| cx.set_crash_context_enabled(false));
+        assert_eq!(crash_context.report(), None);
+    }
+}
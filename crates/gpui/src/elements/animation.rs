@@ -108,14 +108,17 @@ impl<E: IntoElement + 'static> Element for AnimationElement<E> {
         cx: &mut App,
     ) -> (crate::LayoutId, Self::RequestLayoutState) {
         window.with_element_state(global_id.unwrap(), |state, window| {
-            let state = state.unwrap_or_else(|| AnimationState {
-                start: Instant::now(),
-            });
-            let mut delta =
-                state.start.elapsed().as_secs_f32() / self.animation.duration.as_secs_f32();
+            // Consult the executor's clock so that animations can be driven by tests.
+            let now = cx.background_executor().now();
+            let state = state.unwrap_or(AnimationState { start: now });
+            let mut delta = now.saturating_duration_since(state.start).as_secs_f32()
+                / self.animation.duration.as_secs_f32();
 
             let mut done = false;
-            if delta > 1.0 {
+            if cx.accessibility_preferences().reduce_motion {
+                done = true;
+                delta = 1.0;
+            } else if delta > 1.0 {
                 if self.animation.oneshot {
                     done = true;
                     delta = 1.0;
@@ -215,3 +218,49 @@ mod easing {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, rc::Rc, time::Duration};
+
+    use super::*;
+    use crate::{self as gpui, div, AppContext as _, Context, Render, TestAppContext};
+
+    struct AnimatedView {
+        delta: Rc<Cell<f32>>,
+    }
+
+    impl Render for AnimatedView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let delta = self.delta.clone();
+            div().with_animation(
+                "animation",
+                Animation::new(Duration::from_secs(1)),
+                move |div, value| {
+                    delta.set(value);
+                    div
+                },
+            )
+        }
+    }
+
+    #[gpui::test]
+    fn test_animation_follows_test_clock(cx: &mut TestAppContext) {
+        let delta = Rc::new(Cell::new(-1.));
+        cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|_| AnimatedView {
+                    delta: delta.clone(),
+                })
+            })
+            .unwrap()
+        });
+        assert_eq!(delta.get(), 0.);
+
+        cx.advance_clock(Duration::from_millis(250));
+        assert_eq!(delta.get(), 0.25);
+
+        cx.advance_clock(Duration::from_secs(1));
+        assert_eq!(delta.get(), 1.);
+    }
+}
@@ -0,0 +1,198 @@
+use std::{cell::RefCell, future::Future, pin::Pin, rc::Rc};
+
+use crate::{
+    AnyElement, App, Bounds, Element, ElementId, GlobalElementId, IntoElement, LayoutId, Pixels,
+    Task, Window,
+};
+
+/// Builds an [`AsyncElement`], which draws `placeholder` until `future` resolves and then draws
+/// the element returned by `render` for its output.
+///
+/// The future is only spawned the first time the element is drawn with the given id, and is
+/// dropped along with its output when the element stops being drawn, so the `future` passed on
+/// later frames is ignored.
+pub fn async_element<T: 'static, E: IntoElement>(
+    id: impl Into<ElementId>,
+    future: impl Future<Output = T> + 'static,
+    placeholder: impl IntoElement,
+    render: impl FnOnce(&T, &mut Window, &mut App) -> E + 'static,
+) -> AsyncElement<T> {
+    AsyncElement {
+        id: id.into(),
+        future: Some(Box::pin(future)),
+        placeholder: Some(placeholder.into_any_element()),
+        render: Some(Box::new(move |output, window, cx| {
+            render(output, window, cx).into_any_element()
+        })),
+    }
+}
+
+/// An element that draws the output of a future once it resolves, and a placeholder until then.
+/// The view containing it is notified when the future resolves.
+pub struct AsyncElement<T> {
+    id: ElementId,
+    future: Option<Pin<Box<dyn Future<Output = T>>>>,
+    placeholder: Option<AnyElement>,
+    render: Option<Box<dyn FnOnce(&T, &mut Window, &mut App) -> AnyElement>>,
+}
+
+struct AsyncElementState<T> {
+    output: Rc<RefCell<Option<T>>>,
+    /// Dropping the state cancels the future if it hasn't resolved yet.
+    _task: Task<()>,
+}
+
+impl<T: 'static> IntoElement for AsyncElement<T> {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl<T: 'static> Element for AsyncElement<T> {
+    type RequestLayoutState = AnyElement;
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        Some(self.id.clone())
+    }
+
+    fn request_layout(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let output = window.with_element_state(
+            global_id.unwrap(),
+            |state: Option<AsyncElementState<T>>, window| {
+                let state = state.unwrap_or_else(|| {
+                    let output = Rc::new(RefCell::new(None));
+                    let future = self.future.take().unwrap();
+                    let view = window.current_view();
+                    let task = window.spawn(cx, {
+                        let output = output.clone();
+                        |mut cx| async move {
+                            let value = future.await;
+                            output.replace(Some(value));
+                            cx.update(|_, cx| cx.notify(view)).ok();
+                        }
+                    });
+                    AsyncElementState {
+                        output,
+                        _task: task,
+                    }
+                });
+                (state.output.clone(), state)
+            },
+        );
+
+        let mut element = match output.borrow().as_ref() {
+            Some(output) => (self.render.take().unwrap())(output, window, cx),
+            None => self.placeholder.take().unwrap(),
+        };
+        (element.request_layout(window, cx), element)
+    }
+
+    fn prepaint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Bounds<Pixels>,
+        element: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        element.prepaint(window, cx);
+    }
+
+    fn paint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Bounds<Pixels>,
+        element: &mut Self::RequestLayoutState,
+        _: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        element.paint(window, cx);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        self as gpui, div, AppContext as _, Context, ParentElement as _, Render, SharedString,
+        TestAppContext,
+    };
+    use futures::channel::oneshot;
+
+    struct TestView {
+        show: bool,
+        receiver: Option<oneshot::Receiver<SharedString>>,
+        renders: Rc<RefCell<Vec<SharedString>>>,
+    }
+
+    impl Render for TestView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let mut root = div();
+            if self.show {
+                let receiver = self.receiver.take();
+                let renders = self.renders.clone();
+                renders.borrow_mut().push("view".into());
+                root = root.child(async_element(
+                    "async",
+                    async move { receiver.unwrap().await.unwrap_or_default() },
+                    div(),
+                    move |output: &SharedString, _, _| {
+                        renders.borrow_mut().push(output.clone());
+                        div()
+                    },
+                ));
+            }
+            root
+        }
+    }
+
+    #[gpui::test]
+    fn test_async_element(cx: &mut TestAppContext) {
+        let (sender, receiver) = oneshot::channel();
+        let renders = Rc::new(RefCell::new(Vec::new()));
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|_| TestView {
+                    show: true,
+                    receiver: Some(receiver),
+                    renders: renders.clone(),
+                })
+            })
+            .unwrap()
+        });
+        cx.run_until_parked();
+        assert_eq!(renders.take(), ["view"]);
+
+        // The view is redrawn with the output once the future resolves.
+        sender.send("done".into()).unwrap();
+        cx.run_until_parked();
+        assert_eq!(renders.take(), ["view", "done"]);
+
+        // Once the element stops being drawn, its output is dropped and a new future is spawned
+        // when it's drawn again, which is cancelled when it's removed before resolving.
+        let (sender, receiver) = oneshot::channel();
+        window
+            .update(cx, |view, _, _| view.receiver = Some(receiver))
+            .unwrap();
+        for show in [false, true, false] {
+            window
+                .update(cx, |view, _, cx| {
+                    view.show = show;
+                    cx.notify();
+                })
+                .unwrap();
+            cx.run_until_parked();
+        }
+        assert_eq!(renders.take(), ["view"]);
+        assert!(sender.is_canceled());
+    }
+}
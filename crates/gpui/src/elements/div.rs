@@ -16,19 +16,20 @@
 //! constructed by combining these two systems into an all-in-one element.
 
 use crate::{
-    point, px, size, Action, AnyDrag, AnyElement, AnyTooltip, AnyView, App, Bounds, ClickEvent,
-    DispatchPhase, Element, ElementId, Entity, FocusHandle, Global, GlobalElementId, Hitbox,
-    HitboxId, IntoElement, IsZero, KeyContext, KeyDownEvent, KeyUpEvent, LayoutId,
-    ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
-    ParentElement, Pixels, Point, Render, ScrollWheelEvent, SharedString, Size, Style,
-    StyleRefinement, Styled, Task, TooltipId, Visibility, Window,
+    point, px, size, AccessibilityProperties, AccessibilityRole, Action, AnnouncementPriority,
+    AnyDrag, AnyElement, AnyTooltip, AnyView, App, Bounds, ClickEvent, DispatchPhase, Element,
+    ElementId, Entity, FocusHandle, Global, GlobalElementId, Hitbox, HitboxId, IntoElement, IsZero,
+    KeyContext, KeyDownEvent, KeyUpEvent, LayoutId, ModifiersChangedEvent, MouseButton,
+    MouseDownEvent, MouseMoveEvent, MouseUpEvent, ParentElement, Pixels, Point, Render,
+    ScrollWheelEvent, SharedString, Size, Style, StyleRefinement, Styled, Subscription, Task,
+    TitlebarDoubleClickAction, TooltipId, Visibility, Window,
 };
 use collections::HashMap;
 use refineable::Refineable;
 use smallvec::SmallVec;
 use std::{
     any::{Any, TypeId},
-    cell::RefCell,
+    cell::{Cell, RefCell},
     cmp::Ordering,
     fmt::Debug,
     marker::PhantomData,
@@ -542,6 +543,59 @@ impl Interactivity {
     pub fn occlude_mouse(&mut self) {
         self.occlude_mouse = true;
     }
+
+    /// Mark this element as a region that moves the window when dragged, and zooms or
+    /// minimizes the window when double-clicked, like a native title bar.
+    /// The imperative API equivalent to [`StatefulInteractiveElement::window_drag_region`]
+    pub fn window_drag_region(&mut self) {
+        self.window_drag_region = true;
+    }
+
+    /// Set what double-clicking this element's window drag region does, instead of
+    /// following the platform's title bar behavior.
+    /// The imperative API equivalent to [`StatefulInteractiveElement::window_drag_region_double_click`]
+    pub fn window_drag_region_double_click(&mut self, action: TitlebarDoubleClickAction) {
+        self.window_drag_region_double_click = action;
+    }
+
+    /// Create a subscription the first time this element is painted, and keep it alive for
+    /// as long as the element is rendered. The subscription is dropped in the first frame that
+    /// doesn't render this element. Elements without an id never create the subscription.
+    /// The imperative API equivalent to [`StatefulInteractiveElement::scoped_subscription`]
+    pub fn scoped_subscription(
+        &mut self,
+        subscribe: impl FnOnce(&mut Window, &mut App) -> Subscription + 'static,
+    ) {
+        self.subscription_builders.push(Box::new(subscribe));
+    }
+
+    /// Expose this element to assistive technology, such as screen readers, with the given role.
+    /// The imperative API equivalent to [`InteractiveElement::accessibility_role`]
+    pub fn accessibility_role(&mut self, role: AccessibilityRole) {
+        self.accessibility.get_or_insert_with(Default::default).role = role;
+    }
+
+    /// Set the label assistive technology uses to describe this element.
+    /// The imperative API equivalent to [`InteractiveElement::accessibility_label`]
+    pub fn accessibility_label(&mut self, label: impl Into<SharedString>) {
+        self.accessibility
+            .get_or_insert_with(Default::default)
+            .label = Some(label.into());
+    }
+
+    /// Set the value assistive technology reports for this element.
+    /// The imperative API equivalent to [`InteractiveElement::accessibility_value`]
+    pub fn accessibility_value(&mut self, value: impl Into<SharedString>) {
+        self.accessibility
+            .get_or_insert_with(Default::default)
+            .value = Some(value.into());
+    }
+
+    /// Announce changes to this element's label or value with the given priority.
+    /// The imperative API equivalent to [`InteractiveElement::accessibility_live_region`]
+    pub fn accessibility_live_region(&mut self, priority: AnnouncementPriority) {
+        self.accessibility.get_or_insert_with(Default::default).live = Some(priority);
+    }
 }
 
 /// A trait for elements that want to use the standard GPUI event handlers that don't
@@ -922,6 +976,36 @@ pub trait InteractiveElement: Sized {
     fn block_mouse_down(mut self) -> Self {
         self.on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
     }
+
+    /// Expose this element to assistive technology, such as screen readers, with the given role.
+    /// Elements exposed this way form the window's accessibility tree, see [`Window::accessibility_tree`].
+    /// The fluent API equivalent to [`Interactivity::accessibility_role`]
+    fn accessibility_role(mut self, role: AccessibilityRole) -> Self {
+        self.interactivity().accessibility_role(role);
+        self
+    }
+
+    /// Set the label assistive technology uses to describe this element.
+    /// The fluent API equivalent to [`Interactivity::accessibility_label`]
+    fn accessibility_label(mut self, label: impl Into<SharedString>) -> Self {
+        self.interactivity().accessibility_label(label);
+        self
+    }
+
+    /// Set the value assistive technology reports for this element, such as a text field's contents.
+    /// The fluent API equivalent to [`Interactivity::accessibility_value`]
+    fn accessibility_value(mut self, value: impl Into<SharedString>) -> Self {
+        self.interactivity().accessibility_value(value);
+        self
+    }
+
+    /// Make this element a live region, whose changes to its label or value are spoken by
+    /// assistive technology without moving focus to it, see [`App::announce`].
+    /// The fluent API equivalent to [`Interactivity::accessibility_live_region`]
+    fn accessibility_live_region(mut self, priority: AnnouncementPriority) -> Self {
+        self.interactivity().accessibility_live_region(priority);
+        self
+    }
 }
 
 /// A trait for elements that want to use the standard GPUI interactivity features
@@ -989,6 +1073,35 @@ pub trait StatefulInteractiveElement: InteractiveElement {
         self
     }
 
+    /// Mark this element as a region that moves the window when dragged, and zooms or
+    /// minimizes the window when double-clicked, like a native title bar. Children that
+    /// stop propagation of left mouse down events are excluded from the region.
+    /// The fluent API equivalent to [`Interactivity::window_drag_region`]
+    fn window_drag_region(mut self) -> Self {
+        self.interactivity().window_drag_region();
+        self
+    }
+
+    /// Set what double-clicking this element's window drag region does, instead of
+    /// following the platform's title bar behavior.
+    /// The fluent API equivalent to [`Interactivity::window_drag_region_double_click`]
+    fn window_drag_region_double_click(mut self, action: TitlebarDoubleClickAction) -> Self {
+        self.interactivity().window_drag_region_double_click(action);
+        self
+    }
+
+    /// Create a subscription the first time this element is painted, and keep it alive for
+    /// as long as the element is rendered, instead of storing it in a view. The subscription is
+    /// dropped in the first frame that doesn't render this element.
+    /// The fluent API equivalent to [`Interactivity::scoped_subscription`]
+    fn scoped_subscription(
+        mut self,
+        subscribe: impl FnOnce(&mut Window, &mut App) -> Subscription + 'static,
+    ) -> Self {
+        self.interactivity().scoped_subscription(subscribe);
+        self
+    }
+
     /// Bind the given callback to click events of this element
     /// The fluent API equivalent to [`Interactivity::on_click`]
     ///
@@ -1372,6 +1485,10 @@ pub struct Interactivity {
     pub(crate) hover_listener: Option<Box<dyn Fn(&bool, &mut Window, &mut App)>>,
     pub(crate) tooltip_builder: Option<TooltipBuilder>,
     pub(crate) occlude_mouse: bool,
+    pub(crate) window_drag_region: bool,
+    pub(crate) window_drag_region_double_click: TitlebarDoubleClickAction,
+    pub(crate) subscription_builders: Vec<Box<dyn FnOnce(&mut Window, &mut App) -> Subscription>>,
+    pub(crate) accessibility: Option<AccessibilityProperties>,
 
     #[cfg(debug_assertions)]
     pub(crate) location: Option<core::panic::Location<'static>>,
@@ -1517,6 +1634,7 @@ impl Interactivity {
             || self.drag_listener.is_some()
             || !self.drop_listeners.is_empty()
             || self.tooltip_builder.is_some()
+            || self.window_drag_region
     }
 
     fn clamp_scroll_position(
@@ -1580,6 +1698,7 @@ impl Interactivity {
         f: impl FnOnce(&Style, &mut Window, &mut App),
     ) {
         self.hovered = hitbox.map(|hitbox| hitbox.is_hovered(window));
+        self.paint_scoped_subscriptions(global_id, window, cx);
         window.with_optional_element_state::<InteractiveElementState, _>(
             global_id,
             |element_state, window| {
@@ -1634,7 +1753,24 @@ impl Interactivity {
                                     }
 
                                     self.paint_keyboard_listeners(window, cx);
-                                    f(&style, window, cx);
+                                    window.with_inspected_element(
+                                        global_id,
+                                        self.key_context.as_ref(),
+                                        &style,
+                                        bounds,
+                                        |window| {
+                                            if let Some(accessibility) = self.accessibility.as_ref()
+                                            {
+                                                window.with_accessibility_node(
+                                                    accessibility,
+                                                    bounds,
+                                                    |window| f(&style, window, cx),
+                                                );
+                                            } else {
+                                                f(&style, window, cx);
+                                            }
+                                        },
+                                    );
 
                                     if hitbox.is_some() {
                                         if let Some(group) = self.group.as_ref() {
@@ -1652,6 +1788,32 @@ impl Interactivity {
         );
     }
 
+    fn paint_scoped_subscriptions(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        if self.subscription_builders.is_empty() {
+            return;
+        }
+        // Subscriptions are kept in the element's state, which elements without an id don't have.
+        let Some(global_id) = global_id else {
+            self.subscription_builders.clear();
+            return;
+        };
+        let subscription_builders = mem::take(&mut self.subscription_builders);
+        window.with_element_state::<ScopedSubscriptions, _>(global_id, |subscriptions, window| {
+            let subscriptions = subscriptions.unwrap_or_else(|| ScopedSubscriptions {
+                _subscriptions: subscription_builders
+                    .into_iter()
+                    .map(|subscribe| subscribe(window, cx))
+                    .collect(),
+            });
+            ((), subscriptions)
+        });
+    }
+
     #[cfg(debug_assertions)]
     fn paint_debug_info(
         &self,
@@ -1965,6 +2127,53 @@ impl Interactivity {
                 });
             }
 
+            if self.window_drag_region {
+                let pending_window_move = element_state
+                    .pending_window_move
+                    .get_or_insert_with(Default::default)
+                    .clone();
+
+                let double_click_action = self.window_drag_region_double_click;
+                window.on_mouse_event({
+                    let pending_window_move = pending_window_move.clone();
+                    let hitbox = hitbox.clone();
+                    move |event: &MouseDownEvent, phase, window, _cx| {
+                        if !hitbox.is_hovered(window) {
+                            if phase == DispatchPhase::Capture {
+                                pending_window_move.set(false);
+                            }
+                        } else if phase == DispatchPhase::Bubble
+                            && event.button == MouseButton::Left
+                        {
+                            if event.click_count == 2 {
+                                pending_window_move.set(false);
+                                window.titlebar_double_click(double_click_action);
+                            } else {
+                                pending_window_move.set(true);
+                            }
+                        }
+                    }
+                });
+
+                window.on_mouse_event({
+                    let pending_window_move = pending_window_move.clone();
+                    move |event: &MouseMoveEvent, phase, window, _cx| {
+                        if phase == DispatchPhase::Bubble
+                            && event.pressed_button == Some(MouseButton::Left)
+                            && pending_window_move.replace(false)
+                        {
+                            window.start_window_move();
+                        }
+                    }
+                });
+
+                window.on_mouse_event(move |_: &MouseUpEvent, phase, _window, _cx| {
+                    if phase == DispatchPhase::Capture {
+                        pending_window_move.set(false);
+                    }
+                });
+            }
+
             if let Some(hover_listener) = self.hover_listener.take() {
                 let hitbox = hitbox.clone();
                 let was_hovered = element_state
@@ -2288,10 +2497,17 @@ pub struct InteractiveElementState {
     pub(crate) clicked_state: Option<Rc<RefCell<ElementClickedState>>>,
     pub(crate) hover_state: Option<Rc<RefCell<bool>>>,
     pub(crate) pending_mouse_down: Option<Rc<RefCell<Option<MouseDownEvent>>>>,
+    pub(crate) pending_window_move: Option<Rc<Cell<bool>>>,
     pub(crate) scroll_offset: Option<Rc<RefCell<Point<Pixels>>>>,
     pub(crate) active_tooltip: Option<Rc<RefCell<Option<ActiveTooltip>>>>,
 }
 
+/// The subscriptions created with [`Interactivity::scoped_subscription`], dropped along with
+/// the element's state.
+struct ScopedSubscriptions {
+    _subscriptions: Vec<Subscription>,
+}
+
 /// Whether or not the element or a group that contains it is clicked by the mouse.
 #[derive(Copy, Clone, Default, Eq, PartialEq)]
 pub struct ElementClickedState {
@@ -2974,3 +3190,41 @@ impl ScrollHandle {
         self.0.borrow().child_bounds.len()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, rc::Rc};
+
+    use crate::{
+        self as gpui, div, point, px, size, InteractiveElement as _, Subscription, TestAppContext,
+    };
+
+    #[gpui::test]
+    fn test_scoped_subscriptions(cx: &mut TestAppContext) {
+        let cx = cx.add_empty_window();
+        let subscribed = Rc::new(Cell::new(0));
+
+        // Elements without an id have nowhere to keep the subscription, so it isn't created.
+        cx.draw(point(px(0.), px(0.)), size(px(100.), px(100.)), |_, _| {
+            let mut element = div();
+            let subscribed = subscribed.clone();
+            element.interactivity().scoped_subscription(move |_, _| {
+                subscribed.set(subscribed.get() + 1);
+                Subscription::new(|| {})
+            });
+            element
+        });
+        assert_eq!(subscribed.get(), 0);
+
+        cx.draw(point(px(0.), px(0.)), size(px(100.), px(100.)), |_, _| {
+            let mut element = div().id("subscriber");
+            let subscribed = subscribed.clone();
+            element.interactivity().scoped_subscription(move |_, _| {
+                subscribed.set(subscribed.get() + 1);
+                Subscription::new(|| {})
+            });
+            element
+        });
+        assert_eq!(subscribed.get(), 1);
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use crate::{
+    AnyElement, App, Bounds, Element, ElementId, GlobalElementId, IntoElement, LayoutId, Pixels,
+    SharedString, Window,
+};
+
+/// Builds an [`ErrorBoundary`], which draws `child` unless it panics while rendering, laying out
+/// or painting. The element returned by `fallback` is drawn in its place from then on, given the
+/// panic's message.
+pub fn error_boundary(
+    id: impl Into<ElementId>,
+    child: impl IntoElement,
+    fallback: impl Fn(SharedString, &mut Window, &mut App) -> AnyElement + 'static,
+) -> ErrorBoundary {
+    ErrorBoundary {
+        id: id.into(),
+        child: Some(child.into_any_element()),
+        fallback: Rc::new(fallback),
+    }
+}
+
+/// An element that contains panics in its child, so that one broken view doesn't take down the
+/// whole window. Panics are still reported to the panic hook, so this only helps applications
+/// whose hook doesn't abort.
+///
+/// A child that panicked stays replaced by the fallback for as long as the boundary is drawn
+/// with the same id, since the views it contains may have been left in an inconsistent state.
+/// Those views are still alive and can be updated, as the boundary returns the entities whose
+/// updates the panic unwound through to the app.
+pub struct ErrorBoundary {
+    id: ElementId,
+    child: Option<AnyElement>,
+    fallback: Rc<dyn Fn(SharedString, &mut Window, &mut App) -> AnyElement>,
+}
+
+#[derive(Default)]
+struct ErrorBoundaryState {
+    error: Option<SharedString>,
+}
+
+impl ErrorBoundary {
+    fn error(&self, global_id: &GlobalElementId, window: &mut Window) -> Option<SharedString> {
+        window.with_element_state(global_id, |state: Option<ErrorBoundaryState>, _| {
+            let state = state.unwrap_or_default();
+            (state.error.clone(), state)
+        })
+    }
+
+    fn set_error(&self, global_id: &GlobalElementId, error: SharedString, window: &mut Window) {
+        log::error!("{:?} panicked while drawing: {}", self.id, error);
+        window.with_element_state(global_id, |_: Option<ErrorBoundaryState>, _| {
+            ((), ErrorBoundaryState { error: Some(error) })
+        })
+    }
+}
+
+impl IntoElement for ErrorBoundary {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Element for ErrorBoundary {
+    type RequestLayoutState = AnyElement;
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        Some(self.id.clone())
+    }
+
+    fn request_layout(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let global_id = global_id.unwrap();
+        let error = match self.error(global_id, window) {
+            Some(error) => error,
+            None => {
+                let mut child = self.child.take().unwrap();
+                match catch_panic(window, cx, |window, cx| child.request_layout(window, cx)) {
+                    Ok(layout_id) => return (layout_id, child),
+                    Err(error) => {
+                        self.set_error(global_id, error.clone(), window);
+                        error
+                    }
+                }
+            }
+        };
+        let mut fallback = (self.fallback)(error, window, cx);
+        (fallback.request_layout(window, cx), fallback)
+    }
+
+    fn prepaint(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        bounds: Bounds<Pixels>,
+        element: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        if let Err(error) = catch_panic(window, cx, |window, cx| element.prepaint(window, cx)) {
+            self.set_error(global_id.unwrap(), error.clone(), window);
+            // Layout has already happened, so lay out the fallback on its own in the child's place.
+            let mut fallback = (self.fallback)(error, window, cx);
+            fallback.prepaint_as_root(bounds.origin, bounds.size.into(), window, cx);
+            *element = fallback;
+        }
+    }
+
+    fn paint(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        _bounds: Bounds<Pixels>,
+        element: &mut Self::RequestLayoutState,
+        _: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        if let Err(error) = catch_panic(window, cx, |window, cx| element.paint(window, cx)) {
+            // It's too late to replace what the child painted, so draw the fallback next frame.
+            self.set_error(global_id.unwrap(), error, window);
+            window.request_animation_frame();
+        }
+    }
+}
+
+/// Runs `f`, restoring the window and app to the state they were in beforehand if it panics.
+fn catch_panic<R>(
+    window: &mut Window,
+    cx: &mut App,
+    f: impl FnOnce(&mut Window, &mut App) -> R,
+) -> Result<R, SharedString> {
+    let draw_stack_depths = window.draw_stack_depths();
+    let pending_updates = cx.pending_updates;
+    panic::catch_unwind(AssertUnwindSafe(|| f(window, cx))).map_err(|payload| {
+        window.unwind_draw_stacks(&draw_stack_depths, cx);
+        cx.pending_updates = pending_updates;
+        panic_message(payload)
+    })
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> SharedString {
+    if let Some(message) = payload.downcast_ref::<&'static str>() {
+        SharedString::new_static(message)
+    } else if let Ok(message) = payload.downcast::<String>() {
+        SharedString::from(*message)
+    } else {
+        SharedString::new_static("unknown error")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        self as gpui, div, AppContext as _, Context, ParentElement as _, Render, TestAppContext,
+    };
+
+    struct Broken {
+        renders: usize,
+    }
+
+    impl Render for Broken {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            self.renders += 1;
+            if true {
+                panic!("broken view");
+            }
+            div()
+        }
+    }
+
+    struct Root {
+        broken: gpui::Entity<Broken>,
+        fallback_message: Rc<std::cell::RefCell<Option<SharedString>>>,
+    }
+
+    impl Render for Root {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let fallback_message = self.fallback_message.clone();
+            div().id("root").child(error_boundary(
+                "boundary",
+                self.broken.clone(),
+                move |error, _, _| {
+                    fallback_message.replace(Some(error));
+                    div().into_any_element()
+                },
+            ))
+        }
+    }
+
+    #[gpui::test]
+    fn test_error_boundary(cx: &mut TestAppContext) {
+        let fallback_message = Rc::default();
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|cx| Root {
+                    broken: cx.new(|_| Broken { renders: 0 }),
+                    fallback_message: Rc::clone(&fallback_message),
+                })
+            })
+            .unwrap()
+        });
+        assert_eq!(
+            fallback_message.take(),
+            Some(SharedString::from("broken view"))
+        );
+
+        // The window keeps drawing, with the fallback in place of the broken view.
+        window.update(cx, |_, window, _| window.refresh()).unwrap();
+        assert_eq!(
+            fallback_message.take(),
+            Some(SharedString::from("broken view"))
+        );
+
+        // The broken view outlives its panic, keeping the state it had when it panicked.
+        let broken = window.update(cx, |root, _, _| root.broken.clone()).unwrap();
+        let renders = broken.update(cx, |broken, _| {
+            broken.renders += 1;
+            broken.renders
+        });
+        assert_eq!(renders, 2);
+    }
+}
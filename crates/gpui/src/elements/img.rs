@@ -1,8 +1,8 @@
 use crate::{
     px, swap_rgba_pa_to_bgra, AbsoluteLength, AnyElement, App, Asset, AssetLogger, Bounds,
-    DefiniteLength, Element, ElementId, GlobalElementId, Hitbox, Image, InteractiveElement,
-    Interactivity, IntoElement, LayoutId, Length, ObjectFit, Pixels, RenderImage, Resource,
-    SharedString, SharedUri, StyleRefinement, Styled, SvgSize, Task, Window,
+    DefiniteLength, Element, ElementId, GlobalElementId, Hitbox, Image, ImageFilter,
+    InteractiveElement, Interactivity, IntoElement, LayoutId, Length, ObjectFit, Pixels,
+    RenderImage, Resource, SharedString, SharedUri, StyleRefinement, Styled, SvgSize, Task, Window,
     SMOOTH_SVG_SCALE_FACTOR,
 };
 use anyhow::{anyhow, Result};
@@ -127,7 +127,9 @@ where
 /// The style of an image element.
 pub struct ImageStyle {
     grayscale: bool,
+    brightness: f32,
     object_fit: ObjectFit,
+    filter: ImageFilter,
     loading: Option<Box<dyn Fn() -> AnyElement>>,
     fallback: Option<Box<dyn Fn() -> AnyElement>>,
 }
@@ -136,7 +138,9 @@ impl Default for ImageStyle {
     fn default() -> Self {
         Self {
             grayscale: false,
+            brightness: 1.,
             object_fit: ObjectFit::Contain,
+            filter: ImageFilter::default(),
             loading: None,
             fallback: None,
         }
@@ -154,12 +158,27 @@ pub trait StyledImage: Sized {
         self
     }
 
+    /// Set what the image's colors are scaled by. Values above 1 make the image brighter than
+    /// SDR white on windows with [`crate::WindowOptions::hdr`] enabled, up to what their display
+    /// can show, see [`crate::Window::hdr_headroom`]. Elsewhere, the image's brightest colors are
+    /// compressed to SDR white.
+    fn brightness(mut self, brightness: f32) -> Self {
+        self.image_style().brightness = brightness;
+        self
+    }
+
     /// Set the object fit for the image.
     fn object_fit(mut self, object_fit: ObjectFit) -> Self {
         self.image_style().object_fit = object_fit;
         self
     }
 
+    /// Set how the image is filtered when it's drawn at less than half its native size.
+    fn filter(mut self, filter: ImageFilter) -> Self {
+        self.image_style().filter = filter;
+        self
+    }
+
     /// Set the object fit for the image.
     fn with_fallback(mut self, fallback: impl Fn() -> AnyElement + 'static) -> Self {
         self.image_style().fallback = Some(Box::new(fallback));
@@ -281,7 +300,7 @@ impl Element for Img {
                             if let Some(state) = &mut state {
                                 let frame_count = data.frame_count();
                                 if frame_count > 1 {
-                                    let current_time = Instant::now();
+                                    let current_time = cx.background_executor().now();
                                     if let Some(last_frame_time) = state.last_frame_time {
                                         let elapsed = current_time - last_frame_time;
                                         let frame_duration =
@@ -345,7 +364,10 @@ impl Element for Img {
                         None => {
                             if let Some(state) = &mut state {
                                 if let Some((started_loading, _)) = state.started_loading {
-                                    if started_loading.elapsed() > LOADING_DELAY {
+                                    let now = cx.background_executor().now();
+                                    if now.saturating_duration_since(started_loading)
+                                        > LOADING_DELAY
+                                    {
                                         if let Some(loading) = self.style.loading.as_ref() {
                                             let mut element = loading();
                                             replacement_id =
@@ -362,7 +384,8 @@ impl Element for Img {
                                         })
                                         .ok();
                                     });
-                                    state.started_loading = Some((Instant::now(), task));
+                                    state.started_loading =
+                                        Some((cx.background_executor().now(), task));
                                 }
                             }
                         }
@@ -433,6 +456,9 @@ impl Element for Img {
                             data.clone(),
                             layout_state.frame_index,
                             self.style.grayscale,
+                            self.style.brightness,
+                            self.style.filter,
+                            cx,
                         )
                         .log_err();
                 } else if let Some(replacement) = &mut layout_state.replacement {
@@ -1,11 +1,14 @@
 mod anchored;
 mod animation;
+mod async_element;
 mod canvas;
 mod common;
 mod deferred;
 mod div;
+mod error_boundary;
 mod img;
 mod list;
+mod provide;
 mod surface;
 mod svg;
 mod text;
@@ -13,12 +16,15 @@ mod uniform_list;
 
 pub use anchored::*;
 pub use animation::*;
+pub use async_element::*;
 pub use canvas::*;
 pub use common::*;
 pub use deferred::*;
 pub use div::*;
+pub use error_boundary::*;
 pub use img::*;
 pub use list::*;
+pub use provide::*;
 pub use surface::*;
 pub use svg::*;
 pub use text::*;
@@ -0,0 +1,190 @@
+use std::rc::Rc;
+
+use crate::{
+    AnyElement, App, Bounds, Element, ElementId, GlobalElementId, IntoElement, LayoutId, Pixels,
+    Window,
+};
+
+/// Makes `value` available to `child` and everything drawn inside it, including the views it
+/// contains, which retrieve it with [`Window::consume`]. This allows values like theme overrides
+/// to flow down the element tree without passing them to every component in between. Cached views
+/// that read the value are rendered again when it's no longer equal to the value they read.
+pub fn provide<T: PartialEq + 'static>(value: T, child: impl IntoElement) -> Provide<T> {
+    Provide {
+        value: Rc::new(value),
+        child: child.into_any_element(),
+    }
+}
+
+/// An element that provides a value to its child, see [`provide`].
+pub struct Provide<T> {
+    value: Rc<T>,
+    child: AnyElement,
+}
+
+impl<T: PartialEq + 'static> IntoElement for Provide<T> {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl<T: PartialEq + 'static> Element for Provide<T> {
+    type RequestLayoutState = ();
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let layout_id = window.with_provided_value(self.value.clone(), |window| {
+            self.child.request_layout(window, cx)
+        });
+        (layout_id, ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        window.with_provided_value(self.value.clone(), |window| self.child.prepaint(window, cx));
+    }
+
+    fn paint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        _: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        window.with_provided_value(self.value.clone(), |window| self.child.paint(window, cx));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        self as gpui, div, AnyView, AppContext as _, Context, Entity, ParentElement as _, Render,
+        StyleRefinement, Styled as _, TestAppContext,
+    };
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Density(u32);
+
+    #[derive(Default)]
+    struct Consumer {
+        density: Option<Density>,
+        renders: usize,
+    }
+
+    impl Render for Consumer {
+        fn render(&mut self, window: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            self.density = window.consume::<Density>().copied();
+            self.renders += 1;
+            div()
+        }
+    }
+
+    struct Root {
+        inside: Entity<Consumer>,
+        nested: Entity<Consumer>,
+        outside: Entity<Consumer>,
+    }
+
+    impl Render for Root {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .child(provide(
+                    Density(1),
+                    div()
+                        .child(self.inside.clone())
+                        .child(provide(Density(2), self.nested.clone())),
+                ))
+                .child(self.outside.clone())
+        }
+    }
+
+    #[gpui::test]
+    fn test_provide(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|cx| Root {
+                    inside: cx.new(|_| Consumer::default()),
+                    nested: cx.new(|_| Consumer::default()),
+                    outside: cx.new(|_| Consumer::default()),
+                })
+            })
+            .unwrap()
+        });
+        window
+            .update(cx, |root, _, cx| {
+                assert_eq!(root.inside.read(cx).density, Some(Density(1)));
+                assert_eq!(root.nested.read(cx).density, Some(Density(2)));
+                assert_eq!(root.outside.read(cx).density, None);
+            })
+            .unwrap();
+    }
+
+    struct CachedRoot {
+        density: Density,
+        consumer: Entity<Consumer>,
+    }
+
+    impl Render for CachedRoot {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            provide(
+                self.density,
+                AnyView::from(self.consumer.clone()).cached(StyleRefinement::default().size_full()),
+            )
+        }
+    }
+
+    #[gpui::test]
+    fn test_provide_to_cached_view(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|cx| CachedRoot {
+                    density: Density(1),
+                    consumer: cx.new(|_| Consumer::default()),
+                })
+            })
+            .unwrap()
+        });
+        let consumer = window
+            .update(cx, |root, _, _| root.consumer.clone())
+            .unwrap();
+        consumer.read_with(cx, |consumer, _| {
+            assert_eq!(consumer.density, Some(Density(1)));
+            assert_eq!(consumer.renders, 1);
+        });
+
+        // Providing an equal value reuses the view's cached rendering.
+        window.update(cx, |_, _, cx| cx.notify()).unwrap();
+        consumer.read_with(cx, |consumer, _| assert_eq!(consumer.renders, 1));
+
+        window
+            .update(cx, |root, _, cx| {
+                root.density = Density(2);
+                cx.notify();
+            })
+            .unwrap();
+        consumer.read_with(cx, |consumer, _| {
+            assert_eq!(consumer.density, Some(Density(2)));
+            assert_eq!(consumer.renders, 2);
+        });
+    }
+}
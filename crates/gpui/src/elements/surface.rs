@@ -9,7 +9,8 @@ use refineable::Refineable;
 /// A source of a surface's content.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SurfaceSource {
-    /// A macOS image buffer from CoreVideo
+    /// A macOS image buffer from CoreVideo, in either bi-planar full range YCbCr or BGRA format.
+    /// IOSurfaces can be wrapped without copying via `CVImageBuffer::from_io_surface`.
     #[cfg(target_os = "macos")]
     Surface(CVImageBuffer),
 }
@@ -25,6 +26,7 @@ impl From<CVImageBuffer> for SurfaceSource {
 pub struct Surface {
     source: SurfaceSource,
     object_fit: ObjectFit,
+    brightness: f32,
     style: StyleRefinement,
 }
 
@@ -33,6 +35,7 @@ pub fn surface(source: impl Into<SurfaceSource>) -> Surface {
     Surface {
         source: source.into(),
         object_fit: ObjectFit::Contain,
+        brightness: 1.,
         style: Default::default(),
     }
 }
@@ -43,6 +46,13 @@ impl Surface {
         self.object_fit = object_fit;
         self
     }
+
+    /// Set what the surface's colors are scaled by, so that video can use the brightness range
+    /// of HDR displays, see [`crate::StyledImage::brightness`].
+    pub fn brightness(mut self, brightness: f32) -> Self {
+        self.brightness = brightness;
+        self
+    }
 }
 
 impl Element for Surface {
@@ -90,7 +100,7 @@ impl Element for Surface {
                 let size = crate::size(surface.width().into(), surface.height().into());
                 let new_bounds = self.object_fit.get_bounds(bounds, size);
                 // TODO: Add support for corner_radii
-                window.paint_surface(new_bounds, surface.clone());
+                window.paint_surface(new_bounds, surface.clone(), self.brightness);
             }
             #[allow(unreachable_patterns)]
             _ => {}
@@ -13,10 +13,10 @@ use std::{
     pin::Pin,
     rc::Rc,
     sync::{
-        atomic::{AtomicUsize, Ordering::SeqCst},
+        atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst},
         Arc,
     },
-    task::{Context, Poll},
+    task::{Context, Poll, Waker},
     time::{Duration, Instant},
 };
 use util::TryFutureExt;
@@ -74,6 +74,13 @@ impl<T> Task<T> {
             Task(TaskState::Spawned(task)) => task.detach(),
         }
     }
+
+    fn is_finished(&self) -> bool {
+        match self {
+            Task(TaskState::Ready(_)) => true,
+            Task(TaskState::Spawned(task)) => task.is_finished(),
+        }
+    }
 }
 
 impl<E, T> Task<Result<T, E>>
@@ -103,6 +110,115 @@ impl<T> Future for Task<T> {
     }
 }
 
+/// A token for cancelling work cooperatively. Clones of a token share its state, so work
+/// holding a clone can check [`CancellationToken::is_cancelled`] or await
+/// [`CancellationToken::cancelled`] to stop early. See also [`ForegroundExecutor::spawn_scoped`]
+/// and [`BackgroundExecutor::spawn_scoped`], which drop a task's future when it's cancelled.
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<CancellationState>);
+
+#[derive(Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    wakers: parking_lot::Mutex<Vec<Waker>>,
+}
+
+impl CancellationToken {
+    /// Creates a new token that hasn't been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the token, waking any futures waiting on [`CancellationToken::cancelled`].
+    pub fn cancel(&self) {
+        if !self.0.cancelled.swap(true, SeqCst) {
+            for waker in self.0.wakers.lock().drain(..) {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Returns whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(SeqCst)
+    }
+
+    /// Returns a future that resolves once the token is cancelled.
+    pub fn cancelled(&self) -> impl Future<Output = ()> + Send + 'static {
+        let state = self.0.clone();
+        futures::future::poll_fn(move |cx| {
+            if state.cancelled.load(SeqCst) {
+                return Poll::Ready(());
+            }
+            let mut wakers = state.wakers.lock();
+            if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+            drop(wakers);
+            // Check again, in case the token was cancelled before the waker was registered.
+            if state.cancelled.load(SeqCst) {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+    }
+}
+
+impl Debug for CancellationToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// A group of tasks that are cancelled together, either by [`TaskGroup::cancel`] or when the
+/// group is dropped. Store one in a view to cancel in-flight work, like a search, when the
+/// state it was started for changes.
+#[derive(Default)]
+pub struct TaskGroup {
+    tasks: Vec<Task<()>>,
+    token: CancellationToken,
+}
+
+impl TaskGroup {
+    /// Creates an empty task group.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a task to the group, so that it's cancelled along with the group.
+    pub fn push(&mut self, task: Task<()>) {
+        self.tasks.retain(|task| !task.is_finished());
+        self.tasks.push(task);
+    }
+
+    /// Returns the token cancelled along with the group's tasks, for cooperatively
+    /// cancelling work that isn't owned by one of them.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Returns whether any of the group's tasks are still running.
+    pub fn is_running(&self) -> bool {
+        self.tasks.iter().any(|task| !task.is_finished())
+    }
+
+    /// Cancels every task in the group, as well as its token. The group can be reused
+    /// afterwards, with a new token.
+    pub fn cancel(&mut self) {
+        self.tasks.clear();
+        mem::take(&mut self.token).cancel();
+    }
+}
+
+impl Drop for TaskGroup {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
 /// A task label is an opaque identifier that you can use to
 /// refer to a task in tests.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -122,6 +238,20 @@ impl TaskLabel {
     }
 }
 
+/// How urgently a background task should run relative to others, see
+/// [`BackgroundExecutor::spawn_with_priority`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Work the user is waiting on, like highlighting the visible part of a buffer.
+    UserBlocking,
+    /// Work without particular urgency.
+    #[default]
+    Default,
+    /// Long-running work the user isn't waiting on, like indexing a project. It's still run
+    /// while more urgent work is queued, just less often.
+    Background,
+}
+
 type AnyLocalFuture<R> = Pin<Box<dyn 'static + Future<Output = R>>>;
 
 type AnyFuture<R> = Pin<Box<dyn 'static + Send + Future<Output = R>>>;
@@ -141,7 +271,20 @@ impl BackgroundExecutor {
     where
         R: Send + 'static,
     {
-        self.spawn_internal::<R>(Box::pin(future), None)
+        self.spawn_internal::<R>(Box::pin(future), None, Priority::Default)
+    }
+
+    /// Enqueues the given future to be run to completion on a background thread, ahead of or
+    /// behind other tasks depending on the given priority.
+    pub fn spawn_with_priority<R>(
+        &self,
+        priority: Priority,
+        future: impl Future<Output = R> + Send + 'static,
+    ) -> Task<R>
+    where
+        R: Send + 'static,
+    {
+        self.spawn_internal::<R>(Box::pin(future), None, priority)
     }
 
     /// Enqueues the given future to be run to completion on a background thread.
@@ -154,17 +297,33 @@ impl BackgroundExecutor {
     where
         R: Send + 'static,
     {
-        self.spawn_internal::<R>(Box::pin(future), Some(label))
+        self.spawn_internal::<R>(Box::pin(future), Some(label), Priority::Default)
+    }
+
+    /// Enqueues the given future to be run on a background thread until it completes or the
+    /// given token is cancelled. Once cancelled, the future is dropped and the task resolves
+    /// to `None`.
+    pub fn spawn_scoped<R>(
+        &self,
+        token: &CancellationToken,
+        future: impl Future<Output = R> + Send + 'static,
+    ) -> Task<Option<R>>
+    where
+        R: Send + 'static,
+    {
+        self.spawn(cancellable(token, future))
     }
 
     fn spawn_internal<R: Send + 'static>(
         &self,
         future: AnyFuture<R>,
         label: Option<TaskLabel>,
+        priority: Priority,
     ) -> Task<R> {
         let dispatcher = self.dispatcher.clone();
-        let (runnable, task) =
-            async_task::spawn(future, move |runnable| dispatcher.dispatch(runnable, label));
+        let (runnable, task) = async_task::spawn(future, move |runnable| {
+            dispatcher.dispatch(runnable, label, priority)
+        });
         runnable.schedule();
         Task(TaskState::Spawned(task))
     }
@@ -467,6 +626,36 @@ impl ForegroundExecutor {
         }
         inner::<R>(dispatcher, Box::pin(future))
     }
+
+    /// Enqueues the given future to run on the main thread until it completes or the given
+    /// token is cancelled. Once cancelled, the future is dropped and the task resolves to `None`.
+    #[track_caller]
+    pub fn spawn_scoped<R>(
+        &self,
+        token: &CancellationToken,
+        future: impl Future<Output = R> + 'static,
+    ) -> Task<Option<R>>
+    where
+        R: 'static,
+    {
+        self.spawn(cancellable(token, future))
+    }
+}
+
+fn cancellable<R>(
+    token: &CancellationToken,
+    future: impl Future<Output = R>,
+) -> impl Future<Output = Option<R>> {
+    let cancelled = token.cancelled();
+    async move {
+        // Poll the token first, so that cancellation wins over a future that's also ready.
+        async {
+            cancelled.await;
+            None
+        }
+        .or(async { Some(future.await) })
+        .await
+    }
 }
 
 /// Variant of `async_task::spawn_local` that includes the source location of the spawn in panics.
@@ -591,3 +780,40 @@ impl<'a> Drop for Scope<'a> {
         self.executor.block(self.rx.next());
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{self as gpui, TestAppContext};
+    use std::cell::Cell;
+
+    #[gpui::test]
+    async fn test_cancellation(cx: &mut TestAppContext) {
+        let token = CancellationToken::new();
+        let finished = Rc::new(Cell::new(false));
+        let task = cx.foreground_executor().spawn_scoped(&token, {
+            let executor = cx.background_executor.clone();
+            let finished = finished.clone();
+            async move {
+                executor.timer(Duration::from_secs(1)).await;
+                finished.set(true);
+            }
+        });
+        token.cancel();
+        assert_eq!(task.await, None);
+        cx.executor().advance_clock(Duration::from_secs(1));
+        assert!(!finished.get());
+
+        let mut group = TaskGroup::new();
+        let group_token = group.token().clone();
+        group.push(cx.background_executor.spawn(async move {
+            group_token.cancelled().await;
+        }));
+        assert!(group.is_running());
+        let group_token = group.token().clone();
+        group.cancel();
+        assert!(group_token.is_cancelled());
+        assert!(!group.is_running());
+        assert!(!group.token().is_cancelled());
+    }
+}
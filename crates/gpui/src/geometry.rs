@@ -758,6 +758,28 @@ impl Bounds<Pixels> {
             })
     }
 
+    /// Moves and shrinks these bounds as little as possible so that they lie within `area`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use gpui::{point, px, size, Bounds};
+    /// let display = Bounds::new(point(px(0.), px(0.)), size(px(1920.), px(1080.)));
+    /// let window = Bounds::new(point(px(1800.), px(-50.)), size(px(800.), px(600.)));
+    /// assert_eq!(
+    ///     window.fit_within(display),
+    ///     Bounds::new(point(px(1120.), px(0.)), size(px(800.), px(600.)))
+    /// );
+    /// ```
+    pub fn fit_within(&self, area: Bounds<Pixels>) -> Self {
+        let size = self.size.min(&area.size);
+        let max_origin = point(area.right() - size.width, area.bottom() - size.height);
+        Bounds {
+            origin: self.origin.clamp(&area.origin, &max_origin),
+            size,
+        }
+    }
+
     /// Generate maximized bounds for the given display or primary display if none is provided
     pub fn maximized(display_id: Option<DisplayId>, cx: &App) -> Self {
         let display = display_id
@@ -2840,6 +2862,15 @@ impl ScaledPixels {
     /// # Returns
     ///
     /// Returns a new `ScaledPixels` instance with the rounded value.
+    pub fn round(&self) -> Self {
+        Self(self.0.round())
+    }
+
+    /// Rounds the `ScaledPixels` value up to the nearest whole number.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `ScaledPixels` instance with the rounded value.
     pub fn ceil(&self) -> Self {
         Self(self.0.ceil())
     }
@@ -64,6 +64,7 @@
 #![allow(clippy::collapsible_else_if)] // False positives in platform specific code
 #![allow(unused_mut)] // False positives in platform specific code
 
+mod accessibility;
 #[macro_use]
 mod action;
 mod app;
@@ -73,6 +74,7 @@ mod asset_cache;
 mod assets;
 mod bounds_tree;
 mod color;
+mod crash_report;
 mod element;
 mod elements;
 mod executor;
@@ -82,9 +84,12 @@ mod input;
 mod interactive;
 mod key_dispatch;
 mod keymap;
+mod localization;
 mod path_builder;
+mod persistence;
 mod platform;
 pub mod prelude;
+mod progress;
 mod scene;
 mod shared_string;
 mod shared_uri;
@@ -96,6 +101,7 @@ mod taffy;
 #[cfg(any(test, feature = "test-support"))]
 pub mod test;
 mod text_system;
+mod undo;
 mod util;
 mod view;
 mod window;
@@ -117,6 +123,7 @@ mod seal {
     pub trait Sealed {}
 }
 
+pub use accessibility::*;
 pub use action::*;
 pub use anyhow::Result;
 pub use app::*;
@@ -124,6 +131,7 @@ pub(crate) use arena::*;
 pub use asset_cache::*;
 pub use assets::*;
 pub use color::*;
+pub use crash_report::*;
 pub use ctor::ctor;
 pub use element::*;
 pub use elements::*;
@@ -136,8 +144,11 @@ pub use input::*;
 pub use interactive::*;
 use key_dispatch::*;
 pub use keymap::*;
+pub use localization::*;
 pub use path_builder::*;
+pub use persistence::*;
 pub use platform::*;
+pub use progress::*;
 pub use refineable::*;
 pub use scene::*;
 pub use shared_string::*;
@@ -151,6 +162,7 @@ pub use taffy::{AvailableSpace, LayoutId};
 #[cfg(any(test, feature = "test-support"))]
 pub use test::*;
 pub use text_system::*;
+use undo::*;
 pub use util::arc_cow::ArcCow;
 pub use view::*;
 pub use window::*;
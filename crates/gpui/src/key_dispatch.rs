@@ -84,7 +84,7 @@ pub(crate) struct DispatchNode {
     pub modifiers_changed_listeners: Vec<ModifiersChangedListener>,
     pub context: Option<KeyContext>,
     pub focus_id: Option<FocusId>,
-    view_id: Option<EntityId>,
+    pub view_id: Option<EntityId>,
     parent: Option<DispatchNodeId>,
 }
 
@@ -228,6 +228,17 @@ impl DispatchTree {
         }
     }
 
+    pub fn node_stack_depth(&self) -> usize {
+        self.node_stack.len()
+    }
+
+    /// Pops nodes until only the given number remain on the stack.
+    pub fn truncate_node_stack(&mut self, depth: usize) {
+        while self.node_stack.len() > depth {
+            self.pop_node();
+        }
+    }
+
     pub fn pop_node(&mut self) {
         let node = &self.nodes[self.active_node_id().unwrap().0];
         if node.context.is_some() {
@@ -14,6 +14,24 @@ use std::any::TypeId;
 #[derive(Copy, Clone, Eq, PartialEq, Default)]
 pub struct KeymapVersion(usize);
 
+/// The bindings that changed when a keymap's bindings were replaced, see
+/// [`Keymap::replace_bindings`].
+#[derive(Debug, Default)]
+pub struct KeymapDiff {
+    /// Bindings that weren't in the previous keymap
+    pub added: Vec<KeyBinding>,
+    /// Bindings from the previous keymap that are no longer present
+    pub removed: Vec<KeyBinding>,
+}
+
+impl KeymapDiff {
+    /// Whether the keymap contains the same bindings as before, though possibly in a different
+    /// order.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
 /// A collection of key bindings for the user's application.
 #[derive(Default)]
 pub struct Keymap {
@@ -54,6 +72,58 @@ impl Keymap {
         self.version.0 += 1;
     }
 
+    /// Replace all of this keymap's bindings at once, returning how they changed. The version is
+    /// left unchanged if the new bindings are identical to the current ones, in the same order.
+    pub fn replace_bindings(&mut self, bindings: Vec<KeyBinding>) -> KeymapDiff {
+        let mut unmatched = HashMap::<&[Keystroke], Vec<&KeyBinding>>::default();
+        for binding in &self.bindings {
+            unmatched
+                .entry(binding.keystrokes())
+                .or_default()
+                .push(binding);
+        }
+        let mut diff = KeymapDiff::default();
+        for binding in &bindings {
+            let matching = unmatched
+                .get_mut(binding.keystrokes())
+                .and_then(|candidates| {
+                    let ix = candidates
+                        .iter()
+                        .position(|candidate| candidate.is_equivalent(binding))?;
+                    Some(candidates.remove(ix))
+                });
+            if matching.is_none() {
+                diff.added.push(binding.clone());
+            }
+        }
+        diff.removed = self
+            .bindings
+            .iter()
+            .filter(|binding| {
+                unmatched
+                    .get(binding.keystrokes())
+                    .map_or(false, |candidates| {
+                        candidates
+                            .iter()
+                            .any(|candidate| std::ptr::eq(*candidate, *binding))
+                    })
+            })
+            .cloned()
+            .collect();
+
+        // Later bindings take precedence, so a change in order is still a change to the keymap.
+        let reordered = self
+            .bindings
+            .iter()
+            .zip(&bindings)
+            .any(|(old, new)| !old.is_equivalent(new));
+        if !diff.is_empty() || reordered {
+            self.clear();
+            self.add_bindings(bindings);
+        }
+        diff
+    }
+
     /// Reset this keymap to its initial state.
     pub fn clear(&mut self) {
         self.bindings.clear();
@@ -201,6 +271,43 @@ mod tests {
         [ActionAlpha, ActionBeta, ActionGamma, ActionDelta,]
     );
 
+    #[test]
+    fn test_replace_bindings() {
+        let mut keymap = Keymap::new(vec![
+            KeyBinding::new("ctrl-a", ActionAlpha {}, None),
+            KeyBinding::new("ctrl-b", ActionBeta {}, Some("pane")),
+        ]);
+        let version = keymap.version();
+
+        let diff = keymap.replace_bindings(vec![
+            KeyBinding::new("ctrl-a", ActionAlpha {}, None),
+            KeyBinding::new("ctrl-b", ActionBeta {}, Some("pane")),
+        ]);
+        assert!(diff.is_empty());
+        assert!(keymap.version() == version);
+
+        let diff = keymap.replace_bindings(vec![
+            KeyBinding::new("ctrl-a", ActionAlpha {}, None),
+            KeyBinding::new("ctrl-b", ActionBeta {}, Some("editor")),
+            KeyBinding::new("ctrl-c", ActionGamma {}, None),
+        ]);
+        assert_eq!(diff.added.len(), 2);
+        assert!(diff.added[0].is_equivalent(&KeyBinding::new(
+            "ctrl-b",
+            ActionBeta {},
+            Some("editor")
+        )));
+        assert!(diff.added[1].is_equivalent(&KeyBinding::new("ctrl-c", ActionGamma {}, None)));
+        assert_eq!(diff.removed.len(), 1);
+        assert!(diff.removed[0].is_equivalent(&KeyBinding::new(
+            "ctrl-b",
+            ActionBeta {},
+            Some("pane")
+        )));
+        assert!(keymap.version() != version);
+        assert_eq!(keymap.bindings().count(), 3);
+    }
+
     #[test]
     fn test_keymap() {
         let bindings = [
@@ -87,6 +87,13 @@ impl KeyBinding {
         self.action.as_ref()
     }
 
+    /// Whether the given binding has the same keystrokes, action and context as this one.
+    pub fn is_equivalent(&self, other: &KeyBinding) -> bool {
+        self.keystrokes == other.keystrokes
+            && self.context_predicate == other.context_predicate
+            && self.action.partial_eq(other.action.as_ref())
+    }
+
     /// Get the predicate used to match this binding
     pub fn predicate(&self) -> Option<Rc<KeyBindingContextPredicate>> {
         self.context_predicate.as_ref().map(|rc| rc.clone())
@@ -0,0 +1,281 @@
+use anyhow::Result;
+use collections::HashMap;
+use serde::Deserialize;
+
+use crate::SharedString;
+
+/// The locale used when none has been set, see [`crate::App::set_locale`].
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// The plural forms a language distinguishes between, as defined by the Unicode CLDR. Every
+/// language uses [`PluralCategory::Other`], and most use only a few of the others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluralCategory {
+    /// Used for zero in languages like Arabic
+    Zero,
+    /// Used for one, and in some languages for other numbers ending in one
+    One,
+    /// Used for two in languages like Arabic and Hebrew
+    Two,
+    /// Used for small numbers in languages like Czech, Polish and Russian
+    Few,
+    /// Used for large numbers in languages like Arabic, Polish and Russian
+    Many,
+    /// Used for every number that isn't in another category
+    Other,
+}
+
+impl PluralCategory {
+    /// Returns the plural category of `count` in the given locale's language. Unknown languages
+    /// follow English, which distinguishes between one and everything else.
+    pub fn of(locale: &str, count: usize) -> Self {
+        let (n10, n100) = (count % 10, count % 100);
+        match language(locale) {
+            "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" | "lo" | "my" => Self::Other,
+            "fr" | "pt" => match count {
+                0 | 1 => Self::One,
+                _ if count % 1_000_000 == 0 => Self::Many,
+                _ => Self::Other,
+            },
+            "ru" | "uk" | "be" => {
+                if n10 == 1 && n100 != 11 {
+                    Self::One
+                } else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) {
+                    Self::Few
+                } else {
+                    Self::Many
+                }
+            }
+            "pl" => {
+                if count == 1 {
+                    Self::One
+                } else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) {
+                    Self::Few
+                } else {
+                    Self::Many
+                }
+            }
+            "cs" | "sk" => match count {
+                1 => Self::One,
+                2..=4 => Self::Few,
+                _ => Self::Other,
+            },
+            "he" => match count {
+                1 => Self::One,
+                2 => Self::Two,
+                _ => Self::Other,
+            },
+            "ar" => match count {
+                0 => Self::Zero,
+                1 => Self::One,
+                2 => Self::Two,
+                _ if (3..=10).contains(&n100) => Self::Few,
+                _ if (11..=99).contains(&n100) => Self::Many,
+                _ => Self::Other,
+            },
+            _ => match count {
+                1 => Self::One,
+                _ => Self::Other,
+            },
+        }
+    }
+}
+
+/// The translations of an application's UI strings into one language, keyed by their English
+/// text. Catalogs are usually loaded from JSON, in which each message maps to its translation,
+/// or for messages that depend on a count, to a translation for each plural category:
+///
+/// ```json
+/// {
+///     "Open File…": "Ouvrir un fichier…",
+///     "{count} file": { "one": "{count} fichier", "other": "{count} fichiers" }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct MessageCatalog {
+    messages: HashMap<SharedString, Message>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum Message {
+    Text(SharedString),
+    Plural(HashMap<PluralCategory, SharedString>),
+}
+
+impl MessageCatalog {
+    /// Parses a catalog from JSON in the format described on [`MessageCatalog`].
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Adds the translation of a message.
+    pub fn insert(
+        &mut self,
+        message: impl Into<SharedString>,
+        translation: impl Into<SharedString>,
+    ) {
+        self.messages
+            .insert(message.into(), Message::Text(translation.into()));
+    }
+
+    /// Adds the translations of a message that depends on a count, keyed by the English text
+    /// used for a count of one.
+    pub fn insert_plural(
+        &mut self,
+        message: impl Into<SharedString>,
+        translations: impl IntoIterator<Item = (PluralCategory, SharedString)>,
+    ) {
+        self.messages.insert(
+            message.into(),
+            Message::Plural(translations.into_iter().collect()),
+        );
+    }
+}
+
+/// The current locale and the message catalogs that have been added for each locale.
+pub(crate) struct Localization {
+    locale: SharedString,
+    catalogs: HashMap<SharedString, MessageCatalog>,
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        Self {
+            locale: DEFAULT_LOCALE.into(),
+            catalogs: HashMap::default(),
+        }
+    }
+}
+
+impl Localization {
+    pub(crate) fn locale(&self) -> &SharedString {
+        &self.locale
+    }
+
+    pub(crate) fn set_locale(&mut self, locale: SharedString) {
+        self.locale = locale;
+    }
+
+    pub(crate) fn add_catalog(&mut self, locale: SharedString, catalog: MessageCatalog) {
+        let existing = self.catalogs.entry(locale).or_default();
+        existing.messages.extend(catalog.messages);
+    }
+
+    pub(crate) fn translate(&self, message: SharedString) -> SharedString {
+        match self.lookup(&message) {
+            Some(Message::Text(translation)) => translation.clone(),
+            _ => message,
+        }
+    }
+
+    pub(crate) fn translate_plural(
+        &self,
+        one: SharedString,
+        other: SharedString,
+        count: usize,
+    ) -> SharedString {
+        let translation = match self.lookup(&one) {
+            Some(Message::Plural(translations)) => translations
+                .get(&PluralCategory::of(&self.locale, count))
+                .or_else(|| translations.get(&PluralCategory::Other))
+                .cloned(),
+            _ => None,
+        };
+        let message = translation.unwrap_or_else(|| if count == 1 { one } else { other });
+        if message.contains("{count}") {
+            message.replace("{count}", &count.to_string()).into()
+        } else {
+            message
+        }
+    }
+
+    /// Looks up a message in the catalog for the current locale, and then in the catalog for its
+    /// language, so that "pt-BR" falls back to "pt".
+    fn lookup(&self, message: &str) -> Option<&Message> {
+        let language = language(&self.locale);
+        [self.locale.as_ref(), language]
+            .into_iter()
+            .filter_map(|locale| self.catalogs.get(locale))
+            .find_map(|catalog| catalog.messages.get(message))
+    }
+}
+
+fn language(locale: &str) -> &str {
+    locale.split(['-', '_']).next().unwrap_or(locale)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{self as gpui, TestAppContext};
+    use std::{cell::Cell, rc::Rc};
+
+    #[test]
+    fn test_plural_categories() {
+        let categories = |locale: &str, counts: &[usize]| {
+            counts
+                .iter()
+                .map(|count| PluralCategory::of(locale, *count))
+                .collect::<Vec<_>>()
+        };
+        use PluralCategory::*;
+        assert_eq!(categories("en-US", &[0, 1, 2]), [Other, One, Other]);
+        assert_eq!(categories("fr", &[0, 1, 2]), [One, One, Other]);
+        assert_eq!(
+            categories("ru", &[1, 2, 5, 11, 21, 22]),
+            [One, Few, Many, Many, One, Few]
+        );
+        assert_eq!(categories("ja", &[1, 2]), [Other, Other]);
+    }
+
+    #[gpui::test]
+    fn test_translate(cx: &mut TestAppContext) {
+        let locale_changes = Rc::new(Cell::new(0));
+        let _subscription = cx.update(|cx| {
+            let locale_changes = locale_changes.clone();
+            cx.observe_locale(move |_| locale_changes.set(locale_changes.get() + 1))
+        });
+
+        cx.update(|cx| {
+            let catalog = MessageCatalog::from_json(
+                r#"{
+                    "Open File…": "Открыть файл…",
+                    "{count} file": {
+                        "one": "{count} файл",
+                        "few": "{count} файла",
+                        "many": "{count} файлов"
+                    }
+                }"#,
+            )
+            .unwrap();
+            cx.add_message_catalog("ru", catalog);
+
+            assert_eq!(cx.translate("Open File…"), "Open File…");
+            assert_eq!(
+                cx.translate_plural("{count} file", "{count} files", 3),
+                "3 files"
+            );
+
+            cx.set_locale("ru-RU");
+            assert_eq!(cx.locale(), "ru-RU");
+            assert_eq!(cx.translate("Open File…"), "Открыть файл…");
+            assert_eq!(cx.translate("Close"), "Close");
+            assert_eq!(
+                cx.translate_plural("{count} file", "{count} files", 1),
+                "1 файл"
+            );
+            assert_eq!(
+                cx.translate_plural("{count} file", "{count} files", 3),
+                "3 файла"
+            );
+            assert_eq!(
+                cx.translate_plural("{count} file", "{count} files", 5),
+                "5 файлов"
+            );
+        });
+        assert_eq!(locale_changes.get(), 1);
+    }
+}
@@ -1,12 +1,15 @@
 use anyhow::Error;
 use etagere::euclid::Vector2D;
 use lyon::geom::Angle;
+use lyon::path::builder::SvgPathBuilder;
+use lyon::path::iterator::PathIterator;
+use lyon::path::{ArcFlags, PathEvent};
 use lyon::tessellation::{
     BuffersBuilder, FillTessellator, FillVertex, StrokeTessellator, StrokeVertex, VertexBuffers,
 };
 
 pub use lyon::math::Transform;
-pub use lyon::tessellation::{FillOptions, FillRule, StrokeOptions};
+pub use lyon::tessellation::{FillOptions, FillRule, LineCap, LineJoin, StrokeOptions};
 
 use crate::{point, px, Path, Pixels, Point};
 
@@ -18,12 +21,30 @@ pub enum PathStyle {
     Fill(FillOptions),
 }
 
+/// A triangular marker drawn at the start or end of a stroked path, pointing along the path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Arrowhead {
+    /// The distance from the arrowhead's base to its tip
+    pub length: Pixels,
+    /// The width of the arrowhead's base
+    pub width: Pixels,
+}
+
 /// A [`Path`] builder.
 pub struct PathBuilder {
     raw: lyon::path::builder::WithSvg<lyon::path::BuilderImpl>,
     transform: Option<lyon::math::Transform>,
     /// PathStyle of the PathBuilder
     pub style: PathStyle,
+    dash_pattern: Option<DashPattern>,
+    start_arrowhead: Option<Arrowhead>,
+    end_arrowhead: Option<Arrowhead>,
+}
+
+#[derive(Clone, Debug)]
+struct DashPattern {
+    lengths: Vec<f32>,
+    offset: f32,
 }
 
 impl From<lyon::path::Builder> for PathBuilder {
@@ -62,6 +83,9 @@ impl Default for PathBuilder {
             raw: lyon::path::Path::builder().with_svg(),
             style: PathStyle::Fill(FillOptions::default()),
             transform: None,
+            dash_pattern: None,
+            start_arrowhead: None,
+            end_arrowhead: None,
         }
     }
 }
@@ -85,6 +109,75 @@ impl PathBuilder {
         Self { style, ..self }
     }
 
+    /// Sets how the ends of sub-paths are drawn when stroking. Has no effect on fill paths.
+    pub fn line_cap(mut self, cap: LineCap) -> Self {
+        if let PathStyle::Stroke(options) = &mut self.style {
+            *options = options.with_line_cap(cap);
+        }
+        self
+    }
+
+    /// Sets how the corners between segments are drawn when stroking. Has no effect on fill
+    /// paths.
+    pub fn line_join(mut self, join: LineJoin) -> Self {
+        if let PathStyle::Stroke(options) = &mut self.style {
+            *options = options.with_line_join(join);
+        }
+        self
+    }
+
+    /// Sets the ratio of the miter length to the line width beyond which [`LineJoin::Miter`]
+    /// corners are beveled instead. Has no effect on fill paths.
+    pub fn miter_limit(mut self, limit: f32) -> Self {
+        if let PathStyle::Stroke(options) = &mut self.style {
+            *options = options.with_miter_limit(limit);
+        }
+        self
+    }
+
+    /// Strokes the path with dashes, alternating between the lengths of dashes and of the gaps
+    /// between them, as in SVG's `stroke-dasharray`. The pattern starts `offset` into its first
+    /// dash, and restarts at each sub-path. Has no effect on fill paths.
+    pub fn dash_pattern(mut self, lengths: &[Pixels], offset: Pixels) -> Self {
+        let mut lengths = lengths
+            .iter()
+            .map(|length| length.0.max(0.))
+            .collect::<Vec<_>>();
+        // A pattern with an odd number of lengths alternates between dashes and gaps when it's
+        // repeated, so it's repeated once to get pairs of dashes and gaps.
+        if lengths.len() % 2 == 1 {
+            lengths.extend_from_within(..);
+        }
+        self.dash_pattern = (lengths.iter().sum::<f32>() > 0.).then_some(DashPattern {
+            lengths,
+            offset: offset.0,
+        });
+        self
+    }
+
+    /// Draws an arrowhead at the start of a stroked path, pointing away from it. The stroke is
+    /// shortened so that it ends at the arrowhead's base. Has no effect on fill paths.
+    pub fn start_arrowhead(mut self, arrowhead: Arrowhead) -> Self {
+        self.start_arrowhead = Some(arrowhead);
+        self
+    }
+
+    /// Draws an arrowhead at the end of a stroked path, pointing along its final direction. The
+    /// stroke is shortened so that it ends at the arrowhead's base. Has no effect on fill paths.
+    pub fn end_arrowhead(mut self, arrowhead: Arrowhead) -> Self {
+        self.end_arrowhead = Some(arrowhead);
+        self
+    }
+
+    /// Sets the rule that determines which areas are inside the path when filling. Has no
+    /// effect on stroke paths.
+    pub fn fill_rule(mut self, rule: FillRule) -> Self {
+        if let PathStyle::Fill(options) = &mut self.style {
+            *options = options.with_fill_rule(rule);
+        }
+        self
+    }
+
     /// Move the current point to the given point.
     #[inline]
     pub fn move_to(&mut self, to: Point<Pixels>) {
@@ -116,6 +209,28 @@ impl PathBuilder {
             .cubic_bezier_to(control_a.into(), control_b.into(), to.into());
     }
 
+    /// Draw an elliptical arc from the current point to the given point, with the given radii
+    /// and the ellipse rotated by `x_rotation` degrees. Of the four arcs that connect the points,
+    /// `large_arc` and `sweep` choose the one that's longer than half of the ellipse and the one
+    /// that's drawn clockwise, as in SVG.
+    #[inline]
+    pub fn arc_to(
+        &mut self,
+        to: Point<Pixels>,
+        radii: Point<Pixels>,
+        x_rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+    ) {
+        SvgPathBuilder::arc_to(
+            &mut self.raw,
+            Vector2D::new(radii.x.0, radii.y.0),
+            Angle::degrees(x_rotation),
+            ArcFlags { large_arc, sweep },
+            to.into(),
+        );
+    }
+
     /// Close the current sub-path.
     #[inline]
     pub fn close(&mut self) {
@@ -171,6 +286,19 @@ impl PathBuilder {
         };
 
         match self.style {
+            PathStyle::Stroke(options)
+                if self.dash_pattern.is_some()
+                    || self.start_arrowhead.is_some()
+                    || self.end_arrowhead.is_some() =>
+            {
+                Self::tessellate_decorated_stroke(
+                    &path,
+                    &options,
+                    self.dash_pattern.as_ref(),
+                    self.start_arrowhead,
+                    self.end_arrowhead,
+                )
+            }
             PathStyle::Stroke(options) => Self::tessellate_stroke(&path, &options),
             PathStyle::Fill(options) => Self::tessellate_fill(&path, &options),
         }
@@ -212,6 +340,96 @@ impl PathBuilder {
         Ok(Self::build_path(buf))
     }
 
+    /// Strokes a path with dashes or arrowheads, which lyon doesn't support, by flattening it
+    /// into polylines that are trimmed and split into dashes before they're stroked.
+    fn tessellate_decorated_stroke(
+        path: &lyon::path::Path,
+        options: &StrokeOptions,
+        dash_pattern: Option<&DashPattern>,
+        start_arrowhead: Option<Arrowhead>,
+        end_arrowhead: Option<Arrowhead>,
+    ) -> Result<Path<Pixels>, Error> {
+        let mut polylines = Vec::new();
+        for event in path.iter().flattened(options.tolerance) {
+            match event {
+                PathEvent::Begin { at } => polylines.push(Polyline {
+                    points: vec![at],
+                    closed: false,
+                }),
+                PathEvent::Line { to, .. } => {
+                    if let Some(polyline) = polylines.last_mut() {
+                        polyline.points.push(to);
+                    }
+                }
+                PathEvent::End { first, close, .. } => {
+                    if let Some(polyline) = polylines.last_mut() {
+                        if close {
+                            polyline.points.push(first);
+                            polyline.closed = true;
+                        }
+                    }
+                }
+                PathEvent::Quadratic { .. } | PathEvent::Cubic { .. } => {}
+            }
+        }
+
+        let mut arrowheads = Vec::new();
+        if let Some((arrowhead, polyline)) = start_arrowhead.zip(polylines.first_mut()) {
+            polyline.points.reverse();
+            arrowheads.extend(polyline.trim_end(arrowhead));
+            polyline.points.reverse();
+        }
+        if let Some((arrowhead, polyline)) = end_arrowhead.zip(polylines.last_mut()) {
+            arrowheads.extend(polyline.trim_end(arrowhead));
+        }
+
+        let mut builder = lyon::path::Path::builder();
+        for polyline in &polylines {
+            match dash_pattern {
+                Some(dash_pattern) => {
+                    for dash in polyline.dashes(dash_pattern) {
+                        builder.begin(dash[0]);
+                        for point in &dash[1..] {
+                            builder.line_to(*point);
+                        }
+                        builder.end(false);
+                    }
+                }
+                None => {
+                    let Some((first, rest)) = polyline.points.split_first() else {
+                        continue;
+                    };
+                    builder.begin(*first);
+                    // The closing segment is drawn by closing the sub-path, so that it's joined
+                    // to the first one.
+                    let rest = if polyline.closed {
+                        &rest[..rest.len().saturating_sub(1)]
+                    } else {
+                        rest
+                    };
+                    for point in rest {
+                        builder.line_to(*point);
+                    }
+                    builder.end(polyline.closed);
+                }
+            }
+        }
+
+        let mut buf: VertexBuffers<lyon::math::Point, u16> = VertexBuffers::new();
+        StrokeTessellator::new().tessellate_path(
+            &builder.build(),
+            options,
+            &mut BuffersBuilder::new(&mut buf, |vertex: StrokeVertex| vertex.position()),
+        )?;
+        for [tip, left, right] in arrowheads {
+            let index = buf.vertices.len() as u16;
+            buf.vertices.extend([tip, left, right]);
+            buf.indices.extend([index, index + 1, index + 2]);
+        }
+
+        Ok(Self::build_path(buf))
+    }
+
     /// Builds a [`Path`] from a [`lyon::VertexBuffers`].
     pub fn build_path(buf: VertexBuffers<lyon::math::Point, u16>) -> Path<Pixels> {
         if buf.vertices.is_empty() {
@@ -239,3 +457,138 @@ impl PathBuilder {
         path
     }
 }
+
+/// A flattened sub-path. Closed sub-paths end with their first point.
+struct Polyline {
+    points: Vec<lyon::math::Point>,
+    closed: bool,
+}
+
+impl Polyline {
+    /// Shortens the end of the polyline by the arrowhead's length, and returns the corners of
+    /// the arrowhead that fills the removed part, tip first.
+    fn trim_end(&mut self, arrowhead: Arrowhead) -> Option<[lyon::math::Point; 3]> {
+        let tip = *self.points.last()?;
+        self.closed = false;
+        let mut remaining = arrowhead.length.0;
+        while let [.., from, to] = self.points[..] {
+            let segment_length = (to - from).length();
+            if segment_length > remaining {
+                let base = to.lerp(from, remaining / segment_length);
+                *self.points.last_mut()? = base;
+                let direction = (tip - base).normalize();
+                let normal = lyon::math::vector(-direction.y, direction.x) * arrowhead.width.0 / 2.;
+                return Some([tip, base + normal, base - normal]);
+            }
+            remaining -= segment_length;
+            self.points.pop();
+        }
+        None
+    }
+
+    /// Splits the polyline into the dashes of a dash pattern.
+    fn dashes(&self, pattern: &DashPattern) -> Vec<Vec<lyon::math::Point>> {
+        let Some(first_point) = self.points.first() else {
+            return Vec::new();
+        };
+        let pattern_length = pattern.lengths.iter().sum::<f32>();
+        let mut index = 0;
+        let mut remaining = pattern.lengths[0];
+        let mut offset = pattern.offset.rem_euclid(pattern_length);
+        while offset > remaining {
+            offset -= remaining;
+            index = (index + 1) % pattern.lengths.len();
+            remaining = pattern.lengths[index];
+        }
+        remaining -= offset;
+
+        let mut dashes = Vec::new();
+        let mut dash = (index % 2 == 0).then(|| vec![*first_point]);
+        for segment in self.points.windows(2) {
+            let (from, to) = (segment[0], segment[1]);
+            let segment_length = (to - from).length();
+            let mut distance = 0.;
+            while segment_length - distance > remaining {
+                distance += remaining;
+                let split = from.lerp(to, distance / segment_length);
+                match dash.take() {
+                    Some(mut points) => {
+                        points.push(split);
+                        dashes.push(points);
+                    }
+                    None => dash = Some(vec![split]),
+                }
+                index = (index + 1) % pattern.lengths.len();
+                remaining = pattern.lengths[index];
+            }
+            remaining -= segment_length - distance;
+            if let Some(points) = &mut dash {
+                points.push(to);
+            }
+        }
+        dashes.extend(dash);
+        dashes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{bounds, size};
+
+    #[test]
+    fn test_stroke_caps() {
+        let build_line = |cap| {
+            let mut builder = PathBuilder::stroke(px(2.)).line_cap(cap);
+            builder.move_to(point(px(0.), px(0.)));
+            builder.line_to(point(px(10.), px(0.)));
+            builder.build().unwrap()
+        };
+
+        assert_eq!(
+            build_line(LineCap::Butt).bounds,
+            bounds(point(px(0.), px(-1.)), size(px(10.), px(2.)))
+        );
+        // Square caps extend past each end of the line by half of its width.
+        assert_eq!(
+            build_line(LineCap::Square).bounds,
+            bounds(point(px(-1.), px(-1.)), size(px(12.), px(2.)))
+        );
+    }
+
+    #[test]
+    fn test_dashes_and_arrowheads() {
+        let mut polyline = Polyline {
+            points: vec![lyon::math::point(0., 0.), lyon::math::point(8., 0.)],
+            closed: false,
+        };
+        let pattern = DashPattern {
+            lengths: vec![3., 1.],
+            offset: 1.,
+        };
+        let dash_ends = |dashes: Vec<Vec<lyon::math::Point>>| {
+            dashes
+                .into_iter()
+                .map(|dash| (dash[0].x, dash[dash.len() - 1].x))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            dash_ends(polyline.dashes(&pattern)),
+            [(0., 2.), (3., 6.), (7., 8.)]
+        );
+
+        let arrowhead = Arrowhead {
+            length: px(4.),
+            width: px(2.),
+        };
+        assert_eq!(
+            polyline.trim_end(arrowhead),
+            Some([
+                lyon::math::point(8., 0.),
+                lyon::math::point(4., 1.),
+                lyon::math::point(4., -1.)
+            ])
+        );
+        assert_eq!(polyline.points[1], lyon::math::point(4., 0.));
+    }
+}
@@ -0,0 +1,150 @@
+//! Persistence of view and global state across application restarts.
+//!
+//! Views implementing [`PersistedView`] and globals implementing [`PersistedGlobal`] are
+//! registered with the [`App`], which restores their state when they are registered and collects
+//! it again when the application quits. All state lives in a single JSON object keyed by each
+//! view's or global's persistence key, stored in the file given to [`App::set_persistence_path`].
+//! Without a persistence path, state is only kept for the lifetime of the application.
+
+use crate::{App, Context, Entity, Global, SharedString};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+use util::ResultExt as _;
+
+/// A view whose state, such as a panel's size or a toggle, is saved when the application quits
+/// and restored on the next launch. Register each instance with [`App::register_persisted_view`].
+pub trait PersistedView: 'static + Sized {
+    /// The state that is saved and restored
+    type State: Serialize + DeserializeOwned;
+
+    /// Identifies this view's state in the store. Views that are registered with the same key
+    /// share their state, with the most recently registered view taking precedence when saving.
+    fn persistence_key(&self) -> SharedString;
+
+    /// Returns the state to save.
+    fn persisted_state(&self, cx: &App) -> Self::State;
+
+    /// Restores previously saved state, called when the view is registered.
+    fn restore_state(&mut self, state: Self::State, cx: &mut Context<Self>);
+}
+
+/// A global whose state is saved when the application quits and restored on the next launch.
+/// Register it with [`App::register_persisted_global`].
+pub trait PersistedGlobal: Global + Sized {
+    /// The state that is saved and restored
+    type State: Serialize + DeserializeOwned;
+
+    /// Identifies this global's state in the store
+    const PERSISTENCE_KEY: &'static str;
+
+    /// Returns the state to save.
+    fn persisted_state(&self, cx: &App) -> Self::State;
+
+    /// Restores previously saved state, called when the global is registered.
+    fn restore_state(&mut self, state: Self::State, cx: &mut App);
+}
+
+/// A registered view or global whose state is persisted.
+pub(crate) struct PersistedStateSource {
+    /// Returns the state to save, or `None` if the view or global no longer exists
+    pub(crate) save: Box<dyn Fn(&App) -> Option<serde_json::Value>>,
+    /// Restores state that finished loading after the view or global was registered
+    pub(crate) restore: Box<dyn Fn(serde_json::Value, &mut App)>,
+}
+
+/// The state of registered views and globals, keyed by their persistence key.
+#[derive(Default)]
+pub(crate) struct PersistenceStore {
+    pub(crate) path: Option<PathBuf>,
+    pub(crate) values: BTreeMap<String, serde_json::Value>,
+    pub(crate) sources: BTreeMap<String, PersistedStateSource>,
+}
+
+pub(crate) fn restore_persisted_view<V: PersistedView>(
+    view: &Entity<V>,
+    value: serde_json::Value,
+    cx: &mut App,
+) {
+    if let Some(state) = serde_json::from_value(value).log_err() {
+        view.update(cx, |view, cx| view.restore_state(state, cx));
+    }
+}
+
+pub(crate) fn restore_persisted_global<G: PersistedGlobal>(value: serde_json::Value, cx: &mut App) {
+    if let Some(state) = serde_json::from_value(value).log_err() {
+        let mut global = cx.lease_global::<G>();
+        global.restore_state(state, cx);
+        cx.end_global_lease(global);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{self as gpui, AppContext as _, TestAppContext};
+
+    struct Panel {
+        width: f32,
+    }
+
+    impl PersistedView for Panel {
+        type State = f32;
+
+        fn persistence_key(&self) -> SharedString {
+            "panel".into()
+        }
+
+        fn persisted_state(&self, _: &App) -> f32 {
+            self.width
+        }
+
+        fn restore_state(&mut self, width: f32, _: &mut Context<Self>) {
+            self.width = width;
+        }
+    }
+
+    #[gpui::test]
+    async fn test_persisted_view(cx: &mut TestAppContext) {
+        let panel = cx.new(|_| Panel { width: 100. });
+        cx.update(|cx| cx.register_persisted_view(&panel));
+        panel.update(cx, |panel, _| panel.width = 240.);
+        drop(panel);
+        cx.update(|_| {});
+
+        let panel = cx.new(|_| Panel { width: 100. });
+        cx.update(|cx| cx.register_persisted_view(&panel));
+        panel.read_with(cx, |panel, _| assert_eq!(panel.width, 240.));
+
+        panel.update(cx, |panel, _| panel.width = 320.);
+        cx.update(|cx| cx.save_persisted_state()).await.unwrap();
+        cx.update(|cx| {
+            assert_eq!(
+                cx.persistence.values.get("panel"),
+                Some(&serde_json::json!(320.))
+            )
+        });
+    }
+
+    #[gpui::test]
+    async fn test_persistence_path(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let dir = util::test::TempTree::new(serde_json::json!({
+            "state": {
+                "persisted.json": r#"{ "panel": 320.0 }"#,
+            },
+        }));
+        let path = dir.path().join("state/persisted.json");
+
+        // Views registered before the state finishes loading are restored once it does.
+        let panel = cx.new(|_| Panel { width: 240. });
+        cx.update(|cx| cx.register_persisted_view(&panel));
+        cx.update(|cx| cx.set_persistence_path(&path)).await;
+        panel.read_with(cx, |panel, _| assert_eq!(panel.width, 320.));
+
+        panel.update(cx, |panel, _| panel.width = 400.);
+        cx.update(|cx| cx.save_persisted_state()).await.unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(saved, serde_json::json!({ "panel": 400.0 }));
+    }
+}
@@ -27,15 +27,16 @@ mod test;
 mod windows;
 
 use crate::{
-    point, Action, AnyWindowHandle, App, AsyncWindowContext, BackgroundExecutor, Bounds,
-    DevicePixels, DispatchEventResult, Font, FontId, FontMetrics, FontRun, ForegroundExecutor,
-    GlyphId, GpuSpecs, ImageSource, Keymap, LineLayout, Pixels, PlatformInput, Point,
-    RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams, ScaledPixels, Scene,
-    SharedString, Size, SvgRenderer, SvgSize, Task, TaskLabel, Window, DEFAULT_WINDOW_SIZE,
+    point, AccessibilityNode, AccessibilityPreferences, Action, AnnouncementPriority,
+    AnyWindowHandle, App, AsyncWindowContext, BackgroundExecutor, Bounds, DevicePixels,
+    DispatchEventResult, Font, FontId, FontMetrics, FontRun, ForegroundExecutor, GlyphId, GpuSpecs,
+    ImageSource, Keymap, LineLayout, Pixels, PlatformInput, Point, Priority, RenderGlyphParams,
+    RenderImage, RenderImageParams, RenderSvgParams, Rgba, ScaledPixels, Scene, SharedString, Size,
+    SvgRenderer, SvgSize, Task, TaskLabel, Window, DEFAULT_WINDOW_SIZE,
 };
 use anyhow::{anyhow, Result};
 use async_task::Runnable;
-use futures::channel::oneshot;
+use futures::channel::{mpsc, oneshot};
 use image::codecs::gif::GifDecoder;
 use image::{AnimationDecoder as _, Frame};
 use parking::Unparker;
@@ -157,6 +158,19 @@ pub(crate) trait Platform: 'static {
         &self,
     ) -> oneshot::Receiver<Result<Vec<Box<dyn ScreenCaptureSource>>>>;
 
+    /// Presents the system's screen capture picker, resolving to the source the user
+    /// selected, or `None` if the picker was dismissed.
+    fn pick_screen_capture_source(
+        &self,
+    ) -> oneshot::Receiver<Result<Option<Box<dyn ScreenCaptureSource>>>> {
+        let (mut tx, rx) = oneshot::channel();
+        tx.send(Err(anyhow!(
+            "screen capture picker is not supported on this platform"
+        )))
+        .ok();
+        rx
+    }
+
     fn open_window(
         &self,
         handle: AnyWindowHandle,
@@ -165,6 +179,9 @@ pub(crate) trait Platform: 'static {
 
     /// Returns the appearance of the application's windows.
     fn window_appearance(&self) -> WindowAppearance;
+    fn accent_color(&self) -> Option<Rgba> {
+        None
+    }
 
     fn open_url(&self, url: &str);
     fn on_open_urls(&self, callback: Box<dyn FnMut(Vec<String>)>);
@@ -174,14 +191,51 @@ pub(crate) trait Platform: 'static {
         &self,
         options: PathPromptOptions,
     ) -> oneshot::Receiver<Result<Option<Vec<PathBuf>>>>;
-    fn prompt_for_new_path(&self, directory: &Path) -> oneshot::Receiver<Result<Option<PathBuf>>>;
+    fn prompt_for_new_path(
+        &self,
+        options: NewPathPromptOptions,
+    ) -> oneshot::Receiver<Result<Option<PathBuf>>>;
     fn can_select_mixed_files_and_dirs(&self) -> bool;
+    /// Relays an error by default, which is what Windows does until it has a
+    /// toast notification implementation.
+    fn send_notification(
+        &self,
+        _title: &str,
+        _body: &str,
+        _actions: &[&str],
+    ) -> oneshot::Receiver<Result<NotificationResponse>> {
+        let (done_tx, done_rx) = oneshot::channel();
+        done_tx
+            .send(Err(anyhow!(
+                "notifications are not supported on this platform"
+            )))
+            .ok();
+        done_rx
+    }
+    fn pick_color(&self, _initial: Rgba) -> Option<mpsc::UnboundedReceiver<Rgba>> {
+        None
+    }
     fn reveal_path(&self, path: &Path);
     fn open_with_system(&self, path: &Path);
 
     fn on_quit(&self, callback: Box<dyn FnMut()>);
+    /// Called when the user or the OS asks to quit the app other than through [`Platform::quit`],
+    /// such as from the application menu or when logging out. Returning `false` cancels the
+    /// request, after which the app is expected to call [`Platform::quit`] itself if it still
+    /// decides to quit. Only macOS currently reports these requests.
+    fn on_quit_requested(&self, _callback: Box<dyn FnMut() -> bool>) {}
     fn on_reopen(&self, callback: Box<dyn FnMut()>);
     fn on_keyboard_layout_change(&self, callback: Box<dyn FnMut()>);
+    fn on_displays_changed(&self, _callback: Box<dyn FnMut()>) {}
+    fn on_system_appearance_changed(&self, _callback: Box<dyn FnMut()>) {}
+    fn accessibility_preferences(&self) -> AccessibilityPreferences {
+        AccessibilityPreferences::default()
+    }
+    fn on_accessibility_preferences_changed(&self, _callback: Box<dyn FnMut()>) {}
+    fn on_system_event(&self, _callback: Box<dyn FnMut(SystemEvent)>) {}
+    fn power_state(&self) -> PowerState {
+        PowerState::default()
+    }
 
     fn set_menus(&self, menus: Vec<Menu>, keymap: &Keymap);
     fn get_menus(&self) -> Option<Vec<OwnedMenu>> {
@@ -189,11 +243,26 @@ pub(crate) trait Platform: 'static {
     }
 
     fn set_dock_menu(&self, menu: Vec<MenuItem>, keymap: &Keymap);
+    fn set_status_item(&self, _item: Option<StatusItem>, _keymap: &Keymap) {}
+    fn on_status_item_click(&self, _callback: Box<dyn FnMut()>) {}
     fn add_recent_document(&self, _path: &Path) {}
+    fn set_dock_badge(&self, _badge: Option<&str>) {}
+    fn set_dock_progress(&self, _progress: Option<f32>) {}
+    fn can_share(&self) -> bool {
+        false
+    }
+    fn share(&self, _items: Vec<ShareItem>) {}
+    fn announce(&self, _text: &str, _priority: AnnouncementPriority) {}
     fn on_app_menu_action(&self, callback: Box<dyn FnMut(&dyn Action)>);
     fn on_will_open_app_menu(&self, callback: Box<dyn FnMut()>);
     fn on_validate_app_menu_command(&self, callback: Box<dyn FnMut(&dyn Action) -> bool>);
     fn keyboard_layout(&self) -> String;
+    fn layout_keystroke(&self, _physical: &Keystroke) -> Option<Keystroke> {
+        None
+    }
+    fn physical_keystroke(&self, _keystroke: &Keystroke) -> Option<Keystroke> {
+        None
+    }
 
     fn compositor_name(&self) -> &'static str {
         ""
@@ -228,6 +297,22 @@ pub trait PlatformDisplay: Send + Sync + Debug {
     /// Get the bounds for this display
     fn bounds(&self) -> Bounds<Pixels>;
 
+    /// Get the bounds of the part of this display that windows can occupy, which excludes
+    /// taskbars, docks and menu bars
+    fn visible_bounds(&self) -> Bounds<Pixels> {
+        self.bounds()
+    }
+
+    /// Get the number of physical pixels per logical pixel on this display
+    fn scale_factor(&self) -> f32 {
+        1.
+    }
+
+    /// Get the refresh rate of this display in hertz, if it is known
+    fn refresh_rate(&self) -> Option<f32> {
+        None
+    }
+
     /// Get the default bounds for this display to place a window
     fn default_bounds(&self) -> Bounds<Pixels> {
         let center = self.bounds().center();
@@ -289,6 +374,22 @@ pub enum ResizeEdge {
     TopLeft,
 }
 
+/// What double-clicking a window's title bar, or a [window drag region], does
+///
+/// [window drag region]: crate::Interactivity::window_drag_region
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum TitlebarDoubleClickAction {
+    /// Follow the user's preference where the platform has one (macOS), or zoom otherwise
+    #[default]
+    System,
+    /// Zoom the window
+    Zoom,
+    /// Minimize the window
+    Minimize,
+    /// Do nothing
+    Ignore,
+}
+
 /// A type to describe the appearance of a window
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
 pub enum WindowDecorations {
@@ -375,6 +476,9 @@ pub(crate) struct RequestFrameOptions {
 pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     fn bounds(&self) -> Bounds<Pixels>;
     fn is_maximized(&self) -> bool;
+    fn is_minimized(&self) -> bool {
+        false
+    }
     fn window_bounds(&self) -> WindowBounds;
     fn content_size(&self) -> Size<Pixels>;
     fn scale_factor(&self) -> f32;
@@ -396,6 +500,7 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     fn is_hovered(&self) -> bool;
     fn set_title(&mut self, title: &str);
     fn set_background_appearance(&self, background_appearance: WindowBackgroundAppearance);
+    fn set_level(&self, _level: WindowLevel) {}
     fn minimize(&self);
     fn zoom(&self);
     fn toggle_fullscreen(&self);
@@ -409,13 +514,48 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     fn on_should_close(&self, callback: Box<dyn FnMut() -> bool>);
     fn on_close(&self, callback: Box<dyn FnOnce()>);
     fn on_appearance_changed(&self, callback: Box<dyn FnMut()>);
+    /// Returns whether the window is entirely hidden, such as behind other windows or
+    /// on an inactive workspace.
+    fn is_occluded(&self) -> bool {
+        false
+    }
+    fn on_occlusion_change(&self, _callback: Box<dyn FnMut(bool)>) {}
     fn draw(&self, scene: &Scene);
+    /// Draws the scene, only repainting `damage`, the region that changed since the last
+    /// presented frame, if the renderer keeps the rest of that frame. `None` repaints the whole
+    /// window, which is also what renderers that don't keep their frames do.
+    fn draw_damaged(&self, scene: &Scene, _damage: Option<Bounds<ScaledPixels>>) {
+        self.draw(scene)
+    }
     fn completed_frame(&self) {}
+    fn capture_frame(&self, _scene: &Scene) -> Result<image::RgbaImage> {
+        Err(anyhow!(
+            "capturing window contents is not supported on this platform"
+        ))
+    }
     fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas>;
+    /// Changes how the window's renderer anti-aliases what it draws.
+    fn set_antialiasing(&self, _antialiasing: Antialiasing) {}
+    /// How many times brighter than SDR white the window can currently show colors, which is 1
+    /// unless the window renders in HDR and its display supports it.
+    fn hdr_headroom(&self) -> f32 {
+        1.
+    }
+    /// Replaces the elements the window exposes to assistive technology. Called after
+    /// a frame is drawn, whenever its accessibility tree has changed.
+    fn update_accessibility_tree(&self, _nodes: &[AccessibilityNode]) {}
 
     // macOS specific methods
     fn set_edited(&mut self, _edited: bool) {}
     fn show_character_palette(&self) {}
+    fn show_file_preview(&self, _paths: Vec<PathBuf>) {}
+    fn hide_file_preview(&self) {}
+    fn is_file_preview_visible(&self) -> bool {
+        false
+    }
+    fn titlebar_double_click_action(&self) -> TitlebarDoubleClickAction {
+        TitlebarDoubleClickAction::Zoom
+    }
 
     #[cfg(target_os = "windows")]
     fn get_raw_handle(&self) -> windows::HWND;
@@ -454,7 +594,7 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
 #[doc(hidden)]
 pub trait PlatformDispatcher: Send + Sync {
     fn is_main_thread(&self) -> bool;
-    fn dispatch(&self, runnable: Runnable, label: Option<TaskLabel>);
+    fn dispatch(&self, runnable: Runnable, label: Option<TaskLabel>, priority: Priority);
     fn dispatch_on_main_thread(&self, runnable: Runnable);
     fn dispatch_after(&self, duration: Duration, runnable: Runnable);
     fn park(&self, timeout: Option<Duration>) -> bool;
@@ -595,7 +735,13 @@ pub(crate) trait PlatformAtlas: Send + Sync {
         key: &AtlasKey,
         build: &mut dyn FnMut() -> Result<Option<(Size<DevicePixels>, Cow<'a, [u8]>)>>,
     ) -> Result<Option<AtlasTile>>;
+    /// Removes a tile, making its space in the atlas available to other tiles.
     fn remove(&self, key: &AtlasKey);
+    /// Removes every tile whose key doesn't satisfy the given predicate.
+    fn retain(&self, predicate: &mut dyn FnMut(&AtlasKey) -> bool);
+    /// Sets the size of the textures that are allocated for tiles from now on. Tiles larger than
+    /// this are given a texture of their own.
+    fn set_page_size(&self, size: Size<DevicePixels>);
 }
 
 struct AtlasTextureList<T> {
@@ -665,7 +811,7 @@ pub(crate) enum AtlasTextureKind {
     Path = 2,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(C)]
 pub(crate) struct TileId(pub(crate) u32);
 
@@ -949,6 +1095,26 @@ pub struct WindowOptions {
     /// Whether to use client or server side decorations. Wayland only
     /// Note that this may be ignored.
     pub window_decorations: Option<WindowDecorations>,
+
+    /// The window this window should be attached to. Child windows stay above their
+    /// parent, and on platforms that support it, move and minimize along with it.
+    pub parent: Option<AnyWindowHandle>,
+
+    /// Whether this window is modal with respect to its parent. While a modal window is
+    /// open, its parent ignores mouse and keyboard input. On macOS the window is presented
+    /// as a sheet, on Windows the parent is disabled, and on X11 the window manager is asked
+    /// to treat it as a modal dialog.
+    /// Has no effect if `parent` is `None`.
+    pub modal: bool,
+
+    /// The level of the window relative to other windows on the screen.
+    /// If `None`, the default level for the window's kind is used.
+    pub level: Option<WindowLevel>,
+
+    /// Whether the window renders to an HDR swapchain on displays that support it, so that
+    /// images and surfaces can be shown brighter than SDR white. Only macOS supports this,
+    /// other platforms render in SDR.
+    pub hdr: bool,
 }
 
 /// The variables that can be configured when creating a new window
@@ -985,6 +1151,28 @@ pub(crate) struct WindowParams {
     pub display_id: Option<DisplayId>,
 
     pub window_min_size: Option<Size<Pixels>>,
+
+    /// The native handle of the window this window should be attached to
+    pub parent: Option<raw_window_handle::RawWindowHandle>,
+
+    /// Whether the window should block input to its parent while it is open
+    #[cfg_attr(feature = "wayland", allow(dead_code))]
+    pub modal: bool,
+
+    #[cfg_attr(feature = "wayland", allow(dead_code))]
+    pub level: Option<WindowLevel>,
+
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub hdr: bool,
+
+    #[cfg_attr(
+        all(
+            any(target_os = "linux", target_os = "freebsd"),
+            not(any(feature = "x11", feature = "wayland"))
+        ),
+        allow(dead_code)
+    )]
+    pub antialiasing: Antialiasing,
 }
 
 /// Represents the status of how a window should be opened.
@@ -1015,6 +1203,20 @@ impl WindowBounds {
             WindowBounds::Fullscreen(bounds) => *bounds,
         }
     }
+
+    /// Moves and shrinks the inner bounds as little as possible so that they lie within
+    /// `area`, preserving whether the window is windowed, maximized or fullscreen.
+    pub fn fit_within(&self, area: Bounds<Pixels>) -> Self {
+        self.map(|bounds| bounds.fit_within(area))
+    }
+
+    pub(crate) fn map(&self, f: impl FnOnce(Bounds<Pixels>) -> Bounds<Pixels>) -> Self {
+        match self {
+            WindowBounds::Windowed(bounds) => WindowBounds::Windowed(f(*bounds)),
+            WindowBounds::Maximized(bounds) => WindowBounds::Maximized(f(*bounds)),
+            WindowBounds::Fullscreen(bounds) => WindowBounds::Fullscreen(f(*bounds)),
+        }
+    }
 }
 
 impl Default for WindowOptions {
@@ -1035,6 +1237,10 @@ impl Default for WindowOptions {
             app_id: None,
             window_min_size: None,
             window_decorations: None,
+            parent: None,
+            modal: false,
+            level: None,
+            hdr: false,
         }
     }
 }
@@ -1062,6 +1268,88 @@ pub enum WindowKind {
     /// A window that appears above all other windows, usually used for alerts or popups
     /// use sparingly!
     PopUp,
+
+    /// A utility panel, such as a detached palette or inspector. Tool windows have thinner
+    /// chrome, are left out of the taskbar, and don't become the application's main window.
+    /// On macOS they float above normal windows without activating the application; on
+    /// other platforms, give them a parent window to keep them above it. Pair with
+    /// `focus: false` in [`WindowOptions`] to show one without taking focus.
+    Tool,
+}
+
+/// Whether a window is shown normally, maximized, fullscreen, or minimized
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WindowState {
+    /// The window is shown at its own size and position
+    #[default]
+    Windowed,
+
+    /// The window fills the work area of its display
+    Maximized,
+
+    /// The window covers its entire display
+    Fullscreen,
+
+    /// The window is minimized or otherwise hidden by the window manager
+    Minimized,
+}
+
+/// How a window's renderer anti-aliases what it draws, see [`crate::App::set_antialiasing`].
+///
+/// Quads, rounded corners, borders, shadows and text are always anti-aliased analytically by
+/// their shaders, which costs next to nothing. Paths have no analytic anti-aliasing, so their
+/// edges are only smoothed by multisampling.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Antialiasing {
+    /// Only use analytic anti-aliasing. Paths are drawn with jagged edges, but rasterizing them
+    /// takes less GPU time and memory, which saves battery on integrated GPUs.
+    Analytic,
+    /// Also multisample paths with the given number of samples per pixel, which is rounded to
+    /// 1, 2 or 4 since those are supported by every GPU.
+    Multisample(u32),
+}
+
+impl Default for Antialiasing {
+    fn default() -> Self {
+        Self::Multisample(4)
+    }
+}
+
+impl Antialiasing {
+    /// The number of samples per pixel to rasterize paths with. All Metal devices support up to
+    /// 4, see <https://developer.apple.com/documentation/metal/mtldevice/1433355-supportstexturesamplecount>.
+    #[cfg_attr(
+        all(
+            any(target_os = "linux", target_os = "freebsd"),
+            not(any(feature = "x11", feature = "wayland"))
+        ),
+        allow(dead_code)
+    )]
+    pub(crate) fn path_sample_count(self) -> u32 {
+        match self {
+            Self::Analytic => 1,
+            Self::Multisample(sample_count) => sample_count.clamp(1, 4).next_power_of_two(),
+        }
+    }
+}
+
+/// The level of a window, which determines whether it is kept above other windows
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WindowLevel {
+    /// The level of normal application windows
+    #[default]
+    Normal,
+
+    /// Kept above the normal windows of this application, and hidden while the
+    /// application is inactive, like a tool palette.
+    /// Treated as [`WindowLevel::AlwaysOnTop`] on platforms without per-application levels.
+    Floating,
+
+    /// Kept above the normal windows of all applications, like a picture-in-picture video
+    AlwaysOnTop,
+
+    /// The level of pop-up menus, above almost everything else
+    PopUpMenu,
 }
 
 /// The appearance of the window, as defined by the operating system.
@@ -1116,10 +1404,15 @@ pub enum WindowBackgroundAppearance {
     ///
     /// Not always supported.
     Blurred,
+    /// A translucent system material behind the window, such as vibrancy on macOS
+    /// or Mica on Windows 11.
+    ///
+    /// Falls back to [`WindowBackgroundAppearance::Blurred`] where unsupported.
+    Vibrant,
 }
 
 /// The options that can be configured for a file dialog prompt
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct PathPromptOptions {
     /// Should the prompt allow files to be selected?
     pub files: bool,
@@ -1127,6 +1420,60 @@ pub struct PathPromptOptions {
     pub directories: bool,
     /// Should the prompt allow multiple files to be selected?
     pub multiple: bool,
+    /// The kinds of files the prompt allows to be selected. All files can be selected when empty.
+    ///
+    /// Filters are applied by the platform's file dialog, so prompts that applications show in
+    /// its place, such as when the XDG desktop portal is missing on Linux, don't apply them.
+    pub filters: Vec<PathPromptFilter>,
+}
+
+/// The options that can be configured for a save file dialog prompt
+#[derive(Clone, Debug)]
+pub struct NewPathPromptOptions {
+    /// The directory the prompt initially shows
+    pub directory: PathBuf,
+    /// The file name the prompt is initially filled in with
+    pub suggested_name: Option<SharedString>,
+    /// The kinds of files the prompt offers to save as. Any name is allowed when empty.
+    ///
+    /// Like [`PathPromptOptions::filters`], these are only applied by the platform's file dialog.
+    pub filters: Vec<PathPromptFilter>,
+    /// Should the prompt allow new directories to be created?
+    pub can_create_directories: bool,
+}
+
+impl NewPathPromptOptions {
+    /// Creates options for a save prompt starting in the given directory
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            suggested_name: None,
+            filters: Vec::new(),
+            can_create_directories: true,
+        }
+    }
+}
+
+/// A named kind of file that a file dialog prompt can be restricted to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathPromptFilter {
+    /// The name shown for this kind of file, e.g. "Images"
+    pub name: SharedString,
+    /// The file extensions matched by this filter, without a leading dot
+    pub extensions: Vec<SharedString>,
+}
+
+impl PathPromptFilter {
+    /// Creates a filter matching files with any of the given extensions
+    pub fn new(
+        name: impl Into<SharedString>,
+        extensions: impl IntoIterator<Item = impl Into<SharedString>>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            extensions: extensions.into_iter().map(Into::into).collect(),
+        }
+    }
 }
 
 /// What kind of prompt styling to show
@@ -1142,6 +1489,58 @@ pub enum PromptLevel {
     Critical,
 }
 
+/// How the user responded to a notification sent with [`App::send_notification`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NotificationResponse {
+    /// The body of the notification was clicked
+    Clicked,
+
+    /// One of the notification's actions was chosen, identified by its index
+    Action(usize),
+}
+
+/// Content that can be sent to other applications with [`App::share`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShareItem {
+    /// Plain text, such as a selection
+    Text(String),
+
+    /// A link, such as a collaboration URL
+    Url(String),
+
+    /// A file on disk
+    File(PathBuf),
+}
+
+/// A change in the system's power or session state, see [`App::on_system_event`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SystemEvent {
+    /// The system is about to go to sleep
+    WillSleep,
+
+    /// The system woke up from sleep
+    DidWake,
+
+    /// The user's session was locked
+    SessionLocked,
+
+    /// The user's session was unlocked
+    SessionUnlocked,
+
+    /// The system's power source or low power mode changed, see [`App::power_state`]
+    PowerStateChanged,
+}
+
+/// The power state of the system
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PowerState {
+    /// Whether the system is running on battery power
+    pub on_battery: bool,
+
+    /// Whether the user asked applications to reduce their energy usage
+    pub low_power_mode: bool,
+}
+
 /// The style of the cursor (pointer)
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum CursorStyle {
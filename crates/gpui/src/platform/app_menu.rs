@@ -1,4 +1,5 @@
-use crate::{Action, App, Platform, SharedString};
+use crate::{Action, App, Image, Platform, SharedString};
+use std::sync::Arc;
 use util::ResultExt;
 
 /// A menu of the application, either a main menu or a submenu
@@ -39,6 +40,12 @@ pub enum MenuItem {
         /// The OS Action that corresponds to this action, if any
         /// See [`OsAction`] for more information
         os_action: Option<OsAction>,
+
+        /// Whether this menu item is displayed with a checkmark
+        checked: bool,
+
+        /// Whether this menu item is shown but can't be selected
+        disabled: bool,
     },
 }
 
@@ -59,6 +66,8 @@ impl MenuItem {
             name: name.into(),
             action: Box::new(action),
             os_action: None,
+            checked: false,
+            disabled: false,
         }
     }
 
@@ -72,7 +81,33 @@ impl MenuItem {
             name: name.into(),
             action: Box::new(action),
             os_action: Some(os_action),
+            checked: false,
+            disabled: false,
+        }
+    }
+
+    /// Sets whether this menu item is displayed with a checkmark.
+    /// Has no effect on separators and submenus.
+    ///
+    /// Menus are rebuilt on each call to [`App::set_menus`], so toggling a
+    /// checkmark at runtime is done by calling it again with updated items.
+    pub fn checked(mut self, checked: bool) -> Self {
+        if let Self::Action { checked: c, .. } = &mut self {
+            *c = checked;
+        }
+        self
+    }
+
+    /// Sets whether this menu item is shown but can't be selected, regardless of
+    /// whether its action is available. Has no effect on separators and submenus.
+    ///
+    /// Like checkmarks, this is updated at runtime by calling [`App::set_menus`]
+    /// again with updated items.
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        if let Self::Action { disabled: d, .. } = &mut self {
+            *d = disabled;
         }
+        self
     }
 
     /// Create an OwnedMenuItem from this MenuItem
@@ -84,10 +119,14 @@ impl MenuItem {
                 name,
                 action,
                 os_action,
+                checked,
+                disabled,
             } => OwnedMenuItem::Action {
                 name: name.into(),
                 action,
                 os_action,
+                checked,
+                disabled,
             },
         }
     }
@@ -122,6 +161,12 @@ pub enum OwnedMenuItem {
         /// The OS Action that corresponds to this action, if any
         /// See [`OsAction`] for more information
         os_action: Option<OsAction>,
+
+        /// Whether this menu item is displayed with a checkmark
+        checked: bool,
+
+        /// Whether this menu item is shown but can't be selected
+        disabled: bool,
     },
 }
 
@@ -134,15 +179,37 @@ impl Clone for OwnedMenuItem {
                 name,
                 action,
                 os_action,
+                checked,
+                disabled,
             } => OwnedMenuItem::Action {
                 name: name.clone(),
                 action: action.boxed_clone(),
                 os_action: *os_action,
+                checked: *checked,
+                disabled: *disabled,
             },
         }
     }
 }
 
+/// An item in the menu bar's status area, which stays available while the application has no
+/// open windows. Status items are only shown on macOS, and are ignored on other platforms.
+#[derive(Default)]
+pub struct StatusItem {
+    /// The text shown in the status area, if any
+    pub title: Option<SharedString>,
+
+    /// The icon shown in the status area, if any
+    pub icon: Option<Arc<Image>>,
+
+    /// The tooltip shown when hovering the status item
+    pub tooltip: Option<SharedString>,
+
+    /// The menu shown when the status item is clicked. When empty, clicks are
+    /// reported to handlers registered with [`App::on_status_item_click`] instead.
+    pub menu: Vec<MenuItem>,
+}
+
 // TODO: As part of the global selections refactor, these should
 // be moved to GPUI-provided actions that make this association
 // without leaking the platform details to GPUI users
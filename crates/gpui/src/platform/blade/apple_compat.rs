@@ -22,6 +22,9 @@ pub unsafe fn new_renderer(
     native_view: *mut c_void,
     bounds: crate::Size<f32>,
     transparent: bool,
+    path_sample_count: u32,
+    // Blade surfaces are always SDR.
+    _hdr: bool,
 ) -> Renderer {
     use raw_window_handle as rwh;
     struct RawWindow {
@@ -54,6 +57,7 @@ pub unsafe fn new_renderer(
                 depth: 1,
             },
             transparent,
+            path_sample_count,
         },
     )
     .unwrap()
@@ -14,6 +14,11 @@ pub(crate) const PATH_TEXTURE_FORMAT: gpu::TextureFormat = gpu::TextureFormat::R
 
 pub(crate) struct BladeAtlas(Mutex<BladeAtlasState>);
 
+const DEFAULT_PAGE_SIZE: Size<DevicePixels> = Size {
+    width: DevicePixels(1024),
+    height: DevicePixels(1024),
+};
+
 struct PendingUpload {
     id: AtlasTextureId,
     bounds: Bounds<DevicePixels>,
@@ -28,6 +33,7 @@ struct BladeAtlasState {
     initializations: Vec<AtlasTextureId>,
     uploads: Vec<PendingUpload>,
     path_sample_count: u32,
+    page_size: Size<DevicePixels>,
 }
 
 #[cfg(gles)]
@@ -60,6 +66,7 @@ impl BladeAtlas {
             initializations: Vec::new(),
             uploads: Vec::new(),
             path_sample_count,
+            page_size: DEFAULT_PAGE_SIZE,
         }))
     }
 
@@ -67,6 +74,19 @@ impl BladeAtlas {
         self.0.lock().destroy();
     }
 
+    /// Sets how many samples paths are rasterized with, discarding the path textures that were
+    /// allocated with the previous sample count. The GPU must be done with them.
+    pub(crate) fn set_path_sample_count(&self, path_sample_count: u32) {
+        let mut lock = self.0.lock();
+        if lock.path_sample_count != path_sample_count {
+            lock.path_sample_count = path_sample_count;
+            let lock = &mut *lock;
+            for mut texture in lock.storage.path_textures.drain().flatten() {
+                texture.destroy(&lock.gpu);
+            }
+        }
+    }
+
     pub(crate) fn clear_textures(&self, texture_kind: AtlasTextureKind) {
         let mut lock = self.0.lock();
         let textures = &mut lock.storage[texture_kind];
@@ -138,15 +158,17 @@ impl PlatformAtlas for BladeAtlas {
     fn remove(&self, key: &AtlasKey) {
         let mut lock = self.0.lock();
 
-        let Some(id) = lock.tiles_by_key.remove(key).map(|tile| tile.texture_id) else {
+        let Some(tile) = lock.tiles_by_key.remove(key) else {
             return;
         };
+        let id = tile.texture_id;
 
         let Some(texture_slot) = lock.storage[id.kind].textures.get_mut(id.index as usize) else {
             return;
         };
 
         if let Some(mut texture) = texture_slot.take() {
+            texture.allocator.deallocate(tile.tile_id.into());
             texture.decrement_ref_count();
             if texture.is_unreferenced() {
                 lock.storage[id.kind]
@@ -158,6 +180,24 @@ impl PlatformAtlas for BladeAtlas {
             }
         }
     }
+
+    fn retain(&self, predicate: &mut dyn FnMut(&AtlasKey) -> bool) {
+        let keys = self
+            .0
+            .lock()
+            .tiles_by_key
+            .keys()
+            .filter(|key| !predicate(key))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in keys {
+            self.remove(&key);
+        }
+    }
+
+    fn set_page_size(&self, size: Size<DevicePixels>) {
+        self.0.lock().page_size = size;
+    }
 }
 
 impl BladeAtlasState {
@@ -183,12 +223,7 @@ impl BladeAtlasState {
         min_size: Size<DevicePixels>,
         kind: AtlasTextureKind,
     ) -> &mut BladeAtlasTexture {
-        const DEFAULT_ATLAS_SIZE: Size<DevicePixels> = Size {
-            width: DevicePixels(1024),
-            height: DevicePixels(1024),
-        };
-
-        let size = min_size.max(&DEFAULT_ATLAS_SIZE);
+        let size = min_size.max(&self.page_size);
         let format;
         let usage;
         match kind {
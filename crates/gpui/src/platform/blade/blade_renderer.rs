@@ -3,9 +3,9 @@
 
 use super::{BladeAtlas, BladeContext, PATH_TEXTURE_FORMAT};
 use crate::{
-    AtlasTextureKind, AtlasTile, Background, Bounds, ContentMask, DevicePixels, GpuSpecs,
-    MonochromeSprite, Path, PathId, PathVertex, PolychromeSprite, PrimitiveBatch, Quad,
-    ScaledPixels, Scene, Shadow, Size, Underline,
+    AtlasTextureKind, AtlasTile, Background, Bounds, ContentMask, CustomShaderId, DevicePixels,
+    GpuSpecs, LayerSprite, MonochromeSprite, Path, PathId, PathVertex, PolychromeSprite,
+    PrimitiveBatch, Quad, ScaledPixels, Scene, ShaderQuad, Shadow, Size, Underline,
 };
 use blade_graphics as gpu;
 use blade_util::{BufferBelt, BufferBeltDescriptor};
@@ -13,19 +13,18 @@ use bytemuck::{Pod, Zeroable};
 use collections::HashMap;
 #[cfg(target_os = "macos")]
 use media::core_video::CVMetalTextureCache;
-use std::{mem, sync::Arc};
+use std::{mem, slice, sync::Arc};
 
 const MAX_FRAME_TIME_MS: u32 = 10000;
-// Use 4x MSAA, all devices support it.
-// https://developer.apple.com/documentation/metal/mtldevice/1433355-supportstexturesamplecount
-const PATH_SAMPLE_COUNT: u32 = 4;
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 struct GlobalParams {
     viewport_size: [f32; 2],
     premultiplied_alpha: u32,
-    pad: u32,
+    /// How many times brighter than SDR white the surface can show colors, which is always 1
+    /// since blade surfaces are SDR.
+    hdr_headroom: f32,
 }
 
 //Note: we can't use `Bounds` directly here because
@@ -51,6 +50,8 @@ impl From<Bounds<ScaledPixels>> for PodBounds {
 struct SurfaceParams {
     bounds: PodBounds,
     content_mask: PodBounds,
+    brightness: f32,
+    pad: u32,
 }
 
 #[derive(blade_macros::ShaderData)]
@@ -101,6 +102,20 @@ struct ShaderPolySpritesData {
     b_poly_sprites: gpu::BufferPiece,
 }
 
+#[derive(blade_macros::ShaderData)]
+struct ShaderLayerSpritesData {
+    globals: GlobalParams,
+    t_sprite: gpu::TextureView,
+    s_sprite: gpu::Sampler,
+    b_layer_sprites: gpu::BufferPiece,
+}
+
+#[derive(blade_macros::ShaderData)]
+struct ShaderCustomQuadsData {
+    globals: GlobalParams,
+    b_shader_quads: gpu::BufferPiece,
+}
+
 #[derive(blade_macros::ShaderData)]
 struct ShaderSurfacesData {
     globals: GlobalParams,
@@ -126,11 +141,15 @@ struct BladePipelines {
     underlines: gpu::RenderPipeline,
     mono_sprites: gpu::RenderPipeline,
     poly_sprites: gpu::RenderPipeline,
+    layer_sprites: gpu::RenderPipeline,
     surfaces: gpu::RenderPipeline,
+    surface_info: gpu::SurfaceInfo,
+    /// Pipelines for the scene's custom shaders, or `None` for shaders that failed to compile.
+    custom_shaders: HashMap<CustomShaderId, Option<gpu::RenderPipeline>>,
 }
 
 impl BladePipelines {
-    fn new(gpu: &gpu::Context, surface_info: gpu::SurfaceInfo) -> Self {
+    fn new(gpu: &gpu::Context, surface_info: gpu::SurfaceInfo, path_sample_count: u32) -> Self {
         use gpu::ShaderData as _;
 
         log::info!(
@@ -152,13 +171,9 @@ impl BladePipelines {
         shader.check_struct_size::<Underline>();
         shader.check_struct_size::<MonochromeSprite>();
         shader.check_struct_size::<PolychromeSprite>();
+        shader.check_struct_size::<LayerSprite>();
 
-        // See https://apoorvaj.io/alpha-compositing-opengl-blending-and-premultiplied-alpha/
-        let blend_mode = match surface_info.alpha {
-            gpu::AlphaMode::Ignored => gpu::BlendState::ALPHA_BLENDING,
-            gpu::AlphaMode::PreMultiplied => gpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
-            gpu::AlphaMode::PostMultiplied => gpu::BlendState::ALPHA_BLENDING,
-        };
+        let blend_mode = Self::blend_mode(surface_info);
         let color_targets = &[gpu::ColorTargetState {
             format: surface_info.format,
             blend: Some(blend_mode),
@@ -211,7 +226,7 @@ impl BladePipelines {
                     write_mask: gpu::ColorWrites::default(),
                 }],
                 multisample_state: gpu::MultisampleState {
-                    sample_count: PATH_SAMPLE_COUNT,
+                    sample_count: path_sample_count,
                     ..Default::default()
                 },
             }),
@@ -271,6 +286,20 @@ impl BladePipelines {
                 color_targets,
                 multisample_state: gpu::MultisampleState::default(),
             }),
+            layer_sprites: gpu.create_render_pipeline(gpu::RenderPipelineDesc {
+                name: "layer_sprites",
+                data_layouts: &[&ShaderLayerSpritesData::layout()],
+                vertex: shader.at("vs_layer_sprite"),
+                vertex_fetches: &[],
+                primitive: gpu::PrimitiveState {
+                    topology: gpu::PrimitiveTopology::TriangleStrip,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                fragment: Some(shader.at("fs_layer_sprite")),
+                color_targets,
+                multisample_state: gpu::MultisampleState::default(),
+            }),
             surfaces: gpu.create_render_pipeline(gpu::RenderPipelineDesc {
                 name: "surfaces",
                 data_layouts: &[&ShaderSurfacesData::layout()],
@@ -285,6 +314,68 @@ impl BladePipelines {
                 color_targets,
                 multisample_state: gpu::MultisampleState::default(),
             }),
+            surface_info,
+            custom_shaders: HashMap::default(),
+        }
+    }
+
+    fn blend_mode(surface_info: gpu::SurfaceInfo) -> gpu::BlendState {
+        // See https://apoorvaj.io/alpha-compositing-opengl-blending-and-premultiplied-alpha/
+        match surface_info.alpha {
+            gpu::AlphaMode::Ignored => gpu::BlendState::ALPHA_BLENDING,
+            gpu::AlphaMode::PreMultiplied => gpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            gpu::AlphaMode::PostMultiplied => gpu::BlendState::ALPHA_BLENDING,
+        }
+    }
+
+    /// Builds pipelines for any custom shaders in the scene that haven't been seen yet.
+    fn prepare_custom_shaders(&mut self, gpu: &gpu::Context, scene: &Scene) {
+        use gpu::ShaderData as _;
+
+        for (id, shader) in &scene.custom_shaders {
+            if self.custom_shaders.contains_key(id) {
+                continue;
+            }
+
+            let source = format!("{}\n{}", include_str!("custom_shader.wgsl"), shader.wgsl);
+            // `create_shader` panics on invalid source, so validate it up front.
+            let validation = naga::front::wgsl::parse_str(&source)
+                .map_err(|error| error.emit_to_string(&source))
+                .and_then(|module| {
+                    naga::valid::Validator::new(
+                        naga::valid::ValidationFlags::all(),
+                        naga::valid::Capabilities::all(),
+                    )
+                    .validate(&module)
+                    .map_err(|error| error.emit_to_string(&source))
+                });
+            if let Err(error) = validation {
+                log::error!("failed to compile custom shader: {error}");
+                self.custom_shaders.insert(*id, None);
+                continue;
+            }
+
+            let shader = gpu.create_shader(gpu::ShaderDesc { source: &source });
+            shader.check_struct_size::<ShaderQuad>();
+            let pipeline = gpu.create_render_pipeline(gpu::RenderPipelineDesc {
+                name: "custom_shader_quads",
+                data_layouts: &[&ShaderCustomQuadsData::layout()],
+                vertex: shader.at("vs_shader_quad"),
+                vertex_fetches: &[],
+                primitive: gpu::PrimitiveState {
+                    topology: gpu::PrimitiveTopology::TriangleStrip,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                fragment: Some(shader.at("fs_shader_quad")),
+                color_targets: &[gpu::ColorTargetState {
+                    format: self.surface_info.format,
+                    blend: Some(Self::blend_mode(self.surface_info)),
+                    write_mask: gpu::ColorWrites::default(),
+                }],
+                multisample_state: gpu::MultisampleState::default(),
+            });
+            self.custom_shaders.insert(*id, Some(pipeline));
         }
     }
 
@@ -296,13 +387,20 @@ impl BladePipelines {
         gpu.destroy_render_pipeline(&mut self.underlines);
         gpu.destroy_render_pipeline(&mut self.mono_sprites);
         gpu.destroy_render_pipeline(&mut self.poly_sprites);
+        gpu.destroy_render_pipeline(&mut self.layer_sprites);
         gpu.destroy_render_pipeline(&mut self.surfaces);
+        for pipeline in self.custom_shaders.values_mut().flatten() {
+            gpu.destroy_render_pipeline(pipeline);
+        }
     }
 }
 
 pub struct BladeSurfaceConfig {
     pub size: gpu::Extent,
     pub transparent: bool,
+    /// The number of samples per pixel to rasterize paths with, see
+    /// [`crate::Antialiasing::path_sample_count`]
+    pub path_sample_count: u32,
 }
 
 //Note: we could see some of these fields moved into `BladeContext`
@@ -316,14 +414,24 @@ pub struct BladeRenderer {
     command_encoder: gpu::CommandEncoder,
     last_sync_point: Option<gpu::SyncPoint>,
     pipelines: BladePipelines,
+    path_sample_count: u32,
     instance_belt: BufferBelt,
     path_tiles: HashMap<PathId, AtlasTile>,
     atlas: Arc<BladeAtlas>,
     atlas_sampler: gpu::Sampler,
+    /// The textures that offscreen layers are drawn into, which are reused across frames.
+    offscreen_textures: Vec<OffscreenTexture>,
+    offscreen_textures_used: usize,
     #[cfg(target_os = "macos")]
     core_video_texture_cache: CVMetalTextureCache,
 }
 
+struct OffscreenTexture {
+    texture: gpu::Texture,
+    view: gpu::TextureView,
+    size: gpu::Extent,
+}
+
 impl BladeRenderer {
     pub fn new<I: raw_window_handle::HasWindowHandle + raw_window_handle::HasDisplayHandle>(
         context: &BladeContext,
@@ -343,17 +451,32 @@ impl BladeRenderer {
             .create_surface_configured(window, surface_config)
             .unwrap();
 
+        #[cfg(target_os = "macos")]
+        unsafe {
+            use core_graphics::color_space::{kCGColorSpaceSRGB, CGColorSpace};
+            use foreign_types::ForeignType as _;
+            use objc::{msg_send, sel, sel_impl};
+
+            // Colors are defined in sRGB, so tag the layer's contents for the window server to
+            // convert them to the display's color space, as `MetalRenderer` does.
+            if let Some(srgb) = CGColorSpace::create_with_name(kCGColorSpaceSRGB) {
+                let layer = objc2::rc::Retained::as_ptr(&surface.metal_layer())
+                    as *mut objc::runtime::Object;
+                let _: () = msg_send![layer, setColorspace: srgb.as_ptr() as *mut std::ffi::c_void];
+            }
+        }
+
         let command_encoder = context.gpu.create_command_encoder(gpu::CommandEncoderDesc {
             name: "main",
             buffer_count: 2,
         });
-        let pipelines = BladePipelines::new(&context.gpu, surface.info());
+        let pipelines = BladePipelines::new(&context.gpu, surface.info(), config.path_sample_count);
         let instance_belt = BufferBelt::new(BufferBeltDescriptor {
             memory: gpu::Memory::Shared,
             min_chunk_size: 0x1000,
             alignment: 0x40, // Vulkan `minStorageBufferOffsetAlignment` on Intel Xe
         });
-        let atlas = Arc::new(BladeAtlas::new(&context.gpu, PATH_SAMPLE_COUNT));
+        let atlas = Arc::new(BladeAtlas::new(&context.gpu, config.path_sample_count));
         let atlas_sampler = context.gpu.create_sampler(gpu::SamplerDesc {
             name: "atlas",
             mag_filter: gpu::FilterMode::Linear,
@@ -376,10 +499,13 @@ impl BladeRenderer {
             command_encoder,
             last_sync_point: None,
             pipelines,
+            path_sample_count: config.path_sample_count,
             instance_belt,
             path_tiles: HashMap::default(),
             atlas,
             atlas_sampler,
+            offscreen_textures: Vec::new(),
+            offscreen_textures_used: 0,
             #[cfg(target_os = "macos")]
             core_video_texture_cache,
         })
@@ -428,7 +554,21 @@ impl BladeRenderer {
             self.gpu
                 .reconfigure_surface(&mut self.surface, self.surface_config);
             self.pipelines.destroy(&self.gpu);
-            self.pipelines = BladePipelines::new(&self.gpu, self.surface.info());
+            self.pipelines =
+                BladePipelines::new(&self.gpu, self.surface.info(), self.path_sample_count);
+        }
+    }
+
+    /// Sets how many samples paths are rasterized with, see
+    /// [`crate::Antialiasing::path_sample_count`].
+    pub fn update_path_sample_count(&mut self, path_sample_count: u32) {
+        if path_sample_count != self.path_sample_count {
+            self.wait_for_gpu();
+            self.path_sample_count = path_sample_count;
+            self.atlas.set_path_sample_count(path_sample_count);
+            self.pipelines.destroy(&self.gpu);
+            self.pipelines =
+                BladePipelines::new(&self.gpu, self.surface.info(), self.path_sample_count);
         }
     }
 
@@ -498,7 +638,7 @@ impl BladeRenderer {
             let globals = GlobalParams {
                 viewport_size: [tex_info.size.width as f32, tex_info.size.height as f32],
                 premultiplied_alpha: 0,
-                pad: 0,
+                hdr_headroom: 1.,
             };
 
             let vertex_buf = unsafe { self.instance_belt.alloc_typed(&vertices, &self.gpu) };
@@ -541,6 +681,8 @@ impl BladeRenderer {
         self.wait_for_gpu();
         self.atlas.destroy();
         self.gpu.destroy_sampler(self.atlas_sampler);
+        self.offscreen_textures_used = 0;
+        self.release_unused_offscreen_textures();
         self.instance_belt.destroy(&self.gpu);
         self.gpu.destroy_command_encoder(&mut self.command_encoder);
         self.pipelines.destroy(&self.gpu);
@@ -550,7 +692,7 @@ impl BladeRenderer {
     pub fn draw(&mut self, scene: &Scene) {
         self.command_encoder.start();
         self.atlas.before_frame(&mut self.command_encoder);
-        self.rasterize_paths(scene.paths());
+        self.pipelines.prepare_custom_shaders(&self.gpu, scene);
 
         let frame = {
             profiling::scope!("acquire frame");
@@ -567,14 +709,44 @@ impl BladeRenderer {
                 gpu::AlphaMode::Ignored | gpu::AlphaMode::PostMultiplied => 0,
                 gpu::AlphaMode::PreMultiplied => 1,
             },
-            pad: 0,
+            hdr_headroom: 1.,
         };
 
+        self.offscreen_textures_used = 0;
+        self.draw_scene(scene, frame.texture_view(), globals);
+
+        self.command_encoder.present(frame);
+        let sync_point = self.gpu.submit(&mut self.command_encoder);
+
+        profiling::scope!("finish");
+        self.instance_belt.flush(&sync_point);
+        self.atlas.after_frame(&sync_point);
+        self.atlas.clear_textures(AtlasTextureKind::Path);
+
+        self.wait_for_gpu();
+        self.last_sync_point = Some(sync_point);
+        self.release_unused_offscreen_textures();
+    }
+
+    /// Draws a scene into the target, after drawing each of its offscreen layers into a texture
+    /// of their own.
+    fn draw_scene(&mut self, scene: &Scene, target: gpu::TextureView, globals: GlobalParams) {
+        let layer_views = scene
+            .offscreen_layers
+            .iter()
+            .map(|layer| {
+                let view = self.offscreen_texture();
+                self.draw_scene(&layer.scene, view, globals);
+                view
+            })
+            .collect::<Vec<_>>();
+        self.rasterize_paths(scene.paths());
+
         if let mut pass = self.command_encoder.render(
             "main",
             gpu::RenderTargetSet {
                 colors: &[gpu::RenderTarget {
-                    view: frame.texture_view(),
+                    view: target,
                     init_op: gpu::InitOp::Clear(gpu::TextureColor::TransparentBlack),
                     finish_op: gpu::FinishOp::Store,
                 }],
@@ -612,20 +784,28 @@ impl BladeRenderer {
                     }
                     PrimitiveBatch::Paths(paths) => {
                         let mut encoder = pass.with(&self.pipelines.paths);
-                        // todo(linux): group by texture ID
-                        for path in paths {
+                        // Consecutive paths are usually rasterized into the same texture, so
+                        // they're drawn together until the texture changes.
+                        let mut sprites = Vec::new();
+                        let mut paths = paths.iter().peekable();
+                        while let Some(path) = paths.next() {
                             let tile = &self.path_tiles[&path.id];
-                            let tex_info = self.atlas.get_texture_info(tile.texture_id);
                             let origin = path.bounds.intersect(&path.content_mask.bounds).origin;
-                            let sprites = [PathSprite {
+                            sprites.push(PathSprite {
                                 bounds: Bounds {
                                     origin: origin.map(|p| p.floor()),
                                     size: tile.bounds.size.map(Into::into),
                                 },
                                 color: path.color,
-                                tile: (*tile).clone(),
-                            }];
+                                tile: tile.clone(),
+                            });
+                            if paths.peek().map_or(false, |next| {
+                                self.path_tiles[&next.id].texture_id == tile.texture_id
+                            }) {
+                                continue;
+                            }
 
+                            let tex_info = self.atlas.get_texture_info(tile.texture_id);
                             let instance_buf =
                                 unsafe { self.instance_belt.alloc_typed(&sprites, &self.gpu) };
                             encoder.bind(
@@ -638,6 +818,7 @@ impl BladeRenderer {
                                 },
                             );
                             encoder.draw(0, 4, 0, sprites.len() as u32);
+                            sprites.clear();
                         }
                     }
                     PrimitiveBatch::Underlines(underlines) => {
@@ -691,6 +872,40 @@ impl BladeRenderer {
                         );
                         encoder.draw(0, 4, 0, sprites.len() as u32);
                     }
+                    PrimitiveBatch::ShaderQuads { shader_id, quads } => {
+                        let Some(Some(pipeline)) = self.pipelines.custom_shaders.get(&shader_id)
+                        else {
+                            continue;
+                        };
+                        let instance_buf =
+                            unsafe { self.instance_belt.alloc_typed(quads, &self.gpu) };
+                        let mut encoder = pass.with(pipeline);
+                        encoder.bind(
+                            0,
+                            &ShaderCustomQuadsData {
+                                globals,
+                                b_shader_quads: instance_buf,
+                            },
+                        );
+                        encoder.draw(0, 4, 0, quads.len() as u32);
+                    }
+                    PrimitiveBatch::OffscreenLayer { index, sprite } => {
+                        let instance_buf = unsafe {
+                            self.instance_belt
+                                .alloc_typed(slice::from_ref(sprite), &self.gpu)
+                        };
+                        let mut encoder = pass.with(&self.pipelines.layer_sprites);
+                        encoder.bind(
+                            0,
+                            &ShaderLayerSpritesData {
+                                globals,
+                                t_sprite: layer_views[index],
+                                s_sprite: self.atlas_sampler,
+                                b_layer_sprites: instance_buf,
+                            },
+                        );
+                        encoder.draw(0, 4, 0, 1);
+                    }
                     PrimitiveBatch::Surfaces(surfaces) => {
                         let mut _encoder = pass.with(&self.pipelines.surfaces);
 
@@ -767,6 +982,8 @@ impl BladeRenderer {
                                         surface_locals: SurfaceParams {
                                             bounds: surface.bounds.into(),
                                             content_mask: surface.content_mask.bounds.into(),
+                                            brightness: surface.brightness,
+                                            pad: 0,
                                         },
                                         t_y,
                                         t_cb_cr,
@@ -781,16 +998,65 @@ impl BladeRenderer {
                 }
             }
         }
+    }
 
-        self.command_encoder.present(frame);
-        let sync_point = self.gpu.submit(&mut self.command_encoder);
+    /// Returns the view of a texture the size of the surface for an offscreen layer to be drawn
+    /// into, reusing one from a previous frame if there is one.
+    fn offscreen_texture(&mut self) -> gpu::TextureView {
+        let size = self.surface_config.size;
+        let index = self.offscreen_textures_used;
+        self.offscreen_textures_used += 1;
+        if let Some(texture) = self.offscreen_textures.get(index) {
+            if texture.size == size {
+                return texture.view;
+            }
+        }
 
-        profiling::scope!("finish");
-        self.instance_belt.flush(&sync_point);
-        self.atlas.after_frame(&sync_point);
-        self.atlas.clear_textures(AtlasTextureKind::Path);
+        let format = self.surface.info().format;
+        let texture = self.gpu.create_texture(gpu::TextureDesc {
+            name: "offscreen layer",
+            format,
+            size,
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: gpu::TextureDimension::D2,
+            usage: gpu::TextureUsage::TARGET | gpu::TextureUsage::RESOURCE,
+        });
+        let view = self.gpu.create_texture_view(
+            texture,
+            gpu::TextureViewDesc {
+                name: "offscreen layer",
+                format,
+                dimension: gpu::ViewDimension::D2,
+                subresources: &Default::default(),
+            },
+        );
+        self.command_encoder.init_texture(texture);
+        let texture = OffscreenTexture {
+            texture,
+            view,
+            size,
+        };
+        if let Some(previous) = self.offscreen_textures.get_mut(index) {
+            let previous = mem::replace(previous, texture);
+            self.gpu.destroy_texture_view(previous.view);
+            self.gpu.destroy_texture(previous.texture);
+        } else {
+            self.offscreen_textures.push(texture);
+        }
+        view
+    }
 
-        self.wait_for_gpu();
-        self.last_sync_point = Some(sync_point);
+    /// Destroys the textures of offscreen layers that weren't drawn in the last frame. The GPU
+    /// has to be done with the frame.
+    fn release_unused_offscreen_textures(&mut self) {
+        for texture in self
+            .offscreen_textures
+            .drain(self.offscreen_textures_used..)
+        {
+            self.gpu.destroy_texture_view(texture.view);
+            self.gpu.destroy_texture(texture.texture);
+        }
     }
 }
@@ -0,0 +1,72 @@
+// Compiled at runtime together with a `CustomShader`'s WGSL source, which defines `shade`.
+
+struct GlobalParams {
+    viewport_size: vec2<f32>,
+    premultiplied_alpha: u32,
+    hdr_headroom: f32,
+}
+
+var<uniform> globals: GlobalParams;
+
+struct Bounds {
+    origin: vec2<f32>,
+    size: vec2<f32>,
+}
+
+struct ShaderQuad {
+    order: u32,
+    shader_id: u32,
+    bounds: Bounds,
+    content_mask: Bounds,
+    opacity: f32,
+    pad: u32,
+    uniforms: array<vec4<f32>, 4>,
+}
+var<storage, read> b_shader_quads: array<ShaderQuad>;
+
+struct ShaderInput {
+    position: vec2<f32>,
+    size: vec2<f32>,
+    uniforms: array<vec4<f32>, 4>,
+}
+
+struct ShaderQuadVarying {
+    @builtin(position) position: vec4<f32>,
+    @location(0) @interpolate(flat) quad_id: u32,
+    @location(1) clip_distances: vec4<f32>,
+}
+
+@vertex
+fn vs_shader_quad(@builtin(vertex_index) vertex_id: u32, @builtin(instance_index) instance_id: u32) -> ShaderQuadVarying {
+    let unit_vertex = vec2<f32>(f32(vertex_id & 1u), 0.5 * f32(vertex_id & 2u));
+    let quad = b_shader_quads[instance_id];
+    let position = unit_vertex * quad.bounds.size + quad.bounds.origin;
+    let device_position = position / globals.viewport_size * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0);
+    let tl = position - quad.content_mask.origin;
+    let br = quad.content_mask.origin + quad.content_mask.size - position;
+
+    var out = ShaderQuadVarying();
+    out.position = vec4<f32>(device_position, 0.0, 1.0);
+    out.quad_id = instance_id;
+    out.clip_distances = vec4<f32>(tl.x, br.x, tl.y, br.y);
+    return out;
+}
+
+@fragment
+fn fs_shader_quad(input: ShaderQuadVarying) -> @location(0) vec4<f32> {
+    // Alpha clip first, since we don't have `clip_distance`.
+    if (any(input.clip_distances < vec4<f32>(0.0))) {
+        return vec4<f32>(0.0);
+    }
+
+    let quad = b_shader_quads[input.quad_id];
+    var shader_input: ShaderInput;
+    shader_input.position = (input.position.xy - quad.bounds.origin) / quad.bounds.size;
+    shader_input.size = quad.bounds.size;
+    shader_input.uniforms = quad.uniforms;
+    let color = shade(shader_input);
+
+    let alpha = color.a * quad.opacity;
+    let multiplier = select(1.0, alpha, globals.premultiplied_alpha != 0u);
+    return vec4<f32>(color.rgb * multiplier, alpha);
+}
@@ -1,7 +1,7 @@
 struct GlobalParams {
     viewport_size: vec2<f32>,
     premultiplied_alpha: u32,
-    pad: u32,
+    hdr_headroom: f32,
 }
 
 var<uniform> globals: GlobalParams;
@@ -126,6 +126,23 @@ fn linear_to_srgb(linear: vec3<f32>) -> vec3<f32> {
     return select(higher, lower, cutoff);
 }
 
+// Scales the light of an sRGB color by `brightness`. Colors that end up brighter than SDR white
+// are compressed to fit the display's headroom, rolling off smoothly instead of clipping, while
+// colors within SDR white are left as they are.
+fn apply_brightness(color: vec3<f32>, brightness: f32) -> vec3<f32> {
+    if (brightness == 1.0) {
+        return color;
+    }
+    var light = srgb_to_linear(max(color, vec3<f32>(0.0))) * brightness;
+    let peak = max(light.r, max(light.g, light.b));
+    if (peak > 1.0) {
+        let excess = peak - 1.0;
+        let room = max(globals.hdr_headroom - 1.0, 0.0);
+        light *= (1.0 + room * excess / (excess + room)) / peak;
+    }
+    return linear_to_srgb(light);
+}
+
 /// Convert a linear color to sRGBA space.
 fn linear_to_srgba(color: vec4<f32>) -> vec4<f32> {
     return vec4<f32>(linear_to_srgb(color.rgb), color.a);
@@ -660,7 +677,7 @@ struct Underline {
     content_mask: Bounds,
     color: Hsla,
     thickness: f32,
-    wavy: u32,
+    kind: u32,
 }
 var<storage, read> b_underlines: array<Underline>;
 
@@ -693,13 +710,40 @@ fn fs_underline(input: UnderlineVarying) -> @location(0) vec4<f32> {
     }
 
     let underline = b_underlines[input.underline_id];
-    if ((underline.wavy & 0xFFu) == 0u)
-    {
+    let kind = underline.kind & 0xFFu;
+    let local = input.position.xy - underline.bounds.origin;
+
+    // Dashed
+    if (kind == 2u) {
+        let period = underline.thickness * 6.0;
+        let alpha = step(fract(local.x / period), 0.5);
+        return blend_color(input.color, alpha * input.color.a);
+    }
+
+    // Dotted
+    if (kind == 3u) {
+        let period = underline.thickness * 2.0;
+        let center = vec2<f32>((floor(local.x / period) + 0.5) * period, underline.bounds.size.y * 0.5);
+        let distance = length(local - center) - underline.thickness * 0.5;
+        let alpha = saturate(0.5 - distance);
+        return blend_color(input.color, alpha * input.color.a);
+    }
+
+    // Double
+    if (kind == 4u) {
+        let inside_top = local.y < underline.thickness;
+        let inside_bottom = local.y >= underline.bounds.size.y - underline.thickness;
+        let alpha = select(0.0, 1.0, inside_top || inside_bottom);
+        return blend_color(input.color, alpha * input.color.a);
+    }
+
+    // Solid
+    if (kind != 1u) {
         return blend_color(input.color, input.color.a);
     }
 
     let half_thickness = underline.thickness * 0.5;
-    let st = (input.position.xy - underline.bounds.origin) / underline.bounds.size.y - vec2<f32>(0.0, 0.5);
+    let st = local / underline.bounds.size.y - vec2<f32>(0.0, 0.5);
     let frequency = M_PI_F * 3.0 * underline.thickness / 3.0;
     let amplitude = 1.0 / (4.0 * underline.thickness);
     let sine = sin(st.x * frequency) * amplitude;
@@ -760,7 +804,7 @@ fn fs_mono_sprite(input: MonoSpriteVarying) -> @location(0) vec4<f32> {
 
 struct PolychromeSprite {
     order: u32,
-    pad: u32,
+    brightness: f32,
     grayscale: u32,
     opacity: f32,
     bounds: Bounds,
@@ -806,14 +850,93 @@ fn fs_poly_sprite(input: PolySpriteVarying) -> @location(0) vec4<f32> {
         let grayscale = dot(color.rgb, GRAYSCALE_FACTORS);
         color = vec4<f32>(vec3<f32>(grayscale), sample.a);
     }
+    color = vec4<f32>(apply_brightness(color.rgb, sprite.brightness), color.a);
     return blend_color(color, sprite.opacity * saturate(0.5 - distance));
 }
 
+// --- layer sprites --- //
+
+struct LayerSprite {
+    order: u32,
+    pad: u32,
+    bounds: Bounds,
+    content_mask: Bounds,
+    opacity: f32,
+    blur_radius: f32,
+}
+var<storage, read> b_layer_sprites: array<LayerSprite>;
+
+struct LayerSpriteVarying {
+    @builtin(position) position: vec4<f32>,
+    @location(0) @interpolate(flat) sprite_id: u32,
+    @location(1) clip_distances: vec4<f32>,
+}
+
+@vertex
+fn vs_layer_sprite(@builtin(vertex_index) vertex_id: u32, @builtin(instance_index) instance_id: u32) -> LayerSpriteVarying {
+    let unit_vertex = vec2<f32>(f32(vertex_id & 1u), 0.5 * f32(vertex_id & 2u));
+    let sprite = b_layer_sprites[instance_id];
+
+    // The blur reaches three standard deviations past the layer's bounds.
+    let margin = 1.5 * sprite.blur_radius;
+    var bounds = sprite.bounds;
+    bounds.origin -= vec2<f32>(margin);
+    bounds.size += vec2<f32>(2.0 * margin);
+
+    var out = LayerSpriteVarying();
+    out.position = to_device_position(unit_vertex, bounds);
+    out.sprite_id = instance_id;
+    out.clip_distances = distance_from_clip_rect(unit_vertex, bounds, sprite.content_mask);
+    return out;
+}
+
+@fragment
+fn fs_layer_sprite(input: LayerSpriteVarying) -> @location(0) vec4<f32> {
+    // Alpha clip first, since we don't have `clip_distance`.
+    if (any(input.clip_distances < vec4<f32>(0.0))) {
+        return vec4<f32>(0.0);
+    }
+
+    let sprite = b_layer_sprites[input.sprite_id];
+    let texture_size = vec2<f32>(textureDimensions(t_sprite, 0));
+    let bounds_min = sprite.bounds.origin;
+    let bounds_max = sprite.bounds.origin + sprite.bounds.size;
+
+    // Sample a gaussian with a standard deviation of half the blur radius, out to three
+    // standard deviations. Large blurs take more than one pixel per step to bound the number of
+    // samples. The texture holds premultiplied colors, so transparent pixels don't darken the
+    // result.
+    let sigma = sprite.blur_radius / 2.0;
+    let step = max(1.0, sigma / 2.0);
+    let reach = select(0, i32(ceil(sigma * 3.0 / step)), sigma > 0.0);
+    var sum = vec4<f32>(0.0);
+    var total_weight = 0.0;
+    for (var dy = -reach; dy <= reach; dy += 1) {
+        for (var dx = -reach; dx <= reach; dx += 1) {
+            let offset = vec2<f32>(f32(dx), f32(dy)) * step;
+            let weight = select(1.0, exp(-dot(offset, offset) / (2.0 * sigma * sigma)), sigma > 0.0);
+            total_weight += weight;
+            let position = input.position.xy + offset;
+            if (all(position >= bounds_min) && all(position < bounds_max)) {
+                sum += textureSampleLevel(t_sprite, s_sprite, position / texture_size, 0.0) * weight;
+            }
+        }
+    }
+
+    let color = sum / total_weight;
+    if (color.a <= 0.0) {
+        return vec4<f32>(0.0);
+    }
+    return blend_color(vec4<f32>(color.rgb / color.a, color.a), sprite.opacity);
+}
+
 // --- surfaces --- //
 
 struct SurfaceParams {
     bounds: Bounds,
     content_mask: Bounds,
+    brightness: f32,
+    pad: u32,
 }
 
 var<uniform> surface_locals: SurfaceParams;
@@ -857,5 +980,6 @@ fn fs_surface(input: SurfaceVarying) -> @location(0) vec4<f32> {
         textureSampleLevel(t_cb_cr, s_surface, input.texture_position, 0.0).rg,
         1.0);
 
-    return ycbcr_to_RGB * y_cb_cr;
+    let color = ycbcr_to_RGB * y_cb_cr;
+    return vec4<f32>(apply_brightness(color.rgb, surface_locals.brightness), color.a);
 }
@@ -1,4 +1,5 @@
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::{
     error::Error,
     fmt::{Display, Write},
@@ -306,7 +307,8 @@ impl std::fmt::Display for Keystroke {
 }
 
 /// The state of the modifier keys at some point in time
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Default, Deserialize, Hash)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default, Serialize, Deserialize, Hash, JsonSchema)]
+#[serde(default)]
 pub struct Modifiers {
     /// The control key
     pub control: bool,
@@ -457,3 +459,124 @@ impl Modifiers {
             && (other.function || !self.function)
     }
 }
+
+/// Set 1 scan codes of the character keys on a PC keyboard, by the key they produce with the
+/// US QWERTY layout. Linux evdev key codes use the same numbers.
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "windows"))]
+const PC_SCAN_CODES: &[(&str, u32)] = &[
+    ("`", 0x29),
+    ("1", 0x02),
+    ("2", 0x03),
+    ("3", 0x04),
+    ("4", 0x05),
+    ("5", 0x06),
+    ("6", 0x07),
+    ("7", 0x08),
+    ("8", 0x09),
+    ("9", 0x0A),
+    ("0", 0x0B),
+    ("-", 0x0C),
+    ("=", 0x0D),
+    ("q", 0x10),
+    ("w", 0x11),
+    ("e", 0x12),
+    ("r", 0x13),
+    ("t", 0x14),
+    ("y", 0x15),
+    ("u", 0x16),
+    ("i", 0x17),
+    ("o", 0x18),
+    ("p", 0x19),
+    ("[", 0x1A),
+    ("]", 0x1B),
+    ("a", 0x1E),
+    ("s", 0x1F),
+    ("d", 0x20),
+    ("f", 0x21),
+    ("g", 0x22),
+    ("h", 0x23),
+    ("j", 0x24),
+    ("k", 0x25),
+    ("l", 0x26),
+    (";", 0x27),
+    ("'", 0x28),
+    ("\\", 0x2B),
+    ("z", 0x2C),
+    ("x", 0x2D),
+    ("c", 0x2E),
+    ("v", 0x2F),
+    ("b", 0x30),
+    ("n", 0x31),
+    ("m", 0x32),
+    (",", 0x33),
+    (".", 0x34),
+    ("/", 0x35),
+];
+
+/// Returns the keystroke typed by pressing the keys of the given physical keystroke, where
+/// `layout_key` returns the key that a scan code types with the current keyboard layout.
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "windows"))]
+pub(crate) fn layout_keystroke_for_scan_codes(
+    physical: &Keystroke,
+    layout_key: impl Fn(u32) -> Option<String>,
+) -> Option<Keystroke> {
+    let (_, scan_code) = PC_SCAN_CODES.iter().find(|(key, _)| *key == physical.key)?;
+    Some(Keystroke {
+        modifiers: physical.modifiers,
+        key: layout_key(*scan_code)?,
+        key_char: None,
+    })
+}
+
+/// Returns the physical keystroke, named after the US QWERTY layout, that types the given
+/// keystroke, where `layout_key` returns the key that a scan code types with the current
+/// keyboard layout.
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "windows"))]
+pub(crate) fn physical_keystroke_for_scan_codes(
+    keystroke: &Keystroke,
+    layout_key: impl Fn(u32) -> Option<String>,
+) -> Option<Keystroke> {
+    let (key, _) = PC_SCAN_CODES
+        .iter()
+        .find(|(_, scan_code)| layout_key(*scan_code).as_ref() == Some(&keystroke.key))?;
+    Some(Keystroke {
+        modifiers: keystroke.modifiers,
+        key: key.to_string(),
+        key_char: None,
+    })
+}
+
+#[cfg(all(
+    test,
+    any(target_os = "linux", target_os = "freebsd", target_os = "windows")
+))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_keystrokes_with_scan_codes() {
+        // AZERTY swaps a with q, and z with w.
+        let azerty = |scan_code| {
+            let (key, _) = PC_SCAN_CODES.iter().find(|(_, code)| *code == scan_code)?;
+            let key = match *key {
+                "a" => "q",
+                "q" => "a",
+                "z" => "w",
+                "w" => "z",
+                key => key,
+            };
+            Some(key.to_string())
+        };
+        let physical = Keystroke::parse("ctrl-q").unwrap();
+        let layout = layout_keystroke_for_scan_codes(&physical, azerty).unwrap();
+        assert_eq!(layout, Keystroke::parse("ctrl-a").unwrap());
+        assert_eq!(
+            physical_keystroke_for_scan_codes(&layout, azerty),
+            Some(physical)
+        );
+        assert_eq!(
+            layout_keystroke_for_scan_codes(&Keystroke::parse("f1").unwrap(), azerty),
+            None
+        );
+    }
+}
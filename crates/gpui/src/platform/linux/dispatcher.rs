@@ -1,4 +1,4 @@
-use crate::{PlatformDispatcher, TaskLabel};
+use crate::{PlatformDispatcher, Priority, TaskLabel};
 use async_task::Runnable;
 use calloop::{
     channel::{self, Sender},
@@ -6,13 +6,18 @@ use calloop::{
     EventLoop,
 };
 use parking::{Parker, Unparker};
-use parking_lot::Mutex;
+use parking_lot::{Condvar, Mutex};
 use std::{
+    collections::VecDeque,
+    sync::Arc,
     thread,
     time::{Duration, Instant},
 };
 use util::ResultExt;
 
+/// How long a task waits before it's promoted by one priority level.
+const AGING_INTERVAL: Duration = Duration::from_millis(100);
+
 struct TimerAfter {
     duration: Duration,
     runnable: Runnable,
@@ -22,33 +27,32 @@ pub(crate) struct LinuxDispatcher {
     parker: Mutex<Parker>,
     main_sender: Sender<Runnable>,
     timer_sender: Sender<TimerAfter>,
-    background_sender: flume::Sender<Runnable>,
+    background_queue: Arc<PriorityQueue<Runnable>>,
     _background_threads: Vec<thread::JoinHandle<()>>,
     main_thread_id: thread::ThreadId,
 }
 
 impl LinuxDispatcher {
     pub fn new(main_sender: Sender<Runnable>) -> Self {
-        let (background_sender, background_receiver) = flume::unbounded::<Runnable>();
+        let background_queue = Arc::new(PriorityQueue::default());
         let thread_count = std::thread::available_parallelism()
             .map(|i| i.get())
             .unwrap_or(1);
 
         let mut background_threads = (0..thread_count)
             .map(|i| {
-                let receiver = background_receiver.clone();
-                std::thread::spawn(move || {
-                    for runnable in receiver {
-                        let start = Instant::now();
-
-                        runnable.run();
-
-                        log::trace!(
-                            "background thread {}: ran runnable. took: {:?}",
-                            i,
-                            start.elapsed()
-                        );
-                    }
+                let queue = background_queue.clone();
+                std::thread::spawn(move || loop {
+                    let runnable = queue.pop();
+                    let start = Instant::now();
+
+                    runnable.run();
+
+                    log::trace!(
+                        "background thread {}: ran runnable. took: {:?}",
+                        i,
+                        start.elapsed()
+                    );
                 })
             })
             .collect::<Vec<_>>();
@@ -89,7 +93,7 @@ impl LinuxDispatcher {
             parker: Mutex::new(Parker::new()),
             main_sender,
             timer_sender,
-            background_sender,
+            background_queue,
             _background_threads: background_threads,
             main_thread_id: thread::current().id(),
         }
@@ -101,8 +105,8 @@ impl PlatformDispatcher for LinuxDispatcher {
         thread::current().id() == self.main_thread_id
     }
 
-    fn dispatch(&self, runnable: Runnable, _: Option<TaskLabel>) {
-        self.background_sender.send(runnable).unwrap();
+    fn dispatch(&self, runnable: Runnable, _: Option<TaskLabel>, priority: Priority) {
+        self.background_queue.push(runnable, priority);
     }
 
     fn dispatch_on_main_thread(&self, runnable: Runnable) {
@@ -128,3 +132,126 @@ impl PlatformDispatcher for LinuxDispatcher {
         self.parker.lock().unparker()
     }
 }
+
+/// The tasks waiting for a background thread, in a lane for each [`Priority`]. The most urgent
+/// task is run first, and tasks are promoted by one level for every [`AGING_INTERVAL`] they wait,
+/// so that background work runs even while more urgent work keeps being queued.
+struct PriorityQueue<T> {
+    lanes: Mutex<PriorityLanes<T>>,
+    condvar: Condvar,
+}
+
+impl<T> Default for PriorityQueue<T> {
+    fn default() -> Self {
+        Self {
+            lanes: Mutex::new(PriorityLanes::default()),
+            condvar: Condvar::new(),
+        }
+    }
+}
+
+impl<T> PriorityQueue<T> {
+    fn push(&self, item: T, priority: Priority) {
+        self.lanes.lock().push(item, priority, Instant::now());
+        self.condvar.notify_one();
+    }
+
+    /// Waits for a task to be queued and takes it.
+    fn pop(&self) -> T {
+        let mut lanes = self.lanes.lock();
+        loop {
+            if let Some(item) = lanes.pop() {
+                return item;
+            }
+            self.condvar.wait(&mut lanes);
+        }
+    }
+}
+
+struct PriorityLanes<T>([VecDeque<(Instant, T)>; 3]);
+
+impl<T> Default for PriorityLanes<T> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+impl<T> PriorityLanes<T> {
+    fn push(&mut self, item: T, priority: Priority, now: Instant) {
+        let lane = match priority {
+            Priority::UserBlocking => 0,
+            Priority::Default => 1,
+            Priority::Background => 2,
+        };
+        self.0[lane].push_back((now, item));
+    }
+
+    /// Takes the task with the highest priority after aging. A task that has been promoted to the
+    /// level of a task that was more urgent to begin with runs after it, which makes each level
+    /// equivalent to queueing a task [`AGING_INTERVAL`] later. Unlike running whichever task has
+    /// waited longest, this keeps urgent tasks ahead of the others when every task is waiting.
+    fn pop(&mut self) -> Option<T> {
+        let (lane, _) = self
+            .0
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, lane)| {
+                let (queued_at, _) = lane.front()?;
+                Some((ix, *queued_at + AGING_INTERVAL * ix as u32))
+            })
+            .min_by_key(|(ix, due_at)| (*due_at, *ix))?;
+        self.0[lane].pop_front().map(|(_, item)| item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_lanes() {
+        let start = Instant::now();
+        let mut lanes = PriorityLanes::default();
+        lanes.push("index", Priority::Background, start);
+        lanes.push(
+            "search",
+            Priority::Default,
+            start + Duration::from_millis(10),
+        );
+        let now = start + Duration::from_millis(20);
+        lanes.push("highlight", Priority::UserBlocking, now);
+        lanes.push("highlight again", Priority::UserBlocking, now);
+        assert_eq!(lanes.pop(), Some("highlight"));
+        assert_eq!(lanes.pop(), Some("highlight again"));
+
+        // Tasks that waited for longer than more urgent ones were promoted run first.
+        lanes.push(
+            "highlight later",
+            Priority::UserBlocking,
+            start + AGING_INTERVAL + Duration::from_millis(15),
+        );
+        assert_eq!(lanes.pop(), Some("search"));
+        assert_eq!(lanes.pop(), Some("highlight later"));
+        assert_eq!(lanes.pop(), Some("index"));
+        assert_eq!(lanes.pop(), None);
+    }
+
+    #[test]
+    fn test_priority_lanes_under_load() {
+        let start = Instant::now();
+        let mut lanes = PriorityLanes::default();
+        lanes.push("index".to_string(), Priority::Background, start);
+        lanes.push("search".to_string(), Priority::Default, start);
+        for ms in (0..=400).step_by(10) {
+            let queued_at = start + Duration::from_millis(ms);
+            lanes.push(format!("highlight {ms}"), Priority::UserBlocking, queued_at);
+        }
+        let order = std::iter::from_fn(|| lanes.pop()).collect::<Vec<_>>();
+
+        // Less urgent tasks only run ahead of urgent ones queued long enough after them.
+        let position = |name: &str| order.iter().position(|item| item == name).unwrap();
+        assert_eq!(position("search"), position("highlight 100") + 1);
+        assert_eq!(position("index"), position("highlight 200") + 1);
+        assert_eq!(order.len(), 43);
+    }
+}
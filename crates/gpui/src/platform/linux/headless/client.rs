@@ -51,6 +51,10 @@ impl LinuxClient for HeadlessClient {
         "unknown".to_string()
     }
 
+    fn layout_key(&self, _scan_code: u32) -> Option<String> {
+        None
+    }
+
     fn displays(&self) -> Vec<Rc<dyn PlatformDisplay>> {
         vec![]
     }
@@ -17,16 +17,18 @@ use std::{
 use anyhow::{anyhow, Context as _};
 use async_task::Runnable;
 use calloop::{channel::Channel, LoopSignal};
-use futures::channel::oneshot;
+use futures::channel::{mpsc, oneshot};
 use util::ResultExt as _;
 #[cfg(any(feature = "wayland", feature = "x11"))]
 use xkbcommon::xkb::{self, Keycode, Keysym, State};
 
 use crate::{
-    px, Action, AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, DisplayId,
-    ForegroundExecutor, Keymap, LinuxDispatcher, Menu, MenuItem, OwnedMenu, PathPromptOptions,
-    Pixels, Platform, PlatformDisplay, PlatformTextSystem, PlatformWindow, Point, Result,
-    ScreenCaptureSource, Task, WindowAppearance, WindowParams,
+    layout_keystroke_for_scan_codes, physical_keystroke_for_scan_codes, px, Action,
+    AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, DisplayId, ForegroundExecutor,
+    Keymap, Keystroke, LinuxDispatcher, Menu, MenuItem, NewPathPromptOptions, NotificationResponse,
+    OwnedMenu, PathPromptOptions, Pixels, Platform, PlatformDisplay, PlatformTextSystem,
+    PlatformWindow, Point, PowerState, Result, Rgba, ScreenCaptureSource, Task, WindowAppearance,
+    WindowParams,
 };
 #[cfg(any(feature = "wayland", feature = "x11"))]
 pub(crate) const SCROLL_LINES: f32 = 3.0;
@@ -42,10 +44,16 @@ pub(crate) const KEYRING_LABEL: &str = "zed-github-account";
 const FILE_PICKER_PORTAL_MISSING: &str =
     "Couldn't open file picker due to missing xdg-desktop-portal implementation.";
 
+const NOTIFICATION_PORTAL_MISSING: &str =
+    "Couldn't send notification due to missing xdg-desktop-portal implementation.";
+
 pub trait LinuxClient {
     fn compositor_name(&self) -> &'static str;
     fn with_common<R>(&self, f: impl FnOnce(&mut LinuxCommon) -> R) -> R;
     fn keyboard_layout(&self) -> String;
+    /// The key that the key with the given evdev code types with the current keyboard layout,
+    /// ignoring modifiers.
+    fn layout_key(&self, scan_code: u32) -> Option<String>;
     fn displays(&self) -> Vec<Rc<dyn PlatformDisplay>>;
     #[allow(unused)]
     fn display(&self, id: DisplayId) -> Option<Rc<dyn PlatformDisplay>>;
@@ -77,6 +85,8 @@ pub(crate) struct PlatformHandlers {
     pub(crate) will_open_app_menu: Option<Box<dyn FnMut()>>,
     pub(crate) validate_app_menu_command: Option<Box<dyn FnMut(&dyn Action) -> bool>>,
     pub(crate) keyboard_layout_change: Option<Box<dyn FnMut()>>,
+    pub(crate) displays_changed: Option<Box<dyn FnMut()>>,
+    pub(crate) system_appearance_changed: Option<Box<dyn FnMut()>>,
 }
 
 pub(crate) struct LinuxCommon {
@@ -84,6 +94,7 @@ pub(crate) struct LinuxCommon {
     pub(crate) foreground_executor: ForegroundExecutor,
     pub(crate) text_system: Arc<dyn PlatformTextSystem>,
     pub(crate) appearance: WindowAppearance,
+    pub(crate) accent_color: Option<Rgba>,
     pub(crate) auto_hide_scrollbars: bool,
     pub(crate) callbacks: PlatformHandlers,
     pub(crate) signal: LoopSignal,
@@ -110,6 +121,7 @@ impl LinuxCommon {
             foreground_executor: ForegroundExecutor::new(dispatcher.clone()),
             text_system,
             appearance: WindowAppearance::Light,
+            accent_color: None,
             auto_hide_scrollbars: false,
             callbacks,
             signal,
@@ -141,6 +153,14 @@ impl<P: LinuxClient + 'static> Platform for P {
         self.with_common(|common| common.callbacks.keyboard_layout_change = Some(callback));
     }
 
+    fn on_displays_changed(&self, callback: Box<dyn FnMut()>) {
+        self.with_common(|common| common.callbacks.displays_changed = Some(callback));
+    }
+
+    fn on_system_appearance_changed(&self, callback: Box<dyn FnMut()>) {
+        self.with_common(|common| common.callbacks.system_appearance_changed = Some(callback));
+    }
+
     fn run(&self, on_finish_launching: Box<dyn FnOnce()>) {
         on_finish_launching();
 
@@ -292,6 +312,7 @@ impl<P: LinuxClient + 'static> Platform for P {
                     .title(title)
                     .multiple(options.multiple)
                     .directory(options.directories)
+                    .filters(portal_file_filters(&options.filters))
                     .send()
                     .await
                 {
@@ -323,49 +344,52 @@ impl<P: LinuxClient + 'static> Platform for P {
         done_rx
     }
 
-    fn prompt_for_new_path(&self, directory: &Path) -> oneshot::Receiver<Result<Option<PathBuf>>> {
+    fn prompt_for_new_path(
+        &self,
+        options: NewPathPromptOptions,
+    ) -> oneshot::Receiver<Result<Option<PathBuf>>> {
         let (done_tx, done_rx) = oneshot::channel();
 
         #[cfg(not(any(feature = "wayland", feature = "x11")))]
-        let _ = (done_tx.send(Ok(None)), directory);
+        let _ = (done_tx.send(Ok(None)), options);
 
         #[cfg(any(feature = "wayland", feature = "x11"))]
         self.foreground_executor()
-            .spawn({
-                let directory = directory.to_owned();
-
-                async move {
-                    let request = match ashpd::desktop::file_chooser::SaveFileRequest::default()
-                        .modal(true)
-                        .title("Save File")
-                        .current_folder(directory)
-                        .expect("pathbuf should not be nul terminated")
-                        .send()
-                        .await
-                    {
-                        Ok(request) => request,
-                        Err(err) => {
-                            let result = match err {
-                                ashpd::Error::PortalNotFound(_) => {
-                                    anyhow!(FILE_PICKER_PORTAL_MISSING)
-                                }
-                                err => err.into(),
-                            };
-                            let _ = done_tx.send(Err(result));
-                            return;
-                        }
-                    };
+            .spawn(async move {
+                let request = match ashpd::desktop::file_chooser::SaveFileRequest::default()
+                    .modal(true)
+                    .title("Save File")
+                    .current_folder(options.directory)
+                    .expect("pathbuf should not be nul terminated")
+                    .current_name(options.suggested_name.as_deref())
+                    // The portal has no option to disallow creating directories,
+                    // so `can_create_directories` is left to the file chooser.
+                    .filters(portal_file_filters(&options.filters))
+                    .send()
+                    .await
+                {
+                    Ok(request) => request,
+                    Err(err) => {
+                        let result = match err {
+                            ashpd::Error::PortalNotFound(_) => {
+                                anyhow!(FILE_PICKER_PORTAL_MISSING)
+                            }
+                            err => err.into(),
+                        };
+                        let _ = done_tx.send(Err(result));
+                        return;
+                    }
+                };
 
-                    let result = match request.response() {
-                        Ok(response) => Ok(response
-                            .uris()
-                            .first()
-                            .and_then(|uri| uri.to_file_path().ok())),
-                        Err(ashpd::Error::Response(_)) => Ok(None),
-                        Err(e) => Err(e.into()),
-                    };
-                    let _ = done_tx.send(result);
-                }
+                let result = match request.response() {
+                    Ok(response) => Ok(response
+                        .uris()
+                        .first()
+                        .and_then(|uri| uri.to_file_path().ok())),
+                    Err(ashpd::Error::Response(_)) => Ok(None),
+                    Err(e) => Err(e.into()),
+                };
+                let _ = done_tx.send(result);
             })
             .detach();
 
@@ -377,6 +401,88 @@ impl<P: LinuxClient + 'static> Platform for P {
         false
     }
 
+    fn send_notification(
+        &self,
+        title: &str,
+        body: &str,
+        actions: &[&str],
+    ) -> oneshot::Receiver<Result<NotificationResponse>> {
+        let (done_tx, done_rx) = oneshot::channel();
+
+        #[cfg(not(any(feature = "wayland", feature = "x11")))]
+        let _ = (
+            done_tx.send(Err(anyhow!(NOTIFICATION_PORTAL_MISSING))),
+            title,
+            body,
+            actions,
+        );
+
+        #[cfg(any(feature = "wayland", feature = "x11"))]
+        {
+            use ashpd::desktop::notification::{Button, Notification};
+            use std::sync::atomic::{AtomicUsize, Ordering};
+
+            static NEXT_NOTIFICATION_ID: AtomicUsize = AtomicUsize::new(0);
+            let id = format!(
+                "gpui-notification-{}",
+                NEXT_NOTIFICATION_ID.fetch_add(1, Ordering::SeqCst)
+            );
+
+            let mut notification = Notification::new(title)
+                .body(body)
+                .default_action("default");
+            for (ix, action) in actions.iter().enumerate() {
+                notification = notification.button(Button::new(action, &ix.to_string()));
+            }
+
+            self.foreground_executor()
+                .spawn(async move {
+                    let result = send_portal_notification(id, notification).await;
+                    let _ = done_tx.send(result);
+                })
+                .detach();
+        }
+
+        done_rx
+    }
+
+    fn pick_color(&self, initial: Rgba) -> Option<mpsc::UnboundedReceiver<Rgba>> {
+        #[cfg(not(any(feature = "wayland", feature = "x11")))]
+        {
+            let _ = initial;
+            None
+        }
+
+        #[cfg(any(feature = "wayland", feature = "x11"))]
+        {
+            // The color portal only offers an eyedropper, so a single color is
+            // picked from the screen, keeping the initial color's alpha.
+            let (tx, rx) = mpsc::unbounded();
+            self.foreground_executor()
+                .spawn(async move {
+                    let color = ashpd::desktop::Color::pick()
+                        .send()
+                        .await
+                        .and_then(|request| request.response());
+                    match color {
+                        Ok(color) => {
+                            tx.unbounded_send(Rgba {
+                                r: color.red() as f32,
+                                g: color.green() as f32,
+                                b: color.blue() as f32,
+                                a: initial.a,
+                            })
+                            .ok();
+                        }
+                        Err(ashpd::Error::Response(_)) => {}
+                        Err(err) => log::error!("failed to pick color: {err}"),
+                    }
+                })
+                .detach();
+            Some(rx)
+        }
+    }
+
     fn reveal_path(&self, path: &Path) {
         self.reveal_path(path.to_owned());
     }
@@ -525,6 +631,25 @@ impl<P: LinuxClient + 'static> Platform for P {
         self.with_common(|common| common.appearance)
     }
 
+    fn accent_color(&self) -> Option<Rgba> {
+        self.with_common(|common| common.accent_color)
+    }
+
+    fn layout_keystroke(&self, physical: &Keystroke) -> Option<Keystroke> {
+        layout_keystroke_for_scan_codes(physical, |scan_code| self.layout_key(scan_code))
+    }
+
+    fn physical_keystroke(&self, keystroke: &Keystroke) -> Option<Keystroke> {
+        physical_keystroke_for_scan_codes(keystroke, |scan_code| self.layout_key(scan_code))
+    }
+
+    fn power_state(&self) -> PowerState {
+        PowerState {
+            on_battery: on_battery_power(),
+            low_power_mode: false,
+        }
+    }
+
     fn register_url_scheme(&self, _: &str) -> Task<anyhow::Result<()>> {
         Task::ready(Err(anyhow!("register_url_scheme unimplemented")))
     }
@@ -548,6 +673,48 @@ impl<P: LinuxClient + 'static> Platform for P {
     fn add_recent_document(&self, _path: &Path) {}
 }
 
+#[cfg(any(feature = "wayland", feature = "x11"))]
+fn portal_file_filters(
+    filters: &[crate::PathPromptFilter],
+) -> Vec<ashpd::desktop::file_chooser::FileFilter> {
+    filters
+        .iter()
+        .map(|filter| {
+            filter.extensions.iter().fold(
+                ashpd::desktop::file_chooser::FileFilter::new(&filter.name),
+                |file_filter, extension| file_filter.glob(&format!("*.{extension}")),
+            )
+        })
+        .collect()
+}
+
+#[cfg(any(feature = "wayland", feature = "x11"))]
+async fn send_portal_notification(
+    id: String,
+    notification: ashpd::desktop::notification::Notification,
+) -> Result<NotificationResponse> {
+    use futures::StreamExt as _;
+
+    let proxy = ashpd::desktop::notification::NotificationProxy::new()
+        .await
+        .map_err(|err| match err {
+            ashpd::Error::PortalNotFound(_) => anyhow!(NOTIFICATION_PORTAL_MISSING),
+            err => err.into(),
+        })?;
+    // Subscribe before sending, so that an immediate click isn't missed.
+    let mut invoked_actions = proxy.receive_action_invoked().await?;
+    proxy.add_notification(&id, notification).await?;
+    while let Some(action) = invoked_actions.next().await {
+        if action.id() == id {
+            return Ok(action
+                .name()
+                .parse()
+                .map_or(NotificationResponse::Clicked, NotificationResponse::Action));
+        }
+    }
+    Err(anyhow!("notification portal closed"))
+}
+
 #[cfg(any(feature = "wayland", feature = "x11"))]
 pub(super) fn open_uri_internal(
     executor: BackgroundExecutor,
@@ -609,12 +776,40 @@ pub(super) fn reveal_path_internal(
         .detach();
 }
 
+/// Whether any battery reported by the kernel is discharging.
+fn on_battery_power() -> bool {
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    supplies.flatten().any(|supply| {
+        let path = supply.path();
+        let read = |name: &str| std::fs::read_to_string(path.join(name)).unwrap_or_default();
+        read("type").trim() == "Battery" && read("status").trim() == "Discharging"
+    })
+}
+
 #[allow(unused)]
 pub(super) fn is_within_click_distance(a: Point<Pixels>, b: Point<Pixels>) -> bool {
     let diff = a - b;
     diff.x.abs() <= DOUBLE_CLICK_DISTANCE && diff.y.abs() <= DOUBLE_CLICK_DISTANCE
 }
 
+/// Returns the key that the key with the given evdev code types with the state's current
+/// layout when no modifiers are held.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) fn xkb_layout_key(state: &State, scan_code: u32) -> Option<String> {
+    let layout = state.serialize_layout(xkb::STATE_LAYOUT_EFFECTIVE);
+    let mut unmodified = State::new(&state.get_keymap());
+    unmodified.update_mask(0, 0, 0, 0, 0, layout);
+    // xkb key codes are offset from evdev ones by 8.
+    let key = unmodified.key_get_utf8(Keycode::new(scan_code + 8));
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(char), None) if !char.is_control() => Some(char.to_lowercase().to_string()),
+        _ => None,
+    }
+}
+
 #[cfg(any(feature = "wayland", feature = "x11"))]
 pub(super) fn get_xkb_compose_state(cx: &xkb::Context) -> Option<xkb::compose::State> {
     let mut locales = Vec::default();
@@ -16,6 +16,7 @@ use collections::HashMap;
 use filedescriptor::Pipe;
 
 use http_client::Url;
+use raw_window_handle as rwh;
 use smallvec::SmallVec;
 use util::ResultExt;
 use wayland_backend::client::ObjectId;
@@ -79,7 +80,7 @@ use crate::platform::linux::{
         window::WaylandWindow,
     },
     xdg_desktop_portal::{Event as XDPEvent, XDPEventSource},
-    LinuxClient,
+    xkb_layout_key, LinuxClient,
 };
 use crate::platform::{blade::BladeContext, PlatformWindow};
 use crate::{
@@ -143,7 +144,7 @@ impl Globals {
             primary_selection_manager: globals.bind(&qh, 1..=1, ()).ok(),
             shm: globals.bind(&qh, 1..=1, ()).unwrap(),
             seat,
-            wm_base: globals.bind(&qh, 2..=5, ()).unwrap(),
+            wm_base: globals.bind(&qh, 2..=6, ()).unwrap(),
             viewporter: globals.bind(&qh, 1..=1, ()).ok(),
             fractional_scale_manager: globals.bind(&qh, 1..=1, ()).ok(),
             decoration_manager: globals.bind(&qh, 1..=1, ()).ok(),
@@ -161,6 +162,7 @@ pub struct InProgressOutput {
     scale: Option<i32>,
     position: Option<Point<DevicePixels>>,
     size: Option<Size<DevicePixels>>,
+    refresh: Option<i32>,
 }
 
 impl InProgressOutput {
@@ -171,6 +173,7 @@ impl InProgressOutput {
                 name: self.name.clone(),
                 scale,
                 bounds: Bounds::new(position, size),
+                refresh: self.refresh.filter(|refresh| *refresh > 0),
             })
         } else {
             None
@@ -183,6 +186,8 @@ pub struct Output {
     pub name: Option<String>,
     pub scale: i32,
     pub bounds: Bounds<DevicePixels>,
+    /// The refresh rate of the output's current mode, in millihertz
+    pub refresh: Option<i32>,
 }
 
 pub(crate) struct WaylandClientState {
@@ -203,7 +208,11 @@ pub(crate) struct WaylandClientState {
     windows: HashMap<ObjectId, WaylandWindowStatePtr>,
     // Output to scale mapping
     outputs: HashMap<ObjectId, Output>,
+    // Every property received for each output so far, as the compositor only resends the ones
+    // that change before each `done` event.
     in_progress_outputs: HashMap<ObjectId, InProgressOutput>,
+    // Registry global name to output mapping, for outputs being removed
+    output_globals: HashMap<u32, ObjectId>,
     keymap_state: Option<xkb::State>,
     compose_state: Option<xkb::compose::State>,
     drag: DragState,
@@ -419,6 +428,7 @@ impl WaylandClient {
         let mut seat: Option<wl_seat::WlSeat> = None;
         #[allow(clippy::mutable_key_type)]
         let mut in_progress_outputs = HashMap::default();
+        let mut output_globals = HashMap::default();
         globals.contents().with_list(|list| {
             for global in list {
                 match &global.interface[..] {
@@ -438,6 +448,7 @@ impl WaylandClient {
                             (),
                         );
                         in_progress_outputs.insert(output.id(), InProgressOutput::default());
+                        output_globals.insert(global.name, output.id());
                     }
                     _ => {}
                 }
@@ -489,13 +500,22 @@ impl WaylandClient {
                 move |event, _, client| match event {
                     XDPEvent::WindowAppearance(appearance) => {
                         if let Some(client) = client.0.upgrade() {
-                            let mut client = client.borrow_mut();
+                            let mut state = client.borrow_mut();
 
-                            client.common.appearance = appearance;
+                            state.common.appearance = appearance;
 
-                            for (_, window) in &mut client.windows {
+                            for (_, window) in &mut state.windows {
                                 window.set_appearance(appearance);
                             }
+
+                            drop(state);
+                            system_appearance_changed(&client);
+                        }
+                    }
+                    XDPEvent::AccentColor(color) => {
+                        if let Some(client) = client.0.upgrade() {
+                            client.borrow_mut().common.accent_color = Some(color);
+                            system_appearance_changed(&client);
                         }
                     }
                     XDPEvent::CursorTheme(theme) => {
@@ -530,6 +550,7 @@ impl WaylandClient {
             composing: false,
             outputs: HashMap::default(),
             in_progress_outputs,
+            output_globals,
             windows: HashMap::default(),
             common,
             keymap_state: None,
@@ -601,6 +622,11 @@ impl LinuxClient for WaylandClient {
         }
     }
 
+    fn layout_key(&self, scan_code: u32) -> Option<String> {
+        let state = self.0.borrow();
+        xkb_layout_key(state.keymap_state.as_ref()?, scan_code)
+    }
+
     fn displays(&self) -> Vec<Rc<dyn PlatformDisplay>> {
         self.0
             .borrow()
@@ -611,6 +637,8 @@ impl LinuxClient for WaylandClient {
                     id: id.clone(),
                     name: output.name.clone(),
                     bounds: output.bounds.to_pixels(output.scale as f32),
+                    scale_factor: output.scale as f32,
+                    refresh_rate: output.refresh.map(|refresh| refresh as f32 / 1000.),
                 }) as Rc<dyn PlatformDisplay>
             })
             .collect()
@@ -627,6 +655,8 @@ impl LinuxClient for WaylandClient {
                         id: object_id.clone(),
                         name: output.name.clone(),
                         bounds: output.bounds.to_pixels(output.scale as f32),
+                        scale_factor: output.scale as f32,
+                        refresh_rate: output.refresh.map(|refresh| refresh as f32 / 1000.),
                     }) as Rc<dyn PlatformDisplay>
                 })
             })
@@ -643,6 +673,18 @@ impl LinuxClient for WaylandClient {
     ) -> anyhow::Result<Box<dyn PlatformWindow>> {
         let mut state = self.0.borrow_mut();
 
+        let parent = match params.parent {
+            Some(rwh::RawWindowHandle::Wayland(parent)) => state
+                .windows
+                .values()
+                .find(|window| {
+                    window.surface().id().as_ptr().cast::<std::ffi::c_void>()
+                        == parent.surface.as_ptr()
+                })
+                .map(|window| window.toplevel()),
+            _ => None,
+        };
+
         let (window, surface_id) = WaylandWindow::new(
             handle,
             state.globals.clone(),
@@ -651,6 +693,9 @@ impl LinuxClient for WaylandClient {
             params,
             state.common.appearance,
         )?;
+        if let Some(parent) = parent {
+            window.0.toplevel().set_parent(Some(&parent));
+        }
         state.windows.insert(surface_id, window.0.clone());
 
         Ok(Box::new(window))
@@ -845,11 +890,19 @@ impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for WaylandClientStat
                     state
                         .in_progress_outputs
                         .insert(output.id(), InProgressOutput::default());
+                    state.output_globals.insert(name, output.id());
                 }
                 _ => {}
             },
-            wl_registry::Event::GlobalRemove { name: _ } => {
-                // TODO: handle global removal
+            wl_registry::Event::GlobalRemove { name } => {
+                // Outputs are the only globals we bind that come and go while the compositor runs.
+                if let Some(output_id) = state.output_globals.remove(&name) {
+                    state.in_progress_outputs.remove(&output_id);
+                    if state.outputs.remove(&output_id).is_some() {
+                        drop(state);
+                        displays_changed(&client);
+                    }
+                }
             }
             _ => {}
         }
@@ -955,20 +1008,52 @@ impl Dispatch<wl_output::WlOutput, ()> for WaylandClientStatePtr {
             wl_output::Event::Geometry { x, y, .. } => {
                 in_progress_output.position = Some(point(DevicePixels(x), DevicePixels(y)))
             }
-            wl_output::Event::Mode { width, height, .. } => {
-                in_progress_output.size = Some(size(DevicePixels(width), DevicePixels(height)))
+            wl_output::Event::Mode {
+                width,
+                height,
+                refresh,
+                ..
+            } => {
+                in_progress_output.size = Some(size(DevicePixels(width), DevicePixels(height)));
+                in_progress_output.refresh = Some(refresh);
             }
             wl_output::Event::Done => {
                 if let Some(complete) = in_progress_output.complete() {
                     state.outputs.insert(output.id(), complete);
+                    drop(state);
+                    displays_changed(&client);
                 }
-                state.in_progress_outputs.remove(&output.id());
             }
             _ => {}
         }
     }
 }
 
+fn displays_changed(client: &RefCell<WaylandClientState>) {
+    let callback = client.borrow_mut().common.callbacks.displays_changed.take();
+    if let Some(mut callback) = callback {
+        callback();
+        client.borrow_mut().common.callbacks.displays_changed = Some(callback);
+    }
+}
+
+fn system_appearance_changed(client: &RefCell<WaylandClientState>) {
+    let callback = client
+        .borrow_mut()
+        .common
+        .callbacks
+        .system_appearance_changed
+        .take();
+    if let Some(mut callback) = callback {
+        callback();
+        client
+            .borrow_mut()
+            .common
+            .callbacks
+            .system_appearance_changed = Some(callback);
+    }
+}
+
 impl Dispatch<xdg_surface::XdgSurface, ObjectId> for WaylandClientStatePtr {
     fn event(
         state: &mut Self,
@@ -14,6 +14,8 @@ pub(crate) struct WaylandDisplay {
     pub id: ObjectId,
     pub name: Option<String>,
     pub bounds: Bounds<Pixels>,
+    pub scale_factor: f32,
+    pub refresh_rate: Option<f32>,
 }
 
 impl Hash for WaylandDisplay {
@@ -38,4 +40,12 @@ impl PlatformDisplay for WaylandDisplay {
     fn bounds(&self) -> Bounds<Pixels> {
         self.bounds
     }
+
+    fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    fn refresh_rate(&self) -> Option<f32> {
+        self.refresh_rate
+    }
 }
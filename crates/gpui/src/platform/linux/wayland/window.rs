@@ -28,9 +28,9 @@ use crate::platform::{
 };
 use crate::scene::Scene;
 use crate::{
-    px, size, AnyWindowHandle, Bounds, Decorations, Globals, GpuSpecs, Modifiers, Output, Pixels,
-    PlatformDisplay, PlatformInput, Point, PromptLevel, RequestFrameOptions, ResizeEdge,
-    ScaledPixels, Size, Tiling, WaylandClientStatePtr, WindowAppearance,
+    px, size, Antialiasing, AnyWindowHandle, Bounds, Decorations, Globals, GpuSpecs, Modifiers,
+    Output, Pixels, PlatformDisplay, PlatformInput, Point, PromptLevel, RequestFrameOptions,
+    ResizeEdge, ScaledPixels, Size, Tiling, WaylandClientStatePtr, WindowAppearance,
     WindowBackgroundAppearance, WindowBounds, WindowControls, WindowDecorations, WindowParams,
 };
 
@@ -45,6 +45,7 @@ pub(crate) struct Callbacks {
     should_close: Option<Box<dyn FnMut() -> bool>>,
     close: Option<Box<dyn FnOnce()>>,
     appearance_changed: Option<Box<dyn FnMut()>>,
+    occlusion_change: Option<Box<dyn FnMut(bool)>>,
 }
 
 struct RawWindow {
@@ -72,6 +73,7 @@ struct InProgressConfigure {
     size: Option<Size<Pixels>>,
     fullscreen: bool,
     maximized: bool,
+    suspended: bool,
     tiling: Tiling,
 }
 
@@ -96,6 +98,7 @@ pub struct WaylandWindowState {
     background_appearance: WindowBackgroundAppearance,
     fullscreen: bool,
     maximized: bool,
+    suspended: bool,
     tiling: Tiling,
     window_bounds: Bounds<Pixels>,
     client: WaylandClientStatePtr,
@@ -146,6 +149,7 @@ impl WaylandWindowState {
                     depth: 1,
                 },
                 transparent: true,
+                path_sample_count: options.antialiasing.path_sample_count(),
             };
             BladeRenderer::new(gpu_context, &raw_window, config)?
         };
@@ -170,6 +174,7 @@ impl WaylandWindowState {
             background_appearance: WindowBackgroundAppearance::Opaque,
             fullscreen: false,
             maximized: false,
+            suspended: false,
             tiling: Tiling::default(),
             window_bounds: options.bounds,
             in_progress_configure: None,
@@ -325,6 +330,10 @@ impl WaylandWindowStatePtr {
         self.state.borrow().surface.clone()
     }
 
+    pub fn toplevel(&self) -> xdg_toplevel::XdgToplevel {
+        self.state.borrow().toplevel.clone()
+    }
+
     pub fn ptr_eq(&self, other: &Self) -> bool {
         Rc::ptr_eq(&self.state, &other.state)
     }
@@ -360,8 +369,10 @@ impl WaylandWindowStatePtr {
 
                     if let Some(mut configure) = state.in_progress_configure.take() {
                         let got_unmaximized = state.maximized && !configure.maximized;
+                        let suspended_changed = state.suspended != configure.suspended;
                         state.fullscreen = configure.fullscreen;
                         state.maximized = configure.maximized;
+                        state.suspended = configure.suspended;
                         state.tiling = configure.tiling;
                         if !configure.fullscreen && !configure.maximized {
                             configure.size = if got_unmaximized {
@@ -380,6 +391,12 @@ impl WaylandWindowStatePtr {
                         if let Some(size) = configure.size {
                             self.resize(size);
                         }
+                        if suspended_changed {
+                            let mut callbacks = self.callbacks.borrow_mut();
+                            if let Some(occlusion_change) = callbacks.occlusion_change.as_mut() {
+                                occlusion_change(configure.suspended);
+                            }
+                        }
                     }
                 }
                 let mut state = self.state.borrow_mut();
@@ -469,6 +486,7 @@ impl WaylandWindowStatePtr {
                 let mut tiling = Tiling::default();
                 let mut fullscreen = false;
                 let mut maximized = false;
+                let mut suspended = false;
 
                 for state in states {
                     match state {
@@ -478,6 +496,9 @@ impl WaylandWindowStatePtr {
                         xdg_toplevel::State::Fullscreen => {
                             fullscreen = true;
                         }
+                        xdg_toplevel::State::Suspended => {
+                            suspended = true;
+                        }
                         xdg_toplevel::State::TiledTop => {
                             tiling.top = true;
                         }
@@ -505,6 +526,7 @@ impl WaylandWindowStatePtr {
                     size,
                     fullscreen,
                     maximized,
+                    suspended,
                     tiling,
                 });
 
@@ -751,12 +773,27 @@ where
 
 impl rwh::HasWindowHandle for WaylandWindow {
     fn window_handle(&self) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
-        unimplemented!()
+        let surface = self.borrow().surface.id().as_ptr().cast::<c_void>();
+        let Some(window) = NonNull::new(surface) else {
+            log::error!("Null wl_surface when getting window handle.");
+            return Err(rwh::HandleError::Unavailable);
+        };
+        let handle = rwh::WaylandWindowHandle::new(window);
+        Ok(unsafe { rwh::WindowHandle::borrow_raw(handle.into()) })
     }
 }
 impl rwh::HasDisplayHandle for WaylandWindow {
     fn display_handle(&self) -> Result<rwh::DisplayHandle<'_>, rwh::HandleError> {
-        unimplemented!()
+        let Some(backend) = self.borrow().surface.backend().upgrade() else {
+            log::error!("Wayland backend dropped when getting display handle.");
+            return Err(rwh::HandleError::Unavailable);
+        };
+        let Some(display) = NonNull::new(backend.display_ptr().cast::<c_void>()) else {
+            log::error!("Null wl_display when getting display handle.");
+            return Err(rwh::HandleError::Unavailable);
+        };
+        let handle = rwh::WaylandDisplayHandle::new(display);
+        Ok(unsafe { rwh::DisplayHandle::borrow_raw(handle.into()) })
     }
 }
 
@@ -769,6 +806,12 @@ impl PlatformWindow for WaylandWindow {
         self.borrow().maximized
     }
 
+    fn is_minimized(&self) -> bool {
+        // xdg-shell has no minimized state, but compositors suspend windows that they hide,
+        // including minimized ones.
+        self.borrow().suspended
+    }
+
     fn window_bounds(&self) -> WindowBounds {
         let state = self.borrow();
         if state.fullscreen {
@@ -951,6 +994,14 @@ impl PlatformWindow for WaylandWindow {
         self.0.callbacks.borrow_mut().appearance_changed = Some(callback);
     }
 
+    fn is_occluded(&self) -> bool {
+        self.borrow().suspended
+    }
+
+    fn on_occlusion_change(&self, callback: Box<dyn FnMut(bool)>) {
+        self.0.callbacks.borrow_mut().occlusion_change = Some(callback);
+    }
+
     fn draw(&self, scene: &Scene) {
         let mut state = self.borrow_mut();
         state.renderer.draw(scene);
@@ -966,6 +1017,13 @@ impl PlatformWindow for WaylandWindow {
         state.renderer.sprite_atlas().clone()
     }
 
+    fn set_antialiasing(&self, antialiasing: Antialiasing) {
+        let mut state = self.borrow_mut();
+        state
+            .renderer
+            .update_path_sample_count(antialiasing.path_sample_count());
+    }
+
     fn show_window_menu(&self, position: Point<Pixels>) {
         let state = self.borrow();
         let serial = state.client.get_serial(SerialKind::MousePress);
@@ -1067,7 +1125,10 @@ fn update_window(mut state: RefMut<WaylandWindowState>) {
     }
 
     if let Some(ref blur_manager) = state.globals.blur_manager {
-        if state.background_appearance == WindowBackgroundAppearance::Blurred {
+        if matches!(
+            state.background_appearance,
+            WindowBackgroundAppearance::Blurred | WindowBackgroundAppearance::Vibrant
+        ) {
             if state.blur.is_none() {
                 let blur = blur_manager.create(&state.surface, &state.globals.qh, ());
                 state.blur = Some(blur);
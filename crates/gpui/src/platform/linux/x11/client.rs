@@ -53,7 +53,7 @@ use crate::platform::{
         platform::{DOUBLE_CLICK_INTERVAL, SCROLL_LINES},
         reveal_path_internal,
         xdg_desktop_portal::{Event as XDPEvent, XDPEventSource},
-        LinuxClient,
+        xkb_layout_key, LinuxClient,
     },
     LinuxCommon, PlatformWindow,
 };
@@ -359,6 +359,18 @@ impl X11Client {
             .unwrap();
         assert!(xkb.supported);
 
+        // Listen for monitors being added, removed, resized or changing their refresh rate.
+        for screen in &xcb_connection.setup().roots {
+            xcb_connection
+                .randr_select_input(
+                    screen.root,
+                    randr::NotifyMask::SCREEN_CHANGE
+                        | randr::NotifyMask::CRTC_CHANGE
+                        | randr::NotifyMask::OUTPUT_CHANGE,
+                )
+                .log_err();
+        }
+
         let xkb_context = xkbc::Context::new(xkbc::CONTEXT_NO_FLAGS);
         let xkb_device_id = xkbc::x11::get_core_keyboard_device_id(&xcb_connection);
         let xkb_state = {
@@ -426,6 +438,11 @@ impl X11Client {
                         for (_, window) in &mut client.0.borrow_mut().windows {
                             window.window.set_appearance(appearance);
                         }
+                        client.system_appearance_changed();
+                    }
+                    XDPEvent::AccentColor(color) => {
+                        client.with_common(|common| common.accent_color = Some(color));
+                        client.system_appearance_changed();
                     }
                     XDPEvent::CursorTheme(_) | XDPEvent::CursorSize(_) => {
                         // noop, X11 manages this for us.
@@ -648,6 +665,14 @@ impl X11Client {
         }
     }
 
+    fn system_appearance_changed(&self) {
+        let callback = self.with_common(|common| common.callbacks.system_appearance_changed.take());
+        if let Some(mut callback) = callback {
+            callback();
+            self.with_common(|common| common.callbacks.system_appearance_changed = Some(callback));
+        }
+    }
+
     fn get_window(&self, win: xproto::Window) -> Option<X11WindowStatePtr> {
         let state = self.0.borrow();
         state
@@ -841,6 +866,14 @@ impl X11Client {
                 };
                 state.xkb = xkb_state;
             }
+            Event::RandrScreenChangeNotify(_) | Event::RandrNotify(_) => {
+                let mut state = self.0.borrow_mut();
+                if let Some(mut callback) = state.common.callbacks.displays_changed.take() {
+                    drop(state);
+                    callback();
+                    self.0.borrow_mut().common.callbacks.displays_changed = Some(callback);
+                }
+            }
             Event::XkbStateNotify(event) => {
                 let mut state = self.0.borrow_mut();
                 let old_layout = state.xkb.serialize_layout(STATE_LAYOUT_EFFECTIVE);
@@ -930,11 +963,13 @@ impl X11Client {
                             }
                             xkbc::Status::Cancelled => {
                                 let pre_edit = state.pre_edit_text.take();
+                                let new_pre_edit = Keystroke::underlying_dead_key(keysym);
+                                state.pre_edit_text = new_pre_edit.clone();
                                 drop(state);
                                 if let Some(pre_edit) = pre_edit {
                                     window.handle_ime_commit(pre_edit);
                                 }
-                                if let Some(current_key) = Keystroke::underlying_dead_key(keysym) {
+                                if let Some(current_key) = new_pre_edit {
                                     window.handle_ime_preedit(current_key);
                                 }
                                 state = self.0.borrow_mut();
@@ -1292,6 +1327,10 @@ impl LinuxClient for X11Client {
             .to_string()
     }
 
+    fn layout_key(&self, scan_code: u32) -> Option<String> {
+        xkb_layout_key(&self.0.borrow().xkb, scan_code)
+    }
+
     fn displays(&self) -> Vec<Rc<dyn PlatformDisplay>> {
         let state = self.0.borrow();
         let setup = state.xcb_connection.setup();
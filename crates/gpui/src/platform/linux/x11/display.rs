@@ -1,6 +1,10 @@
 use anyhow::Result;
 use uuid::Uuid;
-use x11rb::{connection::Connection as _, xcb_ffi::XCBConnection};
+use x11rb::{
+    connection::Connection as _,
+    protocol::{randr::ConnectionExt as _, xproto::ConnectionExt as _},
+    xcb_ffi::XCBConnection,
+};
 
 use crate::{px, Bounds, DisplayId, Pixels, PlatformDisplay, Size};
 
@@ -8,6 +12,8 @@ use crate::{px, Bounds, DisplayId, Pixels, PlatformDisplay, Size};
 pub(crate) struct X11Display {
     x_screen_index: usize,
     bounds: Bounds<Pixels>,
+    scale_factor: f32,
+    refresh_rate: Option<f32>,
     uuid: Uuid,
 }
 
@@ -23,20 +29,56 @@ impl X11Display {
                 x_screen_index
             ));
         };
+        // The screen's size in the connection setup is the size it had when we connected, so ask
+        // for its current size, which changes when monitors are added, removed or resized.
+        let (width, height) = xcb
+            .get_geometry(screen.root)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map_or(
+                (screen.width_in_pixels, screen.height_in_pixels),
+                |geometry| (geometry.width, geometry.height),
+            );
         Ok(Self {
             x_screen_index,
             bounds: Bounds {
                 origin: Default::default(),
                 size: Size {
-                    width: px(screen.width_in_pixels as f32 / scale_factor),
-                    height: px(screen.height_in_pixels as f32 / scale_factor),
+                    width: px(width as f32 / scale_factor),
+                    height: px(height as f32 / scale_factor),
                 },
             },
+            scale_factor,
+            refresh_rate: refresh_rate(xcb, screen.root),
             uuid: Uuid::from_bytes([0; 16]),
         })
     }
 }
 
+/// Returns the refresh rate of the first active CRTC driving the given root window's screen.
+fn refresh_rate(xcb: &XCBConnection, root: u32) -> Option<f32> {
+    let resources = xcb
+        .randr_get_screen_resources_current(root)
+        .ok()?
+        .reply()
+        .ok()?;
+    resources.crtcs.iter().find_map(|crtc| {
+        let crtc_info = xcb
+            .randr_get_crtc_info(*crtc, resources.config_timestamp)
+            .ok()?
+            .reply()
+            .ok()?;
+        let mode = resources
+            .modes
+            .iter()
+            .find(|mode| mode.id == crtc_info.mode)?;
+        if mode.dot_clock == 0 || mode.htotal == 0 || mode.vtotal == 0 {
+            return None;
+        }
+        Some(mode.dot_clock as f32 / (mode.htotal as f32 * mode.vtotal as f32))
+    })
+}
+
 impl PlatformDisplay for X11Display {
     fn id(&self) -> DisplayId {
         DisplayId(self.x_screen_index as u32)
@@ -49,4 +91,12 @@ impl PlatformDisplay for X11Display {
     fn bounds(&self) -> Bounds<Pixels> {
         self.bounds
     }
+
+    fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    fn refresh_rate(&self) -> Option<f32> {
+        self.refresh_rate
+    }
 }
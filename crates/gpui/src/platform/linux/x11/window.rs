@@ -2,11 +2,11 @@ use anyhow::{anyhow, Context as _};
 
 use crate::platform::blade::{BladeContext, BladeRenderer, BladeSurfaceConfig};
 use crate::{
-    px, size, AnyWindowHandle, Bounds, Decorations, DevicePixels, ForegroundExecutor, GpuSpecs,
-    Modifiers, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler,
-    PlatformWindow, Point, PromptLevel, RequestFrameOptions, ResizeEdge, ScaledPixels, Scene, Size,
-    Tiling, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowDecorations,
-    WindowKind, WindowParams, X11ClientStatePtr,
+    px, size, Antialiasing, AnyWindowHandle, Bounds, Decorations, DevicePixels, ForegroundExecutor,
+    GpuSpecs, Modifiers, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
+    PlatformInputHandler, PlatformWindow, Point, PromptLevel, RequestFrameOptions, ResizeEdge,
+    ScaledPixels, Scene, Size, Tiling, WindowAppearance, WindowBackgroundAppearance, WindowBounds,
+    WindowDecorations, WindowKind, WindowLevel, WindowParams, X11ClientStatePtr,
 };
 
 use blade_graphics as gpu;
@@ -63,6 +63,8 @@ x11rb::atom_manager! {
         _NET_WM_STATE_FULLSCREEN,
         _NET_WM_STATE_HIDDEN,
         _NET_WM_STATE_FOCUSED,
+        _NET_WM_STATE_ABOVE,
+        _NET_WM_STATE_MODAL,
         _NET_ACTIVE_WINDOW,
         _NET_WM_SYNC_REQUEST,
         _NET_WM_SYNC_REQUEST_COUNTER,
@@ -70,6 +72,7 @@ x11rb::atom_manager! {
         _NET_WM_MOVERESIZE,
         _NET_WM_WINDOW_TYPE,
         _NET_WM_WINDOW_TYPE_NOTIFICATION,
+        _NET_WM_WINDOW_TYPE_UTILITY,
         _NET_WM_SYNC,
         _NET_SUPPORTED,
         _MOTIF_WM_HINTS,
@@ -238,6 +241,7 @@ pub struct Callbacks {
     should_close: Option<Box<dyn FnMut() -> bool>>,
     close: Option<Box<dyn FnOnce()>>,
     appearance_changed: Option<Box<dyn FnMut()>>,
+    occlusion_change: Option<Box<dyn FnMut(bool)>>,
 }
 
 pub struct X11WindowState {
@@ -251,6 +255,7 @@ pub struct X11WindowState {
     bounds: Bounds<Pixels>,
     scale_factor: f32,
     renderer: BladeRenderer,
+    raw_window: RawWindow,
     display: Rc<dyn PlatformDisplay>,
     input_handler: Option<PlatformInputHandler>,
     appearance: WindowAppearance,
@@ -306,12 +311,16 @@ impl rwh::HasDisplayHandle for RawWindow {
 
 impl rwh::HasWindowHandle for X11Window {
     fn window_handle(&self) -> Result<rwh::WindowHandle, rwh::HandleError> {
-        unimplemented!()
+        let state = self.0.state.borrow();
+        let handle = rwh::HasWindowHandle::window_handle(&state.raw_window)?.as_raw();
+        Ok(unsafe { rwh::WindowHandle::borrow_raw(handle) })
     }
 }
 impl rwh::HasDisplayHandle for X11Window {
     fn display_handle(&self) -> Result<rwh::DisplayHandle, rwh::HandleError> {
-        unimplemented!()
+        let state = self.0.state.borrow();
+        let handle = rwh::HasDisplayHandle::display_handle(&state.raw_window)?.as_raw();
+        Ok(unsafe { rwh::DisplayHandle::borrow_raw(handle) })
     }
 }
 
@@ -491,6 +500,46 @@ impl X11WindowState {
                     )?;
                 }
             }
+            if let Some(rwh::RawWindowHandle::Xcb(parent)) = params.parent {
+                check_reply(
+                    || "X11 ChangeProperty32 setting WM_TRANSIENT_FOR failed.",
+                    xcb.change_property32(
+                        xproto::PropMode::REPLACE,
+                        x_window,
+                        xproto::AtomEnum::WM_TRANSIENT_FOR,
+                        xproto::AtomEnum::WINDOW,
+                        &[parent.window.get()],
+                    ),
+                )?;
+            }
+            if params
+                .level
+                .is_some_and(|level| level != WindowLevel::Normal)
+            {
+                check_reply(
+                    || "X11 ChangeProperty32 setting initial window level failed.",
+                    xcb.change_property32(
+                        xproto::PropMode::REPLACE,
+                        x_window,
+                        atoms._NET_WM_STATE,
+                        xproto::AtomEnum::ATOM,
+                        &[atoms._NET_WM_STATE_ABOVE],
+                    ),
+                )?;
+            }
+            if params.modal {
+                // Appended so that it doesn't replace the initial window level.
+                check_reply(
+                    || "X11 ChangeProperty32 setting modal window state failed.",
+                    xcb.change_property32(
+                        xproto::PropMode::APPEND,
+                        x_window,
+                        atoms._NET_WM_STATE,
+                        xproto::AtomEnum::ATOM,
+                        &[atoms._NET_WM_STATE_MODAL],
+                    ),
+                )?;
+            }
             if params.kind == WindowKind::PopUp {
                 check_reply(
                     || "X11 ChangeProperty32 setting window type for pop-up failed.",
@@ -503,6 +552,18 @@ impl X11WindowState {
                     ),
                 )?;
             }
+            if params.kind == WindowKind::Tool {
+                check_reply(
+                    || "X11 ChangeProperty32 setting window type for tool window failed.",
+                    xcb.change_property32(
+                        xproto::PropMode::REPLACE,
+                        x_window,
+                        atoms._NET_WM_WINDOW_TYPE,
+                        xproto::AtomEnum::ATOM,
+                        &[atoms._NET_WM_WINDOW_TYPE_UTILITY],
+                    ),
+                )?;
+            }
 
             check_reply(
                 || "X11 ChangeProperty32 setting protocols failed.",
@@ -568,15 +629,14 @@ impl X11WindowState {
 
             xcb.flush().with_context(|| "X11 Flush failed.")?;
 
+            let raw_window = RawWindow {
+                connection: as_raw_xcb_connection::AsRawXcbConnection::as_raw_xcb_connection(xcb)
+                    as *mut _,
+                screen_id: x_screen_index,
+                window_id: x_window,
+                visual_id: visual.id,
+            };
             let renderer = {
-                let raw_window = RawWindow {
-                    connection: as_raw_xcb_connection::AsRawXcbConnection::as_raw_xcb_connection(
-                        xcb,
-                    ) as *mut _,
-                    screen_id: x_screen_index,
-                    window_id: x_window,
-                    visual_id: visual.id,
-                };
                 let config = BladeSurfaceConfig {
                     // Note: this has to be done after the GPU init, or otherwise
                     // the sizes are immediately invalidated.
@@ -586,6 +646,7 @@ impl X11WindowState {
                     // If the window appearance changes, then the renderer will get updated
                     // too
                     transparent: false,
+                    path_sample_count: params.antialiasing.path_sample_count(),
                 };
                 BladeRenderer::new(gpu_context, &raw_window, config)?
             };
@@ -600,6 +661,7 @@ impl X11WindowState {
                 bounds: bounds.to_pixels(scale_factor),
                 scale_factor,
                 renderer,
+                raw_window,
                 atoms: *atoms,
                 input_handler: None,
                 active: false,
@@ -706,8 +768,8 @@ impl Drop for X11Window {
 }
 
 enum WmHintPropertyState {
-    // Remove = 0,
-    // Add = 1,
+    Remove = 0,
+    Add = 1,
     Toggle = 2,
 }
 
@@ -851,7 +913,14 @@ impl X11WindowStatePtr {
     pub fn property_notify(&self, event: xproto::PropertyNotifyEvent) -> anyhow::Result<()> {
         let mut state = self.state.borrow_mut();
         if event.atom == state.atoms._NET_WM_STATE {
+            let was_hidden = state.hidden;
             self.set_wm_properties(state)?;
+            let hidden = self.state.borrow().hidden;
+            if hidden != was_hidden {
+                if let Some(ref mut fun) = self.callbacks.borrow_mut().occlusion_change {
+                    fun(hidden);
+                }
+            }
         } else if event.atom == state.atoms._GTK_EDGE_CONSTRAINTS {
             self.set_edge_constraints(state)?;
         }
@@ -908,7 +977,7 @@ impl X11WindowStatePtr {
         state.fullscreen = false;
         state.maximized_vertical = false;
         state.maximized_horizontal = false;
-        state.hidden = true;
+        state.hidden = false;
 
         for atom in atoms {
             if atom == state.atoms._NET_WM_STATE_FOCUSED {
@@ -1103,6 +1172,10 @@ impl PlatformWindow for X11Window {
         !state.hidden && state.maximized_vertical && state.maximized_horizontal
     }
 
+    fn is_minimized(&self) -> bool {
+        self.0.state.borrow().hidden
+    }
+
     fn window_bounds(&self) -> WindowBounds {
         let state = self.0.state.borrow();
         if self.is_maximized() {
@@ -1332,6 +1405,22 @@ impl PlatformWindow for X11Window {
         .unwrap();
     }
 
+    fn set_level(&self, level: WindowLevel) {
+        let state = self.0.state.borrow();
+        let property_state = if level == WindowLevel::Normal {
+            WmHintPropertyState::Remove
+        } else {
+            WmHintPropertyState::Add
+        };
+        self.set_wm_hints(
+            || "X11 SendEvent to set window level failed.",
+            property_state,
+            state.atoms._NET_WM_STATE_ABOVE,
+            xproto::AtomEnum::NONE.into(),
+        )
+        .log_err();
+    }
+
     fn toggle_fullscreen(&self) {
         let state = self.0.state.borrow();
         self.set_wm_hints(
@@ -1383,6 +1472,10 @@ impl PlatformWindow for X11Window {
         self.0.callbacks.borrow_mut().appearance_changed = Some(callback);
     }
 
+    fn on_occlusion_change(&self, callback: Box<dyn FnMut(bool)>) {
+        self.0.callbacks.borrow_mut().occlusion_change = Some(callback);
+    }
+
     fn draw(&self, scene: &Scene) {
         let mut inner = self.0.state.borrow_mut();
         inner.renderer.draw(scene);
@@ -1393,6 +1486,13 @@ impl PlatformWindow for X11Window {
         inner.renderer.sprite_atlas().clone()
     }
 
+    fn set_antialiasing(&self, antialiasing: Antialiasing) {
+        let mut inner = self.0.state.borrow_mut();
+        inner
+            .renderer
+            .update_path_sample_count(antialiasing.path_sample_count());
+    }
+
     fn show_window_menu(&self, position: Point<Pixels>) {
         let state = self.0.state.borrow();
 
@@ -7,10 +7,11 @@ use calloop::channel::Channel;
 use calloop::{EventSource, Poll, PostAction, Readiness, Token, TokenFactory};
 use smol::stream::StreamExt;
 
-use crate::{BackgroundExecutor, WindowAppearance};
+use crate::{BackgroundExecutor, Rgba, WindowAppearance};
 
 pub enum Event {
     WindowAppearance(WindowAppearance),
+    AccentColor(Rgba),
     #[cfg_attr(feature = "x11", allow(dead_code))]
     CursorTheme(String),
     #[cfg_attr(feature = "x11", allow(dead_code))]
@@ -36,6 +37,9 @@ impl XDPEventSource {
                         initial_appearance,
                     )))?;
                 }
+                if let Ok(initial_accent_color) = settings.accent_color().await {
+                    sender.send(Event::AccentColor(rgba_from_native(initial_accent_color)))?;
+                }
                 if let Ok(initial_theme) = settings
                     .read::<String>("org.gnome.desktop.interface", "cursor-theme")
                     .await
@@ -89,6 +93,19 @@ impl XDPEventSource {
                         .detach();
                 }
 
+                if let Ok(mut accent_color_changed) = settings.receive_accent_color_changed().await
+                {
+                    let sender = sender.clone();
+                    background
+                        .spawn(async move {
+                            while let Some(color) = accent_color_changed.next().await {
+                                sender.send(Event::AccentColor(rgba_from_native(color)))?;
+                            }
+                            anyhow::Ok(())
+                        })
+                        .detach();
+                }
+
                 let mut appearance_changed = settings.receive_color_scheme_changed().await?;
                 while let Some(scheme) = appearance_changed.next().await {
                     sender.send(Event::WindowAppearance(WindowAppearance::from_native(
@@ -155,6 +172,15 @@ impl EventSource for XDPEventSource {
     }
 }
 
+fn rgba_from_native(color: ashpd::desktop::Color) -> Rgba {
+    Rgba {
+        r: color.red() as f32,
+        g: color.green() as f32,
+        b: color.blue() as f32,
+        a: 1.0,
+    }
+}
+
 impl WindowAppearance {
     fn from_native(cs: ColorScheme) -> WindowAppearance {
         match cs {
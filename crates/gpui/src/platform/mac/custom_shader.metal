@@ -0,0 +1,70 @@
+// Compiled at runtime together with a `CustomShader`'s Metal source, which defines `shade`.
+
+#include <metal_stdlib>
+#include <simd/simd.h>
+
+using namespace metal;
+
+// Matches `ShaderQuad` in scene.rs.
+struct ShaderQuad {
+  uint order;
+  uint shader_id;
+  float2 origin;
+  float2 size;
+  float2 content_mask_origin;
+  float2 content_mask_size;
+  float opacity;
+  uint pad;
+  float4 uniforms[4];
+};
+
+struct ShaderInput {
+  float2 position;
+  float2 size;
+  float4 uniforms[4];
+};
+
+float4 shade(ShaderInput input);
+
+struct ShaderQuadVertexOutput {
+  uint quad_id [[flat]];
+  float4 position [[position]];
+  float clip_distance [[clip_distance]][4];
+};
+
+struct ShaderQuadFragmentInput {
+  uint quad_id [[flat]];
+  float4 position [[position]];
+};
+
+// The buffer indices match `QuadInputIndex` in metal_renderer.rs.
+vertex ShaderQuadVertexOutput shader_quad_vertex(uint unit_vertex_id [[vertex_id]],
+                                                 uint quad_id [[instance_id]],
+                                                 constant float2 *unit_vertices [[buffer(0)]],
+                                                 constant ShaderQuad *quads [[buffer(1)]],
+                                                 constant int2 *viewport_size [[buffer(2)]]) {
+  float2 unit_vertex = unit_vertices[unit_vertex_id];
+  ShaderQuad quad = quads[quad_id];
+  float2 position = unit_vertex * quad.size + quad.origin;
+  float2 device_position =
+      position / float2(*viewport_size) * float2(2., -2.) + float2(-1., 1.);
+  float2 content_mask_end = quad.content_mask_origin + quad.content_mask_size;
+  return ShaderQuadVertexOutput{
+      quad_id,
+      float4(device_position, 0., 1.),
+      {position.x - quad.content_mask_origin.x, content_mask_end.x - position.x,
+       position.y - quad.content_mask_origin.y, content_mask_end.y - position.y}};
+}
+
+fragment float4 shader_quad_fragment(ShaderQuadFragmentInput input [[stage_in]],
+                                     constant ShaderQuad *quads [[buffer(1)]]) {
+  ShaderQuad quad = quads[input.quad_id];
+  ShaderInput shader_input;
+  shader_input.position = (input.position.xy - quad.origin) / quad.size;
+  shader_input.size = quad.size;
+  for (int i = 0; i < 4; i++) {
+    shader_input.uniforms[i] = quad.uniforms[i];
+  }
+  float4 color = shade(shader_input);
+  return float4(color.rgb, color.a * quad.opacity);
+}
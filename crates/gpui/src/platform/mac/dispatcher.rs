@@ -2,7 +2,7 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 
-use crate::{PlatformDispatcher, TaskLabel};
+use crate::{PlatformDispatcher, Priority, TaskLabel};
 use async_task::Runnable;
 use objc::{
     class, msg_send,
@@ -29,6 +29,10 @@ pub(crate) fn dispatch_get_main_queue() -> dispatch_queue_t {
     unsafe { addr_of!(_dispatch_main_q) as *const _ as dispatch_queue_t }
 }
 
+/// The quality of service class of the global queue for user-interactive work, from `sys/qos.h`.
+/// Global queues can be requested by quality of service class in place of a priority.
+const QOS_CLASS_USER_INTERACTIVE: u32 = 0x21;
+
 pub(crate) struct MacDispatcher {
     parker: Arc<Mutex<Parker>>,
 }
@@ -53,10 +57,17 @@ impl PlatformDispatcher for MacDispatcher {
         is_main_thread == YES
     }
 
-    fn dispatch(&self, runnable: Runnable, _: Option<TaskLabel>) {
+    fn dispatch(&self, runnable: Runnable, _: Option<TaskLabel>, priority: Priority) {
+        // Tasks have always been dispatched to the high priority queue, so that stays the default
+        // and urgent tasks go to the user-interactive queue, which is the only one above it.
+        let queue_priority = match priority {
+            Priority::UserBlocking => i64::from(QOS_CLASS_USER_INTERACTIVE),
+            Priority::Default => i64::from(DISPATCH_QUEUE_PRIORITY_HIGH),
+            Priority::Background => i64::from(DISPATCH_QUEUE_PRIORITY_LOW),
+        };
         unsafe {
             dispatch_async_f(
-                dispatch_get_global_queue(DISPATCH_QUEUE_PRIORITY_HIGH.try_into().unwrap(), 0),
+                dispatch_get_global_queue(queue_priority.try_into().unwrap(), 0),
                 runnable.into_raw().as_ptr() as *mut c_void,
                 Some(trampoline),
             );
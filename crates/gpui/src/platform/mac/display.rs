@@ -1,12 +1,14 @@
-use crate::{px, size, Bounds, DisplayId, Pixels, PlatformDisplay};
+use crate::{point, px, size, Bounds, DisplayId, Pixels, PlatformDisplay};
 use anyhow::Result;
 use cocoa::{
     appkit::NSScreen,
     base::{id, nil},
-    foundation::{NSDictionary, NSString},
+    foundation::{NSArray, NSDictionary, NSString},
 };
 use core_foundation::uuid::{CFUUIDGetUUIDBytes, CFUUIDRef};
-use core_graphics::display::{CGDirectDisplayID, CGDisplayBounds, CGGetActiveDisplayList};
+use core_graphics::display::{
+    CGDirectDisplayID, CGDisplay, CGDisplayBounds, CGGetActiveDisplayList,
+};
 use objc::{msg_send, sel, sel_impl};
 use uuid::Uuid;
 
@@ -62,6 +64,23 @@ impl MacDisplay {
             }
         }
     }
+
+    /// Get the `NSScreen` for this display, if it is still connected.
+    fn ns_screen(&self) -> Option<id> {
+        unsafe {
+            let screens = NSScreen::screens(nil);
+            let screen_number_key: id = NSString::alloc(nil).init_str("NSScreenNumber");
+            (0..NSArray::count(screens))
+                .map(|ix| NSArray::objectAtIndex(screens, ix))
+                .find(|&screen| {
+                    let device_description = NSScreen::deviceDescription(screen);
+                    let screen_number = device_description.objectForKey_(screen_number_key);
+                    let screen_number: CGDirectDisplayID =
+                        msg_send![screen_number, unsignedIntegerValue];
+                    screen_number == self.0
+                })
+        }
+    }
 }
 
 #[link(name = "ApplicationServices", kind = "framework")]
@@ -114,4 +133,39 @@ impl PlatformDisplay for MacDisplay {
             }
         }
     }
+    fn visible_bounds(&self) -> Bounds<Pixels> {
+        let Some(screen) = self.ns_screen() else {
+            return self.bounds();
+        };
+        unsafe {
+            let frame = NSScreen::frame(screen);
+            let visible_frame = NSScreen::visibleFrame(screen);
+
+            // Flip the y coordinate to be top-left origin, relative to the display.
+            let top = frame.origin.y + frame.size.height
+                - (visible_frame.origin.y + visible_frame.size.height);
+            Bounds {
+                origin: point(
+                    px((visible_frame.origin.x - frame.origin.x) as f32),
+                    px(top as f32),
+                ),
+                size: size(
+                    px(visible_frame.size.width as f32),
+                    px(visible_frame.size.height as f32),
+                ),
+            }
+        }
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.ns_screen().map_or(1., |screen| unsafe {
+            NSScreen::backingScaleFactor(screen) as f32
+        })
+    }
+
+    fn refresh_rate(&self) -> Option<f32> {
+        // Built-in displays report a refresh rate of zero.
+        let refresh_rate = CGDisplay::new(self.0).display_mode()?.refresh_rate();
+        (refresh_rate > 0.).then_some(refresh_rate as f32)
+    }
 }
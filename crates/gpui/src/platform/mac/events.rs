@@ -410,6 +410,94 @@ unsafe fn parse_keystroke(native_event: id) -> Keystroke {
     }
 }
 
+/// Virtual key codes of the character keys on an ANSI keyboard, by the key they produce
+/// with the US QWERTY layout.
+const ANSI_KEY_CODES: &[(&str, CGKeyCode)] = &[
+    ("a", 0x00),
+    ("s", 0x01),
+    ("d", 0x02),
+    ("f", 0x03),
+    ("h", 0x04),
+    ("g", 0x05),
+    ("z", 0x06),
+    ("x", 0x07),
+    ("c", 0x08),
+    ("v", 0x09),
+    ("b", 0x0B),
+    ("q", 0x0C),
+    ("w", 0x0D),
+    ("e", 0x0E),
+    ("r", 0x0F),
+    ("y", 0x10),
+    ("t", 0x11),
+    ("1", 0x12),
+    ("2", 0x13),
+    ("3", 0x14),
+    ("4", 0x15),
+    ("6", 0x16),
+    ("5", 0x17),
+    ("=", 0x18),
+    ("9", 0x19),
+    ("7", 0x1A),
+    ("-", 0x1B),
+    ("8", 0x1C),
+    ("0", 0x1D),
+    ("]", 0x1E),
+    ("o", 0x1F),
+    ("u", 0x20),
+    ("[", 0x21),
+    ("i", 0x22),
+    ("p", 0x23),
+    ("l", 0x25),
+    ("j", 0x26),
+    ("'", 0x27),
+    ("k", 0x28),
+    (";", 0x29),
+    ("\\", 0x2A),
+    (",", 0x2B),
+    ("/", 0x2C),
+    ("n", 0x2D),
+    ("m", 0x2E),
+    (".", 0x2F),
+    ("`", 0x32),
+];
+
+/// Returns the keystroke typed by pressing the keys of the given physical keystroke with the
+/// current keyboard layout, resolving keys the same way as [`parse_keystroke`].
+pub(crate) fn layout_keystroke(physical: &Keystroke) -> Option<Keystroke> {
+    let (_, code) = ANSI_KEY_CODES
+        .iter()
+        .find(|(key, _)| *key == physical.key)?;
+    let key = chars_for_modified_key(*code, layout_modifiers(physical));
+    (!key.is_empty()).then(|| Keystroke {
+        modifiers: physical.modifiers,
+        key,
+        key_char: None,
+    })
+}
+
+/// Returns the physical keystroke, named after the US QWERTY layout, that types the given
+/// keystroke with the current keyboard layout.
+pub(crate) fn physical_keystroke(keystroke: &Keystroke) -> Option<Keystroke> {
+    let modifiers = layout_modifiers(keystroke);
+    let (key, _) = ANSI_KEY_CODES
+        .iter()
+        .find(|(_, code)| chars_for_modified_key(*code, modifiers) == keystroke.key)?;
+    Some(Keystroke {
+        modifiers: keystroke.modifiers,
+        key: key.to_string(),
+        key_char: None,
+    })
+}
+
+fn layout_modifiers(keystroke: &Keystroke) -> u32 {
+    if keystroke.modifiers.platform || always_use_command_layout() {
+        CMD_MOD
+    } else {
+        NO_MOD
+    }
+}
+
 fn always_use_command_layout() -> bool {
     if chars_for_modified_key(0, NO_MOD).is_ascii() {
         return false;
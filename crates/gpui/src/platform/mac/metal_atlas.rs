@@ -12,6 +12,11 @@ use std::borrow::Cow;
 
 pub(crate) struct MetalAtlas(Mutex<MetalAtlasState>);
 
+const DEFAULT_PAGE_SIZE: Size<DevicePixels> = Size {
+    width: DevicePixels(1024),
+    height: DevicePixels(1024),
+};
+
 impl MetalAtlas {
     pub(crate) fn new(device: Device, path_sample_count: u32) -> Self {
         MetalAtlas(Mutex::new(MetalAtlasState {
@@ -21,6 +26,7 @@ impl MetalAtlas {
             path_textures: Default::default(),
             tiles_by_key: Default::default(),
             path_sample_count,
+            page_size: DEFAULT_PAGE_SIZE,
         }))
     }
 
@@ -40,6 +46,16 @@ impl MetalAtlas {
         self.0.lock().allocate(size, texture_kind)
     }
 
+    /// Sets how many samples paths are rasterized with, discarding the path textures that were
+    /// allocated with the previous sample count.
+    pub(crate) fn set_path_sample_count(&self, path_sample_count: u32) {
+        let mut lock = self.0.lock();
+        if lock.path_sample_count != path_sample_count {
+            lock.path_sample_count = path_sample_count;
+            lock.path_textures.drain();
+        }
+    }
+
     pub(crate) fn clear_textures(&self, texture_kind: AtlasTextureKind) {
         let mut lock = self.0.lock();
         let textures = match texture_kind {
@@ -60,6 +76,7 @@ struct MetalAtlasState {
     path_textures: AtlasTextureList<MetalAtlasTexture>,
     tiles_by_key: FxHashMap<AtlasKey, AtlasTile>,
     path_sample_count: u32,
+    page_size: Size<DevicePixels>,
 }
 
 impl PlatformAtlas for MetalAtlas {
@@ -87,14 +104,15 @@ impl PlatformAtlas for MetalAtlas {
 
     fn remove(&self, key: &AtlasKey) {
         let mut lock = self.0.lock();
-        let Some(id) = lock.tiles_by_key.get(key).map(|v| v.texture_id) else {
+        let Some(tile) = lock.tiles_by_key.remove(key) else {
             return;
         };
+        let id = tile.texture_id;
 
         let textures = match id.kind {
             AtlasTextureKind::Monochrome => &mut lock.monochrome_textures,
             AtlasTextureKind::Polychrome => &mut lock.polychrome_textures,
-            AtlasTextureKind::Path => &mut lock.polychrome_textures,
+            AtlasTextureKind::Path => &mut lock.path_textures,
         };
 
         let Some(texture_slot) = textures
@@ -106,16 +124,34 @@ impl PlatformAtlas for MetalAtlas {
         };
 
         if let Some(mut texture) = texture_slot.take() {
+            texture.allocator.deallocate(tile.tile_id.into());
             texture.decrement_ref_count();
 
             if texture.is_unreferenced() {
                 textures.free_list.push(id.index as usize);
-                lock.tiles_by_key.remove(key);
             } else {
                 *texture_slot = Some(texture);
             }
         }
     }
+
+    fn retain(&self, predicate: &mut dyn FnMut(&AtlasKey) -> bool) {
+        let keys = self
+            .0
+            .lock()
+            .tiles_by_key
+            .keys()
+            .filter(|key| !predicate(key))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in keys {
+            self.remove(&key);
+        }
+    }
+
+    fn set_page_size(&self, size: Size<DevicePixels>) {
+        self.0.lock().page_size = size;
+    }
 }
 
 impl MetalAtlasState {
@@ -149,16 +185,12 @@ impl MetalAtlasState {
         min_size: Size<DevicePixels>,
         kind: AtlasTextureKind,
     ) -> &mut MetalAtlasTexture {
-        const DEFAULT_ATLAS_SIZE: Size<DevicePixels> = Size {
-            width: DevicePixels(1024),
-            height: DevicePixels(1024),
-        };
         // Max texture size on all modern Apple GPUs. Anything bigger than that crashes in validateWithDevice.
         const MAX_ATLAS_SIZE: Size<DevicePixels> = Size {
             width: DevicePixels(16384),
             height: DevicePixels(16384),
         };
-        let size = min_size.min(&MAX_ATLAS_SIZE).max(&DEFAULT_ATLAS_SIZE);
+        let size = min_size.max(&self.page_size).min(&MAX_ATLAS_SIZE);
         let texture_descriptor = metal::TextureDescriptor::new();
         texture_descriptor.set_width(size.width.into());
         texture_descriptor.set_height(size.height.into());
@@ -1,8 +1,9 @@
 use super::metal_atlas::MetalAtlas;
 use crate::{
     point, size, AtlasTextureId, AtlasTextureKind, AtlasTile, Background, Bounds, ContentMask,
-    DevicePixels, MonochromeSprite, PaintSurface, Path, PathId, PathVertex, PolychromeSprite,
-    PrimitiveBatch, Quad, ScaledPixels, Scene, Shadow, Size, Surface, Underline,
+    CustomShaderId, DevicePixels, LayerSprite, MonochromeSprite, PaintSurface, Path, PathId,
+    PathVertex, PolychromeSprite, PrimitiveBatch, Quad, ScaledPixels, Scene, Shadow, Size, Surface,
+    Underline,
 };
 use anyhow::{anyhow, Result};
 use block::ConcreteBlock;
@@ -12,14 +13,16 @@ use cocoa::{
     quartzcore::AutoresizingMask,
 };
 use collections::HashMap;
-use core_foundation::base::TCFType;
+use core_foundation::{base::TCFType, string::CFStringRef};
+use core_graphics::color_space::{kCGColorSpaceSRGB, CGColorSpace};
 use foreign_types::ForeignType;
+use image::RgbaImage;
 use media::core_video::CVMetalTextureCache;
 use metal::{CAMetalLayer, CommandQueue, MTLPixelFormat, MTLResourceOptions, NSRange};
 use objc::{self, msg_send, sel, sel_impl};
 use parking_lot::Mutex;
 use smallvec::SmallVec;
-use std::{cell::Cell, ffi::c_void, mem, ptr, sync::Arc};
+use std::{borrow::Cow, cell::Cell, ffi::c_void, mem, ptr, slice, sync::Arc};
 
 // Exported to metal
 pub(crate) type PointF = crate::Point<f32>;
@@ -28,9 +31,6 @@ pub(crate) type PointF = crate::Point<f32>;
 const SHADERS_METALLIB: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/shaders.metallib"));
 #[cfg(feature = "runtime_shaders")]
 const SHADERS_SOURCE_FILE: &str = include_str!(concat!(env!("OUT_DIR"), "/stitched_shaders.metal"));
-// Use 4x MSAA, all devices support it.
-// https://developer.apple.com/documentation/metal/mtldevice/1433355-supportstexturesamplecount
-const PATH_SAMPLE_COUNT: u32 = 4;
 
 pub type Context = Arc<Mutex<InstanceBufferPool>>;
 pub type Renderer = MetalRenderer;
@@ -41,8 +41,15 @@ pub unsafe fn new_renderer(
     _native_view: *mut c_void,
     _bounds: crate::Size<f32>,
     _transparent: bool,
+    path_sample_count: u32,
+    hdr: bool,
 ) -> Renderer {
-    MetalRenderer::new(context)
+    MetalRenderer::new(context, path_sample_count, hdr)
+}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    static kCGColorSpaceExtendedSRGB: CFStringRef;
 }
 
 pub(crate) struct InstanceBufferPool {
@@ -95,23 +102,47 @@ pub(crate) struct MetalRenderer {
     layer: metal::MetalLayer,
     presents_with_transaction: bool,
     command_queue: CommandQueue,
+    library: metal::Library,
+    path_sample_count: u32,
+    /// The format of the drawables and of every texture the scene is drawn into, which is half
+    /// float for HDR windows and 8-bit otherwise.
+    pixel_format: MTLPixelFormat,
+    /// How many times brighter than SDR white the window's screen can show colors, see
+    /// [`crate::Window::hdr_headroom`].
+    hdr_headroom: f32,
     paths_rasterization_pipeline_state: metal::RenderPipelineState,
     path_sprites_pipeline_state: metal::RenderPipelineState,
     shadows_pipeline_state: metal::RenderPipelineState,
     quads_pipeline_state: metal::RenderPipelineState,
+    clear_pipeline_state: metal::RenderPipelineState,
     underlines_pipeline_state: metal::RenderPipelineState,
     monochrome_sprites_pipeline_state: metal::RenderPipelineState,
     polychrome_sprites_pipeline_state: metal::RenderPipelineState,
+    layer_sprites_pipeline_state: metal::RenderPipelineState,
     surfaces_pipeline_state: metal::RenderPipelineState,
+    bgra_surfaces_pipeline_state: metal::RenderPipelineState,
+    /// The pipelines of the custom shaders drawn so far, or `None` for those that failed to
+    /// compile.
+    custom_shader_pipeline_states: HashMap<CustomShaderId, Option<metal::RenderPipelineState>>,
     unit_vertices: metal::Buffer,
     #[allow(clippy::arc_with_non_send_sync)]
     instance_buffer_pool: Arc<Mutex<InstanceBufferPool>>,
     sprite_atlas: Arc<MetalAtlas>,
     core_video_texture_cache: CVMetalTextureCache,
+    /// The last presented frame, which is kept so that only the damaged region of the next
+    /// frame has to be repainted before it's copied to the drawable.
+    back_buffer: Option<metal::Texture>,
+    /// The textures that offscreen layers are drawn into, which are reused across frames.
+    offscreen_textures: Vec<metal::Texture>,
+    offscreen_textures_used: usize,
 }
 
 impl MetalRenderer {
-    pub fn new(instance_buffer_pool: Arc<Mutex<InstanceBufferPool>>) -> Self {
+    pub fn new(
+        instance_buffer_pool: Arc<Mutex<InstanceBufferPool>>,
+        path_sample_count: u32,
+        hdr: bool,
+    ) -> Self {
         // Prefer low‐power integrated GPUs on Intel Mac. On Apple
         // Silicon, there is only ever one GPU, so this is equivalent to
         // `metal::Device::system_default()`.
@@ -122,14 +153,35 @@ impl MetalRenderer {
             std::process::exit(1);
         };
 
+        let pixel_format = if hdr {
+            MTLPixelFormat::RGBA16Float
+        } else {
+            MTLPixelFormat::BGRA8Unorm
+        };
         let layer = metal::MetalLayer::new();
         layer.set_device(&device);
-        layer.set_pixel_format(MTLPixelFormat::BGRA8Unorm);
+        layer.set_pixel_format(pixel_format);
         layer.set_opaque(false);
         layer.set_maximum_drawable_count(3);
+        // Frames are copied into drawables from the back buffer.
+        layer.set_framebuffer_only(false);
         unsafe {
             let _: () = msg_send![&*layer, setAllowsNextDrawableTimeout: NO];
             let _: () = msg_send![&*layer, setNeedsDisplayOnBoundsChange: YES];
+            // Colors are defined in sRGB, so the layer's contents are tagged as sRGB for the
+            // window server to convert them to the display's color space. Untagged contents are
+            // shown in the display's native color space, which makes them look oversaturated on
+            // wide-gamut displays. HDR layers use extended sRGB, which matches sRGB up to SDR
+            // white and goes as far above it as the display's headroom.
+            let color_space_name = if hdr {
+                let _: () = msg_send![&*layer, setWantsExtendedDynamicRangeContent: YES];
+                kCGColorSpaceExtendedSRGB
+            } else {
+                kCGColorSpaceSRGB
+            };
+            if let Some(color_space) = CGColorSpace::create_with_name(color_space_name) {
+                let _: () = msg_send![&*layer, setColorspace: color_space.as_ptr() as *mut c_void];
+            }
             let _: () = msg_send![
                 &*layer,
                 setAutoresizingMask: AutoresizingMask::WIDTH_SIZABLE
@@ -173,7 +225,7 @@ impl MetalRenderer {
             "path_rasterization_vertex",
             "path_rasterization_fragment",
             MTLPixelFormat::R16Float,
-            PATH_SAMPLE_COUNT,
+            path_sample_count,
         );
         let path_sprites_pipeline_state = build_pipeline_state(
             &device,
@@ -181,7 +233,7 @@ impl MetalRenderer {
             "path_sprites",
             "path_sprite_vertex",
             "path_sprite_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
         );
         let shadows_pipeline_state = build_pipeline_state(
             &device,
@@ -189,7 +241,7 @@ impl MetalRenderer {
             "shadows",
             "shadow_vertex",
             "shadow_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
         );
         let quads_pipeline_state = build_pipeline_state(
             &device,
@@ -197,7 +249,15 @@ impl MetalRenderer {
             "quads",
             "quad_vertex",
             "quad_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
+        );
+        let clear_pipeline_state = build_clear_pipeline_state(
+            &device,
+            &library,
+            "clear",
+            "quad_vertex",
+            "quad_fragment",
+            pixel_format,
         );
         let underlines_pipeline_state = build_pipeline_state(
             &device,
@@ -205,7 +265,7 @@ impl MetalRenderer {
             "underlines",
             "underline_vertex",
             "underline_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
         );
         let monochrome_sprites_pipeline_state = build_pipeline_state(
             &device,
@@ -213,7 +273,7 @@ impl MetalRenderer {
             "monochrome_sprites",
             "monochrome_sprite_vertex",
             "monochrome_sprite_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
         );
         let polychrome_sprites_pipeline_state = build_pipeline_state(
             &device,
@@ -221,7 +281,15 @@ impl MetalRenderer {
             "polychrome_sprites",
             "polychrome_sprite_vertex",
             "polychrome_sprite_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
+        );
+        let layer_sprites_pipeline_state = build_pipeline_state(
+            &device,
+            &library,
+            "layer_sprites",
+            "layer_sprite_vertex",
+            "layer_sprite_fragment",
+            pixel_format,
         );
         let surfaces_pipeline_state = build_pipeline_state(
             &device,
@@ -229,11 +297,19 @@ impl MetalRenderer {
             "surfaces",
             "surface_vertex",
             "surface_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
+        );
+        let bgra_surfaces_pipeline_state = build_pipeline_state(
+            &device,
+            &library,
+            "bgra_surfaces",
+            "surface_vertex",
+            "surface_bgra_fragment",
+            pixel_format,
         );
 
         let command_queue = device.new_command_queue();
-        let sprite_atlas = Arc::new(MetalAtlas::new(device.clone(), PATH_SAMPLE_COUNT));
+        let sprite_atlas = Arc::new(MetalAtlas::new(device.clone(), path_sample_count));
         let core_video_texture_cache =
             unsafe { CVMetalTextureCache::new(device.as_ptr()).unwrap() };
 
@@ -242,21 +318,88 @@ impl MetalRenderer {
             layer,
             presents_with_transaction: false,
             command_queue,
+            library,
+            path_sample_count,
+            pixel_format,
+            hdr_headroom: 1.,
             paths_rasterization_pipeline_state,
             path_sprites_pipeline_state,
             shadows_pipeline_state,
             quads_pipeline_state,
+            clear_pipeline_state,
             underlines_pipeline_state,
             monochrome_sprites_pipeline_state,
             polychrome_sprites_pipeline_state,
+            layer_sprites_pipeline_state,
             surfaces_pipeline_state,
+            bgra_surfaces_pipeline_state,
+            custom_shader_pipeline_states: HashMap::default(),
             unit_vertices,
             instance_buffer_pool,
             sprite_atlas,
             core_video_texture_cache,
+            back_buffer: None,
+            offscreen_textures: Vec::new(),
+            offscreen_textures_used: 0,
+        }
+    }
+
+    /// Sets how many samples paths are rasterized with, see
+    /// [`crate::Antialiasing::path_sample_count`].
+    pub fn update_path_sample_count(&mut self, path_sample_count: u32) {
+        if path_sample_count != self.path_sample_count {
+            self.path_sample_count = path_sample_count;
+            self.sprite_atlas.set_path_sample_count(path_sample_count);
+            self.paths_rasterization_pipeline_state = build_path_rasterization_pipeline_state(
+                &self.device,
+                &self.library,
+                "paths_rasterization",
+                "path_rasterization_vertex",
+                "path_rasterization_fragment",
+                MTLPixelFormat::R16Float,
+                path_sample_count,
+            );
         }
     }
 
+    /// Compiles the custom shaders of the scene that haven't been drawn before.
+    fn prepare_custom_shaders(&mut self, scene: &Scene) {
+        for (id, shader) in &scene.custom_shaders {
+            let device = &self.device;
+            let pixel_format = self.pixel_format;
+            self.custom_shader_pipeline_states
+                .entry(*id)
+                .or_insert_with(|| {
+                    let source = format!("{}\n{}", include_str!("custom_shader.metal"), shader.msl);
+                    match device.new_library_with_source(&source, &metal::CompileOptions::new()) {
+                        Ok(library) => Some(build_pipeline_state(
+                            device,
+                            &library,
+                            "custom_shader_quads",
+                            "shader_quad_vertex",
+                            "shader_quad_fragment",
+                            pixel_format,
+                        )),
+                        Err(error) => {
+                            log::error!("failed to compile custom shader: {error}");
+                            None
+                        }
+                    }
+                });
+        }
+    }
+
+    /// Whether the renderer outputs to an HDR drawable.
+    pub fn hdr(&self) -> bool {
+        self.pixel_format == MTLPixelFormat::RGBA16Float
+    }
+
+    /// Sets how many times brighter than SDR white the window's screen can currently show
+    /// colors, which the brightest colors of images and surfaces are compressed to.
+    pub fn update_hdr_headroom(&mut self, hdr_headroom: f32) {
+        self.hdr_headroom = hdr_headroom;
+    }
+
     pub fn layer(&self) -> &metal::MetalLayerRef {
         &self.layer
     }
@@ -296,7 +439,9 @@ impl MetalRenderer {
         // nothing to do
     }
 
-    pub fn draw(&mut self, scene: &Scene) {
+    /// Draws the scene into the next drawable. If `damage` is given, only that region of the
+    /// previously drawn frame is repainted.
+    pub fn draw(&mut self, scene: &Scene, damage: Option<Bounds<ScaledPixels>>) {
         let layer = self.layer.clone();
         let viewport_size = layer.drawable_size();
         let viewport_size: Size<DevicePixels> = size(
@@ -310,17 +455,48 @@ impl MetalRenderer {
                 "failed to retrieve next drawable, drawable size: {:?}",
                 viewport_size
             );
+            // The damage of this frame is dropped, so the next one has to be drawn in full.
+            self.back_buffer = None;
             return;
         };
+        let (back_buffer, is_new_back_buffer) = self.back_buffer(drawable.texture());
+        // Pixels outside the damaged region can only be kept if there's a previous frame to keep
+        // them from.
+        let scissor_rect = damage
+            .filter(|_| !is_new_back_buffer)
+            .and_then(|damage| scissor_rect(damage, viewport_size));
 
         loop {
             let mut instance_buffer = self.instance_buffer_pool.lock().acquire(&self.device);
 
-            let command_buffer =
-                self.draw_primitives(scene, &mut instance_buffer, drawable, viewport_size);
+            let command_buffer = self.draw_primitives(
+                scene,
+                &mut instance_buffer,
+                &back_buffer,
+                viewport_size,
+                scissor_rect,
+            );
 
             match command_buffer {
                 Ok(command_buffer) => {
+                    let blit_encoder = command_buffer.new_blit_command_encoder();
+                    blit_encoder.copy_from_texture(
+                        &back_buffer,
+                        0,
+                        0,
+                        metal::MTLOrigin { x: 0, y: 0, z: 0 },
+                        metal::MTLSize {
+                            width: back_buffer.width(),
+                            height: back_buffer.height(),
+                            depth: 1,
+                        },
+                        drawable.texture(),
+                        0,
+                        0,
+                        metal::MTLOrigin { x: 0, y: 0, z: 0 },
+                    );
+                    blit_encoder.end_encoding();
+
                     let instance_buffer_pool = self.instance_buffer_pool.clone();
                     let instance_buffer = Cell::new(Some(instance_buffer));
                     let block = ConcreteBlock::new(move |_| {
@@ -346,39 +522,196 @@ impl MetalRenderer {
                         "failed to render: {}. retrying with larger instance buffer size",
                         err
                     );
-                    let mut instance_buffer_pool = self.instance_buffer_pool.lock();
-                    let buffer_size = instance_buffer_pool.buffer_size;
-                    if buffer_size >= 256 * 1024 * 1024 {
-                        log::error!("instance buffer size grew too large: {}", buffer_size);
+                    if !self.grow_instance_buffers() {
+                        self.back_buffer = None;
                         break;
                     }
-                    instance_buffer_pool.reset(buffer_size * 2);
-                    log::info!(
-                        "increased instance buffer size to {}",
-                        instance_buffer_pool.buffer_size
-                    );
                 }
             }
         }
     }
 
+    /// Returns the back buffer, recreating it if it doesn't match the size of the drawable, along
+    /// with whether it was recreated.
+    fn back_buffer(&mut self, drawable: &metal::TextureRef) -> (metal::Texture, bool) {
+        if let Some(back_buffer) = self.back_buffer.as_ref() {
+            if back_buffer.width() == drawable.width() && back_buffer.height() == drawable.height()
+            {
+                return (back_buffer.clone(), false);
+            }
+        }
+
+        let texture_descriptor = metal::TextureDescriptor::new();
+        texture_descriptor.set_width(drawable.width());
+        texture_descriptor.set_height(drawable.height());
+        texture_descriptor.set_pixel_format(self.pixel_format);
+        texture_descriptor.set_storage_mode(metal::MTLStorageMode::Private);
+        texture_descriptor.set_usage(metal::MTLTextureUsage::RenderTarget);
+        let back_buffer = self.device.new_texture(&texture_descriptor);
+        self.back_buffer = Some(back_buffer.clone());
+        (back_buffer, true)
+    }
+
+    /// Renders the scene into an offscreen texture the size of the drawable and reads it back,
+    /// leaving what's presented in the window untouched.
+    pub fn capture(&mut self, scene: &Scene) -> Result<RgbaImage> {
+        let drawable_size = self.layer.drawable_size();
+        let viewport_size: Size<DevicePixels> = size(
+            (drawable_size.width.ceil() as i32).into(),
+            (drawable_size.height.ceil() as i32).into(),
+        );
+        let width = i32::from(viewport_size.width).max(1) as NSUInteger;
+        let height = i32::from(viewport_size.height).max(1) as NSUInteger;
+
+        let texture_descriptor = metal::TextureDescriptor::new();
+        texture_descriptor.set_width(width);
+        texture_descriptor.set_height(height);
+        texture_descriptor.set_pixel_format(self.pixel_format);
+        texture_descriptor.set_storage_mode(metal::MTLStorageMode::Managed);
+        texture_descriptor.set_usage(metal::MTLTextureUsage::RenderTarget);
+        let texture = self.device.new_texture(&texture_descriptor);
+
+        // The captured image is SDR, so colors are compressed to SDR white.
+        let hdr_headroom = mem::replace(&mut self.hdr_headroom, 1.);
+        let result = loop {
+            let mut instance_buffer = self.instance_buffer_pool.lock().acquire(&self.device);
+            match self.draw_primitives(scene, &mut instance_buffer, &texture, viewport_size, None) {
+                Ok(command_buffer) => {
+                    // Managed textures have to be synchronized before the CPU can read them.
+                    let blit_encoder = command_buffer.new_blit_command_encoder();
+                    blit_encoder.synchronize_resource(&texture);
+                    blit_encoder.end_encoding();
+                    command_buffer.commit();
+                    command_buffer.wait_until_completed();
+                    self.instance_buffer_pool.lock().release(instance_buffer);
+                    break Ok(());
+                }
+                Err(err) => {
+                    if !self.grow_instance_buffers() {
+                        break Err(err);
+                    }
+                }
+            }
+        };
+        self.hdr_headroom = hdr_headroom;
+        result?;
+
+        let region = metal::MTLRegion::new_2d(0, 0, width, height);
+        let pixels = if self.hdr() {
+            let mut channels = vec![0u16; (width * height * 4) as usize];
+            texture.get_bytes(channels.as_mut_ptr() as *mut c_void, width * 8, region, 0);
+            channels.into_iter().map(half_to_unorm8).collect()
+        } else {
+            let mut pixels = vec![0; (width * height * 4) as usize];
+            texture.get_bytes(pixels.as_mut_ptr() as *mut c_void, width * 4, region, 0);
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+            pixels
+        };
+        RgbaImage::from_raw(width as u32, height as u32, pixels)
+            .ok_or_else(|| anyhow!("captured frame has an unexpected size"))
+    }
+
+    /// Doubles the size of pooled instance buffers after a scene didn't fit in one, returning
+    /// false if they've already grown too large.
+    fn grow_instance_buffers(&self) -> bool {
+        let mut instance_buffer_pool = self.instance_buffer_pool.lock();
+        let buffer_size = instance_buffer_pool.buffer_size;
+        if buffer_size >= 256 * 1024 * 1024 {
+            log::error!("instance buffer size grew too large: {}", buffer_size);
+            return false;
+        }
+        instance_buffer_pool.reset(buffer_size * 2);
+        log::info!(
+            "increased instance buffer size to {}",
+            instance_buffer_pool.buffer_size
+        );
+        true
+    }
+
     fn draw_primitives(
         &mut self,
         scene: &Scene,
         instance_buffer: &mut InstanceBuffer,
-        drawable: &metal::MetalDrawableRef,
+        target: &metal::TextureRef,
         viewport_size: Size<DevicePixels>,
+        scissor_rect: Option<metal::MTLScissorRect>,
     ) -> Result<metal::CommandBuffer> {
+        self.prepare_custom_shaders(scene);
         let command_queue = self.command_queue.clone();
         let command_buffer = command_queue.new_command_buffer();
         let mut instance_offset = 0;
-
-        let Some(path_tiles) = self.rasterize_paths(
-            scene.paths(),
+        self.offscreen_textures_used = 0;
+        let alpha = if self.layer.is_opaque() { 1. } else { 0. };
+        self.encode_scene(
+            scene,
             instance_buffer,
             &mut instance_offset,
             command_buffer,
-        ) else {
+            target,
+            viewport_size,
+            scissor_rect,
+            metal::MTLClearColor::new(0., 0., 0., alpha),
+        )?;
+        // Textures of offscreen layers that are no longer painted are released.
+        self.offscreen_textures
+            .truncate(self.offscreen_textures_used);
+
+        instance_buffer.metal_buffer.did_modify_range(NSRange {
+            location: 0,
+            length: instance_offset as NSUInteger,
+        });
+        Ok(command_buffer.to_owned())
+    }
+
+    /// Encodes the commands that draw a scene into the target, after drawing each of its
+    /// offscreen layers into a texture of their own.
+    #[allow(clippy::too_many_arguments)]
+    fn encode_scene(
+        &mut self,
+        scene: &Scene,
+        instance_buffer: &mut InstanceBuffer,
+        instance_offset: &mut usize,
+        command_buffer: &metal::CommandBufferRef,
+        target: &metal::TextureRef,
+        viewport_size: Size<DevicePixels>,
+        scissor_rect: Option<metal::MTLScissorRect>,
+        clear_color: metal::MTLClearColor,
+    ) -> Result<()> {
+        let mut layer_textures = Vec::with_capacity(scene.offscreen_layers.len());
+        for layer in &scene.offscreen_layers {
+            let texture = self.offscreen_texture(viewport_size);
+            self.encode_scene(
+                &layer.scene,
+                instance_buffer,
+                instance_offset,
+                command_buffer,
+                &texture,
+                viewport_size,
+                None,
+                metal::MTLClearColor::new(0., 0., 0., 0.),
+            )?;
+            layer_textures.push(texture);
+        }
+
+        let damage = scissor_rect.map(|scissor_rect| Bounds {
+            origin: point(
+                ScaledPixels(scissor_rect.x as f32),
+                ScaledPixels(scissor_rect.y as f32),
+            ),
+            size: size(
+                ScaledPixels(scissor_rect.width as f32),
+                ScaledPixels(scissor_rect.height as f32),
+            ),
+        });
+
+        let paths = cull(scene.paths(), damage, |path| {
+            path.bounds.intersect(&path.content_mask.bounds)
+        });
+        let Some(path_tiles) =
+            self.rasterize_paths(&paths, instance_buffer, instance_offset, command_buffer)
+        else {
             return Err(anyhow!("failed to rasterize {} paths", scene.paths().len()));
         };
 
@@ -388,11 +721,14 @@ impl MetalRenderer {
             .object_at(0)
             .unwrap();
 
-        color_attachment.set_texture(Some(drawable.texture()));
-        color_attachment.set_load_action(metal::MTLLoadAction::Clear);
+        color_attachment.set_texture(Some(target));
+        color_attachment.set_load_action(if scissor_rect.is_some() {
+            metal::MTLLoadAction::Load
+        } else {
+            metal::MTLLoadAction::Clear
+        });
         color_attachment.set_store_action(metal::MTLStoreAction::Store);
-        let alpha = if self.layer.is_opaque() { 1. } else { 0. };
-        color_attachment.set_clear_color(metal::MTLClearColor::new(0., 0., 0., alpha));
+        color_attachment.set_clear_color(clear_color);
         let command_encoder = command_buffer.new_render_command_encoder(render_pass_descriptor);
 
         command_encoder.set_viewport(metal::MTLViewport {
@@ -404,34 +740,76 @@ impl MetalRenderer {
             zfar: 1.0,
         });
 
+        // Only the primitives that paint inside the damaged region are drawn again, and only that
+        // region is rasterized. The rest of the back buffer keeps the previous frame's pixels.
+        // The damaged region is cleared first by drawing a quad of the clear color over it
+        // without blending.
+        if let (Some(scissor_rect), Some(bounds)) = (scissor_rect, damage) {
+            command_encoder.set_scissor_rect(scissor_rect);
+            let clear_color = if self.layer.is_opaque() {
+                crate::black()
+            } else {
+                crate::transparent_black()
+            };
+            let clear_quad = Quad {
+                bounds,
+                content_mask: ContentMask { bounds },
+                background: clear_color.into(),
+                ..Default::default()
+            };
+            if !self.draw_quads(
+                &[clear_quad],
+                &self.clear_pipeline_state,
+                instance_buffer,
+                instance_offset,
+                viewport_size,
+                command_encoder,
+            ) {
+                command_encoder.end_encoding();
+                return Err(anyhow!("scene too large to clear the damaged region"));
+            }
+        }
+
         for batch in scene.batches() {
             let ok = match batch {
                 PrimitiveBatch::Shadows(shadows) => self.draw_shadows(
-                    shadows,
+                    &cull(shadows, damage, |shadow| {
+                        shadow
+                            .bounds
+                            .dilate(ScaledPixels(shadow.blur_radius.0 * 3.))
+                            .intersect(&shadow.content_mask.bounds)
+                    }),
                     instance_buffer,
-                    &mut instance_offset,
+                    instance_offset,
                     viewport_size,
                     command_encoder,
                 ),
                 PrimitiveBatch::Quads(quads) => self.draw_quads(
-                    quads,
+                    &cull(quads, damage, |quad| {
+                        quad.bounds.intersect(&quad.content_mask.bounds)
+                    }),
+                    &self.quads_pipeline_state,
                     instance_buffer,
-                    &mut instance_offset,
+                    instance_offset,
                     viewport_size,
                     command_encoder,
                 ),
                 PrimitiveBatch::Paths(paths) => self.draw_paths(
-                    paths,
+                    &cull(paths, damage, |path| {
+                        path.bounds.intersect(&path.content_mask.bounds)
+                    }),
                     &path_tiles,
                     instance_buffer,
-                    &mut instance_offset,
+                    instance_offset,
                     viewport_size,
                     command_encoder,
                 ),
                 PrimitiveBatch::Underlines(underlines) => self.draw_underlines(
-                    underlines,
+                    &cull(underlines, damage, |underline| {
+                        underline.bounds.intersect(&underline.content_mask.bounds)
+                    }),
                     instance_buffer,
-                    &mut instance_offset,
+                    instance_offset,
                     viewport_size,
                     command_encoder,
                 ),
@@ -440,9 +818,11 @@ impl MetalRenderer {
                     sprites,
                 } => self.draw_monochrome_sprites(
                     texture_id,
-                    sprites,
+                    &cull(sprites, damage, |sprite| {
+                        sprite.bounds.intersect(&sprite.content_mask.bounds)
+                    }),
                     instance_buffer,
-                    &mut instance_offset,
+                    instance_offset,
                     viewport_size,
                     command_encoder,
                 ),
@@ -451,16 +831,43 @@ impl MetalRenderer {
                     sprites,
                 } => self.draw_polychrome_sprites(
                     texture_id,
-                    sprites,
+                    &cull(sprites, damage, |sprite| {
+                        sprite.bounds.intersect(&sprite.content_mask.bounds)
+                    }),
+                    instance_buffer,
+                    instance_offset,
+                    viewport_size,
+                    command_encoder,
+                ),
+                PrimitiveBatch::ShaderQuads { shader_id, quads } => {
+                    match self.custom_shader_pipeline_states.get(&shader_id) {
+                        Some(Some(pipeline_state)) => self.draw_quads(
+                            &cull(quads, damage, |quad| {
+                                quad.bounds.intersect(&quad.content_mask.bounds)
+                            }),
+                            pipeline_state,
+                            instance_buffer,
+                            instance_offset,
+                            viewport_size,
+                            command_encoder,
+                        ),
+                        _ => true,
+                    }
+                }
+                PrimitiveBatch::OffscreenLayer { index, sprite } => self.draw_layer_sprites(
+                    &cull(slice::from_ref(sprite), damage, LayerSprite::painted_bounds),
+                    &layer_textures[index],
                     instance_buffer,
-                    &mut instance_offset,
+                    instance_offset,
                     viewport_size,
                     command_encoder,
                 ),
                 PrimitiveBatch::Surfaces(surfaces) => self.draw_surfaces(
-                    surfaces,
+                    &cull(surfaces, damage, |surface| {
+                        surface.bounds.intersect(&surface.content_mask.bounds)
+                    }),
                     instance_buffer,
-                    &mut instance_offset,
+                    instance_offset,
                     viewport_size,
                     command_encoder,
                 ),
@@ -468,7 +875,7 @@ impl MetalRenderer {
 
             if !ok {
                 command_encoder.end_encoding();
-                return Err(anyhow!("scene too large: {} paths, {} shadows, {} quads, {} underlines, {} mono, {} poly, {} surfaces",
+                return Err(anyhow!("scene too large: {} paths, {} shadows, {} quads, {} underlines, {} mono, {} poly, {} surfaces, {} shader quads, {} offscreen layers",
                     scene.paths.len(),
                     scene.shadows.len(),
                     scene.quads.len(),
@@ -476,17 +883,43 @@ impl MetalRenderer {
                     scene.monochrome_sprites.len(),
                     scene.polychrome_sprites.len(),
                     scene.surfaces.len(),
+                    scene.shader_quads.len(),
+                    scene.offscreen_layers.len(),
                 ));
             }
         }
 
         command_encoder.end_encoding();
+        Ok(())
+    }
 
-        instance_buffer.metal_buffer.did_modify_range(NSRange {
-            location: 0,
-            length: instance_offset as NSUInteger,
-        });
-        Ok(command_buffer.to_owned())
+    /// Returns a texture the size of the viewport for an offscreen layer to be drawn into,
+    /// reusing one from a previous frame if there is one.
+    fn offscreen_texture(&mut self, viewport_size: Size<DevicePixels>) -> metal::Texture {
+        let width = i32::from(viewport_size.width).max(1) as NSUInteger;
+        let height = i32::from(viewport_size.height).max(1) as NSUInteger;
+        let index = self.offscreen_textures_used;
+        self.offscreen_textures_used += 1;
+        if let Some(texture) = self.offscreen_textures.get(index) {
+            if texture.width() == width && texture.height() == height {
+                return texture.clone();
+            }
+        }
+
+        let texture_descriptor = metal::TextureDescriptor::new();
+        texture_descriptor.set_width(width);
+        texture_descriptor.set_height(height);
+        texture_descriptor.set_pixel_format(self.pixel_format);
+        texture_descriptor.set_storage_mode(metal::MTLStorageMode::Private);
+        texture_descriptor
+            .set_usage(metal::MTLTextureUsage::RenderTarget | metal::MTLTextureUsage::ShaderRead);
+        let texture = self.device.new_texture(&texture_descriptor);
+        if index < self.offscreen_textures.len() {
+            self.offscreen_textures[index] = texture.clone();
+        } else {
+            self.offscreen_textures.push(texture.clone());
+        }
+        texture
     }
 
     fn rasterize_paths(
@@ -652,9 +1085,12 @@ impl MetalRenderer {
         true
     }
 
-    fn draw_quads(
+    /// Draws quads with the given pipeline. Shader quads are drawn with this too, since their
+    /// pipelines take the same buffers.
+    fn draw_quads<Q>(
         &self,
-        quads: &[Quad],
+        quads: &[Q],
+        pipeline_state: &metal::RenderPipelineStateRef,
         instance_buffer: &mut InstanceBuffer,
         instance_offset: &mut usize,
         viewport_size: Size<DevicePixels>,
@@ -665,7 +1101,7 @@ impl MetalRenderer {
         }
         align_offset(instance_offset);
 
-        command_encoder.set_render_pipeline_state(&self.quads_pipeline_state);
+        command_encoder.set_render_pipeline_state(pipeline_state);
         command_encoder.set_vertex_buffer(
             QuadInputIndex::Vertices as u64,
             Some(&self.unit_vertices),
@@ -1004,6 +1440,75 @@ impl MetalRenderer {
             *instance_offset as u64,
         );
         command_encoder.set_fragment_texture(SpriteInputIndex::AtlasTexture as u64, Some(&texture));
+        command_encoder.set_fragment_bytes(
+            SpriteInputIndex::HdrHeadroom as u64,
+            mem::size_of_val(&self.hdr_headroom) as u64,
+            &self.hdr_headroom as *const f32 as *const _,
+        );
+
+        let sprite_bytes_len = mem::size_of_val(sprites);
+        let buffer_contents =
+            unsafe { (instance_buffer.metal_buffer.contents() as *mut u8).add(*instance_offset) };
+
+        let next_offset = *instance_offset + sprite_bytes_len;
+        if next_offset > instance_buffer.size {
+            return false;
+        }
+
+        unsafe {
+            ptr::copy_nonoverlapping(
+                sprites.as_ptr() as *const u8,
+                buffer_contents,
+                sprite_bytes_len,
+            );
+        }
+
+        command_encoder.draw_primitives_instanced(
+            metal::MTLPrimitiveType::Triangle,
+            0,
+            6,
+            sprites.len() as u64,
+        );
+        *instance_offset = next_offset;
+        true
+    }
+
+    fn draw_layer_sprites(
+        &self,
+        sprites: &[LayerSprite],
+        texture: &metal::TextureRef,
+        instance_buffer: &mut InstanceBuffer,
+        instance_offset: &mut usize,
+        viewport_size: Size<DevicePixels>,
+        command_encoder: &metal::RenderCommandEncoderRef,
+    ) -> bool {
+        if sprites.is_empty() {
+            return true;
+        }
+        align_offset(instance_offset);
+
+        command_encoder.set_render_pipeline_state(&self.layer_sprites_pipeline_state);
+        command_encoder.set_vertex_buffer(
+            SpriteInputIndex::Vertices as u64,
+            Some(&self.unit_vertices),
+            0,
+        );
+        command_encoder.set_vertex_buffer(
+            SpriteInputIndex::Sprites as u64,
+            Some(&instance_buffer.metal_buffer),
+            *instance_offset as u64,
+        );
+        command_encoder.set_vertex_bytes(
+            SpriteInputIndex::ViewportSize as u64,
+            mem::size_of_val(&viewport_size) as u64,
+            &viewport_size as *const Size<DevicePixels> as *const _,
+        );
+        command_encoder.set_fragment_buffer(
+            SpriteInputIndex::Sprites as u64,
+            Some(&instance_buffer.metal_buffer),
+            *instance_offset as u64,
+        );
+        command_encoder.set_fragment_texture(SpriteInputIndex::AtlasTexture as u64, Some(texture));
 
         let sprite_bytes_len = mem::size_of_val(sprites);
         let buffer_contents =
@@ -1040,7 +1545,6 @@ impl MetalRenderer {
         viewport_size: Size<DevicePixels>,
         command_encoder: &metal::RenderCommandEncoderRef,
     ) -> bool {
-        command_encoder.set_render_pipeline_state(&self.surfaces_pipeline_state);
         command_encoder.set_vertex_buffer(
             SurfaceInputIndex::Vertices as u64,
             Some(&self.unit_vertices),
@@ -1051,6 +1555,11 @@ impl MetalRenderer {
             mem::size_of_val(&viewport_size) as u64,
             &viewport_size as *const Size<DevicePixels> as *const _,
         );
+        command_encoder.set_fragment_bytes(
+            SurfaceInputIndex::HdrHeadroom as u64,
+            mem::size_of_val(&self.hdr_headroom) as u64,
+            &self.hdr_headroom as *const f32 as *const _,
+        );
 
         for surface in surfaces {
             let texture_size = size(
@@ -1058,34 +1567,68 @@ impl MetalRenderer {
                 DevicePixels::from(surface.image_buffer.height() as i32),
             );
 
-            assert_eq!(
-                surface.image_buffer.pixel_format_type(),
-                media::core_video::kCVPixelFormatType_420YpCbCr8BiPlanarFullRange
-            );
-
-            let y_texture = unsafe {
-                self.core_video_texture_cache
-                    .create_texture_from_image(
-                        surface.image_buffer.as_concrete_TypeRef(),
-                        ptr::null(),
-                        MTLPixelFormat::R8Unorm,
-                        surface.image_buffer.plane_width(0),
-                        surface.image_buffer.plane_height(0),
-                        0,
-                    )
-                    .unwrap()
-            };
-            let cb_cr_texture = unsafe {
-                self.core_video_texture_cache
-                    .create_texture_from_image(
-                        surface.image_buffer.as_concrete_TypeRef(),
-                        ptr::null(),
-                        MTLPixelFormat::RG8Unorm,
-                        surface.image_buffer.plane_width(1),
-                        surface.image_buffer.plane_height(1),
-                        1,
-                    )
-                    .unwrap()
+            // Keep the textures alive until the surface has been drawn.
+            let _textures = match surface.image_buffer.pixel_format_type() {
+                media::core_video::kCVPixelFormatType_420YpCbCr8BiPlanarFullRange => {
+                    let y_texture = unsafe {
+                        self.core_video_texture_cache
+                            .create_texture_from_image(
+                                surface.image_buffer.as_concrete_TypeRef(),
+                                ptr::null(),
+                                MTLPixelFormat::R8Unorm,
+                                surface.image_buffer.plane_width(0),
+                                surface.image_buffer.plane_height(0),
+                                0,
+                            )
+                            .unwrap()
+                    };
+                    let cb_cr_texture = unsafe {
+                        self.core_video_texture_cache
+                            .create_texture_from_image(
+                                surface.image_buffer.as_concrete_TypeRef(),
+                                ptr::null(),
+                                MTLPixelFormat::RG8Unorm,
+                                surface.image_buffer.plane_width(1),
+                                surface.image_buffer.plane_height(1),
+                                1,
+                            )
+                            .unwrap()
+                    };
+                    command_encoder.set_render_pipeline_state(&self.surfaces_pipeline_state);
+                    command_encoder.set_fragment_texture(
+                        SurfaceInputIndex::YTexture as u64,
+                        Some(y_texture.as_texture_ref()),
+                    );
+                    command_encoder.set_fragment_texture(
+                        SurfaceInputIndex::CbCrTexture as u64,
+                        Some(cb_cr_texture.as_texture_ref()),
+                    );
+                    vec![y_texture, cb_cr_texture]
+                }
+                media::core_video::kCVPixelFormatType_32BGRA => {
+                    let rgba_texture = unsafe {
+                        self.core_video_texture_cache
+                            .create_texture_from_image(
+                                surface.image_buffer.as_concrete_TypeRef(),
+                                ptr::null(),
+                                MTLPixelFormat::BGRA8Unorm,
+                                surface.image_buffer.width(),
+                                surface.image_buffer.height(),
+                                0,
+                            )
+                            .unwrap()
+                    };
+                    command_encoder.set_render_pipeline_state(&self.bgra_surfaces_pipeline_state);
+                    command_encoder.set_fragment_texture(
+                        SurfaceInputIndex::RgbaTexture as u64,
+                        Some(rgba_texture.as_texture_ref()),
+                    );
+                    vec![rgba_texture]
+                }
+                pixel_format => {
+                    log::error!("unsupported surface pixel format {pixel_format:#x}");
+                    continue;
+                }
             };
 
             align_offset(instance_offset);
@@ -1104,15 +1647,6 @@ impl MetalRenderer {
                 mem::size_of_val(&texture_size) as u64,
                 &texture_size as *const Size<DevicePixels> as *const _,
             );
-            command_encoder.set_fragment_texture(
-                SurfaceInputIndex::YTexture as u64,
-                Some(y_texture.as_texture_ref()),
-            );
-            command_encoder.set_fragment_texture(
-                SurfaceInputIndex::CbCrTexture as u64,
-                Some(cb_cr_texture.as_texture_ref()),
-            );
-
             unsafe {
                 let buffer_contents = (instance_buffer.metal_buffer.contents() as *mut u8)
                     .add(*instance_offset)
@@ -1122,6 +1656,7 @@ impl MetalRenderer {
                     SurfaceBounds {
                         bounds: surface.bounds,
                         content_mask: surface.content_mask.clone(),
+                        brightness: surface.brightness,
                     },
                 );
             }
@@ -1133,6 +1668,50 @@ impl MetalRenderer {
     }
 }
 
+/// The region of the viewport covering `damage`, in whole pixels, or `None` if they don't
+/// overlap.
+fn scissor_rect(
+    damage: Bounds<ScaledPixels>,
+    viewport_size: Size<DevicePixels>,
+) -> Option<metal::MTLScissorRect> {
+    let left = damage.left().0.floor().max(0.);
+    let top = damage.top().0.floor().max(0.);
+    let right = damage
+        .right()
+        .0
+        .ceil()
+        .min(i32::from(viewport_size.width) as f32);
+    let bottom = damage
+        .bottom()
+        .0
+        .ceil()
+        .min(i32::from(viewport_size.height) as f32);
+    (right > left && bottom > top).then(|| metal::MTLScissorRect {
+        x: left as NSUInteger,
+        y: top as NSUInteger,
+        width: (right - left) as NSUInteger,
+        height: (bottom - top) as NSUInteger,
+    })
+}
+
+/// The primitives whose painted bounds intersect `damage`. They're borrowed as they are when the
+/// whole frame is drawn, or when every primitive intersects the damage.
+fn cull<P: Clone>(
+    primitives: &[P],
+    damage: Option<Bounds<ScaledPixels>>,
+    painted_bounds: impl Fn(&P) -> Bounds<ScaledPixels>,
+) -> Cow<[P]> {
+    let Some(damage) = damage else {
+        return Cow::Borrowed(primitives);
+    };
+    let is_visible = |primitive: &&P| painted_bounds(primitive).intersects(&damage);
+    if primitives.iter().all(|primitive| is_visible(&primitive)) {
+        Cow::Borrowed(primitives)
+    } else {
+        Cow::Owned(primitives.iter().filter(is_visible).cloned().collect())
+    }
+}
+
 fn build_pipeline_state(
     device: &metal::DeviceRef,
     library: &metal::LibraryRef,
@@ -1167,6 +1746,35 @@ fn build_pipeline_state(
         .expect("could not create render pipeline state")
 }
 
+/// Builds a pipeline that replaces what's in the target rather than blending over it.
+fn build_clear_pipeline_state(
+    device: &metal::DeviceRef,
+    library: &metal::LibraryRef,
+    label: &str,
+    vertex_fn_name: &str,
+    fragment_fn_name: &str,
+    pixel_format: metal::MTLPixelFormat,
+) -> metal::RenderPipelineState {
+    let vertex_fn = library
+        .get_function(vertex_fn_name, None)
+        .expect("error locating vertex function");
+    let fragment_fn = library
+        .get_function(fragment_fn_name, None)
+        .expect("error locating fragment function");
+
+    let descriptor = metal::RenderPipelineDescriptor::new();
+    descriptor.set_label(label);
+    descriptor.set_vertex_function(Some(vertex_fn.as_ref()));
+    descriptor.set_fragment_function(Some(fragment_fn.as_ref()));
+    let color_attachment = descriptor.color_attachments().object_at(0).unwrap();
+    color_attachment.set_pixel_format(pixel_format);
+    color_attachment.set_blending_enabled(false);
+
+    device
+        .new_render_pipeline_state(&descriptor)
+        .expect("could not create render pipeline state")
+}
+
 fn build_path_rasterization_pipeline_state(
     device: &metal::DeviceRef,
     library: &metal::LibraryRef,
@@ -1206,6 +1814,22 @@ fn build_path_rasterization_pipeline_state(
         .expect("could not create render pipeline state")
 }
 
+/// Converts a channel of a half float texture to 8 bits, clamping it to the SDR range.
+fn half_to_unorm8(bits: u16) -> u8 {
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32 / 1024.;
+    let value = match exponent {
+        0 => mantissa * 2f32.powi(-14),
+        0x1f => f32::INFINITY,
+        _ => (1. + mantissa) * 2f32.powi(exponent - 15),
+    };
+    if bits & 0x8000 != 0 {
+        0
+    } else {
+        (value.min(1.) * 255.).round() as u8
+    }
+}
+
 // Align to multiples of 256 make Metal happy.
 fn align_offset(offset: &mut usize) {
     *offset = ((*offset + 255) / 256) * 256;
@@ -1239,6 +1863,7 @@ enum SpriteInputIndex {
     ViewportSize = 2,
     AtlasTextureSize = 3,
     AtlasTexture = 4,
+    HdrHeadroom = 5,
 }
 
 #[repr(C)]
@@ -1249,6 +1874,8 @@ enum SurfaceInputIndex {
     TextureSize = 3,
     YTexture = 4,
     CbCrTexture = 5,
+    RgbaTexture = 6,
+    HdrHeadroom = 7,
 }
 
 #[repr(C)]
@@ -1270,4 +1897,5 @@ pub struct PathSprite {
 pub struct SurfaceBounds {
     pub bounds: Bounds<ScaledPixels>,
     pub content_mask: ContentMask<ScaledPixels>,
+    pub brightness: f32,
 }
@@ -1,13 +1,15 @@
 use super::{
     attributed_string::{NSAttributedString, NSMutableAttributedString},
-    events::key_to_native,
+    events::{key_to_native, layout_keystroke, physical_keystroke},
     renderer, screen_capture, BoolExt,
 };
 use crate::{
-    hash, Action, AnyWindowHandle, BackgroundExecutor, ClipboardEntry, ClipboardItem,
-    ClipboardString, CursorStyle, ForegroundExecutor, Image, ImageFormat, Keymap, MacDispatcher,
-    MacDisplay, MacWindow, Menu, MenuItem, PathPromptOptions, Platform, PlatformDisplay,
-    PlatformTextSystem, PlatformWindow, Result, ScreenCaptureSource, SemanticVersion, Task,
+    hash, AccessibilityPreferences, Action, AnnouncementPriority, AnyWindowHandle,
+    BackgroundExecutor, ClipboardEntry, ClipboardItem, ClipboardString, CursorStyle,
+    ForegroundExecutor, Image, ImageFormat, Keymap, Keystroke, MacDispatcher, MacDisplay,
+    MacWindow, Menu, MenuItem, NewPathPromptOptions, NotificationResponse, PathPromptFilter,
+    PathPromptOptions, Platform, PlatformDisplay, PlatformTextSystem, PlatformWindow, PowerState,
+    Result, Rgba, ScreenCaptureSource, SemanticVersion, ShareItem, StatusItem, SystemEvent, Task,
     WindowAppearance, WindowParams,
 };
 use anyhow::{anyhow, Context as _};
@@ -21,20 +23,21 @@ use cocoa::{
     },
     base::{id, nil, selector, BOOL, NO, YES},
     foundation::{
-        NSArray, NSAutoreleasePool, NSBundle, NSData, NSInteger, NSProcessInfo, NSRange, NSString,
-        NSUInteger, NSURL,
+        NSArray, NSAutoreleasePool, NSBundle, NSData, NSInteger, NSPoint, NSProcessInfo, NSRange,
+        NSRect, NSSize, NSString, NSUInteger, NSURL,
     },
 };
+use collections::HashMap;
 use core_foundation::{
     base::{CFRelease, CFType, CFTypeRef, OSStatus, TCFType},
     boolean::CFBoolean,
     data::CFData,
     dictionary::{CFDictionary, CFDictionaryRef, CFMutableDictionary},
-    runloop::CFRunLoopRun,
+    runloop::{kCFRunLoopDefaultMode, CFRunLoopAddSource, CFRunLoopGetMain, CFRunLoopRun},
     string::{CFString, CFStringRef},
 };
 use ctor::ctor;
-use futures::channel::oneshot;
+use futures::channel::{mpsc, oneshot};
 use objc::{
     class,
     declare::ClassDecl,
@@ -62,6 +65,55 @@ use util::ResultExt;
 #[allow(non_upper_case_globals)]
 const NSUTF8StringEncoding: NSUInteger = 4;
 
+#[allow(non_upper_case_globals)]
+const NSControlStateValueOn: NSInteger = 1;
+
+#[allow(non_upper_case_globals)]
+const NSVariableStatusItemLength: f64 = -1.;
+
+#[allow(non_upper_case_globals)]
+const UNAuthorizationOptionSound: NSUInteger = 1 << 1;
+
+#[allow(non_upper_case_globals)]
+const UNAuthorizationOptionAlert: NSUInteger = 1 << 2;
+
+#[allow(non_upper_case_globals)]
+const UNNotificationActionOptionForeground: NSUInteger = 1 << 2;
+
+#[allow(non_upper_case_globals)]
+const UNNotificationCategoryOptionCustomDismissAction: NSUInteger = 1 << 0;
+
+#[allow(non_upper_case_globals)]
+const UNNotificationPresentationOptionList: NSUInteger = 1 << 3;
+
+#[allow(non_upper_case_globals)]
+const UNNotificationPresentationOptionBanner: NSUInteger = 1 << 4;
+
+#[allow(non_upper_case_globals)]
+const NSProgressIndicatorStyleBar: NSUInteger = 0;
+
+#[allow(non_upper_case_globals)]
+const NSMinYEdge: NSUInteger = 1;
+
+#[allow(non_upper_case_globals)]
+const NSAccessibilityPriorityMedium: NSInteger = 50;
+
+#[allow(non_upper_case_globals)]
+const NSAccessibilityPriorityHigh: NSInteger = 90;
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+    fn NSAccessibilityPostNotificationWithUserInfo(element: id, notification: id, user_info: id);
+}
+
+#[link(name = "UserNotifications", kind = "framework")]
+extern "C" {
+    static UNNotificationDefaultActionIdentifier: id;
+    static UNNotificationDismissActionIdentifier: id;
+}
+
+const STATUS_ITEM_ICON_HEIGHT: f64 = 18.;
+
 const MAC_PLATFORM_IVAR: &str = "platform";
 static mut APP_CLASS: *const Class = ptr::null();
 static mut APP_DELEGATE_CLASS: *const Class = ptr::null();
@@ -85,6 +137,10 @@ unsafe fn build_classes() {
             sel!(applicationShouldHandleReopen:hasVisibleWindows:),
             should_handle_reopen as extern "C" fn(&mut Object, Sel, id, bool),
         );
+        decl.add_method(
+            sel!(applicationShouldTerminate:),
+            should_terminate as extern "C" fn(&mut Object, Sel, id) -> NSUInteger,
+        );
         decl.add_method(
             sel!(applicationWillTerminate:),
             will_terminate as extern "C" fn(&mut Object, Sel, id),
@@ -139,6 +195,54 @@ unsafe fn build_classes() {
             sel!(onKeyboardLayoutChange:),
             on_keyboard_layout_change as extern "C" fn(&mut Object, Sel, id),
         );
+        decl.add_method(
+            sel!(onDisplaysChanged:),
+            on_displays_changed as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(onSystemAppearanceChanged:),
+            on_system_appearance_changed as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(onAccessibilityPreferencesChanged:),
+            on_accessibility_preferences_changed as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(onWillSleep:),
+            on_will_sleep as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(onDidWake:),
+            on_did_wake as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(onSessionLocked:),
+            on_session_locked as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(onSessionUnlocked:),
+            on_session_unlocked as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(onPowerStateChanged:),
+            on_power_state_changed as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(onStatusItemClick:),
+            on_status_item_click as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(onColorPanelChange:),
+            on_color_panel_change as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(userNotificationCenter:didReceiveNotificationResponse:withCompletionHandler:),
+            did_receive_notification_response as extern "C" fn(&mut Object, Sel, id, id, id),
+        );
+        decl.add_method(
+            sel!(userNotificationCenter:willPresentNotification:withCompletionHandler:),
+            will_present_notification as extern "C" fn(&mut Object, Sel, id, id, id),
+        );
 
         decl.register()
     }
@@ -146,6 +250,12 @@ unsafe fn build_classes() {
 
 pub(crate) struct MacPlatform(Mutex<MacPlatformState>);
 
+/// The senders of notifications that the user hasn't responded to yet, keyed by
+/// the notification's identifier. The notification center reports delivery
+/// failures on a background queue, so this is shared with its callbacks.
+type NotificationResponses =
+    Arc<Mutex<HashMap<String, oneshot::Sender<Result<NotificationResponse>>>>>;
+
 pub(crate) struct MacPlatformState {
     background_executor: BackgroundExecutor,
     foreground_executor: ForegroundExecutor,
@@ -157,7 +267,13 @@ pub(crate) struct MacPlatformState {
     metadata_pasteboard_type: id,
     reopen: Option<Box<dyn FnMut()>>,
     on_keyboard_layout_change: Option<Box<dyn FnMut()>>,
+    on_displays_changed: Option<Box<dyn FnMut()>>,
+    on_system_appearance_changed: Option<Box<dyn FnMut()>>,
+    on_accessibility_preferences_changed: Option<Box<dyn FnMut()>>,
+    on_system_event: Option<Box<dyn FnMut(SystemEvent)>>,
     quit: Option<Box<dyn FnMut()>>,
+    quit_requested: Option<Box<dyn FnMut() -> bool>>,
+    quitting: bool,
     menu_command: Option<Box<dyn FnMut(&dyn Action)>>,
     validate_menu_command: Option<Box<dyn FnMut(&dyn Action) -> bool>>,
     will_open_menu: Option<Box<dyn FnMut()>>,
@@ -165,6 +281,13 @@ pub(crate) struct MacPlatformState {
     open_urls: Option<Box<dyn FnMut(Vec<String>)>>,
     finish_launching: Option<Box<dyn FnOnce()>>,
     dock_menu: Option<id>,
+    status_item: Option<id>,
+    status_item_click: Option<Box<dyn FnMut()>>,
+    notification_responses: NotificationResponses,
+    notification_categories: HashMap<Vec<String>, id>,
+    next_notification_id: usize,
+    picked_colors: Option<mpsc::UnboundedSender<Rgba>>,
+    dock_progress_indicator: Option<id>,
 }
 
 impl Default for MacPlatform {
@@ -194,6 +317,8 @@ impl MacPlatform {
             metadata_pasteboard_type: unsafe { ns_string("zed-metadata") },
             reopen: None,
             quit: None,
+            quit_requested: None,
+            quitting: false,
             menu_command: None,
             validate_menu_command: None,
             will_open_menu: None,
@@ -201,7 +326,18 @@ impl MacPlatform {
             open_urls: None,
             finish_launching: None,
             dock_menu: None,
+            status_item: None,
+            status_item_click: None,
+            notification_responses: NotificationResponses::default(),
+            notification_categories: HashMap::default(),
+            next_notification_id: 0,
+            picked_colors: None,
+            dock_progress_indicator: None,
             on_keyboard_layout_change: None,
+            on_displays_changed: None,
+            on_system_appearance_changed: None,
+            on_accessibility_preferences_changed: None,
+            on_system_event: None,
         }))
     }
 
@@ -289,6 +425,8 @@ impl MacPlatform {
                 name,
                 action,
                 os_action,
+                checked,
+                disabled,
             } => {
                 // Note that this is not the standard logic for selecting which keybinding to
                 // display. Typically the last binding takes precedence for display. However, in
@@ -384,9 +522,18 @@ impl MacPlatform {
                         .autorelease();
                 }
 
-                let tag = actions.len() as NSInteger;
-                let _: () = msg_send![item, setTag: tag];
-                actions.push(action);
+                if checked {
+                    let _: () = msg_send![item, setState: NSControlStateValueOn];
+                }
+
+                if disabled {
+                    // `validateMenuItem:` disables items whose tag has no action.
+                    let _: () = msg_send![item, setTag: -1 as NSInteger];
+                } else {
+                    let tag = actions.len() as NSInteger;
+                    let _: () = msg_send![item, setTag: tag];
+                    actions.push(action);
+                }
                 item
             }
             MenuItem::Submenu(Menu { name, items }) => {
@@ -473,6 +620,8 @@ impl Platform for MacPlatform {
 
         use super::dispatcher::{dispatch_get_main_queue, dispatch_sys::dispatch_async_f};
 
+        // The app has already decided to quit, so `applicationShouldTerminate:` must not ask again.
+        self.0.lock().quitting = true;
         unsafe {
             dispatch_async_f(dispatch_get_main_queue(), ptr::null_mut(), Some(quit));
         }
@@ -562,6 +711,12 @@ impl Platform for MacPlatform {
         screen_capture::get_sources()
     }
 
+    fn pick_screen_capture_source(
+        &self,
+    ) -> oneshot::Receiver<Result<Option<Box<dyn ScreenCaptureSource>>>> {
+        screen_capture::pick_source()
+    }
+
     fn active_window(&self) -> Option<AnyWindowHandle> {
         MacWindow::active_window()
     }
@@ -594,6 +749,18 @@ impl Platform for MacPlatform {
         }
     }
 
+    fn accent_color(&self) -> Option<Rgba> {
+        unsafe {
+            let color: id = msg_send![class!(NSColor), controlAccentColor];
+            let color_space: id = msg_send![class!(NSColorSpace), sRGBColorSpace];
+            let color: id = msg_send![color, colorUsingColorSpace: color_space];
+            if color == nil {
+                return None;
+            }
+            Some(ns_color_to_rgba(color))
+        }
+    }
+
     fn open_url(&self, url: &str) {
         unsafe {
             let url = NSURL::alloc(nil)
@@ -671,6 +838,7 @@ impl Platform for MacPlatform {
                     panel.setAllowsMultipleSelection_(options.multiple.to_objc());
                     panel.setCanCreateDirectories(true.to_objc());
                     panel.setResolvesAliases_(false.to_objc());
+                    set_allowed_file_types(panel, &options.filters);
                     let done_tx = Cell::new(Some(done_tx));
                     let block = ConcreteBlock::new(move |response: NSModalResponse| {
                         let result = if response == NSModalResponse::NSModalResponseOk {
@@ -701,16 +869,23 @@ impl Platform for MacPlatform {
         done_rx
     }
 
-    fn prompt_for_new_path(&self, directory: &Path) -> oneshot::Receiver<Result<Option<PathBuf>>> {
-        let directory = directory.to_owned();
+    fn prompt_for_new_path(
+        &self,
+        options: NewPathPromptOptions,
+    ) -> oneshot::Receiver<Result<Option<PathBuf>>> {
         let (done_tx, done_rx) = oneshot::channel();
         self.foreground_executor()
             .spawn(async move {
                 unsafe {
                     let panel = NSSavePanel::savePanel(nil);
-                    let path = ns_string(directory.to_string_lossy().as_ref());
+                    let path = ns_string(options.directory.to_string_lossy().as_ref());
                     let url = NSURL::fileURLWithPath_isDirectory_(nil, path, true.to_objc());
                     panel.setDirectoryURL(url);
+                    if let Some(name) = &options.suggested_name {
+                        let _: () = msg_send![panel, setNameFieldStringValue: ns_string(name)];
+                    }
+                    panel.setCanCreateDirectories(options.can_create_directories.to_objc());
+                    set_allowed_file_types(panel, &options.filters);
 
                     let done_tx = Cell::new(Some(done_tx));
                     let block = ConcreteBlock::new(move |response: NSModalResponse| {
@@ -768,6 +943,173 @@ impl Platform for MacPlatform {
         true
     }
 
+    fn send_notification(
+        &self,
+        title: &str,
+        body: &str,
+        actions: &[&str],
+    ) -> oneshot::Receiver<Result<NotificationResponse>> {
+        let (done_tx, done_rx) = oneshot::channel();
+        unsafe {
+            // The notification center can only be used by bundled apps, and
+            // raises an exception when accessed from anywhere else.
+            let bundle: id = msg_send![class!(NSBundle), mainBundle];
+            let bundle_identifier: id = msg_send![bundle, bundleIdentifier];
+            if bundle_identifier == nil {
+                done_tx
+                    .send(Err(anyhow!("notifications require an app bundle")))
+                    .ok();
+                return done_rx;
+            }
+
+            let app: id = msg_send![APP_CLASS, sharedApplication];
+            let center: id = msg_send![class!(UNUserNotificationCenter), currentNotificationCenter];
+            let _: () = msg_send![center, setDelegate: NSWindow::delegate(app)];
+
+            let mut state = self.0.lock();
+            let identifier = format!("gpui-notification-{}", state.next_notification_id);
+            state.next_notification_id += 1;
+            let category_identifier = state.notification_category(center, actions);
+
+            let content: id = msg_send![class!(UNMutableNotificationContent), new];
+            let _: () = msg_send![content, setTitle: ns_string(title)];
+            let _: () = msg_send![content, setBody: ns_string(body)];
+            let _: () = msg_send![content, setCategoryIdentifier: category_identifier];
+            let request: id = msg_send![
+                class!(UNNotificationRequest),
+                requestWithIdentifier: ns_string(&identifier)
+                content: content
+                trigger: nil
+            ];
+            let _: () = msg_send![content, release];
+            let _: () = msg_send![request, retain];
+
+            let responses = state.notification_responses.clone();
+            responses.lock().insert(identifier.clone(), done_tx);
+            drop(state);
+
+            // Authorization is only prompted for the first time, after which
+            // the user's choice is reported right away.
+            let block = ConcreteBlock::new(move |granted: BOOL, _: id| {
+                if granted == NO {
+                    fail_notification(
+                        &responses,
+                        &identifier,
+                        anyhow!("notifications are not allowed"),
+                    );
+                    let _: () = msg_send![request, release];
+                    return;
+                }
+
+                let responses = responses.clone();
+                let identifier = identifier.clone();
+                let block = ConcreteBlock::new(move |error: id| {
+                    if error != nil {
+                        let description: id = msg_send![error, localizedDescription];
+                        let description =
+                            CStr::from_ptr(description.UTF8String()).to_string_lossy();
+                        fail_notification(
+                            &responses,
+                            &identifier,
+                            anyhow!("failed to deliver notification: {description}"),
+                        );
+                    }
+                });
+                let block = block.copy();
+                let _: () =
+                    msg_send![center, addNotificationRequest: request withCompletionHandler: block];
+                let _: () = msg_send![request, release];
+            });
+            let block = block.copy();
+            let _: () = msg_send![
+                center,
+                requestAuthorizationWithOptions: UNAuthorizationOptionAlert | UNAuthorizationOptionSound
+                completionHandler: block
+            ];
+        }
+        done_rx
+    }
+
+    fn can_share(&self) -> bool {
+        true
+    }
+
+    fn share(&self, items: Vec<ShareItem>) {
+        // The sharing picker tracks the mouse in a nested event loop, so show it outside
+        // of the current update.
+        self.foreground_executor()
+            .spawn(async move {
+                unsafe {
+                    let app: id = msg_send![APP_CLASS, sharedApplication];
+                    let mut window: id = msg_send![app, keyWindow];
+                    if window == nil {
+                        window = msg_send![app, mainWindow];
+                    }
+                    if window == nil {
+                        log::error!("no window to show the share sheet in");
+                        return;
+                    }
+                    let view: id = msg_send![window, contentView];
+
+                    let objects: id = msg_send![class!(NSMutableArray), array];
+                    for item in items {
+                        let object: id = match item {
+                            ShareItem::Text(text) => ns_string(&text),
+                            ShareItem::Url(url) => {
+                                msg_send![class!(NSURL), URLWithString: ns_string(&url)]
+                            }
+                            ShareItem::File(path) => {
+                                let path = ns_string(&path.to_string_lossy());
+                                msg_send![class!(NSURL), fileURLWithPath: path]
+                            }
+                        };
+                        if object != nil {
+                            let _: () = msg_send![objects, addObject: object];
+                        }
+                    }
+
+                    let location: NSPoint = msg_send![window, mouseLocationOutsideOfEventStream];
+                    let location: NSPoint = msg_send![view, convertPoint: location fromView: nil];
+                    let anchor = NSRect::new(location, NSSize::new(1., 1.));
+
+                    let picker: id = msg_send![class!(NSSharingServicePicker), alloc];
+                    let picker: id = msg_send![picker, initWithItems: objects];
+                    let _: () = msg_send![
+                        picker,
+                        showRelativeToRect: anchor
+                        ofView: view
+                        preferredEdge: NSMinYEdge
+                    ];
+                    let _: () = msg_send![picker, autorelease];
+                }
+            })
+            .detach();
+    }
+
+    fn pick_color(&self, initial: Rgba) -> Option<mpsc::UnboundedReceiver<Rgba>> {
+        let (tx, rx) = mpsc::unbounded();
+        unsafe {
+            let app: id = msg_send![APP_CLASS, sharedApplication];
+            let panel: id = msg_send![class!(NSColorPanel), sharedColorPanel];
+            let color: id = msg_send![
+                class!(NSColor),
+                colorWithSRGBRed: initial.r as f64
+                green: initial.g as f64
+                blue: initial.b as f64
+                alpha: initial.a as f64
+            ];
+            // Replace the previous picker's stream before updating the color,
+            // so that the initial color isn't reported to it.
+            self.0.lock().picked_colors = Some(tx);
+            let _: () = msg_send![panel, setShowsAlpha: YES];
+            let _: () = msg_send![panel, setColor: color];
+            let _: () = msg_send![panel, setTarget: NSWindow::delegate(app)];
+            let _: () = msg_send![panel, setAction: sel!(onColorPanelChange:)];
+            let _: () = msg_send![panel, orderFront: nil];
+        }
+        Some(rx)
+    }
+
     fn reveal_path(&self, path: &Path) {
         unsafe {
             let path = path.to_path_buf();
@@ -807,6 +1149,10 @@ impl Platform for MacPlatform {
         self.0.lock().quit = Some(callback);
     }
 
+    fn on_quit_requested(&self, callback: Box<dyn FnMut() -> bool>) {
+        self.0.lock().quit_requested = Some(callback);
+    }
+
     fn on_reopen(&self, callback: Box<dyn FnMut()>) {
         self.0.lock().reopen = Some(callback);
     }
@@ -815,6 +1161,70 @@ impl Platform for MacPlatform {
         self.0.lock().on_keyboard_layout_change = Some(callback);
     }
 
+    fn on_displays_changed(&self, callback: Box<dyn FnMut()>) {
+        self.0.lock().on_displays_changed = Some(callback);
+    }
+
+    fn on_system_appearance_changed(&self, callback: Box<dyn FnMut()>) {
+        self.0.lock().on_system_appearance_changed = Some(callback);
+    }
+
+    fn accessibility_preferences(&self) -> AccessibilityPreferences {
+        unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let reduce_motion: BOOL = msg_send![workspace, accessibilityDisplayShouldReduceMotion];
+            let increase_contrast: BOOL =
+                msg_send![workspace, accessibilityDisplayShouldIncreaseContrast];
+            let reduce_transparency: BOOL =
+                msg_send![workspace, accessibilityDisplayShouldReduceTransparency];
+            AccessibilityPreferences {
+                reduce_motion: reduce_motion == YES,
+                increase_contrast: increase_contrast == YES,
+                reduce_transparency: reduce_transparency == YES,
+            }
+        }
+    }
+
+    fn on_accessibility_preferences_changed(&self, callback: Box<dyn FnMut()>) {
+        self.0.lock().on_accessibility_preferences_changed = Some(callback);
+    }
+
+    fn on_system_event(&self, callback: Box<dyn FnMut(SystemEvent)>) {
+        self.0.lock().on_system_event = Some(callback);
+    }
+
+    fn power_state(&self) -> PowerState {
+        unsafe {
+            let process_info = NSProcessInfo::processInfo(nil);
+            // Low power mode is only available on macOS 12 and later.
+            let supports_low_power_mode: BOOL =
+                msg_send![process_info, respondsToSelector: sel!(isLowPowerModeEnabled)];
+            let low_power_mode = supports_low_power_mode == YES && {
+                let enabled: BOOL = msg_send![process_info, isLowPowerModeEnabled];
+                enabled == YES
+            };
+
+            let snapshot = power::IOPSCopyPowerSourcesInfo();
+            let on_battery = !snapshot.is_null() && {
+                let source_type = power::IOPSGetProvidingPowerSourceType(snapshot);
+                let on_battery = !source_type.is_null()
+                    && CFString::wrap_under_get_rule(source_type)
+                        == CFString::from_static_string(power::kIOPMBatteryPowerKey);
+                CFRelease(snapshot);
+                on_battery
+            };
+
+            PowerState {
+                on_battery,
+                low_power_mode,
+            }
+        }
+    }
+
+    fn on_status_item_click(&self, callback: Box<dyn FnMut()>) {
+        self.0.lock().status_item_click = Some(callback);
+    }
+
     fn on_app_menu_action(&self, callback: Box<dyn FnMut(&dyn Action)>) {
         self.0.lock().menu_command = Some(callback);
     }
@@ -843,6 +1253,14 @@ impl Platform for MacPlatform {
         }
     }
 
+    fn layout_keystroke(&self, physical: &Keystroke) -> Option<Keystroke> {
+        layout_keystroke(physical)
+    }
+
+    fn physical_keystroke(&self, keystroke: &Keystroke) -> Option<Keystroke> {
+        physical_keystroke(keystroke)
+    }
+
     fn app_path(&self) -> Result<PathBuf> {
         unsafe {
             let bundle: id = NSBundle::mainBundle();
@@ -877,6 +1295,117 @@ impl Platform for MacPlatform {
         }
     }
 
+    fn set_status_item(&self, item: Option<StatusItem>, keymap: &Keymap) {
+        unsafe {
+            let app: id = msg_send![APP_CLASS, sharedApplication];
+            let delegate = NSWindow::delegate(app);
+            let mut state = self.0.lock();
+            if let Some(old) = state.status_item.take() {
+                let status_bar: id = msg_send![class!(NSStatusBar), systemStatusBar];
+                let _: () = msg_send![status_bar, removeStatusItem: old];
+                let _: () = msg_send![old, release];
+            }
+
+            let Some(item) = item else {
+                return;
+            };
+
+            let status_bar: id = msg_send![class!(NSStatusBar), systemStatusBar];
+            let status_item: id =
+                msg_send![status_bar, statusItemWithLength: NSVariableStatusItemLength];
+            let _: () = msg_send![status_item, retain];
+            let button: id = msg_send![status_item, button];
+
+            if let Some(title) = item.title {
+                let _: () = msg_send![button, setTitle: ns_string(&title)];
+            }
+            if let Some(icon) = item.icon {
+                let data: id = msg_send![class!(NSData), dataWithBytes: icon.bytes.as_ptr()
+                    length: icon.bytes.len() as NSUInteger];
+                let image: id = msg_send![class!(NSImage), alloc];
+                let image: id = msg_send![image, initWithData: data];
+                if image != nil {
+                    // Scale the icon to the menu bar's height, keeping its aspect ratio.
+                    let size: NSSize = msg_send![image, size];
+                    if size.height > 0. {
+                        let height = STATUS_ITEM_ICON_HEIGHT;
+                        let width = size.width * height / size.height;
+                        let _: () = msg_send![image, setSize: NSSize::new(width, height)];
+                    }
+                    let _: () = msg_send![button, setImage: image];
+                    let _: () = msg_send![image, release];
+                }
+            }
+            if let Some(tooltip) = item.tooltip {
+                let _: () = msg_send![button, setToolTip: ns_string(&tooltip)];
+            }
+
+            if item.menu.is_empty() {
+                let _: () = msg_send![button, setTarget: delegate];
+                let _: () = msg_send![button, setAction: sel!(onStatusItemClick:)];
+            } else {
+                let actions = &mut state.menu_actions;
+                let menu = self.create_dock_menu(item.menu, delegate, actions, keymap);
+                let _: () = msg_send![status_item, setMenu: menu];
+                let _: () = msg_send![menu, release];
+            }
+
+            state.status_item = Some(status_item);
+        }
+    }
+
+    fn announce(&self, text: &str, priority: AnnouncementPriority) {
+        let priority: NSInteger = match priority {
+            AnnouncementPriority::Polite => NSAccessibilityPriorityMedium,
+            AnnouncementPriority::Assertive => NSAccessibilityPriorityHigh,
+        };
+        unsafe {
+            let app: id = msg_send![APP_CLASS, sharedApplication];
+            let user_info: id = msg_send![class!(NSMutableDictionary), dictionary];
+            let _: () = msg_send![user_info, setObject: ns_string(text) forKey: ns_string("AXAnnouncementKey")];
+            let priority: id = msg_send![class!(NSNumber), numberWithInteger: priority];
+            let _: () =
+                msg_send![user_info, setObject: priority forKey: ns_string("AXPriorityKey")];
+            NSAccessibilityPostNotificationWithUserInfo(
+                app,
+                ns_string("AXAnnouncementRequested"),
+                user_info,
+            );
+        }
+    }
+
+    fn set_dock_badge(&self, badge: Option<&str>) {
+        unsafe {
+            let app: id = msg_send![APP_CLASS, sharedApplication];
+            let dock_tile: id = msg_send![app, dockTile];
+            let label = badge.map_or(nil, |badge| ns_string(badge));
+            let _: () = msg_send![dock_tile, setBadgeLabel: label];
+        }
+    }
+
+    fn set_dock_progress(&self, progress: Option<f32>) {
+        unsafe {
+            let app: id = msg_send![APP_CLASS, sharedApplication];
+            let dock_tile: id = msg_send![app, dockTile];
+            let mut state = self.0.lock();
+            match progress {
+                Some(progress) => {
+                    let indicator = *state
+                        .dock_progress_indicator
+                        .get_or_insert_with(|| create_dock_progress_indicator(app, dock_tile));
+                    let _: () = msg_send![indicator, setDoubleValue: progress as f64];
+                }
+                None => {
+                    // The indicator is owned by the dock tile's content view.
+                    if state.dock_progress_indicator.take().is_some() {
+                        let _: () = msg_send![dock_tile, setContentView: nil];
+                    }
+                }
+            }
+            let _: () = msg_send![dock_tile, display];
+        }
+    }
+
     fn add_recent_document(&self, path: &Path) {
         if let Some(path_str) = path.to_str() {
             unsafe {
@@ -1297,6 +1826,62 @@ unsafe fn path_from_objc(path: id) -> PathBuf {
     PathBuf::from(path)
 }
 
+/// Replaces the dock tile's content with the application icon and a progress bar along
+/// its bottom edge, returning the progress bar.
+unsafe fn create_dock_progress_indicator(app: id, dock_tile: id) -> id {
+    let size: NSSize = msg_send![dock_tile, size];
+    let tile_frame = NSRect::new(NSPoint::new(0., 0.), size);
+
+    let content_view: id = msg_send![class!(NSImageView), alloc];
+    let content_view: id = msg_send![content_view, initWithFrame: tile_frame];
+    let icon: id = msg_send![app, applicationIconImage];
+    let _: () = msg_send![content_view, setImage: icon];
+
+    let indicator_frame = NSRect::new(
+        NSPoint::new(size.width * 0.1, size.height * 0.05),
+        NSSize::new(size.width * 0.8, size.height * 0.15),
+    );
+    let indicator: id = msg_send![class!(NSProgressIndicator), alloc];
+    let indicator: id = msg_send![indicator, initWithFrame: indicator_frame];
+    let _: () = msg_send![indicator, setStyle: NSProgressIndicatorStyleBar];
+    let _: () = msg_send![indicator, setIndeterminate: NO];
+    let _: () = msg_send![indicator, setMinValue: 0f64];
+    let _: () = msg_send![indicator, setMaxValue: 1f64];
+    let _: () = msg_send![content_view, addSubview: indicator];
+    let _: () = msg_send![indicator, release];
+
+    let _: () = msg_send![dock_tile, setContentView: content_view];
+    let _: () = msg_send![content_view, release];
+    indicator
+}
+
+/// Restricts an open or save panel to the extensions of the given filters.
+/// `NSSavePanel` has no notion of named filters, so all extensions are merged.
+unsafe fn set_allowed_file_types(panel: id, filters: &[PathPromptFilter]) {
+    if filters.is_empty() {
+        return;
+    }
+    let file_types: id = msg_send![class!(NSMutableArray), array];
+    for extension in filters.iter().flat_map(|filter| &filter.extensions) {
+        let _: () = msg_send![file_types, addObject: ns_string(extension)];
+    }
+    let _: () = msg_send![panel, setAllowedFileTypes: file_types];
+}
+
+/// Converts an `NSColor` that is already in the sRGB color space.
+unsafe fn ns_color_to_rgba(color: id) -> Rgba {
+    let r: f64 = msg_send![color, redComponent];
+    let g: f64 = msg_send![color, greenComponent];
+    let b: f64 = msg_send![color, blueComponent];
+    let a: f64 = msg_send![color, alphaComponent];
+    Rgba {
+        r: r as f32,
+        g: g as f32,
+        b: b as f32,
+        a: a as f32,
+    }
+}
+
 unsafe fn get_mac_platform(object: &mut Object) -> &MacPlatform {
     let platform_ptr: *mut c_void = *object.get_ivar(MAC_PLATFORM_IVAR);
     assert!(!platform_ptr.is_null());
@@ -1316,6 +1901,80 @@ extern "C" fn did_finish_launching(this: &mut Object, _: Sel, _: id) {
             name: name
             object: nil
         ];
+        let name = ns_string("NSApplicationDidChangeScreenParametersNotification");
+        let _: () = msg_send![notification_center, addObserver: this as id
+            selector: sel!(onDisplaysChanged:)
+            name: name
+            object: nil
+        ];
+        // Posted when the accent or highlight color changes.
+        let name = ns_string("NSSystemColorsDidChangeNotification");
+        let _: () = msg_send![notification_center, addObserver: this as id
+            selector: sel!(onSystemAppearanceChanged:)
+            name: name
+            object: nil
+        ];
+
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let workspace_notification_center: id = msg_send![workspace, notificationCenter];
+        let name = ns_string("NSWorkspaceAccessibilityDisplayOptionsDidChangeNotification");
+        let _: () = msg_send![workspace_notification_center, addObserver: this as id
+            selector: sel!(onAccessibilityPreferencesChanged:)
+            name: name
+            object: nil
+        ];
+
+        let distributed_notification_center: id =
+            msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+        let name = ns_string("AppleInterfaceThemeChangedNotification");
+        let _: () = msg_send![distributed_notification_center, addObserver: this as id
+            selector: sel!(onSystemAppearanceChanged:)
+            name: name
+            object: nil
+        ];
+        let name = ns_string("com.apple.screenIsLocked");
+        let _: () = msg_send![distributed_notification_center, addObserver: this as id
+            selector: sel!(onSessionLocked:)
+            name: name
+            object: nil
+        ];
+        let name = ns_string("com.apple.screenIsUnlocked");
+        let _: () = msg_send![distributed_notification_center, addObserver: this as id
+            selector: sel!(onSessionUnlocked:)
+            name: name
+            object: nil
+        ];
+
+        let name = ns_string("NSProcessInfoPowerStateDidChangeNotification");
+        let _: () = msg_send![notification_center, addObserver: this as id
+            selector: sel!(onPowerStateChanged:)
+            name: name
+            object: nil
+        ];
+
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let workspace_notification_center: id = msg_send![workspace, notificationCenter];
+        let name = ns_string("NSWorkspaceWillSleepNotification");
+        let _: () = msg_send![workspace_notification_center, addObserver: this as id
+            selector: sel!(onWillSleep:)
+            name: name
+            object: nil
+        ];
+        let name = ns_string("NSWorkspaceDidWakeNotification");
+        let _: () = msg_send![workspace_notification_center, addObserver: this as id
+            selector: sel!(onDidWake:)
+            name: name
+            object: nil
+        ];
+
+        // Switching between battery and AC power isn't reported through a notification.
+        let platform_ptr: *mut c_void = *this.get_ivar(MAC_PLATFORM_IVAR);
+        let power_source =
+            power::IOPSNotificationCreateRunLoopSource(on_power_source_changed, platform_ptr);
+        if !power_source.is_null() {
+            CFRunLoopAddSource(CFRunLoopGetMain(), power_source, kCFRunLoopDefaultMode);
+            CFRelease(power_source as _);
+        }
 
         let platform = get_mac_platform(this);
         let callback = platform.0.lock().finish_launching.take();
@@ -1337,6 +1996,27 @@ extern "C" fn should_handle_reopen(this: &mut Object, _: Sel, _: id, has_open_wi
     }
 }
 
+extern "C" fn should_terminate(this: &mut Object, _: Sel, _: id) -> NSUInteger {
+    const NS_TERMINATE_CANCEL: NSUInteger = 0;
+    const NS_TERMINATE_NOW: NSUInteger = 1;
+
+    let platform = unsafe { get_mac_platform(this) };
+    let mut lock = platform.0.lock();
+    if lock.quitting {
+        return NS_TERMINATE_NOW;
+    }
+    if let Some(mut callback) = lock.quit_requested.take() {
+        drop(lock);
+        let should_quit = callback();
+        platform.0.lock().quit_requested.get_or_insert(callback);
+        if !should_quit {
+            // The app quits through `Platform::quit` once it has decided to.
+            return NS_TERMINATE_CANCEL;
+        }
+    }
+    NS_TERMINATE_NOW
+}
+
 extern "C" fn will_terminate(this: &mut Object, _: Sel, _: id) {
     let platform = unsafe { get_mac_platform(this) };
     let mut lock = platform.0.lock();
@@ -1361,6 +2041,235 @@ extern "C" fn on_keyboard_layout_change(this: &mut Object, _: Sel, _: id) {
     }
 }
 
+extern "C" fn on_displays_changed(this: &mut Object, _: Sel, _: id) {
+    let platform = unsafe { get_mac_platform(this) };
+    let mut lock = platform.0.lock();
+    if let Some(mut callback) = lock.on_displays_changed.take() {
+        drop(lock);
+        callback();
+        platform
+            .0
+            .lock()
+            .on_displays_changed
+            .get_or_insert(callback);
+    }
+}
+
+extern "C" fn on_accessibility_preferences_changed(this: &mut Object, _: Sel, _: id) {
+    let platform = unsafe { get_mac_platform(this) };
+    let mut lock = platform.0.lock();
+    if let Some(mut callback) = lock.on_accessibility_preferences_changed.take() {
+        drop(lock);
+        callback();
+        platform
+            .0
+            .lock()
+            .on_accessibility_preferences_changed
+            .get_or_insert(callback);
+    }
+}
+
+extern "C" fn on_system_appearance_changed(this: &mut Object, _: Sel, _: id) {
+    let platform = unsafe { get_mac_platform(this) };
+    let mut lock = platform.0.lock();
+    if let Some(mut callback) = lock.on_system_appearance_changed.take() {
+        drop(lock);
+        callback();
+        platform
+            .0
+            .lock()
+            .on_system_appearance_changed
+            .get_or_insert(callback);
+    }
+}
+
+fn handle_system_event(platform: &MacPlatform, event: SystemEvent) {
+    let mut lock = platform.0.lock();
+    if let Some(mut callback) = lock.on_system_event.take() {
+        drop(lock);
+        callback(event);
+        platform.0.lock().on_system_event.get_or_insert(callback);
+    }
+}
+
+extern "C" fn on_will_sleep(this: &mut Object, _: Sel, _: id) {
+    handle_system_event(unsafe { get_mac_platform(this) }, SystemEvent::WillSleep);
+}
+
+extern "C" fn on_did_wake(this: &mut Object, _: Sel, _: id) {
+    handle_system_event(unsafe { get_mac_platform(this) }, SystemEvent::DidWake);
+}
+
+extern "C" fn on_session_locked(this: &mut Object, _: Sel, _: id) {
+    handle_system_event(
+        unsafe { get_mac_platform(this) },
+        SystemEvent::SessionLocked,
+    );
+}
+
+extern "C" fn on_session_unlocked(this: &mut Object, _: Sel, _: id) {
+    handle_system_event(
+        unsafe { get_mac_platform(this) },
+        SystemEvent::SessionUnlocked,
+    );
+}
+
+extern "C" fn on_power_state_changed(this: &mut Object, _: Sel, _: id) {
+    handle_system_event(
+        unsafe { get_mac_platform(this) },
+        SystemEvent::PowerStateChanged,
+    );
+}
+
+extern "C" fn on_power_source_changed(context: *mut c_void) {
+    let platform = unsafe { &*(context as *const MacPlatform) };
+    handle_system_event(platform, SystemEvent::PowerStateChanged);
+}
+
+extern "C" fn on_status_item_click(this: &mut Object, _: Sel, _: id) {
+    let platform = unsafe { get_mac_platform(this) };
+    let mut lock = platform.0.lock();
+    if let Some(mut callback) = lock.status_item_click.take() {
+        drop(lock);
+        callback();
+        platform.0.lock().status_item_click.get_or_insert(callback);
+    }
+}
+
+extern "C" fn on_color_panel_change(this: &mut Object, _: Sel, panel: id) {
+    unsafe {
+        let platform = get_mac_platform(this);
+        let mut lock = platform.0.lock();
+        let Some(picked_colors) = lock.picked_colors.as_ref() else {
+            return;
+        };
+
+        let color: id = msg_send![panel, color];
+        let color_space: id = msg_send![class!(NSColorSpace), sRGBColorSpace];
+        let color: id = msg_send![color, colorUsingColorSpace: color_space];
+        if color == nil {
+            return;
+        }
+        if picked_colors
+            .unbounded_send(ns_color_to_rgba(color))
+            .is_err()
+        {
+            lock.picked_colors = None;
+        }
+    }
+}
+
+impl MacPlatformState {
+    /// Returns the identifier of a notification category that offers the
+    /// given actions, registering a new category for unseen sets of actions.
+    unsafe fn notification_category(&mut self, center: id, actions: &[&str]) -> id {
+        let key = actions.iter().map(|action| action.to_string()).collect();
+        let category_count = self.notification_categories.len();
+        let category = *self.notification_categories.entry(key).or_insert_with(|| {
+            let category_actions: id = msg_send![class!(NSMutableArray), array];
+            for (ix, action) in actions.iter().enumerate() {
+                let action: id = msg_send![
+                    class!(UNNotificationAction),
+                    actionWithIdentifier: ns_string(&ix.to_string())
+                    title: ns_string(action)
+                    options: UNNotificationActionOptionForeground
+                ];
+                let _: () = msg_send![category_actions, addObject: action];
+            }
+            let intent_identifiers: id = msg_send![class!(NSArray), array];
+            // The custom dismiss action makes the notification center report
+            // dismissals, so that the response sender can be dropped.
+            let category: id = msg_send![
+                class!(UNNotificationCategory),
+                categoryWithIdentifier: ns_string(&format!("gpui-notification-category-{category_count}"))
+                actions: category_actions
+                intentIdentifiers: intent_identifiers
+                options: UNNotificationCategoryOptionCustomDismissAction
+            ];
+            let _: () = msg_send![category, retain];
+            category
+        });
+
+        if self.notification_categories.len() > category_count {
+            let categories: id = msg_send![class!(NSMutableSet), set];
+            for category in self.notification_categories.values() {
+                let _: () = msg_send![categories, addObject: *category];
+            }
+            let _: () = msg_send![center, setNotificationCategories: categories];
+        }
+        msg_send![category, identifier]
+    }
+}
+
+fn fail_notification(responses: &NotificationResponses, identifier: &str, error: anyhow::Error) {
+    if let Some(done_tx) = responses.lock().remove(identifier) {
+        done_tx.send(Err(error)).ok();
+    }
+}
+
+extern "C" fn did_receive_notification_response(
+    this: &mut Object,
+    _: Sel,
+    _: id,
+    response: id,
+    completion_handler: id,
+) {
+    unsafe {
+        let notification: id = msg_send![response, notification];
+        let request: id = msg_send![notification, request];
+        let identifier: id = msg_send![request, identifier];
+        let identifier = CStr::from_ptr(identifier.UTF8String())
+            .to_string_lossy()
+            .into_owned();
+
+        let action_identifier: id = msg_send![response, actionIdentifier];
+        let dismissed: BOOL =
+            msg_send![action_identifier, isEqualToString: UNNotificationDismissActionIdentifier];
+        let clicked: BOOL =
+            msg_send![action_identifier, isEqualToString: UNNotificationDefaultActionIdentifier];
+        let response = if dismissed == YES {
+            None
+        } else if clicked == YES {
+            Some(NotificationResponse::Clicked)
+        } else {
+            Some(
+                CStr::from_ptr(action_identifier.UTF8String())
+                    .to_str()
+                    .ok()
+                    .and_then(|ix| ix.parse().ok())
+                    .map_or(NotificationResponse::Clicked, NotificationResponse::Action),
+            )
+        };
+
+        // Dismissed notifications drop their sender without a response.
+        let platform = get_mac_platform(this);
+        let responses = platform.0.lock().notification_responses.clone();
+        let done_tx = responses.lock().remove(&identifier);
+        if let Some((done_tx, response)) = done_tx.zip(response) {
+            done_tx.send(Ok(response)).ok();
+        }
+
+        let completion_handler = &*(completion_handler as *const block::Block<(), ()>);
+        completion_handler.call(());
+    }
+}
+
+/// Shows notifications even while the app is active, as they would be
+/// otherwise be discarded.
+extern "C" fn will_present_notification(
+    _: &mut Object,
+    _: Sel,
+    _: id,
+    _: id,
+    completion_handler: id,
+) {
+    unsafe {
+        let completion_handler = &*(completion_handler as *const block::Block<(NSUInteger,), ()>);
+        completion_handler
+            .call((UNNotificationPresentationOptionBanner | UNNotificationPresentationOptionList,));
+    }
+}
+
 extern "C" fn open_urls(this: &mut Object, _: Sel, _: id, urls: id) {
     let urls = unsafe {
         (0..urls.count())
@@ -1492,6 +2401,24 @@ extern "C" {
     pub(super) static kTISPropertyInputSourceID: CFStringRef;
 }
 
+mod power {
+    #![allow(non_upper_case_globals)]
+    use super::*;
+    use core_foundation::runloop::CFRunLoopSourceRef;
+
+    pub const kIOPMBatteryPowerKey: &str = "Battery Power";
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        pub fn IOPSCopyPowerSourcesInfo() -> CFTypeRef;
+        pub fn IOPSGetProvidingPowerSourceType(snapshot: CFTypeRef) -> CFStringRef;
+        pub fn IOPSNotificationCreateRunLoopSource(
+            callback: extern "C" fn(context: *mut c_void),
+            context: *mut c_void,
+        ) -> CFRunLoopSourceRef;
+    }
+}
+
 mod security {
     #![allow(non_upper_case_globals)]
     use super::*;
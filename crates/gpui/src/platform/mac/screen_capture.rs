@@ -5,8 +5,8 @@ use crate::{
 use anyhow::{anyhow, Result};
 use block::ConcreteBlock;
 use cocoa::{
-    base::{id, nil, YES},
-    foundation::NSArray,
+    base::{id, nil, NO, YES},
+    foundation::{NSArray, NSRect},
 };
 use core_foundation::base::TCFType;
 use ctor::ctor;
@@ -17,14 +17,15 @@ use objc::{
     class,
     declare::ClassDecl,
     msg_send,
-    runtime::{Class, Object, Sel},
+    runtime::{Class, Object, Protocol, Sel},
     sel, sel_impl,
 };
 use std::{cell::RefCell, ffi::c_void, mem, ptr, rc::Rc};
 
 #[derive(Clone)]
 pub struct MacScreenCaptureSource {
-    sc_display: id,
+    sc_filter: id,
+    resolution: Size<Pixels>,
 }
 
 pub struct MacScreenCaptureStream {
@@ -37,18 +38,18 @@ extern "C" {}
 
 static mut DELEGATE_CLASS: *const Class = ptr::null();
 static mut OUTPUT_CLASS: *const Class = ptr::null();
+static mut PICKER_OBSERVER_CLASS: *const Class = ptr::null();
 const FRAME_CALLBACK_IVAR: &str = "frame_callback";
+const PICKER_RESULT_IVAR: &str = "picker_result";
+
+type PickerResultSender = oneshot::Sender<Result<Option<Box<dyn ScreenCaptureSource>>>>;
 
 #[allow(non_upper_case_globals)]
 const SCStreamOutputTypeScreen: NSInteger = 0;
 
 impl ScreenCaptureSource for MacScreenCaptureSource {
     fn resolution(&self) -> Result<Size<Pixels>> {
-        unsafe {
-            let width: i64 = msg_send![self.sc_display, width];
-            let height: i64 = msg_send![self.sc_display, height];
-            Ok(size(px(width as f32), px(height as f32)))
-        }
+        Ok(self.resolution)
     }
 
     fn stream(
@@ -57,13 +58,10 @@ impl ScreenCaptureSource for MacScreenCaptureSource {
     ) -> oneshot::Receiver<Result<Box<dyn ScreenCaptureStream>>> {
         unsafe {
             let stream: id = msg_send![class!(SCStream), alloc];
-            let filter: id = msg_send![class!(SCContentFilter), alloc];
             let configuration: id = msg_send![class!(SCStreamConfiguration), alloc];
             let delegate: id = msg_send![DELEGATE_CLASS, alloc];
             let output: id = msg_send![OUTPUT_CLASS, alloc];
 
-            let excluded_windows = NSArray::array(nil);
-            let filter: id = msg_send![filter, initWithDisplay:self.sc_display excludingWindows:excluded_windows];
             let configuration: id = msg_send![configuration, init];
             let delegate: id = msg_send![delegate, init];
             let output: id = msg_send![output, init];
@@ -73,7 +71,7 @@ impl ScreenCaptureSource for MacScreenCaptureSource {
                 Box::into_raw(Box::new(frame_callback)) as *mut c_void,
             );
 
-            let stream: id = msg_send![stream, initWithFilter:filter configuration:configuration delegate:delegate];
+            let stream: id = msg_send![stream, initWithFilter:self.sc_filter configuration:configuration delegate:delegate];
 
             let (mut tx, rx) = oneshot::channel();
 
@@ -114,7 +112,7 @@ impl ScreenCaptureSource for MacScreenCaptureSource {
 impl Drop for MacScreenCaptureSource {
     fn drop(&mut self) {
         unsafe {
-            let _: () = msg_send![self.sc_display, release];
+            let _: () = msg_send![self.sc_filter, release];
         }
     }
 }
@@ -159,8 +157,14 @@ pub(crate) fn get_sources() -> oneshot::Receiver<Result<Vec<Box<dyn ScreenCaptur
                 let mut result = Vec::new();
                 for i in 0..displays.count() {
                     let display = displays.objectAtIndex(i);
+                    let width: i64 = msg_send![display, width];
+                    let height: i64 = msg_send![display, height];
+                    let excluded_windows = NSArray::array(nil);
+                    let filter: id = msg_send![class!(SCContentFilter), alloc];
+                    let filter: id = msg_send![filter, initWithDisplay:display excludingWindows:excluded_windows];
                     let source = MacScreenCaptureSource {
-                        sc_display: msg_send![display, retain],
+                        sc_filter: filter,
+                        resolution: size(px(width as f32), px(height as f32)),
                     };
                     result.push(Box::new(source) as Box<dyn ScreenCaptureSource>);
                 }
@@ -182,6 +186,52 @@ pub(crate) fn get_sources() -> oneshot::Receiver<Result<Vec<Box<dyn ScreenCaptur
     }
 }
 
+/// Presents `SCContentSharingPicker`, which is only available on macOS 14 and later.
+pub(crate) fn pick_source() -> oneshot::Receiver<Result<Option<Box<dyn ScreenCaptureSource>>>> {
+    unsafe {
+        let (mut tx, rx) = oneshot::channel();
+        let Some(picker_class) = Class::get("SCContentSharingPicker") else {
+            tx.send(Err(anyhow!(
+                "the screen capture picker requires macOS 14 or later"
+            )))
+            .ok();
+            return rx;
+        };
+
+        let observer: id = msg_send![PICKER_OBSERVER_CLASS, alloc];
+        let observer: id = msg_send![observer, init];
+        observer.as_mut().unwrap().set_ivar(
+            PICKER_RESULT_IVAR,
+            Box::into_raw(Box::new(Some(tx))) as *mut c_void,
+        );
+
+        let picker: id = msg_send![picker_class, sharedPicker];
+        let _: () = msg_send![picker, addObserver: observer];
+        let _: () = msg_send![picker, setActive: YES];
+        let _: () = msg_send![picker, present];
+        rx
+    }
+}
+
+/// Delivers the picker's result, then detaches and releases the observer.
+unsafe fn finish_picking(
+    this: &Object,
+    picker: id,
+    result: Result<Option<Box<dyn ScreenCaptureSource>>>,
+) {
+    let slot = *this.get_ivar::<*mut c_void>(PICKER_RESULT_IVAR) as *mut Option<PickerResultSender>;
+    let Some(tx) = (*slot).take() else {
+        return;
+    };
+    tx.send(result).ok();
+
+    let observer = this as *const Object as id;
+    let _: () = msg_send![picker, removeObserver: observer];
+    let _: () = msg_send![picker, setActive: NO];
+    let _: () = msg_send![observer, release];
+    drop(Box::from_raw(slot));
+}
+
 #[ctor]
 unsafe fn build_classes() {
     let mut decl = ClassDecl::new("GPUIStreamDelegate", class!(NSObject)).unwrap();
@@ -207,6 +257,26 @@ unsafe fn build_classes() {
     decl.add_ivar::<*mut c_void>(FRAME_CALLBACK_IVAR);
 
     OUTPUT_CLASS = decl.register();
+
+    let mut decl = ClassDecl::new("GPUIContentSharingPickerObserver", class!(NSObject)).unwrap();
+    if let Some(protocol) = Protocol::get("SCContentSharingPickerObserver") {
+        decl.add_protocol(protocol);
+    }
+    decl.add_method(
+        sel!(contentSharingPicker:didUpdateWithFilter:forStream:),
+        content_sharing_picker_did_update as extern "C" fn(&Object, Sel, id, id, id),
+    );
+    decl.add_method(
+        sel!(contentSharingPicker:didCancelForStream:),
+        content_sharing_picker_did_cancel as extern "C" fn(&Object, Sel, id, id),
+    );
+    decl.add_method(
+        sel!(contentSharingPickerStartDidFailWithError:),
+        content_sharing_picker_did_fail as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_ivar::<*mut c_void>(PICKER_RESULT_IVAR);
+
+    PICKER_OBSERVER_CLASS = decl.register();
 }
 
 extern "C" fn output_video_effect_did_start_for_stream(_this: &Object, _: Sel, _stream: id) {}
@@ -237,3 +307,47 @@ extern "C" fn stream_did_output_sample_buffer_of_type(
         }
     }
 }
+
+extern "C" fn content_sharing_picker_did_update(
+    this: &Object,
+    _: Sel,
+    picker: id,
+    filter: id,
+    _stream: id,
+) {
+    unsafe {
+        let content_rect: NSRect = msg_send![filter, contentRect];
+        let source = MacScreenCaptureSource {
+            sc_filter: msg_send![filter, retain],
+            resolution: size(
+                px(content_rect.size.width as f32),
+                px(content_rect.size.height as f32),
+            ),
+        };
+        finish_picking(
+            this,
+            picker,
+            Ok(Some(Box::new(source) as Box<dyn ScreenCaptureSource>)),
+        );
+    }
+}
+
+extern "C" fn content_sharing_picker_did_cancel(this: &Object, _: Sel, picker: id, _stream: id) {
+    unsafe {
+        finish_picking(this, picker, Ok(None));
+    }
+}
+
+extern "C" fn content_sharing_picker_did_fail(this: &Object, _: Sel, error: id) {
+    unsafe {
+        let message: id = msg_send![error, localizedDescription];
+        let picker: id = msg_send![class!(SCContentSharingPicker), sharedPicker];
+        finish_picking(
+            this,
+            picker,
+            Err(anyhow!(
+                "failed to present screen capture picker {message:?}"
+            )),
+        );
+    }
+}
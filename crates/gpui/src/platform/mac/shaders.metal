@@ -6,6 +6,7 @@ using namespace metal;
 float4 hsla_to_rgba(Hsla hsla);
 float3 srgb_to_linear(float3 color);
 float3 linear_to_srgb(float3 color);
+float3 apply_brightness(float3 color, float brightness, float hdr_headroom);
 float4 srgb_to_oklab(float4 color);
 float4 oklab_to_srgb(float4 color);
 float4 to_device_position(float2 unit_vertex, Bounds_ScaledPixels bounds,
@@ -290,12 +291,12 @@ fragment float4 underline_fragment(UnderlineFragmentInput input [[stage_in]],
                                    constant Underline *underlines
                                    [[buffer(UnderlineInputIndex_Underlines)]]) {
   Underline underline = underlines[input.underline_id];
-  if (underline.wavy) {
+  float2 origin = float2(underline.bounds.origin.x, underline.bounds.origin.y);
+  float2 local = input.position.xy - origin;
+  switch (underline.kind) {
+  case 1: { // Wavy
     float half_thickness = underline.thickness * 0.5;
-    float2 origin =
-        float2(underline.bounds.origin.x, underline.bounds.origin.y);
-    float2 st = ((input.position.xy - origin) / underline.bounds.size.height) -
-                float2(0., 0.5);
+    float2 st = (local / underline.bounds.size.height) - float2(0., 0.5);
     float frequency = (M_PI_F * (3. * underline.thickness)) / 8.;
     float amplitude = 1. / (2. * underline.thickness);
     float sine = sin(st.x * frequency) * amplitude;
@@ -307,7 +308,28 @@ fragment float4 underline_fragment(UnderlineFragmentInput input [[stage_in]],
     float alpha = saturate(
         0.5 - max(-distance_from_bottom_border, distance_from_top_border));
     return input.color * float4(1., 1., 1., alpha);
-  } else {
+  }
+  case 2: { // Dashed
+    float period = underline.thickness * 6.;
+    float alpha = step(fract(local.x / period), 0.5);
+    return input.color * float4(1., 1., 1., alpha);
+  }
+  case 3: { // Dotted
+    float period = underline.thickness * 2.;
+    float2 center = float2((floor(local.x / period) + 0.5) * period,
+                           underline.bounds.size.height * 0.5);
+    float distance = length(local - center) - underline.thickness * 0.5;
+    float alpha = saturate(0.5 - distance);
+    return input.color * float4(1., 1., 1., alpha);
+  }
+  case 4: { // Double
+    bool inside_top = local.y < underline.thickness;
+    bool inside_bottom =
+        local.y >= underline.bounds.size.height - underline.thickness;
+    float alpha = (inside_top || inside_bottom) ? 1. : 0.;
+    return input.color * float4(1., 1., 1., alpha);
+  }
+  default:
     return input.color;
   }
 }
@@ -400,7 +422,8 @@ vertex PolychromeSpriteVertexOutput polychrome_sprite_vertex(
 fragment float4 polychrome_sprite_fragment(
     PolychromeSpriteFragmentInput input [[stage_in]],
     constant PolychromeSprite *sprites [[buffer(SpriteInputIndex_Sprites)]],
-    texture2d<float> atlas_texture [[texture(SpriteInputIndex_AtlasTexture)]]) {
+    texture2d<float> atlas_texture [[texture(SpriteInputIndex_AtlasTexture)]],
+    constant float *hdr_headroom [[buffer(SpriteInputIndex_HdrHeadroom)]]) {
   PolychromeSprite sprite = sprites[input.sprite_id];
   constexpr sampler atlas_texture_sampler(mag_filter::linear,
                                           min_filter::linear);
@@ -416,10 +439,93 @@ fragment float4 polychrome_sprite_fragment(
     color.g = grayscale;
     color.b = grayscale;
   }
+  color.rgb = apply_brightness(color.rgb, sprite.brightness, *hdr_headroom);
   color.a *= sprite.opacity * saturate(0.5 - distance);
   return color;
 }
 
+struct LayerSpriteVertexOutput {
+  float4 position [[position]];
+  uint sprite_id [[flat]];
+  float clip_distance [[clip_distance]][4];
+};
+
+struct LayerSpriteFragmentInput {
+  float4 position [[position]];
+  uint sprite_id [[flat]];
+};
+
+vertex LayerSpriteVertexOutput layer_sprite_vertex(
+    uint unit_vertex_id [[vertex_id]], uint sprite_id [[instance_id]],
+    constant float2 *unit_vertices [[buffer(SpriteInputIndex_Vertices)]],
+    constant LayerSprite *sprites [[buffer(SpriteInputIndex_Sprites)]],
+    constant Size_DevicePixels *viewport_size
+    [[buffer(SpriteInputIndex_ViewportSize)]]) {
+  float2 unit_vertex = unit_vertices[unit_vertex_id];
+  LayerSprite sprite = sprites[sprite_id];
+
+  // The blur reaches three standard deviations past the layer's bounds.
+  float margin = 1.5 * sprite.blur_radius;
+  Bounds_ScaledPixels bounds = sprite.bounds;
+  bounds.origin.x -= margin;
+  bounds.origin.y -= margin;
+  bounds.size.width += 2. * margin;
+  bounds.size.height += 2. * margin;
+
+  float4 device_position =
+      to_device_position(unit_vertex, bounds, viewport_size);
+  float4 clip_distance = distance_from_clip_rect(unit_vertex, bounds,
+                                                 sprite.content_mask.bounds);
+  return LayerSpriteVertexOutput{
+      device_position,
+      sprite_id,
+      {clip_distance.x, clip_distance.y, clip_distance.z, clip_distance.w}};
+}
+
+fragment float4 layer_sprite_fragment(
+    LayerSpriteFragmentInput input [[stage_in]],
+    constant LayerSprite *sprites [[buffer(SpriteInputIndex_Sprites)]],
+    texture2d<float> layer_texture [[texture(SpriteInputIndex_AtlasTexture)]]) {
+  LayerSprite sprite = sprites[input.sprite_id];
+  constexpr sampler layer_texture_sampler(mag_filter::nearest,
+                                          min_filter::nearest);
+  float2 texture_size =
+      float2(layer_texture.get_width(), layer_texture.get_height());
+  float2 bounds_min = float2(sprite.bounds.origin.x, sprite.bounds.origin.y);
+  float2 bounds_max =
+      bounds_min + float2(sprite.bounds.size.width, sprite.bounds.size.height);
+
+  // Sample a gaussian with a standard deviation of half the blur radius, out to three
+  // standard deviations. Large blurs take more than one pixel per step to bound the number of
+  // samples. The texture holds premultiplied colors, so transparent pixels don't darken the
+  // result.
+  float sigma = sprite.blur_radius / 2.;
+  float step = max(1., sigma / 2.);
+  int reach = sigma > 0. ? int(ceil(sigma * 3. / step)) : 0;
+  float4 sum = float4(0.);
+  float total_weight = 0.;
+  for (int dy = -reach; dy <= reach; dy++) {
+    for (int dx = -reach; dx <= reach; dx++) {
+      float2 offset = float2(dx, dy) * step;
+      float weight =
+          sigma > 0. ? exp(-dot(offset, offset) / (2. * sigma * sigma)) : 1.;
+      total_weight += weight;
+      float2 position = input.position.xy + offset;
+      if (all(position >= bounds_min) && all(position < bounds_max)) {
+        sum += layer_texture.sample(layer_texture_sampler,
+                                    position / texture_size) *
+               weight;
+      }
+    }
+  }
+
+  float4 color = sum / total_weight;
+  if (color.a <= 0.) {
+    return float4(0.);
+  }
+  return float4(color.rgb / color.a, color.a * sprite.opacity);
+}
+
 struct PathRasterizationVertexOutput {
   float4 position [[position]];
   float2 st_position;
@@ -529,12 +635,14 @@ fragment float4 path_sprite_fragment(
 struct SurfaceVertexOutput {
   float4 position [[position]];
   float2 texture_position;
+  float brightness [[flat]];
   float clip_distance [[clip_distance]][4];
 };
 
 struct SurfaceFragmentInput {
   float4 position [[position]];
   float2 texture_position;
+  float brightness [[flat]];
 };
 
 vertex SurfaceVertexOutput surface_vertex(
@@ -557,6 +665,7 @@ vertex SurfaceVertexOutput surface_vertex(
   return SurfaceVertexOutput{
       device_position,
       texture_position,
+      surface.brightness,
       {clip_distance.x, clip_distance.y, clip_distance.z, clip_distance.w}};
 }
 
@@ -564,7 +673,9 @@ fragment float4 surface_fragment(SurfaceFragmentInput input [[stage_in]],
                                  texture2d<float> y_texture
                                  [[texture(SurfaceInputIndex_YTexture)]],
                                  texture2d<float> cb_cr_texture
-                                 [[texture(SurfaceInputIndex_CbCrTexture)]]) {
+                                 [[texture(SurfaceInputIndex_CbCrTexture)]],
+                                 constant float *hdr_headroom
+                                 [[buffer(SurfaceInputIndex_HdrHeadroom)]]) {
   constexpr sampler texture_sampler(mag_filter::linear, min_filter::linear);
   const float4x4 ycbcrToRGBTransform =
       float4x4(float4(+1.0000f, +1.0000f, +1.0000f, +0.0000f),
@@ -575,7 +686,20 @@ fragment float4 surface_fragment(SurfaceFragmentInput input [[stage_in]],
       y_texture.sample(texture_sampler, input.texture_position).r,
       cb_cr_texture.sample(texture_sampler, input.texture_position).rg, 1.0);
 
-  return ycbcrToRGBTransform * ycbcr;
+  float4 color = ycbcrToRGBTransform * ycbcr;
+  color.rgb = apply_brightness(color.rgb, input.brightness, *hdr_headroom);
+  return color;
+}
+
+fragment float4 surface_bgra_fragment(SurfaceFragmentInput input [[stage_in]],
+                                      texture2d<float> rgba_texture
+                                      [[texture(SurfaceInputIndex_RgbaTexture)]],
+                                      constant float *hdr_headroom
+                                      [[buffer(SurfaceInputIndex_HdrHeadroom)]]) {
+  constexpr sampler texture_sampler(mag_filter::linear, min_filter::linear);
+  float4 color = rgba_texture.sample(texture_sampler, input.texture_position);
+  color.rgb = apply_brightness(color.rgb, input.brightness, *hdr_headroom);
+  return color;
 }
 
 float4 hsla_to_rgba(Hsla hsla) {
@@ -634,6 +758,23 @@ float3 linear_to_srgb(float3 color) {
   return pow(color, float3(1.0 / 2.2));
 }
 
+// Scales the light of an sRGB color by `brightness`. Colors that end up brighter than SDR white
+// are compressed to fit the display's headroom, rolling off smoothly instead of clipping, while
+// colors within SDR white are left as they are.
+float3 apply_brightness(float3 color, float brightness, float hdr_headroom) {
+  if (brightness == 1.0) {
+    return color;
+  }
+  float3 light = srgb_to_linear(max(color, float3(0.0))) * brightness;
+  float peak = max(light.r, max(light.g, light.b));
+  if (peak > 1.0) {
+    float excess = peak - 1.0;
+    float room = max(hdr_headroom - 1.0, 0.0);
+    light *= (1.0 + room * excess / (excess + room)) / peak;
+  }
+  return linear_to_srgb(light);
+}
+
 // Converts a sRGB color to the Oklab color space.
 // Reference: https://bottosson.github.io/posts/oklab/#converting-from-linear-srgb-to-oklab
 float4 srgb_to_oklab(float4 color) {
@@ -1,11 +1,12 @@
 use super::{ns_string, renderer, MacDisplay, NSRange, NSStringExt};
 use crate::{
-    platform::PlatformInputHandler, point, px, size, AnyWindowHandle, Bounds, DisplayLink,
-    ExternalPaths, FileDropEvent, ForegroundExecutor, KeyDownEvent, Keystroke, Modifiers,
-    ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels,
-    PlatformAtlas, PlatformDisplay, PlatformInput, PlatformWindow, Point, PromptLevel,
-    RequestFrameOptions, ScaledPixels, Size, Timer, WindowAppearance, WindowBackgroundAppearance,
-    WindowBounds, WindowKind, WindowParams,
+    platform::PlatformInputHandler, point, px, size, AccessibilityNode, AccessibilityRole,
+    Antialiasing, AnyWindowHandle, Bounds, DisplayLink, ExternalPaths, FileDropEvent,
+    ForegroundExecutor, KeyDownEvent, Keystroke, Modifiers, ModifiersChangedEvent, MouseButton,
+    MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels, PlatformAtlas, PlatformDisplay,
+    PlatformInput, PlatformWindow, Point, PromptLevel, RequestFrameOptions, ResizeEdge,
+    ScaledPixels, Size, Timer, TitlebarDoubleClickAction, WindowAppearance,
+    WindowBackgroundAppearance, WindowBounds, WindowKind, WindowLevel, WindowParams,
 };
 use block::ConcreteBlock;
 use cocoa::{
@@ -13,7 +14,7 @@ use cocoa::{
         NSApplication, NSBackingStoreBuffered, NSColor, NSEvent, NSEventModifierFlags,
         NSFilenamesPboardType, NSPasteboard, NSScreen, NSView, NSViewHeightSizable,
         NSViewWidthSizable, NSWindow, NSWindowButton, NSWindowCollectionBehavior,
-        NSWindowOcclusionState, NSWindowStyleMask, NSWindowTitleVisibility,
+        NSWindowOcclusionState, NSWindowOrderingMode, NSWindowStyleMask, NSWindowTitleVisibility,
     },
     base::{id, nil},
     foundation::{
@@ -51,14 +52,20 @@ const WINDOW_STATE_IVAR: &str = "windowState";
 
 static mut WINDOW_CLASS: *const Class = ptr::null();
 static mut PANEL_CLASS: *const Class = ptr::null();
+static mut TOOL_PANEL_CLASS: *const Class = ptr::null();
 static mut VIEW_CLASS: *const Class = ptr::null();
 
 #[allow(non_upper_case_globals)]
 const NSWindowStyleMaskNonactivatingPanel: NSWindowStyleMask =
     NSWindowStyleMask::from_bits_retain(1 << 7);
 #[allow(non_upper_case_globals)]
+const NSWindowStyleMaskUtilityWindow: NSWindowStyleMask =
+    NSWindowStyleMask::from_bits_retain(1 << 4);
+#[allow(non_upper_case_globals)]
 const NSNormalWindowLevel: NSInteger = 0;
 #[allow(non_upper_case_globals)]
+const NSFloatingWindowLevel: NSInteger = 3;
+#[allow(non_upper_case_globals)]
 const NSPopUpWindowLevel: NSInteger = 101;
 #[allow(non_upper_case_globals)]
 const NSTrackingMouseEnteredAndExited: NSUInteger = 0x01;
@@ -72,6 +79,13 @@ const NSTrackingInVisibleRect: NSUInteger = 0x200;
 const NSWindowAnimationBehaviorUtilityWindow: NSInteger = 4;
 #[allow(non_upper_case_globals)]
 const NSViewLayerContentsRedrawDuringViewResize: NSInteger = 2;
+// https://developer.apple.com/documentation/appkit/nsvisualeffectview
+#[allow(non_upper_case_globals)]
+const NSVisualEffectMaterialUnderWindowBackground: NSInteger = 21;
+#[allow(non_upper_case_globals)]
+const NSVisualEffectBlendingModeBehindWindow: NSInteger = 0;
+#[allow(non_upper_case_globals)]
+const NSVisualEffectStateActive: NSInteger = 1;
 // https://developer.apple.com/documentation/appkit/nsdragoperation
 type NSDragOperation = NSUInteger;
 #[allow(non_upper_case_globals)]
@@ -79,6 +93,9 @@ const NSDragOperationNone: NSDragOperation = 0;
 #[allow(non_upper_case_globals)]
 const NSDragOperationCopy: NSDragOperation = 1;
 
+#[link(name = "Quartz", kind = "framework")]
+extern "C" {}
+
 #[link(name = "CoreGraphics", kind = "framework")]
 extern "C" {
     // Widely used private APIs; Apple uses them for their Terminal.app.
@@ -92,8 +109,9 @@ extern "C" {
 
 #[ctor]
 unsafe fn build_classes() {
-    WINDOW_CLASS = build_window_class("GPUIWindow", class!(NSWindow));
-    PANEL_CLASS = build_window_class("GPUIPanel", class!(NSPanel));
+    WINDOW_CLASS = build_window_class("GPUIWindow", class!(NSWindow), true);
+    PANEL_CLASS = build_window_class("GPUIPanel", class!(NSPanel), true);
+    TOOL_PANEL_CLASS = build_window_class("GPUIToolPanel", class!(NSPanel), false);
     VIEW_CLASS = {
         let mut decl = ClassDecl::new("GPUIView", class!(NSView)).unwrap();
         decl.add_ivar::<*mut c_void>(WINDOW_STATE_IVAR);
@@ -104,6 +122,26 @@ unsafe fn build_classes() {
             sel!(performKeyEquivalent:),
             handle_key_equivalent as extern "C" fn(&Object, Sel, id) -> BOOL,
         );
+        decl.add_method(
+            sel!(acceptsPreviewPanelControl:),
+            accepts_preview_panel_control as extern "C" fn(&Object, Sel, id) -> BOOL,
+        );
+        decl.add_method(
+            sel!(beginPreviewPanelControl:),
+            begin_preview_panel_control as extern "C" fn(&Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(endPreviewPanelControl:),
+            end_preview_panel_control as extern "C" fn(&Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(numberOfPreviewItemsInPreviewPanel:),
+            number_of_preview_items as extern "C" fn(&Object, Sel, id) -> NSInteger,
+        );
+        decl.add_method(
+            sel!(previewPanel:previewItemAtIndex:),
+            preview_item_at_index as extern "C" fn(&Object, Sel, id, NSInteger) -> id,
+        );
         decl.add_method(
             sel!(keyDown:),
             handle_key_down as extern "C" fn(&Object, Sel, id),
@@ -244,14 +282,25 @@ pub(crate) fn convert_mouse_position(position: NSPoint, window_height: Pixels) -
     )
 }
 
-unsafe fn build_window_class(name: &'static str, superclass: &Class) -> *const Class {
+unsafe fn build_window_class(
+    name: &'static str,
+    superclass: &Class,
+    can_become_main: bool,
+) -> *const Class {
     let mut decl = ClassDecl::new(name, superclass).unwrap();
     decl.add_ivar::<*mut c_void>(WINDOW_STATE_IVAR);
     decl.add_method(sel!(dealloc), dealloc_window as extern "C" fn(&Object, Sel));
-    decl.add_method(
-        sel!(canBecomeMainWindow),
-        yes as extern "C" fn(&Object, Sel) -> BOOL,
-    );
+    if can_become_main {
+        decl.add_method(
+            sel!(canBecomeMainWindow),
+            yes as extern "C" fn(&Object, Sel) -> BOOL,
+        );
+    } else {
+        decl.add_method(
+            sel!(canBecomeMainWindow),
+            no as extern "C" fn(&Object, Sel) -> BOOL,
+        );
+    }
     decl.add_method(
         sel!(canBecomeKeyWindow),
         yes as extern "C" fn(&Object, Sel) -> BOOL,
@@ -330,6 +379,8 @@ struct MacWindowState {
     should_close_callback: Option<Box<dyn FnMut() -> bool>>,
     close_callback: Option<Box<dyn FnOnce()>>,
     appearance_changed_callback: Option<Box<dyn FnMut()>>,
+    occlusion_change_callback: Option<Box<dyn FnMut(bool)>>,
+    file_preview_paths: Vec<PathBuf>,
     input_handler: Option<PlatformInputHandler>,
     last_key_equivalent: Option<KeyDownEvent>,
     synthetic_drag_counter: usize,
@@ -340,7 +391,11 @@ struct MacWindowState {
     external_files_dragged: bool,
     // Whether the next left-mouse click is also the focusing click.
     first_mouse: bool,
+    // The retained left mouse down event that the current press started with, which window
+    // drags have to be started with.
+    left_mouse_down_event: Option<id>,
     fullscreen_restore_bounds: Bounds<Pixels>,
+    visual_effect_view: Option<id>,
 }
 
 impl MacWindowState {
@@ -395,16 +450,19 @@ impl MacWindowState {
         }
     }
 
-    fn start_display_link(&mut self) {
-        self.stop_display_link();
+    fn is_occluded(&self) -> bool {
         unsafe {
-            if !self
+            !self
                 .native_window
                 .occlusionState()
                 .contains(NSWindowOcclusionState::NSWindowOcclusionStateVisible)
-            {
-                return;
-            }
+        }
+    }
+
+    fn start_display_link(&mut self) {
+        self.stop_display_link();
+        if self.is_occluded() {
+            return;
         }
         let display_id = unsafe { display_id_for_screen(self.native_window.screen()) };
         if let Some(mut display_link) =
@@ -500,6 +558,11 @@ impl MacWindow {
             show,
             display_id,
             window_min_size,
+            parent,
+            modal,
+            level,
+            antialiasing,
+            hdr,
         }: WindowParams,
         executor: ForegroundExecutor,
         renderer_context: renderer::Context,
@@ -528,6 +591,11 @@ impl MacWindow {
                     style_mask |= NSWindowStyleMaskNonactivatingPanel;
                     msg_send![PANEL_CLASS, alloc]
                 }
+                WindowKind::Tool => {
+                    style_mask |=
+                        NSWindowStyleMaskNonactivatingPanel | NSWindowStyleMaskUtilityWindow;
+                    msg_send![TOOL_PANEL_CLASS, alloc]
+                }
             };
 
             let display = display_id
@@ -598,6 +666,8 @@ impl MacWindow {
                     native_view as *mut _,
                     bounds.size.map(|pixels| pixels.0),
                     false,
+                    antialiasing.path_sample_count(),
+                    hdr,
                 ),
                 request_frame_callback: None,
                 event_callback: None,
@@ -607,6 +677,8 @@ impl MacWindow {
                 should_close_callback: None,
                 close_callback: None,
                 appearance_changed_callback: None,
+                occlusion_change_callback: None,
+                file_preview_paths: Vec::new(),
                 input_handler: None,
                 last_key_equivalent: None,
                 synthetic_drag_counter: 0,
@@ -618,7 +690,9 @@ impl MacWindow {
                 do_command_handled: None,
                 external_files_dragged: false,
                 first_mouse: false,
+                left_mouse_down_event: None,
                 fullscreen_restore_bounds: Bounds::default(),
+                visual_effect_view: None,
             })));
 
             (*native_window).set_ivar(
@@ -699,19 +773,63 @@ impl MacWindow {
                         NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary
                     );
                 }
+                WindowKind::Tool => {
+                    native_window.setLevel_(NSFloatingWindowLevel);
+                    native_window.setAcceptsMouseMovedEvents_(YES);
+                    let _: () = msg_send![native_window, setFloatingPanel: YES];
+                    let _: () = msg_send![
+                        native_window,
+                        setAnimationBehavior: NSWindowAnimationBehaviorUtilityWindow
+                    ];
+                    native_window.setCollectionBehavior_(
+                        NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary,
+                    );
+                }
             }
 
-            if focus && show {
-                native_window.makeKeyAndOrderFront_(nil);
-            } else if show {
-                native_window.orderFront_(nil);
+            if let Some(level) = level {
+                set_window_level(native_window, level);
             }
 
-            // Set the initial position of the window to the specified origin.
-            // Although we already specified the position using `initWithContentRect_styleMask_backing_defer_screen_`,
-            // the window position might be incorrect if the main screen (the screen that contains the window that has focus)
-            //  is different from the primary screen.
-            NSWindow::setFrameTopLeftPoint_(native_window, window_rect.origin);
+            let parent_window = match parent {
+                Some(rwh::RawWindowHandle::AppKit(parent)) => {
+                    let parent_view: id = parent.ns_view.as_ptr().cast();
+                    let parent_window: id = msg_send![parent_view, window];
+                    (!parent_window.is_null()).then_some(parent_window)
+                }
+                _ => None,
+            };
+
+            if let Some(parent_window) = parent_window.filter(|_| modal) {
+                // Modal windows are presented as sheets, which AppKit positions on their parent
+                // and which block input to the parent until they are ended.
+                let _: () = msg_send![
+                    parent_window,
+                    beginSheet: native_window
+                    completionHandler: nil
+                ];
+            } else {
+                if focus && show {
+                    native_window.makeKeyAndOrderFront_(nil);
+                } else if show {
+                    native_window.orderFront_(nil);
+                }
+
+                // Child windows stay above their parent, and move and minimize along with it.
+                if let Some(parent_window) = parent_window {
+                    let _: () = msg_send![
+                        parent_window,
+                        addChildWindow: native_window
+                        ordered: NSWindowOrderingMode::NSWindowAbove
+                    ];
+                }
+
+                // Set the initial position of the window to the specified origin.
+                // Although we already specified the position using `initWithContentRect_styleMask_backing_defer_screen_`,
+                // the window position might be incorrect if the main screen (the screen that contains the window that has focus)
+                //  is different from the primary screen.
+                NSWindow::setFrameTopLeftPoint_(native_window, window_rect.origin);
+            }
             window.0.lock().move_traffic_light();
 
             pool.drain();
@@ -763,9 +881,19 @@ impl Drop for MacWindow {
             this.native_window.setDelegate_(nil);
         }
         this.input_handler.take();
+        if let Some(event) = this.left_mouse_down_event.take() {
+            unsafe {
+                let _: () = msg_send![event, release];
+            }
+        }
         this.executor
             .spawn(async move {
                 unsafe {
+                    // A sheet has to be ended before it closes, or its parent stays blocked.
+                    let sheet_parent: id = msg_send![window, sheetParent];
+                    if !sheet_parent.is_null() {
+                        let _: () = msg_send![sheet_parent, endSheet: window];
+                    }
                     window.close();
                     window.autorelease();
                 }
@@ -787,6 +915,11 @@ impl PlatformWindow for MacWindow {
         self.0.as_ref().lock().is_maximized()
     }
 
+    fn is_minimized(&self) -> bool {
+        let window = self.0.as_ref().lock().native_window;
+        unsafe { msg_send![window, isMiniaturized] }
+    }
+
     fn content_size(&self) -> Size<Pixels> {
         self.0.as_ref().lock().content_size()
     }
@@ -972,6 +1105,11 @@ impl PlatformWindow for MacWindow {
 
     fn set_app_id(&mut self, _app_id: &str) {}
 
+    fn set_level(&self, level: WindowLevel) {
+        let window = self.0.lock().native_window;
+        unsafe { set_window_level(window, level) };
+    }
+
     fn set_background_appearance(&self, background_appearance: WindowBackgroundAppearance) {
         let mut this = self.0.as_ref().lock();
         this.renderer
@@ -999,6 +1137,38 @@ impl PlatformWindow for MacWindow {
             this.native_window.setBackgroundColor_(clear_color);
             let window_number = this.native_window.windowNumber();
             CGSSetWindowBackgroundBlurRadius(CGSMainConnectionID(), window_number, blur_radius);
+
+            if background_appearance == WindowBackgroundAppearance::Vibrant {
+                if this.visual_effect_view.is_none() {
+                    // Place the effect view behind the content view, so that the Metal layer
+                    // draws on top of the system material.
+                    let content_view = this.native_window.contentView();
+                    let frame_view: id = msg_send![content_view, superview];
+                    let effect_view: id = msg_send![class!(NSVisualEffectView), alloc];
+                    let effect_view: id =
+                        msg_send![effect_view, initWithFrame: NSView::frame(frame_view)];
+                    let _: () = msg_send![
+                        effect_view,
+                        setMaterial: NSVisualEffectMaterialUnderWindowBackground
+                    ];
+                    let _: () = msg_send![
+                        effect_view,
+                        setBlendingMode: NSVisualEffectBlendingModeBehindWindow
+                    ];
+                    let _: () = msg_send![effect_view, setState: NSVisualEffectStateActive];
+                    effect_view.setAutoresizingMask_(NSViewWidthSizable | NSViewHeightSizable);
+                    let _: () = msg_send![
+                        frame_view,
+                        addSubview: effect_view
+                        positioned: NSWindowOrderingMode::NSWindowBelow
+                        relativeTo: content_view
+                    ];
+                    this.visual_effect_view = Some(effect_view);
+                }
+            } else if let Some(effect_view) = this.visual_effect_view.take() {
+                let _: () = msg_send![effect_view, removeFromSuperview];
+                let _: () = msg_send![effect_view, release];
+            }
         }
     }
 
@@ -1013,6 +1183,30 @@ impl PlatformWindow for MacWindow {
         self.0.lock().move_traffic_light();
     }
 
+    fn titlebar_double_click_action(&self) -> TitlebarDoubleClickAction {
+        // Reflects the "Double-click a window's title bar to" setting in System Settings.
+        unsafe {
+            let defaults: id = msg_send![class!(NSUserDefaults), standardUserDefaults];
+            let action: id =
+                msg_send![defaults, stringForKey: ns_string("AppleActionOnDoubleClick")];
+            if action != nil {
+                return match action.to_str() {
+                    "Minimize" => TitlebarDoubleClickAction::Minimize,
+                    "None" => TitlebarDoubleClickAction::Ignore,
+                    _ => TitlebarDoubleClickAction::Zoom,
+                };
+            }
+            // Older versions of macOS only store whether to minimize.
+            let minimize: BOOL =
+                msg_send![defaults, boolForKey: ns_string("AppleMiniaturizeOnDoubleClick")];
+            if minimize == YES {
+                TitlebarDoubleClickAction::Minimize
+            } else {
+                TitlebarDoubleClickAction::Zoom
+            }
+        }
+    }
+
     fn show_character_palette(&self) {
         let this = self.0.lock();
         let window = this.native_window;
@@ -1026,6 +1220,46 @@ impl PlatformWindow for MacWindow {
             .detach();
     }
 
+    fn show_file_preview(&self, paths: Vec<PathBuf>) {
+        let mut this = self.0.lock();
+        this.file_preview_paths = paths;
+        // The panel calls back into the view to take control of it, so show it outside
+        // of the current update.
+        this.executor
+            .spawn(async move {
+                unsafe {
+                    let panel: id = msg_send![class!(QLPreviewPanel), sharedPreviewPanel];
+                    let visible: BOOL = msg_send![panel, isVisible];
+                    if visible == YES {
+                        let _: () = msg_send![panel, updateController];
+                        let _: () = msg_send![panel, reloadData];
+                    } else {
+                        let _: () = msg_send![panel, makeKeyAndOrderFront: nil];
+                    }
+                }
+            })
+            .detach();
+    }
+
+    fn hide_file_preview(&self) {
+        self.0
+            .lock()
+            .executor
+            .spawn(async move {
+                unsafe {
+                    if is_preview_panel_visible() {
+                        let panel: id = msg_send![class!(QLPreviewPanel), sharedPreviewPanel];
+                        let _: () = msg_send![panel, orderOut: nil];
+                    }
+                }
+            })
+            .detach();
+    }
+
+    fn is_file_preview_visible(&self) -> bool {
+        unsafe { is_preview_panel_visible() }
+    }
+
     fn minimize(&self) {
         let window = self.0.lock().native_window;
         unsafe {
@@ -1045,6 +1279,34 @@ impl PlatformWindow for MacWindow {
             .detach();
     }
 
+    fn start_window_move(&self) {
+        let mut this = self.0.lock();
+        let window = this.native_window;
+        let Some(event) = this.left_mouse_down_event.take() else {
+            return;
+        };
+        drop(this);
+        unsafe {
+            let _: () = msg_send![window, performWindowDragWithEvent: event];
+            let _: () = msg_send![event, release];
+        }
+    }
+
+    fn start_window_resize(&self, edge: ResizeEdge) {
+        // AppKit has no API to begin a resize from an arbitrary point, so track the mouse
+        // ourselves. This runs outside of the current event handler, as resizing the window
+        // re-enters the app to lay out and draw at the new size.
+        let this = self.0.lock();
+        let window = this.native_window;
+        this.executor
+            .spawn(async move {
+                unsafe {
+                    track_window_resize(window, edge);
+                }
+            })
+            .detach();
+    }
+
     fn toggle_fullscreen(&self) {
         let this = self.0.lock();
         let window = this.native_window;
@@ -1102,15 +1364,110 @@ impl PlatformWindow for MacWindow {
         self.0.lock().appearance_changed_callback = Some(callback);
     }
 
+    fn is_occluded(&self) -> bool {
+        self.0.lock().is_occluded()
+    }
+
+    fn on_occlusion_change(&self, callback: Box<dyn FnMut(bool)>) {
+        self.0.lock().occlusion_change_callback = Some(callback);
+    }
+
+    #[cfg(feature = "macos-blade")]
     fn draw(&self, scene: &crate::Scene) {
         let mut this = self.0.lock();
         this.renderer.draw(scene);
     }
 
+    #[cfg(not(feature = "macos-blade"))]
+    fn draw(&self, scene: &crate::Scene) {
+        self.draw_damaged(scene, None);
+    }
+
+    #[cfg(not(feature = "macos-blade"))]
+    fn draw_damaged(&self, scene: &crate::Scene, damage: Option<Bounds<ScaledPixels>>) {
+        let mut this = self.0.lock();
+        let hdr_headroom = get_hdr_headroom(this.native_window, &this.renderer);
+        this.renderer.update_hdr_headroom(hdr_headroom);
+        this.renderer.draw(scene, damage);
+    }
+
+    #[cfg(not(feature = "macos-blade"))]
+    fn capture_frame(&self, scene: &crate::Scene) -> anyhow::Result<image::RgbaImage> {
+        self.0.lock().renderer.capture(scene)
+    }
+
     fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas> {
         self.0.lock().renderer.sprite_atlas().clone()
     }
 
+    fn set_antialiasing(&self, antialiasing: Antialiasing) {
+        self.0
+            .lock()
+            .renderer
+            .update_path_sample_count(antialiasing.path_sample_count());
+    }
+
+    #[cfg(not(feature = "macos-blade"))]
+    fn hdr_headroom(&self) -> f32 {
+        let this = self.0.lock();
+        get_hdr_headroom(this.native_window, &this.renderer)
+    }
+
+    fn update_accessibility_tree(&self, nodes: &[AccessibilityNode]) {
+        let this = self.0.lock();
+        let view = this.native_view.as_ptr();
+        let view_bounds = Bounds::new(Point::default(), this.content_size());
+        unsafe {
+            let mut elements: Vec<id> = Vec::with_capacity(nodes.len());
+            let mut children: Vec<Vec<id>> = vec![Vec::new(); nodes.len()];
+            let mut top_level_elements = Vec::new();
+            for node in nodes {
+                let (parent, parent_bounds) = match node.parent {
+                    Some(ix) => (elements[ix], nodes[ix].bounds),
+                    None => (view, view_bounds),
+                };
+                // Frames are relative to the parent's frame, whose origin is its bottom left corner.
+                let frame = NSRect::new(
+                    NSPoint::new(
+                        (node.bounds.origin.x - parent_bounds.origin.x).0 as f64,
+                        (parent_bounds.bottom() - node.bounds.bottom()).0 as f64,
+                    ),
+                    NSSize::new(
+                        node.bounds.size.width.0 as f64,
+                        node.bounds.size.height.0 as f64,
+                    ),
+                );
+
+                let element: id = msg_send![class!(NSAccessibilityElement), new];
+                let element: id = msg_send![element, autorelease];
+                let _: () = msg_send![element, setAccessibilityRole: ns_string(accessibility_role(node.role))];
+                let _: () = msg_send![element, setAccessibilityFrameInParentSpace: frame];
+                let _: () = msg_send![element, setAccessibilityParent: parent];
+                if let Some(label) = node.label.as_ref() {
+                    let _: () = msg_send![element, setAccessibilityLabel: ns_string(label)];
+                }
+                if let Some(value) = node.value.as_ref() {
+                    let _: () = msg_send![element, setAccessibilityValue: ns_string(value)];
+                }
+
+                match node.parent {
+                    Some(ix) => children[ix].push(element),
+                    None => top_level_elements.push(element),
+                }
+                elements.push(element);
+            }
+
+            for (element, children) in elements.iter().zip(children) {
+                if !children.is_empty() {
+                    let children = NSArray::arrayWithObjects(nil, &children);
+                    let _: () = msg_send![*element, setAccessibilityChildren: children];
+                }
+            }
+            let top_level_elements = NSArray::arrayWithObjects(nil, &top_level_elements);
+            let _: () = msg_send![view, setAccessibilityChildren: top_level_elements];
+        }
+    }
+
     fn gpu_specs(&self) -> Option<crate::GpuSpecs> {
         None
     }
@@ -1170,6 +1527,87 @@ fn get_scale_factor(native_window: id) -> f32 {
     }
 }
 
+/// How many times brighter than SDR white the window's screen can currently show colors, or 1 if
+/// the renderer doesn't output HDR.
+#[cfg(not(feature = "macos-blade"))]
+fn get_hdr_headroom(native_window: id, renderer: &renderer::Renderer) -> f32 {
+    if !renderer.hdr() {
+        return 1.;
+    }
+    unsafe {
+        let screen: id = msg_send![native_window, screen];
+        if screen == nil {
+            return 1.;
+        }
+        let headroom: f64 = msg_send![screen, maximumExtendedDynamicRangeColorComponentValue];
+        (headroom as f32).max(1.)
+    }
+}
+
+unsafe fn set_window_level(native_window: id, level: WindowLevel) {
+    let (window_level, hides_on_deactivate) = match level {
+        WindowLevel::Normal => (NSNormalWindowLevel, NO),
+        WindowLevel::Floating => (NSFloatingWindowLevel, YES),
+        WindowLevel::AlwaysOnTop => (NSFloatingWindowLevel, NO),
+        WindowLevel::PopUpMenu => (NSPopUpWindowLevel, NO),
+    };
+    native_window.setLevel_(window_level);
+    let _: () = msg_send![native_window, setHidesOnDeactivate: hides_on_deactivate];
+}
+
+unsafe fn track_window_resize(window: id, edge: ResizeEdge) {
+    const NS_EVENT_MASK_LEFT_MOUSE_UP: u64 = 1 << 2;
+    const NS_EVENT_MASK_LEFT_MOUSE_DRAGGED: u64 = 1 << 6;
+    const NS_EVENT_TYPE_LEFT_MOUSE_UP: u64 = 2;
+
+    let app = NSApplication::sharedApplication(nil);
+    let initial_frame = NSWindow::frame(window);
+    let initial_mouse: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+    let min_size: NSSize = msg_send![window, minSize];
+    let (left, right, top, bottom) = match edge {
+        ResizeEdge::Top => (false, false, true, false),
+        ResizeEdge::TopRight => (false, true, true, false),
+        ResizeEdge::Right => (false, true, false, false),
+        ResizeEdge::BottomRight => (false, true, false, true),
+        ResizeEdge::Bottom => (false, false, false, true),
+        ResizeEdge::BottomLeft => (true, false, false, true),
+        ResizeEdge::Left => (true, false, false, false),
+        ResizeEdge::TopLeft => (true, false, true, false),
+    };
+
+    loop {
+        let distant_future: id = msg_send![class!(NSDate), distantFuture];
+        let event: id = msg_send![app,
+            nextEventMatchingMask: NS_EVENT_MASK_LEFT_MOUSE_UP | NS_EVENT_MASK_LEFT_MOUSE_DRAGGED
+            untilDate: distant_future
+            inMode: ns_string("kCFRunLoopDefaultMode")
+            dequeue: YES
+        ];
+        if event == nil || event.eventType() as u64 == NS_EVENT_TYPE_LEFT_MOUSE_UP {
+            break;
+        }
+
+        // Cocoa's y axis points up, so the window's origin is its bottom left corner.
+        let mouse: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+        let dx = mouse.x - initial_mouse.x;
+        let dy = mouse.y - initial_mouse.y;
+        let mut frame = initial_frame;
+        if left {
+            frame.size.width = (initial_frame.size.width - dx).max(min_size.width);
+            frame.origin.x = initial_frame.origin.x + initial_frame.size.width - frame.size.width;
+        } else if right {
+            frame.size.width = (initial_frame.size.width + dx).max(min_size.width);
+        }
+        if bottom {
+            frame.size.height = (initial_frame.size.height - dy).max(min_size.height);
+            frame.origin.y = initial_frame.origin.y + initial_frame.size.height - frame.size.height;
+        } else if top {
+            frame.size.height = (initial_frame.size.height + dy).max(min_size.height);
+        }
+        let _: () = msg_send![window, setFrame: frame display: YES];
+    }
+}
+
 unsafe fn get_window_state(object: &Object) -> Arc<Mutex<MacWindowState>> {
     let raw: *mut c_void = *object.get_ivar(WINDOW_STATE_IVAR);
     let rc1 = Arc::from_raw(raw as *mut Mutex<MacWindowState>);
@@ -1183,10 +1621,86 @@ unsafe fn drop_window_state(object: &Object) {
     Arc::from_raw(raw as *mut Mutex<MacWindowState>);
 }
 
+fn accessibility_role(role: AccessibilityRole) -> &'static str {
+    match role {
+        AccessibilityRole::Group | AccessibilityRole::Dialog | AccessibilityRole::ListItem => {
+            "AXGroup"
+        }
+        AccessibilityRole::Button => "AXButton",
+        AccessibilityRole::CheckBox => "AXCheckBox",
+        AccessibilityRole::Heading => "AXHeading",
+        AccessibilityRole::Image => "AXImage",
+        AccessibilityRole::Link => "AXLink",
+        AccessibilityRole::List => "AXList",
+        AccessibilityRole::Menu => "AXMenu",
+        AccessibilityRole::MenuItem => "AXMenuItem",
+        AccessibilityRole::StaticText => "AXStaticText",
+        // AppKit represents tabs as radio buttons within a tab group.
+        AccessibilityRole::Tab => "AXRadioButton",
+        AccessibilityRole::TabList => "AXTabGroup",
+        AccessibilityRole::TextField => "AXTextField",
+        AccessibilityRole::Toolbar => "AXToolbar",
+    }
+}
+
+unsafe fn is_preview_panel_visible() -> bool {
+    let exists: BOOL = msg_send![class!(QLPreviewPanel), sharedPreviewPanelExists];
+    if exists == NO {
+        return false;
+    }
+    let panel: id = msg_send![class!(QLPreviewPanel), sharedPreviewPanel];
+    let visible: BOOL = msg_send![panel, isVisible];
+    visible == YES
+}
+
+extern "C" fn accepts_preview_panel_control(this: &Object, _: Sel, _panel: id) -> BOOL {
+    let window_state = unsafe { get_window_state(this) };
+    let has_paths = !window_state.lock().file_preview_paths.is_empty();
+    has_paths as BOOL
+}
+
+extern "C" fn begin_preview_panel_control(this: &Object, _: Sel, panel: id) {
+    unsafe {
+        let view = this as *const Object as id;
+        let _: () = msg_send![panel, setDataSource: view];
+        let _: () = msg_send![panel, reloadData];
+    }
+}
+
+extern "C" fn end_preview_panel_control(this: &Object, _: Sel, panel: id) {
+    unsafe {
+        let _: () = msg_send![panel, setDataSource: nil];
+    }
+    let window_state = unsafe { get_window_state(this) };
+    window_state.lock().file_preview_paths.clear();
+}
+
+extern "C" fn number_of_preview_items(this: &Object, _: Sel, _panel: id) -> NSInteger {
+    let window_state = unsafe { get_window_state(this) };
+    let count = window_state.lock().file_preview_paths.len();
+    count as NSInteger
+}
+
+extern "C" fn preview_item_at_index(this: &Object, _: Sel, _panel: id, index: NSInteger) -> id {
+    let window_state = unsafe { get_window_state(this) };
+    let lock = window_state.lock();
+    let Some(path) = lock.file_preview_paths.get(index as usize) else {
+        return nil;
+    };
+    unsafe {
+        let path = ns_string(&path.to_string_lossy());
+        msg_send![class!(NSURL), fileURLWithPath: path]
+    }
+}
+
 extern "C" fn yes(_: &Object, _: Sel) -> BOOL {
     YES
 }
 
+extern "C" fn no(_: &Object, _: Sel) -> BOOL {
+    NO
+}
+
 extern "C" fn dealloc_window(this: &Object, _: Sel) {
     unsafe {
         drop_window_state(this);
@@ -1406,7 +1920,15 @@ extern "C" fn handle_view_event(this: &Object, _: Sel, native_event: id) {
         };
 
         match &event {
-            PlatformInput::MouseDown(_) => {
+            PlatformInput::MouseDown(event) => {
+                if event.button == MouseButton::Left {
+                    unsafe {
+                        let _: () = msg_send![native_event, retain];
+                        if let Some(previous) = lock.left_mouse_down_event.replace(native_event) {
+                            let _: () = msg_send![previous, release];
+                        }
+                    }
+                }
                 drop(lock);
                 unsafe {
                     let input_context: id = msg_send![this, inputContext];
@@ -1438,6 +1960,11 @@ extern "C" fn handle_view_event(this: &Object, _: Sel, native_event: id) {
 
             PlatformInput::MouseUp(MouseUpEvent { .. }) => {
                 lock.synthetic_drag_counter += 1;
+                if let Some(event) = lock.left_mouse_down_event.take() {
+                    unsafe {
+                        let _: () = msg_send![event, release];
+                    }
+                }
             }
 
             PlatformInput::ModifiersChanged(ModifiersChangedEvent { modifiers }) => {
@@ -1467,17 +1994,18 @@ extern "C" fn handle_view_event(this: &Object, _: Sel, native_event: id) {
 
 extern "C" fn window_did_change_occlusion_state(this: &Object, _: Sel, _: id) {
     let window_state = unsafe { get_window_state(this) };
-    let lock = &mut *window_state.lock();
-    unsafe {
-        if lock
-            .native_window
-            .occlusionState()
-            .contains(NSWindowOcclusionState::NSWindowOcclusionStateVisible)
-        {
-            lock.start_display_link();
-        } else {
-            lock.stop_display_link();
-        }
+    let mut lock = window_state.lock();
+    let occluded = lock.is_occluded();
+    if occluded {
+        lock.stop_display_link();
+    } else {
+        lock.start_display_link();
+    }
+
+    if let Some(mut callback) = lock.occlusion_change_callback.take() {
+        drop(lock);
+        callback(occluded);
+        window_state.lock().occlusion_change_callback = Some(callback);
     }
 }
 
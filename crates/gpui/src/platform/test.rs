@@ -1,11 +1,17 @@
 mod dispatcher;
 mod display;
+mod golden;
 mod platform;
+mod rasterizer;
+mod snapshot;
 mod window;
 
 pub(crate) use dispatcher::*;
 pub(crate) use display::*;
+pub(crate) use golden::*;
 pub(crate) use platform::*;
+pub(crate) use rasterizer::*;
+pub(crate) use snapshot::*;
 pub(crate) use window::*;
 
 pub use platform::TestScreenCaptureSource;
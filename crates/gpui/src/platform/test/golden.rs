@@ -0,0 +1,155 @@
+use anyhow::{Context as _, Result};
+use image::{Rgba, RgbaImage};
+use std::path::{Path, PathBuf};
+
+/// When this environment variable is set, golden image assertions write the rendered image
+/// as the new reference instead of comparing against it.
+const UPDATE_GOLDEN_IMAGES: &str = "UPDATE_GOLDEN_IMAGES";
+
+/// The largest difference between two colors in the YIQ color space, see [`color_difference`].
+const MAX_YIQ_DIFFERENCE: f32 = 35215.;
+
+/// Compares a rendered image against the reference image at the given path, panicking if any
+/// pixel differs perceptually by more than `threshold`, which ranges from 0 (identical) to 1.
+///
+/// When the comparison fails, the rendered image and an image highlighting the differing pixels
+/// are written next to the reference so they can be inspected.
+#[track_caller]
+pub(crate) fn assert_golden_image(actual: &RgbaImage, path: &Path, threshold: f32) {
+    if std::env::var(UPDATE_GOLDEN_IMAGES).is_ok() {
+        actual
+            .save(path)
+            .with_context(|| format!("failed to write golden image {:?}", path))
+            .unwrap();
+        return;
+    }
+
+    let expected = match image::open(path) {
+        Ok(expected) => expected.into_rgba8(),
+        Err(error) => panic!(
+            "failed to read golden image {:?}: {}. Run with {}=1 to create it.",
+            path, error, UPDATE_GOLDEN_IMAGES
+        ),
+    };
+    if expected.dimensions() != actual.dimensions() {
+        save_failure_artifacts(path, actual, None).ok();
+        panic!(
+            "rendered image is {:?} but golden image {:?} is {:?}",
+            actual.dimensions(),
+            path,
+            expected.dimensions()
+        );
+    }
+
+    let diff = diff_images(&expected, actual, threshold);
+    if diff.differing_pixels > 0 {
+        save_failure_artifacts(path, actual, Some(&diff.image)).ok();
+        panic!(
+            "{} pixels differ from golden image {:?}, by up to {:.3} (threshold {:.3}). \
+             Run with {}=1 to accept the rendered image.",
+            diff.differing_pixels, path, diff.max_difference, threshold, UPDATE_GOLDEN_IMAGES
+        );
+    }
+}
+
+struct ImageDiff {
+    differing_pixels: usize,
+    max_difference: f32,
+    /// The expected image faded to grayscale, with differing pixels in red
+    image: RgbaImage,
+}
+
+fn diff_images(expected: &RgbaImage, actual: &RgbaImage, threshold: f32) -> ImageDiff {
+    let mut image = RgbaImage::new(expected.width(), expected.height());
+    let mut differing_pixels = 0;
+    let mut max_difference = 0f32;
+    for ((expected, actual), diff) in expected
+        .pixels()
+        .zip(actual.pixels())
+        .zip(image.pixels_mut())
+    {
+        let difference = color_difference(*expected, *actual);
+        max_difference = max_difference.max(difference);
+        *diff = if difference > threshold {
+            differing_pixels += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let luma = 192 + (luminance(*expected) / 4.) as u8;
+            Rgba([luma, luma, luma, 255])
+        };
+    }
+    ImageDiff {
+        differing_pixels,
+        max_difference,
+        image,
+    }
+}
+
+/// The perceptual difference between two colors from 0 to 1, measured in the YIQ color space
+/// after blending them onto white, which weighs changes in brightness above changes in hue.
+fn color_difference(a: Rgba<u8>, b: Rgba<u8>) -> f32 {
+    if a == b {
+        return 0.;
+    }
+    let (y_a, i_a, q_a) = yiq(a);
+    let (y_b, i_b, q_b) = yiq(b);
+    let (y, i, q) = (y_a - y_b, i_a - i_b, q_a - q_b);
+    let difference = 0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q;
+    (difference / MAX_YIQ_DIFFERENCE).sqrt().min(1.)
+}
+
+fn yiq(color: Rgba<u8>) -> (f32, f32, f32) {
+    let [r, g, b] = blend_onto_white(color);
+    (
+        r * 0.2989 + g * 0.5866 + b * 0.1145,
+        r * 0.5960 - g * 0.2742 - b * 0.3218,
+        r * 0.2115 - g * 0.5226 + b * 0.3111,
+    )
+}
+
+fn luminance(color: Rgba<u8>) -> f32 {
+    yiq(color).0
+}
+
+fn blend_onto_white(color: Rgba<u8>) -> [f32; 3] {
+    let alpha = color.0[3] as f32 / 255.;
+    let blend = |channel: u8| 255. + (channel as f32 - 255.) * alpha;
+    [blend(color.0[0]), blend(color.0[1]), blend(color.0[2])]
+}
+
+fn save_failure_artifacts(path: &Path, actual: &RgbaImage, diff: Option<&RgbaImage>) -> Result<()> {
+    actual.save(sibling_path(path, "actual"))?;
+    if let Some(diff) = diff {
+        diff.save(sibling_path(path, "diff"))?;
+    }
+    Ok(())
+}
+
+/// Returns `dir/name.suffix.ext` for `dir/name.ext`.
+pub(super) fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "png".to_string());
+    path.with_file_name(format!("{}.{}.{}", stem, suffix, extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_difference() {
+        let white = Rgba([255, 255, 255, 255]);
+        let black = Rgba([0, 0, 0, 255]);
+        assert_eq!(color_difference(white, white), 0.);
+        assert!(color_difference(white, black) > 0.9);
+        // Fully transparent pixels look like the white they're blended onto.
+        assert_eq!(color_difference(white, Rgba([0, 0, 0, 0])), 0.);
+        assert!(color_difference(Rgba([100, 100, 100, 255]), Rgba([101, 100, 100, 255])) < 0.01);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
-    px, size, AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, ForegroundExecutor,
-    Keymap, Platform, PlatformDisplay, PlatformTextSystem, ScreenCaptureFrame, ScreenCaptureSource,
-    ScreenCaptureStream, Task, TestDisplay, TestWindow, WindowAppearance, WindowParams,
+    px, size, AnnouncementPriority, AnyWindowHandle, BackgroundExecutor, ClipboardItem,
+    CursorStyle, ForegroundExecutor, Keymap, Platform, PlatformDisplay, PlatformTextSystem,
+    ScreenCaptureFrame, ScreenCaptureSource, ScreenCaptureStream, Task, TestDisplay, TestWindow,
+    WindowAppearance, WindowParams,
 };
 use anyhow::Result;
 use collections::VecDeque;
@@ -33,6 +34,8 @@ pub(crate) struct TestPlatform {
     pub(crate) prompts: RefCell<TestPrompts>,
     screen_capture_sources: RefCell<Vec<TestScreenCaptureSource>>,
     pub opened_url: RefCell<Option<String>>,
+    pub announcements: RefCell<Vec<(String, AnnouncementPriority)>>,
+    quit_requested: RefCell<Option<Box<dyn FnMut() -> bool>>>,
     pub text_system: Arc<dyn PlatformTextSystem>,
     #[cfg(target_os = "windows")]
     bitmap_factory: std::mem::ManuallyDrop<IWICImagingFactory>,
@@ -107,6 +110,8 @@ impl TestPlatform {
             current_primary_item: Mutex::new(None),
             weak: weak.clone(),
             opened_url: Default::default(),
+            announcements: Default::default(),
+            quit_requested: Default::default(),
             #[cfg(target_os = "windows")]
             bitmap_factory,
             text_system,
@@ -126,6 +131,17 @@ impl TestPlatform {
         tx.send(Ok(select_path(&path))).ok();
     }
 
+    pub(crate) fn simulate_quit_request(&self) -> bool {
+        let callback = self.quit_requested.borrow_mut().take();
+        if let Some(mut callback) = callback {
+            let should_quit = callback();
+            self.quit_requested.borrow_mut().get_or_insert(callback);
+            should_quit
+        } else {
+            true
+        }
+    }
+
     pub(crate) fn simulate_prompt_answer(&self, response_ix: usize) {
         let tx = self
             .prompts
@@ -247,6 +263,19 @@ impl Platform for TestPlatform {
         rx
     }
 
+    /// Picks the first of the sources set with `TestAppContext::set_screen_capture_sources`.
+    fn pick_screen_capture_source(
+        &self,
+    ) -> oneshot::Receiver<Result<Option<Box<dyn ScreenCaptureSource>>>> {
+        let source = self.screen_capture_sources.borrow().first().cloned();
+        let (mut tx, rx) = oneshot::channel();
+        tx.send(Ok(
+            source.map(|source| Box::new(source) as Box<dyn ScreenCaptureSource>)
+        ))
+        .ok();
+        rx
+    }
+
     fn active_window(&self) -> Option<crate::AnyWindowHandle> {
         self.active_window
             .borrow()
@@ -276,6 +305,12 @@ impl Platform for TestPlatform {
         *self.opened_url.borrow_mut() = Some(url.to_string())
     }
 
+    fn announce(&self, text: &str, priority: AnnouncementPriority) {
+        self.announcements
+            .borrow_mut()
+            .push((text.to_string(), priority));
+    }
+
     fn on_open_urls(&self, _callback: Box<dyn FnMut(Vec<String>)>) {
         unimplemented!()
     }
@@ -289,13 +324,13 @@ impl Platform for TestPlatform {
 
     fn prompt_for_new_path(
         &self,
-        directory: &std::path::Path,
+        options: crate::NewPathPromptOptions,
     ) -> oneshot::Receiver<Result<Option<std::path::PathBuf>>> {
         let (tx, rx) = oneshot::channel();
         self.prompts
             .borrow_mut()
             .new_path
-            .push_back((directory.to_path_buf(), tx));
+            .push_back((options.directory, tx));
         rx
     }
 
@@ -309,6 +344,10 @@ impl Platform for TestPlatform {
 
     fn on_quit(&self, _callback: Box<dyn FnMut()>) {}
 
+    fn on_quit_requested(&self, callback: Box<dyn FnMut() -> bool>) {
+        *self.quit_requested.borrow_mut() = Some(callback);
+    }
+
     fn on_reopen(&self, _callback: Box<dyn FnMut()>) {
         unimplemented!()
     }
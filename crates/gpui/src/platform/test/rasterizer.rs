@@ -0,0 +1,682 @@
+use crate::{
+    AtlasTextureKind, Background, BackgroundTag, Bounds, Corners, DevicePixels, Edges, LayerSprite,
+    MonochromeSprite, PaintOperation, Path, PolychromeSprite, Primitive, Quad, Rgba, ScaledPixels,
+    Scene, Shadow, Size, TestAtlas, TestTileContents, Underline,
+};
+use image::RgbaImage;
+
+/// Rasterizes a [`Scene`] on the CPU, so that tests can make assertions about real
+/// pixel output without a GPU or a window server.
+///
+/// Primitives are drawn in paint order, with sprites sampled from the tiles the test atlas
+/// kept for them, so text rendered by the platform's text system is included. Offscreen layers
+/// are drawn onto their own canvas and then composited. Surfaces and shader quads are skipped,
+/// since their contents aren't available to tests.
+pub(crate) fn rasterize_scene(
+    scene: &Scene,
+    size: Size<DevicePixels>,
+    sprite_atlas: &TestAtlas,
+) -> RgbaImage {
+    // The canvases of the offscreen layers being painted are pushed on top of the window's.
+    let mut canvases = vec![Canvas::new(size)];
+    let mut layer_sprites = Vec::new();
+    for operation in &scene.paint_operations {
+        let primitive = match operation {
+            PaintOperation::Primitive(primitive) => primitive,
+            PaintOperation::StartOffscreenLayer(sprite) => {
+                canvases.push(Canvas::new(size));
+                layer_sprites.push(sprite);
+                continue;
+            }
+            PaintOperation::EndOffscreenLayer => {
+                if let (Some(layer), Some(sprite)) = (canvases.pop(), layer_sprites.pop()) {
+                    canvases.last_mut().unwrap().draw_layer(sprite, &layer);
+                }
+                continue;
+            }
+            PaintOperation::StartLayer(_) | PaintOperation::EndLayer => continue,
+        };
+        let canvas = canvases.last_mut().unwrap();
+        match primitive {
+            Primitive::Shadow(shadow) => canvas.draw_shadow(shadow),
+            Primitive::Quad(quad) => canvas.draw_quad(quad),
+            Primitive::Path(path) => canvas.draw_path(path),
+            Primitive::Underline(underline) => canvas.draw_underline(underline),
+            Primitive::MonochromeSprite(sprite) => {
+                if let Some(contents) = sprite_atlas.tile_contents(sprite.tile.tile_id) {
+                    canvas.draw_monochrome_sprite(sprite, &contents);
+                }
+            }
+            Primitive::PolychromeSprite(sprite) => {
+                if let Some(contents) = sprite_atlas.tile_contents(sprite.tile.tile_id) {
+                    canvas.draw_polychrome_sprite(sprite, &contents);
+                }
+            }
+            Primitive::Surface(_) | Primitive::ShaderQuad(_) => {}
+        }
+    }
+    canvases.swap_remove(0).into_image()
+}
+
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<Rgba>,
+}
+
+impl Canvas {
+    fn new(size: Size<DevicePixels>) -> Self {
+        let width = size.width.0.max(0) as u32;
+        let height = size.height.0.max(0) as u32;
+        let transparent = Rgba {
+            r: 0.,
+            g: 0.,
+            b: 0.,
+            a: 0.,
+        };
+        Self {
+            width,
+            height,
+            pixels: vec![transparent; (width * height) as usize],
+        }
+    }
+
+    fn draw_shadow(&mut self, shadow: &Shadow) {
+        let color = shadow.color.to_rgb();
+        let blur_radius = shadow.blur_radius.0.max(0.5);
+        let rect = Rect::from_bounds(&shadow.bounds);
+        let radii = corner_radii(&shadow.corner_radii);
+        let clip = Rect::from_bounds(&shadow.content_mask.bounds);
+        self.fill(rect.dilate(blur_radius), clip, |x, y| {
+            let distance = rect.rounded_distance(x, y, radii);
+            Some((color, (0.5 - distance / blur_radius).clamp(0., 1.)))
+        });
+    }
+
+    fn draw_quad(&mut self, quad: &Quad) {
+        let background = background_color(&quad.background);
+        let border_color = quad.border_color.to_rgb();
+        let outer = Rect::from_bounds(&quad.bounds);
+        let inner = outer.inset(&quad.border_widths);
+        let outer_radii = corner_radii(&quad.corner_radii);
+        let max_border = quad
+            .border_widths
+            .top
+            .0
+            .max(quad.border_widths.right.0)
+            .max(quad.border_widths.bottom.0)
+            .max(quad.border_widths.left.0);
+        let inner_radii = outer_radii.map(|radius| (radius - max_border).max(0.));
+        let clip = Rect::from_bounds(&quad.content_mask.bounds);
+        self.fill(outer, clip, |x, y| {
+            let coverage = edge_coverage(outer.rounded_distance(x, y, outer_radii));
+            if max_border <= 0. {
+                return Some((background, coverage));
+            }
+            let inner_coverage = edge_coverage(inner.rounded_distance(x, y, inner_radii));
+            Some((mix(border_color, background, inner_coverage), coverage))
+        });
+    }
+
+    fn draw_underline(&mut self, underline: &Underline) {
+        let color = underline.color.to_rgb();
+        let rect = Rect::from_bounds(&underline.bounds);
+        let clip = Rect::from_bounds(&underline.content_mask.bounds);
+        self.fill(rect, clip, |x, y| {
+            Some((color, edge_coverage(rect.rounded_distance(x, y, [0.; 4]))))
+        });
+    }
+
+    fn draw_path(&mut self, path: &Path<ScaledPixels>) {
+        let color = background_color(&path.color);
+        let clip = Rect::from_bounds(&path.content_mask.bounds);
+        for triangle in path.vertices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|ix| {
+                let position = triangle[ix].xy_position;
+                (position.x.0, position.y.0)
+            });
+            let bounds = Rect {
+                left: a.0.min(b.0).min(c.0),
+                top: a.1.min(b.1).min(c.1),
+                right: a.0.max(b.0).max(c.0),
+                bottom: a.1.max(b.1).max(c.1),
+            };
+            self.fill(bounds, clip, |x, y| {
+                let d0 = edge_function(a, b, (x, y));
+                let d1 = edge_function(b, c, (x, y));
+                let d2 = edge_function(c, a, (x, y));
+                let inside =
+                    (d0 >= 0. && d1 >= 0. && d2 >= 0.) || (d0 <= 0. && d1 <= 0. && d2 <= 0.);
+                inside.then_some((color, 1.))
+            });
+        }
+    }
+
+    fn draw_monochrome_sprite(&mut self, sprite: &MonochromeSprite, contents: &TestTileContents) {
+        let color = sprite.color.to_rgb();
+        let rect = Rect::from_bounds(&sprite.bounds);
+        let clip = Rect::from_bounds(&sprite.content_mask.bounds);
+        let [[a, b], [c, d]] = sprite.transformation.rotation_scale;
+        let [translate_x, translate_y] = sprite.transformation.translation;
+        let determinant = a * d - b * c;
+        if determinant == 0. {
+            return;
+        }
+
+        // Sprites are clipped before they're transformed, so each pixel is mapped back into the
+        // sprite's own space to be clipped and sampled.
+        let corners = [
+            (rect.left, rect.top),
+            (rect.right, rect.top),
+            (rect.right, rect.bottom),
+            (rect.left, rect.bottom),
+        ]
+        .map(|(x, y)| (a * x + b * y + translate_x, c * x + d * y + translate_y));
+        let transformed = Rect {
+            left: corners
+                .iter()
+                .map(|corner| corner.0)
+                .fold(f32::MAX, f32::min),
+            top: corners
+                .iter()
+                .map(|corner| corner.1)
+                .fold(f32::MAX, f32::min),
+            right: corners
+                .iter()
+                .map(|corner| corner.0)
+                .fold(f32::MIN, f32::max),
+            bottom: corners
+                .iter()
+                .map(|corner| corner.1)
+                .fold(f32::MIN, f32::max),
+        };
+        self.fill(transformed, transformed, |x, y| {
+            let (x, y) = (x - translate_x, y - translate_y);
+            let (x, y) = ((d * x - b * y) / determinant, (a * y - c * x) / determinant);
+            if !rect.contains(x, y) || !clip.contains(x, y) {
+                return None;
+            }
+            let alpha = sample_tile(contents, rect, x, y)?[3];
+            Some((color, alpha))
+        });
+    }
+
+    fn draw_polychrome_sprite(&mut self, sprite: &PolychromeSprite, contents: &TestTileContents) {
+        let rect = Rect::from_bounds(&sprite.bounds);
+        let clip = Rect::from_bounds(&sprite.content_mask.bounds);
+        let radii = corner_radii(&sprite.corner_radii);
+        self.fill(rect, clip, |x, y| {
+            let [r, g, b, a] = sample_tile(contents, rect, x, y)?;
+            let mut color = Rgba { r, g, b, a };
+            if sprite.grayscale {
+                let gray = 0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b;
+                color.r = gray;
+                color.g = gray;
+                color.b = gray;
+            }
+            let color = apply_brightness(color, sprite.brightness);
+            let coverage = edge_coverage(rect.rounded_distance(x, y, radii));
+            Some((color, sprite.opacity * coverage))
+        });
+    }
+
+    /// Composites an offscreen layer's canvas, blurring it with a gaussian whose standard
+    /// deviation is half the blur radius, like shadows.
+    fn draw_layer(&mut self, sprite: &LayerSprite, layer: &Canvas) {
+        let rect = Rect::from_bounds(&sprite.bounds);
+        let clip = Rect::from_bounds(&sprite.content_mask.bounds);
+        let sigma = sprite.blur_radius.0 / 2.;
+        let reach = (sigma * 3.).ceil() as i32;
+        self.fill(rect.dilate(sigma * 3.), clip, |x, y| {
+            // Colors are averaged with premultiplied alpha, so transparent pixels don't darken
+            // the result.
+            let (mut r, mut g, mut b, mut a, mut total_weight) = (0., 0., 0., 0., 0.);
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    let weight = if sigma > 0. {
+                        (-((dx * dx + dy * dy) as f32) / (2. * sigma * sigma)).exp()
+                    } else {
+                        1.
+                    };
+                    total_weight += weight;
+                    let (x, y) = (x + dx as f32, y + dy as f32);
+                    if !rect.contains(x, y) {
+                        continue;
+                    }
+                    if let Some(pixel) = layer.pixel(x, y) {
+                        r += pixel.r * pixel.a * weight;
+                        g += pixel.g * pixel.a * weight;
+                        b += pixel.b * pixel.a * weight;
+                        a += pixel.a * weight;
+                    }
+                }
+            }
+            if a <= 0. {
+                return None;
+            }
+            let color = Rgba {
+                r: r / a,
+                g: g / a,
+                b: b / a,
+                a: a / total_weight,
+            };
+            Some((color, sprite.opacity))
+        })
+    }
+
+    /// The pixel whose area contains the given point, if it's on the canvas.
+    fn pixel(&self, x: f32, y: f32) -> Option<Rgba> {
+        if x < 0. || y < 0. || x >= self.width as f32 || y >= self.height as f32 {
+            return None;
+        }
+        Some(self.pixels[y as usize * self.width as usize + x as usize])
+    }
+
+    /// Blends the color returned by `pixel` into every pixel whose center lies within
+    /// both `rect` and `clip`.
+    fn fill(
+        &mut self,
+        rect: Rect,
+        clip: Rect,
+        mut pixel: impl FnMut(f32, f32) -> Option<(Rgba, f32)>,
+    ) {
+        let area = rect.intersect(clip);
+        let left = area.left.floor().clamp(0., self.width as f32) as u32;
+        let right = area.right.ceil().clamp(0., self.width as f32) as u32;
+        let top = area.top.floor().clamp(0., self.height as f32) as u32;
+        let bottom = area.bottom.ceil().clamp(0., self.height as f32) as u32;
+        for y in top..bottom {
+            for x in left..right {
+                let (center_x, center_y) = (x as f32 + 0.5, y as f32 + 0.5);
+                if !clip.contains(center_x, center_y) {
+                    continue;
+                }
+                if let Some((color, coverage)) = pixel(center_x, center_y) {
+                    self.blend(x, y, color, coverage);
+                }
+            }
+        }
+    }
+
+    fn blend(&mut self, x: u32, y: u32, color: Rgba, coverage: f32) {
+        let alpha = color.a * coverage;
+        if alpha <= 0. {
+            return;
+        }
+        let destination = &mut self.pixels[(y * self.width + x) as usize];
+        let destination_alpha = destination.a * (1. - alpha);
+        let alpha_out = alpha + destination_alpha;
+        destination.r = (color.r * alpha + destination.r * destination_alpha) / alpha_out;
+        destination.g = (color.g * alpha + destination.g * destination_alpha) / alpha_out;
+        destination.b = (color.b * alpha + destination.b * destination_alpha) / alpha_out;
+        destination.a = alpha_out;
+    }
+
+    fn into_image(self) -> RgbaImage {
+        let to_byte = |channel: f32| (channel.clamp(0., 1.) * 255.).round() as u8;
+        RgbaImage::from_fn(self.width, self.height, |x, y| {
+            let pixel = self.pixels[(y * self.width + x) as usize];
+            image::Rgba([
+                to_byte(pixel.r),
+                to_byte(pixel.g),
+                to_byte(pixel.b),
+                to_byte(pixel.a),
+            ])
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Rect {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+}
+
+impl Rect {
+    fn from_bounds(bounds: &Bounds<ScaledPixels>) -> Self {
+        Self {
+            left: bounds.origin.x.0,
+            top: bounds.origin.y.0,
+            right: bounds.origin.x.0 + bounds.size.width.0,
+            bottom: bounds.origin.y.0 + bounds.size.height.0,
+        }
+    }
+
+    fn intersect(self, other: Self) -> Self {
+        Self {
+            left: self.left.max(other.left),
+            top: self.top.max(other.top),
+            right: self.right.min(other.right),
+            bottom: self.bottom.min(other.bottom),
+        }
+    }
+
+    fn inset(self, edges: &Edges<ScaledPixels>) -> Self {
+        Self {
+            left: self.left + edges.left.0,
+            top: self.top + edges.top.0,
+            right: self.right - edges.right.0,
+            bottom: self.bottom - edges.bottom.0,
+        }
+    }
+
+    fn dilate(self, amount: f32) -> Self {
+        Self {
+            left: self.left - amount,
+            top: self.top - amount,
+            right: self.right + amount,
+            bottom: self.bottom + amount,
+        }
+    }
+
+    fn contains(self, x: f32, y: f32) -> bool {
+        x >= self.left && x < self.right && y >= self.top && y < self.bottom
+    }
+
+    /// Signed distance from the point to the edge of this rectangle with rounded corners,
+    /// given as `[top_left, top_right, bottom_right, bottom_left]`. Negative inside.
+    fn rounded_distance(self, x: f32, y: f32, radii: [f32; 4]) -> f32 {
+        let half_width = (self.right - self.left) / 2.;
+        let half_height = (self.bottom - self.top) / 2.;
+        let dx = x - (self.left + half_width);
+        let dy = y - (self.top + half_height);
+        let radius = match (dx < 0., dy < 0.) {
+            (true, true) => radii[0],
+            (false, true) => radii[1],
+            (false, false) => radii[2],
+            (true, false) => radii[3],
+        };
+        let edge_x = dx.abs() - half_width + radius;
+        let edge_y = dy.abs() - half_height + radius;
+        let outside = edge_x.max(0.).hypot(edge_y.max(0.));
+        let inside = edge_x.max(edge_y).min(0.);
+        outside + inside - radius
+    }
+}
+
+fn corner_radii(corners: &Corners<ScaledPixels>) -> [f32; 4] {
+    [
+        corners.top_left.0,
+        corners.top_right.0,
+        corners.bottom_right.0,
+        corners.bottom_left.0,
+    ]
+}
+
+fn background_color(background: &Background) -> Rgba {
+    match background.tag {
+        BackgroundTag::Solid | BackgroundTag::PatternSlash => background.solid.to_rgb(),
+        BackgroundTag::LinearGradient => mix(
+            background.colors[0].color.to_rgb(),
+            background.colors[1].color.to_rgb(),
+            0.5,
+        ),
+    }
+}
+
+/// Samples the tile pixel nearest to the given point of a sprite drawn over `rect`, as RGBA.
+/// Monochrome tiles only have an alpha channel.
+fn sample_tile(contents: &TestTileContents, rect: Rect, x: f32, y: f32) -> Option<[f32; 4]> {
+    let width = contents.size.width.0.max(0) as usize;
+    let height = contents.size.height.0.max(0) as usize;
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let column = ((x - rect.left) / (rect.right - rect.left) * width as f32) as usize;
+    let row = ((y - rect.top) / (rect.bottom - rect.top) * height as f32) as usize;
+    let ix = row.min(height - 1) * width + column.min(width - 1);
+    let to_channel = |byte: &u8| *byte as f32 / 255.;
+    match contents.kind {
+        AtlasTextureKind::Monochrome => {
+            let alpha = to_channel(contents.bytes.get(ix)?);
+            Some([1., 1., 1., alpha])
+        }
+        AtlasTextureKind::Polychrome | AtlasTextureKind::Path => {
+            let [b, g, r, a] = contents.bytes.get(ix * 4..ix * 4 + 4)? else {
+                return None;
+            };
+            Some([r, g, b, a].map(to_channel))
+        }
+    }
+}
+
+/// Scales the light of a color like the shaders do. Test windows don't render in HDR, so colors
+/// brighter than SDR white are scaled down to it, keeping their hue.
+fn apply_brightness(color: Rgba, brightness: f32) -> Rgba {
+    if brightness == 1. {
+        return color;
+    }
+    let [r, g, b] =
+        [color.r, color.g, color.b].map(|channel| channel.max(0.).powf(2.2) * brightness);
+    let scale = 1. / r.max(g).max(b).max(1.);
+    let [r, g, b] = [r, g, b].map(|channel| (channel * scale).powf(1. / 2.2));
+    Rgba {
+        r,
+        g,
+        b,
+        a: color.a,
+    }
+}
+
+fn edge_coverage(distance: f32) -> f32 {
+    (0.5 - distance).clamp(0., 1.)
+}
+
+fn edge_function(a: (f32, f32), b: (f32, f32), point: (f32, f32)) -> f32 {
+    (b.0 - a.0) * (point.1 - a.1) - (b.1 - a.1) * (point.0 - a.0)
+}
+
+fn mix(from: Rgba, to: Rgba, amount: f32) -> Rgba {
+    Rgba {
+        r: from.r + (to.r - from.r) * amount,
+        g: from.g + (to.g - from.g) * amount,
+        b: from.b + (to.b - from.b) * amount,
+        a: from.a + (to.a - from.a) * amount,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        self as gpui, bounds, div, point, px, rgb, size, AtlasKey, ContentMask, Context, Hsla,
+        ImageId, InteractiveElement as _, IntoElement, ParentElement as _, PlatformAtlas as _,
+        Render, RenderImageParams, RenderSvgParams, Styled, TestAppContext, TransformationMatrix,
+        Window,
+    };
+    use std::borrow::Cow;
+
+    struct Swatch;
+
+    impl Render for Swatch {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .size_full()
+                .bg(rgb(0x0000ff))
+                .child(div().id("swatch").size(px(10.)).bg(rgb(0xff0000)))
+        }
+    }
+
+    #[gpui::test]
+    fn test_rasterize_window(cx: &mut TestAppContext) {
+        let (_, cx) = cx.add_window_view(|_, _| Swatch);
+
+        // The test window has a scale factor of 2, so the red swatch covers 20x20 pixels.
+        let image = cx.rasterize_window();
+        assert_eq!(image.get_pixel(5, 5).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(19, 19).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(20, 20).0, [0, 0, 255, 255]);
+
+        let swatch = cx.update(|window, _| {
+            window
+                .capture_bounds(bounds(point(px(0.), px(0.)), size(px(10.), px(10.))))
+                .unwrap()
+        });
+        assert_eq!(swatch.dimensions(), (20, 20));
+        assert!(swatch.pixels().all(|pixel| pixel.0 == [255, 0, 0, 255]));
+
+        let swatch = cx.update(|window, cx| window.capture_element("swatch", cx).unwrap());
+        assert_eq!(swatch.dimensions(), (20, 20));
+        assert!(swatch.pixels().all(|pixel| pixel.0 == [255, 0, 0, 255]));
+        assert!(cx
+            .update(|window, cx| window.capture_element("missing", cx))
+            .is_err());
+    }
+
+    #[test]
+    fn test_rasterize_monochrome_sprite() {
+        let atlas = TestAtlas::new();
+        let key = AtlasKey::Svg(RenderSvgParams {
+            path: "icon.svg".into(),
+            size: size(DevicePixels(2), DevicePixels(1)),
+        });
+        // The left pixel of the tile is opaque and the right one is transparent.
+        let tile = atlas
+            .get_or_insert_with(&key, &mut || {
+                Ok(Some((
+                    size(DevicePixels(2), DevicePixels(1)),
+                    Cow::Borrowed(&[255, 0]),
+                )))
+            })
+            .unwrap()
+            .unwrap();
+
+        let bounds = Bounds {
+            origin: point(ScaledPixels(1.), ScaledPixels(1.)),
+            size: size(ScaledPixels(2.), ScaledPixels(1.)),
+        };
+        let mut scene = Scene::default();
+        scene.insert_primitive(MonochromeSprite {
+            order: 0,
+            pad: 0,
+            bounds,
+            content_mask: ContentMask { bounds },
+            color: Hsla::white(),
+            tile,
+            transformation: TransformationMatrix::unit(),
+        });
+        scene.finish();
+
+        let image = rasterize_scene(&scene, size(DevicePixels(4), DevicePixels(3)), &atlas);
+        assert_eq!(image.get_pixel(1, 1).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(2, 1).0[3], 0);
+        assert_eq!(image.get_pixel(0, 0).0[3], 0);
+    }
+
+    #[test]
+    fn test_rasterize_image_brightness() {
+        let atlas = TestAtlas::new();
+        let key = AtlasKey::Image(RenderImageParams {
+            image_id: ImageId(0),
+            frame_index: 0,
+            downscaled_size: None,
+        });
+        // A gray pixel and an orange one, in BGRA.
+        let tile = atlas
+            .get_or_insert_with(&key, &mut || {
+                Ok(Some((
+                    size(DevicePixels(2), DevicePixels(1)),
+                    Cow::Borrowed(&[128, 128, 128, 255, 0, 80, 200, 255]),
+                )))
+            })
+            .unwrap()
+            .unwrap();
+        let rasterize = |brightness: f32| {
+            let bounds = Bounds {
+                origin: point(ScaledPixels(0.), ScaledPixels(0.)),
+                size: size(ScaledPixels(2.), ScaledPixels(1.)),
+            };
+            let mut scene = Scene::default();
+            scene.insert_primitive(PolychromeSprite {
+                order: 0,
+                brightness,
+                grayscale: false,
+                opacity: 1.,
+                bounds,
+                content_mask: ContentMask { bounds },
+                corner_radii: Default::default(),
+                tile: tile.clone(),
+            });
+            scene.finish();
+            rasterize_scene(&scene, size(DevicePixels(2), DevicePixels(1)), &atlas)
+        };
+
+        let image = rasterize(1.);
+        assert_eq!(image.get_pixel(0, 0).0, [128, 128, 128, 255]);
+        assert_eq!(image.get_pixel(1, 0).0, [200, 80, 0, 255]);
+
+        // Doubling the light of the gray pixel brightens it less than twice as much in sRGB.
+        let image = rasterize(2.);
+        assert_eq!(image.get_pixel(0, 0).0, [175, 175, 175, 255]);
+
+        // Colors brighter than SDR white are scaled down to it, keeping their hue.
+        let image = rasterize(8.);
+        assert_eq!(image.get_pixel(0, 0).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(1, 0).0, [255, 102, 0, 255]);
+    }
+
+    #[test]
+    fn test_rasterize_offscreen_layer() {
+        let atlas = TestAtlas::new();
+        let bounds = |x: f32, width: f32| Bounds {
+            origin: point(ScaledPixels(x), ScaledPixels(0.)),
+            size: size(ScaledPixels(width), ScaledPixels(1.)),
+        };
+        let quad = |x: f32, width: f32, color: u32| Quad {
+            bounds: bounds(x, width),
+            content_mask: ContentMask {
+                bounds: bounds(x, width),
+            },
+            background: Hsla::from(rgb(color)).into(),
+            ..Default::default()
+        };
+        let layer = |blur_radius: f32| LayerSprite {
+            order: 0,
+            pad: 0,
+            bounds: bounds(0., 3.),
+            content_mask: ContentMask {
+                bounds: bounds(0., 8.),
+            },
+            opacity: 0.5,
+            blur_radius: ScaledPixels(blur_radius),
+        };
+
+        // The quads are faded together, so the red one doesn't show through the blue one where
+        // they overlap, and what's painted outside of the layer's bounds is clipped.
+        let mut scene = Scene::default();
+        scene.push_offscreen_layer(layer(0.));
+        scene.insert_primitive(quad(0., 2., 0xff0000));
+        scene.insert_primitive(quad(1., 3., 0x0000ff));
+        scene.pop_offscreen_layer();
+        scene.finish();
+        let image = rasterize_scene(&scene, size(DevicePixels(8), DevicePixels(1)), &atlas);
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 128]);
+        assert_eq!(image.get_pixel(1, 0).0, [0, 0, 255, 128]);
+        assert_eq!(image.get_pixel(3, 0).0[3], 0);
+
+        // A blurred layer fades out past its bounds.
+        let mut scene = Scene::default();
+        scene.push_offscreen_layer(layer(2.));
+        scene.insert_primitive(quad(0., 3., 0x0000ff));
+        scene.pop_offscreen_layer();
+        scene.finish();
+        let image = rasterize_scene(&scene, size(DevicePixels(8), DevicePixels(1)), &atlas);
+        let alpha = |x: u32| image.get_pixel(x, 0).0[3];
+        assert!(alpha(1) > alpha(3) && alpha(3) > alpha(4) && alpha(4) > 0);
+        assert_eq!(alpha(6), 0);
+    }
+
+    #[gpui::test]
+    fn test_golden_image(cx: &mut TestAppContext) {
+        let (_, cx) = cx.add_window_view(|_, _| Swatch);
+        cx.simulate_resize(size(px(16.), px(16.)));
+        cx.run_until_parked();
+
+        cx.assert_golden_image(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/swatch.png"),
+            0.05,
+        );
+    }
+}
@@ -0,0 +1,426 @@
+use super::sibling_path;
+use crate::{
+    AtlasKey, AtlasTile, Background, BackgroundTag, Bounds, ContentMask, Corners, Edges, Hsla,
+    PaintOperation, Point, Primitive, Rgba, ScaledPixels, Scene,
+};
+use anyhow::Context as _;
+use std::path::Path;
+
+/// When this environment variable is set, scene snapshot assertions write the serialized scene
+/// as the new snapshot instead of comparing against it.
+const UPDATE_SCENE_SNAPSHOTS: &str = "UPDATE_SCENE_SNAPSHOTS";
+
+/// Serializes a [`Scene`] into a stable, line-based text format, so that tests can diff what a
+/// window draws without a GPU.
+///
+/// Every paint operation is written on its own line in paint order, with the contents of layers
+/// and offscreen layers indented. Coordinates are in device pixels rounded to hundredths, and colors are written as
+/// `#rrggbbaa`. Consecutive glyphs with the same font, size and color are written as one run.
+/// Sprites are described by the atlas key that `sprite_key` returns for their tile, since where
+/// a tile ends up in the atlas depends on what else has been rasterized.
+pub(crate) fn serialize_scene(
+    scene: &Scene,
+    sprite_key: &dyn Fn(&AtlasTile) -> Option<AtlasKey>,
+) -> String {
+    let mut writer = SceneWriter::default();
+    for operation in &scene.paint_operations {
+        match operation {
+            PaintOperation::StartLayer(bounds) => {
+                writer.line(format!("layer {}", bounds_to_string(bounds)));
+                writer.depth += 1;
+            }
+            PaintOperation::StartOffscreenLayer(sprite) => {
+                writer.line(format!(
+                    "offscreen layer {} opacity={} blur={}{}",
+                    bounds_to_string(&sprite.bounds),
+                    number(sprite.opacity),
+                    number(sprite.blur_radius.0),
+                    clip_to_string(&sprite.bounds, &sprite.content_mask)
+                ));
+                writer.depth += 1;
+            }
+            PaintOperation::EndLayer | PaintOperation::EndOffscreenLayer => {
+                writer.finish_glyph_run();
+                writer.depth = writer.depth.saturating_sub(1);
+            }
+            PaintOperation::Primitive(primitive) => writer.primitive(primitive, sprite_key),
+        }
+    }
+    writer.finish_glyph_run();
+    writer.output
+}
+
+#[derive(Default)]
+struct SceneWriter {
+    output: String,
+    depth: usize,
+    glyph_run: Option<GlyphRun>,
+}
+
+struct GlyphRun {
+    style: String,
+    glyphs: Vec<String>,
+}
+
+impl SceneWriter {
+    fn primitive(
+        &mut self,
+        primitive: &Primitive,
+        sprite_key: &dyn Fn(&AtlasTile) -> Option<AtlasKey>,
+    ) {
+        let clip = clip_to_string(primitive.bounds(), primitive.content_mask());
+        match primitive {
+            Primitive::Shadow(shadow) => self.line(format!(
+                "shadow {} blur={} radii={} color={}{}",
+                bounds_to_string(&shadow.bounds),
+                number(shadow.blur_radius.0),
+                corners_to_string(&shadow.corner_radii),
+                color_to_string(shadow.color),
+                clip
+            )),
+            Primitive::Quad(quad) => {
+                let mut line = format!(
+                    "quad {} background={}",
+                    bounds_to_string(&quad.bounds),
+                    background_to_string(&quad.background)
+                );
+                let widths = &quad.border_widths;
+                if [widths.top, widths.right, widths.bottom, widths.left]
+                    .iter()
+                    .any(|width| width.0 > 0.)
+                {
+                    line.push_str(&format!(
+                        " border={} {}",
+                        edges_to_string(widths),
+                        color_to_string(quad.border_color)
+                    ));
+                }
+                let radii = &quad.corner_radii;
+                if [
+                    radii.top_left,
+                    radii.top_right,
+                    radii.bottom_right,
+                    radii.bottom_left,
+                ]
+                .iter()
+                .any(|radius| radius.0 > 0.)
+                {
+                    line.push_str(&format!(" radii={}", corners_to_string(radii)));
+                }
+                line.push_str(&clip);
+                self.line(line)
+            }
+            Primitive::Path(path) => {
+                let vertices = path
+                    .vertices
+                    .iter()
+                    .map(|vertex| point_to_string(vertex.xy_position))
+                    .collect::<Vec<_>>();
+                self.line(format!(
+                    "path {} background={} vertices={}{}",
+                    bounds_to_string(&path.bounds),
+                    background_to_string(&path.color),
+                    vertices.join(" "),
+                    clip
+                ))
+            }
+            Primitive::Underline(underline) => {
+                let kind = match underline.kind {
+                    0 => "solid",
+                    1 => "wavy",
+                    2 => "dashed",
+                    3 => "dotted",
+                    _ => "double",
+                };
+                self.line(format!(
+                    "underline {} {} thickness={} color={}{}",
+                    kind,
+                    bounds_to_string(&underline.bounds),
+                    number(underline.thickness.0),
+                    color_to_string(underline.color),
+                    clip
+                ))
+            }
+            Primitive::MonochromeSprite(sprite) => match sprite_key(&sprite.tile) {
+                Some(AtlasKey::Glyph(params)) if clip.is_empty() => {
+                    let style = format!(
+                        "glyphs font={} size={} color={}",
+                        params.font_id.0,
+                        number(params.font_size.0),
+                        color_to_string(sprite.color)
+                    );
+                    let glyph = format!(
+                        "{}@{}",
+                        params.glyph_id.0,
+                        point_to_string(sprite.bounds.origin)
+                    );
+                    self.push_glyph(style, glyph);
+                }
+                Some(AtlasKey::Glyph(params)) => self.line(format!(
+                    "glyph {} font={} size={} {} color={}{}",
+                    params.glyph_id.0,
+                    params.font_id.0,
+                    number(params.font_size.0),
+                    bounds_to_string(&sprite.bounds),
+                    color_to_string(sprite.color),
+                    clip
+                )),
+                Some(AtlasKey::Svg(params)) => self.line(format!(
+                    "svg {} {} color={}{}",
+                    params.path,
+                    bounds_to_string(&sprite.bounds),
+                    color_to_string(sprite.color),
+                    clip
+                )),
+                Some(AtlasKey::Image(_)) | None => self.line(format!(
+                    "sprite {} color={}{}",
+                    bounds_to_string(&sprite.bounds),
+                    color_to_string(sprite.color),
+                    clip
+                )),
+            },
+            Primitive::PolychromeSprite(sprite) => {
+                let source = match sprite_key(&sprite.tile) {
+                    Some(AtlasKey::Glyph(params)) => format!(
+                        "emoji {} font={} size={}",
+                        params.glyph_id.0,
+                        params.font_id.0,
+                        number(params.font_size.0)
+                    ),
+                    // Image IDs aren't included, since they depend on how many images were
+                    // loaded before.
+                    Some(AtlasKey::Image(params)) => format!("image frame={}", params.frame_index),
+                    Some(AtlasKey::Svg(params)) => format!("svg {}", params.path),
+                    None => "sprite".to_string(),
+                };
+                let mut line = format!(
+                    "{} {} opacity={}",
+                    source,
+                    bounds_to_string(&sprite.bounds),
+                    number(sprite.opacity)
+                );
+                if sprite.grayscale {
+                    line.push_str(" grayscale");
+                }
+                if sprite.brightness != 1. {
+                    line.push_str(&format!(" brightness={}", number(sprite.brightness)));
+                }
+                line.push_str(&clip);
+                self.line(line)
+            }
+            Primitive::Surface(surface) => {
+                let mut line = format!("surface {}", bounds_to_string(&surface.bounds));
+                if surface.brightness != 1. {
+                    line.push_str(&format!(" brightness={}", number(surface.brightness)));
+                }
+                line.push_str(&clip);
+                self.line(line)
+            }
+            // Shader IDs aren't included, since they depend on how many shaders were created
+            // before.
+            Primitive::ShaderQuad(quad) => self.line(format!(
+                "shader quad {} opacity={}{}",
+                bounds_to_string(&quad.bounds),
+                number(quad.opacity),
+                clip
+            )),
+        }
+    }
+
+    fn push_glyph(&mut self, style: String, glyph: String) {
+        if let Some(run) = self.glyph_run.as_mut().filter(|run| run.style == style) {
+            run.glyphs.push(glyph);
+        } else {
+            self.finish_glyph_run();
+            self.glyph_run = Some(GlyphRun {
+                style,
+                glyphs: vec![glyph],
+            });
+        }
+    }
+
+    fn finish_glyph_run(&mut self) {
+        if let Some(run) = self.glyph_run.take() {
+            self.write_line(&format!("{}: {}", run.style, run.glyphs.join(" ")));
+        }
+    }
+
+    fn line(&mut self, line: String) {
+        self.finish_glyph_run();
+        self.write_line(&line);
+    }
+
+    fn write_line(&mut self, line: &str) {
+        for _ in 0..self.depth {
+            self.output.push_str("  ");
+        }
+        self.output.push_str(line);
+        self.output.push('\n');
+    }
+}
+
+/// Rounds to hundredths of a pixel, so that snapshots don't change with floating point noise.
+fn number(value: f32) -> String {
+    // Adding zero turns negative zero into zero.
+    format!("{}", (value * 100.).round() / 100. + 0.)
+}
+
+fn point_to_string(point: Point<ScaledPixels>) -> String {
+    format!("({}, {})", number(point.x.0), number(point.y.0))
+}
+
+fn bounds_to_string(bounds: &Bounds<ScaledPixels>) -> String {
+    format!(
+        "({}, {}, {}x{})",
+        number(bounds.origin.x.0),
+        number(bounds.origin.y.0),
+        number(bounds.size.width.0),
+        number(bounds.size.height.0)
+    )
+}
+
+fn corners_to_string(corners: &Corners<ScaledPixels>) -> String {
+    format!(
+        "({} {} {} {})",
+        number(corners.top_left.0),
+        number(corners.top_right.0),
+        number(corners.bottom_right.0),
+        number(corners.bottom_left.0)
+    )
+}
+
+fn edges_to_string(edges: &Edges<ScaledPixels>) -> String {
+    format!(
+        "({} {} {} {})",
+        number(edges.top.0),
+        number(edges.right.0),
+        number(edges.bottom.0),
+        number(edges.left.0)
+    )
+}
+
+fn color_to_string(color: Hsla) -> String {
+    let color = Rgba::from(color);
+    let [r, g, b, a] = [color.r, color.g, color.b, color.a]
+        .map(|channel| (channel.clamp(0., 1.) * 255.).round() as u8);
+    format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+}
+
+fn background_to_string(background: &Background) -> String {
+    match background.tag {
+        BackgroundTag::Solid => color_to_string(background.solid),
+        BackgroundTag::LinearGradient => {
+            let [from, to] = &background.colors;
+            format!(
+                "linear-gradient({}deg, {} {}, {} {})",
+                number(background.angle),
+                color_to_string(from.color),
+                number(from.percentage),
+                color_to_string(to.color),
+                number(to.percentage)
+            )
+        }
+        BackgroundTag::PatternSlash => {
+            format!("pattern-slash({})", color_to_string(background.solid))
+        }
+    }
+}
+
+/// Describes the content mask of a primitive, if it actually clips what the primitive draws.
+fn clip_to_string(
+    bounds: &Bounds<ScaledPixels>,
+    content_mask: &ContentMask<ScaledPixels>,
+) -> String {
+    if bounds.intersect(&content_mask.bounds) == *bounds {
+        String::new()
+    } else {
+        format!(" clip={}", bounds_to_string(&content_mask.bounds))
+    }
+}
+
+/// Compares a serialized scene against the snapshot at the given path, panicking with the first
+/// line that differs. On failure, the serialized scene is written next to the snapshot so it can
+/// be inspected.
+#[track_caller]
+pub(crate) fn assert_scene_snapshot(actual: &str, path: &Path) {
+    if std::env::var(UPDATE_SCENE_SNAPSHOTS).is_ok() {
+        std::fs::write(path, actual)
+            .with_context(|| format!("failed to write scene snapshot {:?}", path))
+            .unwrap();
+        return;
+    }
+
+    let expected = match std::fs::read_to_string(path) {
+        Ok(expected) => expected,
+        Err(error) => panic!(
+            "failed to read scene snapshot {:?}: {}. Run with {}=1 to create it.",
+            path, error, UPDATE_SCENE_SNAPSHOTS
+        ),
+    };
+    if expected == actual {
+        return;
+    }
+
+    std::fs::write(sibling_path(path, "actual"), actual).ok();
+    let line_ix = expected
+        .lines()
+        .zip(actual.lines())
+        .take_while(|(expected, actual)| expected == actual)
+        .count();
+    panic!(
+        "scene differs from snapshot {:?} at line {}:\n  expected: {}\n    actual: {}\n\
+         Run with {}=1 to accept the new scene.",
+        path,
+        line_ix + 1,
+        expected.lines().nth(line_ix).unwrap_or("<end of snapshot>"),
+        actual.lines().nth(line_ix).unwrap_or("<end of scene>"),
+        UPDATE_SCENE_SNAPSHOTS
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        self as gpui, div, linear_color_stop, linear_gradient, px, rgb, size, Context, IntoElement,
+        ParentElement as _, Render, Styled as _, TestAppContext, Window,
+    };
+
+    struct TestView;
+
+    impl Render for TestView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size_full().bg(rgb(0xffffff)).child(
+                div()
+                    .absolute()
+                    .top(px(10.))
+                    .left(px(20.))
+                    .size(px(30.))
+                    .rounded(px(4.))
+                    .border_1()
+                    .border_color(rgb(0xff0000))
+                    .bg(linear_gradient(
+                        90.,
+                        linear_color_stop(rgb(0x000000), 0.),
+                        linear_color_stop(rgb(0x0000ff), 1.),
+                    )),
+            )
+        }
+    }
+
+    #[gpui::test]
+    fn test_scene_snapshot(cx: &mut TestAppContext) {
+        let window = cx.add_window(|_, _| TestView);
+        let cx = &mut gpui::VisualTestContext::from_window(*window, cx);
+        cx.simulate_resize(size(px(100.), px(80.)));
+        cx.run_until_parked();
+
+        assert_eq!(
+            cx.scene_snapshot(),
+            // The test window has a scale factor of 2.
+            "quad (0, 0, 200x160) background=#ffffffff\n\
+             quad (40, 20, 60x60) background=linear-gradient(90deg, #000000ff 0, #0000ffff 1) \
+             border=(2 2 2 2) #ff0000ff radii=(8 8 8 8)\n"
+        );
+    }
+}
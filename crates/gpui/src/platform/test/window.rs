@@ -1,8 +1,8 @@
 use crate::{
-    AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DispatchEventResult, GpuSpecs,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, RequestFrameOptions, ScaledPixels, Size, TestPlatform, TileId, WindowAppearance,
-    WindowBackgroundAppearance, WindowBounds, WindowParams,
+    rasterize_scene, AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTextureKind, AtlasTile,
+    Bounds, DispatchEventResult, GpuSpecs, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
+    PlatformInputHandler, PlatformWindow, Point, RequestFrameOptions, ScaledPixels, Size,
+    TestPlatform, TileId, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowParams,
 };
 use collections::HashMap;
 use parking_lot::Mutex;
@@ -19,15 +19,19 @@ pub(crate) struct TestWindowState {
     pub(crate) title: Option<String>,
     pub(crate) edited: bool,
     platform: Weak<TestPlatform>,
-    sprite_atlas: Arc<dyn PlatformAtlas>,
+    sprite_atlas: Arc<TestAtlas>,
     pub(crate) should_close_handler: Option<Box<dyn FnMut() -> bool>>,
     input_callback: Option<Box<dyn FnMut(PlatformInput) -> DispatchEventResult>>,
     active_status_change_callback: Option<Box<dyn FnMut(bool)>>,
     hover_status_change_callback: Option<Box<dyn FnMut(bool)>>,
     resize_callback: Option<Box<dyn FnMut(Size<Pixels>, f32)>>,
     moved_callback: Option<Box<dyn FnMut()>>,
+    occlusion_change_callback: Option<Box<dyn FnMut(bool)>>,
+    request_frame_callback: Option<Box<dyn FnMut(RequestFrameOptions)>>,
     input_handler: Option<PlatformInputHandler>,
     is_fullscreen: bool,
+    is_maximized: bool,
+    is_minimized: bool,
 }
 
 #[derive(Clone)]
@@ -37,7 +41,8 @@ impl HasWindowHandle for TestWindow {
     fn window_handle(
         &self,
     ) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
-        unimplemented!("Test Windows are not backed by a real platform window")
+        // Test windows are not backed by a real platform window
+        Err(raw_window_handle::HandleError::NotSupported)
     }
 }
 
@@ -45,7 +50,8 @@ impl HasDisplayHandle for TestWindow {
     fn display_handle(
         &self,
     ) -> Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError> {
-        unimplemented!("Test Windows are not backed by a real platform window")
+        // Test windows are not backed by a real platform window
+        Err(raw_window_handle::HandleError::NotSupported)
     }
 }
 
@@ -70,8 +76,12 @@ impl TestWindow {
             hover_status_change_callback: None,
             resize_callback: None,
             moved_callback: None,
+            occlusion_change_callback: None,
+            request_frame_callback: None,
             input_handler: None,
             is_fullscreen: false,
+            is_maximized: false,
+            is_minimized: false,
         })))
     }
 
@@ -97,6 +107,30 @@ impl TestWindow {
         self.0.lock().active_status_change_callback = Some(callback);
     }
 
+    /// Minimizes or restores the window, notifying it the way Linux platforms do, through a change
+    /// in whether it can be seen.
+    pub(crate) fn simulate_minimized(&self, minimized: bool) {
+        let mut lock = self.0.lock();
+        lock.is_minimized = minimized;
+        let Some(mut callback) = lock.occlusion_change_callback.take() else {
+            return;
+        };
+        drop(lock);
+        callback(minimized);
+        self.0.lock().occlusion_change_callback = Some(callback);
+    }
+
+    /// Runs the window's frame callback, as the platform does when the display refreshes.
+    pub(crate) fn simulate_frame(&self) {
+        let mut lock = self.0.lock();
+        let Some(mut callback) = lock.request_frame_callback.take() else {
+            return;
+        };
+        drop(lock);
+        callback(RequestFrameOptions::default());
+        self.0.lock().request_frame_callback = Some(callback);
+    }
+
     pub fn simulate_input(&mut self, event: PlatformInput) -> bool {
         let mut lock = self.0.lock();
         let Some(mut callback) = lock.input_callback.take() else {
@@ -115,11 +149,19 @@ impl PlatformWindow for TestWindow {
     }
 
     fn window_bounds(&self) -> WindowBounds {
-        WindowBounds::Windowed(self.bounds())
+        if self.is_maximized() {
+            WindowBounds::Maximized(self.bounds())
+        } else {
+            WindowBounds::Windowed(self.bounds())
+        }
     }
 
     fn is_maximized(&self) -> bool {
-        false
+        self.0.lock().is_maximized
+    }
+
+    fn is_minimized(&self) -> bool {
+        self.0.lock().is_minimized
     }
 
     fn content_size(&self) -> Size<Pixels> {
@@ -205,11 +247,12 @@ impl PlatformWindow for TestWindow {
     }
 
     fn minimize(&self) {
-        unimplemented!()
+        self.0.lock().is_minimized = true;
     }
 
     fn zoom(&self) {
-        unimplemented!()
+        let mut lock = self.0.lock();
+        lock.is_maximized = !lock.is_maximized;
     }
 
     fn toggle_fullscreen(&self) {
@@ -221,7 +264,9 @@ impl PlatformWindow for TestWindow {
         self.0.lock().is_fullscreen
     }
 
-    fn on_request_frame(&self, _callback: Box<dyn FnMut(RequestFrameOptions)>) {}
+    fn on_request_frame(&self, callback: Box<dyn FnMut(RequestFrameOptions)>) {
+        self.0.lock().request_frame_callback = Some(callback)
+    }
 
     fn on_input(&self, callback: Box<dyn FnMut(crate::PlatformInput) -> DispatchEventResult>) {
         self.0.lock().input_callback = Some(callback)
@@ -243,6 +288,10 @@ impl PlatformWindow for TestWindow {
         self.0.lock().moved_callback = Some(callback)
     }
 
+    fn on_occlusion_change(&self, callback: Box<dyn FnMut(bool)>) {
+        self.0.lock().occlusion_change_callback = Some(callback)
+    }
+
     fn on_should_close(&self, callback: Box<dyn FnMut() -> bool>) {
         self.0.lock().should_close_handler = Some(callback);
     }
@@ -253,6 +302,12 @@ impl PlatformWindow for TestWindow {
 
     fn draw(&self, _scene: &crate::Scene) {}
 
+    fn capture_frame(&self, scene: &crate::Scene) -> anyhow::Result<image::RgbaImage> {
+        let size = self.bounds().size.to_device_pixels(self.scale_factor());
+        let sprite_atlas = self.0.lock().sprite_atlas.clone();
+        Ok(rasterize_scene(scene, size, &sprite_atlas))
+    }
+
     fn sprite_atlas(&self) -> sync::Arc<dyn crate::PlatformAtlas> {
         self.0.lock().sprite_atlas.clone()
     }
@@ -270,9 +325,7 @@ impl PlatformWindow for TestWindow {
         unimplemented!()
     }
 
-    fn start_window_move(&self) {
-        unimplemented!()
-    }
+    fn start_window_move(&self) {}
 
     fn update_ime_position(&self, _bounds: Bounds<ScaledPixels>) {}
 
@@ -284,6 +337,16 @@ impl PlatformWindow for TestWindow {
 pub(crate) struct TestAtlasState {
     next_id: u32,
     tiles: HashMap<AtlasKey, AtlasTile>,
+    contents: HashMap<TileId, TestTileContents>,
+}
+
+/// The pixels of a tile, kept so that scenes can be rasterized on the CPU.
+#[derive(Clone)]
+pub(crate) struct TestTileContents {
+    pub size: Size<crate::DevicePixels>,
+    pub kind: AtlasTextureKind,
+    /// One alpha byte per pixel for monochrome tiles, or four BGRA bytes for polychrome ones.
+    pub bytes: Arc<[u8]>,
 }
 
 pub(crate) struct TestAtlas(Mutex<TestAtlasState>);
@@ -293,8 +356,13 @@ impl TestAtlas {
         TestAtlas(Mutex::new(TestAtlasState {
             next_id: 0,
             tiles: HashMap::default(),
+            contents: HashMap::default(),
         }))
     }
+
+    pub(crate) fn tile_contents(&self, tile_id: TileId) -> Option<TestTileContents> {
+        self.0.lock().contents.get(&tile_id).cloned()
+    }
 }
 
 impl PlatformAtlas for TestAtlas {
@@ -311,7 +379,7 @@ impl PlatformAtlas for TestAtlas {
         }
         drop(state);
 
-        let Some((size, _)) = build()? else {
+        let Some((size, bytes)) = build()? else {
             return Ok(None);
         };
 
@@ -326,7 +394,7 @@ impl PlatformAtlas for TestAtlas {
             crate::AtlasTile {
                 texture_id: AtlasTextureId {
                     index: texture_id,
-                    kind: crate::AtlasTextureKind::Path,
+                    kind: key.texture_kind(),
                 },
                 tile_id: TileId(tile_id),
                 padding: 0,
@@ -336,12 +404,36 @@ impl PlatformAtlas for TestAtlas {
                 },
             },
         );
+        state.contents.insert(
+            TileId(tile_id),
+            TestTileContents {
+                size,
+                kind: key.texture_kind(),
+                bytes: bytes.into_owned().into(),
+            },
+        );
 
         Ok(Some(state.tiles[key].clone()))
     }
 
     fn remove(&self, key: &AtlasKey) {
         let mut state = self.0.lock();
-        state.tiles.remove(key);
+        if let Some(tile) = state.tiles.remove(key) {
+            state.contents.remove(&tile.tile_id);
+        }
+    }
+
+    fn retain(&self, predicate: &mut dyn FnMut(&AtlasKey) -> bool) {
+        let state = &mut *self.0.lock();
+        let contents = &mut state.contents;
+        state.tiles.retain(|key, tile| {
+            let retain = predicate(key);
+            if !retain {
+                contents.remove(&tile.tile_id);
+            }
+            retain
+        });
     }
+
+    fn set_page_size(&self, _size: Size<crate::DevicePixels>) {}
 }
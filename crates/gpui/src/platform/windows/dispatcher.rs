@@ -21,7 +21,7 @@ use windows::{
     },
 };
 
-use crate::{PlatformDispatcher, TaskLabel, WM_GPUI_TASK_DISPATCHED_ON_MAIN_THREAD};
+use crate::{PlatformDispatcher, Priority, TaskLabel, WM_GPUI_TASK_DISPATCHED_ON_MAIN_THREAD};
 
 pub(crate) struct WindowsDispatcher {
     main_sender: Sender<Runnable>,
@@ -49,7 +49,7 @@ impl WindowsDispatcher {
         }
     }
 
-    fn dispatch_on_threadpool(&self, runnable: Runnable) {
+    fn dispatch_on_threadpool(&self, runnable: Runnable, priority: Priority) {
         let handler = {
             let mut task_wrapper = Some(runnable);
             WorkItemHandler::new(move |_| {
@@ -57,12 +57,14 @@ impl WindowsDispatcher {
                 Ok(())
             })
         };
-        ThreadPool::RunWithPriorityAndOptionsAsync(
-            &handler,
-            WorkItemPriority::High,
-            WorkItemOptions::TimeSliced,
-        )
-        .log_err();
+        // Tasks have always run with high priority, so that stays the default. The thread pool
+        // has no priority above it, so urgent tasks share it.
+        let priority = match priority {
+            Priority::UserBlocking | Priority::Default => WorkItemPriority::High,
+            Priority::Background => WorkItemPriority::Low,
+        };
+        ThreadPool::RunWithPriorityAndOptionsAsync(&handler, priority, WorkItemOptions::TimeSliced)
+            .log_err();
     }
 
     fn dispatch_on_threadpool_after(&self, runnable: Runnable, duration: Duration) {
@@ -87,8 +89,8 @@ impl PlatformDispatcher for WindowsDispatcher {
         current().id() == self.main_thread_id
     }
 
-    fn dispatch(&self, runnable: Runnable, label: Option<TaskLabel>) {
-        self.dispatch_on_threadpool(runnable);
+    fn dispatch(&self, runnable: Runnable, label: Option<TaskLabel>, priority: Priority) {
+        self.dispatch_on_threadpool(runnable, priority);
         if let Some(label) = label {
             log::debug!("TaskLabel: {label:?}");
         }
@@ -209,6 +209,33 @@ impl PlatformDisplay for WindowsDisplay {
     fn bounds(&self) -> Bounds<Pixels> {
         self.bounds
     }
+
+    fn visible_bounds(&self) -> Bounds<Pixels> {
+        let Some(info) = get_monitor_info(self.handle).log_err() else {
+            return self.bounds;
+        };
+        let work_area = info.monitorInfo.rcWork;
+        let physical_size = size(
+            DevicePixels(work_area.right - work_area.left),
+            DevicePixels(work_area.bottom - work_area.top),
+        );
+        Bounds {
+            origin: logical_point(
+                work_area.left as f32,
+                work_area.top as f32,
+                self.scale_factor,
+            ),
+            size: physical_size.to_pixels(self.scale_factor),
+        }
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    fn refresh_rate(&self) -> Option<f32> {
+        self.frequency().map(|frequency| frequency as f32)
+    }
 }
 
 fn available_monitors() -> SmallVec<[HMONITOR; 4]> {
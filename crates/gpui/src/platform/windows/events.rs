@@ -7,7 +7,7 @@ use windows::{
     Win32::{
         Foundation::*,
         Graphics::Gdi::*,
-        System::SystemServices::*,
+        System::{RemoteDesktop::*, SystemServices::*},
         UI::{
             Controls::*,
             HiDpi::*,
@@ -22,6 +22,10 @@ use crate::*;
 pub(crate) const WM_GPUI_CURSOR_STYLE_CHANGED: u32 = WM_USER + 1;
 pub(crate) const WM_GPUI_CLOSE_ONE_WINDOW: u32 = WM_USER + 2;
 pub(crate) const WM_GPUI_TASK_DISPATCHED_ON_MAIN_THREAD: u32 = WM_USER + 3;
+pub(crate) const WM_GPUI_DISPLAYS_CHANGED: u32 = WM_USER + 4;
+pub(crate) const WM_GPUI_SYSTEM_APPEARANCE_CHANGED: u32 = WM_USER + 5;
+pub(crate) const WM_GPUI_SYSTEM_EVENT: u32 = WM_USER + 6;
+pub(crate) const WM_GPUI_KEYBOARD_LAYOUT_CHANGED: u32 = WM_USER + 7;
 
 const SIZE_MOVE_LOOP_TIMER_ID: usize = 1;
 const AUTO_HIDE_TASKBAR_THICKNESS_PX: i32 = 1;
@@ -87,10 +91,14 @@ pub(crate) fn handle_msg(
         WM_KEYDOWN => handle_keydown_msg(wparam, lparam, state_ptr),
         WM_KEYUP => handle_keyup_msg(wparam, state_ptr),
         WM_CHAR => handle_char_msg(wparam, lparam, state_ptr),
+        WM_DEADCHAR => handle_dead_char_msg(wparam, state_ptr),
         WM_IME_STARTCOMPOSITION => handle_ime_position(handle, state_ptr),
         WM_IME_COMPOSITION => handle_ime_composition(handle, lparam, state_ptr),
         WM_SETCURSOR => handle_set_cursor(lparam, state_ptr),
-        WM_SETTINGCHANGE => handle_system_settings_changed(handle, lparam, state_ptr),
+        WM_SETTINGCHANGE => handle_system_settings_changed(handle, wparam, lparam, state_ptr),
+        WM_INPUTLANGCHANGE => handle_input_language_changed(state_ptr),
+        WM_POWERBROADCAST => handle_power_broadcast_msg(wparam, state_ptr),
+        WM_WTSSESSION_CHANGE => handle_session_change_msg(wparam, state_ptr),
         WM_GPUI_CURSOR_STYLE_CHANGED => handle_cursor_changed(lparam, state_ptr),
         _ => None,
     };
@@ -227,11 +235,14 @@ fn handle_close_msg(state_ptr: Rc<WindowsWindowStatePtr>) -> Option<isize> {
         let should_close = callback();
         state_ptr.state.borrow_mut().callbacks.should_close = Some(callback);
         if should_close {
+            enable_modal_parent(&state_ptr);
             None
         } else {
             Some(0)
         }
     } else {
+        drop(lock);
+        enable_modal_parent(&state_ptr);
         None
     }
 }
@@ -245,6 +256,7 @@ fn handle_destroy_msg(handle: HWND, state_ptr: Rc<WindowsWindowStatePtr>) -> Opt
         callback();
     }
     unsafe {
+        WTSUnRegisterSessionNotification(handle).log_err();
         PostThreadMessageW(
             state_ptr.main_thread_id_win32,
             WM_GPUI_CLOSE_ONE_WINDOW,
@@ -358,6 +370,10 @@ fn handle_keydown_msg(
     let Some(keystroke_or_modifier) = parse_keydown_msg_keystroke(wparam) else {
         return Some(1);
     };
+    // Keys that don't type a character, like escape or the arrow keys, discard a pending dead key.
+    if let KeystrokeOrModifier::Keystroke(_) = keystroke_or_modifier {
+        remove_dead_char_preview(&state_ptr);
+    }
     let mut lock = state_ptr.state.borrow_mut();
     let Some(mut func) = lock.callbacks.input.take() else {
         return Some(1);
@@ -416,6 +432,8 @@ fn handle_char_msg(
     lparam: LPARAM,
     state_ptr: Rc<WindowsWindowStatePtr>,
 ) -> Option<isize> {
+    // The character replaces the dead key, either combined with it or following it.
+    remove_dead_char_preview(&state_ptr);
     let Some(keystroke) = parse_char_msg_keystroke(wparam) else {
         return Some(1);
     };
@@ -445,6 +463,30 @@ fn handle_char_msg(
     Some(0)
 }
 
+/// Shows the character of a dead key as marked text until the key it combines with is typed.
+/// macOS gets the same behavior from its text input system, and X11 and Wayland from the xkb
+/// compose state.
+fn handle_dead_char_msg(wparam: WPARAM, state_ptr: Rc<WindowsWindowStatePtr>) -> Option<isize> {
+    let dead_char = char::from_u32((wparam.0 as u16).into())?.to_string();
+    remove_dead_char_preview(&state_ptr);
+    with_input_handler(&state_ptr, |input_handler| {
+        input_handler.replace_and_mark_text_in_range(None, &dead_char, None);
+    })?;
+    state_ptr.state.borrow_mut().pending_dead_char = true;
+    Some(0)
+}
+
+fn remove_dead_char_preview(state_ptr: &Rc<WindowsWindowStatePtr>) {
+    let pending_dead_char = std::mem::take(&mut state_ptr.state.borrow_mut().pending_dead_char);
+    if pending_dead_char {
+        with_input_handler(state_ptr, |input_handler| {
+            if let Some(marked_range) = input_handler.marked_text_range() {
+                input_handler.replace_text_in_range(Some(marked_range), "");
+            }
+        });
+    }
+}
+
 fn handle_mouse_down_msg(
     handle: HWND,
     button: MouseButton,
@@ -798,6 +840,8 @@ fn handle_activate_msg(
 }
 
 fn handle_create_msg(handle: HWND, state_ptr: Rc<WindowsWindowStatePtr>) -> Option<isize> {
+    // Needed for this window to receive `WM_WTSSESSION_CHANGE`.
+    unsafe { WTSRegisterSessionNotification(handle, NOTIFY_FOR_THIS_SESSION) }.log_err();
     if state_ptr.hide_title_bar {
         notify_frame_changed(handle);
         Some(0)
@@ -851,6 +895,7 @@ fn handle_dpi_changed_msg(
 /// For example, in the case of condition 2, where the monitor on which the window is
 /// located has actually changed nothing, it will still receive this event.
 fn handle_display_change_msg(handle: HWND, state_ptr: Rc<WindowsWindowStatePtr>) -> Option<isize> {
+    notify_displays_changed(&state_ptr);
     // NOTE:
     // Even the `lParam` holds the resolution of the screen, we just ignore it.
     // Because WM_DPICHANGED, WM_MOVE, WM_SIZE will come first, window reposition and resize
@@ -878,6 +923,19 @@ fn handle_display_change_msg(handle: HWND, state_ptr: Rc<WindowsWindowStatePtr>)
     Some(0)
 }
 
+/// Tells the platform that the displays, or the parts of them that windows can occupy, changed.
+fn notify_displays_changed(state_ptr: &WindowsWindowStatePtr) {
+    unsafe {
+        PostThreadMessageW(
+            state_ptr.main_thread_id_win32,
+            WM_GPUI_DISPLAYS_CHANGED,
+            WPARAM(state_ptr.validation_number),
+            LPARAM::default(),
+        )
+        .log_err();
+    }
+}
+
 fn handle_hit_test_msg(
     handle: HWND,
     msg: u32,
@@ -1138,9 +1196,16 @@ fn handle_set_cursor(lparam: LPARAM, state_ptr: Rc<WindowsWindowStatePtr>) -> Op
 
 fn handle_system_settings_changed(
     handle: HWND,
+    wparam: WPARAM,
     lparam: LPARAM,
     state_ptr: Rc<WindowsWindowStatePtr>,
 ) -> Option<isize> {
+    // wParam is the system parameter that was changed, if it was changed through
+    // `SystemParametersInfo`. The work area changes when the taskbar is moved or resized.
+    if wparam.0 as u32 == SPI_SETWORKAREA.0 {
+        notify_displays_changed(&state_ptr);
+    }
+
     let mut lock = state_ptr.state.borrow_mut();
     let display = lock.display;
     // system settings
@@ -1172,6 +1237,73 @@ fn handle_system_settings_changed(
     Some(0)
 }
 
+fn handle_input_language_changed(state_ptr: Rc<WindowsWindowStatePtr>) -> Option<isize> {
+    unsafe {
+        PostThreadMessageW(
+            state_ptr.main_thread_id_win32,
+            WM_GPUI_KEYBOARD_LAYOUT_CHANGED,
+            WPARAM(state_ptr.validation_number),
+            LPARAM::default(),
+        )
+        .log_err();
+    }
+    // Let `DefWindowProcW` pass the change on to child windows.
+    None
+}
+
+fn handle_power_broadcast_msg(
+    wparam: WPARAM,
+    state_ptr: Rc<WindowsWindowStatePtr>,
+) -> Option<isize> {
+    let event = match wparam.0 as u32 {
+        PBT_APMSUSPEND => SystemEvent::WillSleep,
+        PBT_APMRESUMEAUTOMATIC => SystemEvent::DidWake,
+        PBT_APMPOWERSTATUSCHANGE => SystemEvent::PowerStateChanged,
+        _ => return None,
+    };
+    post_system_event(event, &state_ptr);
+    Some(1)
+}
+
+fn handle_session_change_msg(
+    wparam: WPARAM,
+    state_ptr: Rc<WindowsWindowStatePtr>,
+) -> Option<isize> {
+    let event = match wparam.0 as u32 {
+        WTS_SESSION_LOCK => SystemEvent::SessionLocked,
+        WTS_SESSION_UNLOCK => SystemEvent::SessionUnlocked,
+        _ => return None,
+    };
+    post_system_event(event, &state_ptr);
+    Some(0)
+}
+
+/// Every window receives power and session messages, so they are forwarded to the platform,
+/// which reports each change once.
+fn post_system_event(event: SystemEvent, state_ptr: &WindowsWindowStatePtr) {
+    unsafe {
+        PostThreadMessageW(
+            state_ptr.main_thread_id_win32,
+            WM_GPUI_SYSTEM_EVENT,
+            WPARAM(state_ptr.validation_number),
+            LPARAM(event as isize),
+        )
+        .log_err();
+    }
+}
+
+pub(crate) fn system_event_from_lparam(lparam: LPARAM) -> Option<SystemEvent> {
+    [
+        SystemEvent::WillSleep,
+        SystemEvent::DidWake,
+        SystemEvent::SessionLocked,
+        SystemEvent::SessionUnlocked,
+        SystemEvent::PowerStateChanged,
+    ]
+    .into_iter()
+    .find(|event| *event as isize == lparam.0)
+}
+
 fn handle_system_command(wparam: WPARAM, state_ptr: Rc<WindowsWindowStatePtr>) -> Option<isize> {
     if wparam.0 == SC_KEYMENU as usize {
         let mut lock = state_ptr.state.borrow_mut();
@@ -1187,6 +1319,15 @@ fn handle_system_theme_changed(
     handle: HWND,
     state_ptr: Rc<WindowsWindowStatePtr>,
 ) -> Option<isize> {
+    unsafe {
+        PostThreadMessageW(
+            state_ptr.main_thread_id_win32,
+            WM_GPUI_SYSTEM_APPEARANCE_CHANGED,
+            WPARAM(state_ptr.validation_number),
+            LPARAM::default(),
+        )
+        .log_err();
+    }
     let mut callback = state_ptr
         .state
         .borrow_mut()
@@ -1392,6 +1533,13 @@ fn basic_vkcode_to_string(code: u16, modifiers: Modifiers) -> Option<Keystroke>
     })
 }
 
+/// Returns the key that the key with the given scan code types with the current keyboard
+/// layout, resolved the same way as keystrokes with modifiers.
+pub(crate) fn layout_key(scan_code: u32) -> Option<String> {
+    let vk_code = unsafe { MapVirtualKeyW(scan_code, MAPVK_VSC_TO_VK) };
+    basic_vkcode_to_string(vk_code as u16, Modifiers::default()).map(|keystroke| keystroke.key)
+}
+
 #[inline]
 fn is_virtual_key_pressed(vkey: VIRTUAL_KEY) -> bool {
     unsafe { GetKeyState(vkey.0 as i32) < 0 }
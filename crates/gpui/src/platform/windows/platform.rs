@@ -22,7 +22,7 @@ use windows::{
             Imaging::{CLSID_WICImagingFactory, IWICImagingFactory},
         },
         Security::Credentials::*,
-        System::{Com::*, LibraryLoader::*, Ole::*, SystemInformation::*, Threading::*},
+        System::{Com::*, LibraryLoader::*, Ole::*, Power::*, SystemInformation::*, Threading::*},
         UI::{Input::KeyboardAndMouse::*, Shell::*, WindowsAndMessaging::*},
     },
     UI::ViewManagement::UISettings,
@@ -51,6 +51,8 @@ pub(crate) struct WindowsPlatformState {
     menus: Vec<OwnedMenu>,
     // NOTE: standard cursor handles don't need to close.
     pub(crate) current_cursor: HCURSOR,
+    last_system_event: Option<SystemEvent>,
+    power_state: PowerState,
 }
 
 #[derive(Default)]
@@ -58,6 +60,10 @@ struct PlatformCallbacks {
     open_urls: Option<Box<dyn FnMut(Vec<String>)>>,
     quit: Option<Box<dyn FnMut()>>,
     reopen: Option<Box<dyn FnMut()>>,
+    displays_changed: Option<Box<dyn FnMut()>>,
+    system_appearance_changed: Option<Box<dyn FnMut()>>,
+    system_event: Option<Box<dyn FnMut(SystemEvent)>>,
+    keyboard_layout_change: Option<Box<dyn FnMut()>>,
     app_menu_action: Option<Box<dyn FnMut(&dyn Action)>>,
     will_open_app_menu: Option<Box<dyn FnMut()>>,
     validate_app_menu_command: Option<Box<dyn FnMut(&dyn Action) -> bool>>,
@@ -72,6 +78,8 @@ impl WindowsPlatformState {
             callbacks,
             current_cursor,
             menus: Vec::new(),
+            last_system_event: None,
+            power_state: system_power_state(),
         }
     }
 }
@@ -184,6 +192,81 @@ impl WindowsPlatform {
         }
     }
 
+    fn displays_changed(&self) {
+        let callback = self.state.borrow_mut().callbacks.displays_changed.take();
+        if let Some(mut callback) = callback {
+            callback();
+            self.state
+                .borrow_mut()
+                .callbacks
+                .displays_changed
+                .get_or_insert(callback);
+        }
+    }
+
+    fn system_appearance_changed(&self) {
+        let callback = self
+            .state
+            .borrow_mut()
+            .callbacks
+            .system_appearance_changed
+            .take();
+        if let Some(mut callback) = callback {
+            callback();
+            self.state
+                .borrow_mut()
+                .callbacks
+                .system_appearance_changed
+                .get_or_insert(callback);
+        }
+    }
+
+    fn keyboard_layout_changed(&self) {
+        let callback = self
+            .state
+            .borrow_mut()
+            .callbacks
+            .keyboard_layout_change
+            .take();
+        if let Some(mut callback) = callback {
+            callback();
+            self.state
+                .borrow_mut()
+                .callbacks
+                .keyboard_layout_change
+                .get_or_insert(callback);
+        }
+    }
+
+    fn system_event(&self, lparam: LPARAM) {
+        let Some(event) = system_event_from_lparam(lparam) else {
+            return;
+        };
+        let callback = {
+            let mut state = self.state.borrow_mut();
+            // Each window forwards the same change, so only the first one is reported.
+            if event == SystemEvent::PowerStateChanged {
+                let power_state = system_power_state();
+                if power_state == state.power_state {
+                    return;
+                }
+                state.power_state = power_state;
+            } else if state.last_system_event == Some(event) {
+                return;
+            }
+            state.last_system_event = Some(event);
+            state.callbacks.system_event.take()
+        };
+        if let Some(mut callback) = callback {
+            callback(event);
+            self.state
+                .borrow_mut()
+                .callbacks
+                .system_event
+                .get_or_insert(callback);
+        }
+    }
+
     // Returns true if the app should quit.
     fn handle_events(&self) -> bool {
         let mut msg = MSG::default();
@@ -191,7 +274,12 @@ impl WindowsPlatform {
             while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
                 match msg.message {
                     WM_QUIT => return true,
-                    WM_GPUI_CLOSE_ONE_WINDOW | WM_GPUI_TASK_DISPATCHED_ON_MAIN_THREAD => {
+                    WM_GPUI_CLOSE_ONE_WINDOW
+                    | WM_GPUI_TASK_DISPATCHED_ON_MAIN_THREAD
+                    | WM_GPUI_DISPLAYS_CHANGED
+                    | WM_GPUI_SYSTEM_APPEARANCE_CHANGED
+                    | WM_GPUI_SYSTEM_EVENT
+                    | WM_GPUI_KEYBOARD_LAYOUT_CHANGED => {
                         if self.handle_gpui_evnets(msg.message, msg.wParam, msg.lParam, &msg) {
                             return true;
                         }
@@ -227,6 +315,10 @@ impl WindowsPlatform {
                 }
             }
             WM_GPUI_TASK_DISPATCHED_ON_MAIN_THREAD => self.run_foreground_task(),
+            WM_GPUI_DISPLAYS_CHANGED => self.displays_changed(),
+            WM_GPUI_SYSTEM_APPEARANCE_CHANGED => self.system_appearance_changed(),
+            WM_GPUI_SYSTEM_EVENT => self.system_event(lparam),
+            WM_GPUI_KEYBOARD_LAYOUT_CHANGED => self.keyboard_layout_changed(),
             _ => unreachable!(),
         }
         false
@@ -247,11 +339,25 @@ impl Platform for WindowsPlatform {
     }
 
     fn keyboard_layout(&self) -> String {
-        "unknown".into()
+        let mut name = [0u16; KL_NAMELENGTH as usize];
+        let result = unsafe { GetKeyboardLayoutNameW(&mut name) };
+        if result.log_err().is_none() {
+            return "unknown".into();
+        }
+        let len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+        String::from_utf16_lossy(&name[..len])
     }
 
-    fn on_keyboard_layout_change(&self, _callback: Box<dyn FnMut()>) {
-        // todo(windows)
+    fn on_keyboard_layout_change(&self, callback: Box<dyn FnMut()>) {
+        self.state.borrow_mut().callbacks.keyboard_layout_change = Some(callback);
+    }
+
+    fn layout_keystroke(&self, physical: &Keystroke) -> Option<Keystroke> {
+        layout_keystroke_for_scan_codes(physical, layout_key)
+    }
+
+    fn physical_keystroke(&self, keystroke: &Keystroke) -> Option<Keystroke> {
+        physical_keystroke_for_scan_codes(keystroke, layout_key)
     }
 
     fn run(&self, on_finish_launching: Box<dyn 'static + FnOnce()>) {
@@ -380,6 +486,10 @@ impl Platform for WindowsPlatform {
         system_appearance().log_err().unwrap_or_default()
     }
 
+    fn accent_color(&self) -> Option<Rgba> {
+        system_accent_color().log_err()
+    }
+
     fn open_url(&self, url: &str) {
         let url_string = url.to_string();
         self.background_executor()
@@ -410,12 +520,14 @@ impl Platform for WindowsPlatform {
         rx
     }
 
-    fn prompt_for_new_path(&self, directory: &Path) -> Receiver<Result<Option<PathBuf>>> {
-        let directory = directory.to_owned();
+    fn prompt_for_new_path(
+        &self,
+        options: NewPathPromptOptions,
+    ) -> Receiver<Result<Option<PathBuf>>> {
         let (tx, rx) = oneshot::channel();
         self.foreground_executor()
             .spawn(async move {
-                let _ = tx.send(file_save_dialog(directory));
+                let _ = tx.send(file_save_dialog(options));
             })
             .detach();
 
@@ -471,6 +583,22 @@ impl Platform for WindowsPlatform {
         self.state.borrow_mut().callbacks.reopen = Some(callback);
     }
 
+    fn on_displays_changed(&self, callback: Box<dyn FnMut()>) {
+        self.state.borrow_mut().callbacks.displays_changed = Some(callback);
+    }
+
+    fn on_system_appearance_changed(&self, callback: Box<dyn FnMut()>) {
+        self.state.borrow_mut().callbacks.system_appearance_changed = Some(callback);
+    }
+
+    fn on_system_event(&self, callback: Box<dyn FnMut(SystemEvent)>) {
+        self.state.borrow_mut().callbacks.system_event = Some(callback);
+    }
+
+    fn power_state(&self) -> PowerState {
+        system_power_state()
+    }
+
     fn set_menus(&self, menus: Vec<Menu>, _keymap: &Keymap) {
         self.state.borrow_mut().menus = menus.into_iter().map(|menu| menu.owned()).collect();
     }
@@ -482,6 +610,10 @@ impl Platform for WindowsPlatform {
     // todo(windows)
     fn set_dock_menu(&self, _menus: Vec<MenuItem>, _keymap: &Keymap) {}
 
+    fn set_dock_progress(&self, progress: Option<f32>) {
+        set_taskbar_progress(&self.raw_window_handles.read(), progress).log_err();
+    }
+
     fn on_app_menu_action(&self, callback: Box<dyn FnMut(&dyn Action)>) {
         self.state.borrow_mut().callbacks.app_menu_action = Some(callback);
     }
@@ -669,6 +801,7 @@ fn file_open_dialog(options: PathPromptOptions) -> Result<Option<Vec<PathBuf>>>
 
     unsafe {
         folder_dialog.SetOptions(dialog_options)?;
+        set_file_types(&folder_dialog, &options.filters)?;
         if folder_dialog.Show(None).is_err() {
             // User cancelled
             return Ok(None);
@@ -691,10 +824,11 @@ fn file_open_dialog(options: PathPromptOptions) -> Result<Option<Vec<PathBuf>>>
     Ok(Some(paths))
 }
 
-fn file_save_dialog(directory: PathBuf) -> Result<Option<PathBuf>> {
+// The common item dialog always offers to create folders, so `can_create_directories` is ignored.
+fn file_save_dialog(options: NewPathPromptOptions) -> Result<Option<PathBuf>> {
     let dialog: IFileSaveDialog = unsafe { CoCreateInstance(&FileSaveDialog, None, CLSCTX_ALL)? };
-    if !directory.to_string_lossy().is_empty() {
-        if let Some(full_path) = directory.canonicalize().log_err() {
+    if !options.directory.to_string_lossy().is_empty() {
+        if let Some(full_path) = options.directory.canonicalize().log_err() {
             let full_path = SanitizedPath::from(full_path);
             let full_path_string = full_path.to_string();
             let path_item: IShellItem =
@@ -703,10 +837,17 @@ fn file_save_dialog(directory: PathBuf) -> Result<Option<PathBuf>> {
         }
     }
     unsafe {
-        dialog.SetFileTypes(&[Common::COMDLG_FILTERSPEC {
-            pszName: windows::core::w!("All files"),
-            pszSpec: windows::core::w!("*.*"),
-        }])?;
+        if let Some(name) = &options.suggested_name {
+            dialog.SetFileName(&HSTRING::from(name.as_ref()))?;
+        }
+        if options.filters.is_empty() {
+            dialog.SetFileTypes(&[Common::COMDLG_FILTERSPEC {
+                pszName: windows::core::w!("All files"),
+                pszSpec: windows::core::w!("*.*"),
+            }])?;
+        } else {
+            set_file_types(&dialog, &options.filters)?;
+        }
         if dialog.Show(None).is_err() {
             // User cancelled
             return Ok(None);
@@ -722,6 +863,58 @@ fn file_save_dialog(directory: PathBuf) -> Result<Option<PathBuf>> {
     Ok(Some(PathBuf::from(file_path_string)))
 }
 
+fn set_file_types(dialog: &IFileDialog, filters: &[PathPromptFilter]) -> Result<()> {
+    if filters.is_empty() {
+        return Ok(());
+    }
+    // The filter specs point into these strings, so they must outlive the call.
+    let strings = filters
+        .iter()
+        .map(|filter| {
+            let spec = filter
+                .extensions
+                .iter()
+                .map(|extension| format!("*.{extension}"))
+                .collect::<Vec<_>>()
+                .join(";");
+            (HSTRING::from(filter.name.as_ref()), HSTRING::from(spec))
+        })
+        .collect::<Vec<_>>();
+    let specs = strings
+        .iter()
+        .map(|(name, spec)| Common::COMDLG_FILTERSPEC {
+            pszName: PCWSTR(name.as_ptr()),
+            pszSpec: PCWSTR(spec.as_ptr()),
+        })
+        .collect::<Vec<_>>();
+    unsafe { dialog.SetFileTypes(&specs)? };
+    Ok(())
+}
+
+fn set_taskbar_progress(windows: &[HWND], progress: Option<f32>) -> Result<()> {
+    const PROGRESS_TOTAL: u64 = 1000;
+
+    let taskbar: ITaskbarList3 =
+        unsafe { CoCreateInstance(&TaskbarList, None, CLSCTX_INPROC_SERVER)? };
+    unsafe { taskbar.HrInit()? };
+    for &hwnd in windows {
+        unsafe {
+            match progress {
+                Some(progress) => {
+                    taskbar.SetProgressState(hwnd, TBPF_NORMAL)?;
+                    taskbar.SetProgressValue(
+                        hwnd,
+                        (progress * PROGRESS_TOTAL as f32) as u64,
+                        PROGRESS_TOTAL,
+                    )?;
+                }
+                None => taskbar.SetProgressState(hwnd, TBPF_NOPROGRESS)?,
+            }
+        }
+    }
+    Ok(())
+}
+
 fn begin_vsync(vsync_event: HANDLE) {
     let event: SafeHandle = vsync_event.into();
     std::thread::spawn(move || unsafe {
@@ -754,6 +947,20 @@ fn should_auto_hide_scrollbars() -> Result<bool> {
     Ok(ui_settings.AutoHideScrollBars()?)
 }
 
+fn system_power_state() -> PowerState {
+    let mut status = SYSTEM_POWER_STATUS::default();
+    let result = unsafe { GetSystemPowerStatus(&mut status) };
+    if result.log_err().is_none() {
+        return PowerState::default();
+    }
+    PowerState {
+        // 0 means offline, 1 online and 255 unknown.
+        on_battery: status.ACLineStatus == 0,
+        // 1 means battery saver is on.
+        low_power_mode: status.SystemStatusFlag == 1,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{read_from_clipboard, write_to_clipboard, ClipboardItem};
@@ -178,6 +178,17 @@ pub(crate) fn system_appearance() -> Result<WindowAppearance> {
     }
 }
 
+pub(crate) fn system_accent_color() -> Result<Rgba> {
+    let ui_settings = UISettings::new()?;
+    let accent_color = ui_settings.GetColorValue(UIColorType::Accent)?;
+    Ok(Rgba {
+        r: accent_color.R as f32 / 255.0,
+        g: accent_color.G as f32 / 255.0,
+        b: accent_color.B as f32 / 255.0,
+        a: accent_color.A as f32 / 255.0,
+    })
+}
+
 #[inline(always)]
 fn is_color_light(color: &Color) -> bool {
     ((5 * color.G as u32) + (2 * color.R as u32) + color.B as u32) > (8 * 128)
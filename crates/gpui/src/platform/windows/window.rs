@@ -21,7 +21,7 @@ use windows::{
     core::*,
     Win32::{
         Foundation::*,
-        Graphics::Gdi::*,
+        Graphics::{Dwm::*, Gdi::*},
         System::{Com::*, LibraryLoader::*, Ole::*, SystemServices::*},
         UI::{Controls::*, HiDpi::*, Input::KeyboardAndMouse::*, Shell::*, WindowsAndMessaging::*},
     },
@@ -43,6 +43,7 @@ pub struct WindowsWindowState {
     pub callbacks: Callbacks,
     pub input_handler: Option<PlatformInputHandler>,
     pub system_key_handled: bool,
+    pub pending_dead_char: bool,
     pub hovered: bool,
 
     pub renderer: BladeRenderer,
@@ -53,6 +54,8 @@ pub struct WindowsWindowState {
     pub nc_button_pressed: Option<u32>,
 
     pub display: WindowsDisplay,
+    /// The parent window disabled while this modal window is open
+    pub modal_parent: Option<HWND>,
     fullscreen: Option<StyleAndBounds>,
     initial_placement: Option<WindowOpenStatus>,
     hwnd: HWND,
@@ -80,6 +83,7 @@ impl WindowsWindowState {
         current_cursor: HCURSOR,
         display: WindowsDisplay,
         gpu_context: &BladeContext,
+        path_sample_count: u32,
     ) -> Result<Self> {
         let scale_factor = {
             let monitor_dpi = unsafe { GetDpiForWindow(hwnd) } as f32;
@@ -96,14 +100,16 @@ impl WindowsWindowState {
         };
         let border_offset = WindowBorderOffset::default();
         let restore_from_minimized = None;
-        let renderer = windows_renderer::init(gpu_context, hwnd, transparent)?;
+        let renderer = windows_renderer::init(gpu_context, hwnd, transparent, path_sample_count)?;
         let callbacks = Callbacks::default();
         let input_handler = None;
         let system_key_handled = false;
+        let pending_dead_char = false;
         let hovered = false;
         let click_state = ClickState::new();
         let system_settings = WindowsSystemSettings::new(display);
         let nc_button_pressed = None;
+        let modal_parent = None;
         let fullscreen = None;
         let initial_placement = None;
 
@@ -117,6 +123,7 @@ impl WindowsWindowState {
             callbacks,
             input_handler,
             system_key_handled,
+            pending_dead_char,
             hovered,
             renderer,
             click_state,
@@ -124,6 +131,7 @@ impl WindowsWindowState {
             current_cursor,
             nc_button_pressed,
             display,
+            modal_parent,
             fullscreen,
             initial_placement,
             hwnd,
@@ -230,6 +238,7 @@ impl WindowsWindowStatePtr {
             context.current_cursor,
             context.display,
             context.gpu_context,
+            context.path_sample_count,
         )?);
 
         Ok(Rc::new_cyclic(|this| Self {
@@ -357,6 +366,7 @@ struct WindowCreateContext<'a> {
     validation_number: usize,
     main_receiver: flume::Receiver<Runnable>,
     gpu_context: &'a BladeContext,
+    path_sample_count: u32,
     main_thread_id_win32: u32,
 }
 
@@ -390,14 +400,20 @@ impl WindowsWindow {
                 .map(|title| title.as_ref())
                 .unwrap_or(""),
         );
-        let (dwexstyle, mut dwstyle) = if params.kind == WindowKind::PopUp {
-            (WS_EX_TOOLWINDOW, WINDOW_STYLE(0x0))
-        } else {
-            (
+        let (mut dwexstyle, mut dwstyle) = match params.kind {
+            WindowKind::PopUp => (WS_EX_TOOLWINDOW, WINDOW_STYLE(0x0)),
+            WindowKind::Tool => (WS_EX_TOOLWINDOW, WS_THICKFRAME | WS_SYSMENU),
+            WindowKind::Normal => (
                 WS_EX_APPWINDOW,
                 WS_THICKFRAME | WS_SYSMENU | WS_MAXIMIZEBOX | WS_MINIMIZEBOX,
-            )
+            ),
         };
+        if params
+            .level
+            .is_some_and(|level| level != WindowLevel::Normal)
+        {
+            dwexstyle |= WS_EX_TOPMOST;
+        }
 
         let hinstance = get_module_handle();
         let display = if let Some(display_id) = params.display_id {
@@ -419,8 +435,13 @@ impl WindowsWindow {
             validation_number,
             main_receiver,
             gpu_context,
+            path_sample_count: params.antialiasing.path_sample_count(),
             main_thread_id_win32,
         };
+        let parent = match params.parent {
+            Some(rwh::RawWindowHandle::Win32(parent)) => HWND(parent.hwnd.get() as _),
+            _ => HWND::default(),
+        };
         let lpparam = Some(&context as *const _ as *const _);
         let creation_result = unsafe {
             CreateWindowExW(
@@ -432,7 +453,7 @@ impl WindowsWindow {
                 CW_USEDEFAULT,
                 CW_USEDEFAULT,
                 CW_USEDEFAULT,
-                None,
+                parent,
                 None,
                 hinstance,
                 lpparam,
@@ -452,6 +473,11 @@ impl WindowsWindow {
             state_ptr.state.borrow().scale_factor,
             state_ptr.state.borrow().border_offset,
         )?;
+        if params.modal && !parent.is_invalid() {
+            // The parent ignores input until the modal window closes and enables it again.
+            let _ = unsafe { EnableWindow(parent, false) };
+            state_ptr.state.borrow_mut().modal_parent = Some(parent);
+        }
         if params.show {
             unsafe { SetWindowPlacement(hwnd, &placement)? };
         } else {
@@ -475,10 +501,14 @@ impl rwh::HasWindowHandle for WindowsWindow {
     }
 }
 
-// todo(windows)
 impl rwh::HasDisplayHandle for WindowsWindow {
     fn display_handle(&self) -> std::result::Result<rwh::DisplayHandle<'_>, rwh::HandleError> {
-        unimplemented!()
+        // SAFETY: Windows has no display connection, the handle carries no data
+        unsafe {
+            Ok(rwh::DisplayHandle::borrow_raw(
+                rwh::WindowsDisplayHandle::new().into(),
+            ))
+        }
     }
 }
 
@@ -491,6 +521,7 @@ impl Drop for WindowsWindow {
             .executor
             .spawn(async move {
                 let handle = this.hwnd;
+                enable_modal_parent(&this);
                 unsafe {
                     RevokeDragDrop(handle).log_err();
                     DestroyWindow(handle).log_err();
@@ -500,6 +531,15 @@ impl Drop for WindowsWindow {
     }
 }
 
+/// Enables the parent of a modal window again. This has to happen before the modal window is
+/// destroyed, otherwise Windows activates some other application instead of the parent.
+pub(crate) fn enable_modal_parent(state_ptr: &WindowsWindowStatePtr) {
+    let modal_parent = state_ptr.state.borrow_mut().modal_parent.take();
+    if let Some(parent) = modal_parent {
+        let _ = unsafe { EnableWindow(parent, true) };
+    }
+}
+
 impl PlatformWindow for WindowsWindow {
     fn bounds(&self) -> Bounds<Pixels> {
         self.0.state.borrow().bounds()
@@ -509,6 +549,10 @@ impl PlatformWindow for WindowsWindow {
         self.0.state.borrow().is_maximized()
     }
 
+    fn is_minimized(&self) -> bool {
+        unsafe { IsIconic(self.0.hwnd) }.as_bool()
+    }
+
     fn window_bounds(&self) -> WindowBounds {
         self.0.state.borrow().window_bounds()
     }
@@ -670,7 +714,25 @@ impl PlatformWindow for WindowsWindow {
         let mut version = unsafe { std::mem::zeroed() };
         let status = unsafe { windows::Wdk::System::SystemServices::RtlGetVersion(&mut version) };
         if status.is_ok() {
-            if background_appearance == WindowBackgroundAppearance::Blurred {
+            // Mica is only available starting with Windows 11 22H2.
+            let supports_system_backdrop = version.dwBuildNumber >= 22621;
+            if supports_system_backdrop {
+                let backdrop_type = if background_appearance == WindowBackgroundAppearance::Vibrant
+                {
+                    DWMSBT_MAINWINDOW
+                } else {
+                    DWMSBT_AUTO
+                };
+                set_system_backdrop(window_state.hwnd, backdrop_type);
+            }
+            let blurred = match background_appearance {
+                WindowBackgroundAppearance::Blurred => true,
+                WindowBackgroundAppearance::Vibrant => !supports_system_backdrop,
+                WindowBackgroundAppearance::Opaque | WindowBackgroundAppearance::Transparent => {
+                    false
+                }
+            };
+            if blurred {
                 if version.dwBuildNumber >= 17763 {
                     set_window_composition_attribute(window_state.hwnd, Some((0, 0, 0, 10)), 4);
                 }
@@ -706,6 +768,27 @@ impl PlatformWindow for WindowsWindow {
         }
     }
 
+    // Windows has no per-application window levels, so all levels above normal are topmost.
+    fn set_level(&self, level: WindowLevel) {
+        let insert_after = if level == WindowLevel::Normal {
+            HWND_NOTOPMOST
+        } else {
+            HWND_TOPMOST
+        };
+        unsafe {
+            SetWindowPos(
+                self.0.hwnd,
+                insert_after,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+            )
+        }
+        .log_err();
+    }
+
     fn minimize(&self) {
         unsafe { ShowWindowAsync(self.0.hwnd, SW_MINIMIZE).ok().log_err() };
     }
@@ -720,6 +803,44 @@ impl PlatformWindow for WindowsWindow {
         }
     }
 
+    fn start_window_move(&self) {
+        // Hand the drag over to the system, as if the caption had been pressed.
+        unsafe {
+            ReleaseCapture().log_err();
+            PostMessageW(
+                self.0.hwnd,
+                WM_SYSCOMMAND,
+                WPARAM((SC_MOVE | HTCAPTION) as usize),
+                LPARAM::default(),
+            )
+            .log_err();
+        }
+    }
+
+    fn start_window_resize(&self, edge: ResizeEdge) {
+        let edge = match edge {
+            ResizeEdge::Left => WMSZ_LEFT,
+            ResizeEdge::Right => WMSZ_RIGHT,
+            ResizeEdge::Top => WMSZ_TOP,
+            ResizeEdge::TopLeft => WMSZ_TOPLEFT,
+            ResizeEdge::TopRight => WMSZ_TOPRIGHT,
+            ResizeEdge::Bottom => WMSZ_BOTTOM,
+            ResizeEdge::BottomLeft => WMSZ_BOTTOMLEFT,
+            ResizeEdge::BottomRight => WMSZ_BOTTOMRIGHT,
+        };
+        // Like `start_window_move`, hand the drag over to the system's sizing loop.
+        unsafe {
+            ReleaseCapture().log_err();
+            PostMessageW(
+                self.0.hwnd,
+                WM_SYSCOMMAND,
+                WPARAM(SC_SIZE as usize | edge as usize),
+                LPARAM::default(),
+            )
+            .log_err();
+        }
+    }
+
     fn toggle_fullscreen(&self) {
         if unsafe { IsWindowVisible(self.0.hwnd).as_bool() } {
             self.0.toggle_fullscreen();
@@ -776,6 +897,14 @@ impl PlatformWindow for WindowsWindow {
         self.0.state.borrow().renderer.sprite_atlas().clone()
     }
 
+    fn set_antialiasing(&self, antialiasing: Antialiasing) {
+        self.0
+            .state
+            .borrow_mut()
+            .renderer
+            .update_path_sample_count(antialiasing.path_sample_count());
+    }
+
     fn get_raw_handle(&self) -> HWND {
         self.0.hwnd
     }
@@ -1207,6 +1336,19 @@ fn retrieve_window_placement(
     Ok(placement)
 }
 
+fn set_system_backdrop(hwnd: HWND, backdrop_type: DWM_SYSTEMBACKDROP_TYPE) {
+    unsafe {
+        DwmSetWindowAttribute(
+            hwnd,
+            DWMWA_SYSTEMBACKDROP_TYPE,
+            &backdrop_type as *const _ as _,
+            std::mem::size_of::<DWM_SYSTEMBACKDROP_TYPE>() as u32,
+        )
+        .inspect_err(|e| log::error!("Unable to set window backdrop: {e}"))
+        .ok();
+    }
+}
+
 fn set_window_composition_attribute(hwnd: HWND, color: Option<Color>, state: u32) {
     unsafe {
         type SetWindowCompositionAttributeType =
@@ -1257,11 +1399,13 @@ mod windows_renderer {
         context: &BladeContext,
         hwnd: HWND,
         transparent: bool,
+        path_sample_count: u32,
     ) -> anyhow::Result<BladeRenderer> {
         let raw = RawWindow { hwnd };
         let config = BladeSurfaceConfig {
             size: Default::default(),
             transparent,
+            path_sample_count,
         };
         BladeRenderer::new(context, &raw, config)
     }
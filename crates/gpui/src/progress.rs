@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use collections::BTreeMap;
+use futures::channel::mpsc;
+use parking_lot::Mutex;
+
+use crate::{CancellationToken, SharedString};
+
+/// Identifies a task that's reporting its progress, see [`crate::App::start_progress`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProgressId(usize);
+
+/// The progress most recently reported by a task, see [`crate::App::progress`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgressInfo {
+    /// Identifies the task, so it can be cancelled with [`crate::App::cancel_progress`]
+    pub id: ProgressId,
+    /// What the task is doing, such as "Indexing"
+    pub title: SharedString,
+    /// A description of the task's current step, if it has reported one
+    pub message: Option<SharedString>,
+    /// How much of the task is complete, from 0 to 1, if it's known
+    pub fraction: Option<f32>,
+    /// Whether the task stops when it's cancelled
+    pub cancellable: bool,
+}
+
+/// A handle through which a task reports its progress, see [`crate::App::start_progress`]. It
+/// can be cloned and sent to other threads, and the task is considered finished once every clone
+/// has been dropped.
+#[derive(Clone)]
+pub struct Progress(Arc<ProgressHandle>);
+
+struct ProgressHandle {
+    id: ProgressId,
+    registry: Arc<RegistryState>,
+    cancellation_token: CancellationToken,
+}
+
+impl Drop for ProgressHandle {
+    fn drop(&mut self) {
+        self.registry.update(|entries| {
+            entries.remove(&self.id);
+        });
+    }
+}
+
+impl Progress {
+    /// Returns the id that identifies this task in [`crate::App::progress`].
+    pub fn id(&self) -> ProgressId {
+        self.0.id
+    }
+
+    /// Describes the task's current step.
+    pub fn set_message(&self, message: impl Into<SharedString>) {
+        let message = message.into();
+        self.update(|info| info.message = Some(message));
+    }
+
+    /// Reports how much of the task is complete, from 0 to 1.
+    pub fn set_fraction(&self, fraction: f32) {
+        self.update(|info| info.fraction = Some(fraction.clamp(0., 1.)));
+    }
+
+    /// Returns whether the task has been cancelled with [`crate::App::cancel_progress`].
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancellation_token.is_cancelled()
+    }
+
+    /// Returns a token that's cancelled along with the task, which can be used to stop futures
+    /// spawned with `spawn_scoped`.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.0.cancellation_token.clone()
+    }
+
+    fn update(&self, f: impl FnOnce(&mut ProgressInfo)) {
+        self.0.registry.update(|entries| {
+            if let Some((info, _)) = entries.get_mut(&self.0.id) {
+                f(info);
+            }
+        });
+    }
+}
+
+/// The tasks that are currently reporting their progress.
+pub(crate) struct ProgressRegistry {
+    state: Arc<RegistryState>,
+    next_id: usize,
+    changes: Option<mpsc::UnboundedReceiver<()>>,
+}
+
+struct RegistryState {
+    entries: Mutex<BTreeMap<ProgressId, (ProgressInfo, CancellationToken)>>,
+    changed: mpsc::UnboundedSender<()>,
+}
+
+impl RegistryState {
+    fn update(&self, f: impl FnOnce(&mut BTreeMap<ProgressId, (ProgressInfo, CancellationToken)>)) {
+        f(&mut self.entries.lock());
+        self.changed.unbounded_send(()).ok();
+    }
+}
+
+impl Default for ProgressRegistry {
+    fn default() -> Self {
+        let (changed, changes) = mpsc::unbounded();
+        Self {
+            state: Arc::new(RegistryState {
+                entries: Mutex::default(),
+                changed,
+            }),
+            next_id: 0,
+            changes: Some(changes),
+        }
+    }
+}
+
+impl ProgressRegistry {
+    pub(crate) fn start(&mut self, title: SharedString, cancellable: bool) -> Progress {
+        let id = ProgressId(self.next_id);
+        self.next_id += 1;
+        let cancellation_token = CancellationToken::new();
+        let info = ProgressInfo {
+            id,
+            title,
+            message: None,
+            fraction: None,
+            cancellable,
+        };
+        self.state.update(|entries| {
+            entries.insert(id, (info, cancellation_token.clone()));
+        });
+        Progress(Arc::new(ProgressHandle {
+            id,
+            registry: self.state.clone(),
+            cancellation_token,
+        }))
+    }
+
+    pub(crate) fn all(&self) -> Vec<ProgressInfo> {
+        self.state
+            .entries
+            .lock()
+            .values()
+            .map(|(info, _)| info.clone())
+            .collect()
+    }
+
+    pub(crate) fn cancel(&self, id: ProgressId) {
+        if let Some((info, cancellation_token)) = self.state.entries.lock().get(&id) {
+            if info.cancellable {
+                cancellation_token.cancel();
+            }
+        }
+    }
+
+    /// Takes the stream that's notified whenever progress changes, which can only be done once.
+    pub(crate) fn take_changes(&mut self) -> Option<mpsc::UnboundedReceiver<()>> {
+        self.changes.take()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, rc::Rc};
+
+    use crate::{self as gpui, TestAppContext};
+
+    #[gpui::test]
+    fn test_progress(cx: &mut TestAppContext) {
+        let notifications = Rc::new(Cell::new(0));
+        let _subscription = cx.update(|cx| {
+            let notifications = notifications.clone();
+            cx.observe_progress(move |_| notifications.set(notifications.get() + 1))
+        });
+
+        let progress = cx.update(|cx| cx.start_progress("Indexing", true));
+        cx.background_executor
+            .spawn({
+                let progress = progress.clone();
+                async move {
+                    progress.set_message("src/main.rs");
+                    progress.set_fraction(0.5);
+                }
+            })
+            .detach();
+        cx.run_until_parked();
+        assert!(notifications.get() > 0);
+        cx.update(|cx| {
+            let [info] = cx.progress().try_into().unwrap();
+            assert_eq!(info.title, "Indexing");
+            assert_eq!(info.message.as_deref(), Some("src/main.rs"));
+            assert_eq!(info.fraction, Some(0.5));
+            cx.cancel_progress(info.id);
+        });
+        assert!(progress.is_cancelled());
+
+        notifications.set(0);
+        drop(progress);
+        cx.run_until_parked();
+        assert_eq!(notifications.get(), 1);
+        cx.update(|cx| assert!(cx.progress().is_empty()));
+    }
+}
@@ -3,9 +3,16 @@
 
 use crate::{
     bounds_tree::BoundsTree, point, AtlasTextureId, AtlasTile, Background, Bounds, ContentMask,
-    Corners, Edges, Hsla, Pixels, Point, Radians, ScaledPixels, Size,
+    Corners, Edges, Hsla, Pixels, Point, Radians, ScaledPixels, SharedString, Size,
+};
+use collections::FxHashMap;
+use std::{
+    fmt::Debug,
+    iter::Peekable,
+    ops::Range,
+    slice,
+    sync::atomic::{AtomicU32, Ordering::SeqCst},
 };
-use std::{fmt::Debug, iter::Peekable, ops::Range, slice};
 
 #[allow(non_camel_case_types, unused)]
 pub(crate) type PathVertex_ScaledPixels = PathVertex<ScaledPixels>;
@@ -24,6 +31,14 @@ pub(crate) struct Scene {
     pub(crate) monochrome_sprites: Vec<MonochromeSprite>,
     pub(crate) polychrome_sprites: Vec<PolychromeSprite>,
     pub(crate) surfaces: Vec<PaintSurface>,
+    pub(crate) shader_quads: Vec<ShaderQuad>,
+    /// The shaders of this scene's shader quads, for renderers to compile the ones they haven't
+    /// drawn before.
+    pub(crate) custom_shaders: FxHashMap<CustomShaderId, CustomShader>,
+    pub(crate) offscreen_layers: Vec<OffscreenLayer>,
+    /// The offscreen layers being painted, innermost last. Primitives go into the scene of the
+    /// innermost one until it's popped.
+    offscreen_layer_stack: Vec<OffscreenLayer>,
 }
 
 impl Scene {
@@ -38,6 +53,10 @@ impl Scene {
         self.monochrome_sprites.clear();
         self.polychrome_sprites.clear();
         self.surfaces.clear();
+        self.shader_quads.clear();
+        self.custom_shaders.clear();
+        self.offscreen_layers.clear();
+        self.offscreen_layer_stack.clear();
     }
 
     #[cfg_attr(
@@ -56,25 +75,76 @@ impl Scene {
     }
 
     pub fn push_layer(&mut self, bounds: Bounds<ScaledPixels>) {
-        let order = self.primitive_bounds.insert(bounds);
-        self.layer_stack.push(order);
+        let target = self.paint_target();
+        let order = target.primitive_bounds.insert(bounds);
+        target.layer_stack.push(order);
         self.paint_operations
             .push(PaintOperation::StartLayer(bounds));
     }
 
     pub fn pop_layer(&mut self) {
-        self.layer_stack.pop();
+        self.paint_target().layer_stack.pop();
         self.paint_operations.push(PaintOperation::EndLayer);
     }
 
+    /// Starts painting into an offscreen layer, which is drawn into its own texture and then
+    /// composited into this scene as a whole when it's popped.
+    pub fn push_offscreen_layer(&mut self, sprite: LayerSprite) {
+        self.paint_operations
+            .push(PaintOperation::StartOffscreenLayer(sprite.clone()));
+        self.offscreen_layer_stack.push(OffscreenLayer {
+            sprite,
+            scene: Scene::default(),
+        });
+    }
+
+    pub fn pop_offscreen_layer(&mut self) {
+        self.paint_operations
+            .push(PaintOperation::EndOffscreenLayer);
+        let Some(mut layer) = self.offscreen_layer_stack.pop() else {
+            return;
+        };
+        layer.scene.finish();
+
+        let target = self.paint_target();
+        let painted_bounds = layer.sprite.painted_bounds();
+        if painted_bounds.is_empty() {
+            return;
+        }
+        layer.sprite.order = target
+            .layer_stack
+            .last()
+            .copied()
+            .unwrap_or_else(|| target.primitive_bounds.insert(painted_bounds));
+        target.offscreen_layers.push(layer);
+    }
+
+    /// The scene that primitives are currently painted into, which is that of the innermost
+    /// offscreen layer if there is one.
+    fn paint_target(&mut self) -> &mut Scene {
+        if self.offscreen_layer_stack.is_empty() {
+            self
+        } else {
+            &mut self.offscreen_layer_stack.last_mut().unwrap().scene
+        }
+    }
+
     pub fn insert_primitive(&mut self, primitive: impl Into<Primitive>) {
-        let mut primitive = primitive.into();
+        if let Some(primitive) = self.paint_target().add_primitive(primitive.into()) {
+            self.paint_operations
+                .push(PaintOperation::Primitive(primitive));
+        }
+    }
+
+    /// Adds a primitive to be drawn, returning it with its draw order assigned, or `None` if it
+    /// is entirely clipped.
+    fn add_primitive(&mut self, mut primitive: Primitive) -> Option<Primitive> {
         let clipped_bounds = primitive
             .bounds()
             .intersect(&primitive.content_mask().bounds);
 
         if clipped_bounds.is_empty() {
-            return;
+            return None;
         }
 
         let order = self
@@ -112,31 +182,106 @@ impl Scene {
                 surface.order = order;
                 self.surfaces.push(surface.clone());
             }
+            Primitive::ShaderQuad(quad) => {
+                quad.order = order;
+                self.shader_quads.push(quad.clone());
+            }
         }
-        self.paint_operations
-            .push(PaintOperation::Primitive(primitive));
+        Some(primitive)
     }
 
     pub fn replay(&mut self, range: Range<usize>, prev_scene: &Scene) {
         for operation in &prev_scene.paint_operations[range] {
             match operation {
-                PaintOperation::Primitive(primitive) => self.insert_primitive(primitive.clone()),
+                PaintOperation::Primitive(primitive) => {
+                    if let Primitive::ShaderQuad(quad) = primitive {
+                        if let Some(shader) = prev_scene.custom_shaders.get(&quad.shader_id) {
+                            self.custom_shaders.insert(quad.shader_id, shader.clone());
+                        }
+                    }
+                    self.insert_primitive(primitive.clone())
+                }
                 PaintOperation::StartLayer(bounds) => self.push_layer(*bounds),
                 PaintOperation::EndLayer => self.pop_layer(),
+                PaintOperation::StartOffscreenLayer(sprite) => {
+                    self.push_offscreen_layer(sprite.clone())
+                }
+                PaintOperation::EndOffscreenLayer => self.pop_offscreen_layer(),
             }
         }
     }
 
+    /// Returns the region, in device pixels, where this scene paints differently than the
+    /// previous one, or `None` if they paint the same primitives. Only the operations between
+    /// the longest common prefix and suffix of both scenes are compared, which keeps this cheap
+    /// while still isolating small changes like a blinking cursor.
+    pub fn damage_since(&self, previous_scene: &Scene) -> Option<Bounds<ScaledPixels>> {
+        let current = &self.paint_operations;
+        let previous = &previous_scene.paint_operations;
+        let prefix_len = current
+            .iter()
+            .zip(previous)
+            .take_while(|(current, previous)| current.paints_same(previous))
+            .count();
+        let suffix_len = current[prefix_len..]
+            .iter()
+            .rev()
+            .zip(previous[prefix_len..].iter().rev())
+            .take_while(|(current, previous)| current.paints_same(previous))
+            .count();
+        let damage = current[prefix_len..current.len() - suffix_len]
+            .iter()
+            .chain(&previous[prefix_len..previous.len() - suffix_len])
+            .filter_map(PaintOperation::painted_bounds)
+            .reduce(|damage, bounds| damage.union(&bounds))?;
+
+        // A change inside a blurred layer spreads as far as the blur reaches, so the blurred
+        // layers it overlaps are drawn again as a whole.
+        let mut blurred_layers = Vec::new();
+        self.blurred_layer_bounds(&mut blurred_layers);
+        previous_scene.blurred_layer_bounds(&mut blurred_layers);
+        Some(
+            blurred_layers
+                .into_iter()
+                .filter(|bounds| bounds.intersects(&damage))
+                .fold(damage, |damage, bounds| damage.union(&bounds)),
+        )
+    }
+
+    fn blurred_layer_bounds(&self, bounds: &mut Vec<Bounds<ScaledPixels>>) {
+        for layer in &self.offscreen_layers {
+            if layer.sprite.blur_radius > ScaledPixels(0.) {
+                bounds.push(layer.sprite.painted_bounds());
+            }
+            layer.scene.blurred_layer_bounds(bounds);
+        }
+    }
+
     pub fn finish(&mut self) {
         self.shadows.sort_by_key(|shadow| shadow.order);
         self.quads.sort_by_key(|quad| quad.order);
         self.paths.sort_by_key(|path| path.order);
         self.underlines.sort_by_key(|underline| underline.order);
-        self.monochrome_sprites
-            .sort_by_key(|sprite| (sprite.order, sprite.tile.tile_id));
-        self.polychrome_sprites
-            .sort_by_key(|sprite| (sprite.order, sprite.tile.tile_id));
+        // Sprites with the same draw order don't overlap, so they're grouped by texture to
+        // draw them in as few batches as possible.
+        self.monochrome_sprites.sort_by_key(|sprite| {
+            (
+                sprite.order,
+                sprite.tile.texture_id.index,
+                sprite.tile.tile_id,
+            )
+        });
+        self.polychrome_sprites.sort_by_key(|sprite| {
+            (
+                sprite.order,
+                sprite.tile.texture_id.index,
+                sprite.tile.tile_id,
+            )
+        });
         self.surfaces.sort_by_key(|surface| surface.order);
+        self.shader_quads.sort_by_key(|quad| quad.order);
+        self.offscreen_layers
+            .sort_by_key(|layer| layer.sprite.order);
     }
 
     #[cfg_attr(
@@ -169,6 +314,12 @@ impl Scene {
             surfaces: &self.surfaces,
             surfaces_start: 0,
             surfaces_iter: self.surfaces.iter().peekable(),
+            shader_quads: &self.shader_quads,
+            shader_quads_start: 0,
+            shader_quads_iter: self.shader_quads.iter().peekable(),
+            offscreen_layers: &self.offscreen_layers,
+            offscreen_layers_start: 0,
+            offscreen_layers_iter: self.offscreen_layers.iter().peekable(),
         }
     }
 }
@@ -190,12 +341,49 @@ pub(crate) enum PrimitiveKind {
     MonochromeSprite,
     PolychromeSprite,
     Surface,
+    ShaderQuad,
+    OffscreenLayer,
 }
 
 pub(crate) enum PaintOperation {
     Primitive(Primitive),
     StartLayer(Bounds<ScaledPixels>),
     EndLayer,
+    StartOffscreenLayer(LayerSprite),
+    EndOffscreenLayer,
+}
+
+impl PaintOperation {
+    /// Whether two operations paint the same pixels, regardless of their draw order.
+    fn paints_same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (PaintOperation::Primitive(a), PaintOperation::Primitive(b)) => a.paints_same(b),
+            (PaintOperation::StartLayer(a), PaintOperation::StartLayer(b)) => a == b,
+            (PaintOperation::EndLayer, PaintOperation::EndLayer) => true,
+            (PaintOperation::StartOffscreenLayer(a), PaintOperation::StartOffscreenLayer(b)) => {
+                a.bounds == b.bounds
+                    && a.content_mask == b.content_mask
+                    && a.opacity == b.opacity
+                    && a.blur_radius == b.blur_radius
+            }
+            (PaintOperation::EndOffscreenLayer, PaintOperation::EndOffscreenLayer) => true,
+            _ => false,
+        }
+    }
+
+    fn painted_bounds(&self) -> Option<Bounds<ScaledPixels>> {
+        match self {
+            PaintOperation::Primitive(primitive) => Some(
+                primitive
+                    .bounds()
+                    .intersect(&primitive.content_mask().bounds),
+            ),
+            PaintOperation::StartOffscreenLayer(sprite) => Some(sprite.painted_bounds()),
+            PaintOperation::StartLayer(_)
+            | PaintOperation::EndLayer
+            | PaintOperation::EndOffscreenLayer => None,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -207,6 +395,7 @@ pub(crate) enum Primitive {
     MonochromeSprite(MonochromeSprite),
     PolychromeSprite(PolychromeSprite),
     Surface(PaintSurface),
+    ShaderQuad(ShaderQuad),
 }
 
 impl Primitive {
@@ -219,6 +408,66 @@ impl Primitive {
             Primitive::MonochromeSprite(sprite) => &sprite.bounds,
             Primitive::PolychromeSprite(sprite) => &sprite.bounds,
             Primitive::Surface(surface) => &surface.bounds,
+            Primitive::ShaderQuad(quad) => &quad.bounds,
+        }
+    }
+
+    /// Whether two primitives paint the same pixels, ignoring their draw order.
+    fn paints_same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Primitive::Shadow(a), Primitive::Shadow(b)) => {
+                a.bounds == b.bounds
+                    && a.content_mask == b.content_mask
+                    && a.blur_radius == b.blur_radius
+                    && a.corner_radii == b.corner_radii
+                    && a.color == b.color
+            }
+            (Primitive::Quad(a), Primitive::Quad(b)) => {
+                a.bounds == b.bounds
+                    && a.content_mask == b.content_mask
+                    && a.background == b.background
+                    && a.border_color == b.border_color
+                    && a.corner_radii == b.corner_radii
+                    && a.border_widths == b.border_widths
+            }
+            (Primitive::Path(a), Primitive::Path(b)) => {
+                a.bounds == b.bounds
+                    && a.content_mask == b.content_mask
+                    && a.color == b.color
+                    && a.vertices == b.vertices
+            }
+            (Primitive::Underline(a), Primitive::Underline(b)) => {
+                a.bounds == b.bounds
+                    && a.content_mask == b.content_mask
+                    && a.color == b.color
+                    && a.thickness == b.thickness
+                    && a.kind == b.kind
+            }
+            (Primitive::MonochromeSprite(a), Primitive::MonochromeSprite(b)) => {
+                a.bounds == b.bounds
+                    && a.content_mask == b.content_mask
+                    && a.color == b.color
+                    && a.tile == b.tile
+                    && a.transformation == b.transformation
+            }
+            (Primitive::PolychromeSprite(a), Primitive::PolychromeSprite(b)) => {
+                a.bounds == b.bounds
+                    && a.content_mask == b.content_mask
+                    && a.grayscale == b.grayscale
+                    && a.opacity == b.opacity
+                    && a.brightness == b.brightness
+                    && a.corner_radii == b.corner_radii
+                    && a.tile == b.tile
+            }
+            (Primitive::ShaderQuad(a), Primitive::ShaderQuad(b)) => {
+                a.bounds == b.bounds
+                    && a.content_mask == b.content_mask
+                    && a.shader_id == b.shader_id
+                    && a.opacity == b.opacity
+                    && a.uniforms == b.uniforms
+            }
+            // Surfaces show content, like video frames, that changes without being painted again.
+            _ => false,
         }
     }
 
@@ -231,6 +480,7 @@ impl Primitive {
             Primitive::MonochromeSprite(sprite) => &sprite.content_mask,
             Primitive::PolychromeSprite(sprite) => &sprite.content_mask,
             Primitive::Surface(surface) => &surface.content_mask,
+            Primitive::ShaderQuad(quad) => &quad.content_mask,
         }
     }
 }
@@ -264,6 +514,130 @@ struct BatchIterator<'a> {
     surfaces: &'a [PaintSurface],
     surfaces_start: usize,
     surfaces_iter: Peekable<slice::Iter<'a, PaintSurface>>,
+    shader_quads: &'a [ShaderQuad],
+    shader_quads_start: usize,
+    shader_quads_iter: Peekable<slice::Iter<'a, ShaderQuad>>,
+    offscreen_layers: &'a [OffscreenLayer],
+    offscreen_layers_start: usize,
+    offscreen_layers_iter: Peekable<slice::Iter<'a, OffscreenLayer>>,
+}
+
+/// The most primitives of other kinds that a batch looks past to merge in primitives that are
+/// drawn after them.
+const MAX_EARLY_DRAW_LOOKAHEAD: usize = 64;
+
+impl BatchIterator<'_> {
+    /// Whether a quad or path can be drawn ahead of the primitives of other kinds that precede
+    /// it in draw order, which holds when it overlaps none of them. This lets batches of quads
+    /// and paths continue across other primitives painted between them, such as the text
+    /// between an editor's indent guides.
+    fn can_draw_early(
+        &self,
+        order: DrawOrder,
+        kind: PrimitiveKind,
+        bounds: Bounds<ScaledPixels>,
+    ) -> bool {
+        fn preceding<'a, T>(
+            primitives: &'a [T],
+            start: usize,
+            primitive_kind: PrimitiveKind,
+            order_and_kind: (DrawOrder, PrimitiveKind),
+            order: impl Fn(&T) -> DrawOrder + 'a,
+            clipped_bounds: impl Fn(&T) -> Bounds<ScaledPixels> + 'a,
+        ) -> impl Iterator<Item = Bounds<ScaledPixels>> + 'a {
+            primitives[start..]
+                .iter()
+                .take_while(move |primitive| (order(primitive), primitive_kind) < order_and_kind)
+                .map(clipped_bounds)
+        }
+
+        let order_and_kind = (order, kind);
+        let quads = (kind != PrimitiveKind::Quad).then(|| {
+            preceding(
+                self.quads,
+                self.quads_start,
+                PrimitiveKind::Quad,
+                order_and_kind,
+                |quad| quad.order,
+                |quad| quad.bounds.intersect(&quad.content_mask.bounds),
+            )
+        });
+        let paths = (kind != PrimitiveKind::Path).then(|| {
+            preceding(
+                self.paths,
+                self.paths_start,
+                PrimitiveKind::Path,
+                order_and_kind,
+                |path| path.order,
+                |path| path.bounds.intersect(&path.content_mask.bounds),
+            )
+        });
+        let mut preceding_bounds = preceding(
+            self.shadows,
+            self.shadows_start,
+            PrimitiveKind::Shadow,
+            order_and_kind,
+            |shadow| shadow.order,
+            |shadow| shadow.bounds.intersect(&shadow.content_mask.bounds),
+        )
+        .chain(quads.into_iter().flatten())
+        .chain(paths.into_iter().flatten())
+        .chain(preceding(
+            self.underlines,
+            self.underlines_start,
+            PrimitiveKind::Underline,
+            order_and_kind,
+            |underline| underline.order,
+            |underline| underline.bounds.intersect(&underline.content_mask.bounds),
+        ))
+        .chain(preceding(
+            self.monochrome_sprites,
+            self.monochrome_sprites_start,
+            PrimitiveKind::MonochromeSprite,
+            order_and_kind,
+            |sprite| sprite.order,
+            |sprite| sprite.bounds.intersect(&sprite.content_mask.bounds),
+        ))
+        .chain(preceding(
+            self.polychrome_sprites,
+            self.polychrome_sprites_start,
+            PrimitiveKind::PolychromeSprite,
+            order_and_kind,
+            |sprite| sprite.order,
+            |sprite| sprite.bounds.intersect(&sprite.content_mask.bounds),
+        ))
+        .chain(preceding(
+            self.surfaces,
+            self.surfaces_start,
+            PrimitiveKind::Surface,
+            order_and_kind,
+            |surface| surface.order,
+            |surface| surface.bounds.intersect(&surface.content_mask.bounds),
+        ))
+        .chain(preceding(
+            self.shader_quads,
+            self.shader_quads_start,
+            PrimitiveKind::ShaderQuad,
+            order_and_kind,
+            |quad| quad.order,
+            |quad| quad.bounds.intersect(&quad.content_mask.bounds),
+        ))
+        .chain(preceding(
+            self.offscreen_layers,
+            self.offscreen_layers_start,
+            PrimitiveKind::OffscreenLayer,
+            order_and_kind,
+            |layer| layer.sprite.order,
+            |layer| layer.sprite.painted_bounds(),
+        ));
+
+        // Looking further ahead would make batching quadratic in the number of primitives.
+        preceding_bounds
+            .by_ref()
+            .take(MAX_EARLY_DRAW_LOOKAHEAD)
+            .all(|preceding| !preceding.intersects(&bounds))
+            && preceding_bounds.next().is_none()
+    }
 }
 
 impl<'a> Iterator for BatchIterator<'a> {
@@ -293,6 +667,14 @@ impl<'a> Iterator for BatchIterator<'a> {
                 self.surfaces_iter.peek().map(|s| s.order),
                 PrimitiveKind::Surface,
             ),
+            (
+                self.shader_quads_iter.peek().map(|q| q.order),
+                PrimitiveKind::ShaderQuad,
+            ),
+            (
+                self.offscreen_layers_iter.peek().map(|l| l.sprite.order),
+                PrimitiveKind::OffscreenLayer,
+            ),
         ];
         orders_and_kinds.sort_by_key(|(order, kind)| (order.unwrap_or(u32::MAX), *kind));
 
@@ -325,11 +707,17 @@ impl<'a> Iterator for BatchIterator<'a> {
                 let quads_start = self.quads_start;
                 let mut quads_end = quads_start + 1;
                 self.quads_iter.next();
-                while self
-                    .quads_iter
-                    .next_if(|quad| (quad.order, batch_kind) < max_order_and_kind)
-                    .is_some()
-                {
+                while let Some(&quad) = self.quads_iter.peek() {
+                    if (quad.order, batch_kind) >= max_order_and_kind
+                        && !self.can_draw_early(
+                            quad.order,
+                            batch_kind,
+                            quad.bounds.intersect(&quad.content_mask.bounds),
+                        )
+                    {
+                        break;
+                    }
+                    self.quads_iter.next();
                     quads_end += 1;
                 }
                 self.quads_start = quads_end;
@@ -339,11 +727,17 @@ impl<'a> Iterator for BatchIterator<'a> {
                 let paths_start = self.paths_start;
                 let mut paths_end = paths_start + 1;
                 self.paths_iter.next();
-                while self
-                    .paths_iter
-                    .next_if(|path| (path.order, batch_kind) < max_order_and_kind)
-                    .is_some()
-                {
+                while let Some(&path) = self.paths_iter.peek() {
+                    if (path.order, batch_kind) >= max_order_and_kind
+                        && !self.can_draw_early(
+                            path.order,
+                            batch_kind,
+                            path.bounds.intersect(&path.content_mask.bounds),
+                        )
+                    {
+                        break;
+                    }
+                    self.paths_iter.next();
                     paths_end += 1;
                 }
                 self.paths_start = paths_end;
@@ -423,6 +817,36 @@ impl<'a> Iterator for BatchIterator<'a> {
                     &self.surfaces[surfaces_start..surfaces_end],
                 ))
             }
+            PrimitiveKind::ShaderQuad => {
+                let shader_id = self.shader_quads_iter.peek().unwrap().shader_id;
+                let quads_start = self.shader_quads_start;
+                let mut quads_end = quads_start + 1;
+                self.shader_quads_iter.next();
+                while self
+                    .shader_quads_iter
+                    .next_if(|quad| {
+                        (quad.order, batch_kind) < max_order_and_kind && quad.shader_id == shader_id
+                    })
+                    .is_some()
+                {
+                    quads_end += 1;
+                }
+                self.shader_quads_start = quads_end;
+                Some(PrimitiveBatch::ShaderQuads {
+                    shader_id,
+                    quads: &self.shader_quads[quads_start..quads_end],
+                })
+            }
+            PrimitiveKind::OffscreenLayer => {
+                // Every layer has its own texture, so each is drawn on its own.
+                let index = self.offscreen_layers_start;
+                let layer = self.offscreen_layers_iter.next().unwrap();
+                self.offscreen_layers_start += 1;
+                Some(PrimitiveBatch::OffscreenLayer {
+                    index,
+                    sprite: &layer.sprite,
+                })
+            }
         }
     }
 }
@@ -449,6 +873,16 @@ pub(crate) enum PrimitiveBatch<'a> {
         sprites: &'a [PolychromeSprite],
     },
     Surfaces(&'a [PaintSurface]),
+    ShaderQuads {
+        shader_id: CustomShaderId,
+        quads: &'a [ShaderQuad],
+    },
+    /// An offscreen layer to composite, with the index of the layer in the scene's
+    /// `offscreen_layers`.
+    OffscreenLayer {
+        index: usize,
+        sprite: &'a LayerSprite,
+    },
 }
 
 #[derive(Default, Debug, Clone)]
@@ -479,7 +913,7 @@ pub(crate) struct Underline {
     pub content_mask: ContentMask<ScaledPixels>,
     pub color: Hsla,
     pub thickness: ScaledPixels,
-    pub wavy: bool,
+    pub kind: u32,
 }
 
 impl From<Underline> for Primitive {
@@ -630,7 +1064,8 @@ impl From<MonochromeSprite> for Primitive {
 #[repr(C)]
 pub(crate) struct PolychromeSprite {
     pub order: DrawOrder,
-    pub pad: u32, // align to 8 bytes
+    /// What the image's colors are scaled by, see [`crate::StyledImage::brightness`].
+    pub brightness: f32,
     pub grayscale: bool,
     pub opacity: f32,
     pub bounds: Bounds<ScaledPixels>,
@@ -650,6 +1085,8 @@ pub(crate) struct PaintSurface {
     pub order: DrawOrder,
     pub bounds: Bounds<ScaledPixels>,
     pub content_mask: ContentMask<ScaledPixels>,
+    /// What the surface's colors are scaled by, see [`crate::Surface::brightness`].
+    pub brightness: f32,
     #[cfg(target_os = "macos")]
     pub image_buffer: media::core_video::CVImageBuffer,
 }
@@ -660,6 +1097,125 @@ impl From<PaintSurface> for Primitive {
     }
 }
 
+/// A fragment shader that colors the quads painted with it by
+/// [`Window::paint_shader_quad`](crate::Window::paint_shader_quad).
+///
+/// Renderers compile a shader the first time it's drawn and keep it for as long as the window
+/// is open, so create each shader once and reuse it rather than creating one every frame.
+///
+/// Each renderer compiles the shader from source in its own language, so a shader has a source
+/// for each. The blade renderer, used on Linux and Windows, takes WGSL, and the Metal renderer
+/// on macOS takes the Metal Shading Language. Each source defines a `shade` function that's
+/// called for every pixel of the quad and returns the pixel's color, with straight rather than
+/// premultiplied alpha:
+///
+/// ```wgsl
+/// fn shade(input: ShaderInput) -> vec4<f32>
+/// ```
+///
+/// ```metal
+/// float4 shade(ShaderInput input)
+/// ```
+///
+/// `ShaderInput` has a `position` within the quad, from 0 to 1 on each axis, the quad's `size`
+/// in device pixels, and the quad's [`ShaderUniforms`] as `uniforms`, an array of four
+/// 4-component vectors. A shader that fails to compile is logged, and quads painted with it
+/// aren't drawn.
+#[derive(Clone, Debug)]
+pub struct CustomShader {
+    pub(crate) id: CustomShaderId,
+    pub(crate) wgsl: SharedString,
+    pub(crate) msl: SharedString,
+}
+
+impl CustomShader {
+    /// Creates a shader from its WGSL and Metal Shading Language sources.
+    pub fn new(wgsl: impl Into<SharedString>, msl: impl Into<SharedString>) -> Self {
+        static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+        Self {
+            id: CustomShaderId(NEXT_ID.fetch_add(1, SeqCst)),
+            wgsl: wgsl.into(),
+            msl: msl.into(),
+        }
+    }
+}
+
+/// The values passed to every pixel of a quad painted with a [`CustomShader`], such as the
+/// progress of an animation or the colors to draw with.
+pub type ShaderUniforms = [[f32; 4]; 4];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(C)]
+pub(crate) struct CustomShaderId(pub(crate) u32);
+
+#[derive(Debug, Clone)]
+#[repr(C)]
+pub(crate) struct ShaderQuad {
+    pub order: DrawOrder,
+    pub shader_id: CustomShaderId,
+    pub bounds: Bounds<ScaledPixels>,
+    pub content_mask: ContentMask<ScaledPixels>,
+    pub opacity: f32,
+    pub pad: u32, // align the uniforms to 16 bytes
+    pub uniforms: ShaderUniforms,
+}
+
+impl From<ShaderQuad> for Primitive {
+    fn from(quad: ShaderQuad) -> Self {
+        Primitive::ShaderQuad(quad)
+    }
+}
+
+/// How the contents of an offscreen layer are composited into the window, see
+/// [`crate::Window::with_offscreen_layer`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LayerEffect {
+    /// The opacity the layer is drawn with, applied to its contents as a whole rather than to
+    /// each element in it, so overlapping elements don't show through each other.
+    pub opacity: f32,
+    /// The radius of the gaussian blur applied to the layer. The blur extends past the layer's
+    /// bounds by one and a half times this radius.
+    pub blur_radius: Pixels,
+}
+
+impl Default for LayerEffect {
+    fn default() -> Self {
+        Self {
+            opacity: 1.,
+            blur_radius: Pixels::ZERO,
+        }
+    }
+}
+
+/// A layer whose primitives are drawn into an offscreen texture, which is then composited into
+/// the scene that contains it.
+pub(crate) struct OffscreenLayer {
+    pub sprite: LayerSprite,
+    pub scene: Scene,
+}
+
+/// Where and how an offscreen layer's texture is composited. The texture is the size of the
+/// viewport, so the layer's primitives are drawn into it at their position in the window.
+#[derive(Debug, Clone)]
+#[repr(C)]
+pub(crate) struct LayerSprite {
+    pub order: DrawOrder,
+    pub pad: u32, // align to 8 bytes
+    pub bounds: Bounds<ScaledPixels>,
+    pub content_mask: ContentMask<ScaledPixels>,
+    pub opacity: f32,
+    pub blur_radius: ScaledPixels,
+}
+
+impl LayerSprite {
+    /// The bounds of the pixels the layer is composited onto, including the extent of its blur.
+    pub fn painted_bounds(&self) -> Bounds<ScaledPixels> {
+        self.bounds
+            .dilate(ScaledPixels(self.blur_radius.0 * 1.5))
+            .intersect(&self.content_mask.bounds)
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub(crate) struct PathId(pub(crate) usize);
 
@@ -796,7 +1352,7 @@ impl From<Path<ScaledPixels>> for Primitive {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 #[repr(C)]
 pub(crate) struct PathVertex<P: Clone + Default + Debug> {
     pub(crate) xy_position: Point<P>,
@@ -813,3 +1369,270 @@ impl PathVertex<Pixels> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{size, AtlasTextureKind, DevicePixels, Hsla, TileId};
+
+    fn quad(x: f32, color: Hsla) -> Quad {
+        let bounds = Bounds::new(
+            point(ScaledPixels(x), ScaledPixels(0.)),
+            size(ScaledPixels(10.), ScaledPixels(10.)),
+        );
+        Quad {
+            bounds,
+            content_mask: ContentMask { bounds },
+            background: color.into(),
+            ..Default::default()
+        }
+    }
+
+    fn scene(quads: impl IntoIterator<Item = Quad>) -> Scene {
+        let mut scene = Scene::default();
+        for quad in quads {
+            scene.insert_primitive(quad);
+        }
+        scene.finish();
+        scene
+    }
+
+    #[test]
+    fn test_sprites_batched_by_texture() {
+        let mut scene = Scene::default();
+        for ix in 0..4 {
+            let bounds = Bounds::new(
+                point(ScaledPixels(ix as f32 * 20.), ScaledPixels(0.)),
+                size(ScaledPixels(10.), ScaledPixels(10.)),
+            );
+            scene.insert_primitive(MonochromeSprite {
+                order: 0,
+                pad: 0,
+                bounds,
+                content_mask: ContentMask { bounds },
+                color: Hsla::black(),
+                tile: AtlasTile {
+                    texture_id: AtlasTextureId {
+                        index: ix % 2,
+                        kind: AtlasTextureKind::Monochrome,
+                    },
+                    tile_id: TileId(ix),
+                    padding: 0,
+                    bounds: Bounds::new(
+                        point(DevicePixels(0), DevicePixels(0)),
+                        size(DevicePixels(10), DevicePixels(10)),
+                    ),
+                },
+                transformation: TransformationMatrix::unit(),
+            });
+        }
+        scene.finish();
+
+        // The sprites don't overlap, so the ones in each texture are drawn together even though
+        // they were painted alternating between textures.
+        let batch_sizes = scene
+            .batches()
+            .map(|batch| match batch {
+                PrimitiveBatch::MonochromeSprites { sprites, .. } => sprites.len(),
+                _ => panic!("expected only monochrome sprites"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(batch_sizes, [2, 2]);
+    }
+
+    #[test]
+    fn test_quads_batched_across_other_primitives() {
+        let black = Hsla::black();
+        let bounds = |x: f32, width: f32| {
+            Bounds::new(
+                point(ScaledPixels(x), ScaledPixels(0.)),
+                size(ScaledPixels(width), ScaledPixels(10.)),
+            )
+        };
+        let mut scene = Scene::default();
+        scene.insert_primitive(Quad {
+            bounds: bounds(0., 20.),
+            content_mask: ContentMask {
+                bounds: bounds(0., 20.),
+            },
+            background: black.into(),
+            ..Default::default()
+        });
+        scene.insert_primitive(Underline {
+            order: 0,
+            pad: 0,
+            bounds: bounds(0., 5.),
+            content_mask: ContentMask {
+                bounds: bounds(0., 5.),
+            },
+            color: black,
+            thickness: ScaledPixels(1.),
+            kind: 0,
+        });
+        scene.insert_primitive(Quad {
+            bounds: bounds(10., 10.),
+            content_mask: ContentMask {
+                bounds: bounds(10., 10.),
+            },
+            background: black.into(),
+            ..Default::default()
+        });
+        // Drawn after the underline, but doesn't overlap it.
+        scene.insert_primitive(Quad {
+            bounds: bounds(12., 5.),
+            content_mask: ContentMask {
+                bounds: bounds(12., 5.),
+            },
+            background: black.into(),
+            ..Default::default()
+        });
+        // Drawn after the underline and overlaps it.
+        scene.insert_primitive(Quad {
+            bounds: bounds(0., 5.),
+            content_mask: ContentMask {
+                bounds: bounds(0., 5.),
+            },
+            background: black.into(),
+            ..Default::default()
+        });
+        scene.finish();
+
+        let batches = scene
+            .batches()
+            .map(|batch| match batch {
+                PrimitiveBatch::Quads(quads) => ("quads", quads.len()),
+                PrimitiveBatch::Underlines(underlines) => ("underlines", underlines.len()),
+                _ => panic!("expected only quads and underlines"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(batches, [("quads", 3), ("underlines", 1), ("quads", 1)]);
+    }
+
+    #[test]
+    fn test_shader_quads_batched_by_shader() {
+        let first = CustomShader::new("", "");
+        let second = CustomShader::new("", "");
+        let mut scene = Scene::default();
+        for (ix, shader) in [&first, &first, &second, &first].into_iter().enumerate() {
+            let bounds = Bounds::new(
+                point(ScaledPixels(ix as f32 * 20.), ScaledPixels(0.)),
+                size(ScaledPixels(10.), ScaledPixels(10.)),
+            );
+            scene.insert_primitive(ShaderQuad {
+                order: 0,
+                shader_id: shader.id,
+                bounds,
+                content_mask: ContentMask { bounds },
+                opacity: 1.,
+                pad: 0,
+                uniforms: ShaderUniforms::default(),
+            });
+        }
+        scene.finish();
+
+        // Consecutive quads using the same shader share a batch, but paint order is kept.
+        let batches = scene
+            .batches()
+            .map(|batch| match batch {
+                PrimitiveBatch::ShaderQuads { shader_id, quads } => (shader_id, quads.len()),
+                _ => panic!("expected only shader quads"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(batches, [(first.id, 2), (second.id, 1), (first.id, 1)]);
+    }
+
+    #[test]
+    fn test_offscreen_layers() {
+        let black = Hsla::black();
+        let white = Hsla::white();
+        let layer = |blur_radius: f32| LayerSprite {
+            order: 0,
+            pad: 0,
+            bounds: Bounds::new(
+                point(ScaledPixels(0.), ScaledPixels(0.)),
+                size(ScaledPixels(30.), ScaledPixels(10.)),
+            ),
+            content_mask: ContentMask {
+                bounds: Bounds::new(
+                    point(ScaledPixels(-100.), ScaledPixels(-100.)),
+                    size(ScaledPixels(200.), ScaledPixels(200.)),
+                ),
+            },
+            opacity: 0.5,
+            blur_radius: ScaledPixels(blur_radius),
+        };
+        let layered_scene = |blur_radius: f32, color: Hsla| {
+            let mut scene = Scene::default();
+            scene.insert_primitive(quad(0., black));
+            scene.push_offscreen_layer(layer(blur_radius));
+            scene.insert_primitive(quad(0., black));
+            scene.insert_primitive(quad(20., color));
+            scene.pop_offscreen_layer();
+            scene.finish();
+            scene
+        };
+
+        // The layer's primitives are drawn into its own scene, which is composited after the
+        // quad painted before it.
+        let scene = layered_scene(0., black);
+        assert_eq!(scene.quads.len(), 1);
+        assert_eq!(scene.offscreen_layers.len(), 1);
+        assert_eq!(scene.offscreen_layers[0].scene.quads.len(), 2);
+        let batches = scene
+            .batches()
+            .map(|batch| match batch {
+                PrimitiveBatch::Quads(quads) => format!("quads {}", quads.len()),
+                PrimitiveBatch::OffscreenLayer { index, .. } => format!("layer {index}"),
+                _ => panic!("expected only quads and layers"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(batches, ["quads 1", "layer 0"]);
+
+        let mut replayed = Scene::default();
+        replayed.replay(0..scene.len(), &scene);
+        replayed.finish();
+        assert_eq!(replayed.quads.len(), 1);
+        assert_eq!(replayed.offscreen_layers[0].scene.quads.len(), 2);
+
+        // Only the quad that changed is damaged, unless the layer is blurred, which spreads the
+        // change across the whole layer.
+        assert_eq!(
+            layered_scene(0., white).damage_since(&layered_scene(0., black)),
+            Some(quad(20., white).bounds)
+        );
+        assert_eq!(
+            layered_scene(4., white).damage_since(&layered_scene(4., black)),
+            Some(layer(4.).painted_bounds())
+        );
+    }
+
+    #[test]
+    fn test_damage_since() {
+        let black = Hsla::black();
+        let white = Hsla::white();
+        let previous = scene([quad(0., black), quad(20., black), quad(40., black)]);
+
+        assert_eq!(
+            scene([quad(0., black), quad(20., black), quad(40., black)]).damage_since(&previous),
+            None
+        );
+
+        // Only the quad that changed color is damaged.
+        assert_eq!(
+            scene([quad(0., black), quad(20., white), quad(40., black)]).damage_since(&previous),
+            Some(quad(20., white).bounds)
+        );
+
+        // A removed quad damages the area it was painted in.
+        assert_eq!(
+            scene([quad(0., black), quad(40., black)]).damage_since(&previous),
+            Some(quad(20., black).bounds)
+        );
+
+        // Changes at both ends damage everything between them.
+        assert_eq!(
+            scene([quad(0., white), quad(20., black), quad(40., white)]).damage_since(&previous),
+            Some(quad(0., black).bounds.union(&quad(40., black).bounds))
+        );
+    }
+}
@@ -750,8 +750,28 @@ pub struct UnderlineStyle {
     /// The color of the underline.
     pub color: Option<Hsla>,
 
-    /// Whether the underline should be wavy, like in a spell checker.
-    pub wavy: bool,
+    /// The shape of the underline, such as a wavy line like in a spell checker.
+    pub kind: UnderlineKind,
+}
+
+/// The shape of the line drawn for an underline.
+///
+/// The discriminants are passed to the underline shaders as is, so they must match the values
+/// that the shaders switch on.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum UnderlineKind {
+    /// A single straight line.
+    #[default]
+    Solid = 0,
+    /// A wavy line, like in a spell checker.
+    Wavy = 1,
+    /// A line made of short dashes.
+    Dashed = 2,
+    /// A line made of dots.
+    Dotted = 3,
+    /// Two parallel straight lines.
+    Double = 4,
 }
 
 /// The properties that can be applied to a strikethrough.
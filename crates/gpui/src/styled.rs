@@ -1,7 +1,7 @@
 use crate::{
     self as gpui, px, relative, rems, AbsoluteLength, AlignItems, CursorStyle, DefiniteLength,
     Fill, FlexDirection, FlexWrap, Font, FontStyle, FontWeight, Hsla, JustifyContent, Length,
-    SharedString, StrikethroughStyle, StyleRefinement, TextOverflow, WhiteSpace,
+    SharedString, StrikethroughStyle, StyleRefinement, TextOverflow, UnderlineKind, WhiteSpace,
 };
 use crate::{TextAlign, TextStyleRefinement};
 pub use gpui_macros::{
@@ -520,7 +520,7 @@ pub trait Styled: Sized {
     fn text_decoration_solid(mut self) -> Self {
         let style = self.text_style().get_or_insert_with(Default::default);
         let underline = style.underline.get_or_insert_with(Default::default);
-        underline.wavy = false;
+        underline.kind = UnderlineKind::Solid;
         self
     }
 
@@ -529,7 +529,34 @@ pub trait Styled: Sized {
     fn text_decoration_wavy(mut self) -> Self {
         let style = self.text_style().get_or_insert_with(Default::default);
         let underline = style.underline.get_or_insert_with(Default::default);
-        underline.wavy = true;
+        underline.kind = UnderlineKind::Wavy;
+        self
+    }
+
+    /// Sets the text decoration style to a dashed line.
+    /// [Docs](https://tailwindcss.com/docs/text-decoration-style)
+    fn text_decoration_dashed(mut self) -> Self {
+        let style = self.text_style().get_or_insert_with(Default::default);
+        let underline = style.underline.get_or_insert_with(Default::default);
+        underline.kind = UnderlineKind::Dashed;
+        self
+    }
+
+    /// Sets the text decoration style to a dotted line.
+    /// [Docs](https://tailwindcss.com/docs/text-decoration-style)
+    fn text_decoration_dotted(mut self) -> Self {
+        let style = self.text_style().get_or_insert_with(Default::default);
+        let underline = style.underline.get_or_insert_with(Default::default);
+        underline.kind = UnderlineKind::Dotted;
+        self
+    }
+
+    /// Sets the text decoration style to a double line.
+    /// [Docs](https://tailwindcss.com/docs/text-decoration-style)
+    fn text_decoration_double(mut self) -> Self {
+        let style = self.text_style().get_or_insert_with(Default::default);
+        let underline = style.underline.get_or_insert_with(Default::default);
+        underline.kind = UnderlineKind::Double;
         self
     }
 
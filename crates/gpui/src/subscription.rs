@@ -88,6 +88,11 @@ where
         (subscription, move || active.set(true))
     }
 
+    /// Returns whether there are no subscribers to the given emitter.
+    pub fn is_empty(&self, emitter: &EmitterKey) -> bool {
+        !self.0.lock().subscribers.contains_key(emitter)
+    }
+
     pub fn remove(&self, emitter: &EmitterKey) -> impl IntoIterator<Item = Callback> {
         let subscribers = self.0.lock().subscribers.remove(emitter);
         subscribers
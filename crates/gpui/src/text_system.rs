@@ -13,8 +13,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    px, Bounds, DevicePixels, Hsla, Pixels, PlatformTextSystem, Point, Result, SharedString, Size,
-    StrikethroughStyle, UnderlineStyle,
+    point, px, Bounds, DevicePixels, Hsla, Pixels, PlatformTextSystem, Point, Result, ScaledPixels,
+    SharedString, Size, StrikethroughStyle, UnderlineStyle,
 };
 use anyhow::anyhow;
 use collections::FxHashMap;
@@ -43,12 +43,47 @@ pub struct FontFamilyId(pub usize);
 
 pub(crate) const SUBPIXEL_VARIANTS: u8 = 4;
 
+/// Which axes glyphs are positioned on at a fraction of a device pixel, see
+/// [`TextSystem::set_subpixel_positioning`]. Each glyph is rasterized once for every fractional
+/// position it's drawn at, so positioning glyphs on more axes uses more of the sprite atlas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubpixelPositioning {
+    /// Glyphs are snapped to the nearest device pixel. Text is rendered the sharpest, but glyphs
+    /// visibly jump between pixels as text moves.
+    None,
+    /// Glyphs are positioned at a fraction of a pixel horizontally and snapped vertically, so
+    /// that the spacing of proportional text is preserved and text moves smoothly as containers
+    /// resize or animate.
+    Horizontal,
+    /// Glyphs are positioned at a fraction of a pixel on both axes.
+    #[default]
+    Both,
+}
+
+impl SubpixelPositioning {
+    /// Snaps the origin of a glyph to the nearest device pixel on the axes that aren't
+    /// positioned at a fraction of a pixel, and returns it along with its subpixel variant.
+    pub(crate) fn position_glyph(
+        self,
+        origin: Point<ScaledPixels>,
+    ) -> (Point<ScaledPixels>, Point<u8>) {
+        let origin = match self {
+            SubpixelPositioning::None => origin.map(|px| px.round()),
+            SubpixelPositioning::Horizontal => point(origin.x, origin.y.round()),
+            SubpixelPositioning::Both => origin,
+        };
+        let variant = origin.map(|px| (px.0.fract() * SUBPIXEL_VARIANTS as f32).floor() as u8);
+        (origin, variant)
+    }
+}
+
 /// The GPUI text rendering sub system.
 pub struct TextSystem {
     platform_text_system: Arc<dyn PlatformTextSystem>,
     font_ids_by_font: RwLock<FxHashMap<Font, Result<FontId>>>,
     font_metrics: RwLock<FxHashMap<FontId, FontMetrics>>,
     raster_bounds: RwLock<FxHashMap<RenderGlyphParams, Bounds<DevicePixels>>>,
+    subpixel_positioning: RwLock<SubpixelPositioning>,
     wrapper_pool: Mutex<FxHashMap<FontIdWithSize, Vec<LineWrapper>>>,
     font_runs_pool: Mutex<Vec<Vec<FontRun>>>,
     fallback_font_stack: SmallVec<[Font; 2]>,
@@ -60,6 +95,7 @@ impl TextSystem {
             platform_text_system,
             font_metrics: RwLock::default(),
             raster_bounds: RwLock::default(),
+            subpixel_positioning: RwLock::default(),
             font_ids_by_font: RwLock::default(),
             wrapper_pool: Mutex::default(),
             font_runs_pool: Mutex::default(),
@@ -95,6 +131,17 @@ impl TextSystem {
         self.platform_text_system.add_fonts(fonts)
     }
 
+    /// Returns which axes glyphs are positioned on at a fraction of a device pixel.
+    pub fn subpixel_positioning(&self) -> SubpixelPositioning {
+        *self.subpixel_positioning.read()
+    }
+
+    /// Sets which axes glyphs are positioned on at a fraction of a device pixel. This takes
+    /// effect the next time each window is drawn, see [`crate::App::refresh_windows`].
+    pub fn set_subpixel_positioning(&self, positioning: SubpixelPositioning) {
+        *self.subpixel_positioning.write() = positioning;
+    }
+
     /// Get the FontId for the configure font family and style.
     pub fn font_id(&self, font: &Font) -> Result<FontId> {
         fn clone_font_id_result(font_id: &Result<FontId>) -> Result<FontId> {
@@ -819,3 +866,25 @@ impl FontMetrics {
         (self.bounding_box / self.units_per_em as f32 * font_size.0).map(px)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_subpixel_positioning() {
+        let origin = point(ScaledPixels(10.3), ScaledPixels(20.6));
+        assert_eq!(
+            SubpixelPositioning::None.position_glyph(origin),
+            (point(ScaledPixels(10.), ScaledPixels(21.)), point(0, 0))
+        );
+        assert_eq!(
+            SubpixelPositioning::Horizontal.position_glyph(origin),
+            (point(ScaledPixels(10.3), ScaledPixels(21.)), point(1, 0))
+        );
+        assert_eq!(
+            SubpixelPositioning::Both.position_glyph(origin),
+            (origin, point(1, 2))
+        );
+    }
+}
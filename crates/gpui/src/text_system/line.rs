@@ -279,7 +279,7 @@ fn paint_line(
                                 UnderlineStyle {
                                     color: Some(run_underline.color.unwrap_or(style_run.color)),
                                     thickness: run_underline.thickness,
-                                    wavy: run_underline.wavy,
+                                    kind: run_underline.kind,
                                 },
                             ));
                         }
@@ -0,0 +1,186 @@
+use crate::{App, SharedString};
+
+type UndoCallback = Box<dyn FnMut(&mut App)>;
+
+/// An operation that can be undone and redone, see [`App::register_undo`].
+struct UndoOperation {
+    undo: UndoCallback,
+    redo: UndoCallback,
+}
+
+/// A group of operations that are undone and redone together, see [`App::transact`].
+pub(crate) struct UndoTransaction {
+    label: SharedString,
+    operations: Vec<UndoOperation>,
+}
+
+impl UndoTransaction {
+    pub(crate) fn undo(&mut self, cx: &mut App) {
+        for operation in self.operations.iter_mut().rev() {
+            (operation.undo)(cx);
+        }
+    }
+
+    pub(crate) fn redo(&mut self, cx: &mut App) {
+        for operation in &mut self.operations {
+            (operation.redo)(cx);
+        }
+    }
+}
+
+/// The application's history of reversible operations.
+#[derive(Default)]
+pub(crate) struct UndoStack {
+    undo: Vec<UndoTransaction>,
+    redo: Vec<UndoTransaction>,
+    /// Transactions that are being built, innermost last.
+    pending: Vec<UndoTransaction>,
+    /// Set while undoing or redoing, so that operations performed by callbacks aren't recorded.
+    replaying: bool,
+}
+
+impl UndoStack {
+    pub(crate) fn start_transaction(&mut self, label: SharedString) {
+        self.pending.push(UndoTransaction {
+            label,
+            operations: Vec::new(),
+        });
+    }
+
+    pub(crate) fn end_transaction(&mut self) {
+        let Some(transaction) = self.pending.pop() else {
+            return;
+        };
+        if let Some(parent) = self.pending.last_mut() {
+            parent.operations.extend(transaction.operations);
+        } else if !transaction.operations.is_empty() {
+            self.undo.push(transaction);
+            self.redo.clear();
+        }
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        label: SharedString,
+        undo: impl FnMut(&mut App) + 'static,
+        redo: impl FnMut(&mut App) + 'static,
+    ) {
+        if self.replaying {
+            return;
+        }
+        let operation = UndoOperation {
+            undo: Box::new(undo),
+            redo: Box::new(redo),
+        };
+        if let Some(transaction) = self.pending.last_mut() {
+            transaction.operations.push(operation);
+        } else {
+            self.undo.push(UndoTransaction {
+                label,
+                operations: vec![operation],
+            });
+            self.redo.clear();
+        }
+    }
+
+    pub(crate) fn undo_label(&self) -> Option<&SharedString> {
+        self.undo.last().map(|transaction| &transaction.label)
+    }
+
+    pub(crate) fn redo_label(&self) -> Option<&SharedString> {
+        self.redo.last().map(|transaction| &transaction.label)
+    }
+
+    /// Removes the transaction to undo, which must be passed to [`UndoStack::finish_undo`] after
+    /// reversing it. It's removed right away so that it's only undone once, even if undo is
+    /// requested again before it has been reversed.
+    pub(crate) fn start_undo(&mut self) -> Option<UndoTransaction> {
+        self.undo.pop()
+    }
+
+    pub(crate) fn finish_undo(&mut self, transaction: UndoTransaction) {
+        self.redo.push(transaction);
+    }
+
+    /// Removes the transaction to redo, which must be passed to [`UndoStack::finish_redo`] after
+    /// performing it.
+    pub(crate) fn start_redo(&mut self) -> Option<UndoTransaction> {
+        self.redo.pop()
+    }
+
+    pub(crate) fn finish_redo(&mut self, transaction: UndoTransaction) {
+        self.undo.push(transaction);
+    }
+
+    /// Sets whether a transaction is being undone or redone, during which operations aren't
+    /// recorded.
+    pub(crate) fn set_replaying(&mut self, replaying: bool) {
+        self.replaying = replaying;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{self as gpui, AppContext as _, Context, TestAppContext};
+
+    fn increment(count: &mut i32, cx: &mut Context<i32>) {
+        *count += 1;
+        cx.register_undo("Increment", |count, _| *count -= 1, |count, _| *count += 1);
+    }
+
+    #[gpui::test]
+    fn test_undo_redo(cx: &mut TestAppContext) {
+        let counter = cx.new(|_| 0);
+        cx.update(|cx| {
+            cx.transact("Increment twice", |cx| {
+                counter.update(cx, increment);
+                counter.update(cx, increment);
+            });
+            counter.update(cx, increment);
+        });
+        counter.read_with(cx, |count, _| assert_eq!(*count, 3));
+
+        cx.update(|cx| {
+            assert_eq!(cx.undo_label().unwrap(), "Increment");
+            assert!(cx.undo());
+        });
+        counter.read_with(cx, |count, _| assert_eq!(*count, 2));
+
+        // Undoing while the entity is being updated reverses the transaction afterwards.
+        counter.update(cx, |_, cx| {
+            assert_eq!(cx.undo_label().unwrap(), "Increment twice");
+            assert!(cx.undo());
+        });
+        counter.read_with(cx, |count, _| assert_eq!(*count, 0));
+        cx.update(|cx| assert!(!cx.undo()));
+
+        cx.update(|cx| assert!(cx.redo()));
+        counter.read_with(cx, |count, _| assert_eq!(*count, 2));
+
+        counter.update(cx, increment);
+        cx.update(|cx| {
+            assert_eq!(cx.redo_label(), None);
+            assert!(!cx.redo());
+        });
+    }
+
+    #[gpui::test]
+    fn test_repeated_undo(cx: &mut TestAppContext) {
+        let counter = cx.new(|_| 0);
+        counter.update(cx, increment);
+
+        // The transaction is consumed by the first undo, even though it's reversed later.
+        cx.update(|cx| {
+            assert!(cx.undo());
+            assert_eq!(cx.undo_label(), None);
+            assert!(!cx.undo());
+        });
+        counter.read_with(cx, |count, _| assert_eq!(*count, 0));
+        cx.update(|cx| assert_eq!(cx.redo_label().unwrap(), "Increment"));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
-    AnyElement, AnyEntity, AnyWeakEntity, App, Bounds, ContentMask, Context, Element, ElementId,
-    Entity, EntityId, GlobalElementId, IntoElement, LayoutId, PaintIndex, Pixels,
-    PrepaintStateIndex, Render, Style, StyleRefinement, TextStyle, WeakEntity,
+    AnyElement, AnyEntity, AnyWeakEntity, App, Bounds, ConsumedValue, ContentMask, Context,
+    Element, ElementId, Entity, EntityId, GlobalElementId, IntoElement, LayoutId, PaintIndex,
+    Pixels, PrepaintStateIndex, Render, Style, StyleRefinement, TextStyle, WeakEntity,
 };
 use crate::{Empty, Window};
 use anyhow::Result;
@@ -9,13 +9,18 @@ use collections::FxHashSet;
 use refineable::Refineable;
 use std::mem;
 use std::rc::Rc;
-use std::{any::TypeId, fmt, ops::Range};
+use std::{
+    any::{type_name, TypeId},
+    fmt,
+    ops::Range,
+};
 
 struct AnyViewState {
     prepaint_range: Range<PrepaintStateIndex>,
     paint_range: Range<PaintIndex>,
     cache_key: ViewCacheKey,
     accessed_entities: FxHashSet<EntityId>,
+    consumed_values: Vec<ConsumedValue>,
 }
 
 #[derive(Default)]
@@ -39,9 +44,13 @@ impl<V: Render> Element for Entity<V> {
         window: &mut Window,
         cx: &mut App,
     ) -> (LayoutId, Self::RequestLayoutState) {
-        let mut element = self.update(cx, |view, cx| view.render(window, cx).into_any_element());
-        let layout_id = element.request_layout(window, cx);
-        (layout_id, element)
+        window.record_view_type(self.entity_id(), type_name::<V>());
+        window.profile_view(self.entity_id(), |window| {
+            let mut element =
+                self.update(cx, |view, cx| view.render(window, cx).into_any_element());
+            let layout_id = element.request_layout(window, cx);
+            (layout_id, element)
+        })
     }
 
     fn prepaint(
@@ -53,7 +62,7 @@ impl<V: Render> Element for Entity<V> {
         cx: &mut App,
     ) {
         window.set_view_id(self.entity_id());
-        element.prepaint(window, cx);
+        window.profile_view(self.entity_id(), |window| element.prepaint(window, cx));
     }
 
     fn paint(
@@ -65,7 +74,7 @@ impl<V: Render> Element for Entity<V> {
         window: &mut Window,
         cx: &mut App,
     ) {
-        element.paint(window, cx);
+        window.profile_view(self.entity_id(), |window| element.paint(window, cx));
     }
 }
 
@@ -186,11 +195,13 @@ impl Element for AnyView {
                             && element_state.cache_key.text_style == text_style
                             && !window.dirty_views.contains(&self.entity_id())
                             && !window.refreshing
+                            && !window.consumed_values_changed(&element_state.consumed_values)
                         {
                             let prepaint_start = window.prepaint_index();
                             window.reuse_prepaint(element_state.prepaint_range.clone());
                             cx.entities
                                 .extend_accessed(&element_state.accessed_entities);
+                            window.extend_consumed_values(&element_state.consumed_values);
                             let prepaint_end = window.prepaint_index();
                             element_state.prepaint_range = prepaint_start..prepaint_end;
 
@@ -200,13 +211,15 @@ impl Element for AnyView {
 
                     let refreshing = mem::replace(&mut window.refreshing, true);
                     let prepaint_start = window.prepaint_index();
-                    let (mut element, accessed_entities) =
-                        window.with_rendered_view(self.entity_id(), |window| {
-                            cx.detect_accessed_entities(|cx| {
-                                let mut element = (self.render)(self, window, cx);
-                                element.layout_as_root(bounds.size.into(), window, cx);
-                                element.prepaint_at(bounds.origin, window, cx);
-                                element
+                    let ((mut element, accessed_entities), consumed_values) = window
+                        .detect_consumed_values(|window| {
+                            window.with_rendered_view(self.entity_id(), |window| {
+                                cx.detect_accessed_entities(|cx| {
+                                    let mut element = (self.render)(self, window, cx);
+                                    element.layout_as_root(bounds.size.into(), window, cx);
+                                    element.prepaint_at(bounds.origin, window, cx);
+                                    element
+                                })
                             })
                         });
 
@@ -217,6 +230,7 @@ impl Element for AnyView {
                         Some(element),
                         AnyViewState {
                             accessed_entities,
+                            consumed_values,
                             prepaint_range: prepaint_start..prepaint_end,
                             paint_range: PaintIndex::default()..PaintIndex::default(),
                             cache_key: ViewCacheKey {
@@ -336,6 +350,7 @@ impl std::fmt::Debug for AnyWeakView {
 
 mod any_view {
     use crate::{AnyElement, AnyView, App, IntoElement, Render, Window};
+    use std::any::type_name;
 
     pub(crate) fn render<V: 'static + Render>(
         view: &AnyView,
@@ -343,6 +358,7 @@ mod any_view {
         cx: &mut App,
     ) -> AnyElement {
         let view = view.clone().downcast::<V>().unwrap();
+        window.record_view_type(view.entity_id(), type_name::<V>());
         view.update(cx, |view, cx| view.render(window, cx).into_any_element())
     }
 }
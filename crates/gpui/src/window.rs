@@ -1,29 +1,33 @@
 use crate::{
-    point, prelude::*, px, size, transparent_black, Action, AnyDrag, AnyElement, AnyTooltip,
-    AnyView, App, AppContext, Arena, Asset, AsyncWindowContext, AvailableSpace, Background, Bounds,
-    BoxShadow, Context, Corners, CursorStyle, Decorations, DevicePixels, DispatchActionListener,
-    DispatchNodeId, DispatchTree, DisplayId, Edges, Effect, Entity, EntityId, EventEmitter,
-    FileDropEvent, FontId, Global, GlobalElementId, GlyphId, GpuSpecs, Hsla, InputHandler, IsZero,
-    KeyBinding, KeyContext, KeyDownEvent, KeyEvent, Keystroke, KeystrokeEvent, LayoutId,
-    LineLayoutIndex, Modifiers, ModifiersChangedEvent, MonochromeSprite, MouseButton, MouseEvent,
-    MouseMoveEvent, MouseUpEvent, Path, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
-    PlatformInputHandler, PlatformWindow, Point, PolychromeSprite, PromptLevel, Quad, Render,
-    RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams, Replay, ResizeEdge,
-    ScaledPixels, Scene, Shadow, SharedString, Size, StrikethroughStyle, Style, SubscriberSet,
-    Subscription, TaffyLayoutEngine, Task, TextStyle, TextStyleRefinement, TransformationMatrix,
-    Underline, UnderlineStyle, WindowAppearance, WindowBackgroundAppearance, WindowBounds,
-    WindowControls, WindowDecorations, WindowOptions, WindowParams, WindowTextSystem,
-    SMOOTH_SVG_SCALE_FACTOR, SUBPIXEL_VARIANTS,
+    live_region_announcements, point, prelude::*, px, size, transparent_black, AccessibilityNode,
+    AccessibilityProperties, Action, AnyDrag, AnyElement, AnyTooltip, AnyView, App, AppContext,
+    Arena, Asset, AsyncWindowContext, AtlasKey, AvailableSpace, Background, Bounds, BoxShadow,
+    Context, Corners, CursorStyle, CustomShader, Decorations, DevicePixels, DispatchActionListener,
+    DispatchNodeId, DispatchTree, DisplayId, Edges, Effect, Entity, EntityId, EntityMap,
+    EventEmitter, FileDropEvent, FontId, Global, GlobalElementId, GlyphId, GpuSpecs, Hsla,
+    ImageFilter, InputHandler, IsZero, KeyBinding, KeyContext, KeyDownEvent, KeyEvent, Keystroke,
+    KeystrokeEvent, LayerEffect, LayerSprite, LayoutId, LineLayoutIndex, Modifiers,
+    ModifiersChangedEvent, MonochromeSprite, MouseButton, MouseEvent, MouseMoveEvent, MouseUpEvent,
+    Path, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler,
+    PlatformWindow, Point, PolychromeSprite, PromptLevel, Quad, Render, RenderGlyphParams,
+    RenderImage, RenderImageParams, RenderSvgParams, Replay, ResizeEdge, ScaledPixels, Scene,
+    ShaderQuad, ShaderUniforms, Shadow, SharedString, Size, StrikethroughStyle, Style,
+    SubscriberSet, Subscription, TaffyLayoutEngine, Task, TextStyle, TextStyleRefinement,
+    TitlebarDoubleClickAction, TransformationMatrix, Underline, UnderlineKind, UnderlineStyle,
+    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControls, WindowDecorations,
+    WindowLevel, WindowOptions, WindowParams, WindowState, WindowTextSystem,
+    SMOOTH_SVG_SCALE_FACTOR,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::{FxHashMap, FxHashSet};
 use derive_more::{Deref, DerefMut};
 use futures::channel::oneshot;
 use futures::FutureExt;
+use image::RgbaImage;
 #[cfg(target_os = "macos")]
 use media::core_video::CVImageBuffer;
 use parking_lot::RwLock;
-use raw_window_handle::{HandleError, HasWindowHandle};
+use raw_window_handle::{HandleError, HasDisplayHandle, HasWindowHandle};
 use refineable::Refineable;
 use slotmap::SlotMap;
 use smallvec::SmallVec;
@@ -38,6 +42,7 @@ use std::{
     marker::PhantomData,
     mem,
     ops::{DerefMut, Range},
+    path::PathBuf,
     rc::Rc,
     sync::{
         atomic::{AtomicUsize, Ordering::SeqCst},
@@ -49,8 +54,16 @@ use util::post_inc;
 use util::{measure, ResultExt};
 use uuid::Uuid;
 
+mod atlas;
+mod frame_stats;
+mod inspector;
+mod profiler;
 mod prompts;
 
+pub use atlas::*;
+pub use frame_stats::*;
+pub use inspector::*;
+pub use profiler::*;
 pub use prompts::*;
 
 pub(crate) const DEFAULT_WINDOW_SIZE: Size<Pixels> = size(px(1024.), px(700.));
@@ -172,6 +185,8 @@ impl WindowInvalidator {
 
 type AnyObserver = Box<dyn FnMut(&mut Window, &mut App) -> bool + 'static>;
 
+type AnyFrameObserver = Box<dyn FnMut(Instant, &mut Window, &mut App) -> bool + 'static>;
+
 pub(crate) type AnyWindowFocusListener =
     Box<dyn FnMut(&WindowFocusEvent, &mut Window, &mut App) -> bool + 'static>;
 
@@ -403,6 +418,51 @@ pub struct DismissEvent;
 
 type FrameCallback = Box<dyn FnOnce(&mut Window, &mut App)>;
 
+/// Decides which of the platform's frame requests a window actually renders.
+#[derive(Default)]
+struct FramePacing {
+    half_rate: Cell<bool>,
+    skip_next_frame: Cell<bool>,
+    render_on_demand: Cell<bool>,
+    continuous_rendering_requests: Rc<Cell<usize>>,
+    max_frame_rate: Cell<Option<f32>>,
+    last_frame_start: Cell<Option<Instant>>,
+}
+
+impl FramePacing {
+    /// Returns whether the current frame request should be skipped, alternating
+    /// between skipped and rendered frames when rendering at half rate, and dropping
+    /// frames that would exceed the maximum frame rate.
+    fn should_skip_frame(&self) -> bool {
+        if self.half_rate.get() {
+            let skip = self.skip_next_frame.get();
+            self.skip_next_frame.set(!skip);
+            if skip {
+                return true;
+            }
+        }
+
+        if let Some(max_frame_rate) = self.max_frame_rate.get() {
+            // Leave some slack so that jitter in the platform's frame requests doesn't
+            // cause every other frame to be dropped when the cap divides the refresh rate.
+            let min_frame_interval = Duration::from_secs_f32(0.9 / max_frame_rate);
+            let now = Instant::now();
+            if self
+                .last_frame_start
+                .get()
+                .map_or(false, |last_frame_start| {
+                    now.duration_since(last_frame_start) < min_frame_interval
+                })
+            {
+                return true;
+            }
+            self.last_frame_start.set(Some(now));
+        }
+
+        false
+    }
+}
+
 pub(crate) type AnyMouseListener =
     Box<dyn FnMut(&dyn Any, DispatchPhase, &mut Window, &mut App) + 'static>;
 
@@ -476,17 +536,61 @@ pub(crate) struct TooltipRequest {
     tooltip: AnyTooltip,
 }
 
+/// A value provided to part of the element tree, see [`Window::with_provided_value`].
+pub(crate) trait ProvidedValue {
+    fn as_any(&self) -> &dyn Any;
+    fn eq_value(&self, other: &dyn ProvidedValue) -> bool;
+}
+
+impl<T: PartialEq + 'static> ProvidedValue for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_value(&self, other: &dyn ProvidedValue) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+}
+
+/// A provided value that was read while rendering a view, so that a cached rendering of the view
+/// can be discarded once the value changes, see [`Window::consume`].
+#[derive(Clone)]
+pub(crate) struct ConsumedValue {
+    type_id: TypeId,
+    /// The value that was read and its index in the provided value stack, or `None` if no value
+    /// of the type was provided.
+    provider: Option<(usize, Rc<dyn ProvidedValue>)>,
+}
+
 pub(crate) struct DeferredDraw {
     priority: usize,
     parent_node: DispatchNodeId,
     element_id_stack: SmallVec<[ElementId; 32]>,
     text_style_stack: Vec<TextStyleRefinement>,
+    provided_value_stack: Vec<(TypeId, Rc<dyn ProvidedValue>)>,
     element: Option<AnyElement>,
     absolute_offset: Point<Pixels>,
     prepaint_range: Range<PrepaintStateIndex>,
     paint_range: Range<PaintIndex>,
 }
 
+/// The depth of the window's drawing stacks, so they can be unwound if drawing an element
+/// panics, see [`crate::ErrorBoundary`].
+pub(crate) struct DrawStackDepths {
+    element_id: usize,
+    text_style: usize,
+    provided_value: usize,
+    rem_size_override: usize,
+    accessibility_node: usize,
+    inspected_element: usize,
+    rendered_entity: usize,
+    element_offset: usize,
+    element_opacity: Option<f32>,
+    content_mask: usize,
+    dispatch_node: usize,
+    unwound_leases: usize,
+}
+
 pub(crate) struct Frame {
     pub(crate) focus: Option<FocusId>,
     pub(crate) window_active: bool,
@@ -500,6 +604,8 @@ pub(crate) struct Frame {
     pub(crate) input_handlers: Vec<Option<PlatformInputHandler>>,
     pub(crate) tooltip_requests: Vec<Option<TooltipRequest>>,
     pub(crate) cursor_styles: Vec<CursorStyleRequest>,
+    pub(crate) accessibility_nodes: Vec<AccessibilityNode>,
+    pub(crate) inspected_elements: Vec<InspectedElement>,
     #[cfg(any(test, feature = "test-support"))]
     pub(crate) debug_bounds: FxHashMap<String, Bounds<Pixels>>,
 }
@@ -520,6 +626,8 @@ pub(crate) struct PaintIndex {
     mouse_listeners_index: usize,
     input_handlers_index: usize,
     cursor_styles_index: usize,
+    accessibility_nodes_index: usize,
+    inspected_elements_index: usize,
     accessed_element_states_index: usize,
     line_layout_index: LineLayoutIndex,
 }
@@ -539,6 +647,8 @@ impl Frame {
             input_handlers: Vec::new(),
             tooltip_requests: Vec::new(),
             cursor_styles: Vec::new(),
+            accessibility_nodes: Vec::new(),
+            inspected_elements: Vec::new(),
 
             #[cfg(any(test, feature = "test-support"))]
             debug_bounds: FxHashMap::default(),
@@ -554,6 +664,8 @@ impl Frame {
         self.input_handlers.clear();
         self.tooltip_requests.clear();
         self.cursor_styles.clear();
+        self.accessibility_nodes.clear();
+        self.inspected_elements.clear();
         self.hitboxes.clear();
         self.deferred_draws.clear();
         self.focus = None;
@@ -601,6 +713,8 @@ pub struct Window {
     pub(crate) platform_window: Box<dyn PlatformWindow>,
     display_id: Option<DisplayId>,
     sprite_atlas: Arc<dyn PlatformAtlas>,
+    atlas_usage: RefCell<AtlasUsage>,
+    downscaled_images: RefCell<FxHashMap<RenderImageParams, DownscaledImage>>,
     text_system: Arc<WindowTextSystem>,
     rem_size: Pixels,
     /// The stack of override values for the window's rem size.
@@ -613,6 +727,19 @@ pub struct Window {
     pub(crate) root: Option<AnyView>,
     pub(crate) element_id_stack: SmallVec<[ElementId; 32]>,
     pub(crate) text_style_stack: Vec<TextStyleRefinement>,
+    provided_value_stack: Vec<(TypeId, Rc<dyn ProvidedValue>)>,
+    consumed_values: RefCell<Vec<ConsumedValue>>,
+    accessibility_node_stack: Vec<usize>,
+    inspector_enabled: bool,
+    recording_element_tree: bool,
+    inspected_element_stack: Vec<usize>,
+    frame_profiler: Option<FrameProfiler>,
+    render_stats: RenderStats,
+    frame_stats_listeners: SubscriberSet<(), FrameStatsListener>,
+    /// The timings of the last frame drawn while frame stats were being collected, which are
+    /// reported along with its present time once the next frame is drawn.
+    unreported_frame: Option<FrameTimings>,
+    last_present: Cell<Duration>,
     pub(crate) rendered_entity_stack: Vec<EntityId>,
     pub(crate) element_offset_stack: Vec<Point<Pixels>>,
     pub(crate) element_opacity: Option<f32>,
@@ -632,21 +759,37 @@ pub struct Window {
     mouse_hit_test: HitTest,
     modifiers: Modifiers,
     scale_factor: f32,
+    stale_scale_factor: Option<f32>,
+    pub(crate) scale_factor_observers: SubscriberSet<(), AnyObserver>,
     pub(crate) bounds_observers: SubscriberSet<(), AnyObserver>,
     appearance: WindowAppearance,
     pub(crate) appearance_observers: SubscriberSet<(), AnyObserver>,
+    background_appearance: WindowBackgroundAppearance,
+    reduce_transparency: bool,
     active: Rc<Cell<bool>>,
     hovered: Rc<Cell<bool>>,
     pub(crate) needs_present: Rc<Cell<bool>>,
+    /// The region that has changed since a frame was last presented, in device pixels.
+    damage: Cell<Option<Bounds<ScaledPixels>>>,
+    /// Whether the next frame must be presented in full, such as after the window is resized.
+    full_damage: Cell<bool>,
+    frame_pacing: Rc<FramePacing>,
     pub(crate) last_input_timestamp: Rc<Cell<Instant>>,
     pub(crate) refreshing: bool,
     pub(crate) activation_observers: SubscriberSet<(), AnyObserver>,
+    window_state: WindowState,
+    pub(crate) window_state_observers: SubscriberSet<(), AnyObserver>,
+    animation_frame_observers: SubscriberSet<(), AnyFrameObserver>,
+    visible: bool,
+    pub(crate) visibility_observers: SubscriberSet<(), AnyObserver>,
     pub(crate) focus: Option<FocusId>,
     focus_enabled: bool,
     pending_input: Option<PendingInput>,
     pending_modifier: ModifierState,
     pub(crate) pending_input_observers: SubscriberSet<(), AnyObserver>,
     prompt: Option<RenderablePromptHandle>,
+    parent: Option<AnyWindowHandle>,
+    modal: bool,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -676,6 +819,33 @@ pub(crate) struct ElementStateBox {
     pub(crate) type_name: &'static str,
 }
 
+fn effective_background_appearance(
+    background_appearance: WindowBackgroundAppearance,
+    reduce_transparency: bool,
+) -> WindowBackgroundAppearance {
+    match background_appearance {
+        WindowBackgroundAppearance::Blurred if reduce_transparency => {
+            WindowBackgroundAppearance::Opaque
+        }
+        background_appearance => background_appearance,
+    }
+}
+
+/// Frame data that forms a tree refers to parents by index, so when reusing a range of it from the
+/// previous frame, rebase indices within the range and attach the range's top-level entries to the
+/// entry currently being painted.
+fn rebase_parent(
+    parent: Option<usize>,
+    reused_start: usize,
+    next_start: usize,
+    current_parent: Option<usize>,
+) -> Option<usize> {
+    match parent {
+        Some(parent) if parent >= reused_start => Some(parent - reused_start + next_start),
+        _ => current_parent,
+    }
+}
+
 fn default_bounds(display_id: Option<DisplayId>, cx: &mut App) -> Bounds<Pixels> {
     const DEFAULT_WINDOW_OFFSET: Point<Pixels> = point(px(0.), px(35.));
 
@@ -717,11 +887,17 @@ impl Window {
             app_id,
             window_min_size,
             window_decorations,
+            parent,
+            modal,
+            level,
+            hdr,
         } = options;
 
         let bounds = window_bounds
             .map(|bounds| bounds.get_bounds())
             .unwrap_or_else(|| default_bounds(display_id, cx));
+        let parent_window_handle =
+            parent.and_then(|parent| cx.raw_window_handles.get(&parent.id).copied());
         let mut platform_window = cx.platform.open_window(
             handle,
             WindowParams {
@@ -733,10 +909,16 @@ impl Window {
                 show,
                 display_id,
                 window_min_size,
+                parent: parent_window_handle,
+                modal: modal && parent_window_handle.is_some(),
+                level,
+                antialiasing: cx.antialiasing,
+                hdr,
             },
         )?;
         let display_id = platform_window.display().map(|display| display.id());
         let sprite_atlas = platform_window.sprite_atlas();
+        sprite_atlas.set_page_size(cx.atlas_config.page_size);
         let mouse_position = platform_window.mouse_position();
         let modifiers = platform_window.modifiers();
         let content_size = platform_window.content_size();
@@ -746,13 +928,19 @@ impl Window {
         let invalidator = WindowInvalidator::new();
         let active = Rc::new(Cell::new(platform_window.is_active()));
         let hovered = Rc::new(Cell::new(platform_window.is_hovered()));
+        let visible = !platform_window.is_occluded() && !platform_window.is_minimized();
         let needs_present = Rc::new(Cell::new(false));
+        let frame_pacing = Rc::new(FramePacing::default());
         let next_frame_callbacks: Rc<RefCell<Vec<FrameCallback>>> = Default::default();
         let last_input_timestamp = Rc::new(Cell::new(Instant::now()));
 
         platform_window
             .request_decorations(window_decorations.unwrap_or(WindowDecorations::Server));
-        platform_window.set_background_appearance(window_background);
+        let reduce_transparency = cx.accessibility_preferences().reduce_transparency;
+        platform_window.set_background_appearance(effective_background_appearance(
+            window_background,
+            reduce_transparency,
+        ));
 
         if let Some(ref window_open_state) = window_bounds {
             match window_open_state {
@@ -773,9 +961,19 @@ impl Window {
             let invalidator = invalidator.clone();
             let active = active.clone();
             let needs_present = needs_present.clone();
+            let frame_pacing = frame_pacing.clone();
             let next_frame_callbacks = next_frame_callbacks.clone();
             let last_input_timestamp = last_input_timestamp.clone();
             move |request_frame_options| {
+                if !request_frame_options.require_presentation && frame_pacing.should_skip_frame() {
+                    // The platform may rely on the frame being completed to schedule
+                    // its next frame request, so complete it without drawing.
+                    handle
+                        .update(&mut cx, |_, window, _| window.complete_frame())
+                        .log_err();
+                    return;
+                }
+
                 let next_frame_callbacks = next_frame_callbacks.take();
                 if !next_frame_callbacks.is_empty() {
                     handle
@@ -787,11 +985,21 @@ impl Window {
                         .log_err();
                 }
 
+                if frame_pacing.continuous_rendering_requests.get() > 0 {
+                    handle
+                        .update(&mut cx, |_, window, cx| {
+                            window.animation_frame(cx);
+                            window.refresh();
+                        })
+                        .log_err();
+                }
+
                 // Keep presenting the current scene for 1 extra second since the
                 // last input to prevent the display from underclocking the refresh rate.
                 let needs_present = request_frame_options.require_presentation
                     || needs_present.get()
-                    || (active.get()
+                    || (!frame_pacing.render_on_demand.get()
+                        && active.get()
                         && last_input_timestamp.get().elapsed() < Duration::from_secs(1));
 
                 if invalidator.is_dirty() {
@@ -799,7 +1007,12 @@ impl Window {
                         handle
                             .update(&mut cx, |_, window, cx| {
                                 window.draw(cx);
-                                window.present();
+                                // Frames that paint what's already on screen, like a
+                                // notification that didn't change anything visible, don't need
+                                // to be presented.
+                                if needs_present || window.needs_present.get() {
+                                    window.present();
+                                }
                             })
                             .log_err();
                     })
@@ -840,6 +1053,17 @@ impl Window {
                     .log_err();
             }
         }));
+        platform_window.on_occlusion_change(Box::new({
+            let mut cx = cx.to_async();
+            move |_| {
+                handle
+                    .update(&mut cx, |_, window, cx| {
+                        window.window_state_changed(cx);
+                        window.visibility_changed(cx);
+                    })
+                    .log_err();
+            }
+        }));
         platform_window.on_active_status_change(Box::new({
             let mut cx = cx.to_async();
             move |active| {
@@ -850,7 +1074,9 @@ impl Window {
                             .activation_observers
                             .clone()
                             .retain(&(), |callback| callback(window, cx));
+                        window.window_state_changed(cx);
                         window.refresh();
+                        cx.window_activation_changed();
                     })
                     .log_err();
             }
@@ -881,6 +1107,7 @@ impl Window {
         }
 
         platform_window.map_window().unwrap();
+        let window_state = platform_window_state(&*platform_window);
 
         Ok(Window {
             handle,
@@ -889,6 +1116,8 @@ impl Window {
             platform_window,
             display_id,
             sprite_atlas,
+            atlas_usage: RefCell::default(),
+            downscaled_images: RefCell::default(),
             text_system,
             rem_size: px(16.),
             rem_size_override_stack: SmallVec::new(),
@@ -897,6 +1126,17 @@ impl Window {
             root: None,
             element_id_stack: SmallVec::default(),
             text_style_stack: Vec::new(),
+            provided_value_stack: Vec::new(),
+            consumed_values: RefCell::default(),
+            accessibility_node_stack: Vec::new(),
+            inspector_enabled: false,
+            recording_element_tree: false,
+            inspected_element_stack: Vec::new(),
+            frame_profiler: None,
+            render_stats: RenderStats::default(),
+            frame_stats_listeners: SubscriberSet::new(),
+            unreported_frame: None,
+            last_present: Cell::default(),
             rendered_entity_stack: Vec::new(),
             element_offset_stack: Vec::new(),
             content_mask_stack: Vec::new(),
@@ -916,21 +1156,35 @@ impl Window {
             mouse_hit_test: HitTest::default(),
             modifiers,
             scale_factor,
+            stale_scale_factor: None,
+            scale_factor_observers: SubscriberSet::new(),
             bounds_observers: SubscriberSet::new(),
             appearance,
             appearance_observers: SubscriberSet::new(),
+            background_appearance: window_background,
+            reduce_transparency,
             active,
             hovered,
             needs_present,
+            damage: Cell::new(None),
+            full_damage: Cell::new(true),
+            frame_pacing,
             last_input_timestamp,
             refreshing: false,
             activation_observers: SubscriberSet::new(),
+            window_state,
+            window_state_observers: SubscriberSet::new(),
+            animation_frame_observers: SubscriberSet::new(),
+            visible,
+            visibility_observers: SubscriberSet::new(),
             focus: None,
             focus_enabled: true,
             pending_input: None,
             pending_modifier: ModifierState::default(),
             pending_input_observers: SubscriberSet::new(),
             prompt: None,
+            parent,
+            modal: modal && parent.is_some(),
         })
     }
 
@@ -1105,7 +1359,10 @@ impl Window {
         self.platform_window.request_decorations(decorations);
     }
 
-    /// Start a window resize operation (Wayland)
+    /// Tells the platform to take control of resizing the window from the given edge,
+    /// for use while the mouse is pressed, e.g. on the borders of custom window decorations.
+    ///
+    /// Events may not be received during a resize operation.
     pub fn start_window_resize(&self, edge: ResizeEdge) {
         self.platform_window.start_window_resize(edge);
     }
@@ -1299,15 +1556,138 @@ impl Window {
     }
 
     fn bounds_changed(&mut self, cx: &mut App) {
+        let previous_scale_factor = self.scale_factor;
         self.scale_factor = self.platform_window.scale_factor();
         self.viewport_size = self.platform_window.content_size();
         self.display_id = self.platform_window.display().map(|display| display.id());
 
+        self.full_damage.set(true);
         self.refresh();
 
+        if self.scale_factor != previous_scale_factor {
+            self.stale_scale_factor.get_or_insert(previous_scale_factor);
+            self.scale_factor_observers
+                .clone()
+                .retain(&(), |callback| callback(self, cx));
+        }
         self.bounds_observers
             .clone()
             .retain(&(), |callback| callback(self, cx));
+        self.window_state_changed(cx);
+    }
+
+    /// After the window moves to a display with a different scale factor, glyphs are
+    /// rasterized again at the new scale. Once a frame has been drawn at that scale, the
+    /// glyphs rasterized for the old one are no longer referenced and can be freed.
+    fn evict_stale_glyphs(&mut self) {
+        let Some(stale_scale_factor) = self.stale_scale_factor.take() else {
+            return;
+        };
+        if stale_scale_factor == self.scale_factor {
+            return;
+        }
+        self.retain_atlas_tiles(|key| match key {
+            AtlasKey::Glyph(params) => params.scale_factor != stale_scale_factor,
+            AtlasKey::Svg(_) | AtlasKey::Image(_) => true,
+        });
+    }
+
+    /// Removes the renderings of the SVG at the given path from the sprite atlas, so that it's
+    /// rendered again from the asset source. See [`App::reload_assets`].
+    pub(crate) fn evict_svg(&self, path: &SharedString) {
+        // The SVG may be rendered into the same tile again, so its sprites' damage can't be
+        // detected by comparing scenes.
+        self.full_damage.set(true);
+        self.retain_atlas_tiles(|key| match key {
+            AtlasKey::Svg(params) => params.path != *path,
+            AtlasKey::Glyph(_) | AtlasKey::Image(_) => true,
+        });
+    }
+
+    /// Returns the tile for the given key from the sprite atlas, rasterizing it with `build` if
+    /// it isn't cached, and records that it was drawn so it's not evicted.
+    fn atlas_tile<'a>(
+        &self,
+        key: &AtlasKey,
+        build: &mut dyn FnMut() -> Result<Option<(Size<DevicePixels>, Cow<'a, [u8]>)>>,
+    ) -> Result<Option<AtlasTile>> {
+        let mut uploaded_bytes = None;
+        let tile = self.sprite_atlas.get_or_insert_with(key, &mut || {
+            let rasterized = build()?;
+            uploaded_bytes = rasterized.as_ref().map(|(_, bytes)| bytes.len());
+            Ok(rasterized)
+        })?;
+        let mut atlas_usage = self.atlas_usage.borrow_mut();
+        if let Some(bytes) = uploaded_bytes {
+            atlas_usage.record_upload(bytes);
+        }
+        if let Some(tile) = &tile {
+            atlas_usage.record(key, tile);
+        }
+        Ok(tile)
+    }
+
+    fn retain_atlas_tiles(&self, mut predicate: impl FnMut(&AtlasKey) -> bool) {
+        self.sprite_atlas.retain(&mut predicate);
+        self.atlas_usage.borrow_mut().retain(predicate);
+    }
+
+    /// Evicts the least recently drawn tiles from the sprite atlas once it exceeds the budget
+    /// set with [`App::set_atlas_config`].
+    fn evict_atlas_tiles(&self, cx: &App) {
+        let evicted_keys = self
+            .atlas_usage
+            .borrow_mut()
+            .finish_frame(&self.rendered_frame.scene, cx.atlas_config.budget);
+        for key in evicted_keys {
+            self.sprite_atlas.remove(&key);
+        }
+    }
+
+    pub(crate) fn set_atlas_page_size(&self, page_size: Size<DevicePixels>) {
+        self.sprite_atlas.set_page_size(page_size);
+    }
+
+    /// Returns how much of this window's sprite atlas, which caches rasterized glyphs, SVGs and
+    /// images, is in use.
+    pub fn atlas_stats(&self) -> AtlasStats {
+        self.atlas_usage.borrow().stats()
+    }
+
+    fn window_state_changed(&mut self, cx: &mut App) {
+        let window_state = self.window_state();
+        if window_state == self.window_state {
+            return;
+        }
+        self.window_state = window_state;
+
+        self.window_state_observers
+            .clone()
+            .retain(&(), |callback| callback(self, cx));
+        self.visibility_changed(cx);
+    }
+
+    fn visibility_changed(&mut self, cx: &mut App) {
+        let visible = self.is_visible();
+        if visible == self.visible {
+            return;
+        }
+        self.visible = visible;
+
+        self.visibility_observers
+            .clone()
+            .retain(&(), |callback| callback(self, cx));
+        if visible {
+            self.refresh();
+        }
+    }
+
+    /// Returns whether any part of the window may be visible on screen. Windows that are
+    /// minimized or entirely hidden, such as behind other windows or on another workspace,
+    /// are not visible. Use [`Context::observe_window_visibility`] to be notified when this
+    /// changes, for example to pause animations while the window can't be seen.
+    pub fn is_visible(&self) -> bool {
+        !self.platform_window.is_occluded() && !self.platform_window.is_minimized()
     }
 
     /// Returns the bounds of the current window in the global coordinate space, which could span across multiple displays.
@@ -1320,6 +1700,17 @@ impl Window {
         self.platform_window.is_fullscreen()
     }
 
+    /// Returns whether or not the window is currently minimized
+    pub fn is_minimized(&self) -> bool {
+        self.platform_window.is_minimized()
+    }
+
+    /// Returns whether the window is windowed, maximized, fullscreen, or minimized.
+    /// Use [`Context::observe_window_state`] to be notified when this changes.
+    pub fn window_state(&self) -> WindowState {
+        platform_window_state(&*self.platform_window)
+    }
+
     pub(crate) fn appearance_changed(&mut self, cx: &mut App) {
         self.appearance = self.platform_window.appearance();
 
@@ -1363,12 +1754,28 @@ impl Window {
         self.platform_window.zoom();
     }
 
+    /// Performs the given action as if the window's title bar was double-clicked,
+    /// resolving [`TitlebarDoubleClickAction::System`] to the platform's behavior.
+    pub fn titlebar_double_click(&self, action: TitlebarDoubleClickAction) {
+        let action = match action {
+            TitlebarDoubleClickAction::System => {
+                self.platform_window.titlebar_double_click_action()
+            }
+            action => action,
+        };
+        match action {
+            TitlebarDoubleClickAction::Zoom => self.zoom_window(),
+            TitlebarDoubleClickAction::Minimize => self.minimize_window(),
+            TitlebarDoubleClickAction::System | TitlebarDoubleClickAction::Ignore => {}
+        }
+    }
+
     /// Opens the native title bar context menu, useful when implementing client side decorations (Wayland and X11)
     pub fn show_window_menu(&self, position: Point<Pixels>) {
         self.platform_window.show_window_menu(position)
     }
 
-    /// Tells the compositor to take control of window movement (Wayland and X11)
+    /// Tells the platform to take control of window movement, for use while the mouse is pressed
     ///
     /// Events may not be received during a move operation.
     pub fn start_window_move(&self) {
@@ -1400,10 +1807,26 @@ impl Window {
         self.platform_window.set_app_id(app_id);
     }
 
-    /// Sets the window background appearance.
-    pub fn set_background_appearance(&self, background_appearance: WindowBackgroundAppearance) {
+    /// Sets the window background appearance. Blurred backgrounds are rendered opaque while
+    /// the system is set to reduce transparency, see [`App::accessibility_preferences`].
+    pub fn set_background_appearance(&mut self, background_appearance: WindowBackgroundAppearance) {
+        self.background_appearance = background_appearance;
         self.platform_window
-            .set_background_appearance(background_appearance);
+            .set_background_appearance(effective_background_appearance(
+                background_appearance,
+                self.reduce_transparency,
+            ));
+    }
+
+    pub(crate) fn accessibility_preferences_changed(&mut self, cx: &App) {
+        self.reduce_transparency = cx.accessibility_preferences().reduce_transparency;
+        self.set_background_appearance(self.background_appearance);
+        self.refresh();
+    }
+
+    /// Sets the level of the window, to keep it above other windows.
+    pub fn set_window_level(&self, level: WindowLevel) {
+        self.platform_window.set_level(level);
     }
 
     /// Mark the window as dirty at the platform level.
@@ -1419,11 +1842,147 @@ impl Window {
             .find(|display| Some(display.id()) == self.display_id)
     }
 
+    /// Returns the refresh rate, in hertz, of the display on which the window is visible,
+    /// if it is known.
+    pub fn refresh_rate(&self, cx: &App) -> Option<f32> {
+        self.display(cx)?.refresh_rate()
+    }
+
+    /// Returns the rate, in hertz, at which this window renders frames, taking
+    /// half-rate rendering and the frame rate cap into account. Returns `None` if the
+    /// display's refresh rate is unknown.
+    pub fn frame_rate(&self, cx: &App) -> Option<f32> {
+        let mut frame_rate = self.refresh_rate(cx)?;
+        if self.frame_pacing.half_rate.get() {
+            frame_rate /= 2.;
+        }
+        if let Some(max_frame_rate) = self.frame_pacing.max_frame_rate.get() {
+            frame_rate = frame_rate.min(max_frame_rate);
+        }
+        Some(frame_rate)
+    }
+
+    /// Returns the maximum number of frames per second this window will render, if capped.
+    pub fn max_frame_rate(&self) -> Option<f32> {
+        self.frame_pacing.max_frame_rate.get()
+    }
+
+    /// Cap the number of frames per second this window renders, or pass `None` to render
+    /// at the display's refresh rate. Animations are driven by elapsed time, so they run
+    /// at the same speed regardless of the cap.
+    pub fn set_max_frame_rate(&mut self, max_frame_rate: Option<f32>) {
+        let max_frame_rate = max_frame_rate.filter(|rate| *rate > 0.);
+        self.frame_pacing.max_frame_rate.set(max_frame_rate);
+        self.frame_pacing.last_frame_start.set(None);
+    }
+
+    /// Returns whether the window only renders on every other display refresh.
+    pub fn is_half_rate_rendering(&self) -> bool {
+        self.frame_pacing.half_rate.get()
+    }
+
+    /// Render on every other display refresh instead of every refresh. This halves the
+    /// cost of animations on high refresh rate displays, at the expense of smoothness.
+    /// Frames the platform requires for presentation, such as during a live resize,
+    /// are always rendered.
+    pub fn set_half_rate_rendering(&mut self, half_rate: bool) {
+        self.frame_pacing.half_rate.set(half_rate);
+        self.frame_pacing.skip_next_frame.set(false);
+    }
+
+    /// Returns whether the window only presents frames in response to invalidation.
+    pub fn is_render_on_demand(&self) -> bool {
+        self.frame_pacing.render_on_demand.get()
+    }
+
+    /// Only present frames when the window has been invalidated by input, a notify,
+    /// or an animation, instead of continuing to present for a short while after input
+    /// to keep the display at its highest refresh rate. This trades some latency on
+    /// the first frame after an idle period for lower power use.
+    pub fn set_render_on_demand(&mut self, render_on_demand: bool) {
+        self.frame_pacing.render_on_demand.set(render_on_demand);
+    }
+
+    /// Redraw the window on every frame for as long as the returned subscription is
+    /// held, such as while a video is playing. Dropping it returns the window to
+    /// drawing only when invalidated.
+    pub fn request_continuous_rendering(&mut self) -> Subscription {
+        let requests = self.frame_pacing.continuous_rendering_requests.clone();
+        requests.set(requests.get() + 1);
+        self.refresh();
+        Subscription::new(move || requests.set(requests.get() - 1))
+    }
+
+    /// Invokes the callback at the start of every frame the window renders, like a display
+    /// link, passing the time at which the frame started. The window renders every frame its
+    /// frame pacing allows for as long as the returned subscription is held.
+    pub fn on_animation_frame(
+        &mut self,
+        mut callback: impl FnMut(Instant, &mut Window, &mut App) + 'static,
+    ) -> Subscription {
+        let (subscription, activate) = self.animation_frame_observers.insert(
+            (),
+            Box::new(move |now, window, cx| {
+                callback(now, window, cx);
+                true
+            }),
+        );
+        activate();
+        let continuous_rendering = self.request_continuous_rendering();
+        Subscription::new(move || {
+            drop(subscription);
+            drop(continuous_rendering);
+        })
+    }
+
+    fn animation_frame(&mut self, cx: &mut App) {
+        if self.animation_frame_observers.is_empty(&()) {
+            return;
+        }
+        let now = cx.background_executor().now();
+        self.animation_frame_observers
+            .clone()
+            .retain(&(), |callback| callback(now, self, cx));
+    }
+
+    /// Returns the window this window was opened as a child of, if any.
+    pub fn parent_window(&self) -> Option<AnyWindowHandle> {
+        self.parent
+    }
+
+    /// Returns whether this window blocks input to its parent window while it is open.
+    pub fn is_modal(&self) -> bool {
+        self.modal
+    }
+
+    fn modal_child<'a>(&self, cx: &'a App) -> Option<&'a Window> {
+        cx.windows
+            .values()
+            .flatten()
+            .find(|window| window.modal && !window.removed && window.parent == Some(self.handle))
+    }
+
     /// Show the platform character palette.
     pub fn show_character_palette(&self) {
         self.platform_window.show_character_palette();
     }
 
+    /// Show the system's preview of the given files, such as Quick Look on macOS, or update
+    /// the files it shows if it is already visible. This does nothing on other platforms.
+    pub fn show_file_preview(&self, paths: Vec<PathBuf>) {
+        self.platform_window.show_file_preview(paths);
+    }
+
+    /// Hide the system file preview, if it is visible.
+    pub fn hide_file_preview(&self) {
+        self.platform_window.hide_file_preview();
+    }
+
+    /// Returns whether the system file preview is currently visible.
+    pub fn is_file_preview_visible(&self) -> bool {
+        self.platform_window.is_file_preview_visible()
+    }
+
     /// The scale factor of the display associated with the window. For example, it could
     /// return 2.0 for a "retina" display, indicating that each logical pixel should actually
     /// be rendered as two pixels on screen.
@@ -1431,6 +1990,14 @@ impl Window {
         self.scale_factor
     }
 
+    /// How many times brighter than SDR white the window can currently show colors, see
+    /// [`crate::StyledImage::brightness`]. This is 1 unless the window was opened with
+    /// [`WindowOptions::hdr`] on a display that supports HDR, and changes as the window moves
+    /// between displays or the display's brightness is adjusted.
+    pub fn hdr_headroom(&self) -> f32 {
+        self.platform_window.hdr_headroom()
+    }
+
     /// The size of an em for the base font of the application. Adjusting this value allows the
     /// UI to scale, just like zooming a web page.
     pub fn rem_size(&self) -> Pixels {
@@ -1516,6 +2083,7 @@ impl Window {
     pub fn draw(&mut self, cx: &mut App) {
         self.invalidate_entities();
         cx.entities.clear_accessed();
+        self.consumed_values.get_mut().clear();
         debug_assert!(self.rendered_entity_stack.is_empty());
         self.invalidator.set_dirty(false);
         self.requested_autoscroll = None;
@@ -1524,6 +2092,10 @@ impl Window {
         if let Some(input_handler) = self.platform_window.take_input_handler() {
             self.rendered_frame.input_handlers.push(Some(input_handler));
         }
+        let previous_frame = self.unreported_frame.take().map(|frame| FrameTimings {
+            present: self.last_present.take(),
+            ..frame
+        });
         self.draw_roots(cx);
         self.dirty_views.clear();
         self.next_frame.window_active = self.active.get();
@@ -1549,7 +2121,24 @@ impl Window {
         let previous_focus_path = self.rendered_frame.focus_path();
         let previous_window_active = self.rendered_frame.window_active;
         mem::swap(&mut self.rendered_frame, &mut self.next_frame);
+        if self.rendered_frame.accessibility_nodes != self.next_frame.accessibility_nodes {
+            self.platform_window
+                .update_accessibility_tree(&self.rendered_frame.accessibility_nodes);
+            for (text, priority) in live_region_announcements(
+                &self.next_frame.accessibility_nodes,
+                &self.rendered_frame.accessibility_nodes,
+            ) {
+                cx.announce(&text, priority);
+            }
+        }
+        self.record_damage();
         self.next_frame.clear();
+        self.evict_stale_glyphs();
+        self.evict_atlas_tiles(cx);
+        self.render_stats = RenderStats::new(
+            &self.rendered_frame.scene,
+            &mut self.atlas_usage.borrow_mut(),
+        );
         let current_focus_path = self.rendered_frame.focus_path();
         let current_window_active = self.rendered_frame.window_active;
 
@@ -1581,10 +2170,56 @@ impl Window {
 
         debug_assert!(self.rendered_entity_stack.is_empty());
         self.record_entities_accessed(cx);
+        self.record_focused_views(cx);
         self.reset_cursor_style(cx);
         self.refreshing = false;
         self.invalidator.set_phase(DrawPhase::None);
-        self.needs_present.set(true);
+        if self.damage.get().is_some() {
+            self.needs_present.set(true);
+        }
+        self.report_frame_stats(previous_frame, cx);
+    }
+
+    /// Accumulates the region where the frame that was just drawn differs from the previous
+    /// one, so that frames which paint exactly what's already on screen aren't presented, and
+    /// renderers that keep the previous frame only repaint what changed.
+    fn record_damage(&self) {
+        let damage = if self.full_damage.take() {
+            Some(Bounds {
+                origin: Point::default(),
+                size: self.viewport_size.scale(self.scale_factor),
+            })
+        } else {
+            self.rendered_frame
+                .scene
+                .damage_since(&self.next_frame.scene)
+        };
+        if let Some(damage) = damage {
+            let damage = match self.damage.get() {
+                Some(previous_damage) => previous_damage.union(&damage),
+                None => damage,
+            };
+            self.damage.set(Some(damage));
+        }
+    }
+
+    fn record_focused_views(&self, cx: &App) {
+        if !cx.crash_context.is_enabled() {
+            return;
+        }
+        let dispatch_tree = &self.rendered_frame.dispatch_tree;
+        let node_id = self
+            .focus
+            .and_then(|focus_id| dispatch_tree.focusable_node_id(focus_id))
+            .unwrap_or_else(|| dispatch_tree.root_node_id());
+        let focused_views = dispatch_tree
+            .dispatch_path(node_id)
+            .iter()
+            .filter_map(|node_id| dispatch_tree.node(*node_id).view_id)
+            .filter_map(|view_id| cx.entities.type_name(view_id))
+            .collect();
+        cx.crash_context
+            .set_focused_views(self.handle.id, focused_views);
     }
 
     fn record_entities_accessed(&mut self, cx: &mut App) {
@@ -1612,14 +2247,26 @@ impl Window {
 
     #[profiling::function]
     fn present(&self) {
-        self.platform_window.draw(&self.rendered_frame.scene);
+        let present_start = Instant::now();
+        self.platform_window
+            .draw_damaged(&self.rendered_frame.scene, self.damage.get());
+        let present_time = present_start.elapsed();
+        self.last_present.set(present_time);
+        if let Some(frame_profiler) = self.frame_profiler.as_ref() {
+            frame_profiler.last_present.set(present_time);
+        }
         self.needs_present.set(false);
+        self.damage.set(None);
         profiling::finish_frame!();
     }
 
     fn draw_roots(&mut self, cx: &mut App) {
         self.invalidator.set_phase(DrawPhase::Prepaint);
         self.tooltip_bounds.take();
+        let prepaint_start = Instant::now();
+        if let Some(frame_profiler) = self.frame_profiler.as_mut() {
+            frame_profiler.start_frame();
+        }
 
         // Layout all root elements.
         let mut root_element = self.root.as_ref().unwrap().clone().into_any();
@@ -1652,6 +2299,7 @@ impl Window {
 
         // Now actually paint the elements.
         self.invalidator.set_phase(DrawPhase::Paint);
+        let paint_start = Instant::now();
         root_element.paint(self, cx);
 
         self.paint_deferred_draws(&sorted_deferred_draws, cx);
@@ -1663,6 +2311,23 @@ impl Window {
         } else if let Some(mut tooltip_element) = tooltip_element {
             tooltip_element.paint(self, cx);
         }
+
+        let layout_time = paint_start - prepaint_start;
+        let paint_time = paint_start.elapsed();
+        if let Some(frame_profiler) = self.frame_profiler.as_mut() {
+            frame_profiler.finish_frame(layout_time, paint_time);
+        }
+        if !self.frame_stats_listeners.is_empty(&()) {
+            self.unreported_frame = Some(FrameTimings {
+                layout: layout_time,
+                paint: paint_time,
+                present: Duration::ZERO,
+            });
+        }
+        if self.inspector_enabled {
+            paint_inspector(self, cx);
+        }
+        paint_frame_profiler(self, cx);
     }
 
     fn prepaint_tooltip(&mut self, cx: &mut App) -> Option<AnyElement> {
@@ -1744,6 +2409,8 @@ impl Window {
                 .clone_from(&deferred_draw.element_id_stack);
             self.text_style_stack
                 .clone_from(&deferred_draw.text_style_stack);
+            self.provided_value_stack
+                .clone_from(&deferred_draw.provided_value_stack);
             self.next_frame
                 .dispatch_tree
                 .set_active_node(deferred_draw.parent_node);
@@ -1767,6 +2434,7 @@ impl Window {
         self.next_frame.deferred_draws = deferred_draws;
         self.element_id_stack.clear();
         self.text_style_stack.clear();
+        self.provided_value_stack.clear();
     }
 
     fn paint_deferred_draws(&mut self, deferred_draw_indices: &[usize], cx: &mut App) {
@@ -1777,6 +2445,8 @@ impl Window {
             let mut deferred_draw = &mut deferred_draws[*deferred_draw_ix];
             self.element_id_stack
                 .clone_from(&deferred_draw.element_id_stack);
+            self.provided_value_stack
+                .clone_from(&deferred_draw.provided_value_stack);
             self.next_frame
                 .dispatch_tree
                 .set_active_node(deferred_draw.parent_node);
@@ -1792,6 +2462,7 @@ impl Window {
         }
         self.next_frame.deferred_draws = deferred_draws;
         self.element_id_stack.clear();
+        self.provided_value_stack.clear();
     }
 
     pub(crate) fn prepaint_index(&self) -> PrepaintStateIndex {
@@ -1844,6 +2515,7 @@ impl Window {
                     parent_node: reused_subtree.refresh_node_id(deferred_draw.parent_node),
                     element_id_stack: deferred_draw.element_id_stack.clone(),
                     text_style_stack: deferred_draw.text_style_stack.clone(),
+                    provided_value_stack: deferred_draw.provided_value_stack.clone(),
                     priority: deferred_draw.priority,
                     element: None,
                     absolute_offset: deferred_draw.absolute_offset,
@@ -1859,6 +2531,8 @@ impl Window {
             mouse_listeners_index: self.next_frame.mouse_listeners.len(),
             input_handlers_index: self.next_frame.input_handlers.len(),
             cursor_styles_index: self.next_frame.cursor_styles.len(),
+            accessibility_nodes_index: self.next_frame.accessibility_nodes.len(),
+            inspected_elements_index: self.next_frame.inspected_elements.len(),
             accessed_element_states_index: self.next_frame.accessed_element_states.len(),
             line_layout_index: self.text_system.layout_index(),
         }
@@ -1890,6 +2564,41 @@ impl Window {
                 .map(|(id, type_id)| (GlobalElementId(id.0.clone()), *type_id)),
         );
 
+        let reused_nodes_start = range.start.accessibility_nodes_index;
+        let next_nodes_start = self.next_frame.accessibility_nodes.len();
+        let current_parent = self.accessibility_node_stack.last().copied();
+        self.next_frame.accessibility_nodes.extend(
+            self.rendered_frame.accessibility_nodes
+                [reused_nodes_start..range.end.accessibility_nodes_index]
+                .iter()
+                .map(|node| AccessibilityNode {
+                    parent: rebase_parent(
+                        node.parent,
+                        reused_nodes_start,
+                        next_nodes_start,
+                        current_parent,
+                    ),
+                    ..node.clone()
+                }),
+        );
+        let reused_elements_start = range.start.inspected_elements_index;
+        let next_elements_start = self.next_frame.inspected_elements.len();
+        let current_parent = self.inspected_element_stack.last().copied();
+        self.next_frame.inspected_elements.extend(
+            self.rendered_frame.inspected_elements
+                [reused_elements_start..range.end.inspected_elements_index]
+                .iter()
+                .map(|element| InspectedElement {
+                    parent: rebase_parent(
+                        element.parent,
+                        reused_elements_start,
+                        next_elements_start,
+                        current_parent,
+                    ),
+                    ..element.clone()
+                }),
+        );
+
         self.text_system
             .reuse_layouts(range.start.line_layout_index..range.end.line_layout_index);
         self.next_frame.scene.replay(
@@ -1916,6 +2625,87 @@ impl Window {
         }
     }
 
+    /// Provide a value to the elements drawn by the given function, and the views they render,
+    /// which they can retrieve with [`Window::consume`]. This method should only be called as
+    /// part of element drawing.
+    pub fn with_provided_value<T, F, R>(&mut self, value: Rc<T>, f: F) -> R
+    where
+        T: PartialEq + 'static,
+        F: FnOnce(&mut Self) -> R,
+    {
+        self.invalidator.debug_assert_paint_or_prepaint();
+        self.provided_value_stack.push((TypeId::of::<T>(), value));
+        let result = f(self);
+        self.provided_value_stack.pop();
+        result
+    }
+
+    /// The value of the given type provided by the nearest enclosing element, see
+    /// [`Window::with_provided_value`] and [`crate::provide`]. Cached views that read a value are
+    /// rendered again when it changes.
+    pub fn consume<T: 'static>(&self) -> Option<&T> {
+        let type_id = TypeId::of::<T>();
+        let provider = self.provided_value(type_id);
+        self.consumed_values.borrow_mut().push(ConsumedValue {
+            type_id,
+            provider: provider.map(|(ix, value)| (ix, value.clone())),
+        });
+        provider.and_then(|(_, value)| value.as_any().downcast_ref())
+    }
+
+    fn provided_value(&self, type_id: TypeId) -> Option<(usize, &Rc<dyn ProvidedValue>)> {
+        self.provided_value_stack
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, (provided_type_id, _))| *provided_type_id == type_id)
+            .map(|(ix, (_, value))| (ix, value))
+    }
+
+    /// Calls the given function and returns the provided values that were read during it from
+    /// outside of it. Values provided by elements drawn within the function are omitted, since
+    /// they're provided again whenever it's called.
+    pub(crate) fn detect_consumed_values<R>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> (R, Vec<ConsumedValue>) {
+        let depth = self.provided_value_stack.len();
+        let outer_consumed_values = mem::take(self.consumed_values.get_mut());
+        let result = f(self);
+        let mut consumed_values =
+            mem::replace(self.consumed_values.get_mut(), outer_consumed_values);
+        consumed_values.retain(|consumed| {
+            consumed
+                .provider
+                .as_ref()
+                .map_or(true, |(ix, _)| *ix < depth)
+        });
+        self.extend_consumed_values(&consumed_values);
+        (result, consumed_values)
+    }
+
+    /// Records provided values as read again, such as when reusing the cached rendering of a view
+    /// that read them.
+    pub(crate) fn extend_consumed_values(&mut self, consumed_values: &[ConsumedValue]) {
+        self.consumed_values
+            .get_mut()
+            .extend(consumed_values.iter().cloned());
+    }
+
+    /// Returns whether any of the given values, read from outside of a view drawn at the current
+    /// position in the element tree, have been replaced by a different value since.
+    pub(crate) fn consumed_values_changed(&self, consumed_values: &[ConsumedValue]) -> bool {
+        consumed_values.iter().any(|consumed| {
+            match (&consumed.provider, self.provided_value(consumed.type_id)) {
+                (Some((_, value)), Some((_, current_value))) => {
+                    !value.eq_value(current_value.as_ref())
+                }
+                (None, None) => false,
+                _ => true,
+            }
+        })
+    }
+
     /// Updates the cursor style at the platform level. This method should only be called
     /// during the prepaint phase of element drawing.
     pub fn set_cursor_style(&mut self, style: CursorStyle, hitbox: &Hitbox) {
@@ -1937,6 +2727,123 @@ impl Window {
         id
     }
 
+    /// Add a node describing an element to the window's accessibility tree, and call a function
+    /// with that node as the parent of any nodes added within it. This method should only be
+    /// called as part of the paint phase of element drawing.
+    pub fn with_accessibility_node<R>(
+        &mut self,
+        properties: &AccessibilityProperties,
+        bounds: Bounds<Pixels>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.invalidator.debug_assert_paint();
+
+        let index = self.next_frame.accessibility_nodes.len();
+        self.next_frame.accessibility_nodes.push(AccessibilityNode {
+            role: properties.role,
+            label: properties.label.clone(),
+            value: properties.value.clone(),
+            live: properties.live,
+            bounds,
+            parent: self.accessibility_node_stack.last().copied(),
+        });
+        self.accessibility_node_stack.push(index);
+        let result = f(self);
+        self.accessibility_node_stack.pop();
+        result
+    }
+
+    /// Returns the accessibility tree of the most recently rendered frame, in paint order.
+    /// Each node refers to its parent by index into this slice.
+    pub fn accessibility_tree(&self) -> &[AccessibilityNode] {
+        &self.rendered_frame.accessibility_nodes
+    }
+
+    /// Shows or hides the inspector, which highlights the element under the mouse and describes
+    /// its id, key context, style, bounds and position in the element tree.
+    pub fn toggle_inspector(&mut self) {
+        self.inspector_enabled = !self.inspector_enabled;
+        self.refresh();
+    }
+
+    /// Shows or hides the frame profiler, which paints the time spent drawing each frame, a graph
+    /// of recent frames, and the views that took the longest to draw.
+    pub fn toggle_frame_profiler(&mut self) {
+        self.frame_profiler = match self.frame_profiler {
+            Some(_) => None,
+            None => Some(FrameProfiler::default()),
+        };
+        self.refresh();
+    }
+
+    /// Returns how long it took to draw the most recently rendered frame, if the frame profiler
+    /// was enabled while drawing it. The frame's present time is recorded once the next frame is
+    /// drawn.
+    pub fn last_frame_timings(&self) -> Option<FrameTimings> {
+        self.frame_profiler.as_ref()?.last_frame()
+    }
+
+    /// Returns what the GPU was asked to draw for the most recently rendered frame, so that
+    /// tests and the frame profiler can check it against a budget.
+    pub fn last_frame_render_stats(&self) -> RenderStats {
+        self.render_stats
+    }
+
+    /// Returns whether the inspector is shown, see [`Window::toggle_inspector`].
+    pub fn is_inspector_enabled(&self) -> bool {
+        self.inspector_enabled
+    }
+
+    /// Returns the elements painted in the most recently rendered frame while the inspector was
+    /// enabled, in paint order. Each element refers to its parent by index into this slice.
+    pub fn inspected_elements(&self) -> &[InspectedElement] {
+        &self.rendered_frame.inspected_elements
+    }
+
+    /// Draws the window again while recording its element tree, and returns a JSON description of
+    /// it for use in tests and bug reports. Each element is described by its id, key context,
+    /// bounds, style and children. This method should not be called while drawing.
+    pub fn debug_tree(&mut self, cx: &mut App) -> serde_json::Value {
+        debug_assert!(
+            self.invalidator.not_drawing(),
+            "debug_tree can't be called while drawing"
+        );
+
+        self.recording_element_tree = true;
+        self.refresh();
+        self.draw(cx);
+        self.recording_element_tree = false;
+        element_tree_json(&self.rendered_frame.inspected_elements)
+    }
+
+    /// Records an element for the inspector if it's enabled, and calls a function with that
+    /// element as the parent of any elements recorded within it.
+    pub(crate) fn with_inspected_element<R>(
+        &mut self,
+        id: Option<&GlobalElementId>,
+        key_context: Option<&KeyContext>,
+        style: &Style,
+        bounds: Bounds<Pixels>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        if !self.inspector_enabled && !self.recording_element_tree {
+            return f(self);
+        }
+
+        let index = self.next_frame.inspected_elements.len();
+        self.next_frame.inspected_elements.push(InspectedElement {
+            id: id.and_then(|id| id.last().cloned()),
+            key_context: key_context.cloned(),
+            style: style.clone(),
+            bounds,
+            parent: self.inspected_element_stack.last().copied(),
+        });
+        self.inspected_element_stack.push(index);
+        let result = f(self);
+        self.inspected_element_stack.pop();
+        result
+    }
+
     /// Invoke the given function with the given content mask after intersecting it
     /// with the current mask. This method should only be called during element drawing.
     pub fn with_content_mask<R>(
@@ -2119,6 +3026,46 @@ impl Window {
         result
     }
 
+    pub(crate) fn draw_stack_depths(&self) -> DrawStackDepths {
+        DrawStackDepths {
+            element_id: self.element_id_stack.len(),
+            text_style: self.text_style_stack.len(),
+            provided_value: self.provided_value_stack.len(),
+            rem_size_override: self.rem_size_override_stack.len(),
+            accessibility_node: self.accessibility_node_stack.len(),
+            inspected_element: self.inspected_element_stack.len(),
+            rendered_entity: self.rendered_entity_stack.len(),
+            element_offset: self.element_offset_stack.len(),
+            element_opacity: self.element_opacity,
+            content_mask: self.content_mask_stack.len(),
+            dispatch_node: self.next_frame.dispatch_tree.node_stack_depth(),
+            unwound_leases: EntityMap::unwound_lease_count(),
+        }
+    }
+
+    /// Pops anything pushed onto the drawing stacks since the given depths were captured, which
+    /// is left behind when drawing an element panics, and returns the entities whose updates the
+    /// panic unwound through to the app.
+    pub(crate) fn unwind_draw_stacks(&mut self, depths: &DrawStackDepths, cx: &mut App) {
+        self.element_id_stack.truncate(depths.element_id);
+        self.text_style_stack.truncate(depths.text_style);
+        self.provided_value_stack.truncate(depths.provided_value);
+        self.rem_size_override_stack
+            .truncate(depths.rem_size_override);
+        self.accessibility_node_stack
+            .truncate(depths.accessibility_node);
+        self.inspected_element_stack
+            .truncate(depths.inspected_element);
+        self.rendered_entity_stack.truncate(depths.rendered_entity);
+        self.element_offset_stack.truncate(depths.element_offset);
+        self.element_opacity = depths.element_opacity;
+        self.content_mask_stack.truncate(depths.content_mask);
+        self.next_frame
+            .dispatch_tree
+            .truncate_node_stack(depths.dispatch_node);
+        cx.entities.restore_unwound_leases(depths.unwound_leases);
+    }
+
     /// Updates or initializes state for an element with the given id that lives across multiple
     /// frames. If an element with this ID existed in the rendered frame, its state will be passed
     /// to the given closure. The state returned by the closure will be stored so it can be referenced
@@ -2250,6 +3197,7 @@ impl Window {
             parent_node,
             element_id_stack: self.element_id_stack.clone(),
             text_style_stack: self.text_style_stack.clone(),
+            provided_value_stack: self.provided_value_stack.clone(),
             priority,
             element: Some(element),
             absolute_offset,
@@ -2284,6 +3232,38 @@ impl Window {
         result
     }
 
+    /// Paints the elements painted by `f` into an offscreen layer covering the given bounds,
+    /// which is composited into the scene as a whole with the given effect. This is how a
+    /// group of elements is faded or blurred together, rather than each element on its own.
+    /// What they paint outside of the bounds is clipped.
+    ///
+    /// Each layer is drawn into a texture the size of the window, so they're best kept to
+    /// elements that are actually being faded or blurred.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn with_offscreen_layer<R>(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        effect: LayerEffect,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.invalidator.debug_assert_paint();
+
+        let scale_factor = self.scale_factor();
+        let content_mask = self.content_mask();
+        self.next_frame.scene.push_offscreen_layer(LayerSprite {
+            order: 0,
+            pad: 0,
+            bounds: bounds.scale(scale_factor),
+            content_mask: content_mask.scale(scale_factor),
+            opacity: effect.opacity,
+            blur_radius: effect.blur_radius.scale(scale_factor),
+        });
+        let result = f(self);
+        self.next_frame.scene.pop_offscreen_layer();
+        result
+    }
+
     /// Paint one or more drop shadows into the scene for the next frame at the current z-index.
     ///
     /// This method should only be called as part of the paint phase of element drawing.
@@ -2334,6 +3314,38 @@ impl Window {
         });
     }
 
+    /// Paint a quad whose pixels are colored by a custom fragment shader into the scene for the
+    /// next frame at the current stacking context. The shader is given the `uniforms` along with
+    /// each pixel's position in the quad, see [`CustomShader`].
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn paint_shader_quad(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        shader: &CustomShader,
+        uniforms: ShaderUniforms,
+    ) {
+        self.invalidator.debug_assert_paint();
+
+        let scale_factor = self.scale_factor();
+        let content_mask = self.content_mask();
+        let opacity = self.element_opacity();
+        let scene = &mut self.next_frame.scene;
+        scene
+            .custom_shaders
+            .entry(shader.id)
+            .or_insert_with(|| shader.clone());
+        scene.insert_primitive(ShaderQuad {
+            order: 0,
+            shader_id: shader.id,
+            bounds: bounds.scale(scale_factor),
+            content_mask: content_mask.scale(scale_factor),
+            opacity,
+            pad: 0,
+            uniforms,
+        });
+    }
+
     /// Paint the given `Path` into the scene for the next frame at the current z-index.
     ///
     /// This method should only be called as part of the paint phase of element drawing.
@@ -2363,10 +3375,9 @@ impl Window {
         self.invalidator.debug_assert_paint();
 
         let scale_factor = self.scale_factor();
-        let height = if style.wavy {
-            style.thickness * 3.
-        } else {
-            style.thickness
+        let height = match style.kind {
+            UnderlineKind::Wavy | UnderlineKind::Double => style.thickness * 3.,
+            UnderlineKind::Solid | UnderlineKind::Dashed | UnderlineKind::Dotted => style.thickness,
         };
         let bounds = Bounds {
             origin,
@@ -2382,7 +3393,7 @@ impl Window {
             content_mask: content_mask.scale(scale_factor),
             color: style.color.unwrap_or_default().opacity(element_opacity),
             thickness: style.thickness.scale(scale_factor),
-            wavy: style.wavy,
+            kind: style.kind as u32,
         });
     }
 
@@ -2413,7 +3424,7 @@ impl Window {
             content_mask: content_mask.scale(scale_factor),
             thickness: style.thickness.scale(scale_factor),
             color: style.color.unwrap_or_default().opacity(opacity),
-            wavy: false,
+            kind: UnderlineKind::Solid as u32,
         });
     }
 
@@ -2437,11 +3448,10 @@ impl Window {
 
         let element_opacity = self.element_opacity();
         let scale_factor = self.scale_factor();
-        let glyph_origin = origin.scale(scale_factor);
-        let subpixel_variant = Point {
-            x: (glyph_origin.x.0.fract() * SUBPIXEL_VARIANTS as f32).floor() as u8,
-            y: (glyph_origin.y.0.fract() * SUBPIXEL_VARIANTS as f32).floor() as u8,
-        };
+        let (glyph_origin, subpixel_variant) = self
+            .text_system()
+            .subpixel_positioning()
+            .position_glyph(origin.scale(scale_factor));
         let params = RenderGlyphParams {
             font_id,
             glyph_id,
@@ -2454,8 +3464,7 @@ impl Window {
         let raster_bounds = self.text_system().raster_bounds(&params)?;
         if !raster_bounds.is_zero() {
             let tile = self
-                .sprite_atlas
-                .get_or_insert_with(&params.clone().into(), &mut || {
+                .atlas_tile(&params.clone().into(), &mut || {
                     let (size, bytes) = self.text_system().rasterize_glyph(&params)?;
                     Ok(Some((size, Cow::Owned(bytes))))
                 })?
@@ -2510,8 +3519,7 @@ impl Window {
         let raster_bounds = self.text_system().raster_bounds(&params)?;
         if !raster_bounds.is_zero() {
             let tile = self
-                .sprite_atlas
-                .get_or_insert_with(&params.clone().into(), &mut || {
+                .atlas_tile(&params.clone().into(), &mut || {
                     let (size, bytes) = self.text_system().rasterize_glyph(&params)?;
                     Ok(Some((size, Cow::Owned(bytes))))
                 })?
@@ -2526,7 +3534,7 @@ impl Window {
 
             self.next_frame.scene.insert_primitive(PolychromeSprite {
                 order: 0,
-                pad: 0,
+                brightness: 1.,
                 grayscale: false,
                 bounds,
                 corner_radii: Default::default(),
@@ -2561,14 +3569,12 @@ impl Window {
             }),
         };
 
-        let Some(tile) =
-            self.sprite_atlas
-                .get_or_insert_with(&params.clone().into(), &mut || {
-                    let Some(bytes) = cx.svg_renderer.render(&params)? else {
-                        return Ok(None);
-                    };
-                    Ok(Some((params.size, Cow::Owned(bytes))))
-                })?
+        let Some(tile) = self.atlas_tile(&params.clone().into(), &mut || {
+            let Some(bytes) = cx.svg_renderer.render(&params)? else {
+                return Ok(None);
+            };
+            Ok(Some((params.size, Cow::Owned(bytes))))
+        })?
         else {
             return Ok(());
         };
@@ -2593,6 +3599,7 @@ impl Window {
     /// This method will panic if the frame_index is not valid
     ///
     /// This method should only be called as part of the paint phase of element drawing.
+    #[allow(clippy::too_many_arguments)]
     pub fn paint_image(
         &mut self,
         bounds: Bounds<Pixels>,
@@ -2600,35 +3607,84 @@ impl Window {
         data: Arc<RenderImage>,
         frame_index: usize,
         grayscale: bool,
+        brightness: f32,
+        filter: ImageFilter,
+        cx: &App,
     ) -> Result<()> {
         self.invalidator.debug_assert_paint();
 
         let scale_factor = self.scale_factor();
         let bounds = bounds.scale(scale_factor);
-        let params = RenderImageParams {
-            image_id: data.id,
-            frame_index,
-        };
+        let mut tile = None;
+        if let Some(downscaled_size) = filter.downscaled_size(data.size(frame_index), bounds.size) {
+            let params = RenderImageParams {
+                image_id: data.id,
+                frame_index,
+                downscaled_size: Some(downscaled_size),
+            };
+            // Downscaling a large image takes a while, so it's done in the background and the
+            // image is drawn at its native size until the downscaled copy is ready.
+            tile = self.atlas_tile(&params.clone().into(), &mut || {
+                let mut downscaled_images = self.downscaled_images.borrow_mut();
+                match downscaled_images.get(&params) {
+                    Some(DownscaledImage::Ready(_)) => {
+                        let Some(DownscaledImage::Ready(bytes)) = downscaled_images.remove(&params)
+                        else {
+                            unreachable!()
+                        };
+                        Ok(Some((downscaled_size, Cow::Owned(bytes))))
+                    }
+                    Some(DownscaledImage::Pending(_)) => Ok(None),
+                    None => {
+                        let window = self.handle;
+                        let data = data.clone();
+                        let downscale = cx
+                            .background_executor()
+                            .spawn(async move { data.downscale(frame_index, downscaled_size) });
+                        let params = params.clone();
+                        let task = cx.spawn(|mut cx| async move {
+                            let bytes = downscale.await;
+                            cx.update_window(window, |_, window, _| {
+                                if let Some(image) =
+                                    window.downscaled_images.get_mut().get_mut(&params)
+                                {
+                                    *image = DownscaledImage::Ready(bytes);
+                                    window.refresh();
+                                }
+                            })
+                            .ok();
+                        });
+                        downscaled_images.insert(params.clone(), DownscaledImage::Pending(task));
+                        Ok(None)
+                    }
+                }
+            })?;
+        }
 
-        let tile = self
-            .sprite_atlas
-            .get_or_insert_with(&params.clone().into(), &mut || {
-                Ok(Some((
-                    data.size(frame_index),
-                    Cow::Borrowed(
-                        data.as_bytes(frame_index)
-                            .expect("It's the caller's job to pass a valid frame index"),
-                    ),
-                )))
-            })?
-            .expect("Callback above only returns Some");
+        let tile = match tile {
+            Some(tile) => tile,
+            None => {
+                let params = RenderImageParams {
+                    image_id: data.id,
+                    frame_index,
+                    downscaled_size: None,
+                };
+                self.atlas_tile(&params.into(), &mut || {
+                    let bytes = data
+                        .as_bytes(frame_index)
+                        .expect("It's the caller's job to pass a valid frame index");
+                    Ok(Some((data.size(frame_index), Cow::Borrowed(bytes))))
+                })?
+                .expect("Callback above only returns Some")
+            }
+        };
         let content_mask = self.content_mask().scale(scale_factor);
         let corner_radii = corner_radii.scale(scale_factor);
         let opacity = self.element_opacity();
 
         self.next_frame.scene.insert_primitive(PolychromeSprite {
             order: 0,
-            pad: 0,
+            brightness,
             grayscale,
             bounds,
             content_mask,
@@ -2643,7 +3699,12 @@ impl Window {
     ///
     /// This method should only be called as part of the paint phase of element drawing.
     #[cfg(target_os = "macos")]
-    pub fn paint_surface(&mut self, bounds: Bounds<Pixels>, image_buffer: CVImageBuffer) {
+    pub fn paint_surface(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        image_buffer: CVImageBuffer,
+        brightness: f32,
+    ) {
         use crate::PaintSurface;
 
         self.invalidator.debug_assert_paint();
@@ -2655,21 +3716,20 @@ impl Window {
             order: 0,
             bounds,
             content_mask,
+            brightness,
             image_buffer,
         });
     }
 
-    /// Removes an image from the sprite atlas.
+    /// Removes an image, including every frame and downscaled copy of it, from the sprite atlas.
     pub fn drop_image(&mut self, data: Arc<RenderImage>) -> Result<()> {
-        for frame_index in 0..data.frame_count() {
-            let params = RenderImageParams {
-                image_id: data.id,
-                frame_index,
-            };
-
-            self.sprite_atlas.remove(&params.clone().into());
-        }
-
+        self.downscaled_images
+            .get_mut()
+            .retain(|params, _| params.image_id != data.id);
+        self.retain_atlas_tiles(|key| match key {
+            AtlasKey::Image(params) => params.image_id != data.id,
+            AtlasKey::Glyph(_) | AtlasKey::Svg(_) => true,
+        });
         Ok(())
     }
 
@@ -2821,11 +3881,33 @@ impl Window {
         f: impl FnOnce(&mut Self) -> R,
     ) -> R {
         self.rendered_entity_stack.push(id);
-        let result = f(self);
+        let result = self.profile_view(id, f);
         self.rendered_entity_stack.pop();
         result
     }
 
+    /// Attributes the time spent in the given function to the given view if the frame profiler is
+    /// enabled, see [`Window::toggle_frame_profiler`].
+    pub(crate) fn profile_view<R>(&mut self, id: EntityId, f: impl FnOnce(&mut Self) -> R) -> R {
+        if self.frame_profiler.is_none() {
+            return f(self);
+        }
+
+        let start = Instant::now();
+        let result = f(self);
+        if let Some(frame_profiler) = self.frame_profiler.as_mut() {
+            *frame_profiler.view_times.entry(id).or_default() += start.elapsed();
+        }
+        result
+    }
+
+    /// Records the type of a view being rendered, so the frame profiler can name it.
+    pub(crate) fn record_view_type(&mut self, id: EntityId, type_name: &'static str) {
+        if let Some(frame_profiler) = self.frame_profiler.as_mut() {
+            frame_profiler.view_names.insert(id, type_name);
+        }
+    }
+
     /// Sets an input handler, such as [`ElementInputHandler`][element_input_handler], which interfaces with the
     /// platform to receive textual input with proper integration with concerns such
     /// as IME interactions. This handler will be active for the upcoming frame until the following frame is
@@ -3022,6 +4104,20 @@ impl Window {
     #[profiling::function]
     pub fn dispatch_event(&mut self, event: PlatformInput, cx: &mut App) -> DispatchEventResult {
         self.last_input_timestamp.set(Instant::now());
+        cx.crash_context.record_input(self.handle.id, &event);
+
+        // While a modal child window is open, this window ignores input. Clicking on it
+        // brings the modal window to the front instead.
+        if let Some(modal_child) = self.modal_child(cx) {
+            if matches!(event, PlatformInput::MouseDown(_)) {
+                modal_child.platform_window.activate();
+            }
+            return DispatchEventResult {
+                propagate: false,
+                default_prevented: true,
+            };
+        }
+
         // Handlers may set this to false by calling `stop_propagation`.
         cx.propagate_event = true;
         // Handlers may set this to true by calling `prevent_default`.
@@ -3413,6 +4509,8 @@ impl Window {
         action: &dyn Action,
         cx: &mut App,
     ) {
+        cx.crash_context
+            .record_action(Some(self.handle.id), action.name());
         let dispatch_path = self.rendered_frame.dispatch_tree.dispatch_path(node_id);
 
         // Capture phase for global actions.
@@ -3536,6 +4634,13 @@ impl Window {
         self.platform_window.minimize();
     }
 
+    /// Maximize the current window at the platform level, if it isn't already maximized.
+    pub fn maximize_window(&self) {
+        if !self.is_maximized() {
+            self.platform_window.zoom();
+        }
+    }
+
     /// Toggle full screen status on the current window at the platform level.
     pub fn toggle_fullscreen(&self) {
         self.platform_window.toggle_fullscreen();
@@ -3729,6 +4834,57 @@ impl Window {
     pub fn gpu_specs(&self) -> Option<GpuSpecs> {
         self.platform_window.gpu_specs()
     }
+
+    /// Capture the most recently rendered frame of this window as an RGBA image, in device pixels.
+    /// Returns an error if the platform doesn't support reading back window contents.
+    pub fn capture_screenshot(&self) -> Result<RgbaImage> {
+        self.platform_window
+            .capture_frame(&self.rendered_frame.scene)
+    }
+
+    /// Serializes the most recently rendered frame into a stable text format for snapshot tests.
+    #[cfg(any(test, feature = "test-support"))]
+    pub(crate) fn scene_snapshot(&self) -> String {
+        let atlas_usage = self.atlas_usage.borrow();
+        crate::serialize_scene(&self.rendered_frame.scene, &|tile| {
+            atlas_usage.key(tile).cloned()
+        })
+    }
+
+    /// Draws the window again while recording its element tree, and captures the region of the
+    /// first element painted with the given id. This method should not be called while drawing.
+    pub fn capture_element(&mut self, id: impl Into<ElementId>, cx: &mut App) -> Result<RgbaImage> {
+        debug_assert!(
+            self.invalidator.not_drawing(),
+            "capture_element can't be called while drawing"
+        );
+
+        let id = id.into();
+        self.recording_element_tree = true;
+        self.refresh();
+        self.draw(cx);
+        self.recording_element_tree = false;
+        let bounds = self
+            .rendered_frame
+            .inspected_elements
+            .iter()
+            .find(|element| element.id.as_ref() == Some(&id))
+            .map(|element| element.bounds)
+            .ok_or_else(|| anyhow!("no element with id {} was painted", id))?;
+        self.capture_bounds(bounds)
+    }
+
+    /// Capture the given region of the most recently rendered frame, such as the bounds of an
+    /// element, as an RGBA image in device pixels.
+    pub fn capture_bounds(&self, bounds: Bounds<Pixels>) -> Result<RgbaImage> {
+        let screenshot = self.capture_screenshot()?;
+        let bounds = bounds.to_device_pixels(self.scale_factor());
+        let x = bounds.origin.x.0.max(0) as u32;
+        let y = bounds.origin.y.0.max(0) as u32;
+        let width = bounds.size.width.0.max(0) as u32;
+        let height = bounds.size.height.0.max(0) as u32;
+        Ok(image::imageops::crop_imm(&screenshot, x, y, width, height).to_image())
+    }
 }
 
 // #[derive(Clone, Copy, Eq, PartialEq, Hash)]
@@ -3950,6 +5106,12 @@ impl HasWindowHandle for Window {
     }
 }
 
+impl HasDisplayHandle for Window {
+    fn display_handle(&self) -> Result<raw_window_handle::DisplayHandle<'_>, HandleError> {
+        self.platform_window.display_handle()
+    }
+}
+
 /// An identifier for an [`Element`](crate::Element).
 ///
 /// Can be constructed with a string, a number, or both, as well
@@ -4160,3 +5322,167 @@ pub fn outline(bounds: impl Into<Bounds<Pixels>>, border_color: impl Into<Hsla>)
         border_color: border_color.into(),
     }
 }
+
+fn platform_window_state(platform_window: &dyn PlatformWindow) -> WindowState {
+    if platform_window.is_minimized() {
+        WindowState::Minimized
+    } else if platform_window.is_fullscreen() {
+        WindowState::Fullscreen
+    } else if platform_window.is_maximized() {
+        WindowState::Maximized
+    } else {
+        WindowState::Windowed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{self as gpui, canvas, div, PrimitiveBatch, TestAppContext};
+
+    struct TestView;
+
+    impl Render for TestView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+        }
+    }
+
+    #[gpui::test]
+    fn test_on_animation_frame(cx: &mut TestAppContext) {
+        let frames = Rc::new(RefCell::new(Vec::new()));
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| cx.new(|_| TestView))
+                .unwrap()
+        });
+        let start = cx.now();
+        let subscription = window
+            .update(cx, |_, window, _| {
+                let frames = frames.clone();
+                window.on_animation_frame(move |now, _, _| frames.borrow_mut().push(now))
+            })
+            .unwrap();
+
+        cx.advance_clock(Duration::from_millis(16));
+        cx.advance_clock(Duration::from_millis(16));
+        assert_eq!(
+            frames.take(),
+            vec![
+                start + Duration::from_millis(16),
+                start + Duration::from_millis(32)
+            ]
+        );
+
+        drop(subscription);
+        cx.advance_clock(Duration::from_millis(16));
+        assert!(frames.borrow().is_empty());
+    }
+
+    #[gpui::test]
+    fn test_underline_kinds_reach_the_scene(cx: &mut TestAppContext) {
+        struct Underlines;
+
+        impl Render for Underlines {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                canvas(
+                    |_, _, _| {},
+                    |bounds, _, window, _| {
+                        let kinds = [
+                            UnderlineKind::Solid,
+                            UnderlineKind::Wavy,
+                            UnderlineKind::Dashed,
+                            UnderlineKind::Dotted,
+                            UnderlineKind::Double,
+                        ];
+                        for (ix, kind) in kinds.into_iter().enumerate() {
+                            let origin = bounds.origin + point(px(0.), px(ix as f32 * 10.));
+                            let style = UnderlineStyle {
+                                thickness: px(1.),
+                                color: Some(Hsla::black()),
+                                kind,
+                            };
+                            window.paint_underline(origin, px(100.), &style);
+                        }
+                    },
+                )
+                .size_full()
+            }
+        }
+
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| cx.new(|_| Underlines))
+                .unwrap()
+        });
+        let kinds = window
+            .update(cx, |_, window, _| {
+                window
+                    .rendered_frame
+                    .scene
+                    .batches()
+                    .flat_map(|batch| match batch {
+                        PrimitiveBatch::Underlines(underlines) => {
+                            underlines.iter().map(|underline| underline.kind).collect()
+                        }
+                        _ => Vec::new(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap();
+        // These are the values that the Metal and WGSL underline shaders switch on.
+        assert_eq!(kinds, [0, 1, 2, 3, 4]);
+    }
+
+    #[gpui::test]
+    fn test_observe_window_state(cx: &mut TestAppContext) {
+        struct StateView {
+            states: Vec<WindowState>,
+            _subscription: Subscription,
+        }
+
+        impl Render for StateView {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                div()
+            }
+        }
+
+        let window = cx.update(|cx| {
+            let options = WindowOptions {
+                window_bounds: Some(WindowBounds::Maximized(Bounds::new(
+                    point(px(0.), px(0.)),
+                    size(px(800.), px(600.)),
+                ))),
+                ..Default::default()
+            };
+            cx.open_window(options, |window, cx| {
+                cx.new(|cx| StateView {
+                    states: Vec::new(),
+                    _subscription: cx.observe_window_state(window, |view, window, _| {
+                        view.states.push(window.window_state())
+                    }),
+                })
+            })
+            .unwrap()
+        });
+        let test_window = cx.test_window(window.into());
+
+        // The window starts out in the state of its platform window, so events that don't change
+        // it aren't reported.
+        assert_eq!(
+            window
+                .update(cx, |_, window, _| window.window_state())
+                .unwrap(),
+            WindowState::Maximized
+        );
+        test_window.simulate_active_status_change(true);
+        assert!(window
+            .update(cx, |view, _, _| view.states.is_empty())
+            .unwrap());
+
+        test_window.simulate_minimized(true);
+        test_window.simulate_minimized(false);
+        assert_eq!(
+            window.update(cx, |view, _, _| view.states.clone()).unwrap(),
+            [WindowState::Minimized, WindowState::Maximized]
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use collections::FxHashMap;
+
+use crate::{
+    size, AtlasKey, AtlasTextureId, AtlasTextureKind, AtlasTile, DevicePixels, Scene, Size, Task,
+    TileId,
+};
+
+/// Limits on the sprite atlas of each window, which caches the rasterized glyphs, SVGs and
+/// images that it draws. See [`crate::App::set_atlas_config`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasConfig {
+    /// The size of the textures that tiles are allocated from. Larger textures fit more tiles,
+    /// but take up more memory while they're mostly empty.
+    pub page_size: Size<DevicePixels>,
+    /// How many bytes of tiles the atlas can hold before the least recently drawn ones are
+    /// evicted, or `None` to never evict tiles. Tiles drawn in the current frame are kept even
+    /// when they exceed the budget.
+    pub budget: Option<usize>,
+}
+
+impl Default for AtlasConfig {
+    fn default() -> Self {
+        Self {
+            page_size: size(DevicePixels(1024), DevicePixels(1024)),
+            budget: Some(128 * 1024 * 1024),
+        }
+    }
+}
+
+/// How much of a window's sprite atlas is in use, see [`crate::Window::atlas_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AtlasStats {
+    /// How many tiles the atlas holds
+    pub tile_count: usize,
+    /// How many bytes the atlas's tiles take up
+    pub bytes: usize,
+    /// How many tiles have been evicted to stay within the budget since the window was opened
+    pub evicted_tiles: usize,
+}
+
+/// A copy of an image that's downscaled before it's added to the sprite atlas, see
+/// [`crate::ImageFilter`].
+pub(super) enum DownscaledImage {
+    /// The image is being downscaled on the background executor.
+    Pending(Task<()>),
+    /// The image was downscaled, and is added to the atlas the next time it's drawn.
+    Ready(Vec<u8>),
+}
+
+/// Tracks when each tile in a window's sprite atlas was last drawn, so that the least recently
+/// drawn tiles can be evicted once the atlas exceeds its budget.
+#[derive(Default)]
+pub(super) struct AtlasUsage {
+    frame: u64,
+    tiles: FxHashMap<AtlasKey, TileUsage>,
+    keys_by_tile: FxHashMap<(AtlasTextureId, TileId), AtlasKey>,
+    stats: AtlasStats,
+    /// How many tiles were uploaded since the last call to `take_uploads`, and their size
+    uploads: (usize, usize),
+}
+
+struct TileUsage {
+    tile: (AtlasTextureId, TileId),
+    bytes: usize,
+    last_drawn: u64,
+}
+
+impl AtlasUsage {
+    pub(super) fn stats(&self) -> AtlasStats {
+        self.stats
+    }
+
+    pub(super) fn record_upload(&mut self, bytes: usize) {
+        self.uploads.0 += 1;
+        self.uploads.1 += bytes;
+    }
+
+    pub(super) fn take_uploads(&mut self) -> (usize, usize) {
+        std::mem::take(&mut self.uploads)
+    }
+
+    /// Returns the key that the given tile was rasterized for.
+    pub(super) fn key(&self, tile: &AtlasTile) -> Option<&AtlasKey> {
+        self.keys_by_tile.get(&(tile.texture_id, tile.tile_id))
+    }
+
+    pub(super) fn record(&mut self, key: &AtlasKey, tile: &AtlasTile) {
+        let frame = self.frame;
+        if let Some(usage) = self.tiles.get_mut(key) {
+            usage.last_drawn = frame;
+            return;
+        }
+
+        let bytes_per_pixel = match key.texture_kind() {
+            AtlasTextureKind::Monochrome => 1,
+            AtlasTextureKind::Polychrome | AtlasTextureKind::Path => 4,
+        };
+        let tile_size = tile.bounds.size;
+        let usage = TileUsage {
+            tile: (tile.texture_id, tile.tile_id),
+            bytes: tile_size.width.0.max(0) as usize
+                * tile_size.height.0.max(0) as usize
+                * bytes_per_pixel,
+            last_drawn: frame,
+        };
+        self.stats.tile_count += 1;
+        self.stats.bytes += usage.bytes;
+        self.keys_by_tile.insert(usage.tile, key.clone());
+        self.tiles.insert(key.clone(), usage);
+    }
+
+    pub(super) fn remove(&mut self, key: &AtlasKey) {
+        let Some(usage) = self.tiles.remove(key) else {
+            return;
+        };
+        self.stats.tile_count -= 1;
+        self.stats.bytes -= usage.bytes;
+        if self.keys_by_tile.get(&usage.tile) == Some(key) {
+            self.keys_by_tile.remove(&usage.tile);
+        }
+    }
+
+    pub(super) fn retain(&mut self, mut predicate: impl FnMut(&AtlasKey) -> bool) {
+        let removed_keys = self
+            .tiles
+            .keys()
+            .filter(|key| !predicate(key))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in removed_keys {
+            self.remove(&key);
+        }
+    }
+
+    /// Marks the tiles drawn by the given scene as used in the current frame, and returns the
+    /// least recently drawn tiles that have to be evicted to get within the budget. Tiles are
+    /// marked from the finished scene rather than when they're painted, since views that are
+    /// cached replay their sprites without painting them again.
+    pub(super) fn finish_frame(&mut self, scene: &Scene, budget: Option<usize>) -> Vec<AtlasKey> {
+        let drawn_tiles = scene
+            .monochrome_sprites
+            .iter()
+            .map(|sprite| &sprite.tile)
+            .chain(scene.polychrome_sprites.iter().map(|sprite| &sprite.tile));
+        for tile in drawn_tiles {
+            if let Some(usage) = self
+                .keys_by_tile
+                .get(&(tile.texture_id, tile.tile_id))
+                .and_then(|key| self.tiles.get_mut(key))
+            {
+                usage.last_drawn = self.frame;
+            }
+        }
+
+        let mut evicted_keys = Vec::new();
+        if let Some(budget) = budget.filter(|budget| self.stats.bytes > *budget) {
+            let mut candidates = self
+                .tiles
+                .iter()
+                .filter(|(_, usage)| usage.last_drawn < self.frame)
+                .map(|(key, usage)| (usage.last_drawn, key.clone()))
+                .collect::<Vec<_>>();
+            candidates.sort_by_key(|(last_drawn, _)| *last_drawn);
+            for (_, key) in candidates {
+                if self.stats.bytes <= budget {
+                    break;
+                }
+                self.remove(&key);
+                self.stats.evicted_tiles += 1;
+                evicted_keys.push(key);
+            }
+        }
+
+        self.frame += 1;
+        evicted_keys
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Bounds, ContentMask, ImageId, PolychromeSprite, RenderImageParams, ScaledPixels};
+
+    fn image_tile(index: u32) -> (AtlasKey, AtlasTile) {
+        let key = AtlasKey::Image(RenderImageParams {
+            image_id: ImageId(index as usize),
+            frame_index: 0,
+            downscaled_size: None,
+        });
+        let tile = AtlasTile {
+            texture_id: AtlasTextureId {
+                index: 0,
+                kind: AtlasTextureKind::Polychrome,
+            },
+            tile_id: TileId(index),
+            padding: 0,
+            bounds: Bounds {
+                origin: Default::default(),
+                size: size(DevicePixels(8), DevicePixels(8)),
+            },
+        };
+        (key, tile)
+    }
+
+    #[test]
+    fn test_atlas_eviction() {
+        let mut usage = AtlasUsage::default();
+        let tiles = (0..3).map(image_tile).collect::<Vec<_>>();
+        for (key, tile) in &tiles {
+            usage.record(key, tile);
+        }
+        assert_eq!(usage.stats().bytes, 3 * 256);
+        assert!(usage
+            .finish_frame(&Scene::default(), Some(3 * 256))
+            .is_empty());
+
+        // Only the first tile is painted, and the second one is replayed from a cached view.
+        usage.record(&tiles[0].0, &tiles[0].1);
+        let bounds = Bounds {
+            origin: Default::default(),
+            size: size(ScaledPixels(8.), ScaledPixels(8.)),
+        };
+        let mut scene = Scene::default();
+        scene.insert_primitive(PolychromeSprite {
+            order: 0,
+            brightness: 1.,
+            grayscale: false,
+            opacity: 1.,
+            bounds,
+            content_mask: ContentMask { bounds },
+            corner_radii: Default::default(),
+            tile: tiles[1].1.clone(),
+        });
+        let evicted_keys = usage.finish_frame(&scene, Some(2 * 256));
+        assert!(evicted_keys == [tiles[2].0.clone()]);
+        assert_eq!(
+            usage.stats(),
+            AtlasStats {
+                tile_count: 2,
+                bytes: 2 * 256,
+                evicted_tiles: 1,
+            }
+        );
+    }
+}
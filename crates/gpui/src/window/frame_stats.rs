@@ -0,0 +1,238 @@
+use std::time::{Duration, Instant};
+
+use crate::{App, Subscription};
+
+use super::{FrameTimings, Window};
+
+/// Frames that spend longer than this laying out or painting are reported individually.
+const OUTLIER_THRESHOLD: Duration = Duration::from_millis(50);
+const MAX_OUTLIERS: usize = 10;
+
+pub(super) type FrameStatsListener =
+    Box<dyn FnMut(FrameTimings, Instant, &mut Window, &mut App) -> bool>;
+
+/// Statistics about the frames a window drew over an interval, see [`Window::on_frame_stats`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    /// How long the frames were collected for. This can be longer than the requested interval,
+    /// since stats are only reported once a frame is drawn.
+    pub interval: Duration,
+    /// How many frames were drawn
+    pub frame_count: usize,
+    /// The average time spent in each phase of drawing a frame
+    pub mean: FrameTimings,
+    /// The time within which 95% of frames were drawn
+    pub p95: Duration,
+    /// The time it took to draw the slowest frame
+    pub max: Duration,
+    /// How many display refreshes were missed because a frame took too long to draw, at the
+    /// refresh rate of the window's display when the stats were reported
+    pub dropped_frames: usize,
+    /// The slowest frames that spent an unusually long time laying out or painting, slowest
+    /// first
+    pub outliers: Vec<FrameTimings>,
+}
+
+/// Collects the frames drawn by a window until an interval has passed.
+struct FrameStatsAggregator {
+    interval: Duration,
+    started_at: Option<Instant>,
+    frames: Vec<FrameTimings>,
+}
+
+impl FrameStatsAggregator {
+    fn push(
+        &mut self,
+        frame: FrameTimings,
+        now: Instant,
+        frame_budget: Duration,
+    ) -> Option<FrameStats> {
+        let started_at = *self.started_at.get_or_insert(now);
+        self.frames.push(frame);
+        let elapsed = now.saturating_duration_since(started_at);
+        if elapsed < self.interval {
+            return None;
+        }
+        self.started_at = None;
+        Some(frame_stats(
+            elapsed,
+            std::mem::take(&mut self.frames),
+            frame_budget,
+        ))
+    }
+}
+
+fn frame_stats(
+    interval: Duration,
+    frames: Vec<FrameTimings>,
+    frame_budget: Duration,
+) -> FrameStats {
+    let frame_count = frames.len();
+    let sum = frames
+        .iter()
+        .fold(FrameTimings::default(), |sum, frame| FrameTimings {
+            layout: sum.layout + frame.layout,
+            paint: sum.paint + frame.paint,
+            present: sum.present + frame.present,
+        });
+    let mean = FrameTimings {
+        layout: sum.layout / frame_count as u32,
+        paint: sum.paint / frame_count as u32,
+        present: sum.present / frame_count as u32,
+    };
+
+    let mut totals = frames.iter().map(FrameTimings::total).collect::<Vec<_>>();
+    totals.sort();
+    let p95 = totals[(frame_count * 95).div_ceil(100).saturating_sub(1)];
+    let max = totals[frame_count - 1];
+    let dropped_frames = totals
+        .iter()
+        .map(|total| (total.as_nanos() / frame_budget.as_nanos()) as usize)
+        .sum();
+
+    let mut outliers = frames
+        .into_iter()
+        .filter(|frame| frame.layout > OUTLIER_THRESHOLD || frame.paint > OUTLIER_THRESHOLD)
+        .collect::<Vec<_>>();
+    outliers.sort_by_key(|frame| std::cmp::Reverse(frame.total()));
+    outliers.truncate(MAX_OUTLIERS);
+
+    FrameStats {
+        interval,
+        frame_count,
+        mean,
+        p95,
+        max,
+        dropped_frames,
+        outliers,
+    }
+}
+
+impl Window {
+    /// Calls `callback` with statistics about the frames this window drew, once every `interval`
+    /// during which it drew at least one frame. Frame timings are only collected while a
+    /// callback is registered, so that performance can be measured in the field by applications
+    /// that opt into it.
+    pub fn on_frame_stats(
+        &mut self,
+        interval: Duration,
+        mut callback: impl FnMut(&FrameStats, &mut Window, &mut App) + 'static,
+    ) -> Subscription {
+        let mut aggregator = FrameStatsAggregator {
+            interval,
+            started_at: None,
+            frames: Vec::new(),
+        };
+        let (subscription, activate) = self.frame_stats_listeners.insert(
+            (),
+            Box::new(move |frame, now, window, cx| {
+                if let Some(stats) = aggregator.push(frame, now, window.frame_budget(cx)) {
+                    callback(&stats, window, cx);
+                }
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Reports the frame drawn before the current one to the frame stats listeners, now that
+    /// it has been presented.
+    pub(super) fn report_frame_stats(
+        &mut self,
+        previous_frame: Option<FrameTimings>,
+        cx: &mut App,
+    ) {
+        if let Some(frame) = previous_frame {
+            let now = cx.background_executor().now();
+            self.frame_stats_listeners
+                .clone()
+                .retain(&(), |listener| listener(frame, now, self, cx));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{self as gpui, div, AppContext as _, Context, IntoElement, Render, TestAppContext};
+    use std::{cell::RefCell, rc::Rc};
+
+    struct TestView;
+
+    impl Render for TestView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+        }
+    }
+
+    #[test]
+    fn test_frame_stats_aggregation() {
+        let frame = |layout, paint| FrameTimings {
+            layout: Duration::from_millis(layout),
+            paint: Duration::from_millis(paint),
+            present: Duration::ZERO,
+        };
+        let frames = vec![frame(4, 4), frame(6, 6), frame(60, 8), frame(2, 2)];
+        let stats = frame_stats(
+            Duration::from_secs(1),
+            frames.clone(),
+            Duration::from_secs_f32(1. / 60.),
+        );
+        assert_eq!(stats.frame_count, 4);
+        assert_eq!(stats.mean, frame(18, 5));
+        assert_eq!(stats.p95, Duration::from_millis(68));
+        assert_eq!(stats.max, Duration::from_millis(68));
+        // The 68ms frame missed four refreshes, and the others were within budget.
+        assert_eq!(stats.dropped_frames, 4);
+        assert_eq!(stats.outliers, [frame(60, 8)]);
+
+        // At 120 frames per second, the 68ms frame missed eight refreshes and the 12ms one missed
+        // one.
+        let stats = frame_stats(
+            Duration::from_secs(1),
+            frames,
+            Duration::from_secs_f32(1. / 120.),
+        );
+        assert_eq!(stats.dropped_frames, 9);
+    }
+
+    #[gpui::test]
+    fn test_on_frame_stats(cx: &mut TestAppContext) {
+        let reports = Rc::new(RefCell::new(Vec::new()));
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| cx.new(|_| TestView))
+                .unwrap()
+        });
+        let subscription = window
+            .update(cx, |_, window, _| {
+                let reports = reports.clone();
+                window.on_frame_stats(Duration::from_secs(10), move |stats, _, _| {
+                    reports.borrow_mut().push(stats.clone())
+                })
+            })
+            .unwrap();
+
+        let draw_frame = |cx: &mut TestAppContext| {
+            window.update(cx, |_, window, _| window.refresh()).unwrap();
+            cx.run_until_parked();
+        };
+        for _ in 0..3 {
+            draw_frame(cx);
+        }
+        assert!(reports.borrow().is_empty());
+
+        cx.executor().advance_clock(Duration::from_secs(10));
+        draw_frame(cx);
+        let [stats] = reports.take().try_into().unwrap();
+        assert_eq!(stats.frame_count, 3);
+        assert_eq!(stats.interval, Duration::from_secs(10));
+
+        drop(subscription);
+        cx.executor().advance_clock(Duration::from_secs(10));
+        for _ in 0..3 {
+            draw_frame(cx);
+        }
+        assert!(reports.borrow().is_empty());
+    }
+}
@@ -0,0 +1,345 @@
+use std::{fmt::Debug, mem};
+
+use serde_json::json;
+
+use crate::{
+    self as gpui, actions, fill, hsla, outline, point, px, size, white, App, Bounds, DispatchPhase,
+    Edges, ElementId, KeyContext, MouseMoveEvent, Pixels, Point, ShapedLine, SharedString, Style,
+    TextRun,
+};
+
+use super::Window;
+
+actions!(debug, [ToggleInspector]);
+
+pub(super) const LINE_HEIGHT: Pixels = px(15.);
+pub(super) const PANEL_PADDING: Pixels = px(8.);
+const FONT_SIZE: Pixels = px(11.);
+const MAX_LISTED_CHILDREN: usize = 8;
+
+/// An element painted while the inspector is enabled, see [`Window::inspected_elements`].
+#[derive(Clone, Debug)]
+pub struct InspectedElement {
+    /// The element's id, if it has one
+    pub id: Option<ElementId>,
+    /// The key context set by the element, if any
+    pub key_context: Option<KeyContext>,
+    /// The element's style after applying hover, active and other state-dependent styles
+    pub style: Style,
+    /// The bounds of the element, in window coordinates
+    pub bounds: Bounds<Pixels>,
+    /// The index of the element's parent, or `None` for top-level elements
+    pub parent: Option<usize>,
+}
+
+impl InspectedElement {
+    fn title(&self) -> String {
+        match &self.id {
+            Some(id) => format!("div #{}", id),
+            None => "div".to_string(),
+        }
+    }
+
+    fn to_json(&self, children: Vec<serde_json::Value>) -> serde_json::Value {
+        let style = &self.style;
+        json!({
+            "id": self.id.as_ref().map(|id| id.to_string()),
+            "key_context": self.key_context.as_ref().map(|context| format!("{:?}", context)),
+            "bounds": {
+                "x": self.bounds.origin.x.0,
+                "y": self.bounds.origin.y.0,
+                "width": self.bounds.size.width.0,
+                "height": self.bounds.size.height.0,
+            },
+            "style": {
+                "display": format!("{:?}", style.display),
+                "position": format!("{:?}", style.position),
+                "flex_direction": format!("{:?}", style.flex_direction),
+                "size": format!("{:?} × {:?}", style.size.width, style.size.height),
+                "min_size": format!("{:?} × {:?}", style.min_size.width, style.min_size.height),
+                "max_size": format!("{:?} × {:?}", style.max_size.width, style.max_size.height),
+                "padding": edges(&style.padding),
+                "margin": edges(&style.margin),
+                "border_widths": edges(&style.border_widths),
+                "background": style
+                    .background
+                    .as_ref()
+                    .map(|background| format!("{:?}", background)),
+                "opacity": style.opacity,
+            },
+            "children": children,
+        })
+    }
+}
+
+pub(crate) fn init_inspector(cx: &mut App) {
+    cx.on_action(|_: &ToggleInspector, cx| {
+        // Actions are dispatched while their window is being updated, so toggle it afterwards.
+        if let Some(window) = cx.active_window() {
+            cx.defer(move |cx| {
+                window
+                    .update(cx, |_, window, _| window.toggle_inspector())
+                    .ok();
+            });
+        }
+    });
+}
+
+/// Highlights the element under the mouse and paints a panel describing it and its position in
+/// the element tree.
+pub(super) fn paint_inspector(window: &mut Window, cx: &mut App) {
+    // Follow the mouse by redrawing whenever it moves.
+    window.on_mouse_event(|_: &MouseMoveEvent, phase, window, _| {
+        if phase == DispatchPhase::Capture {
+            window.refresh();
+        }
+    });
+
+    let mouse_position = window.mouse_position();
+    let elements = &window.next_frame.inspected_elements;
+    let Some(hovered_ix) = elements
+        .iter()
+        .rposition(|element| element.bounds.contains(&mouse_position))
+    else {
+        return;
+    };
+    let hovered_bounds = elements[hovered_ix].bounds;
+    let parent_bounds = elements[hovered_ix]
+        .parent
+        .map(|parent| elements[parent].bounds);
+    let lines = describe(elements, hovered_ix);
+
+    if let Some(parent_bounds) = parent_bounds {
+        window.paint_quad(outline(parent_bounds, hsla(0.08, 0.8, 0.6, 0.8)));
+    }
+    window.paint_quad(
+        fill(hovered_bounds, hsla(0.58, 0.8, 0.6, 0.25))
+            .border_widths(px(1.))
+            .border_color(hsla(0.58, 0.8, 0.6, 1.)),
+    );
+
+    let shaped_lines = shape_lines(window, lines);
+
+    // Place the panel in the bottom corner away from the mouse, so it doesn't cover the
+    // inspected element.
+    let viewport_size = window.viewport_size();
+    let panel_size = size(
+        shaped_lines
+            .iter()
+            .map(|line| line.width)
+            .max()
+            .unwrap_or_default()
+            + PANEL_PADDING * 2.,
+        LINE_HEIGHT * shaped_lines.len() as f32 + PANEL_PADDING * 2.,
+    );
+    let panel_x = if mouse_position.x > viewport_size.width / 2. {
+        PANEL_PADDING
+    } else {
+        viewport_size.width - panel_size.width - PANEL_PADDING
+    };
+    let panel_bounds = Bounds::new(
+        point(
+            panel_x,
+            viewport_size.height - panel_size.height - PANEL_PADDING,
+        ),
+        panel_size,
+    );
+    window.paint_quad(fill(panel_bounds, hsla(0., 0., 0.1, 0.9)));
+
+    let mut origin: Point<Pixels> = panel_bounds.origin + point(PANEL_PADDING, PANEL_PADDING);
+    for line in shaped_lines {
+        line.paint(origin, LINE_HEIGHT, window, cx).ok();
+        origin.y += LINE_HEIGHT;
+    }
+}
+
+/// Describes the given elements as a tree, see [`Window::debug_tree`].
+pub(super) fn element_tree_json(elements: &[InspectedElement]) -> serde_json::Value {
+    // Children are painted after their parent, so build the tree from the last element backwards.
+    let mut children = vec![Vec::new(); elements.len()];
+    let mut roots = Vec::new();
+    for (ix, element) in elements.iter().enumerate().rev() {
+        let mut element_children = mem::take(&mut children[ix]);
+        element_children.reverse();
+        let node = element.to_json(element_children);
+        match element.parent {
+            Some(parent) => children[parent].push(node),
+            None => roots.push(node),
+        }
+    }
+    roots.reverse();
+    serde_json::Value::Array(roots)
+}
+
+/// Shapes lines of text for the panels painted by the inspector and frame profiler.
+pub(super) fn shape_lines(window: &Window, lines: Vec<String>) -> Vec<ShapedLine> {
+    let font = window.text_style().font();
+    lines
+        .into_iter()
+        .filter_map(|line| {
+            let run = TextRun {
+                len: line.len(),
+                font: font.clone(),
+                color: white(),
+                background_color: None,
+                underline: None,
+                strikethrough: None,
+            };
+            window
+                .text_system()
+                .shape_line(SharedString::from(line), FONT_SIZE, &[run])
+                .ok()
+        })
+        .collect()
+}
+
+/// Describes the element at the given index, followed by the path to it from the root of the
+/// tree and its children.
+fn describe(elements: &[InspectedElement], ix: usize) -> Vec<String> {
+    let element = &elements[ix];
+    let style = &element.style;
+    let bounds = element.bounds;
+    let mut lines = vec![element.title()];
+    if let Some(key_context) = &element.key_context {
+        lines.push(format!("key context: {:?}", key_context));
+    }
+    lines.push(format!(
+        "bounds: {} × {} at ({}, {})",
+        bounds.size.width.0, bounds.size.height.0, bounds.origin.x.0, bounds.origin.y.0
+    ));
+    lines.push(format!(
+        "display: {:?}, position: {:?}, direction: {:?}",
+        style.display, style.position, style.flex_direction
+    ));
+    lines.push(format!(
+        "size: {:?} × {:?}, min: {:?} × {:?}, max: {:?} × {:?}",
+        style.size.width,
+        style.size.height,
+        style.min_size.width,
+        style.min_size.height,
+        style.max_size.width,
+        style.max_size.height
+    ));
+    lines.push(format!("padding: {}", edges(&style.padding)));
+    lines.push(format!("margin: {}", edges(&style.margin)));
+    lines.push(format!("border: {}", edges(&style.border_widths)));
+    if let Some(background) = &style.background {
+        lines.push(format!("background: {:?}", background));
+    }
+    lines.push(String::new());
+
+    let mut ancestors = vec![ix];
+    while let Some(parent) = elements[*ancestors.last().unwrap()].parent {
+        ancestors.push(parent);
+    }
+    let depth = ancestors.len();
+    for (ancestor_depth, ancestor_ix) in ancestors.into_iter().rev().enumerate() {
+        let mut line = "  ".repeat(ancestor_depth);
+        line.push_str(&elements[ancestor_ix].title());
+        if ancestor_ix == ix {
+            line.push_str("  ◀");
+        }
+        lines.push(line);
+    }
+    let children = elements
+        .iter()
+        .filter(|element| element.parent == Some(ix))
+        .collect::<Vec<_>>();
+    for child in children.iter().take(MAX_LISTED_CHILDREN) {
+        lines.push(format!("{}{}", "  ".repeat(depth), child.title()));
+    }
+    if children.len() > MAX_LISTED_CHILDREN {
+        lines.push(format!(
+            "{}… {} more",
+            "  ".repeat(depth),
+            children.len() - MAX_LISTED_CHILDREN
+        ));
+    }
+    lines
+}
+
+fn edges<T: Clone + Default + Debug>(edges: &Edges<T>) -> String {
+    format!(
+        "{:?} {:?} {:?} {:?}",
+        edges.top, edges.right, edges.bottom, edges.left
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        self as gpui, div, px, AppContext as _, Context, ElementId, InteractiveElement,
+        IntoElement, ParentElement, Render, Styled, TestAppContext, Window,
+    };
+
+    struct TestView;
+
+    impl Render for TestView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .id("root")
+                .key_context("Root")
+                .size_full()
+                .child(div().id("child").w(px(10.)).h(px(20.)))
+        }
+    }
+
+    #[gpui::test]
+    fn test_inspected_elements(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| cx.new(|_| TestView))
+                .unwrap()
+        });
+        window
+            .update(cx, |_, window, _| {
+                assert!(window.inspected_elements().is_empty());
+                window.toggle_inspector();
+            })
+            .unwrap();
+
+        let elements = window
+            .update(cx, |_, window, _| window.inspected_elements().to_vec())
+            .unwrap();
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[0].id, Some(ElementId::from("root")));
+        assert_eq!(
+            elements[0]
+                .key_context
+                .as_ref()
+                .map(|context| format!("{:?}", context)),
+            Some("Root".to_string())
+        );
+        assert_eq!(elements[0].parent, None);
+        assert_eq!(elements[1].id, Some(ElementId::from("child")));
+        assert_eq!(elements[1].bounds.size, gpui::size(px(10.), px(20.)));
+        assert_eq!(elements[1].parent, Some(0));
+    }
+
+    #[gpui::test]
+    fn test_debug_tree(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| cx.new(|_| TestView))
+                .unwrap()
+        });
+        let tree = window
+            .update(cx, |_, window, cx| window.debug_tree(cx))
+            .unwrap();
+
+        assert_eq!(tree[0]["id"], "root");
+        assert_eq!(tree[0]["key_context"], "Root");
+        assert_eq!(tree[0]["style"]["size"], "100% × 100%");
+        let child = &tree[0]["children"][0];
+        assert_eq!(child["id"], "child");
+        assert_eq!(child["bounds"]["width"], 10.);
+        assert_eq!(child["bounds"]["height"], 20.);
+        assert_eq!(child["children"], serde_json::json!([]));
+
+        // Recording stops once the tree has been returned.
+        window.update(cx, |_, window, _| window.refresh()).unwrap();
+        window
+            .update(cx, |_, window, _| {
+                assert!(window.inspected_elements().is_empty())
+            })
+            .unwrap();
+    }
+}
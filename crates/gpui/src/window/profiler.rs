@@ -0,0 +1,349 @@
+use std::{cell::Cell, collections::VecDeque, time::Duration};
+
+use collections::FxHashMap;
+
+use crate::{
+    self as gpui, actions, fill, hsla, point, px, size, App, AtlasStats, Bounds, EntityId, Hsla,
+    Pixels, Scene,
+};
+
+use super::{
+    atlas::AtlasUsage,
+    inspector::{shape_lines, LINE_HEIGHT, PANEL_PADDING},
+    Window,
+};
+
+actions!(debug, [ToggleFrameProfiler]);
+
+const HISTORY_LEN: usize = 120;
+const MAX_LISTED_VIEWS: usize = 5;
+const GRAPH_HEIGHT: Pixels = px(60.);
+const BAR_WIDTH: Pixels = px(2.);
+/// The time available to draw each frame at 60 frames per second, assumed when the display's
+/// refresh rate is unknown.
+const DEFAULT_FRAME_BUDGET: Duration = Duration::from_micros(16_667);
+
+/// How long it took to draw a frame, see [`Window::last_frame_timings`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameTimings {
+    /// The time spent rendering views, computing layout and prepainting elements
+    pub layout: Duration,
+    /// The time spent painting elements into the scene
+    pub paint: Duration,
+    /// The time spent handing the scene to the GPU for rendering. Time spent by the GPU itself
+    /// isn't included, as platforms don't report it.
+    pub present: Duration,
+}
+
+impl FrameTimings {
+    /// Returns the total time it took to draw the frame.
+    pub fn total(&self) -> Duration {
+        self.layout + self.paint + self.present
+    }
+}
+
+/// What a window asked the GPU to draw for a frame, see [`Window::last_frame_render_stats`].
+/// The time the GPU spent drawing isn't included, as platforms don't report it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// How many shadows were drawn
+    pub shadows: usize,
+    /// How many quads were drawn
+    pub quads: usize,
+    /// How many paths were drawn
+    pub paths: usize,
+    /// How many underlines were drawn
+    pub underlines: usize,
+    /// How many single-color sprites, like glyphs and icons, were drawn
+    pub monochrome_sprites: usize,
+    /// How many multi-color sprites, like images and emoji, were drawn
+    pub polychrome_sprites: usize,
+    /// How many surfaces, like video frames, were drawn
+    pub surfaces: usize,
+    /// How many batches of primitives of the same kind the scene was split into. Renderers issue
+    /// at least one draw call per batch, and a few more for paths.
+    pub draw_calls: usize,
+    /// How many tiles were rasterized and uploaded to the sprite atlas while painting the frame
+    pub texture_uploads: usize,
+    /// How many bytes were uploaded to the sprite atlas while painting the frame
+    pub texture_upload_bytes: usize,
+    /// How much of the sprite atlas was in use once the frame was drawn
+    pub atlas: AtlasStats,
+}
+
+impl RenderStats {
+    pub(super) fn new(scene: &Scene, atlas_usage: &mut AtlasUsage) -> Self {
+        let (texture_uploads, texture_upload_bytes) = atlas_usage.take_uploads();
+        Self {
+            shadows: scene.shadows.len(),
+            quads: scene.quads.len(),
+            paths: scene.paths.len(),
+            underlines: scene.underlines.len(),
+            monochrome_sprites: scene.monochrome_sprites.len(),
+            polychrome_sprites: scene.polychrome_sprites.len(),
+            surfaces: scene.surfaces.len(),
+            draw_calls: scene.batches().count(),
+            texture_uploads,
+            texture_upload_bytes,
+            atlas: atlas_usage.stats(),
+        }
+    }
+}
+
+/// Timings for recent frames, collected while the frame profiler is enabled.
+#[derive(Default)]
+pub(crate) struct FrameProfiler {
+    history: VecDeque<FrameTimings>,
+    /// Presenting happens after the frame is drawn, so it's recorded here and attributed to the
+    /// frame when the next one is drawn.
+    pub(super) last_present: Cell<Duration>,
+    /// The time spent in each view during the current frame, including the views it contains.
+    pub(super) view_times: FxHashMap<EntityId, Duration>,
+    pub(super) view_names: FxHashMap<EntityId, &'static str>,
+}
+
+impl FrameProfiler {
+    pub(super) fn start_frame(&mut self) {
+        if let Some(last_frame) = self.history.back_mut() {
+            last_frame.present = self.last_present.get();
+        }
+        self.view_times.clear();
+    }
+
+    pub(super) fn finish_frame(&mut self, layout: Duration, paint: Duration) {
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(FrameTimings {
+            layout,
+            paint,
+            present: Duration::ZERO,
+        });
+    }
+
+    pub(super) fn last_frame(&self) -> Option<FrameTimings> {
+        self.history.back().copied()
+    }
+}
+
+pub(crate) fn init_frame_profiler(cx: &mut App) {
+    cx.on_action(|_: &ToggleFrameProfiler, cx| {
+        // Actions are dispatched while their window is being updated, so toggle it afterwards.
+        if let Some(window) = cx.active_window() {
+            cx.defer(move |cx| {
+                window
+                    .update(cx, |_, window, _| window.toggle_frame_profiler())
+                    .ok();
+            });
+        }
+    });
+}
+
+/// Paints a panel in the top right corner of the window with the timings of the current frame,
+/// a graph of recent frames, and the views that took the longest to draw.
+impl Window {
+    /// Returns the time available to draw each frame before missing a refresh of the display on
+    /// which the window is visible.
+    pub(super) fn frame_budget(&self, cx: &App) -> Duration {
+        self.refresh_rate(cx)
+            .filter(|refresh_rate| *refresh_rate > 0.)
+            .map_or(DEFAULT_FRAME_BUDGET, |refresh_rate| {
+                Duration::from_secs_f32(1. / refresh_rate)
+            })
+    }
+}
+
+pub(super) fn paint_frame_profiler(window: &mut Window, cx: &mut App) {
+    let Some(profiler) = window.frame_profiler.as_ref() else {
+        return;
+    };
+    let Some(frame) = profiler.last_frame() else {
+        return;
+    };
+
+    let mut lines = vec![format!(
+        "frame {}  layout {}  paint {}  present {}",
+        format_duration(frame.total()),
+        format_duration(frame.layout),
+        format_duration(frame.paint),
+        // The current frame hasn't been presented yet.
+        profiler
+            .history
+            .iter()
+            .nth_back(1)
+            .map_or("-".to_string(), |frame| format_duration(frame.present)),
+    )];
+    let stats = window.render_stats;
+    lines.push(format!(
+        "{} draw calls  {} quads  {} sprites  {} paths  {} uploads ({})  atlas {}",
+        stats.draw_calls,
+        stats.quads,
+        stats.monochrome_sprites + stats.polychrome_sprites,
+        stats.paths,
+        stats.texture_uploads,
+        format_bytes(stats.texture_upload_bytes),
+        format_bytes(stats.atlas.bytes),
+    ));
+    let mut view_times = profiler.view_times.iter().collect::<Vec<_>>();
+    view_times.sort_by(|a, b| b.1.cmp(a.1));
+    for (view_id, time) in view_times.into_iter().take(MAX_LISTED_VIEWS) {
+        let name = profiler
+            .view_names
+            .get(view_id)
+            .copied()
+            .unwrap_or("<view>");
+        lines.push(format!(
+            "{}  {} ({})",
+            format_duration(*time),
+            name,
+            view_id
+        ));
+    }
+    let history = profiler.history.iter().copied().collect::<Vec<_>>();
+
+    let shaped_lines = shape_lines(window, lines);
+    let viewport_size = window.viewport_size();
+    let graph_width = BAR_WIDTH * HISTORY_LEN as f32;
+    let panel_size = size(
+        shaped_lines
+            .iter()
+            .map(|line| line.width)
+            .max()
+            .unwrap_or_default()
+            .max(graph_width)
+            + PANEL_PADDING * 2.,
+        GRAPH_HEIGHT + LINE_HEIGHT * shaped_lines.len() as f32 + PANEL_PADDING * 3.,
+    );
+    let panel_bounds = Bounds::new(
+        point(
+            viewport_size.width - panel_size.width - PANEL_PADDING,
+            PANEL_PADDING,
+        ),
+        panel_size,
+    );
+    window.paint_quad(fill(panel_bounds, hsla(0., 0., 0.1, 0.9)));
+
+    // Each bar stacks the layout, paint and present times of a frame, scaled so that the frame
+    // budget reaches half way up the graph.
+    let graph_origin = panel_bounds.origin + point(PANEL_PADDING, PANEL_PADDING);
+    let graph_bottom = graph_origin.y + GRAPH_HEIGHT;
+    let frame_budget = window.frame_budget(cx);
+    let scale = |duration: Duration| {
+        GRAPH_HEIGHT * (duration.as_secs_f32() / frame_budget.as_secs_f32() / 2.).min(1.)
+    };
+    window.paint_quad(fill(
+        Bounds::new(
+            point(graph_origin.x, graph_bottom - scale(frame_budget)),
+            size(graph_width, px(1.)),
+        ),
+        hsla(0., 0., 1., 0.3),
+    ));
+    for (ix, frame) in history.iter().enumerate() {
+        let x = graph_origin.x + BAR_WIDTH * ix as f32;
+        let mut top = graph_bottom;
+        let segments: [(Duration, Hsla); 3] = [
+            (frame.layout, hsla(0.58, 0.8, 0.6, 1.)),
+            (frame.paint, hsla(0.33, 0.7, 0.5, 1.)),
+            (frame.present, hsla(0.08, 0.8, 0.6, 1.)),
+        ];
+        for (duration, color) in segments {
+            let height = scale(duration).min(top - graph_origin.y);
+            top -= height;
+            window.paint_quad(fill(
+                Bounds::new(point(x, top), size(BAR_WIDTH, height)),
+                color,
+            ));
+        }
+    }
+
+    let mut origin = point(graph_origin.x, graph_bottom + PANEL_PADDING);
+    for line in shaped_lines {
+        line.paint(origin, LINE_HEIGHT, window, cx).ok();
+        origin.y += LINE_HEIGHT;
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.)
+}
+
+fn format_bytes(bytes: usize) -> String {
+    if bytes < 1024 * 1024 {
+        format!("{:.1}KiB", bytes as f64 / 1024.)
+    } else {
+        format!("{:.1}MiB", bytes as f64 / (1024. * 1024.))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        self as gpui, div, px, red, AppContext as _, Context, IntoElement, ParentElement as _,
+        Render, Styled as _, TestAppContext, Window,
+    };
+
+    struct TestView;
+
+    impl Render for TestView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+        }
+    }
+
+    struct QuadsView;
+
+    impl Render for QuadsView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .size_full()
+                .bg(red())
+                .child(div().size(px(10.)).bg(red()))
+        }
+    }
+
+    #[gpui::test]
+    fn test_render_stats(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| cx.new(|_| QuadsView))
+                .unwrap()
+        });
+        window.update(cx, |_, window, _| window.refresh()).unwrap();
+        cx.run_until_parked();
+
+        window
+            .update(cx, |_, window, _| {
+                let stats = window.last_frame_render_stats();
+                assert_eq!(stats.quads, 2);
+                assert_eq!(stats.paths, 0);
+                assert_eq!(stats.draw_calls, 1);
+                assert_eq!(stats.texture_uploads, 0);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_frame_profiler(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| cx.new(|_| TestView))
+                .unwrap()
+        });
+        window
+            .update(cx, |_, window, _| {
+                assert_eq!(window.last_frame_timings(), None);
+                window.toggle_frame_profiler();
+            })
+            .unwrap();
+
+        window
+            .update(cx, |_, window, cx| {
+                assert!(window.last_frame_timings().is_some());
+                let profiler = window.frame_profiler.as_ref().unwrap();
+                assert!(profiler.view_times.contains_key(&cx.entity_id()));
+                assert_eq!(
+                    profiler.view_names.get(&cx.entity_id()).copied(),
+                    Some(std::any::type_name::<TestView>())
+                );
+            })
+            .unwrap();
+    }
+}
@@ -26,7 +26,7 @@ use fs::MTime;
 use futures::channel::oneshot;
 use gpui::{
     AnyElement, App, AppContext as _, Context, Entity, EventEmitter, HighlightStyle, Pixels,
-    SharedString, StyledText, Task, TaskLabel, TextStyle, Window,
+    Priority, SharedString, StyledText, Task, TaskLabel, TextStyle, Window,
 };
 use lsp::{LanguageServerId, NumberOrString};
 use parking_lot::Mutex;
@@ -1460,14 +1460,17 @@ impl Buffer {
         let mut syntax_snapshot = syntax_map.snapshot();
         drop(syntax_map);
 
-        let parse_task = cx.background_executor().spawn({
-            let language = language.clone();
-            let language_registry = language_registry.clone();
-            async move {
-                syntax_snapshot.reparse(&text, language_registry, language);
-                syntax_snapshot
-            }
-        });
+        // Parsing blocks the main thread for a moment, and highlighting waits on it afterwards.
+        let parse_task = cx
+            .background_executor()
+            .spawn_with_priority(Priority::UserBlocking, {
+                let language = language.clone();
+                let language_registry = language_registry.clone();
+                async move {
+                    syntax_snapshot.reparse(&text, language_registry, language);
+                    syntax_snapshot
+                }
+            });
 
         self.parse_status.0.send(ParseStatus::Parsing).unwrap();
         match cx
@@ -1539,7 +1542,9 @@ impl Buffer {
 
     fn request_autoindent(&mut self, cx: &mut Context<Self>) {
         if let Some(indent_sizes) = self.compute_autoindents() {
-            let indent_sizes = cx.background_executor().spawn(indent_sizes);
+            let indent_sizes = cx
+                .background_executor()
+                .spawn_with_priority(Priority::UserBlocking, indent_sizes);
             match cx
                 .background_executor()
                 .block_with_timeout(Duration::from_micros(500), indent_sizes)
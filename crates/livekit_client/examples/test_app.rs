@@ -67,6 +67,8 @@ fn main() {
                 name: "Quit".into(),
                 action: Box::new(Quit),
                 os_action: None,
+                checked: false,
+                disabled: false,
             }],
         }]);
 
@@ -67,7 +67,7 @@ struct Dispatcher(Arc<dyn gpui::PlatformDispatcher>);
 #[cfg(not(all(target_os = "windows", target_env = "gnu")))]
 impl livekit::dispatcher::Dispatcher for Dispatcher {
     fn dispatch(&self, runnable: livekit::dispatcher::Runnable) {
-        self.0.dispatch(runnable, None);
+        self.0.dispatch(runnable, None, gpui::Priority::Default);
     }
 
     fn dispatch_after(
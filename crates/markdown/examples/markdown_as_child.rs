@@ -73,7 +73,7 @@ pub fn main() {
                         underline: Some(gpui::UnderlineStyle {
                             thickness: px(1.),
                             color: Some(Color::Accent.color(cx)),
-                            wavy: false,
+                            kind: gpui::UnderlineKind::Solid,
                         }),
                         ..Default::default()
                     },
@@ -59,6 +59,28 @@ pub mod core_video {
     impl_CFTypeDescription!(CVImageBuffer);
 
     impl CVImageBuffer {
+        /// Wraps an existing IOSurface, such as one produced by a video decoder or
+        /// another process, without copying its contents.
+        pub fn from_io_surface(io_surface: &IOSurface) -> Result<Self> {
+            unsafe {
+                let mut this = ptr::null();
+                let result = CVPixelBufferCreateWithIOSurface(
+                    kCFAllocatorDefault,
+                    io_surface.as_concrete_TypeRef(),
+                    ptr::null(),
+                    &mut this,
+                );
+                if result == kCVReturnSuccess {
+                    Ok(CVImageBuffer::wrap_under_create_rule(this))
+                } else {
+                    Err(anyhow!(
+                        "could not create pixel buffer from IOSurface, code: {}",
+                        result
+                    ))
+                }
+            }
+        }
+
         pub fn io_surface(&self) -> IOSurface {
             unsafe {
                 IOSurface::wrap_under_get_rule(CVPixelBufferGetIOSurface(
@@ -92,6 +114,12 @@ pub mod core_video {
     extern "C" {
         fn CVImageBufferGetTypeID() -> CFTypeID;
         fn CVPixelBufferGetIOSurface(buffer: CVImageBufferRef) -> IOSurfaceRef;
+        fn CVPixelBufferCreateWithIOSurface(
+            allocator: CFAllocatorRef,
+            surface: IOSurfaceRef,
+            pixel_buffer_attributes: CFDictionaryRef,
+            pixel_buffer_out: *mut CVImageBufferRef,
+        ) -> CVReturn;
         fn CVPixelBufferGetWidth(buffer: CVImageBufferRef) -> usize;
         fn CVPixelBufferGetHeight(buffer: CVImageBufferRef) -> usize;
         fn CVPixelBufferGetWidthOfPlane(buffer: CVImageBufferRef, plane: usize) -> usize;
@@ -125,6 +125,12 @@ pub fn database_dir() -> &'static PathBuf {
     DATABASE_DIR.get_or_init(|| support_dir().join("db"))
 }
 
+/// Returns the path to the file in which the state of persisted views and globals is saved.
+pub fn persisted_state_file() -> &'static PathBuf {
+    static PERSISTED_STATE_FILE: OnceLock<PathBuf> = OnceLock::new();
+    PERSISTED_STATE_FILE.get_or_init(|| support_dir().join("persisted_state.json"))
+}
+
 /// Returns the path to the crashes directory, if it exists for the current platform.
 pub fn crashes_dir() -> &'static Option<PathBuf> {
     static CRASHES_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
@@ -0,0 +1,141 @@
+use collections::HashMap;
+use gpui::{App, Context, Entity, Subscription};
+use language::{Buffer, BufferEvent, BufferSnapshot, LocalFile as _};
+use std::path::PathBuf;
+use text::{Anchor, Bias, BufferId, Point, ToPoint as _};
+
+/// Lines the user has bookmarked, across all of a project's buffers.
+///
+/// Bookmarked buffers are kept open so that their bookmarks follow edits.
+#[derive(Default)]
+pub struct BookmarkStore {
+    bookmarks: HashMap<BufferId, BufferBookmarks>,
+}
+
+struct BufferBookmarks {
+    buffer: Entity<Buffer>,
+    /// Anchors at the start of each bookmarked line, in buffer order.
+    anchors: Vec<Anchor>,
+    _subscription: Subscription,
+}
+
+impl BookmarkStore {
+    /// Bookmarks `row` in `buffer`, or removes its bookmark if it has one.
+    pub fn toggle(&mut self, buffer: &Entity<Buffer>, row: u32, cx: &mut Context<Self>) {
+        let snapshot = buffer.read(cx).snapshot();
+        let buffer_id = snapshot.remote_id();
+        if let Some(bookmarks) = self.bookmarks.get_mut(&buffer_id) {
+            let len = bookmarks.anchors.len();
+            bookmarks
+                .anchors
+                .retain(|anchor| anchor.to_point(&snapshot).row != row);
+            if bookmarks.anchors.len() != len {
+                if bookmarks.anchors.is_empty() {
+                    self.bookmarks.remove(&buffer_id);
+                }
+                cx.notify();
+                return;
+            }
+        }
+
+        self.insert(buffer, [row], cx);
+    }
+
+    /// Bookmarks each of `rows` in `buffer`, skipping rows past its end.
+    pub fn insert(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        rows: impl IntoIterator<Item = u32>,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = buffer.read(cx).snapshot();
+        let buffer_id = snapshot.remote_id();
+        let bookmarks = self
+            .bookmarks
+            .entry(buffer_id)
+            .or_insert_with(|| BufferBookmarks {
+                buffer: buffer.clone(),
+                anchors: Vec::new(),
+                _subscription: cx.subscribe(buffer, |_, _, event: &BufferEvent, cx| {
+                    // Bookmarks are persisted by row, so rows that moved
+                    // since the last save need to be persisted again.
+                    if let BufferEvent::Saved = event {
+                        cx.notify();
+                    }
+                }),
+            });
+        for row in rows {
+            let point = snapshot.clip_point(Point::new(row, 0), Bias::Left);
+            if point.row != row {
+                continue;
+            }
+            // Anchoring after the line start keeps the bookmark with the line's
+            // text when a newline is inserted before it.
+            let anchor = snapshot.anchor_after(point);
+            if let Err(ix) = bookmarks
+                .anchors
+                .binary_search_by(|probe| probe.cmp(&anchor, &snapshot))
+            {
+                bookmarks.anchors.insert(ix, anchor);
+            }
+        }
+        if bookmarks.anchors.is_empty() {
+            self.bookmarks.remove(&buffer_id);
+        }
+        cx.notify();
+    }
+
+    /// Removes every bookmark in the project.
+    pub fn clear(&mut self, cx: &mut Context<Self>) {
+        if !self.bookmarks.is_empty() {
+            self.bookmarks.clear();
+            cx.notify();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+
+    /// The bookmarked rows of the given buffer, in ascending order.
+    pub fn bookmarked_rows(&self, snapshot: &BufferSnapshot) -> Vec<u32> {
+        let Some(bookmarks) = self.bookmarks.get(&snapshot.remote_id()) else {
+            return Vec::new();
+        };
+        let mut rows = bookmarks
+            .anchors
+            .iter()
+            .map(|anchor| anchor.to_point(snapshot).row)
+            .collect::<Vec<_>>();
+        // Deleting the lines between two bookmarks moves them onto the same row.
+        rows.dedup();
+        rows
+    }
+
+    /// Every bookmarked buffer, along with the anchors of its bookmarks.
+    pub fn bookmarks(&self) -> impl Iterator<Item = (&Entity<Buffer>, &[Anchor])> {
+        self.bookmarks
+            .values()
+            .map(|bookmarks| (&bookmarks.buffer, bookmarks.anchors.as_slice()))
+    }
+
+    /// The absolute path and row of every bookmark in a local file, for
+    /// persisting them across sessions.
+    pub fn serialize(&self, cx: &App) -> Vec<(PathBuf, u32)> {
+        let mut bookmarks = Vec::new();
+        for (buffer, _) in self.bookmarks() {
+            let buffer = buffer.read(cx);
+            let Some(file) = buffer.file().and_then(|file| file.as_local()) else {
+                continue;
+            };
+            let abs_path = file.abs_path(cx);
+            bookmarks.extend(
+                self.bookmarked_rows(&buffer.snapshot())
+                    .into_iter()
+                    .map(|row| (abs_path.clone(), row)),
+            );
+        }
+        bookmarks.sort();
+        bookmarks
+    }
+}
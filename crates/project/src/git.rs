@@ -58,6 +58,7 @@ enum Message {
     },
     Stage(GitRepo, Vec<RepoPath>),
     Unstage(GitRepo, Vec<RepoPath>),
+    SetIndexText(GitRepo, RepoPath, Option<String>),
 }
 
 pub enum GitEvent {
@@ -256,6 +257,29 @@ impl GitState {
                 }
                 Ok(())
             }
+            Message::SetIndexText(repo, path, text) => {
+                match repo {
+                    GitRepo::Local(repo) => repo.set_index_text(&path, text)?,
+                    GitRepo::Remote {
+                        project_id,
+                        client,
+                        worktree_id,
+                        work_directory_id,
+                    } => {
+                        client
+                            .request(proto::SetIndexText {
+                                project_id: project_id.0,
+                                worktree_id: worktree_id.to_proto(),
+                                work_directory_id: work_directory_id.to_proto(),
+                                path: path.to_proto(),
+                                text,
+                            })
+                            .await
+                            .context("sending set index text request")?;
+                    }
+                }
+                Ok(())
+            }
             Message::Commit {
                 git_repo,
                 message,
@@ -465,6 +489,23 @@ impl Repository {
         result_rx
     }
 
+    /// Replaces the staged contents of the given path, which is how individual hunks get
+    /// staged. Passing no text removes the path from the index.
+    pub fn set_index_text(
+        &self,
+        path: RepoPath,
+        text: Option<String>,
+    ) -> oneshot::Receiver<anyhow::Result<()>> {
+        let (result_tx, result_rx) = futures::channel::oneshot::channel();
+        self.update_sender
+            .unbounded_send((
+                Message::SetIndexText(self.git_repo.clone(), path, text),
+                result_tx,
+            ))
+            .ok();
+        result_rx
+    }
+
     pub fn stage_all(&self) -> oneshot::Receiver<anyhow::Result<()>> {
         let to_stage = self
             .repository_entry
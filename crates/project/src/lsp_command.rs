@@ -2512,13 +2512,28 @@ impl InlayHints {
             _ => None,
         });
 
-        let position = buffer_handle.update(cx, |buffer, _| {
+        let (position, text_edits) = buffer_handle.update(cx, |buffer, _| {
             let position = buffer.clip_point_utf16(point_from_lsp(lsp_hint.position), Bias::Left);
-            if kind == Some(InlayHintKind::Parameter) {
+            let position = if kind == Some(InlayHintKind::Parameter) {
                 buffer.anchor_before(position)
             } else {
                 buffer.anchor_after(position)
-            }
+            };
+            let text_edits = lsp_hint
+                .text_edits
+                .into_iter()
+                .flatten()
+                .map(|edit| {
+                    let start =
+                        buffer.clip_point_utf16(point_from_lsp(edit.range.start), Bias::Left);
+                    let end = buffer.clip_point_utf16(point_from_lsp(edit.range.end), Bias::Left);
+                    (
+                        buffer.anchor_before(start)..buffer.anchor_after(end),
+                        edit.new_text,
+                    )
+                })
+                .collect();
+            (position, text_edits)
         })?;
         let label = Self::lsp_inlay_label_to_project(lsp_hint.label, server_id)
             .await
@@ -2548,6 +2563,7 @@ impl InlayHints {
                 }
             }),
             resolve_state,
+            text_edits,
         })
     }
 
@@ -2655,6 +2671,15 @@ impl InlayHints {
                 }
             }),
             resolve_state,
+            text_edits: response_hint
+                .text_edits
+                .into_iter()
+                .map(|(range, new_text)| proto::InlayHintTextEdit {
+                    start: Some(language::proto::serialize_anchor(&range.start)),
+                    end: Some(language::proto::serialize_anchor(&range.end)),
+                    new_text,
+                })
+                .collect(),
         }
     }
 
@@ -2779,6 +2804,21 @@ impl InlayHints {
                 })
             }),
             resolve_state,
+            text_edits: message_hint
+                .text_edits
+                .into_iter()
+                .map(|edit| {
+                    let start = edit
+                        .start
+                        .and_then(language::proto::deserialize_anchor)
+                        .context("invalid text edit start")?;
+                    let end = edit
+                        .end
+                        .and_then(language::proto::deserialize_anchor)
+                        .context("invalid text edit end")?;
+                    anyhow::Ok((start..end, edit.new_text))
+                })
+                .collect::<anyhow::Result<_>>()?,
         })
     }
 
@@ -2789,7 +2829,17 @@ impl InlayHints {
                 InlayHintKind::Type => lsp::InlayHintKind::TYPE,
                 InlayHintKind::Parameter => lsp::InlayHintKind::PARAMETER,
             }),
-            text_edits: None,
+            text_edits: (!hint.text_edits.is_empty()).then(|| {
+                hint.text_edits
+                    .iter()
+                    .filter_map(|(range, new_text)| {
+                        Some(lsp::TextEdit {
+                            range: range_to_lsp(range.to_point_utf16(snapshot)).ok()?,
+                            new_text: new_text.clone(),
+                        })
+                    })
+                    .collect()
+            }),
             tooltip: hint.tooltip.and_then(|tooltip| {
                 Some(match tooltip {
                     InlayHintTooltip::String(s) => lsp::InlayHintTooltip::String(s),
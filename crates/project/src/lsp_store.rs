@@ -5,7 +5,7 @@ use crate::{
     lsp_command::{self, *},
     lsp_ext_command,
     prettier_store::{self, PrettierStore, PrettierStoreEvent},
-    project_settings::{LspSettings, ProjectSettings},
+    project_settings::{is_large_buffer, LspSettings, ProjectSettings},
     relativize_path, resolve_path,
     toolchain_store::{EmptyToolchainStore, ToolchainStoreEvent},
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
@@ -2817,6 +2817,9 @@ pub struct LspStore {
     _maintain_buffer_languages: Task<()>,
     diagnostic_summaries:
         HashMap<WorktreeId, HashMap<Arc<Path>, HashMap<LanguageServerId, DiagnosticSummary>>>,
+    /// Buffers above the large file threshold that the user has opted in to
+    /// language features for.
+    large_buffers_with_language: HashSet<BufferId>,
 }
 
 pub enum LspStoreEvent {
@@ -3026,6 +3029,7 @@ impl LspStore {
             language_server_statuses: Default::default(),
             nonce: StdRng::from_entropy().gen(),
             diagnostic_summaries: Default::default(),
+            large_buffers_with_language: Default::default(),
             active_entry: None,
 
             _maintain_workspace_config,
@@ -3083,6 +3087,7 @@ impl LspStore {
             language_server_statuses: Default::default(),
             nonce: StdRng::from_entropy().gen(),
             diagnostic_summaries: Default::default(),
+            large_buffers_with_language: Default::default(),
             active_entry: None,
             toolchain_store,
             _maintain_workspace_config,
@@ -3130,7 +3135,9 @@ impl LspStore {
                     }
                 }
             }
-            BufferStoreEvent::BufferDropped(_) => {}
+            BufferStoreEvent::BufferDropped(buffer_id) => {
+                self.large_buffers_with_language.remove(buffer_id);
+            }
         }
     }
 
@@ -3387,6 +3394,13 @@ impl LspStore {
         // If the buffer has a language, set it and start the language server if we haven't already.
         let buffer = buffer_handle.read(cx);
         let file = buffer.file()?;
+        if is_large_buffer(buffer, cx)
+            && !self
+                .large_buffers_with_language
+                .contains(&buffer.remote_id())
+        {
+            return None;
+        }
 
         let content = buffer.as_rope();
         let available_language = self.languages.language_for_file(file, Some(content), cx);
@@ -3408,6 +3422,27 @@ impl LspStore {
         available_language
     }
 
+    /// Detects the language of a buffer that was opened without one for being
+    /// too large, and starts its language servers.
+    pub fn enable_language_for_large_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) {
+        let buffer_id = buffer.read(cx).remote_id();
+        if !self.large_buffers_with_language.insert(buffer_id) {
+            return;
+        }
+
+        self.detect_language_for_buffer(buffer, cx);
+        if let Some(local) = self.as_local_mut() {
+            local.initialize_buffer(buffer, cx);
+            if local.registered_buffers.contains_key(&buffer_id) {
+                local.register_buffer_with_language_servers(buffer, cx);
+            }
+        }
+    }
+
     pub(crate) fn set_language_for_buffer(
         &mut self,
         buffer: &Entity<Buffer>,
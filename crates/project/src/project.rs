@@ -1,3 +1,4 @@
+pub mod bookmark_store;
 pub mod buffer_store;
 mod color_extractor;
 pub mod connection_manager;
@@ -29,6 +30,7 @@ mod yarn;
 
 use crate::git::GitState;
 use anyhow::{anyhow, Context as _, Result};
+use bookmark_store::BookmarkStore;
 use buffer_store::{BufferStore, BufferStoreEvent};
 use client::{
     proto, Client, Collaborator, PendingEntitySubscription, ProjectId, TypedEnvelope, UserStore,
@@ -159,6 +161,7 @@ pub struct Project {
     ssh_client: Option<Entity<SshRemoteClient>>,
     client_state: ProjectClientState,
     git_state: Entity<GitState>,
+    bookmark_store: Entity<BookmarkStore>,
     collaborators: HashMap<proto::PeerId, Collaborator>,
     client_subscriptions: Vec<client::Subscription>,
     worktree_store: Entity<WorktreeStore>,
@@ -333,6 +336,8 @@ pub struct InlayHint {
     pub padding_right: bool,
     pub tooltip: Option<InlayHintTooltip>,
     pub resolve_state: ResolveState,
+    /// Edits that insert the hint's text into the buffer, such as an inferred type annotation.
+    pub text_edits: Vec<(Range<language::Anchor>, String)>,
 }
 
 /// The user's intent behind a given completion confirmation
@@ -609,6 +614,7 @@ impl Project {
 
         client.add_entity_request_handler(Self::handle_stage);
         client.add_entity_request_handler(Self::handle_unstage);
+        client.add_entity_request_handler(Self::handle_set_index_text);
         client.add_entity_request_handler(Self::handle_commit);
         client.add_entity_request_handler(Self::handle_open_commit_message_buffer);
 
@@ -715,6 +721,7 @@ impl Project {
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
                 git_state,
+                bookmark_store: cx.new(|_| BookmarkStore::default()),
                 client_subscriptions: Vec::new(),
                 _subscriptions: vec![cx.on_release(Self::release)],
                 active_entry: None,
@@ -843,6 +850,7 @@ impl Project {
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
                 git_state,
+                bookmark_store: cx.new(|_| BookmarkStore::default()),
                 client_subscriptions: Vec::new(),
                 _subscriptions: vec![
                     cx.on_release(Self::release),
@@ -1086,6 +1094,7 @@ impl Project {
                     replica_id,
                 },
                 git_state,
+                bookmark_store: cx.new(|_| BookmarkStore::default()),
                 buffers_needing_diff: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
@@ -2572,6 +2581,16 @@ impl Project {
         })
     }
 
+    pub fn enable_language_for_large_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.enable_language_for_large_buffer(buffer, cx)
+        })
+    }
+
     pub fn restart_language_servers_for_buffers(
         &mut self,
         buffers: impl IntoIterator<Item = Entity<Buffer>>,
@@ -4059,6 +4078,25 @@ impl Project {
         Ok(proto::Ack {})
     }
 
+    async fn handle_set_index_text(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::SetIndexText>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let path = RepoPath::new(PathBuf::from(envelope.payload.path));
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.set_index_text(path, envelope.payload.text)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
     async fn handle_commit(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::Commit>,
@@ -4317,6 +4355,10 @@ impl Project {
         &self.git_state
     }
 
+    pub fn bookmark_store(&self) -> &Entity<BookmarkStore> {
+        &self.bookmark_store
+    }
+
     pub fn active_repository(&self, cx: &App) -> Option<Entity<Repository>> {
         self.git_state.read(cx).active_repository()
     }
@@ -2,6 +2,7 @@ use anyhow::Context as _;
 use collections::HashMap;
 use fs::Fs;
 use gpui::{App, AsyncApp, BorrowAppContext, Context, Entity, EventEmitter};
+use language::Buffer;
 use lsp::LanguageServerName;
 use paths::{
     local_settings_file_relative_path, local_tasks_file_relative_path,
@@ -56,6 +57,10 @@ pub struct ProjectSettings {
     /// Configuration for session-related features
     #[serde(default)]
     pub session: SessionSettings,
+
+    /// Configuration for how files too large to edit comfortably are opened
+    #[serde(default)]
+    pub large_files: LargeFileSettings,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -203,6 +208,41 @@ impl Default for SessionSettings {
     }
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct LargeFileSettings {
+    /// Files larger than this many megabytes are opened without syntax
+    /// highlighting, language servers or soft wrap. Set to 0 to never
+    /// treat a file as large.
+    ///
+    /// Default: 20
+    pub threshold_mb: u64,
+}
+
+impl LargeFileSettings {
+    /// Whether a buffer of `len` bytes exceeds the threshold.
+    pub fn is_large(&self, len: usize) -> bool {
+        self.threshold_mb > 0 && len as u64 > self.threshold_mb * 1024 * 1024
+    }
+}
+
+/// Whether `buffer` is too large to open with language features, according to
+/// the settings that apply to its file.
+pub fn is_large_buffer(buffer: &Buffer, cx: &App) -> bool {
+    let location = buffer.file().map(|file| SettingsLocation {
+        worktree_id: file.worktree_id(cx),
+        path: file.path(),
+    });
+    ProjectSettings::get(location, cx)
+        .large_files
+        .is_large(buffer.len())
+}
+
+impl Default for LargeFileSettings {
+    fn default() -> Self {
+        Self { threshold_mb: 20 }
+    }
+}
+
 impl Settings for ProjectSettings {
     const KEY: Option<&'static str> = None;
 
@@ -1767,6 +1767,58 @@ async fn test_toggling_enable_language_server(cx: &mut gpui::TestAppContext) {
         .await;
 }
 
+#[gpui::test]
+async fn test_large_file_language_detection(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.large_files.threshold_mb = 1;
+            });
+        })
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "small.rs": "fn main() {}\n",
+            "large.rs": "fn main() {}\n".repeat(100_000),
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    let small_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/small.rs"), cx)
+        })
+        .await
+        .unwrap();
+    let large_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/large.rs"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    small_buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.language().unwrap().name(), "Rust".into());
+    });
+    large_buffer.read_with(cx, |buffer, _| assert!(buffer.language().is_none()));
+
+    project.update(cx, |project, cx| {
+        project.enable_language_for_large_buffer(&large_buffer, cx)
+    });
+    cx.executor().run_until_parked();
+    large_buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.language().unwrap().name(), "Rust".into());
+    });
+}
+
 #[gpui::test(iterations = 3)]
 async fn test_transforming_diagnostics(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -21,8 +21,8 @@ use gpui::{
     AnyElement, App, AssetSource, AsyncWindowContext, Bounds, ClipboardItem, Context, DismissEvent,
     Div, DragMoveEvent, Entity, EventEmitter, ExternalPaths, FocusHandle, Focusable, Hsla,
     InteractiveElement, KeyContext, ListHorizontalSizingBehavior, ListSizingBehavior, MouseButton,
-    MouseDownEvent, ParentElement, Pixels, Point, PromptLevel, Render, ScrollStrategy, Stateful,
-    Styled, Subscription, Task, UniformListScrollHandle, WeakEntity, Window,
+    MouseDownEvent, ParentElement, Pixels, Point, PromptLevel, Render, ScrollStrategy, ShareItem,
+    Stateful, Styled, Subscription, Task, UniformListScrollHandle, WeakEntity, Window,
 };
 use indexmap::IndexMap;
 use language::DiagnosticSeverity;
@@ -192,6 +192,8 @@ actions!(
         RevealInFileManager,
         RemoveFromProject,
         OpenWithSystem,
+        ToggleFilePreview,
+        Share,
         Cut,
         Paste,
         Rename,
@@ -686,6 +688,7 @@ impl ProjectPanel {
             let is_read_only = project.is_read_only(cx);
             let is_remote = project.is_via_collab();
             let is_local = project.is_local();
+            let can_share = cx.can_share();
 
             let context_menu = ContextMenu::build(window, cx, |menu, _, _| {
                 menu.context(self.focus_handle.clone()).map(|menu| {
@@ -706,6 +709,9 @@ impl ProjectPanel {
                             .when(is_local, |menu| {
                                 menu.action("Open in Default App", Box::new(OpenWithSystem))
                             })
+                            .when(is_local && can_share, |menu| {
+                                menu.action("Share…", Box::new(Share))
+                            })
                             .action("Open in Terminal", Box::new(OpenInTerminal))
                             .when(is_dir, |menu| {
                                 menu.separator()
@@ -2179,6 +2185,25 @@ impl ProjectPanel {
         }
     }
 
+    fn share(&mut self, _: &Share, _: &mut Window, cx: &mut Context<Self>) {
+        let items = {
+            let project = self.project.read(cx);
+            self.effective_entries()
+                .into_iter()
+                .filter_map(|entry| {
+                    let entry_path = project.path_for_entry(entry.entry_id, cx)?.path;
+                    let abs_path = project
+                        .worktree_for_id(entry.worktree_id, cx)?
+                        .read(cx)
+                        .abs_path()
+                        .join(entry_path);
+                    Some(ShareItem::File(abs_path))
+                })
+                .collect::<Vec<_>>()
+        };
+        cx.share(items);
+    }
+
     fn copy_relative_path(&mut self, _: &CopyRelativePath, _: &mut Window, cx: &mut Context<Self>) {
         let file_paths = {
             let project = self.project.read(cx);
@@ -2224,6 +2249,19 @@ impl ProjectPanel {
         }
     }
 
+    fn toggle_file_preview(
+        &mut self,
+        _: &ToggleFilePreview,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if window.is_file_preview_visible() {
+            window.hide_file_preview();
+        } else if let Some((worktree, entry)) = self.selected_sub_entry(cx) {
+            window.show_file_preview(vec![worktree.read(cx).abs_path().join(&entry.path)]);
+        }
+    }
+
     fn open_system(&mut self, _: &OpenWithSystem, _: &mut Window, cx: &mut Context<Self>) {
         if let Some((worktree, entry)) = self.selected_entry(cx) {
             let abs_path = worktree.abs_path().join(&entry.path);
@@ -4465,6 +4503,8 @@ impl Render for ProjectPanel {
                 .when(project.is_local(), |el| {
                     el.on_action(cx.listener(Self::reveal_in_finder))
                         .on_action(cx.listener(Self::open_system))
+                        .on_action(cx.listener(Self::toggle_file_preview))
+                        .on_action(cx.listener(Self::share))
                         .on_action(cx.listener(Self::open_in_terminal))
                 })
                 .when(project.is_via_ssh(), |el| {
@@ -11,7 +11,7 @@ mod session;
 use std::{sync::Arc, time::Duration};
 
 use async_dispatcher::{set_dispatcher, Dispatcher, Runnable};
-use gpui::{App, PlatformDispatcher};
+use gpui::{App, PlatformDispatcher, Priority};
 use project::Fs;
 pub use runtimelib::ExecutionState;
 use settings::Settings as _;
@@ -46,7 +46,7 @@ fn zed_dispatcher(cx: &mut App) -> impl Dispatcher {
     // other crates in Zed.
     impl Dispatcher for ZedDispatcher {
         fn dispatch(&self, runnable: Runnable) {
-            self.dispatcher.dispatch(runnable, None)
+            self.dispatcher.dispatch(runnable, None, Priority::Default)
         }
 
         fn dispatch_after(&self, duration: Duration, runnable: Runnable) {
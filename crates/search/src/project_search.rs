@@ -257,8 +257,12 @@ impl ProjectSearch {
         self.search_id += 1;
         self.active_query = Some(query);
         self.match_ranges.clear();
+        // Cancelling the search from the activity indicator stops it like reaching the end of
+        // the results does, keeping the matches found so far.
+        let progress = cx.start_progress("Searching project", true);
+        let cancelled = progress.cancellation_token().cancelled();
         self.pending_search = Some(cx.spawn(|this, mut cx| async move {
-            let mut matches = pin!(search.ready_chunks(1024));
+            let mut matches = pin!(search.ready_chunks(1024).take_until(cancelled));
             let this = this.upgrade()?;
             this.update(&mut cx, |this, cx| {
                 this.match_ranges.clear();
@@ -297,6 +301,7 @@ impl ProjectSearch {
 
                 this.update(&mut cx, |this, cx| {
                     this.match_ranges.extend(match_ranges);
+                    progress.set_message(format!("{} matches", this.match_ranges.len()));
                     cx.notify();
                 })
                 .ok()?;
@@ -199,6 +199,7 @@ impl KeymapFile {
         // errors in context and binding parsing.
         let mut errors = Vec::new();
         let mut key_bindings = Vec::new();
+        let mut binding_locator = BindingLocator::new(content);
 
         for KeymapSection {
             context,
@@ -243,6 +244,7 @@ impl KeymapFile {
 
             if let Some(bindings) = bindings {
                 for (keystrokes, action) in bindings {
+                    let line = binding_locator.line_of(keystrokes);
                     let result = Self::load_keybinding(
                         keystrokes,
                         action,
@@ -257,10 +259,14 @@ impl KeymapFile {
                         Err(err) => {
                             write!(
                                 section_errors,
-                                "\n\n - In binding {}, {err}",
+                                "\n\n - In binding {}",
                                 inline_code_string(keystrokes),
                             )
                             .unwrap();
+                            if let Some(line) = line {
+                                write!(section_errors, " on line {line}").unwrap();
+                            }
+                            write!(section_errors, ", {err}").unwrap();
                         }
                     }
                 }
@@ -612,6 +618,44 @@ impl KeymapFile {
     }
 }
 
+/// Finds the lines that bindings are defined on, since the keymap is deserialized without position
+/// information. Bindings are looked up in the order they appear, so that the same keystrokes in
+/// different sections are told apart.
+struct BindingLocator<'a> {
+    content: &'a str,
+    offset: usize,
+    /// The 1-based line that `offset` is on.
+    line: usize,
+}
+
+impl<'a> BindingLocator<'a> {
+    fn new(content: &'a str) -> Self {
+        Self {
+            content,
+            offset: 0,
+            line: 1,
+        }
+    }
+
+    /// Returns the 1-based line of the next binding for the given keystrokes.
+    fn line_of(&mut self, keystrokes: &str) -> Option<usize> {
+        let key = serde_json::to_string(keystrokes).ok()?;
+        let mut search_start = self.offset;
+        loop {
+            let ix = search_start + self.content[search_start..].find(&key)?;
+            let end = ix + key.len();
+            if self.content[end..].trim_start().starts_with(':') {
+                // Only count the lines since the previous binding, so that locating every binding
+                // scans the keymap once.
+                self.line += self.content[self.offset..ix].matches('\n').count();
+                self.offset = end;
+                return Some(self.line);
+            }
+            search_start = end;
+        }
+    }
+}
+
 // Double quotes a string and wraps it in backticks for markdown inline code..
 fn inline_code_string(text: &str) -> MarkdownString {
     MarkdownString::inline_code(&format!("\"{}\"", text))
@@ -619,7 +663,7 @@ fn inline_code_string(text: &str) -> MarkdownString {
 
 #[cfg(test)]
 mod tests {
-    use super::KeymapFile;
+    use super::{KeymapFile, KeymapFileLoadResult};
 
     #[test]
     fn can_deserialize_keymap_with_trailing_comma() {
@@ -635,4 +679,31 @@ mod tests {
         };
         KeymapFile::parse(json).unwrap();
     }
+
+    #[gpui::test]
+    fn reports_lines_of_invalid_bindings(cx: &mut gpui::App) {
+        let json = indoc::indoc! {r#"
+            [
+              {
+                "bindings": {
+                  "up": "test_keymap::Missing"
+                }
+              },
+              {
+                "context": "Editor",
+                "bindings": {
+                  "down": null,
+                  "up": ["test_keymap::Missing", {}]
+                }
+              }
+            ]
+        "#};
+        let KeymapFileLoadResult::SomeFailedToLoad { error_message, .. } =
+            KeymapFile::load(json, cx)
+        else {
+            panic!("expected the keymap to fail to load");
+        };
+        assert!(error_message.0.contains("In binding `\"up\"` on line 4,"));
+        assert!(error_message.0.contains("In binding `\"up\"` on line 11,"));
+    }
 }
@@ -14,11 +14,24 @@ pub struct TabStop {
     pub choices: Option<Vec<String>>,
 }
 
+/// Resolves a snippet variable, such as `TM_FILENAME`, to its value.
+type Variables<'a> = &'a dyn Fn(&str) -> Option<String>;
+
 impl Snippet {
     pub fn parse(source: &str) -> Result<Self> {
+        Self::parse_with_variables(source, |_| None)
+    }
+
+    /// Parses a snippet, replacing variables like `$TM_FILENAME` with the values returned by
+    /// `variables`. Variables without a value are replaced by their default, as in
+    /// `${TM_FILENAME:untitled}`, or otherwise by their name.
+    pub fn parse_with_variables(
+        source: &str,
+        variables: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
         let mut text = String::with_capacity(source.len());
         let mut tabstops = BTreeMap::new();
-        parse_snippet(source, false, &mut text, &mut tabstops)
+        parse_snippet(source, false, &mut text, &mut tabstops, &variables)
             .context("failed to parse snippet")?;
 
         let len = text.len() as isize;
@@ -47,12 +60,22 @@ fn parse_snippet<'a>(
     nested: bool,
     text: &mut String,
     tabstops: &mut BTreeMap<usize, TabStop>,
+    variables: Variables,
 ) -> Result<&'a str> {
     loop {
         match source.chars().next() {
             None => return Ok(""),
             Some('$') => {
-                source = parse_tabstop(&source[1..], text, tabstops)?;
+                let rest = &source[1..];
+                source = if rest
+                    .strip_prefix('{')
+                    .unwrap_or(rest)
+                    .starts_with(|c: char| c == '_' || c.is_ascii_alphabetic())
+                {
+                    parse_variable(rest, text, tabstops, variables)?
+                } else {
+                    parse_tabstop(rest, text, tabstops, variables)?
+                };
             }
             Some('\\') => {
                 // As specified in the LSP spec (`Grammar` section),
@@ -93,6 +116,7 @@ fn parse_tabstop<'a>(
     mut source: &'a str,
     text: &mut String,
     tabstops: &mut BTreeMap<usize, TabStop>,
+    variables: Variables,
 ) -> Result<&'a str> {
     let tabstop_start = text.len();
     let tabstop_index;
@@ -108,7 +132,7 @@ fn parse_tabstop<'a>(
         }
 
         if source.starts_with(':') {
-            source = parse_snippet(&source[1..], true, text, tabstops)?;
+            source = parse_snippet(&source[1..], true, text, tabstops, variables)?;
         }
 
         if source.starts_with('}') {
@@ -133,6 +157,52 @@ fn parse_tabstop<'a>(
     Ok(source)
 }
 
+fn parse_variable<'a>(
+    mut source: &'a str,
+    text: &mut String,
+    tabstops: &mut BTreeMap<usize, TabStop>,
+    variables: Variables,
+) -> Result<&'a str> {
+    let braced = source.starts_with('{');
+    if braced {
+        source = &source[1..];
+    }
+    let name_len = source
+        .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+        .unwrap_or(source.len());
+    let (name, rest) = source.split_at(name_len);
+    source = rest;
+    let value = variables(name);
+
+    if braced && source.starts_with(':') {
+        match value.filter(|value| !value.is_empty()) {
+            Some(value) => {
+                // The default still has to be parsed to find where it ends.
+                source = parse_snippet(
+                    &source[1..],
+                    true,
+                    &mut String::new(),
+                    &mut BTreeMap::new(),
+                    variables,
+                )?;
+                text.push_str(&value);
+            }
+            None => source = parse_snippet(&source[1..], true, text, tabstops, variables)?,
+        }
+    } else {
+        text.push_str(value.as_deref().unwrap_or(name));
+    }
+
+    if braced {
+        if source.starts_with('}') {
+            source = &source[1..];
+        } else {
+            return Err(anyhow!("expected a closing brace"));
+        }
+    }
+    Ok(source)
+}
+
 fn parse_int(source: &str) -> Result<(usize, &str)> {
     let len = source
         .find(|c: char| !c.is_ascii_digit())
@@ -327,6 +397,31 @@ mod tests {
         assert_eq!(tabstops(&snippet), &[vec![4..4], vec![7..7]]);
     }
 
+    #[test]
+    fn test_snippet_with_variables() {
+        let variables = |name: &str| match name {
+            "TM_FILENAME" => Some("main.rs".to_string()),
+            "TM_SELECTED_TEXT" => Some(String::new()),
+            _ => None,
+        };
+
+        let snippet =
+            Snippet::parse_with_variables("// $TM_FILENAME: ${1:$UNKNOWN}", variables).unwrap();
+        assert_eq!(snippet.text, "// main.rs: UNKNOWN");
+        assert_eq!(tabstops(&snippet), &[vec![12..19], vec![19..19]]);
+
+        // Defaults are used when a variable is empty or unknown, and can contain tabstops.
+        let snippet = Snippet::parse_with_variables(
+            "${TM_FILENAME:x}(${TM_SELECTED_TEXT:${1:arg}}) ${UNKNOWN}",
+            variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "main.rs(arg) UNKNOWN");
+        assert_eq!(tabstops(&snippet), &[vec![8..11], vec![20..20]]);
+
+        assert!(Snippet::parse_with_variables("${TM_FILENAME", variables).is_err());
+    }
+
     fn tabstops(snippet: &Snippet) -> Vec<Vec<Range<isize>>> {
         snippet.tabstops.iter().map(|t| t.ranges.to_vec()).collect()
     }
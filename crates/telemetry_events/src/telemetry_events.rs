@@ -286,6 +286,19 @@ pub struct Panic {
     pub installation_id: Option<String>,
     /// Identifier unique to each Zed session (differs for each time you open Zed)
     pub session_id: String,
+    /// What the user was doing in the window they last interacted with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ui_state: Option<PanicUiState>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PanicUiState {
+    /// The actions most recently dispatched, oldest first
+    pub actions: Vec<String>,
+    /// The input events most recently received, oldest first
+    pub input_events: Vec<String>,
+    /// The views containing the focused element, from the root view inwards
+    pub focused_views: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize)]
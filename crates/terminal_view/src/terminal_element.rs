@@ -5,7 +5,8 @@ use gpui::{
     GlobalElementId, HighlightStyle, Hitbox, Hsla, InputHandler, InteractiveElement, Interactivity,
     IntoElement, LayoutId, ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels, Point,
     ShapedLine, StatefulInteractiveElement, StrikethroughStyle, Styled, TextRun, TextStyle,
-    UTF16Selection, UnderlineStyle, WeakEntity, WhiteSpace, Window, WindowTextSystem,
+    UTF16Selection, UnderlineKind, UnderlineStyle, WeakEntity, WhiteSpace, Window,
+    WindowTextSystem,
 };
 use itertools::Itertools;
 use language::CursorShape;
@@ -361,7 +362,17 @@ impl TerminalElement {
         .then(|| UnderlineStyle {
             color: Some(fg),
             thickness: Pixels::from(1.0),
-            wavy: flags.contains(Flags::UNDERCURL),
+            kind: if flags.contains(Flags::UNDERCURL) {
+                UnderlineKind::Wavy
+            } else if flags.contains(Flags::DOUBLE_UNDERLINE) {
+                UnderlineKind::Double
+            } else if flags.contains(Flags::DOTTED_UNDERLINE) {
+                UnderlineKind::Dotted
+            } else if flags.contains(Flags::DASHED_UNDERLINE) {
+                UnderlineKind::Dashed
+            } else {
+                UnderlineKind::Solid
+            },
         });
 
         let strikethrough = flags
@@ -658,7 +669,7 @@ impl Element for TerminalElement {
                     underline: Some(UnderlineStyle {
                         thickness: px(1.0),
                         color: Some(theme.colors().link_text_hover),
-                        wavy: false,
+                        kind: UnderlineKind::Solid,
                     }),
                     strikethrough: None,
                     fade_out: None,
@@ -42,6 +42,7 @@ pub enum WindowBackgroundContent {
     Opaque,
     Transparent,
     Blurred,
+    Vibrant,
 }
 
 impl From<WindowBackgroundContent> for WindowBackgroundAppearance {
@@ -50,6 +51,7 @@ impl From<WindowBackgroundContent> for WindowBackgroundAppearance {
             WindowBackgroundContent::Opaque => WindowBackgroundAppearance::Opaque,
             WindowBackgroundContent::Transparent => WindowBackgroundAppearance::Transparent,
             WindowBackgroundContent::Blurred => WindowBackgroundAppearance::Blurred,
+            WindowBackgroundContent::Vibrant => WindowBackgroundAppearance::Vibrant,
         }
     }
 }
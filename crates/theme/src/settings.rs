@@ -161,6 +161,8 @@ impl ThemeSettings {
 
             if let Some(_theme) = theme_settings.switch_theme(theme_name, cx) {
                 ThemeSettings::override_global(theme_settings, cx);
+                // Views may be cached, so redraw every window to pick up the new colors.
+                cx.refresh_windows();
             }
         }
     }
@@ -9,7 +9,7 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 
 use smallvec::SmallVec;
-use ui::{prelude::*, ContextMenu, PopoverMenu, PopoverMenuHandle, Tooltip};
+use ui::{prelude::*, ContextMenu, ContextMenuEntry, PopoverMenu, PopoverMenuHandle, Tooltip};
 
 #[cfg(not(target_os = "macos"))]
 impl_actions!(app_menu, [OpenApplicationMenu]);
@@ -87,6 +87,31 @@ impl ApplicationMenu {
         cleaned
     }
 
+    fn menu_action(
+        menu: ContextMenu,
+        name: String,
+        action: Box<dyn gpui::Action>,
+        checked: bool,
+        disabled: bool,
+    ) -> ContextMenu {
+        if checked {
+            let handler_action = action.boxed_clone();
+            menu.item(
+                ContextMenuEntry::new(name)
+                    .toggleable(IconPosition::Start, true)
+                    .action(Some(action))
+                    .disabled(disabled)
+                    .handler(move |window, cx| {
+                        window.dispatch_action(handler_action.boxed_clone(), cx)
+                    }),
+            )
+        } else if disabled {
+            menu.disabled_action(name, action)
+        } else {
+            menu.action(name, action)
+        }
+    }
+
     fn build_menu_from_items(
         entry: MenuEntry,
         window: &mut Window,
@@ -101,16 +126,26 @@ impl ApplicationMenu {
                 .into_iter()
                 .fold(menu, |menu, item| match item {
                     OwnedMenuItem::Separator => menu.separator(),
-                    OwnedMenuItem::Action { name, action, .. } => menu.action(name, action),
+                    OwnedMenuItem::Action {
+                        name,
+                        action,
+                        checked,
+                        disabled,
+                        ..
+                    } => Self::menu_action(menu, name, action, checked, disabled),
                     OwnedMenuItem::Submenu(submenu) => {
                         submenu
                             .items
                             .into_iter()
                             .fold(menu, |menu, item| match item {
                                 OwnedMenuItem::Separator => menu.separator(),
-                                OwnedMenuItem::Action { name, action, .. } => {
-                                    menu.action(name, action)
-                                }
+                                OwnedMenuItem::Action {
+                                    name,
+                                    action,
+                                    checked,
+                                    disabled,
+                                    ..
+                                } => Self::menu_action(menu, name, action, checked, disabled),
                                 OwnedMenuItem::Submenu(_) => menu,
                             })
                     }
@@ -122,7 +122,6 @@ pub struct TitleBar {
     user_store: Entity<UserStore>,
     client: Arc<Client>,
     workspace: WeakEntity<Workspace>,
-    should_move: bool,
     application_menu: Option<Entity<ApplicationMenu>>,
     _subscriptions: Vec<Subscription>,
     zed_predict_banner: Entity<ZedPredictBanner>,
@@ -135,7 +134,7 @@ impl Render for TitleBar {
         let supported_controls = window.window_controls();
         let decorations = window.window_decorations();
         let titlebar_color = if cfg!(any(target_os = "linux", target_os = "freebsd")) {
-            if window.is_window_active() && !self.should_move {
+            if window.is_window_active() {
                 cx.theme().colors().title_bar_background
             } else {
                 cx.theme().colors().title_bar_inactive_background
@@ -173,6 +172,10 @@ impl Render for TitleBar {
             })
             .bg(titlebar_color)
             .content_stretch()
+            // Note: On Windows the title bar behavior is handled by the platform implementation.
+            .when(self.platform_style != PlatformStyle::Windows, |this| {
+                this.window_drag_region()
+            })
             .child(
                 div()
                     .id("titlebar-content")
@@ -181,14 +184,6 @@ impl Render for TitleBar {
                     .items_center()
                     .justify_between()
                     .w_full()
-                    // Note: On Windows the title bar behavior is handled by the platform implementation.
-                    .when(self.platform_style != PlatformStyle::Windows, |this| {
-                        this.on_click(|event, window, _| {
-                            if event.up.click_count == 2 {
-                                window.zoom_window();
-                            }
-                        })
-                    })
                     .child(
                         h_flex()
                             .gap_1()
@@ -242,27 +237,6 @@ impl Render for TitleBar {
                                         move |ev, window, _| window.show_window_menu(ev.position),
                                     )
                                 })
-                                .on_mouse_move(cx.listener(move |this, _ev, window, _| {
-                                    if this.should_move {
-                                        this.should_move = false;
-                                        window.start_window_move();
-                                    }
-                                }))
-                                .on_mouse_down_out(cx.listener(move |this, _ev, _window, _cx| {
-                                    this.should_move = false;
-                                }))
-                                .on_mouse_up(
-                                    gpui::MouseButton::Left,
-                                    cx.listener(move |this, _ev, _window, _cx| {
-                                        this.should_move = false;
-                                    }),
-                                )
-                                .on_mouse_down(
-                                    gpui::MouseButton::Left,
-                                    cx.listener(move |this, _ev, _window, _cx| {
-                                        this.should_move = true;
-                                    }),
-                                )
                         } else {
                             title_bar
                         }
@@ -320,7 +294,6 @@ impl TitleBar {
             children: SmallVec::new(),
             application_menu,
             workspace: workspace.weak_handle(),
-            should_move: false,
             project,
             user_store,
             client,
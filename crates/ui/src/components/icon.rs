@@ -159,6 +159,7 @@ pub enum IconName {
     Book,
     BookCopy,
     BookPlus,
+    Bookmark,
     CaseSensitive,
     Check,
     ChevronDown,
@@ -1,6 +1,8 @@
 #![allow(missing_docs)]
 
-use gpui::{relative, AnyElement, FontWeight, StyleRefinement, Styled, UnderlineStyle};
+use gpui::{
+    relative, AnyElement, FontWeight, StyleRefinement, Styled, UnderlineKind, UnderlineStyle,
+};
 use settings::Settings;
 use smallvec::SmallVec;
 use theme::ThemeSettings;
@@ -191,7 +193,7 @@ impl RenderOnce for LabelLike {
                     .underline = Some(UnderlineStyle {
                     thickness: px(1.),
                     color: None,
-                    wavy: false,
+                    kind: UnderlineKind::Solid,
                 });
                 this
             })
@@ -66,7 +66,7 @@ use remote::{ssh_session::ConnectionIdentifier, SshClientDelegate, SshConnection
 use schemars::JsonSchema;
 use serde::Deserialize;
 use session::AppSession;
-use settings::Settings;
+use settings::{Settings, SettingsStore};
 use shared_screen::SharedScreen;
 use sqlez::{
     bindable::{Bind, Column, StaticColumnCount},
@@ -393,6 +393,7 @@ pub fn init(app_state: Arc<AppState>, cx: &mut App) {
                         files: true,
                         directories: true,
                         multiple: true,
+                        filters: Vec::new(),
                     },
                     cx,
                 );
@@ -410,6 +411,7 @@ pub fn init(app_state: Arc<AppState>, cx: &mut App) {
                         files: true,
                         directories,
                         multiple: true,
+                        filters: Vec::new(),
                     },
                     cx,
                 );
@@ -652,7 +654,6 @@ impl AppState {
     pub fn test(cx: &mut App) -> Arc<Self> {
         use node_runtime::NodeRuntime;
         use session::Session;
-        use settings::SettingsStore;
 
         if !cx.has_global::<SettingsStore>() {
             let settings_store = SettingsStore::test(cx);
@@ -1081,6 +1082,12 @@ impl Workspace {
 
                 ThemeSettings::reload_current_theme(cx);
             }),
+            cx.observe_global_in::<SettingsStore>(window, |_, window, cx| {
+                let max_frame_rate = WorkspaceSettings::get_global(cx).max_frame_rate;
+                if window.max_frame_rate() != max_frame_rate {
+                    window.set_max_frame_rate(max_frame_rate);
+                }
+            }),
             cx.on_release(move |this, cx| {
                 this.app_state.workspace_store.update(cx, move |store, _| {
                     store.workspaces.remove(&window_handle.clone());
@@ -1088,6 +1095,8 @@ impl Workspace {
             }),
         ];
 
+        window.set_max_frame_rate(WorkspaceSettings::get_global(cx).max_frame_rate);
+
         cx.defer_in(window, |this, window, cx| {
             this.update_window_title(window, cx);
             this.show_initial_notifications(cx);
@@ -1254,7 +1263,21 @@ impl Workspace {
                         });
 
                     if let Some((serialized_display, serialized_status)) = restorable_bounds {
-                        (Some(serialized_status.0), Some(serialized_display))
+                        // Keep the window on screen if its display was disconnected or rearranged.
+                        let (window_bounds, display) = cx.update(|cx| {
+                            match cx.restore_window_bounds(
+                                Some(serialized_display),
+                                serialized_status.0,
+                            ) {
+                                Some((display_id, window_bounds)) => (
+                                    window_bounds,
+                                    cx.find_display(display_id)
+                                        .and_then(|display| display.uuid().ok()),
+                                ),
+                                None => (serialized_status.0, Some(serialized_display)),
+                            }
+                        })?;
+                        (Some(window_bounds), display)
                     } else {
                         (None, None)
                     }
@@ -1619,6 +1642,7 @@ impl Workspace {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> oneshot::Receiver<Option<Vec<PathBuf>>> {
+        // The in-app prompt lists every file, as it doesn't apply the options' filters.
         if !lister.is_local(cx) || !WorkspaceSettings::get_global(cx).use_system_path_prompts {
             let prompt = self.on_prompt_for_open_path.take().unwrap();
             let rx = prompt(self, lister, window, cx);
@@ -2244,6 +2268,7 @@ impl Workspace {
                 files: false,
                 directories: true,
                 multiple: true,
+                filters: Vec::new(),
             },
             DirectoryLister::Project(self.project.clone()),
             window,
@@ -23,6 +23,7 @@ pub struct WorkspaceSettings {
     pub command_aliases: HashMap<String, String>,
     pub show_user_picture: bool,
     pub max_tabs: Option<NonZeroUsize>,
+    pub max_frame_rate: Option<f32>,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema)]
@@ -141,6 +142,11 @@ pub struct WorkspaceSettingsContent {
     //
     // Default: none
     pub max_tabs: Option<NonZeroUsize>,
+    /// The maximum number of frames per second to render, or null to render at the
+    /// display's refresh rate.
+    ///
+    /// Default: null
+    pub max_frame_rate: Option<f32>,
 }
 
 #[derive(Deserialize)]
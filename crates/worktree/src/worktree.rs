@@ -106,7 +106,9 @@ pub enum CreatedEntry {
 
 pub struct LoadedFile {
     pub file: Arc<File>,
-    pub text: String,
+    /// The file's text, with its line endings normalized to `\n`.
+    pub text: Rope,
+    pub line_ending: LineEnding,
 }
 
 pub struct LoadedBinaryFile {
@@ -1662,7 +1664,7 @@ impl LocalWorktree {
 
         cx.spawn(|this, _cx| async move {
             let abs_path = abs_path?;
-            let text = fs.load(&abs_path).await?;
+            let (text, line_ending) = fs.load_rope(&abs_path).await?;
 
             let worktree = this
                 .upgrade()
@@ -1692,7 +1694,11 @@ impl LocalWorktree {
                 }
             };
 
-            Ok(LoadedFile { file, text })
+            Ok(LoadedFile {
+                file,
+                text,
+                line_ending,
+            })
         })
     }
 
@@ -18,7 +18,9 @@ use extension::ExtensionHostProxy;
 use fs::{Fs, RealFs};
 use futures::{future, StreamExt};
 use git::GitHostingProviderRegistry;
-use gpui::{App, AppContext as _, Application, AsyncApp, UpdateGlobal as _};
+use gpui::{
+    App, AppContext as _, Application, AssetKind, AsyncApp, MessageCatalog, UpdateGlobal as _,
+};
 
 use gpui_tokio::Tokio;
 use http_client::{read_proxy_from_env, Uri};
@@ -199,26 +201,29 @@ fn main() {
         session_id.clone(),
     );
 
+    let args = Args::parse();
     let (open_listener, mut open_rx) = OpenListener::new();
 
     let failed_single_instance_check =
         if *db::ZED_STATELESS || *release_channel::RELEASE_CHANNEL == ReleaseChannel::Dev {
             false
         } else {
-            #[cfg(any(target_os = "linux", target_os = "freebsd"))]
-            {
-                crate::zed::listen_for_cli_connections(open_listener.clone()).is_err()
-            }
+            let forwarded_urls = forwarded_url_args(&args.paths_or_urls);
 
-            #[cfg(target_os = "windows")]
+            #[cfg(any(target_os = "linux", target_os = "freebsd"))]
             {
-                !crate::zed::windows_only_instance::check_single_instance()
+                let is_only_instance =
+                    crate::zed::listen_for_cli_connections(open_listener.clone()).is_ok();
+                if !is_only_instance {
+                    crate::zed::forward_urls_to_running_instance(&forwarded_urls).log_err();
+                }
+                !is_only_instance
             }
 
-            #[cfg(target_os = "macos")]
+            #[cfg(any(target_os = "macos", target_os = "windows"))]
             {
-                use zed::mac_only_instance::*;
-                ensure_only_instance() != IsOnlyInstance::Yes
+                use zed::only_instance::*;
+                ensure_only_instance(open_listener.clone(), &forwarded_urls) != IsOnlyInstance::Yes
             }
         };
     if failed_single_instance_check {
@@ -469,6 +474,8 @@ fn main() {
         recent_projects::init(cx);
 
         load_embedded_fonts(cx);
+        load_locales(cx);
+        cx.set_persistence_path(paths::persisted_state_file()).detach();
 
         #[cfg(any(target_os = "linux", target_os = "freebsd"))]
         crate::zed::linux_prompts::init(cx);
@@ -552,10 +559,13 @@ fn main() {
         let fs = app_state.fs.clone();
         load_user_themes_in_background(fs.clone(), cx);
         watch_themes(fs.clone(), cx);
+        watch_bundled_themes(fs.clone(), cx);
+        watch_assets(fs.clone(), cx);
         watch_languages(fs.clone(), app_state.languages.clone(), cx);
         watch_file_types(fs.clone(), cx);
 
         cx.set_menus(app_menus());
+        cx.observe_locale(|cx| cx.set_menus(app_menus())).detach();
         initialize_workspace(app_state.clone(), prompt_builder, cx);
 
         cx.activate(true);
@@ -566,7 +576,6 @@ fn main() {
         })
         .detach_and_log_err(cx);
 
-        let args = Args::parse();
         let urls: Vec<_> = args
             .paths_or_urls
             .iter()
@@ -1037,18 +1046,25 @@ fn parse_url_arg(arg: &str, cx: &App) -> Result<String> {
     }
 }
 
+/// Resolves path arguments for forwarding to an instance that's already running, before the app
+/// has started. Other arguments are forwarded as-is, and validated by the running instance.
+fn forwarded_url_args(args: &[String]) -> Vec<String> {
+    args.iter()
+        .map(|arg| match std::fs::canonicalize(Path::new(arg)) {
+            Ok(path) => format!("file://{}", path.display()),
+            Err(_) => arg.clone(),
+        })
+        .collect()
+}
+
 fn load_embedded_fonts(cx: &App) {
     let asset_source = cx.asset_source();
-    let font_paths = asset_source.list("fonts").unwrap();
+    let font_paths = cx.asset_paths("fonts", AssetKind::Font).unwrap();
     let embedded_fonts = Mutex::new(Vec::new());
     let executor = cx.background_executor();
 
     executor.block(executor.scoped(|scope| {
         for font_path in &font_paths {
-            if !font_path.ends_with(".ttf") {
-                continue;
-            }
-
             scope.spawn(async {
                 let font_bytes = asset_source.load(font_path).unwrap().unwrap();
                 embedded_fonts.lock().push(font_bytes);
@@ -1061,6 +1077,35 @@ fn load_embedded_fonts(cx: &App) {
         .unwrap();
 }
 
+/// Loads the message catalogs bundled in the locales directory, and switches to the system locale.
+/// Only the application menus are translated so far; the rest of the UI is shown in English.
+fn load_locales(cx: &mut App) {
+    let asset_source = cx.asset_source().clone();
+    let locale_paths = asset_source.list("locales").unwrap_or_default();
+    for locale_path in &locale_paths {
+        let Some(locale) = locale_path
+            .strip_prefix("locales/")
+            .and_then(|file_name| file_name.strip_suffix(".json"))
+        else {
+            continue;
+        };
+        let catalog = asset_source
+            .load(locale_path)
+            .log_err()
+            .flatten()
+            .and_then(|bytes| {
+                MessageCatalog::from_json(&String::from_utf8_lossy(&bytes)).log_err()
+            });
+        if let Some(catalog) = catalog {
+            cx.add_message_catalog(locale.to_string(), catalog);
+        }
+    }
+
+    if let Some(locale) = sys_locale::get_locale() {
+        cx.set_locale(locale);
+    }
+}
+
 /// Spawns a background task to load the user themes from the themes directory.
 fn load_user_themes_in_background(fs: Arc<dyn fs::Fs>, cx: &mut App) {
     cx.spawn({
@@ -1124,6 +1169,74 @@ fn watch_themes(fs: Arc<dyn fs::Fs>, cx: &mut App) {
     .detach()
 }
 
+/// Reloads the bundled themes when they're edited in development, so changes to their
+/// definitions can be seen without restarting.
+#[cfg(debug_assertions)]
+fn watch_bundled_themes(fs: Arc<dyn fs::Fs>, cx: &mut App) {
+    use std::time::Duration;
+
+    let path = {
+        let p = Path::new("assets/themes");
+        let Ok(full_path) = p.canonicalize() else {
+            return;
+        };
+        full_path
+    };
+
+    cx.spawn(|cx| async move {
+        let (mut events, _) = fs.watch(path.as_path(), Duration::from_millis(100)).await;
+        while let Some(event) = events.next().await {
+            let has_theme_file = event.iter().any(|event| {
+                event
+                    .path
+                    .extension()
+                    .map(|ext| ext.to_string_lossy().as_ref() == "json")
+                    .unwrap_or(false)
+            });
+            if has_theme_file {
+                cx.update(|cx| {
+                    ThemeRegistry::global(cx).load_bundled_themes();
+                    ThemeSettings::reload_current_theme(cx);
+                })
+                .log_err();
+            }
+        }
+    })
+    .detach()
+}
+
+#[cfg(not(debug_assertions))]
+fn watch_bundled_themes(_fs: Arc<dyn fs::Fs>, _cx: &mut App) {}
+
+/// In development builds, bundled assets are read from the assets directory, so icons, images
+/// and sounds that change there can be reloaded without restarting.
+#[cfg(debug_assertions)]
+fn watch_assets(fs: Arc<dyn fs::Fs>, cx: &mut App) {
+    use std::time::Duration;
+
+    let Ok(path) = Path::new("assets").canonicalize() else {
+        return;
+    };
+
+    cx.spawn(|cx| async move {
+        let (mut events, _) = fs.watch(path.as_path(), Duration::from_millis(100)).await;
+        while let Some(event) = events.next().await {
+            let changed_assets = event
+                .iter()
+                .filter_map(|event| {
+                    let relative_path = event.path.strip_prefix(&path).ok()?;
+                    Some(relative_path.to_string_lossy().replace('\\', "/").into())
+                })
+                .collect::<Vec<_>>();
+            cx.update(|cx| cx.reload_assets(changed_assets)).log_err();
+        }
+    })
+    .detach()
+}
+
+#[cfg(not(debug_assertions))]
+fn watch_assets(_fs: Arc<dyn fs::Fs>, _cx: &mut App) {}
+
 #[cfg(debug_assertions)]
 fn watch_languages(fs: Arc<dyn fs::Fs>, languages: Arc<LanguageRegistry>, cx: &mut App) {
     use std::time::Duration;
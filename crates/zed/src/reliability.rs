@@ -4,24 +4,26 @@ use backtrace::{self, Backtrace};
 use chrono::Utc;
 use client::{telemetry, TelemetrySettings};
 use db::kvp::KEY_VALUE_STORE;
-use gpui::{App, SemanticVersion};
+use gpui::{App, CrashContext, SemanticVersion};
 use http_client::{self, HttpClient, HttpClientWithUrl, HttpRequestExt, Method};
 use paths::{crashes_dir, crashes_retired_dir};
 use project::Project;
 use release_channel::{AppCommitSha, ReleaseChannel, RELEASE_CHANNEL};
-use settings::Settings;
+use settings::{Settings, SettingsStore};
 use smol::stream::StreamExt;
 use std::{
     env,
     ffi::{c_void, OsStr},
-    sync::{atomic::Ordering, Arc},
+    sync::{atomic::Ordering, Arc, OnceLock},
 };
 use std::{io::Write, panic, sync::atomic::AtomicU32, thread};
-use telemetry_events::{LocationData, Panic, PanicRequest};
+use telemetry_events::{LocationData, Panic, PanicRequest, PanicUiState};
 use url::Url;
 use util::ResultExt;
 
 static PANIC_COUNT: AtomicU32 = AtomicU32::new(0);
+/// Set once the app has started, so that panics can report what the user was doing.
+static CRASH_CONTEXT: OnceLock<CrashContext> = OnceLock::new();
 
 pub fn init_panic_hook(
     app_version: SemanticVersion,
@@ -125,6 +127,14 @@ pub fn init_panic_hook(
             system_id: system_id.clone(),
             installation_id: installation_id.clone(),
             session_id: session_id.clone(),
+            ui_state: CRASH_CONTEXT
+                .get()
+                .and_then(|crash_context| crash_context.report())
+                .map(|report| PanicUiState {
+                    actions: report.actions,
+                    input_events: report.input_events,
+                    focused_views: report.focused_views.into_iter().map(Into::into).collect(),
+                }),
         };
 
         if let Some(panic_data_json) = serde_json::to_string_pretty(&panic_data).log_err() {
@@ -180,6 +190,14 @@ pub fn init(
     #[cfg(target_os = "macos")]
     monitor_main_thread_hangs(http_client.clone(), installation_id.clone(), cx);
 
+    // The user's actions are only recorded for panic reports while they allow sending diagnostics.
+    CRASH_CONTEXT.set(cx.crash_context()).ok();
+    cx.set_crash_context_enabled(TelemetrySettings::get_global(cx).diagnostics);
+    cx.observe_global::<SettingsStore>(|cx| {
+        cx.set_crash_context_enabled(TelemetrySettings::get_global(cx).diagnostics);
+    })
+    .detach();
+
     let Some(panic_report_url) = http_client
         .build_zed_api_url("/telemetry/panics", &[])
         .log_err()
@@ -2,12 +2,10 @@ mod app_menus;
 pub mod inline_completion_registry;
 #[cfg(any(target_os = "linux", target_os = "freebsd"))]
 pub(crate) mod linux_prompts;
-#[cfg(target_os = "macos")]
-pub(crate) mod mac_only_instance;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) mod only_instance;
 mod open_listener;
 mod quick_action_bar;
-#[cfg(target_os = "windows")]
-pub(crate) mod windows_only_instance;
 
 use anyhow::Context as _;
 pub use app_menus::*;
@@ -94,7 +92,8 @@ pub fn init(cx: &mut App) {
     cx.on_action(|_: &HideOthers, cx| cx.hide_other_apps());
     #[cfg(target_os = "macos")]
     cx.on_action(|_: &ShowAll, cx| cx.unhide_other_apps());
-    cx.on_action(quit);
+    cx.on_action(|_: &Quit, cx| cx.request_quit().detach());
+    cx.on_should_quit(should_quit).detach();
 
     if ReleaseChannel::global(cx) == ReleaseChannel::Dev {
         cx.on_action(test_panic);
@@ -133,6 +132,10 @@ pub fn build_window_options(display_uuid: Option<Uuid>, cx: &mut App) -> WindowO
             width: px(360.0),
             height: px(240.0),
         }),
+        parent: None,
+        modal: false,
+        level: None,
+        hdr: false,
     }
 }
 
@@ -509,6 +512,7 @@ fn register_actions(
                     files: true,
                     directories: true,
                     multiple: true,
+                    filters: Vec::new(),
                 },
                 DirectoryLister::Project(workspace.project().clone()),
                 window,
@@ -946,13 +950,17 @@ fn install_cli(
 }
 
 static WAITING_QUIT_CONFIRMATION: AtomicBool = AtomicBool::new(false);
-fn quit(_: &Quit, cx: &mut App) {
+
+/// Asks the user to confirm quitting, if they've opted into that, and to save or discard any
+/// unsaved changes. Quitting is cancelled if they cancel either prompt.
+fn should_quit(cx: &mut App) -> Task<bool> {
+    // Another request is already prompting, and will quit once the user answers it.
     if WAITING_QUIT_CONFIRMATION.load(atomic::Ordering::Acquire) {
-        return;
+        return Task::ready(false);
     }
 
     let should_confirm = WorkspaceSettings::get_global(cx).confirm_quit;
-    cx.spawn(|mut cx| async move {
+    let should_quit = cx.spawn(|mut cx| async move {
         let mut workspace_windows = cx.update(|cx| {
             cx.windows()
                 .into_iter()
@@ -986,7 +994,7 @@ fn quit(_: &Quit, cx: &mut App) {
                     let answer = answer.await.ok();
                     WAITING_QUIT_CONFIRMATION.store(false, atomic::Ordering::Release);
                     if answer != Some(0) {
-                        return Ok(());
+                        return Ok(false);
                     }
                 }
             }
@@ -1001,14 +1009,13 @@ fn quit(_: &Quit, cx: &mut App) {
                 .log_err()
             {
                 if !should_close.await? {
-                    return Ok(());
+                    return Ok(false);
                 }
             }
         }
-        cx.update(|cx| cx.quit())?;
-        anyhow::Ok(())
-    })
-    .detach_and_log_err(cx);
+        anyhow::Ok(true)
+    });
+    cx.spawn(|_| async move { should_quit.await.log_err().unwrap_or(false) })
 }
 
 fn open_log_file(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
@@ -1312,28 +1319,42 @@ fn show_keymap_file_load_error(
 }
 
 fn reload_keymaps(cx: &mut App, user_key_bindings: Vec<KeyBinding>) {
-    cx.clear_key_bindings();
-    load_default_keymap(cx);
-    cx.bind_keys(user_key_bindings);
+    let mut key_bindings = default_key_bindings(cx);
+    key_bindings.extend(user_key_bindings);
+    let diff = cx.replace_key_bindings(key_bindings);
+    if !diff.is_empty() {
+        log::info!(
+            "reloaded keymap: {} bindings added, {} removed",
+            diff.added.len(),
+            diff.removed.len()
+        );
+    }
     cx.set_menus(app_menus());
     cx.set_dock_menu(vec![MenuItem::action("New Window", workspace::NewWindow)]);
 }
 
 pub fn load_default_keymap(cx: &mut App) {
+    let key_bindings = default_key_bindings(cx);
+    cx.bind_keys(key_bindings);
+}
+
+fn default_key_bindings(cx: &App) -> Vec<KeyBinding> {
     let base_keymap = *BaseKeymap::get_global(cx);
     if base_keymap == BaseKeymap::None {
-        return;
+        return Vec::new();
     }
 
-    cx.bind_keys(KeymapFile::load_asset(DEFAULT_KEYMAP_PATH, cx).unwrap());
+    let mut key_bindings = KeymapFile::load_asset(DEFAULT_KEYMAP_PATH, cx).unwrap();
 
     if let Some(asset_path) = base_keymap.asset_path() {
-        cx.bind_keys(KeymapFile::load_asset(asset_path, cx).unwrap());
+        key_bindings.extend(KeymapFile::load_asset(asset_path, cx).unwrap());
     }
 
     if VimModeSetting::get_global(cx).0 {
-        cx.bind_keys(KeymapFile::load_asset(VIM_KEYMAP_PATH, cx).unwrap());
+        key_bindings.extend(KeymapFile::load_asset(VIM_KEYMAP_PATH, cx).unwrap());
     }
+
+    key_bindings
 }
 
 pub fn handle_settings_changed(result: Result<serde_json::Value, anyhow::Error>, cx: &mut App) {
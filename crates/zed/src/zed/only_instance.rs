@@ -9,10 +9,13 @@ use sysinfo::System;
 
 use release_channel::ReleaseChannel;
 
+use super::OpenListener;
+
 const LOCALHOST: Ipv4Addr = Ipv4Addr::new(127, 0, 0, 1);
 const CONNECT_TIMEOUT: Duration = Duration::from_millis(10);
 const RECEIVE_TIMEOUT: Duration = Duration::from_millis(35);
 const SEND_TIMEOUT: Duration = Duration::from_millis(20);
+const FORWARD_TIMEOUT: Duration = Duration::from_millis(500);
 const USER_BLOCK: u16 = 100;
 
 fn address() -> SocketAddr {
@@ -86,8 +89,11 @@ pub enum IsOnlyInstance {
     No,
 }
 
-pub fn ensure_only_instance() -> IsOnlyInstance {
-    if check_got_handshake() {
+/// Checks whether another instance of this release channel is running. If one is, the given
+/// URLs are forwarded to it to open. Otherwise, this instance starts listening for URLs
+/// forwarded by instances launched later, and passes them to `opener`.
+pub fn ensure_only_instance(opener: OpenListener, urls: &[String]) -> IsOnlyInstance {
+    if forward_to_running_instance(urls) {
         return IsOnlyInstance::No;
     }
 
@@ -96,7 +102,7 @@ pub fn ensure_only_instance() -> IsOnlyInstance {
 
         Err(err) => {
             log::warn!("Error binding to single instance port: {err}");
-            if check_got_handshake() {
+            if forward_to_running_instance(urls) {
                 return IsOnlyInstance::No;
             }
 
@@ -116,15 +122,34 @@ pub fn ensure_only_instance() -> IsOnlyInstance {
             };
 
             _ = stream.set_nodelay(true);
-            _ = stream.set_read_timeout(Some(SEND_TIMEOUT));
-            _ = stream.write_all(instance_handshake().as_bytes());
+            _ = stream.set_read_timeout(Some(FORWARD_TIMEOUT));
+            if stream.write_all(instance_handshake().as_bytes()).is_err() {
+                continue;
+            }
+
+            // After the handshake, the new instance sends the URLs it was asked to open, one
+            // per line, and closes the connection.
+            let mut forwarded = String::new();
+            if let Err(err) = stream.read_to_string(&mut forwarded) {
+                log::warn!("Failed to read URLs forwarded by another instance: {err}");
+                continue;
+            }
+            let urls = forwarded
+                .lines()
+                .filter(|url| !url.is_empty())
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            if !urls.is_empty() {
+                opener.open_urls(urls);
+            }
         }
     });
 
     IsOnlyInstance::Yes
 }
 
-fn check_got_handshake() -> bool {
+/// Returns whether another instance is running, in which case the given URLs are sent to it.
+fn forward_to_running_instance(urls: &[String]) -> bool {
     match TcpStream::connect_timeout(&address(), CONNECT_TIMEOUT) {
         Ok(mut stream) => {
             let mut buf = vec![0u8; instance_handshake().len()];
@@ -135,13 +160,17 @@ fn check_got_handshake() -> bool {
                 return false;
             }
 
-            if buf == instance_handshake().as_bytes() {
-                log::info!("Got instance handshake");
-                return true;
+            if buf != instance_handshake().as_bytes() {
+                log::warn!("Got wrong instance handshake value");
+                return false;
             }
 
-            log::warn!("Got wrong instance handshake value");
-            false
+            log::info!("Got instance handshake");
+            _ = stream.set_write_timeout(Some(SEND_TIMEOUT));
+            if let Err(err) = stream.write_all(urls.join("\n").as_bytes()) {
+                log::warn!("Failed to forward URLs to the running instance: {err}");
+            }
+            true
         }
 
         Err(_) => false,
@@ -144,11 +144,17 @@ impl OpenListener {
 }
 
 #[cfg(any(target_os = "linux", target_os = "freebsd"))]
-pub fn listen_for_cli_connections(opener: OpenListener) -> Result<()> {
+fn cli_socket_path() -> PathBuf {
     use release_channel::RELEASE_CHANNEL_NAME;
+
+    paths::support_dir().join(format!("zed-{}.sock", *RELEASE_CHANNEL_NAME))
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+pub fn listen_for_cli_connections(opener: OpenListener) -> Result<()> {
     use std::os::unix::net::UnixDatagram;
 
-    let sock_path = paths::support_dir().join(format!("zed-{}.sock", *RELEASE_CHANNEL_NAME));
+    let sock_path = cli_socket_path();
     // remove the socket if the process listening on it has died
     if let Err(e) = UnixDatagram::unbound()?.connect(&sock_path) {
         if e.kind() == std::io::ErrorKind::ConnectionRefused {
@@ -165,6 +171,21 @@ pub fn listen_for_cli_connections(opener: OpenListener) -> Result<()> {
     Ok(())
 }
 
+/// Sends the given URLs to the instance that's listening with [`listen_for_cli_connections`].
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+pub fn forward_urls_to_running_instance(urls: &[String]) -> Result<()> {
+    use std::os::unix::net::UnixDatagram;
+
+    let socket = UnixDatagram::unbound()?;
+    socket
+        .connect(cli_socket_path())
+        .context("connecting to the running instance")?;
+    for url in urls {
+        socket.send(url.as_bytes())?;
+    }
+    Ok(())
+}
+
 fn connect_to_cli(
     server_name: &str,
 ) -> Result<(mpsc::Receiver<CliRequest>, IpcSender<CliResponse>)> {